@@ -1,9 +1,8 @@
 use spin_sdk::http::{IntoResponse, Request, Response};
-use spin_sdk::http_component;
 
 /// A simple Spin HTTP component.
-#[http_component]
-fn handle_auth_service(req: Request) -> anyhow::Result<impl IntoResponse> {
+#[cfg_attr(target_arch = "wasm32", spin_sdk::http_component)]
+pub fn handle_auth_service(req: Request) -> anyhow::Result<impl IntoResponse> {
     println!("Handling request to {:?}", req.header("spin-full-url"));
     Ok(Response::builder()
         .status(200)
@@ -1,9 +1,13 @@
 use anyhow::Result;
 use http::{Request, StatusCode, Method};
-use spin_sdk::http::{IntoResponse, ResponseBuilder};
-use spin_sdk::http_component;
+use serde_json::json;
+use shared::health::HealthReport;
+use shared::telemetry::{request_id_from, JsonLogger, REQUEST_ID_HEADER};
+use spin_sdk::http::{Response, ResponseBuilder};
 use serde::{Deserialize, Serialize};
 
+const SERVICE_NAME: &str = "booking-service";
+
 #[derive(Serialize, Deserialize)]
 pub struct Booking {
     pub id: String,
@@ -49,12 +53,40 @@ pub struct Pricing {
     pub dormitory: i32,
 }
 
-#[http_component]
-fn handle_request(req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+#[cfg_attr(target_arch = "wasm32", spin_sdk::http_component)]
+pub fn handle_request(req: Request<Vec<u8>>) -> Result<Response> {
+    let request_id = request_id_from(
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let logger = JsonLogger::from_env(SERVICE_NAME).with_request_id(&request_id);
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+
+    let result = route(req);
+    match &result {
+        Ok(response) => logger.info(
+            "request handled",
+            json!({"method": method, "path": path, "status": response.status()}),
+        ),
+        Err(e) => logger.error(
+            "request failed",
+            json!({"method": method, "path": path, "error": e.to_string()}),
+        ),
+    }
+    result.map(|mut response| {
+        response.set_header(REQUEST_ID_HEADER, request_id);
+        response
+    })
+}
+
+fn route(req: Request<Vec<u8>>) -> Result<Response> {
     let method = req.method();
     let path = req.uri().path();
     
     match (method, path) {
+        (&Method::GET, "/health") => health(),
         (&Method::GET, "/bookings") => get_bookings(),
         (&Method::POST, "/bookings") => create_booking(req),
         (&Method::GET, "/rooms") => get_rooms(),
@@ -67,7 +99,18 @@ fn handle_request(req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
     }
 }
 
-fn get_bookings() -> Result<impl IntoResponse> {
+/// Liveness for the gateway's `/health`; this component has no dependencies
+/// of its own to report yet.
+fn health() -> Result<Response> {
+    let report =
+        HealthReport::from_dependencies(SERVICE_NAME, env!("CARGO_PKG_VERSION"), vec![]);
+    Ok(ResponseBuilder::new(StatusCode::OK)
+        .header("content-type", "application/json")
+        .body(serde_json::to_string(&report)?)
+        .build())
+}
+
+fn get_bookings() -> Result<Response> {
     let bookings = vec![
         Booking {
             id: "1".to_string(),
@@ -90,7 +133,7 @@ fn get_bookings() -> Result<impl IntoResponse> {
         .build())
 }
 
-fn create_booking(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+fn create_booking(_req: Request<Vec<u8>>) -> Result<Response> {
     let new_booking = Booking {
         id: "new_id".to_string(),
         guest_name: "New Guest".to_string(),
@@ -111,7 +154,7 @@ fn create_booking(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
         .build())
 }
 
-fn get_dashboard_stats() -> Result<impl IntoResponse> {
+fn get_dashboard_stats() -> Result<Response> {
     let stats = DashboardStats {
         occupancy: OccupancyStats {
             available: 24,
@@ -128,7 +171,7 @@ fn get_dashboard_stats() -> Result<impl IntoResponse> {
         .build())
 }
 
-fn get_pricing() -> Result<impl IntoResponse> {
+fn get_pricing() -> Result<Response> {
     let pricing = Pricing {
         dormitory: 15,
     };
@@ -139,7 +182,7 @@ fn get_pricing() -> Result<impl IntoResponse> {
         .build())
 }
 
-fn get_rooms() -> Result<impl IntoResponse> {
+fn get_rooms() -> Result<Response> {
     let rooms = vec![
         Room {
             id: "dorm-a".to_string(),
@@ -1,8 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use booking_service::*;
     use http::{Method, Request};
-    use std::collections::HashMap;
 
     #[test]
     fn test_get_rooms_returns_valid_data() {
@@ -15,12 +14,12 @@ mod tests {
         let result = handle_request(req).unwrap();
         
         // Check that we get a 200 response
-        assert_eq!(result.status(), 200);
+        assert_eq!(*result.status(), 200);
         
         // Check content type
         assert_eq!(
-            result.headers().get("content-type").unwrap(),
-            "application/json"
+            result.header("content-type").and_then(|v| v.as_str()),
+            Some("application/json")
         );
     }
 
@@ -34,10 +33,10 @@ mod tests {
 
         let result = handle_request(req).unwrap();
         
-        assert_eq!(result.status(), 200);
+        assert_eq!(*result.status(), 200);
         assert_eq!(
-            result.headers().get("content-type").unwrap(),
-            "application/json"
+            result.header("content-type").and_then(|v| v.as_str()),
+            Some("application/json")
         );
     }
 
@@ -51,7 +50,7 @@ mod tests {
 
         let result = handle_request(req).unwrap();
         
-        assert_eq!(result.status(), 201);
+        assert_eq!(*result.status(), 201);
     }
 
     #[test]
@@ -64,6 +63,24 @@ mod tests {
 
         let result = handle_request(req).unwrap();
         
-        assert_eq!(result.status(), 404);
+        assert_eq!(*result.status(), 404);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_keeps_the_forwarded_request_id() {
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("/health")
+            .header("X-Request-Id", "req-42")
+            .body(vec![])
+            .unwrap();
+
+        let result = handle_request(req).unwrap();
+
+        let request_id = result
+            .headers()
+            .find(|(name, _)| name.eq_ignore_ascii_case("x-request-id"))
+            .and_then(|(_, value)| value.as_str());
+        assert_eq!(request_id, Some("req-42"));
+    }
+}
@@ -6,6 +6,12 @@ use shared::{AlbergueError, AlbergueResult};
 
 pub struct CarrascalejoScraperAdapter;
 
+impl Default for CarrascalejoScraperAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CarrascalejoScraperAdapter {
     pub fn new() -> Self {
         Self
@@ -16,9 +22,9 @@ impl CarrascalejoScraperAdapter {
 impl ScraperPort for CarrascalejoScraperAdapter {
     async fn scrape_merida_attractions(&self) -> AlbergueResult<ScrapedContent> {
         // Delegate to MeridaScraperAdapter
-        Err(AlbergueError::NotImplemented(
-            "Use MeridaScraperAdapter for Mérida content".to_string(),
-        ))
+        Err(AlbergueError::Internal {
+            message: "Use MeridaScraperAdapter for Mérida content".to_string(),
+        })
     }
 
     async fn scrape_carrascalejo_info(&self) -> AlbergueResult<ScrapedContent> {
@@ -143,9 +149,9 @@ impl ScraperPort for CarrascalejoScraperAdapter {
 
     async fn scrape_restaurants(&self) -> AlbergueResult<Vec<ScrapedContent>> {
         // Carrascalejo is too small for restaurants - refer to nearby Mérida
-        Err(AlbergueError::NotImplemented(
-            "Use MeridaScraperAdapter for restaurant data".to_string(),
-        ))
+        Err(AlbergueError::Internal {
+            message: "Use MeridaScraperAdapter for restaurant data".to_string(),
+        })
     }
 
     async fn scrape_taxi_services(&self) -> AlbergueResult<Vec<ScrapedContent>> {
@@ -166,8 +172,8 @@ impl ScraperPort for CarrascalejoScraperAdapter {
 
     async fn scrape_car_rentals(&self) -> AlbergueResult<Vec<ScrapedContent>> {
         // No car rentals in Carrascalejo - refer to Mérida
-        Err(AlbergueError::NotImplemented(
-            "Use MeridaScraperAdapter for car rental data".to_string(),
-        ))
+        Err(AlbergueError::Internal {
+            message: "Use MeridaScraperAdapter for car rental data".to_string(),
+        })
     }
 }
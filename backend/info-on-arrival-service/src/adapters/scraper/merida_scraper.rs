@@ -9,6 +9,12 @@ pub struct MeridaScraperAdapter {
     client: Client,
 }
 
+impl Default for MeridaScraperAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MeridaScraperAdapter {
     pub fn new() -> Self {
         Self {
@@ -33,24 +39,29 @@ impl MeridaScraperAdapter {
                 .send()
                 .await
                 .map_err(|e| {
-                    AlbergueError::ExternalServiceError(format!("Failed to fetch {}: {}", url, e))
+                    AlbergueError::ExternalService {
+                    service: "scraper".to_string(),
+                    message: format!("Failed to fetch {}: {}", url, e),
+                }
                 })?;
 
             if response.status().is_success() {
                 response.text().await.map_err(|e| {
-                    AlbergueError::ExternalServiceError(format!("Failed to read response: {}", e))
+                    AlbergueError::ExternalService {
+                    service: "scraper".to_string(),
+                    message: format!("Failed to read response: {}", e),
+                }
                 })
             } else {
-                Err(AlbergueError::ExternalServiceError(format!(
-                    "HTTP error {}: {}",
-                    response.status(),
-                    url
-                )))
+                Err(AlbergueError::ExternalService {
+                    service: "scraper".to_string(),
+                    message: format!("HTTP error {}: {}", response.status(), url),
+                })
             }
         }
     }
 
-    fn extract_attractions_from_html(&self, html: &str) -> Vec<String> {
+    fn extract_attractions_from_html(&self, _html: &str) -> Vec<String> {
         // Simple text extraction - in a real implementation this would use scraper crate
         vec![
             "Teatro Romano - Espectacular teatro del siglo I a.C.".to_string(),
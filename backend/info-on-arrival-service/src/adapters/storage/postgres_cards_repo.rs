@@ -2,7 +2,7 @@ use crate::domain::*;
 use crate::ports::StoragePort;
 use async_trait::async_trait;
 use serde_json;
-use shared::{AlbergueError, AlbergueResult, DatabaseConfig};
+use shared::{AlbergueError, AlbergueResult};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
@@ -10,6 +10,12 @@ pub struct PostgresCardsRepository {
     pool: Option<PgPool>,
 }
 
+impl Default for PostgresCardsRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PostgresCardsRepository {
     pub fn new() -> Self {
         // In WASM context, database operations would be handled differently
@@ -19,8 +25,8 @@ impl PostgresCardsRepository {
 
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn with_database(database_url: &str) -> AlbergueResult<Self> {
-        let pool = PgPool::connect(database_url).await.map_err(|e| {
-            AlbergueError::DatabaseError(format!("Failed to connect to database: {}", e))
+        let pool = PgPool::connect(database_url).await.map_err(|e| AlbergueError::Database {
+            message: format!("Failed to connect to database: {}", e),
         })?;
 
         Ok(Self { pool: Some(pool) })
@@ -44,10 +50,10 @@ impl StoragePort for PostgresCardsRepository {
                 let card_type_str = serde_json::to_string(&card.card_type)?;
                 let links_json = serde_json::to_string(&card.links)?;
 
-                sqlx::query!(
+                sqlx::query(
                     r#"
                     INSERT INTO info_cards (
-                        id, card_type, title, content, markdown_content, 
+                        id, card_type, title, content, markdown_content,
                         links, priority, is_active, language, last_updated,
                         source_url, cache_duration_hours
                     ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
@@ -57,28 +63,28 @@ impl StoragePort for PostgresCardsRepository {
                         links = EXCLUDED.links,
                         last_updated = EXCLUDED.last_updated
                     "#,
-                    card.id,
-                    card_type_str,
-                    card.title,
-                    card.content,
-                    card.markdown_content,
-                    links_json,
-                    card.priority,
-                    card.is_active,
-                    card.language,
-                    card.last_updated,
-                    card.source_url,
-                    card.cache_duration_hours
                 )
+                .bind(card.id)
+                .bind(card_type_str)
+                .bind(&card.title)
+                .bind(&card.content)
+                .bind(&card.markdown_content)
+                .bind(links_json)
+                .bind(card.priority)
+                .bind(card.is_active)
+                .bind(&card.language)
+                .bind(card.last_updated)
+                .bind(&card.source_url)
+                .bind(card.cache_duration_hours)
                 .execute(pool)
                 .await
-                .map_err(|e| AlbergueError::DatabaseError(format!("Failed to save card: {}", e)))?;
+                .map_err(|e| AlbergueError::Database {
+                    message: format!("Failed to save card: {}", e),
+                })?;
 
                 Ok(card)
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
@@ -87,25 +93,25 @@ impl StoragePort for PostgresCardsRepository {
         #[cfg(target_arch = "wasm32")]
         {
             // Simulate returning a card
-            Err(AlbergueError::NotFound(format!(
-                "Card {} not found in WASM storage",
-                id
-            )))
+            Err(AlbergueError::NotFound {
+                resource: format!("Card {} not found in WASM storage", id),
+            })
         }
 
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(pool) = &self.pool {
-                let row = sqlx::query!("SELECT * FROM info_cards WHERE id = $1", id)
+                let row = sqlx::query("SELECT * FROM info_cards WHERE id = $1")
+                    .bind(id)
                     .fetch_one(pool)
                     .await
-                    .map_err(|_| AlbergueError::NotFound(format!("Card {} not found", id)))?;
+                    .map_err(|_| AlbergueError::NotFound {
+                        resource: format!("Card {} not found", id),
+                    })?;
 
                 self.row_to_card(row)
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
@@ -120,7 +126,9 @@ impl StoragePort for PostgresCardsRepository {
                     "Qué ver en Mérida".to_string(),
                     "Contenido por defecto para WASM".to_string(),
                 )),
-                _ => Err(AlbergueError::NotFound("Card type not found".to_string())),
+                _ => Err(AlbergueError::NotFound {
+                    resource: "Card type not found".to_string(),
+                }),
             }
         }
 
@@ -129,19 +137,19 @@ impl StoragePort for PostgresCardsRepository {
             if let Some(pool) = &self.pool {
                 let card_type_str = serde_json::to_string(&card_type)?;
 
-                let row = sqlx::query!(
+                let row = sqlx::query(
                     "SELECT * FROM info_cards WHERE card_type = $1 ORDER BY last_updated DESC LIMIT 1",
-                    card_type_str
                 )
+                .bind(card_type_str)
                 .fetch_one(pool)
                 .await
-                .map_err(|_| AlbergueError::NotFound("Card type not found".to_string()))?;
+                .map_err(|_| AlbergueError::NotFound {
+                    resource: "Card type not found".to_string(),
+                })?;
 
                 self.row_to_card(row)
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
@@ -156,12 +164,14 @@ impl StoragePort for PostgresCardsRepository {
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(pool) = &self.pool {
-                let rows = sqlx::query!(
-                    "SELECT * FROM info_cards WHERE is_active = true ORDER BY priority DESC, last_updated DESC"
+                let rows = sqlx::query(
+                    "SELECT * FROM info_cards WHERE is_active = true ORDER BY priority DESC, last_updated DESC",
                 )
                 .fetch_all(pool)
                 .await
-                .map_err(|e| AlbergueError::DatabaseError(format!("Failed to fetch cards: {}", e)))?;
+                .map_err(|e| AlbergueError::Database {
+                    message: format!("Failed to fetch cards: {}", e),
+                })?;
 
                 let mut cards = Vec::new();
                 for row in rows {
@@ -171,9 +181,7 @@ impl StoragePort for PostgresCardsRepository {
                 }
                 Ok(cards)
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
@@ -188,17 +196,16 @@ impl StoragePort for PostgresCardsRepository {
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(pool) = &self.pool {
-                sqlx::query!("DELETE FROM info_cards WHERE id = $1", id)
+                sqlx::query("DELETE FROM info_cards WHERE id = $1")
+                    .bind(id)
                     .execute(pool)
                     .await
-                    .map_err(|e| {
-                        AlbergueError::DatabaseError(format!("Failed to delete card: {}", e))
+                    .map_err(|e| AlbergueError::Database {
+                        message: format!("Failed to delete card: {}", e),
                     })?;
                 Ok(())
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
@@ -212,13 +219,15 @@ impl StoragePort for PostgresCardsRepository {
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(pool) = &self.pool {
-                let rows = sqlx::query!(
+                let rows = sqlx::query(
                     "SELECT * FROM info_cards WHERE language = $1 AND is_active = true ORDER BY priority DESC",
-                    language
                 )
+                .bind(language)
                 .fetch_all(pool)
                 .await
-                .map_err(|e| AlbergueError::DatabaseError(format!("Failed to fetch cards: {}", e)))?;
+                .map_err(|e| AlbergueError::Database {
+                    message: format!("Failed to fetch cards: {}", e),
+                })?;
 
                 let mut cards = Vec::new();
                 for row in rows {
@@ -228,40 +237,48 @@ impl StoragePort for PostgresCardsRepository {
                 }
                 Ok(cards)
             } else {
-                Err(AlbergueError::DatabaseError(
-                    "No database connection".to_string(),
-                ))
+                Err(no_connection())
             }
         }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn no_connection() -> AlbergueError {
+    AlbergueError::Database {
+        message: "No database connection".to_string(),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn column<'r, T>(row: &'r sqlx::postgres::PgRow, name: &str) -> AlbergueResult<T>
+where
+    T: sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
+{
+    row.try_get(name).map_err(|e| AlbergueError::Database {
+        message: format!("Invalid {}: {}", name, e),
+    })
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl PostgresCardsRepository {
     fn row_to_card(&self, row: sqlx::postgres::PgRow) -> AlbergueResult<InfoCard> {
-        let card_type: CardType = serde_json::from_str(
-            &row.try_get::<String, _>("card_type")
-                .map_err(|e| AlbergueError::DatabaseError(format!("Invalid card_type: {}", e)))?,
-        )?;
-
-        let links: Vec<InfoLink> = serde_json::from_str(
-            &row.try_get::<String, _>("links")
-                .map_err(|e| AlbergueError::DatabaseError(format!("Invalid links: {}", e)))?,
-        )?;
+        let card_type: CardType = serde_json::from_str(&column::<String>(&row, "card_type")?)?;
+        let links: Vec<InfoLink> = serde_json::from_str(&column::<String>(&row, "links")?)?;
 
         Ok(InfoCard {
-            id: row.try_get("id")?,
+            id: column(&row, "id")?,
             card_type,
-            title: row.try_get("title")?,
-            content: row.try_get("content")?,
-            markdown_content: row.try_get("markdown_content")?,
+            title: column(&row, "title")?,
+            content: column(&row, "content")?,
+            markdown_content: column(&row, "markdown_content")?,
             links,
-            priority: row.try_get("priority")?,
-            is_active: row.try_get("is_active")?,
-            language: row.try_get("language")?,
-            last_updated: row.try_get("last_updated")?,
-            source_url: row.try_get("source_url")?,
-            cache_duration_hours: row.try_get("cache_duration_hours")?,
+            priority: column(&row, "priority")?,
+            is_active: column(&row, "is_active")?,
+            language: column(&row, "language")?,
+            last_updated: column(&row, "last_updated")?,
+            source_url: column(&row, "source_url")?,
+            cache_duration_hours: column(&row, "cache_duration_hours")?,
         })
     }
 }
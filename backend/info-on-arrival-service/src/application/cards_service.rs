@@ -8,6 +8,12 @@ pub struct CardsServiceImpl {
     scraper: Box<dyn ScraperPort>,
 }
 
+impl Default for CardsServiceImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CardsServiceImpl {
     pub fn new() -> Self {
         Self {
@@ -45,24 +51,40 @@ impl CardsServiceImpl {
                 url: "https://www.consorciomerida.org/teatro-romano".to_string(),
                 description: Some("Espectacular teatro del siglo I a.C.".to_string()),
                 link_type: LinkType::Website,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "Anfiteatro Romano".to_string(),
                 url: "https://www.consorciomerida.org/anfiteatro".to_string(),
                 description: Some("Donde luchaban los gladiadores".to_string()),
                 link_type: LinkType::Website,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "Museo Nacional de Arte Romano".to_string(),
                 url: "https://www.culturaydeporte.gob.es/mnar".to_string(),
                 description: Some("Impresionante colección de arte romano".to_string()),
                 link_type: LinkType::Website,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "Puente Romano".to_string(),
                 url: "https://goo.gl/maps/example".to_string(),
                 description: Some("Uno de los puentes romanos mejor conservados".to_string()),
                 link_type: LinkType::Map,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
         ])
         .with_priority(1)
@@ -120,12 +142,20 @@ Este pequeño pueblo de apenas 300 habitantes guarda secretos fascinantes:
                 url: "tel:+34924123456".to_string(),
                 description: Some("Información municipal".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "Centro de Salud".to_string(),
                 url: "tel:+34924654321".to_string(),
                 description: Some("Atención médica básica".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
         ])
         .with_priority(2);
@@ -146,36 +176,60 @@ Este pequeño pueblo de apenas 300 habitantes guarda secretos fascinantes:
                 url: "tel:112".to_string(),
                 description: Some("Número europeo de emergencias (24h)".to_string()),
                 link_type: LinkType::Emergency,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "👮 Guardia Civil".to_string(),
                 url: "tel:062".to_string(),
                 description: Some("Fuerzas de seguridad (24h)".to_string()),
                 link_type: LinkType::Emergency,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "🏥 Centro de Salud Mérida".to_string(),
                 url: "tel:+34924330000".to_string(),
                 description: Some("Hospital más cercano (20 km)".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "💊 Farmacia Almendralejo".to_string(),
                 url: "tel:+34924660123".to_string(),
                 description: Some("Farmacia 24h más cercana".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "🚕 Taxi Local".to_string(),
                 url: "tel:+34924987654".to_string(),
                 description: Some("Servicio de taxi local".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "ℹ️ Oficina de Turismo Mérida".to_string(),
                 url: "tel:+34924315353".to_string(),
                 description: Some("Información turística (9-14h, 16-19h)".to_string()),
                 link_type: LinkType::Phone,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
         ])
         .with_priority(10); // High priority for emergency info
@@ -239,12 +293,20 @@ Este pequeño pueblo de apenas 300 habitantes guarda secretos fascinantes:
                 url: route_data.map_embed_url.clone(),
                 description: Some("Mapa detallado de la ruta".to_string()),
                 link_type: LinkType::Map,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
             InfoLink {
                 title: "🌤️ Previsión meteorológica".to_string(),
                 url: format!("https://www.aemet.es/es/eltiempo/prediccion/municipios/{}", next_stage.to_lowercase()),
                 description: Some("Consulta el tiempo antes de salir".to_string()),
                 link_type: LinkType::Website,
+                phone: None,
+                address: None,
+                rating: None,
+                price_range: None,
             },
         ])
         .with_priority(3);
@@ -435,26 +497,24 @@ Este pequeño pueblo de apenas 300 habitantes guarda secretos fascinantes:
         let mut all_cards = Vec::new();
 
         // Get all card types - including new ones
-        let card_methods = vec![
-            self.get_merida_attractions(),
-            self.get_carrascalejo_info(),
-            self.get_emergency_contacts(),
-            self.get_route_map("almendralejo"),
-            self.get_restaurants_eat(),
-            self.get_taxi_services(),
-            self.get_car_rentals(),
+        let card_results = vec![
+            self.get_merida_attractions().await,
+            self.get_carrascalejo_info().await,
+            self.get_emergency_contacts().await,
+            self.get_route_map("almendralejo").await,
+            self.get_restaurants_eat().await,
+            self.get_taxi_services().await,
+            self.get_car_rentals().await,
         ];
 
-        for card_future in card_methods {
-            if let Ok(card_json) = card_future.await {
-                if let Ok(parsed) = serde_json::from_str::<InfoCard>(&card_json) {
-                    all_cards.push(parsed);
-                }
+        for card_json in card_results.into_iter().flatten() {
+            if let Ok(parsed) = serde_json::from_str::<InfoCard>(&card_json) {
+                all_cards.push(parsed);
             }
         }
 
         // Sort by priority (highest first)
-        all_cards.sort_by(|a, b| b.priority.cmp(&a.priority));
+        all_cards.sort_by_key(|card| std::cmp::Reverse(card.priority));
 
         Ok(serde_json::to_string(&all_cards)?)
     }
@@ -465,7 +525,9 @@ Este pequeño pueblo de apenas 300 habitantes guarda secretos fascinantes:
         content: &str,
     ) -> AlbergueResult<String> {
         let card_uuid = uuid::Uuid::parse_str(card_id)
-            .map_err(|_| AlbergueError::ValidationError("Invalid card ID format".to_string()))?;
+            .map_err(|_| AlbergueError::Validation {
+                message: "Invalid card ID format".to_string(),
+            })?;
 
         let mut card = self.storage.get_card_by_id(card_uuid).await?;
         card.update_content(content.to_string());
@@ -18,7 +18,7 @@ pub struct InfoCard {
     pub cache_duration_hours: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CardType {
     MeridaAttractions,
     CarrascalejoInfo,
@@ -45,7 +45,7 @@ pub struct InfoLink {
     pub price_range: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LinkType {
     Website,
     Phone,
@@ -1,11 +1,5 @@
 use shared::AlbergueResult;
 
-pub fn init_logging() {
-    tracing_subscriber::fmt()
-        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))
-        .init();
-}
-
 pub struct InfoServiceConfig {
     pub database_url: String,
     pub cache_duration_hours: i32,
@@ -1,4 +1,18 @@
+use crate::application::CardsServiceImpl;
 use shared::AlbergueResult;
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use shared::telemetry::with_request_span;
 
 pub struct InfoServer {
     port: u16,
@@ -15,13 +29,46 @@ impl InfoServer {
         #[cfg(not(target_arch = "wasm32"))]
         {
             tracing::info!("Info service running in standalone mode");
-            tokio::signal::ctrl_c().await.unwrap();
+            let listener = tokio::net::TcpListener::bind(("0.0.0.0", self.port))
+                .await
+                .map_err(|e| shared::AlbergueError::Internal {
+                    message: format!("Cannot listen on port {}: {}", self.port, e),
+                })?;
+            let server = axum::serve(listener, router(Arc::new(CardsServiceImpl::new())));
+            tokio::select! {
+                result = server => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Info HTTP server stopped");
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => {}
+            }
         }
 
         Ok(())
     }
 }
 
+/// HTTP routes of the standalone service.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn router(service: Arc<CardsServiceImpl>) -> Router {
+    Router::new()
+        .route("/cards", get(all_cards))
+        .layer(middleware::from_fn(with_request_span))
+        .with_state(service)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn all_cards(State(service): State<Arc<CardsServiceImpl>>) -> Response {
+    match service.get_all_info_cards().await {
+        Ok(cards) => ([(header::CONTENT_TYPE, "application/json")], cards).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Info cards unavailable");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 pub async fn create_server() -> AlbergueResult<InfoServer> {
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "8003".to_string())
@@ -4,5 +4,5 @@ pub mod ports;
 pub mod adapters;
 pub mod infrastructure;
 
-pub use application::info_service::InfoOnArrivalService;
-pub use domain::info_card::{InfoCard, InfoCategory};
\ No newline at end of file
+pub use application::CardsServiceImpl;
+pub use domain::{CardType, InfoCard};
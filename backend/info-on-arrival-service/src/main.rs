@@ -1,14 +1,7 @@
 use wasm_bindgen::prelude::*;
-use shared::{AlbergueError, AlbergueResult};
+use shared::AlbergueResult;
 
-mod domain;
-mod application;
-mod ports;
-mod adapters;
-mod infrastructure;
-
-pub use domain::*;
-pub use application::*;
+use info_on_arrival_service::{application, infrastructure};
 
 #[wasm_bindgen]
 extern "C" {
@@ -21,6 +14,12 @@ pub struct InfoOnArrivalService {
     service: application::CardsServiceImpl,
 }
 
+impl Default for InfoOnArrivalService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl InfoOnArrivalService {
     #[wasm_bindgen(constructor)]
@@ -105,7 +104,7 @@ impl InfoOnArrivalService {
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
 async fn main() -> AlbergueResult<()> {
-    infrastructure::config::init_logging();
+    shared::telemetry::init_logging();
     let server = infrastructure::server::create_server().await?;
     server.run().await
 }
\ No newline at end of file
@@ -4,13 +4,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_info_service_creation() {
-        let service = InfoOnArrivalService::new();
-        assert!(true); // Basic smoke test
+        // Basic smoke test
+        let _service = CardsServiceImpl::new();
     }
 
     #[tokio::test]
     async fn test_get_emergency_contacts() {
-        let service = InfoOnArrivalService::new();
+        let service = CardsServiceImpl::new();
         let result = service.get_emergency_contacts().await;
         assert!(result.is_ok());
         
@@ -22,7 +22,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_route_map() {
-        let service = InfoOnArrivalService::new();
+        let service = CardsServiceImpl::new();
         let result = service.get_route_map("almendralejo").await;
         assert!(result.is_ok());
         
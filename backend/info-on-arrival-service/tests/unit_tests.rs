@@ -25,6 +25,10 @@ mod tests {
             url: "https://test.com".to_string(),
             description: Some("Test description".to_string()),
             link_type: LinkType::Website,
+            phone: None,
+            address: None,
+            rating: None,
+            price_range: None,
         };
 
         let card = InfoCard::new(
@@ -3,7 +3,7 @@ use crate::ports::EmailPort;
 use async_trait::async_trait;
 use lettre::{
     message::{header::ContentType, Message},
-    transport::smtp::{authentication::Credentials, response::Response},
+    transport::smtp::authentication::Credentials,
     AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
 };
 use shared::{AlbergueError, AlbergueResult};
@@ -13,6 +13,12 @@ pub struct NodemailerAdapter {
     from_email: String,
 }
 
+impl Default for NodemailerAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NodemailerAdapter {
     pub fn new() -> Self {
         let smtp_host =
@@ -46,34 +52,37 @@ impl EmailPort for NodemailerAdapter {
     async fn send_email(&self, notification: &Notification) -> AlbergueResult<String> {
         let subject = notification
             .subject
-            .as_ref()
-            .unwrap_or(&"Notificación - Albergue del Carrascalejo".to_string());
+            .as_deref()
+            .unwrap_or("Notificación - Albergue del Carrascalejo");
 
         let email = Message::builder()
             .from(
                 format!("Albergue del Carrascalejo <{}>", self.from_email)
                     .parse()
                     .map_err(|e| {
-                        AlbergueError::ValidationError(format!("Invalid from email: {}", e))
+                        AlbergueError::Validation { message: format!("Invalid from email: {}", e) }
                     })?,
             )
             .to(notification.recipient.parse().map_err(|e| {
-                AlbergueError::ValidationError(format!("Invalid recipient email: {}", e))
+                AlbergueError::Validation { message: format!("Invalid recipient email: {}", e) }
             })?)
             .subject(subject)
             .header(ContentType::TEXT_PLAIN)
             .body(notification.message.clone())
-            .map_err(|e| AlbergueError::ValidationError(format!("Failed to build email: {}", e)))?;
+            .map_err(|e| AlbergueError::Validation { message: format!("Failed to build email: {}", e) })?;
 
         match self.smtp_transport.send(email).await {
             Ok(response) => Ok(format!(
                 "Email sent: {}",
-                response.message().iter().next().unwrap_or(&"No message")
+                response.message().next().unwrap_or("No message")
             )),
-            Err(e) => Err(AlbergueError::ExternalServiceError(format!(
+            Err(e) => Err(AlbergueError::ExternalService {
+                service: "smtp".to_string(),
+                message: format!(
                 "SMTP error: {}",
                 e
-            ))),
+            ),
+            }),
         }
     }
 
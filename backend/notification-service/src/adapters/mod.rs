@@ -1,3 +1,4 @@
 pub mod email;
+pub mod outbox;
 pub mod sms;
 pub mod telegram;
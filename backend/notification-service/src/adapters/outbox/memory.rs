@@ -0,0 +1,108 @@
+use super::DEFAULT_CLAIM_LEASE_SECONDS;
+use crate::domain::{Notification, NotificationStatus};
+use crate::ports::OutboxPort;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use shared::AlbergueResult;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Outbox kept in memory, for a single instance and for tests. Clones share
+/// the same notifications, and claims work as in `PostgresOutbox`.
+#[derive(Clone)]
+pub struct MemoryOutbox {
+    notifications: Arc<Mutex<HashMap<Uuid, Notification>>>,
+    lease: Duration,
+}
+
+impl Default for MemoryOutbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryOutbox {
+    pub fn new() -> Self {
+        Self {
+            notifications: Arc::new(Mutex::new(HashMap::new())),
+            lease: Duration::seconds(DEFAULT_CLAIM_LEASE_SECONDS),
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    fn select(&self, keep: impl Fn(&Notification) -> bool) -> Vec<Notification> {
+        let notifications = self.notifications.lock().unwrap();
+        let mut selected: Vec<Notification> = notifications
+            .values()
+            .filter(|n| keep(n))
+            .map(unclaimed)
+            .collect();
+        selected.sort_by_key(|n| (n.next_attempt_at, n.created_at));
+        selected
+    }
+}
+
+#[async_trait]
+impl OutboxPort for MemoryOutbox {
+    async fn save(&self, notification: &Notification) -> AlbergueResult<bool> {
+        let mut notifications = self.notifications.lock().unwrap();
+        if let Some(claim) = notifications.get(&notification.id).and_then(|n| n.claimed_until) {
+            if Some(claim) != notification.claimed_until && claim > Utc::now() {
+                return Ok(false);
+            }
+        }
+        notifications.insert(notification.id, unclaimed(notification));
+        Ok(true)
+    }
+
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<Notification>> {
+        Ok(self.notifications.lock().unwrap().get(&id).map(unclaimed))
+    }
+
+    async fn find_by_provider_message_id(
+        &self,
+        provider_message_id: &str,
+    ) -> AlbergueResult<Option<Notification>> {
+        Ok(self
+            .select(|n| n.provider_message_id.as_deref() == Some(provider_message_id))
+            .into_iter()
+            .next())
+    }
+
+    async fn due(&self, now: DateTime<Utc>, limit: usize) -> AlbergueResult<Vec<Notification>> {
+        let mut notifications = self.notifications.lock().unwrap();
+        let mut due: Vec<&mut Notification> = notifications
+            .values_mut()
+            .filter(|n| n.is_due(now) && n.claimed_until.is_none_or(|c| c <= now))
+            .collect();
+        due.sort_by_key(|n| (n.next_attempt_at, n.created_at));
+        due.truncate(limit);
+
+        Ok(due
+            .into_iter()
+            .map(|notification| {
+                notification.claimed_until = Some(now + self.lease);
+                notification.clone()
+            })
+            .collect())
+    }
+
+    async fn dead_letters(&self) -> AlbergueResult<Vec<Notification>> {
+        let mut dead = self.select(|n| n.status == NotificationStatus::DeadLetter);
+        dead.sort_by_key(|n| n.created_at);
+        Ok(dead)
+    }
+}
+
+/// A copy of `notification` holding no claim.
+fn unclaimed(notification: &Notification) -> Notification {
+    Notification {
+        claimed_until: None,
+        ..notification.clone()
+    }
+}
@@ -0,0 +1,12 @@
+pub mod memory;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod postgres;
+
+pub use memory::*;
+
+/// How long `due` holds the notifications it returns. The worker saves each
+/// one well before then; a claim left by a worker that died is taken over
+/// once it runs out.
+pub const DEFAULT_CLAIM_LEASE_SECONDS: i64 = 300;
+#[cfg(not(target_arch = "wasm32"))]
+pub use postgres::PostgresOutbox;
@@ -0,0 +1,266 @@
+use super::DEFAULT_CLAIM_LEASE_SECONDS;
+use crate::domain::{Notification, NotificationChannel, NotificationStatus, NotificationType};
+use crate::ports::OutboxPort;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use shared::{AlbergueError, AlbergueResult};
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const COLUMNS: &str = "id, notification_type, channel, recipient, subject, message, template_data, \
+     fallback_channel, status, provider_message_id, attempts, next_attempt_at, created_at, sent_at, \
+     delivered_at, error_message";
+
+/// The outbox as the `notifications` table, shared by every instance of the
+/// service. `due` claims what it returns, so two workers never send the
+/// same notification.
+pub struct PostgresOutbox {
+    pool: PgPool,
+    lease: Duration,
+}
+
+impl PostgresOutbox {
+    pub async fn connect(database_url: &str) -> AlbergueResult<Self> {
+        let pool = PgPool::connect(database_url).await.map_err(db_error)?;
+        Ok(Self::new(pool))
+    }
+
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            lease: Duration::seconds(DEFAULT_CLAIM_LEASE_SECONDS),
+        }
+    }
+
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    async fn select(&self, condition: &str, value: &str) -> AlbergueResult<Vec<Notification>> {
+        let sql = format!(
+            "SELECT {} FROM notifications WHERE {} ORDER BY created_at",
+            COLUMNS, condition
+        );
+        let rows = sqlx::query(&sql)
+            .bind(value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+        rows.iter().map(from_row).collect()
+    }
+}
+
+#[async_trait]
+impl OutboxPort for PostgresOutbox {
+    /// Saving a notification's new state also releases its claim. A save
+    /// against another worker's live claim is stale and skipped, so a
+    /// delivery report cannot release a claim mid-send.
+    async fn save(&self, notification: &Notification) -> AlbergueResult<bool> {
+        let result = sqlx::query(
+            "INSERT INTO notifications (id, notification_type, channel, recipient, subject, message, \
+             template_data, fallback_channel, status, provider_message_id, attempts, next_attempt_at, \
+             created_at, sent_at, delivered_at, error_message) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16) \
+             ON CONFLICT (id) DO UPDATE SET channel = EXCLUDED.channel, status = EXCLUDED.status, \
+             provider_message_id = EXCLUDED.provider_message_id, attempts = EXCLUDED.attempts, \
+             next_attempt_at = EXCLUDED.next_attempt_at, sent_at = EXCLUDED.sent_at, \
+             delivered_at = EXCLUDED.delivered_at, error_message = EXCLUDED.error_message, \
+             claimed_until = NULL \
+             WHERE notifications.claimed_until IS NULL OR notifications.claimed_until = $17 \
+             OR notifications.claimed_until <= $18",
+        )
+        .bind(notification.id)
+        .bind(type_name(&notification.notification_type))
+        .bind(channel_name(notification.channel))
+        .bind(&notification.recipient)
+        .bind(&notification.subject)
+        .bind(&notification.message)
+        .bind(Json(&notification.template_data))
+        .bind(notification.fallback_channel.map(channel_name))
+        .bind(status_name(notification.status))
+        .bind(&notification.provider_message_id)
+        .bind(notification.attempts as i32)
+        .bind(notification.next_attempt_at)
+        .bind(notification.created_at)
+        .bind(notification.sent_at)
+        .bind(notification.delivered_at)
+        .bind(&notification.error_message)
+        .bind(notification.claimed_until)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<Notification>> {
+        let sql = format!("SELECT {} FROM notifications WHERE id = $1", COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+        row.as_ref().map(from_row).transpose()
+    }
+
+    async fn find_by_provider_message_id(
+        &self,
+        provider_message_id: &str,
+    ) -> AlbergueResult<Option<Notification>> {
+        Ok(self
+            .select("provider_message_id = $1", provider_message_id)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Claims up to `limit` due notifications nobody holds. Rows another
+    /// worker is claiming at the same moment are skipped, not waited on.
+    async fn due(&self, now: DateTime<Utc>, limit: usize) -> AlbergueResult<Vec<Notification>> {
+        let sql = format!(
+            "UPDATE notifications SET claimed_until = $2 WHERE id IN ( \
+                 SELECT id FROM notifications \
+                 WHERE status IN ('pending', 'failed') AND next_attempt_at <= $1 \
+                 AND (claimed_until IS NULL OR claimed_until <= $1) \
+                 ORDER BY next_attempt_at, created_at LIMIT $3 \
+                 FOR UPDATE SKIP LOCKED) \
+             RETURNING {}, claimed_until",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(now)
+            .bind(now + self.lease)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+        let mut due = vec![];
+        for row in &rows {
+            let mut notification = from_row(row)?;
+            notification.claimed_until = row.try_get("claimed_until").map_err(db_error)?;
+            due.push(notification);
+        }
+        // RETURNING keeps no order
+        due.sort_by_key(|n| (n.next_attempt_at, n.created_at));
+        Ok(due)
+    }
+
+    async fn dead_letters(&self) -> AlbergueResult<Vec<Notification>> {
+        self.select("status = $1", status_name(NotificationStatus::DeadLetter))
+            .await
+    }
+}
+
+fn db_error(e: sqlx::Error) -> AlbergueError {
+    AlbergueError::Database {
+        message: format!("notifications: {}", e),
+    }
+}
+
+fn bad_value(column: &str, value: &str) -> AlbergueError {
+    AlbergueError::Database {
+        message: format!("notifications: unknown {} {}", column, value),
+    }
+}
+
+/// `notifications.notification_type`, e.g. `reservation_created`.
+pub fn type_name(notification_type: &NotificationType) -> &'static str {
+    match notification_type {
+        NotificationType::ReservationCreated => "reservation_created",
+        NotificationType::PaymentConfirmed => "payment_confirmed",
+        NotificationType::ReservationExpired => "reservation_expired",
+        NotificationType::ReservationCancelled => "reservation_cancelled",
+        NotificationType::CheckInReminder => "check_in_reminder",
+        NotificationType::AdminAlert => "admin_alert",
+        NotificationType::MirSubmissionUpdate => "mir_submission_update",
+    }
+}
+
+pub fn parse_type(name: &str) -> AlbergueResult<NotificationType> {
+    Ok(match name {
+        "reservation_created" => NotificationType::ReservationCreated,
+        "payment_confirmed" => NotificationType::PaymentConfirmed,
+        "reservation_expired" => NotificationType::ReservationExpired,
+        "reservation_cancelled" => NotificationType::ReservationCancelled,
+        "check_in_reminder" => NotificationType::CheckInReminder,
+        "admin_alert" => NotificationType::AdminAlert,
+        "mir_submission_update" => NotificationType::MirSubmissionUpdate,
+        other => return Err(bad_value("notification_type", other)),
+    })
+}
+
+/// `notifications.channel`, as its check constraint allows.
+pub fn channel_name(channel: NotificationChannel) -> &'static str {
+    match channel {
+        NotificationChannel::Email => "email",
+        NotificationChannel::SMS => "sms",
+        NotificationChannel::WhatsApp => "whatsapp",
+        NotificationChannel::Telegram => "telegram",
+    }
+}
+
+pub fn parse_channel(name: &str) -> AlbergueResult<NotificationChannel> {
+    Ok(match name {
+        "email" => NotificationChannel::Email,
+        "sms" => NotificationChannel::SMS,
+        "whatsapp" => NotificationChannel::WhatsApp,
+        "telegram" => NotificationChannel::Telegram,
+        other => return Err(bad_value("channel", other)),
+    })
+}
+
+/// `notifications.status`, as its check constraint allows.
+pub fn status_name(status: NotificationStatus) -> &'static str {
+    match status {
+        NotificationStatus::Pending => "pending",
+        NotificationStatus::Sent => "sent",
+        NotificationStatus::Delivered => "delivered",
+        NotificationStatus::Failed => "failed",
+        NotificationStatus::Bounced => "bounced",
+        NotificationStatus::DeadLetter => "dead_letter",
+    }
+}
+
+pub fn parse_status(name: &str) -> AlbergueResult<NotificationStatus> {
+    Ok(match name {
+        "pending" => NotificationStatus::Pending,
+        "sent" => NotificationStatus::Sent,
+        "delivered" => NotificationStatus::Delivered,
+        "failed" => NotificationStatus::Failed,
+        "bounced" => NotificationStatus::Bounced,
+        "dead_letter" => NotificationStatus::DeadLetter,
+        other => return Err(bad_value("status", other)),
+    })
+}
+
+fn from_row(row: &PgRow) -> AlbergueResult<Notification> {
+    let text = |column: &str| row.try_get::<String, _>(column).map_err(db_error);
+    let fallback_channel: Option<String> = row.try_get("fallback_channel").map_err(db_error)?;
+    let template_data: Json<HashMap<String, String>> =
+        row.try_get("template_data").map_err(db_error)?;
+    let attempts: i32 = row.try_get("attempts").map_err(db_error)?;
+
+    Ok(Notification {
+        id: row.try_get("id").map_err(db_error)?,
+        notification_type: parse_type(&text("notification_type")?)?,
+        channel: parse_channel(&text("channel")?)?,
+        recipient: text("recipient")?,
+        subject: row.try_get("subject").map_err(db_error)?,
+        message: text("message")?,
+        template_data: template_data.0,
+        fallback_channel: fallback_channel.as_deref().map(parse_channel).transpose()?,
+        status: parse_status(&text("status")?)?,
+        provider_message_id: row.try_get("provider_message_id").map_err(db_error)?,
+        attempts: attempts.max(0) as u32,
+        next_attempt_at: row.try_get("next_attempt_at").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        sent_at: row.try_get("sent_at").map_err(db_error)?,
+        delivered_at: row.try_get("delivered_at").map_err(db_error)?,
+        error_message: row.try_get("error_message").map_err(db_error)?,
+        claimed_until: None,
+    })
+}
@@ -1,3 +1,5 @@
 pub mod twilio;
+pub mod twilio_webhook;
 
 pub use twilio::*;
+pub use twilio_webhook::*;
@@ -2,7 +2,6 @@ use crate::domain::Notification;
 use crate::ports::SmsPort;
 use async_trait::async_trait;
 use reqwest::Client;
-use serde_json::json;
 use shared::{AlbergueError, AlbergueResult};
 use std::collections::HashMap;
 
@@ -12,6 +11,14 @@ pub struct TwilioAdapter {
     auth_token: String,
     phone_number: String,
     whatsapp_number: String,
+    /// Where Twilio reports each message's delivery, see `TwilioWebhook`
+    status_callback_url: Option<String>,
+}
+
+impl Default for TwilioAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TwilioAdapter {
@@ -20,6 +27,9 @@ impl TwilioAdapter {
         let auth_token = std::env::var("TWILIO_AUTH_TOKEN").unwrap_or_default();
         let phone_number = std::env::var("TWILIO_PHONE_NUMBER").unwrap_or_default();
         let whatsapp_number = std::env::var("TWILIO_WHATSAPP_NUMBER").unwrap_or_default();
+        let status_callback_url = std::env::var("TWILIO_STATUS_CALLBACK_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
 
         Self {
             client: Client::new(),
@@ -27,6 +37,7 @@ impl TwilioAdapter {
             auth_token,
             phone_number,
             whatsapp_number,
+            status_callback_url,
         }
     }
 
@@ -40,6 +51,9 @@ impl TwilioAdapter {
         params.insert("To", to);
         params.insert("From", from);
         params.insert("Body", body);
+        if let Some(url) = &self.status_callback_url {
+            params.insert("StatusCallback", url.as_str());
+        }
 
         let response = self
             .client
@@ -49,15 +63,21 @@ impl TwilioAdapter {
             .send()
             .await
             .map_err(|e| {
-                AlbergueError::ExternalServiceError(format!("Twilio request failed: {}", e))
+                AlbergueError::ExternalService {
+                service: "twilio".to_string(),
+                message: format!("Twilio request failed: {}", e),
+            }
             })?;
 
         if response.status().is_success() {
             let result: serde_json::Value = response.json().await.map_err(|e| {
-                AlbergueError::ExternalServiceError(format!(
+                AlbergueError::ExternalService {
+                service: "twilio".to_string(),
+                message: format!(
                     "Failed to parse Twilio response: {}",
                     e
-                ))
+                ),
+            }
             })?;
 
             Ok(result["sid"].as_str().unwrap_or("unknown").to_string())
@@ -66,10 +86,13 @@ impl TwilioAdapter {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(AlbergueError::ExternalServiceError(format!(
+            Err(AlbergueError::ExternalService {
+                service: "twilio".to_string(),
+                message: format!(
                 "Twilio error: {}",
                 error_text
-            )))
+            ),
+            })
         }
     }
 }
@@ -0,0 +1,97 @@
+use crate::domain::{DeliveryReport, DeliveryStatus};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use shared::{AlbergueError, AlbergueResult};
+
+pub const SIGNATURE_HEADER: &str = "X-Twilio-Signature";
+
+/// Twilio's status callbacks, sent to `TWILIO_STATUS_CALLBACK_URL` for every
+/// message sent with it. Each is signed with the account's auth token: an
+/// HMAC-SHA1 of the callback URL followed by every form parameter, sorted,
+/// as name then value.
+pub struct TwilioWebhook {
+    auth_token: String,
+    callback_url: String,
+}
+
+impl TwilioWebhook {
+    pub fn new(auth_token: &str, callback_url: &str) -> Self {
+        Self {
+            auth_token: auth_token.to_string(),
+            callback_url: callback_url.to_string(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            &std::env::var("TWILIO_AUTH_TOKEN").unwrap_or_default(),
+            &std::env::var("TWILIO_STATUS_CALLBACK_URL").unwrap_or_default(),
+        )
+    }
+
+    /// The `X-Twilio-Signature` of a callback carrying `params`. Lets local
+    /// runs and tests stand in for Twilio.
+    pub fn sign(&self, params: &[(String, String)]) -> String {
+        base64::encode(self.mac(params).finalize().into_bytes())
+    }
+
+    pub fn verify(&self, params: &[(String, String)], signature: &str) -> bool {
+        if self.auth_token.is_empty() {
+            return false;
+        }
+        match base64::decode(signature.trim()) {
+            Ok(signature) => self.mac(params).verify_slice(&signature).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// The delivery report in a signed callback's form body. Callbacks that
+    /// are not about delivery, such as inbound messages, give `None`.
+    pub fn delivery_report(
+        &self,
+        form_body: &str,
+        signature: &str,
+    ) -> AlbergueResult<Option<DeliveryReport>> {
+        let params: Vec<(String, String)> = form_urlencoded::parse(form_body.as_bytes())
+            .into_owned()
+            .collect();
+        if !self.verify(&params, signature) {
+            return Err(AlbergueError::Authentication {
+                message: format!("Invalid {}", SIGNATURE_HEADER),
+            });
+        }
+
+        let param = |names: &[&str]| {
+            params
+                .iter()
+                .find(|(name, value)| names.contains(&name.as_str()) && !value.is_empty())
+                .map(|(_, value)| value.clone())
+        };
+        let (Some(provider_message_id), Some(status)) = (
+            param(&["MessageSid", "SmsSid"]),
+            param(&["MessageStatus", "SmsStatus"]).and_then(|s| DeliveryStatus::from_twilio(&s)),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(DeliveryReport {
+            provider_message_id,
+            status,
+            error_code: param(&["ErrorCode"]),
+        }))
+    }
+
+    fn mac(&self, params: &[(String, String)]) -> Hmac<Sha1> {
+        let mut sorted: Vec<&(String, String)> = params.iter().collect();
+        sorted.sort();
+
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.auth_token.as_bytes())
+            .expect("HMAC takes keys of any length");
+        mac.update(self.callback_url.as_bytes());
+        for (name, value) in sorted {
+            mac.update(name.as_bytes());
+            mac.update(value.as_bytes());
+        }
+        mac
+    }
+}
@@ -11,6 +11,12 @@ pub struct TelegrafAdapter {
     chat_id: String,
 }
 
+impl Default for TelegrafAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TelegrafAdapter {
     pub fn new() -> Self {
         let bot_token = std::env::var("TELEGRAM_BOT_TOKEN").unwrap_or_default();
@@ -48,15 +54,21 @@ impl TelegramPort for TelegrafAdapter {
             .send()
             .await
             .map_err(|e| {
-                AlbergueError::ExternalServiceError(format!("Telegram request failed: {}", e))
+                AlbergueError::ExternalService {
+                service: "telegram".to_string(),
+                message: format!("Telegram request failed: {}", e),
+            }
             })?;
 
         if response.status().is_success() {
             let result: serde_json::Value = response.json().await.map_err(|e| {
-                AlbergueError::ExternalServiceError(format!(
+                AlbergueError::ExternalService {
+                service: "telegram".to_string(),
+                message: format!(
                     "Failed to parse Telegram response: {}",
                     e
-                ))
+                ),
+            }
             })?;
 
             Ok(result["result"]["message_id"].to_string())
@@ -65,10 +77,13 @@ impl TelegramPort for TelegrafAdapter {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(AlbergueError::ExternalServiceError(format!(
+            Err(AlbergueError::ExternalService {
+                service: "telegram".to_string(),
+                message: format!(
                 "Telegram error: {}",
                 error_text
-            )))
+            ),
+            })
         }
     }
 
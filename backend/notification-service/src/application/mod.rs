@@ -1,3 +1,5 @@
 pub mod notification_service;
+pub mod outbox_worker;
 
 pub use notification_service::*;
+pub use outbox_worker::*;
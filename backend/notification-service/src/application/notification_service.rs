@@ -1,16 +1,34 @@
+use super::outbox_worker::OutboxWorker;
+use crate::adapters::sms::TwilioWebhook;
 use crate::domain::*;
+use crate::infrastructure::config::OutboxConfig;
 use crate::ports::*;
+use chrono::Utc;
 use serde_json;
+use shared::health::{probe, HealthReport};
 use shared::{AlbergueError, AlbergueResult};
 use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
 
+/// Notifications are written to the outbox and sent by its `OutboxWorker`,
+/// so a provider being down delays them instead of losing them.
 pub struct NotificationServiceImpl {
-    email_adapter: Box<dyn EmailPort>,
-    sms_adapter: Box<dyn SmsPort>,
-    telegram_adapter: Box<dyn TelegramPort>,
+    outbox: Arc<dyn OutboxPort>,
+    email_adapter: Arc<dyn EmailPort>,
+    sms_adapter: Arc<dyn SmsPort>,
+    telegram_adapter: Arc<dyn TelegramPort>,
+    retry_policy: RetryPolicy,
+    twilio_webhook: TwilioWebhook,
     template_engine: handlebars::Handlebars<'static>,
 }
 
+impl Default for NotificationServiceImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NotificationServiceImpl {
     pub fn new() -> Self {
         let mut template_engine = handlebars::Handlebars::new();
@@ -19,13 +37,147 @@ impl NotificationServiceImpl {
         Self::register_templates(&mut template_engine);
 
         Self {
-            email_adapter: Box::new(crate::adapters::email::NodemailerAdapter::new()),
-            sms_adapter: Box::new(crate::adapters::sms::TwilioAdapter::new()),
-            telegram_adapter: Box::new(crate::adapters::telegram::TelegrafAdapter::new()),
+            outbox: Arc::new(crate::adapters::outbox::MemoryOutbox::new()),
+            email_adapter: Arc::new(crate::adapters::email::NodemailerAdapter::new()),
+            sms_adapter: Arc::new(crate::adapters::sms::TwilioAdapter::new()),
+            telegram_adapter: Arc::new(crate::adapters::telegram::TelegrafAdapter::new()),
+            retry_policy: OutboxConfig::from_env().retry_policy(),
+            twilio_webhook: TwilioWebhook::from_env(),
             template_engine,
         }
     }
 
+    pub fn with_outbox(mut self, outbox: Arc<dyn OutboxPort>) -> Self {
+        self.outbox = outbox;
+        self
+    }
+
+    pub fn with_adapters(
+        mut self,
+        email_adapter: Arc<dyn EmailPort>,
+        sms_adapter: Arc<dyn SmsPort>,
+        telegram_adapter: Arc<dyn TelegramPort>,
+    ) -> Self {
+        self.email_adapter = email_adapter;
+        self.sms_adapter = sms_adapter;
+        self.telegram_adapter = telegram_adapter;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_twilio_webhook(mut self, twilio_webhook: TwilioWebhook) -> Self {
+        self.twilio_webhook = twilio_webhook;
+        self
+    }
+
+    /// A worker sending from this service's outbox through its adapters.
+    pub fn worker(&self) -> OutboxWorker {
+        OutboxWorker::new(
+            self.outbox.clone(),
+            self.email_adapter.clone(),
+            self.sms_adapter.clone(),
+            self.telegram_adapter.clone(),
+        )
+        .with_retry_policy(self.retry_policy)
+    }
+
+    /// Stores the notification for the worker to send; returns its id.
+    pub async fn enqueue(&self, notification: Notification) -> AlbergueResult<String> {
+        self.outbox.save(&notification).await?;
+        tracing::debug!(
+            notification_id = %notification.id,
+            channel = ?notification.channel,
+            "Notification queued"
+        );
+        Ok(notification.id.to_string())
+    }
+
+    /// Notifications out of attempts, for an admin to inspect.
+    pub async fn dead_letters(&self) -> AlbergueResult<Vec<Notification>> {
+        self.outbox.dead_letters().await
+    }
+
+    /// Handles a Twilio status callback: its form body and `X-Twilio-Signature`.
+    /// Returns the notification it updated, if any.
+    pub async fn handle_twilio_status_callback(
+        &self,
+        form_body: &str,
+        signature: &str,
+    ) -> AlbergueResult<Option<Notification>> {
+        match self.twilio_webhook.delivery_report(form_body, signature)? {
+            Some(report) => self.record_delivery(&report).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Updates the notification a delivery report is about. One that bounced
+    /// is queued again on its fallback channel, WhatsApp's being SMS.
+    pub async fn record_delivery(
+        &self,
+        report: &DeliveryReport,
+    ) -> AlbergueResult<Option<Notification>> {
+        let Some(mut notification) = self
+            .outbox
+            .find_by_provider_message_id(&report.provider_message_id)
+            .await?
+        else {
+            // Not ours, or sent before the outbox; Twilio should not retry it
+            tracing::warn!(
+                provider_message_id = %report.provider_message_id,
+                "Delivery report for an unknown message"
+            );
+            return Ok(None);
+        };
+        if !notification.record_delivery(report) {
+            return Ok(Some(notification));
+        }
+
+        if !self.outbox.save(&notification).await? {
+            // A worker is retrying it, so the report is about an old attempt
+            tracing::warn!(
+                notification_id = %notification.id,
+                "Delivery report for a notification being sent again"
+            );
+            return Ok(None);
+        }
+        if notification.status == NotificationStatus::Bounced {
+            if let Some(fallback) = notification.fallback() {
+                tracing::info!(
+                    notification_id = %notification.id,
+                    fallback_id = %fallback.id,
+                    channel = ?fallback.channel,
+                    "Bounced notification queued on its fallback channel"
+                );
+                self.enqueue(fallback).await?;
+            }
+        }
+        Ok(Some(notification))
+    }
+
+    /// Gives a dead-lettered notification a fresh set of attempts.
+    pub async fn requeue(&self, id: Uuid) -> AlbergueResult<Notification> {
+        let mut notification =
+            self.outbox
+                .get(id)
+                .await?
+                .ok_or_else(|| AlbergueError::NotFound {
+                    resource: format!("Notification {}", id),
+                })?;
+        if notification.status != NotificationStatus::DeadLetter {
+            return Err(AlbergueError::Validation {
+                message: format!("Notification {} is not dead-lettered", id),
+            });
+        }
+
+        notification.requeue(Utc::now());
+        self.outbox.save(&notification).await?;
+        Ok(notification)
+    }
+
     fn register_templates(engine: &mut handlebars::Handlebars<'static>) {
         // Booking confirmation email template
         engine
@@ -83,6 +235,23 @@ Albergue del Carrascalejo
         ).unwrap();
     }
 
+    /// Checks every delivery channel. None of them is critical on its own:
+    /// a single channel being down leaves the service in degraded mode.
+    pub async fn health_report(&self) -> HealthReport {
+        let (smtp, twilio, telegram) = futures::join!(
+            probe("smtp", self.email_adapter.verify_smtp_connection()),
+            probe("twilio", self.sms_adapter.verify_twilio_connection()),
+            probe("telegram", self.telegram_adapter.verify_bot_connection()),
+        );
+        let dependencies = vec![smtp, twilio, telegram];
+
+        HealthReport::from_dependencies(
+            "notification-service",
+            env!("CARGO_PKG_VERSION"),
+            dependencies,
+        )
+    }
+
     pub async fn send_email(
         &self,
         recipient: &str,
@@ -97,7 +266,7 @@ Albergue del Carrascalejo
         )
         .with_subject(subject.to_string());
 
-        self.email_adapter.send_email(&notification).await
+        self.enqueue(notification).await
     }
 
     pub async fn send_sms(&self, recipient: &str, message: &str) -> AlbergueResult<String> {
@@ -108,7 +277,7 @@ Albergue del Carrascalejo
             message.to_string(),
         );
 
-        self.sms_adapter.send_sms(&notification).await
+        self.enqueue(notification).await
     }
 
     pub async fn send_whatsapp(&self, recipient: &str, message: &str) -> AlbergueResult<String> {
@@ -119,7 +288,7 @@ Albergue del Carrascalejo
             message.to_string(),
         );
 
-        self.sms_adapter.send_whatsapp(&notification).await
+        self.enqueue(notification).await
     }
 
     pub async fn send_telegram(&self, chat_id: &str, message: &str) -> AlbergueResult<String> {
@@ -130,12 +299,14 @@ Albergue del Carrascalejo
             message.to_string(),
         );
 
-        self.telegram_adapter.send_telegram(&notification).await
+        self.enqueue(notification).await
     }
 
     pub async fn send_booking_confirmation(&self, booking_data: &str) -> AlbergueResult<String> {
-        let data: BookingNotificationData = serde_json::from_str(booking_data)
-            .map_err(|e| AlbergueError::ValidationError(format!("Invalid booking data: {}", e)))?;
+        let data: BookingNotificationData =
+            serde_json::from_str(booking_data).map_err(|e| AlbergueError::Validation {
+                message: format!("Invalid booking data: {}", e),
+            })?;
 
         // Prepare template data
         let mut template_data = HashMap::new();
@@ -151,7 +322,9 @@ Albergue del Carrascalejo
         let email_content = self
             .template_engine
             .render("booking_confirmation_email", &template_data)
-            .map_err(|e| AlbergueError::ValidationError(format!("Template error: {}", e)))?;
+            .map_err(|e| AlbergueError::Validation {
+                message: format!("Template error: {}", e),
+            })?;
 
         let email_notification = Notification::new(
             NotificationType::ReservationCreated,
@@ -162,14 +335,16 @@ Albergue del Carrascalejo
         .with_subject("Reserva confirmada - Albergue del Carrascalejo".to_string())
         .with_template_data(template_data.clone());
 
-        let email_result = self.email_adapter.send_email(&email_notification).await?;
+        let email_result = self.enqueue(email_notification).await?;
 
         // Send WhatsApp if phone available
         if let Some(phone) = data.pilgrim_phone {
             let whatsapp_content = self
                 .template_engine
                 .render("booking_confirmation_whatsapp", &template_data)
-                .map_err(|e| AlbergueError::ValidationError(format!("Template error: {}", e)))?;
+                .map_err(|e| AlbergueError::Validation {
+                    message: format!("Template error: {}", e),
+                })?;
 
             let whatsapp_notification = Notification::new(
                 NotificationType::ReservationCreated,
@@ -177,23 +352,20 @@ Albergue del Carrascalejo
                 phone,
                 whatsapp_content,
             )
-            .with_template_data(template_data);
-
-            // Try WhatsApp, fallback to SMS
-            match self.sms_adapter.send_whatsapp(&whatsapp_notification).await {
-                Ok(_) => {}
-                Err(_) => {
-                    let _ = self.sms_adapter.send_sms(&whatsapp_notification).await;
-                }
-            }
+            .with_template_data(template_data)
+            .with_fallback(NotificationChannel::SMS);
+
+            self.enqueue(whatsapp_notification).await?;
         }
 
         Ok(email_result)
     }
 
     pub async fn send_payment_receipt(&self, payment_data: &str) -> AlbergueResult<String> {
-        let data: PaymentNotificationData = serde_json::from_str(payment_data)
-            .map_err(|e| AlbergueError::ValidationError(format!("Invalid payment data: {}", e)))?;
+        let data: PaymentNotificationData =
+            serde_json::from_str(payment_data).map_err(|e| AlbergueError::Validation {
+                message: format!("Invalid payment data: {}", e),
+            })?;
 
         let mut template_data = HashMap::new();
         template_data.insert("booking_id".to_string(), data.booking_id.clone());
@@ -210,10 +382,12 @@ Albergue del Carrascalejo
             template_data.insert("receipt_url".to_string(), receipt_url.clone());
         }
 
-        let email_content = self
+        self
             .template_engine
             .render("payment_receipt_email", &template_data)
-            .map_err(|e| AlbergueError::ValidationError(format!("Template error: {}", e)))?;
+            .map_err(|e| AlbergueError::Validation {
+                message: format!("Template error: {}", e),
+            })?;
 
         // This would need to get the recipient email from the booking service
         // For now, we'll return a success message
@@ -0,0 +1,172 @@
+use crate::domain::*;
+use crate::ports::*;
+use chrono::{DateTime, Utc};
+use shared::AlbergueResult;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const DEFAULT_BATCH_SIZE: usize = 50;
+
+/// What one pass over the outbox did.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchSummary {
+    pub sent: usize,
+    pub retrying: usize,
+    pub dead_lettered: usize,
+}
+
+/// Sends what is due in the outbox. Each channel backs off on its own
+/// policy; a notification out of attempts is dead-lettered for an admin.
+pub struct OutboxWorker {
+    outbox: Arc<dyn OutboxPort>,
+    email_adapter: Arc<dyn EmailPort>,
+    sms_adapter: Arc<dyn SmsPort>,
+    telegram_adapter: Arc<dyn TelegramPort>,
+    retry_policy: RetryPolicy,
+    channel_policies: HashMap<NotificationChannel, RetryPolicy>,
+    batch_size: usize,
+}
+
+impl OutboxWorker {
+    pub fn new(
+        outbox: Arc<dyn OutboxPort>,
+        email_adapter: Arc<dyn EmailPort>,
+        sms_adapter: Arc<dyn SmsPort>,
+        telegram_adapter: Arc<dyn TelegramPort>,
+    ) -> Self {
+        Self {
+            outbox,
+            email_adapter,
+            sms_adapter,
+            telegram_adapter,
+            retry_policy: RetryPolicy::default(),
+            channel_policies: HashMap::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// The policy of channels without one of their own.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn with_channel_retry_policy(
+        mut self,
+        channel: NotificationChannel,
+        policy: RetryPolicy,
+    ) -> Self {
+        self.channel_policies.insert(channel, policy);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn retry_policy(&self, channel: NotificationChannel) -> RetryPolicy {
+        self.channel_policies
+            .get(&channel)
+            .copied()
+            .unwrap_or(self.retry_policy)
+    }
+
+    /// Attempts every notification due at `now`, up to the batch size.
+    pub async fn dispatch_due(&self, now: DateTime<Utc>) -> AlbergueResult<DispatchSummary> {
+        let mut summary = DispatchSummary::default();
+
+        for mut notification in self.outbox.due(now, self.batch_size).await? {
+            match self.deliver(&mut notification).await {
+                Ok(provider_message_id) => {
+                    notification.provider_message_id = Some(provider_message_id);
+                    notification.attempts += 1;
+                    notification.mark_sent();
+                    summary.sent += 1;
+                }
+                Err(error) => {
+                    let policy = self.retry_policy(notification.channel);
+                    notification.mark_attempt_failed(error, &policy, now);
+                    if notification.status == NotificationStatus::DeadLetter {
+                        tracing::warn!(
+                            notification_id = %notification.id,
+                            attempts = notification.attempts,
+                            error = notification.error_message.as_deref().unwrap_or_default(),
+                            "Notification dead-lettered"
+                        );
+                        summary.dead_lettered += 1;
+                    } else {
+                        summary.retrying += 1;
+                    }
+                }
+            }
+            if !self.outbox.save(&notification).await? {
+                tracing::warn!(
+                    notification_id = %notification.id,
+                    "Claim lost to another worker; attempt not recorded"
+                );
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Dispatches on an interval until the task is dropped. Outbox errors are
+    /// logged and the next pass tries again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn run(&self, interval: std::time::Duration) {
+        loop {
+            match self.dispatch_due(Utc::now()).await {
+                Ok(summary) if summary != DispatchSummary::default() => {
+                    tracing::info!(
+                        sent = summary.sent,
+                        retrying = summary.retrying,
+                        dead_lettered = summary.dead_lettered,
+                        "Outbox dispatched"
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!(error = %e, "Outbox dispatch failed"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Sends on the notification's channel, then on its fallback. A delivery
+    /// by the fallback becomes the notification's channel.
+    async fn deliver(&self, notification: &mut Notification) -> Result<String, String> {
+        let error = match self.send(notification, notification.channel).await {
+            Ok(provider_message_id) => return Ok(provider_message_id),
+            Err(e) => format!("{:?}: {}", notification.channel, e),
+        };
+        let Some(fallback) = notification
+            .fallback_channel
+            .filter(|c| *c != notification.channel)
+        else {
+            return Err(error);
+        };
+
+        match self.send(notification, fallback).await {
+            Ok(provider_message_id) => {
+                notification.channel = fallback;
+                Ok(provider_message_id)
+            }
+            Err(e) => Err(format!("{}; {:?}: {}", error, fallback, e)),
+        }
+    }
+
+    async fn send(
+        &self,
+        notification: &Notification,
+        channel: NotificationChannel,
+    ) -> AlbergueResult<String> {
+        match channel {
+            NotificationChannel::Email => self.email_adapter.send_email(notification).await,
+            NotificationChannel::SMS => self.sms_adapter.send_sms(notification).await,
+            NotificationChannel::WhatsApp => self.sms_adapter.send_whatsapp(notification).await,
+            NotificationChannel::Telegram => {
+                self.telegram_adapter.send_telegram(notification).await
+            }
+        }
+    }
+}
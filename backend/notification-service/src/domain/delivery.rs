@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// A message's progress as the provider reports it after accepting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Queued,
+    Sending,
+    Sent,
+    Delivered,
+    /// WhatsApp only: the recipient opened it
+    Read,
+    /// The carrier or WhatsApp could not deliver it
+    Undelivered,
+    /// The provider could not send it
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// Twilio's `MessageStatus`. Statuses of inbound messages are not
+    /// delivery reports and give `None`.
+    pub fn from_twilio(status: &str) -> Option<Self> {
+        match status {
+            "accepted" | "scheduled" | "queued" => Some(Self::Queued),
+            "sending" => Some(Self::Sending),
+            "sent" => Some(Self::Sent),
+            "delivered" => Some(Self::Delivered),
+            "read" => Some(Self::Read),
+            "undelivered" => Some(Self::Undelivered),
+            "failed" | "canceled" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Undelivered | Self::Failed)
+    }
+}
+
+/// A delivery status callback for a message sent earlier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    /// The id the provider returned when the message was sent
+    pub provider_message_id: String,
+    pub status: DeliveryStatus,
+    pub error_code: Option<String>,
+}
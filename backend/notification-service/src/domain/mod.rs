@@ -1,3 +1,7 @@
+pub mod delivery;
 pub mod notification;
+pub mod retry;
 
+pub use delivery::*;
 pub use notification::*;
+pub use retry::*;
@@ -1,3 +1,4 @@
+use super::{DeliveryReport, DeliveryStatus, RetryPolicy};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -12,14 +13,25 @@ pub struct Notification {
     pub subject: Option<String>,
     pub message: String,
     pub template_data: HashMap<String, String>,
+    /// Tried in the same attempt when `channel` fails
+    pub fallback_channel: Option<NotificationChannel>,
     pub status: NotificationStatus,
+    pub provider_message_id: Option<String>,
+    /// Delivery attempts so far, reset on requeue
+    pub attempts: u32,
+    /// When the outbox worker may pick it up next
+    pub next_attempt_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub sent_at: Option<DateTime<Utc>>,
     pub delivered_at: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
+    /// The claim `OutboxPort::due` gave this copy to a worker. Saving the
+    /// copy releases it; copies read any other way hold none.
+    #[serde(skip)]
+    pub claimed_until: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NotificationType {
     ReservationCreated,
     PaymentConfirmed,
@@ -30,7 +42,7 @@ pub enum NotificationType {
     MirSubmissionUpdate,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NotificationChannel {
     Email,
     SMS,
@@ -38,13 +50,17 @@ pub enum NotificationChannel {
     Telegram,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NotificationStatus {
     Pending,
     Sent,
     Delivered,
+    /// The last attempt failed; another is scheduled
     Failed,
+    /// Accepted by the provider, then reported undelivered or failed
     Bounced,
+    /// Out of attempts; waits for an admin to requeue it
+    DeadLetter,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +106,7 @@ impl Notification {
         recipient: String,
         message: String,
     ) -> Self {
+        let created_at = Utc::now();
         Self {
             id: Uuid::new_v4(),
             notification_type,
@@ -98,11 +115,16 @@ impl Notification {
             subject: None,
             message,
             template_data: HashMap::new(),
+            fallback_channel: None,
             status: NotificationStatus::Pending,
-            created_at: Utc::now(),
+            provider_message_id: None,
+            attempts: 0,
+            next_attempt_at: created_at,
+            created_at,
             sent_at: None,
             delivered_at: None,
             error_message: None,
+            claimed_until: None,
         }
     }
 
@@ -116,6 +138,11 @@ impl Notification {
         self
     }
 
+    pub fn with_fallback(mut self, channel: NotificationChannel) -> Self {
+        self.fallback_channel = Some(channel);
+        self
+    }
+
     pub fn mark_sent(&mut self) {
         self.status = NotificationStatus::Sent;
         self.sent_at = Some(Utc::now());
@@ -130,4 +157,72 @@ impl Notification {
         self.status = NotificationStatus::Failed;
         self.error_message = Some(error);
     }
+
+    /// Whether the outbox worker should try it at `now`.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        matches!(
+            self.status,
+            NotificationStatus::Pending | NotificationStatus::Failed
+        ) && self.next_attempt_at <= now
+    }
+
+    /// Records a failed attempt: another is scheduled after the policy's
+    /// backoff, or the notification is dead-lettered once out of attempts.
+    pub fn mark_attempt_failed(&mut self, error: String, policy: &RetryPolicy, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.mark_failed(error);
+        if self.attempts >= policy.max_attempts {
+            self.status = NotificationStatus::DeadLetter;
+        } else {
+            self.next_attempt_at = now + policy.backoff(self.attempts);
+        }
+    }
+
+    /// Puts a dead-lettered notification back in the outbox with a fresh
+    /// set of attempts. The last error is kept until the next attempt.
+    pub fn requeue(&mut self, now: DateTime<Utc>) {
+        self.status = NotificationStatus::Pending;
+        self.attempts = 0;
+        self.next_attempt_at = now;
+    }
+
+    /// Applies the provider's report on a sent notification. Delivered and
+    /// bounced are final, so late or repeated callbacks change nothing.
+    /// Returns whether the notification changed.
+    pub fn record_delivery(&mut self, report: &DeliveryReport) -> bool {
+        if self.status != NotificationStatus::Sent {
+            return false;
+        }
+        match report.status {
+            DeliveryStatus::Queued | DeliveryStatus::Sending | DeliveryStatus::Sent => false,
+            DeliveryStatus::Delivered | DeliveryStatus::Read => {
+                self.mark_delivered();
+                true
+            }
+            DeliveryStatus::Undelivered | DeliveryStatus::Failed => {
+                self.status = NotificationStatus::Bounced;
+                self.error_message = Some(match &report.error_code {
+                    Some(code) => {
+                        format!("{:?}: {:?} (error {})", self.channel, report.status, code)
+                    }
+                    None => format!("{:?}: {:?}", self.channel, report.status),
+                });
+                true
+            }
+        }
+    }
+
+    /// A copy to send on the fallback channel once this one bounced.
+    pub fn fallback(&self) -> Option<Notification> {
+        let channel = self.fallback_channel.filter(|c| *c != self.channel)?;
+        let mut fallback = Notification::new(
+            self.notification_type.clone(),
+            channel,
+            self.recipient.clone(),
+            self.message.clone(),
+        )
+        .with_template_data(self.template_data.clone());
+        fallback.subject = self.subject.clone();
+        Some(fallback)
+    }
 }
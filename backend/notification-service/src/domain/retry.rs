@@ -0,0 +1,43 @@
+use chrono::Duration;
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub const DEFAULT_BASE_DELAY_SECONDS: i64 = 30;
+pub const DEFAULT_MAX_DELAY_SECONDS: i64 = 3600;
+
+/// Exponential backoff between delivery attempts on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts before the notification is dead-lettered
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// The wait after the `attempts`-th failure: the base delay, doubled for
+    /// each failure before it, up to the maximum.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 1i32 << attempts.saturating_sub(1).min(30);
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_ATTEMPTS,
+            Duration::seconds(DEFAULT_BASE_DELAY_SECONDS),
+            Duration::seconds(DEFAULT_MAX_DELAY_SECONDS),
+        )
+    }
+}
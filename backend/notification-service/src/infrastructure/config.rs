@@ -1,11 +1,9 @@
+use crate::domain::{
+    RetryPolicy, DEFAULT_BASE_DELAY_SECONDS, DEFAULT_MAX_ATTEMPTS, DEFAULT_MAX_DELAY_SECONDS,
+};
+use chrono::Duration;
 use shared::AlbergueResult;
 
-pub fn init_logging() {
-    tracing_subscriber::fmt()
-        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))
-        .init();
-}
-
 pub struct NotificationConfig {
     pub smtp_host: String,
     pub smtp_port: u16,
@@ -41,3 +39,50 @@ impl NotificationConfig {
         })
     }
 }
+
+/// Where the notification outbox is kept, and its retries and pacing.
+pub struct OutboxConfig {
+    /// The `notifications` table's database; the outbox is kept in memory
+    /// without one
+    pub database_url: Option<String>,
+    pub max_attempts: u32,
+    pub base_delay_seconds: i64,
+    pub max_delay_seconds: i64,
+    pub interval_seconds: u64,
+}
+
+impl OutboxConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            database_url: std::env::var("DATABASE_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+            max_attempts: var("NOTIFICATION_RETRY_ATTEMPTS", DEFAULT_MAX_ATTEMPTS),
+            base_delay_seconds: var(
+                "NOTIFICATION_RETRY_BASE_SECONDS",
+                DEFAULT_BASE_DELAY_SECONDS,
+            ),
+            max_delay_seconds: var("NOTIFICATION_RETRY_MAX_SECONDS", DEFAULT_MAX_DELAY_SECONDS),
+            interval_seconds: var("NOTIFICATION_OUTBOX_INTERVAL_SECONDS", 10),
+        }
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(
+            self.max_attempts,
+            Duration::seconds(self.base_delay_seconds),
+            Duration::seconds(self.max_delay_seconds),
+        )
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_seconds.max(1))
+    }
+}
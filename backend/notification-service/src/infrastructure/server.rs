@@ -1,37 +1,141 @@
+use crate::application::NotificationServiceImpl;
 use shared::AlbergueResult;
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::adapters::sms::SIGNATURE_HEADER;
+#[cfg(not(target_arch = "wasm32"))]
+use shared::AlbergueError;
+#[cfg(not(target_arch = "wasm32"))]
+use shared::health::HealthReport;
+#[cfg(not(target_arch = "wasm32"))]
+use shared::telemetry::with_request_span;
 
 pub struct NotificationServer {
     port: u16,
+    service: Arc<NotificationServiceImpl>,
 }
 
 impl NotificationServer {
     pub fn new(port: u16) -> Self {
-        Self { port }
+        Self {
+            port,
+            service: Arc::new(NotificationServiceImpl::new()),
+        }
+    }
+
+    pub fn with_service(mut self, service: Arc<NotificationServiceImpl>) -> Self {
+        self.service = service;
+        self
     }
 
     pub async fn run(self) -> AlbergueResult<()> {
         tracing::info!("Notification service starting on port {}", self.port);
 
         // For WASM deployment, this would be handled by the Spin framework
-        // In standalone mode, this would set up an HTTP server
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            // Standalone server implementation would go here
-            // For now, just log and return
-            tracing::info!("Notification service running in standalone mode");
-            tokio::signal::ctrl_c().await.unwrap();
+            let listener = tokio::net::TcpListener::bind(("0.0.0.0", self.port))
+                .await
+                .map_err(|e| shared::AlbergueError::Internal {
+                    message: format!("Cannot listen on port {}: {}", self.port, e),
+                })?;
+            let interval = super::config::OutboxConfig::from_env().interval();
+            let worker = self.service.worker();
+            let server = axum::serve(listener, router(self.service));
+            tokio::select! {
+                result = server => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Notification HTTP server stopped");
+                    }
+                }
+                _ = worker.run(interval) => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
         }
 
         Ok(())
     }
 }
 
+/// HTTP routes of the standalone service.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn router(service: Arc<NotificationServiceImpl>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/twilio/status", post(twilio_status))
+        .layer(middleware::from_fn(with_request_span))
+        .with_state(service)
+}
+
+/// Reports every delivery channel: 200 while the service can take traffic,
+/// 503 otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+async fn health(
+    State(service): State<Arc<NotificationServiceImpl>>,
+) -> (StatusCode, Json<HealthReport>) {
+    let report = service.health_report().await;
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Twilio's message status callback, proxied by the gateway with the form
+/// body as Twilio sent it, since the `X-Twilio-Signature` covers it.
+#[cfg(not(target_arch = "wasm32"))]
+async fn twilio_status(
+    State(service): State<Arc<NotificationServiceImpl>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    match service.handle_twilio_status_callback(&body, signature).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            let status = match e {
+                AlbergueError::Authentication { .. } => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            tracing::warn!(error = %e, "Twilio status callback refused");
+            (status, Json(serde_json::json!({"error": e.to_string()}))).into_response()
+        }
+    }
+}
+
 pub async fn create_server() -> AlbergueResult<NotificationServer> {
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "8002".to_string())
         .parse()
         .unwrap_or(8002);
 
-    Ok(NotificationServer::new(port))
+    let service = NotificationServiceImpl::new();
+    #[cfg(not(target_arch = "wasm32"))]
+    let service = match super::config::OutboxConfig::from_env().database_url {
+        Some(url) => {
+            let outbox = crate::adapters::outbox::PostgresOutbox::connect(&url).await?;
+            service.with_outbox(Arc::new(outbox))
+        }
+        None => {
+            tracing::warn!("DATABASE_URL is not set; queued notifications are lost on restart");
+            service
+        }
+    };
+
+    Ok(NotificationServer::new(port).with_service(Arc::new(service)))
 }
@@ -4,5 +4,5 @@ pub mod ports;
 pub mod adapters;
 pub mod infrastructure;
 
-pub use application::notification_service::NotificationService;
+pub use application::notification_service::NotificationServiceImpl;
 pub use domain::notification::{Notification, NotificationChannel, NotificationStatus};
\ No newline at end of file
@@ -1,14 +1,7 @@
 use wasm_bindgen::prelude::*;
-use shared::{AlbergueError, AlbergueResult};
+use shared::AlbergueResult;
 
-mod domain;
-mod application;
-mod ports;
-mod adapters;
-mod infrastructure;
-
-pub use domain::*;
-pub use application::*;
+use notification_service::{application, infrastructure};
 
 #[wasm_bindgen]
 extern "C" {
@@ -21,6 +14,12 @@ pub struct NotificationService {
     service: application::NotificationServiceImpl,
 }
 
+impl Default for NotificationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl NotificationService {
     #[wasm_bindgen(constructor)]
@@ -29,6 +28,12 @@ impl NotificationService {
         NotificationService { service }
     }
 
+    #[wasm_bindgen]
+    pub async fn health_check(&self) -> Result<String, JsValue> {
+        let report = self.service.health_report().await;
+        serde_json::to_string(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     #[wasm_bindgen]
     pub async fn send_email(&self, recipient: &str, subject: &str, content: &str) -> Result<String, JsValue> {
         self.service
@@ -69,6 +74,53 @@ impl NotificationService {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// One pass of the outbox worker, for a scheduled trigger.
+    #[wasm_bindgen]
+    pub async fn dispatch_outbox(&self) -> Result<String, JsValue> {
+        let summary = self
+            .service
+            .worker()
+            .dispatch_due(chrono::Utc::now())
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(format!(
+            "sent {}, retrying {}, dead-lettered {}",
+            summary.sent, summary.retrying, summary.dead_lettered
+        ))
+    }
+
+    #[wasm_bindgen]
+    pub async fn dead_letters(&self) -> Result<String, JsValue> {
+        let notifications = self
+            .service
+            .dead_letters()
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&notifications).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen]
+    pub async fn requeue_notification(&self, id: &str) -> Result<String, JsValue> {
+        let id = uuid::Uuid::parse_str(id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let notification = self
+            .service
+            .requeue(id)
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&notification).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Twilio's message status callback, with its `X-Twilio-Signature`.
+    #[wasm_bindgen]
+    pub async fn twilio_status_callback(&self, form_body: &str, signature: &str) -> Result<String, JsValue> {
+        let notification = self
+            .service
+            .handle_twilio_status_callback(form_body, signature)
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_json::to_string(&notification).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     #[wasm_bindgen]
     pub async fn send_payment_receipt(&self, payment_data: &str) -> Result<String, JsValue> {
         self.service
@@ -81,7 +133,7 @@ impl NotificationService {
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
 async fn main() -> AlbergueResult<()> {
-    infrastructure::config::init_logging();
+    shared::telemetry::init_logging();
     let server = infrastructure::server::create_server().await?;
     server.run().await
 }
\ No newline at end of file
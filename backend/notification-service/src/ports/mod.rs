@@ -1,7 +1,9 @@
 pub mod email_port;
+pub mod outbox_port;
 pub mod sms_port;
 pub mod telegram_port;
 
 pub use email_port::*;
+pub use outbox_port::*;
 pub use sms_port::*;
 pub use telegram_port::*;
@@ -0,0 +1,26 @@
+use crate::domain::Notification;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use shared::AlbergueResult;
+use uuid::Uuid;
+
+/// Durable store of notifications waiting to be sent, the `notifications`
+/// table.
+#[async_trait]
+pub trait OutboxPort: Send + Sync {
+    /// Adds a notification, or stores its new state if already there.
+    /// Returns `false`, storing nothing, while a worker holds a live claim
+    /// on it other than the one `notification` carries.
+    async fn save(&self, notification: &Notification) -> AlbergueResult<bool>;
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<Notification>>;
+    /// The notification a provider's delivery report is about.
+    async fn find_by_provider_message_id(
+        &self,
+        provider_message_id: &str,
+    ) -> AlbergueResult<Option<Notification>>;
+    /// Notifications due at `now`, longest waiting first, each claimed for
+    /// the caller until it is saved or the claim runs out.
+    async fn due(&self, now: DateTime<Utc>, limit: usize) -> AlbergueResult<Vec<Notification>>;
+    /// Notifications out of attempts, oldest first.
+    async fn dead_letters(&self) -> AlbergueResult<Vec<Notification>>;
+}
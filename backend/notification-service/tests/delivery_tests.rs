@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use notification_service::adapters::outbox::{MemoryOutbox, DEFAULT_CLAIM_LEASE_SECONDS};
+    use notification_service::adapters::sms::TwilioWebhook;
+    use notification_service::application::NotificationServiceImpl;
+    use notification_service::domain::*;
+    use notification_service::ports::OutboxPort;
+    use shared::AlbergueError;
+    use std::sync::Arc;
+
+    const CALLBACK_URL: &str = "https://albergue.example/api/notifications/twilio/status";
+    const AUTH_TOKEN: &str = "test-auth-token";
+
+    /// Stands in for Twilio: signs callbacks with the test auth token.
+    fn twilio() -> TwilioWebhook {
+        TwilioWebhook::new(AUTH_TOKEN, CALLBACK_URL)
+    }
+
+    fn callback(params: &[(&str, &str)]) -> (String, String) {
+        let params: Vec<(String, String)> = params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&params)
+            .finish();
+        (body, twilio().sign(&params))
+    }
+
+    fn status_callback(sid: &str, status: &str) -> (String, String) {
+        callback(&[
+            ("MessageSid", sid),
+            ("MessageStatus", status),
+            ("AccountSid", "AC42"),
+        ])
+    }
+
+    /// A WhatsApp booking confirmation Twilio accepted as `sid`.
+    async fn sent_whatsapp(outbox: &MemoryOutbox, sid: &str) -> Notification {
+        let mut notification = Notification::new(
+            NotificationType::ReservationCreated,
+            NotificationChannel::WhatsApp,
+            "+34666123456".to_string(),
+            "Reserva confirmada".to_string(),
+        )
+        .with_fallback(NotificationChannel::SMS);
+        notification.provider_message_id = Some(sid.to_string());
+        notification.mark_sent();
+        outbox.save(&notification).await.unwrap();
+        notification
+    }
+
+    fn service(outbox: &MemoryOutbox) -> NotificationServiceImpl {
+        NotificationServiceImpl::new()
+            .with_outbox(Arc::new(outbox.clone()))
+            .with_twilio_webhook(twilio())
+    }
+
+    #[test]
+    fn test_signature_matches_twilio_scheme() {
+        let params: Vec<(String, String)> = [
+            ("MessageSid", "SM0123456789"),
+            ("MessageStatus", "delivered"),
+            ("To", "whatsapp:+34666123456"),
+            ("AccountSid", "AC42"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+        // URL, then parameters sorted by name, under HMAC-SHA1 of the token
+        assert_eq!(twilio().sign(&params), "3PXU4YdBn+efEqyONdOdsnc8JU8=");
+        assert!(twilio().verify(&params, "3PXU4YdBn+efEqyONdOdsnc8JU8="));
+    }
+
+    #[test]
+    fn test_tampered_or_foreign_callbacks_are_refused() {
+        let (body, signature) = status_callback("SM1", "delivered");
+        assert!(twilio()
+            .delivery_report(&body, &signature)
+            .unwrap()
+            .is_some());
+
+        let tampered = body.replace("delivered", "failed");
+        let other_url = TwilioWebhook::new(AUTH_TOKEN, "https://elsewhere.example/status");
+        let other_token = TwilioWebhook::new("another-token", CALLBACK_URL);
+        let unconfigured = TwilioWebhook::new("", CALLBACK_URL);
+
+        for (webhook, body, signature) in [
+            (&twilio(), tampered.as_str(), signature.as_str()),
+            (&twilio(), body.as_str(), "not base64!"),
+            (&other_url, body.as_str(), signature.as_str()),
+            (&other_token, body.as_str(), signature.as_str()),
+            (&unconfigured, body.as_str(), signature.as_str()),
+        ] {
+            assert!(matches!(
+                webhook.delivery_report(body, signature),
+                Err(AlbergueError::Authentication { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_twilio_statuses_are_mapped() {
+        let report = |status| {
+            let (body, signature) = status_callback("SM1", status);
+            twilio()
+                .delivery_report(&body, &signature)
+                .unwrap()
+                .map(|r| r.status)
+        };
+
+        assert_eq!(report("queued"), Some(DeliveryStatus::Queued));
+        assert_eq!(report("sent"), Some(DeliveryStatus::Sent));
+        assert_eq!(report("delivered"), Some(DeliveryStatus::Delivered));
+        assert_eq!(report("read"), Some(DeliveryStatus::Read));
+        assert_eq!(report("undelivered"), Some(DeliveryStatus::Undelivered));
+        assert_eq!(report("failed"), Some(DeliveryStatus::Failed));
+        // Inbound messages are not delivery reports
+        assert_eq!(report("received"), None);
+    }
+
+    #[tokio::test]
+    async fn test_delivered_and_read_mark_the_notification_delivered() {
+        let outbox = MemoryOutbox::new();
+        let sent = sent_whatsapp(&outbox, "SM1").await;
+        let service = service(&outbox);
+
+        let (body, signature) = status_callback("SM1", "sent");
+        let unchanged = service
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(unchanged.status, NotificationStatus::Sent);
+
+        let (body, signature) = status_callback("SM1", "delivered");
+        service
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap();
+        let delivered = outbox.get(sent.id).await.unwrap().unwrap();
+        assert_eq!(delivered.status, NotificationStatus::Delivered);
+        assert!(delivered.delivered_at.is_some());
+
+        // A late failure does not undo the delivery
+        let (body, signature) = status_callback("SM1", "undelivered");
+        service
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap();
+        assert_eq!(
+            outbox.get(sent.id).await.unwrap().unwrap().status,
+            NotificationStatus::Delivered
+        );
+        assert!(outbox.due(Utc::now(), 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_whatsapp_falls_back_to_sms() {
+        let outbox = MemoryOutbox::new();
+        let sent = sent_whatsapp(&outbox, "SM2").await;
+        let service = service(&outbox);
+
+        let (body, signature) = callback(&[
+            ("MessageSid", "SM2"),
+            ("MessageStatus", "undelivered"),
+            ("ErrorCode", "63016"),
+        ]);
+        let bounced = service
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(bounced.status, NotificationStatus::Bounced);
+        assert!(bounced.error_message.unwrap().contains("63016"));
+        let due = outbox.due(Utc::now(), 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].channel, NotificationChannel::SMS);
+        assert_eq!(due[0].recipient, sent.recipient);
+        assert_eq!(due[0].message, sent.message);
+        assert_ne!(due[0].id, sent.id);
+
+        // Twilio repeating the callback queues nothing more; the fallback
+        // is claimed above, so look once that claim has run out
+        let (body, signature) = status_callback("SM2", "failed");
+        service
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap();
+        let after_claim = Utc::now() + Duration::seconds(DEFAULT_CLAIM_LEASE_SECONDS);
+        assert_eq!(outbox.due(after_claim, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_message_without_fallback_only_bounces() {
+        let outbox = MemoryOutbox::new();
+        let mut sms = Notification::new(
+            NotificationType::CheckInReminder,
+            NotificationChannel::SMS,
+            "+34666123456".to_string(),
+            "Recuerde".to_string(),
+        );
+        sms.provider_message_id = Some("SM3".to_string());
+        sms.mark_sent();
+        outbox.save(&sms).await.unwrap();
+
+        let report = DeliveryReport {
+            provider_message_id: "SM3".to_string(),
+            status: DeliveryStatus::Failed,
+            error_code: None,
+        };
+        let bounced = service(&outbox)
+            .record_delivery(&report)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(bounced.status, NotificationStatus::Bounced);
+        assert!(outbox.due(Utc::now(), 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_message_is_acknowledged() {
+        let outbox = MemoryOutbox::new();
+        let (body, signature) = status_callback("SM-unknown", "delivered");
+
+        let updated = service(&outbox)
+            .handle_twilio_status_callback(&body, &signature)
+            .await
+            .unwrap();
+        assert!(updated.is_none());
+    }
+}
@@ -4,21 +4,17 @@ mod tests {
 
     #[tokio::test]
     async fn test_notification_service_creation() {
-        let service = NotificationService::new();
         // Basic smoke test to ensure service can be created
-        assert!(true);
+        let _service = NotificationServiceImpl::new();
     }
 
     #[tokio::test]
     async fn test_email_notification() {
-        // Mock test - would need actual SMTP configuration for real testing
-        let service = NotificationService::new();
-        
-        // This would fail without real SMTP config, so we just test the structure
+        let service = NotificationServiceImpl::new();
+
+        // Sending only queues the email in the outbox, so no SMTP server is needed
         let result = service.send_email("test@example.com", "Test Subject", "Test Content").await;
-        
-        // In a real test environment with proper config, this should succeed
-        // For now, we just ensure the method exists and can be called
-        assert!(result.is_err() || result.is_ok());
+
+        assert!(result.is_ok());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,371 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::{Duration, Utc};
+    use notification_service::adapters::outbox::MemoryOutbox;
+    use notification_service::application::{DispatchSummary, NotificationServiceImpl, OutboxWorker};
+    use notification_service::domain::*;
+    use notification_service::ports::*;
+    use shared::{AlbergueError, AlbergueResult};
+    use std::sync::{Arc, Mutex};
+
+    /// Every channel's provider; the channels listed in `down` fail.
+    #[derive(Default)]
+    struct Provider {
+        down: Mutex<Vec<NotificationChannel>>,
+        sent: Mutex<Vec<NotificationChannel>>,
+    }
+
+    impl Provider {
+        fn with_down(channels: &[NotificationChannel]) -> Arc<Self> {
+            let provider = Self::default();
+            *provider.down.lock().unwrap() = channels.to_vec();
+            Arc::new(provider)
+        }
+
+        fn deliver(&self, channel: NotificationChannel) -> AlbergueResult<String> {
+            if self.down.lock().unwrap().contains(&channel) {
+                return Err(AlbergueError::ExternalService {
+                    service: format!("{:?}", channel),
+                    message: "unavailable".to_string(),
+                });
+            }
+            let mut sent = self.sent.lock().unwrap();
+            sent.push(channel);
+            Ok(format!("msg-{}", sent.len()))
+        }
+    }
+
+    #[async_trait]
+    impl EmailPort for Provider {
+        async fn send_email(&self, _notification: &Notification) -> AlbergueResult<String> {
+            self.deliver(NotificationChannel::Email)
+        }
+
+        async fn verify_smtp_connection(&self) -> AlbergueResult<bool> {
+            Ok(true)
+        }
+    }
+
+    #[async_trait]
+    impl SmsPort for Provider {
+        async fn send_sms(&self, _notification: &Notification) -> AlbergueResult<String> {
+            self.deliver(NotificationChannel::SMS)
+        }
+
+        async fn send_whatsapp(&self, _notification: &Notification) -> AlbergueResult<String> {
+            self.deliver(NotificationChannel::WhatsApp)
+        }
+
+        async fn verify_twilio_connection(&self) -> AlbergueResult<bool> {
+            Ok(true)
+        }
+    }
+
+    #[async_trait]
+    impl TelegramPort for Provider {
+        async fn send_telegram(&self, _notification: &Notification) -> AlbergueResult<String> {
+            self.deliver(NotificationChannel::Telegram)
+        }
+
+        async fn verify_bot_connection(&self) -> AlbergueResult<bool> {
+            Ok(true)
+        }
+    }
+
+    fn worker(outbox: &MemoryOutbox, provider: &Arc<Provider>) -> OutboxWorker {
+        OutboxWorker::new(
+            Arc::new(outbox.clone()),
+            provider.clone(),
+            provider.clone(),
+            provider.clone(),
+        )
+    }
+
+    fn notification(channel: NotificationChannel) -> Notification {
+        Notification::new(
+            NotificationType::ReservationCreated,
+            channel,
+            "+34666123456".to_string(),
+            "Reserva confirmada".to_string(),
+        )
+    }
+
+    async fn queued(outbox: &MemoryOutbox, notification: Notification) -> Notification {
+        outbox.save(&notification).await.unwrap();
+        notification
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_maximum() {
+        let policy = RetryPolicy::new(10, Duration::seconds(30), Duration::minutes(5));
+
+        assert_eq!(policy.backoff(1), Duration::seconds(30));
+        assert_eq!(policy.backoff(2), Duration::seconds(60));
+        assert_eq!(policy.backoff(3), Duration::seconds(120));
+        assert_eq!(policy.backoff(5), Duration::minutes(5));
+        assert_eq!(policy.backoff(64), Duration::minutes(5));
+    }
+
+    #[tokio::test]
+    async fn test_due_notifications_are_sent() {
+        let outbox = MemoryOutbox::new();
+        let provider = Provider::with_down(&[]);
+        let queued = queued(&outbox, notification(NotificationChannel::Email)).await;
+
+        let summary = worker(&outbox, &provider).dispatch_due(Utc::now()).await.unwrap();
+
+        assert_eq!(summary.sent, 1);
+        let sent = outbox.get(queued.id).await.unwrap().unwrap();
+        assert_eq!(sent.status, NotificationStatus::Sent);
+        assert_eq!(sent.provider_message_id, Some("msg-1".to_string()));
+        assert_eq!(sent.attempts, 1);
+        assert!(sent.sent_at.is_some());
+        // Nothing is sent twice
+        let summary = worker(&outbox, &provider).dispatch_due(Utc::now()).await.unwrap();
+        assert_eq!(summary, DispatchSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_retried_after_the_backoff() {
+        let outbox = MemoryOutbox::new();
+        let provider = Provider::with_down(&[NotificationChannel::Email]);
+        let worker = worker(&outbox, &provider).with_retry_policy(RetryPolicy::new(
+            3,
+            Duration::seconds(30),
+            Duration::hours(1),
+        ));
+        let queued = queued(&outbox, notification(NotificationChannel::Email)).await;
+        let now = Utc::now();
+
+        assert_eq!(worker.dispatch_due(now).await.unwrap().retrying, 1);
+        let failed = outbox.get(queued.id).await.unwrap().unwrap();
+        assert_eq!(failed.status, NotificationStatus::Failed);
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(failed.next_attempt_at, now + Duration::seconds(30));
+        assert!(failed.error_message.unwrap().contains("unavailable"));
+
+        // Not before its time
+        let early = now + Duration::seconds(10);
+        assert_eq!(worker.dispatch_due(early).await.unwrap(), DispatchSummary::default());
+
+        provider.down.lock().unwrap().clear();
+        let later = now + Duration::seconds(30);
+        assert_eq!(worker.dispatch_due(later).await.unwrap().sent, 1);
+        assert_eq!(outbox.get(queued.id).await.unwrap().unwrap().attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_notification_out_of_attempts_is_dead_lettered() {
+        let outbox = MemoryOutbox::new();
+        let provider = Provider::with_down(&[NotificationChannel::Telegram]);
+        let worker = worker(&outbox, &provider).with_retry_policy(RetryPolicy::new(
+            2,
+            Duration::seconds(30),
+            Duration::hours(1),
+        ));
+        let queued = queued(&outbox, notification(NotificationChannel::Telegram)).await;
+        let now = Utc::now();
+
+        worker.dispatch_due(now).await.unwrap();
+        let summary = worker.dispatch_due(now + Duration::minutes(1)).await.unwrap();
+
+        assert_eq!(summary.dead_lettered, 1);
+        let dead = outbox.dead_letters().await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].id, dead[0].attempts), (queued.id, 2));
+        // Never picked up again on its own
+        let much_later = now + Duration::days(1);
+        assert_eq!(worker.dispatch_due(much_later).await.unwrap(), DispatchSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_each_channel_backs_off_on_its_own_policy() {
+        let outbox = MemoryOutbox::new();
+        let provider = Provider::with_down(&[NotificationChannel::Email, NotificationChannel::SMS]);
+        let worker = worker(&outbox, &provider)
+            .with_retry_policy(RetryPolicy::new(5, Duration::minutes(5), Duration::hours(1)))
+            .with_channel_retry_policy(
+                NotificationChannel::SMS,
+                RetryPolicy::new(1, Duration::seconds(10), Duration::hours(1)),
+            );
+        let email = queued(&outbox, notification(NotificationChannel::Email)).await;
+        let sms = queued(&outbox, notification(NotificationChannel::SMS)).await;
+        let now = Utc::now();
+
+        let summary = worker.dispatch_due(now).await.unwrap();
+
+        assert_eq!((summary.retrying, summary.dead_lettered), (1, 1));
+        let email = outbox.get(email.id).await.unwrap().unwrap();
+        assert_eq!(email.next_attempt_at, now + Duration::minutes(5));
+        let sms = outbox.get(sms.id).await.unwrap().unwrap();
+        assert_eq!(sms.status, NotificationStatus::DeadLetter);
+    }
+
+    #[tokio::test]
+    async fn test_whatsapp_falls_back_to_sms_in_the_same_attempt() {
+        let outbox = MemoryOutbox::new();
+        let provider = Provider::with_down(&[NotificationChannel::WhatsApp]);
+        let first = queued(
+            &outbox,
+            notification(NotificationChannel::WhatsApp).with_fallback(NotificationChannel::SMS),
+        )
+        .await;
+
+        let summary = worker(&outbox, &provider).dispatch_due(Utc::now()).await.unwrap();
+
+        assert_eq!(summary.sent, 1);
+        let sent = outbox.get(first.id).await.unwrap().unwrap();
+        assert_eq!(sent.status, NotificationStatus::Sent);
+        assert_eq!(sent.channel, NotificationChannel::SMS);
+        assert_eq!(*provider.sent.lock().unwrap(), vec![NotificationChannel::SMS]);
+
+        // Both down: one failed attempt naming both
+        provider.down.lock().unwrap().push(NotificationChannel::SMS);
+        let second = queued(
+            &outbox,
+            notification(NotificationChannel::WhatsApp).with_fallback(NotificationChannel::SMS),
+        )
+        .await;
+        worker(&outbox, &provider).dispatch_due(Utc::now()).await.unwrap();
+        let failed = outbox.get(second.id).await.unwrap().unwrap();
+        assert_eq!((failed.channel, failed.attempts), (NotificationChannel::WhatsApp, 1));
+        let error = failed.error_message.unwrap();
+        assert!(error.contains("WhatsApp") && error.contains("SMS"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_due_notifications_are_claimed_until_saved() {
+        let outbox = MemoryOutbox::new();
+        let queued = queued(&outbox, notification(NotificationChannel::Email)).await;
+        let now = Utc::now();
+
+        let claimed = outbox.due(now, 10).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert!(outbox.due(now, 10).await.unwrap().is_empty());
+
+        // Saving releases the claim
+        assert!(outbox.save(&claimed[0]).await.unwrap());
+        assert_eq!(outbox.due(now, 10).await.unwrap()[0].id, queued.id);
+    }
+
+    #[tokio::test]
+    async fn test_save_without_the_claim_is_skipped_while_it_is_live() {
+        let outbox = MemoryOutbox::new();
+        let queued = queued(&outbox, notification(NotificationChannel::SMS)).await;
+        let now = Utc::now();
+        let mut claimed = outbox.due(now, 10).await.unwrap().remove(0);
+
+        // A copy read meanwhile, as a delivery report handler would
+        let mut read = outbox.get(queued.id).await.unwrap().unwrap();
+        read.error_message = Some("late report".to_string());
+        assert!(!outbox.save(&read).await.unwrap());
+        assert!(outbox.due(now, 10).await.unwrap().is_empty());
+
+        claimed.mark_sent();
+        assert!(outbox.save(&claimed).await.unwrap());
+        let stored = outbox.get(queued.id).await.unwrap().unwrap();
+        assert_eq!((stored.status, stored.error_message), (NotificationStatus::Sent, None));
+    }
+
+    #[tokio::test]
+    async fn test_stale_save_does_not_release_a_taken_over_claim() {
+        let outbox = MemoryOutbox::new().with_lease(Duration::seconds(60));
+        queued(&outbox, notification(NotificationChannel::Email)).await;
+        let now = Utc::now();
+
+        // The first worker stalls past its lease and a second takes over
+        let later = now + Duration::seconds(61);
+        let mut stale = outbox.due(now, 10).await.unwrap().remove(0);
+        let mut current = outbox.due(later, 10).await.unwrap().remove(0);
+
+        stale.mark_sent();
+        assert!(!outbox.save(&stale).await.unwrap());
+        assert!(outbox.due(later, 10).await.unwrap().is_empty());
+        assert_eq!(outbox.get(current.id).await.unwrap().unwrap().status, NotificationStatus::Pending);
+
+        current.mark_sent();
+        assert!(outbox.save(&current).await.unwrap());
+        assert_eq!(outbox.get(current.id).await.unwrap().unwrap().status, NotificationStatus::Sent);
+    }
+
+    #[tokio::test]
+    async fn test_service_queues_instead_of_sending() {
+        let outbox = MemoryOutbox::new();
+        let service = NotificationServiceImpl::new().with_outbox(Arc::new(outbox.clone()));
+
+        let id = service.send_sms("+34666123456", "Hola").await.unwrap();
+
+        let queued = outbox.get(id.parse().unwrap()).await.unwrap().unwrap();
+        assert_eq!(queued.status, NotificationStatus::Pending);
+        assert_eq!(queued.channel, NotificationChannel::SMS);
+        assert!(queued.is_due(Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_admin_requeues_dead_letters() {
+        let outbox = MemoryOutbox::new();
+        let service = NotificationServiceImpl::new().with_outbox(Arc::new(outbox.clone()));
+        let mut dead = notification(NotificationChannel::Email);
+        dead.mark_attempt_failed(
+            "SMTP down".to_string(),
+            &RetryPolicy::new(1, Duration::seconds(30), Duration::hours(1)),
+            Utc::now(),
+        );
+        let dead = queued(&outbox, dead).await;
+        let pending = queued(&outbox, notification(NotificationChannel::Email)).await;
+
+        let listed = service.dead_letters().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].error_message, Some("SMTP down".to_string()));
+
+        let requeued = service.requeue(dead.id).await.unwrap();
+        assert_eq!((requeued.status, requeued.attempts), (NotificationStatus::Pending, 0));
+        assert!(service.dead_letters().await.unwrap().is_empty());
+        assert_eq!(outbox.due(Utc::now(), 10).await.unwrap().len(), 2);
+
+        assert!(matches!(
+            service.requeue(pending.id).await,
+            Err(AlbergueError::Validation { .. })
+        ));
+        assert!(matches!(
+            service.requeue(uuid::Uuid::new_v4()).await,
+            Err(AlbergueError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_outbox_columns_read_back_as_written() {
+        use notification_service::adapters::outbox::postgres::*;
+
+        // As the notifications check constraints spell them
+        let channels = [
+            NotificationChannel::Email,
+            NotificationChannel::SMS,
+            NotificationChannel::WhatsApp,
+            NotificationChannel::Telegram,
+        ];
+        let names: Vec<&str> = channels.iter().map(|c| channel_name(*c)).collect();
+        assert_eq!(names, ["email", "sms", "whatsapp", "telegram"]);
+        for channel in channels {
+            assert_eq!(parse_channel(channel_name(channel)).unwrap(), channel);
+        }
+        for status in [
+            NotificationStatus::Pending,
+            NotificationStatus::Sent,
+            NotificationStatus::Delivered,
+            NotificationStatus::Failed,
+            NotificationStatus::Bounced,
+            NotificationStatus::DeadLetter,
+        ] {
+            assert_eq!(parse_status(status_name(status)).unwrap(), status);
+        }
+        assert_eq!(status_name(NotificationStatus::DeadLetter), "dead_letter");
+        assert_eq!(type_name(&NotificationType::CheckInReminder), "check_in_reminder");
+        assert_eq!(
+            parse_type("reservation_created").unwrap(),
+            NotificationType::ReservationCreated
+        );
+        assert!(matches!(parse_status("queued"), Err(AlbergueError::Database { .. })));
+    }
+}
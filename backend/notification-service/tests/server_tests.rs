@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use notification_service::adapters::outbox::MemoryOutbox;
+    use notification_service::adapters::sms::{TwilioWebhook, SIGNATURE_HEADER};
+    use notification_service::application::NotificationServiceImpl;
+    use notification_service::domain::*;
+    use notification_service::infrastructure::server::router;
+    use notification_service::ports::*;
+    use shared::health::{DependencyStatus, HealthReport};
+    use shared::telemetry::REQUEST_ID_HEADER;
+    use shared::{AlbergueError, AlbergueResult};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    /// Every channel's provider; only its connection checks are exercised.
+    struct Provider {
+        up: bool,
+    }
+
+    impl Provider {
+        fn check(&self) -> AlbergueResult<bool> {
+            if self.up {
+                Ok(true)
+            } else {
+                Err(AlbergueError::ExternalService {
+                    service: "provider".to_string(),
+                    message: "unreachable".to_string(),
+                })
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EmailPort for Provider {
+        async fn send_email(&self, _notification: &Notification) -> AlbergueResult<String> {
+            unimplemented!()
+        }
+
+        async fn verify_smtp_connection(&self) -> AlbergueResult<bool> {
+            self.check()
+        }
+    }
+
+    #[async_trait]
+    impl SmsPort for Provider {
+        async fn send_sms(&self, _notification: &Notification) -> AlbergueResult<String> {
+            unimplemented!()
+        }
+
+        async fn send_whatsapp(&self, _notification: &Notification) -> AlbergueResult<String> {
+            unimplemented!()
+        }
+
+        async fn verify_twilio_connection(&self) -> AlbergueResult<bool> {
+            self.check()
+        }
+    }
+
+    #[async_trait]
+    impl TelegramPort for Provider {
+        async fn send_telegram(&self, _notification: &Notification) -> AlbergueResult<String> {
+            unimplemented!()
+        }
+
+        async fn verify_bot_connection(&self) -> AlbergueResult<bool> {
+            self.check()
+        }
+    }
+
+    fn service(up: bool) -> Arc<NotificationServiceImpl> {
+        let provider = Arc::new(Provider { up });
+        Arc::new(NotificationServiceImpl::new().with_adapters(
+            provider.clone(),
+            provider.clone(),
+            provider,
+        ))
+    }
+
+    async fn get_health(service: Arc<NotificationServiceImpl>) -> (StatusCode, HealthReport) {
+        let response = router(service)
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_every_channel() {
+        let (status, report) = get_health(service(true)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report.status, DependencyStatus::Up);
+        let names: Vec<_> = report.dependencies.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["smtp", "twilio", "telegram"]);
+    }
+
+    #[tokio::test]
+    async fn test_health_degrades_when_channels_are_down() {
+        let (status, report) = get_health(service(false)).await;
+
+        // No channel is critical on its own, so the service keeps taking traffic
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report.status, DependencyStatus::Degraded);
+        assert!(report
+            .dependencies
+            .iter()
+            .all(|d| d.status == DependencyStatus::Down));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_kept_or_minted() {
+        let request = Request::get("/health")
+            .header(REQUEST_ID_HEADER, "req-42")
+            .body(Body::empty())
+            .unwrap();
+        let response = router(service(true)).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-42");
+
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+        let response = router(service(true)).oneshot(request).await.unwrap();
+        let minted = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(minted).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_twilio_status_callback_updates_the_outbox() {
+        let twilio = TwilioWebhook::new(
+            "test-auth-token",
+            "https://albergue.example/api/notifications/twilio/status",
+        );
+        let outbox = MemoryOutbox::new();
+        let mut sent = Notification::new(
+            NotificationType::ReservationCreated,
+            NotificationChannel::SMS,
+            "+34600000000".to_string(),
+            "Reserva confirmada".to_string(),
+        );
+        sent.provider_message_id = Some("SM1".to_string());
+        sent.mark_sent();
+        outbox.save(&sent).await.unwrap();
+        let service = Arc::new(
+            NotificationServiceImpl::new()
+                .with_outbox(Arc::new(outbox.clone()))
+                .with_twilio_webhook(TwilioWebhook::new(
+                    "test-auth-token",
+                    "https://albergue.example/api/notifications/twilio/status",
+                )),
+        );
+        let params = vec![
+            ("MessageSid".to_string(), "SM1".to_string()),
+            ("MessageStatus".to_string(), "delivered".to_string()),
+        ];
+        let body = "MessageSid=SM1&MessageStatus=delivered";
+        let callback = |signature: &str| {
+            Request::post("/twilio/status")
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .header(SIGNATURE_HEADER, signature)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = router(service.clone())
+            .oneshot(callback("forged"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let unchanged = outbox.get(sent.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.status, NotificationStatus::Sent);
+
+        let response = router(service)
+            .oneshot(callback(&twilio.sign(&params)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let delivered = outbox.get(sent.id).await.unwrap().unwrap();
+        assert_eq!(delivered.status, NotificationStatus::Delivered);
+    }
+}
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
     use notification_service::domain::*;
-    use uuid::Uuid;
 
     #[test]
     fn test_notification_creation() {
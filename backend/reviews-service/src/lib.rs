@@ -1,9 +1,10 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use spin_sdk::{
-    http::{IntoResponse, Request, Response},
-    http_component,
+    http::{IntoResponse, Method, Request, Response},
+    variables,
 };
+use shared::security_headers::SecurityPolicy;
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -32,21 +33,44 @@ pub struct ErrorResponse {
     pub message: String,
 }
 
-#[http_component]
-async fn handle_request(req: Request) -> Result<impl IntoResponse> {
-    let uri = req.uri();
-    let path = uri.path();
+#[cfg_attr(target_arch = "wasm32", spin_sdk::http_component)]
+pub async fn handle_request(req: Request) -> Result<impl IntoResponse> {
+    let path = req.path().to_string();
+    let origin = req.header("Origin").and_then(|v| v.as_str()).map(str::to_string);
 
-    // Enable CORS
-    let mut response_builder = Response::builder()
-        .header("Access-Control-Allow-Origin", "*")
-        .header("Access-Control-Allow-Methods", "GET, OPTIONS")
-        .header("Access-Control-Allow-Headers", "Content-Type, Authorization");
+    let allowed_origins = variables::get("cors_allowed_origins").unwrap_or_default();
+    let admin_origin = variables::get("cors_admin_origin").unwrap_or_default();
+    let policy = SecurityPolicy::from_settings(&allowed_origins, &admin_origin).with_methods("GET, OPTIONS");
 
-    if req.method().as_str() == "OPTIONS" {
-        return Ok(response_builder.status(200).body(()).build());
+    let mut response = if *req.method() == Method::Options {
+        match origin.as_deref() {
+            Some(o) if policy.is_origin_allowed(o, &path) => {
+                let mut preflight = Response::builder().status(204).body(()).build();
+                for (name, value) in policy.preflight_headers() {
+                    preflight.set_header(name, value);
+                }
+                preflight
+            }
+            _ => Response::builder()
+                .status(403)
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_string(&ErrorResponse {
+                    error: "Forbidden".to_string(),
+                    message: "Origin not allowed".to_string(),
+                })?)
+                .build(),
+        }
+    } else {
+        route(&path).await?
+    };
+
+    for (name, value) in policy.response_headers(origin.as_deref(), &path) {
+        response.set_header(name, value);
     }
+    Ok(response)
+}
 
+async fn route(path: &str) -> Result<Response> {
     match path {
         "/reviews/google" => handle_google_reviews().await,
         "/reviews/booking" => handle_booking_reviews().await,
@@ -63,7 +87,7 @@ async fn handle_request(req: Request) -> Result<impl IntoResponse> {
     }
 }
 
-async fn handle_google_reviews() -> Result<impl IntoResponse> {
+async fn handle_google_reviews() -> Result<Response> {
     let google_reviews = vec![
         Review {
             id: "google_1".to_string(),
@@ -111,7 +135,7 @@ async fn handle_google_reviews() -> Result<impl IntoResponse> {
         .build())
 }
 
-async fn handle_booking_reviews() -> Result<impl IntoResponse> {
+async fn handle_booking_reviews() -> Result<Response> {
     let booking_reviews = vec![
         Review {
             id: "booking_1".to_string(),
@@ -159,7 +183,7 @@ async fn handle_booking_reviews() -> Result<impl IntoResponse> {
         .build())
 }
 
-async fn handle_all_reviews() -> Result<impl IntoResponse> {
+async fn handle_all_reviews() -> Result<Response> {
     // Combine Google and Booking.com reviews
     let mut all_reviews = Vec::new();
     
@@ -251,7 +275,7 @@ async fn handle_all_reviews() -> Result<impl IntoResponse> {
         .build())
 }
 
-async fn handle_review_stats() -> Result<impl IntoResponse> {
+async fn handle_review_stats() -> Result<Response> {
     let stats = serde_json::json!({
         "total_reviews": 6,
         "average_rating": 4.7,
@@ -54,6 +54,8 @@ impl DatabaseConfig {
 }
 
 // Common database operations that work with both PostgreSQL and SQLite
+// Spin components poll these on a single thread, so no Send bound is needed
+#[allow(async_fn_in_trait)]
 pub trait DatabaseOperations {
     async fn health_check(&self) -> Result<bool, crate::AlbergueError>;
     async fn get_connection_info(&self) -> HashMap<String, String>;
@@ -67,12 +69,16 @@ pub fn generate_id() -> String {
 // JSON handling for cross-database compatibility  
 pub fn serialize_json<T: Serialize>(data: &T) -> Result<String, crate::AlbergueError> {
     serde_json::to_string(data)
-        .map_err(|e| crate::AlbergueError::DatabaseError(format!("JSON serialization failed: {}", e)))
+        .map_err(|e| crate::AlbergueError::Database {
+            message: format!("JSON serialization failed: {}", e),
+        })
 }
 
 pub fn deserialize_json<T: for<'de> Deserialize<'de>>(json_str: &str) -> Result<T, crate::AlbergueError> {
     serde_json::from_str(json_str)
-        .map_err(|e| crate::AlbergueError::DatabaseError(format!("JSON deserialization failed: {}", e)))
+        .map_err(|e| crate::AlbergueError::Database {
+            message: format!("JSON deserialization failed: {}", e),
+        })
 }
 
 // Date handling for cross-database compatibility
@@ -86,7 +92,9 @@ pub fn format_datetime(datetime: &chrono::DateTime<chrono::Utc>) -> String {
 
 pub fn parse_date(date_str: &str) -> Result<chrono::NaiveDate, crate::AlbergueError> {
     chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-        .map_err(|e| crate::AlbergueError::ValidationError(format!("Invalid date format: {}", e)))
+        .map_err(|e| crate::AlbergueError::Validation {
+            message: format!("Invalid date format: {}", e),
+        })
 }
 
 pub fn parse_datetime(datetime_str: &str) -> Result<chrono::DateTime<chrono::Utc>, crate::AlbergueError> {
@@ -96,5 +104,7 @@ pub fn parse_datetime(datetime_str: &str) -> Result<chrono::DateTime<chrono::Utc
             chrono::NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%d %H:%M:%S")
                 .map(|ndt| chrono::DateTime::from_naive_utc_and_offset(ndt, chrono::Utc))
         })
-        .map_err(|e| crate::AlbergueError::ValidationError(format!("Invalid datetime format: {}", e)))
+        .map_err(|e| crate::AlbergueError::Validation {
+            message: format!("Invalid datetime format: {}", e),
+        })
 }
\ No newline at end of file
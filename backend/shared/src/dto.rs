@@ -1,8 +1,10 @@
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct BookingDto {
     pub id: Uuid,
     pub guest_name: String,
@@ -14,14 +16,14 @@ pub struct BookingDto {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub enum BedType {
     DormA,
     DormB,
     Private,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub enum BookingStatus {
     Reserved,
     Confirmed,
@@ -30,29 +32,361 @@ pub enum BookingStatus {
     Cancelled,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
 pub struct ValidationRequest {
-    pub document_type: DocumentType,
+    /// What the pilgrim says the document is; detected from the images when
+    /// left out
+    #[serde(default)]
+    pub document_type: Option<DocumentType>,
     pub front_image: String, // base64
     pub back_image: Option<String>, // base64
+    /// Files read off the document's chip, when the tablet has NFC
+    #[serde(default)]
+    pub chip_data: Option<ChipDump>,
+    /// The pilgrim's `data_retention_until`; the ID photo is kept, encrypted,
+    /// until then, and not at all without it
+    #[serde(default)]
+    pub data_retention_until: Option<DateTime<Utc>>,
+    /// What the pilgrim typed into the registration form, to compare with
+    /// what the document says
+    #[serde(default)]
+    pub entered_data: Option<EnteredData>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The registration form's identity fields, named as the `pilgrims` table
+/// names them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct EnteredData {
+    #[serde(default)]
+    pub first_name: Option<String>,
+    #[serde(default)]
+    pub last_name_1: Option<String>,
+    #[serde(default)]
+    pub last_name_2: Option<String>,
+    #[serde(default)]
+    pub document_number: Option<String>,
+    #[serde(default)]
+    pub birth_date: Option<DateTime<Utc>>,
+    /// ICAO code, as the document gives it
+    #[serde(default)]
+    pub nationality: Option<String>,
+}
+
+/// Raw LDS files (ICAO 9303 part 10) from a DNIe or ePassport chip, each
+/// base64 encoded exactly as read.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
+pub struct ChipDump {
+    #[serde(default)]
+    pub ef_com: Option<String>,
+    /// The MRZ
+    pub dg1: String,
+    /// The face image
+    #[serde(default)]
+    pub dg2: Option<String>,
+    /// Full names, personal number, birthplace and address
+    #[serde(default)]
+    pub dg11: Option<String>,
+    /// The security object listing the data group hashes
+    #[serde(default)]
+    pub sod: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
 pub enum DocumentType {
     DNI,
+    /// Paper NIE certificates, such as the EU citizen registration certificate
     NIE,
+    /// Foreigner identity card (Tarjeta de Identidad de Extranjero)
+    TIE,
     Passport,
+    /// National identity card of an EU/EEA state or Switzerland, read through
+    /// its TD1 MRZ
+    EuIdCard,
+    /// Accepted only when nothing else is at hand
+    DrivingLicence,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl DocumentType {
+    pub const ALL: [DocumentType; 6] = [
+        DocumentType::DNI,
+        DocumentType::NIE,
+        DocumentType::TIE,
+        DocumentType::Passport,
+        DocumentType::EuIdCard,
+        DocumentType::DrivingLicence,
+    ];
+
+    /// `tipoDocumento` code of the ministry's guest register. A TIE is filed
+    /// under the NIE it carries.
+    pub fn ministry_code(self) -> &'static str {
+        match self {
+            DocumentType::DNI => "NIF",
+            DocumentType::NIE | DocumentType::TIE => "NIE",
+            DocumentType::Passport => "PAS",
+            DocumentType::EuIdCard | DocumentType::DrivingLicence => "OTRO",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct ValidationResponse {
     pub is_valid: bool,
     pub extracted_data: ExtractedData,
     pub confidence_score: f32,
     pub errors: Vec<String>,
+    /// Fields where the MRZ and the visual zone disagree; the MRZ value wins.
+    #[serde(default)]
+    pub conflicts: Vec<FieldConflict>,
+    /// What the document looked like, when it could be told from the images.
+    #[serde(default)]
+    pub classification: Option<DocumentClassification>,
+    /// Findings that do not make the document invalid but need staff attention
+    #[serde(default)]
+    pub warnings: Vec<ValidationWarning>,
+    /// 0 to 1 for each `ExtractedData` field that was read, by field name
+    #[serde(default)]
+    pub field_confidence: BTreeMap<String, f32>,
+    /// Fields read with too little confidence, or key fields not read at all,
+    /// for the pilgrim to double-check
+    #[serde(default)]
+    pub fields_to_review: Vec<String>,
+    /// Set when a key field is among `fields_to_review`
+    #[serde(default)]
+    pub requires_manual_review: bool,
+    /// Barcodes decoded from the images that carried identity fields
+    #[serde(default)]
+    pub barcodes: Vec<BarcodeReading>,
+    /// What the chip dump held, when one was sent
+    #[serde(default)]
+    pub chip: Option<ChipReading>,
+    /// Where the encrypted ID photo was stored, for the pilgrim's `id_photo_url`
+    #[serde(default)]
+    pub id_photo_url: Option<String>,
+    /// Signs the images are a recapture or were edited; advisory only
+    #[serde(default)]
+    pub forgery_risk: ForgeryRisk,
+    /// Each `entered_data` field against the document, when it was sent
+    #[serde(default)]
+    pub field_matches: Vec<FieldMatch>,
+    /// Set when the reading was too unsure to rely on and was queued for a
+    /// hospitalero to review; the registration can wait on the review
+    /// instead of failing
+    #[serde(default)]
+    pub review_id: Option<Uuid>,
+}
+
+/// A validation held for a hospitalero because it was read with too little
+/// confidence.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct DocumentReview {
+    pub id: Uuid,
+    pub status: ReviewStatus,
+    pub document_type: DocumentType,
+    /// As read, before any `corrections`
+    pub extracted_data: ExtractedData,
+    pub field_confidence: BTreeMap<String, f32>,
+    pub fields_to_review: Vec<String>,
+    pub confidence_score: f32,
+    pub errors: Vec<String>,
+    /// The OCR text of each side, front first
+    pub ocr_text: Vec<String>,
+    /// The sealed upload, when image storage is configured
+    pub front_image_url: Option<String>,
+    pub back_image_url: Option<String>,
+    /// By `ExtractedData` field name, dates as `YYYY-MM-DD`
+    pub corrections: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    /// The reviewer's JWT sub claim
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ReviewStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Field corrections, sent alone or with an approval.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
+pub struct ReviewCorrectionRequest {
+    /// By `ExtractedData` field name, dates as `YYYY-MM-DD`; an empty value
+    /// clears the field
+    #[serde(default)]
+    pub corrections: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ReviewRejectionRequest {
+    pub reason: String,
+}
+
+/// One typed field against the document's reading of it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldMatch {
+    /// The `EnteredData` field
+    pub field: String,
+    pub entered: Option<String>,
+    /// The document's value, split the way `field` is
+    pub read: Option<String>,
+    /// 0 to 1, once accents, case and MRZ transliteration are set aside
+    pub similarity: f32,
+    pub verdict: MatchVerdict,
+    /// The value to store: the document's, with the accents the pilgrim
+    /// typed when the two agree
+    pub canonical: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum MatchVerdict {
+    /// Identical but for case and spacing
+    Same,
+    /// The same but for accents, hyphens or MRZ transliteration
+    Equivalent,
+    /// Close enough to be a typo or a misread
+    Similar,
+    /// Given names and surnames typed the other way round
+    Swapped,
+    Different,
+    /// Only one side has a value
+    Missing,
+}
+
+/// How likely the images are not an honest capture of the document itself.
+/// For the hospitalero to weigh: it never makes a document invalid.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct ForgeryRisk {
+    /// 0 to 1, from all reasons together
+    pub score: f32,
+    /// Strongest first
+    pub reasons: Vec<ForgeryReason>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ForgeryReason {
+    pub signal: ForgerySignal,
+    /// `front` or `back`, for signals read off one image
+    pub image: Option<String>,
+    /// 0 to 1, how clearly the signal showed
+    pub strength: f32,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ForgerySignal {
+    /// Interference stripes from photographing a screen or a print
+    Moire,
+    /// The regular subpixel grid of a display
+    ScreenGrid,
+    /// Metadata that contradicts a straight camera capture
+    ExifInconsistency,
+    /// Part of the image copied onto another part
+    DuplicatedRegion,
+    /// MRZ characters not in OCR-B, or off its fixed pitch
+    MrzFontMismatch,
+    /// The data of a published specimen document
+    SpecimenDocument,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ChipReading {
+    /// Data groups the chip holds
+    pub data_groups: Vec<u8>,
+    /// Data groups whose hash matches a security object signed under a
+    /// trusted CSCA; empty otherwise
+    pub verified_groups: Vec<u8>,
+    /// The `ExtractedData` fields it supplied, which override the OCR; empty
+    /// unless it was verified
+    pub fields: Vec<String>,
+    /// The DG2 face; the image itself is not returned
+    pub face_image: Option<FaceImageInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum FaceImageFormat {
+    Jpeg,
+    Jpeg2000,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct FaceImageInfo {
+    pub format: FaceImageFormat,
+    pub width: u16,
+    pub height: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum BarcodeSymbology {
+    Qr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BarcodeReading {
+    pub symbology: BarcodeSymbology,
+    /// The `ExtractedData` fields it supplied, which override the OCR
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ValidationWarningCode {
+    /// An expired DNI still inside the renewal grace period
+    ExpiredUnderRenewal,
+    /// No expiry date could be read, so expiry was not checked
+    ExpiryUnknown,
+    /// The holder is under 18 and must be accompanied by a responsible adult
+    MinorMustBeAccompanied,
+    /// The MRZ and the visual zone disagree; the MRZ value was kept
+    MrzMismatch,
+    /// A barcode and the printed text disagree; the barcode value was kept
+    BarcodeMismatch,
+    /// The chip and the printed text disagree; the chip value was kept when
+    /// the chip was verified, the printed one otherwise
+    ChipMismatch,
+    /// The chip's security object was missing or its signature could not be
+    /// traced to a trusted CSCA, so the chip did not override the print
+    ChipUnverified,
+    /// The ID photo could not be found or stored; the document itself is fine
+    PortraitNotStored,
+    /// The images look recaptured or edited; see `forgery_risk`
+    PossibleForgery,
+    /// What the pilgrim typed differs from the document; see `field_matches`
+    EnteredDataMismatch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ValidationWarning {
+    pub code: ValidationWarningCode,
+    /// The `ExtractedData` field concerned, if any
+    pub field: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DocumentClassification {
+    pub detected_type: DocumentType,
+    /// 0 to 1; an MRZ document code is near certain, keywords and layout less so
+    pub confidence: f32,
+    /// ICAO code of the issuing state, when read from the MRZ
+    pub issuing_country: Option<String>,
+    /// The signals the classification rests on
+    pub evidence: Vec<String>,
+    pub requested_type: Option<DocumentType>,
+    /// Validated as `detected_type` rather than `requested_type`
+    pub overridden: bool,
+    /// Set when the detected and requested types disagree
+    pub warning: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldConflict {
+    pub field: String,
+    pub mrz_value: String,
+    pub visual_value: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
 pub struct ExtractedData {
     pub document_number: Option<String>,
     pub name: Option<String>,
@@ -60,6 +394,26 @@ pub struct ExtractedData {
     pub birth_date: Option<DateTime<Utc>>,
     pub nationality: Option<String>,
     pub expiry_date: Option<DateTime<Utc>>,
+    /// Printed in the visual zone only; MRZs do not carry it
+    #[serde(default)]
+    pub issue_date: Option<DateTime<Utc>>,
+    /// ICAO 9303 sex code: `M`, `F` or `X`
+    #[serde(default)]
+    pub sex: Option<String>,
+    /// Street line of the domicile printed on the back of a DNI
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub municipality: Option<String>,
+    #[serde(default)]
+    pub province: Option<String>,
+    /// Municipality and province of birth, as printed
+    #[serde(default)]
+    pub birthplace: Option<String>,
+    /// Number of the physical card (`soporte`): the IDESP of a DNI, the card
+    /// number of a TIE
+    #[serde(default)]
+    pub support_number: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -31,4 +31,11 @@ pub enum AlbergueError {
     Internal { message: String },
 }
 
-pub type AlbergueResult<T> = Result<T, AlbergueError>;
\ No newline at end of file
+pub type AlbergueResult<T> = Result<T, AlbergueError>;
+impl From<serde_json::Error> for AlbergueError {
+    fn from(err: serde_json::Error) -> Self {
+        AlbergueError::Internal {
+            message: format!("JSON serialization failed: {}", err),
+        }
+    }
+}
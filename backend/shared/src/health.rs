@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use utoipa::ToSchema;
+
+use crate::error::AlbergueResult;
+
+/// Probes slower than this are reported as degraded even when they succeed.
+pub const SLOW_PROBE_THRESHOLD_MS: u64 = 2_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub status: DependencyStatus,
+    pub latency_ms: u64,
+    /// A critical dependency being down takes the whole service down;
+    /// anything else only puts it in degraded mode.
+    pub critical: bool,
+    pub message: Option<String>,
+}
+
+impl DependencyHealth {
+    pub fn new(name: impl Into<String>, status: DependencyStatus, latency_ms: u64) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            latency_ms,
+            critical: false,
+            message: None,
+        }
+    }
+
+    pub fn critical(mut self) -> Self {
+        self.critical = true;
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Maps the outcome of a `verify_*`/`health_check` style call to a status.
+    pub fn from_check(name: impl Into<String>, latency_ms: u64, result: AlbergueResult<bool>) -> Self {
+        match result {
+            Ok(true) if latency_ms > SLOW_PROBE_THRESHOLD_MS => {
+                Self::new(name, DependencyStatus::Degraded, latency_ms)
+                    .with_message(format!("slow response ({} ms)", latency_ms))
+            }
+            Ok(true) => Self::new(name, DependencyStatus::Up, latency_ms),
+            Ok(false) => Self::new(name, DependencyStatus::Down, latency_ms)
+                .with_message("connection check failed"),
+            Err(e) => Self::new(name, DependencyStatus::Down, latency_ms).with_message(e.to_string()),
+        }
+    }
+}
+
+/// Runs a check and records how long it took.
+pub async fn probe<F>(name: &str, check: F) -> DependencyHealth
+where
+    F: Future<Output = AlbergueResult<bool>>,
+{
+    let started = chrono::Utc::now();
+    let result = check.await;
+    let latency_ms = (chrono::Utc::now() - started).num_milliseconds().max(0) as u64;
+
+    DependencyHealth::from_check(name, latency_ms, result)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct HealthReport {
+    pub status: DependencyStatus,
+    pub service: String,
+    pub version: String,
+    pub timestamp: String,
+    pub degraded_mode: bool,
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+impl HealthReport {
+    pub fn from_dependencies(
+        service: impl Into<String>,
+        version: impl Into<String>,
+        dependencies: Vec<DependencyHealth>,
+    ) -> Self {
+        let status = Self::aggregate(&dependencies);
+
+        Self {
+            status,
+            service: service.into(),
+            version: version.into(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            degraded_mode: status == DependencyStatus::Degraded,
+            dependencies,
+        }
+    }
+
+    fn aggregate(dependencies: &[DependencyHealth]) -> DependencyStatus {
+        let critical_down = dependencies
+            .iter()
+            .any(|d| d.critical && d.status == DependencyStatus::Down);
+        let any_impaired = dependencies
+            .iter()
+            .any(|d| d.status != DependencyStatus::Up);
+
+        if critical_down {
+            DependencyStatus::Down
+        } else if any_impaired {
+            DependencyStatus::Degraded
+        } else {
+            DependencyStatus::Up
+        }
+    }
+
+    /// Ready to take traffic unless a critical dependency is down.
+    pub fn is_ready(&self) -> bool {
+        self.status != DependencyStatus::Down
+    }
+
+    /// Folds another component's report into this one, prefixing its
+    /// dependency names with the component name.
+    pub fn nested(component: &str, report: HealthReport, latency_ms: u64, critical: bool) -> Vec<DependencyHealth> {
+        let mut component_health = DependencyHealth::new(component, report.status, latency_ms);
+        component_health.critical = critical;
+
+        let mut dependencies = vec![component_health];
+        dependencies.extend(report.dependencies.into_iter().map(|mut d| {
+            d.name = format!("{}.{}", component, d.name);
+            // Only the component itself decides whether the gateway is ready
+            d.critical = false;
+            d
+        }));
+        dependencies
+    }
+}
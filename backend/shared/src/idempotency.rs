@@ -0,0 +1,190 @@
+// Idempotency-Key handling for mutating routes
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AlbergueResult;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+pub const DEFAULT_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+/// How long an unfinished request holds its key. A duplicate arriving later
+/// takes the key over, so a request that died mid-flight does not block it
+/// for the whole window.
+pub const IN_FLIGHT_LEASE_SECONDS: i64 = 60;
+
+const MAX_KEY_LEN: usize = 255;
+
+/// Route prefixes whose POSTs honour `Idempotency-Key`.
+pub const IDEMPOTENT_PATH_PREFIXES: &[&str] = &["/api/booking/", "/api/payments/", "/api/notifications/"];
+
+pub fn applies_to(method: &str, path: &str) -> bool {
+    method.eq_ignore_ascii_case("POST") && IDEMPOTENT_PATH_PREFIXES.iter().any(|p| path.starts_with(p))
+}
+
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LEN && key.chars().all(|c| c.is_ascii_graphic())
+}
+
+/// Identifies the request a key was first used with.
+pub fn fingerprint(method: &str, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.to_ascii_uppercase().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IdempotencyRecord {
+    pub fingerprint: String,
+    pub created_at: i64,
+    /// `None` while the first request is still being handled.
+    pub response: Option<StoredResponse>,
+}
+
+/// Backing storage for idempotency records, e.g. the database. Claiming a
+/// key relies on `insert_if_absent` and `replace` being atomic, such as an
+/// insert against a primary key and a conditional update.
+pub trait IdempotencyStore {
+    fn get(&self, key: &str) -> AlbergueResult<Option<IdempotencyRecord>>;
+    /// Stores `record` unless `key` already has one; returns whether it did.
+    fn insert_if_absent(&self, key: &str, record: &IdempotencyRecord) -> AlbergueResult<bool>;
+    /// Swaps `current` for `new` unless the record under `key` changed since
+    /// it was read; returns whether it did.
+    fn replace(&self, key: &str, current: &IdempotencyRecord, new: &IdempotencyRecord) -> AlbergueResult<bool>;
+    /// Removes `current` unless the record under `key` changed since it was
+    /// read; returns whether it did.
+    fn delete(&self, key: &str, current: &IdempotencyRecord) -> AlbergueResult<bool>;
+}
+
+/// The hold a request has on its key, as left in the store by `begin` or
+/// the last `renew`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claim {
+    fingerprint: String,
+    claimed_at: i64,
+}
+
+impl Claim {
+    fn record(&self) -> IdempotencyRecord {
+        IdempotencyRecord {
+            fingerprint: self.fingerprint.clone(),
+            created_at: self.claimed_at,
+            response: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    /// First use of the key: handle the request, renewing the claim while it
+    /// runs, then call `complete`.
+    Proceed(Claim),
+    /// Same request seen before: send the stored response again.
+    Replay(StoredResponse),
+    /// Key reused with a different payload (422).
+    Conflict,
+    /// The original request has not finished yet (409).
+    InFlight,
+}
+
+pub struct Idempotency<S: IdempotencyStore> {
+    store: S,
+    window_seconds: i64,
+}
+
+impl<S: IdempotencyStore> Idempotency<S> {
+    pub fn new(store: S, window_seconds: i64) -> Self {
+        Self { store, window_seconds }
+    }
+
+    fn storage_key(key: &str) -> String {
+        format!("idempotency:{}", key)
+    }
+
+    fn is_expired(&self, record: &IdempotencyRecord, now: i64) -> bool {
+        now - record.created_at >= self.window_seconds
+    }
+
+    fn is_abandoned(record: &IdempotencyRecord, now: i64) -> bool {
+        record.response.is_none() && now - record.created_at >= IN_FLIGHT_LEASE_SECONDS
+    }
+
+    /// Claims the key for this request unless it is held or answered. Of
+    /// several requests racing for a key, exactly one gets `Proceed`.
+    pub fn begin(&self, key: &str, fingerprint: &str, now: i64) -> AlbergueResult<Decision> {
+        let storage_key = Self::storage_key(key);
+        let claim = Claim {
+            fingerprint: fingerprint.to_string(),
+            claimed_at: now,
+        };
+        let claimed = |won: bool| {
+            if won {
+                Decision::Proceed(claim.clone())
+            } else {
+                Decision::InFlight
+            }
+        };
+
+        if self.store.insert_if_absent(&storage_key, &claim.record())? {
+            return Ok(claimed(true));
+        }
+        let Some(record) = self.store.get(&storage_key)? else {
+            // Freed by a failed request in the meantime
+            return Ok(claimed(self.store.insert_if_absent(&storage_key, &claim.record())?));
+        };
+
+        if self.is_expired(&record, now) || Self::is_abandoned(&record, now) {
+            return Ok(claimed(self.store.replace(&storage_key, &record, &claim.record())?));
+        }
+        if record.fingerprint != fingerprint {
+            return Ok(Decision::Conflict);
+        }
+        Ok(match record.response {
+            Some(response) => Decision::Replay(response),
+            None => Decision::InFlight,
+        })
+    }
+
+    /// Extends the lease of a request that is still running. Returns `false`
+    /// if the key was taken over meanwhile, after which `complete` is a no-op.
+    pub fn renew(&self, key: &str, claim: &mut Claim, now: i64) -> AlbergueResult<bool> {
+        let renewed = Claim {
+            fingerprint: claim.fingerprint.clone(),
+            claimed_at: now,
+        };
+        if !self.store.replace(&Self::storage_key(key), &claim.record(), &renewed.record())? {
+            return Ok(false);
+        }
+        *claim = renewed;
+        Ok(true)
+    }
+
+    /// Stores the response for replay. Server errors are not stored so the
+    /// client can retry with the same key. Either way the key is only
+    /// touched while it still holds `claim`; returns `false` if another
+    /// request took it over after the lease ran out.
+    pub fn complete(&self, key: &str, claim: &Claim, response: StoredResponse) -> AlbergueResult<bool> {
+        let storage_key = Self::storage_key(key);
+
+        if response.status >= 500 {
+            return self.store.delete(&storage_key, &claim.record());
+        }
+
+        let answered = IdempotencyRecord {
+            response: Some(response),
+            ..claim.record()
+        };
+        self.store.replace(&storage_key, &claim.record(), &answered)
+    }
+}
@@ -4,12 +4,23 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+pub mod db;
+pub mod dto;
+pub mod error;
+pub mod health;
+pub mod idempotency;
+pub mod security_headers;
+pub mod telemetry;
+
+pub use dto::*;
+pub use error::*;
+
 // Re-export common types for microservices
 pub use serde_json::{json, Value as JsonValue};
 
 // Common error types for all services
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[wasm_bindgen]
+#[wasm_bindgen(getter_with_clone)]
 pub struct ServiceError {
     pub message: String,
     pub code: u16,
@@ -40,7 +51,6 @@ impl ServiceError {
 
 // Common response wrapper for all API calls
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[wasm_bindgen]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
@@ -49,7 +59,7 @@ pub struct ApiResponse<T> {
 
 // Room availability DTO for frontend consumption
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[wasm_bindgen]
+#[wasm_bindgen(getter_with_clone)]
 pub struct RoomAvailability {
     pub room_id: String,
     pub room_name: String,
@@ -107,7 +117,7 @@ impl RoomAvailability {
 
 // Booking request DTO
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[wasm_bindgen]
+#[wasm_bindgen(getter_with_clone)]
 pub struct BookingRequest {
     pub pilgrim_name: String,
     pub pilgrim_email: String,
@@ -144,10 +154,11 @@ impl BookingRequest {
 
 // Validation result for document processing
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[wasm_bindgen]
+#[wasm_bindgen(getter_with_clone)]
 pub struct ValidationResult {
     pub valid: bool,
     pub confidence: f64,
+    #[wasm_bindgen(skip)]
     pub extracted_data: JsonValue,
     pub errors: Vec<String>,
 }
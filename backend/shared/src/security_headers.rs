@@ -0,0 +1,160 @@
+// CORS and security header policy shared by every HTTP component
+
+/// Route prefixes only the admin origin may call, with credentials.
+pub const DEFAULT_ADMIN_PATH_PREFIXES: &[&str] = &[
+    "/api/auth/",
+    "/api/admin/",
+    "/api/booking/dashboard/",
+    "/api/validation/portraits/",
+    "/api/validation/reviews",
+];
+
+const DEFAULT_CSP: &str = "default-src 'none'; frame-ancestors 'none'; base-uri 'none'";
+const HSTS: &str = "max-age=63072000; includeSubDomains";
+const REFERRER_POLICY: &str = "strict-origin-when-cross-origin";
+const PERMISSIONS_POLICY: &str = "camera=(), microphone=(), geolocation=(), payment=()";
+
+/// Loosens the defaults for responses meant to be embedded by third-party sites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteOverride {
+    pub path_prefix: String,
+    /// Value of the CSP `frame-ancestors` directive, e.g. `*` or a list of origins.
+    pub frame_ancestors: String,
+    /// Answer any origin, never with credentials.
+    pub public_cors: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityPolicy {
+    pub allowed_origins: Vec<String>,
+    pub admin_origin: Option<String>,
+    pub admin_path_prefixes: Vec<String>,
+    pub allowed_methods: String,
+    pub allowed_headers: String,
+    pub max_age_seconds: u32,
+    pub overrides: Vec<RouteOverride>,
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec![],
+            admin_origin: None,
+            admin_path_prefixes: DEFAULT_ADMIN_PATH_PREFIXES.iter().map(|p| p.to_string()).collect(),
+            allowed_methods: "GET, POST, OPTIONS".to_string(),
+            allowed_headers: "Content-Type, Authorization, Idempotency-Key, X-Request-Id".to_string(),
+            max_age_seconds: 600,
+            overrides: vec![],
+        }
+    }
+}
+
+impl SecurityPolicy {
+    /// Builds the policy from the raw variable values; `allowed_origins` is a
+    /// comma-separated list and empty values mean "none".
+    pub fn from_settings(allowed_origins: &str, admin_origin: &str) -> Self {
+        let admin_origin = admin_origin.trim().trim_end_matches('/');
+
+        Self {
+            allowed_origins: allowed_origins
+                .split(',')
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty())
+                .collect(),
+            admin_origin: (!admin_origin.is_empty()).then(|| admin_origin.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_methods(mut self, methods: &str) -> Self {
+        self.allowed_methods = methods.to_string();
+        self
+    }
+
+    pub fn with_override(mut self, path_prefix: &str, frame_ancestors: &str) -> Self {
+        self.overrides.push(RouteOverride {
+            path_prefix: path_prefix.to_string(),
+            frame_ancestors: frame_ancestors.to_string(),
+            public_cors: true,
+        });
+        self
+    }
+
+    pub fn is_admin_route(&self, path: &str) -> bool {
+        self.admin_path_prefixes.iter().any(|p| path.starts_with(p.as_str()))
+    }
+
+    fn route_override(&self, path: &str) -> Option<&RouteOverride> {
+        self.overrides.iter().find(|o| path.starts_with(o.path_prefix.as_str()))
+    }
+
+    /// Whether a browser on `origin` may read responses from `path`.
+    pub fn is_origin_allowed(&self, origin: &str, path: &str) -> bool {
+        let is_admin = self.admin_origin.as_deref() == Some(origin);
+
+        if self.is_admin_route(path) {
+            return is_admin;
+        }
+        if self.route_override(path).is_some_and(|o| o.public_cors) {
+            return true;
+        }
+        is_admin || self.allowed_origins.iter().any(|o| o == origin)
+    }
+
+    /// CORS headers for a response to `origin`. Empty when the origin is not
+    /// allowed, so the browser blocks the read.
+    pub fn cors_headers(&self, origin: Option<&str>, path: &str) -> Vec<(String, String)> {
+        let mut headers = vec![("Vary".to_string(), "Origin".to_string())];
+
+        let Some(origin) = origin.filter(|o| self.is_origin_allowed(o, path)) else {
+            return headers;
+        };
+
+        headers.push(("Access-Control-Allow-Origin".to_string(), origin.to_string()));
+        headers.push((
+            "Access-Control-Expose-Headers".to_string(),
+            "X-Request-Id, Idempotent-Replayed".to_string(),
+        ));
+        if self.is_admin_route(path) && self.admin_origin.as_deref() == Some(origin) {
+            headers.push(("Access-Control-Allow-Credentials".to_string(), "true".to_string()));
+        }
+        headers
+    }
+
+    /// Extra headers for an allowed preflight request.
+    pub fn preflight_headers(&self) -> Vec<(String, String)> {
+        vec![
+            ("Access-Control-Allow-Methods".to_string(), self.allowed_methods.clone()),
+            ("Access-Control-Allow-Headers".to_string(), self.allowed_headers.clone()),
+            ("Access-Control-Max-Age".to_string(), self.max_age_seconds.to_string()),
+        ]
+    }
+
+    pub fn security_headers(&self, path: &str) -> Vec<(String, String)> {
+        let mut headers = vec![
+            ("Strict-Transport-Security".to_string(), HSTS.to_string()),
+            ("X-Content-Type-Options".to_string(), "nosniff".to_string()),
+            ("Referrer-Policy".to_string(), REFERRER_POLICY.to_string()),
+            ("Permissions-Policy".to_string(), PERMISSIONS_POLICY.to_string()),
+        ];
+
+        match self.route_override(path) {
+            Some(o) => headers.push((
+                "Content-Security-Policy".to_string(),
+                format!("default-src 'none'; frame-ancestors {}; base-uri 'none'", o.frame_ancestors),
+            )),
+            None => {
+                headers.push(("Content-Security-Policy".to_string(), DEFAULT_CSP.to_string()));
+                headers.push(("X-Frame-Options".to_string(), "DENY".to_string()));
+            }
+        }
+        headers
+    }
+
+    /// Everything a response to `origin` for `path` should carry.
+    pub fn response_headers(&self, origin: Option<&str>, path: &str) -> Vec<(String, String)> {
+        let mut headers = self.cors_headers(origin, path);
+        headers.extend(self.security_headers(path));
+        headers
+    }
+}
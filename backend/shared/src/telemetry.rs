@@ -0,0 +1,228 @@
+// Request correlation ids, JSON log lines and OTLP span export payloads
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Reuses the caller's request id when it looks sane, otherwise mints a new one.
+pub fn request_id_from(header: Option<&str>) -> String {
+    match header.map(str::trim) {
+        Some(id)
+            if !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+        {
+            id.to_string()
+        }
+        _ => uuid::Uuid::new_v4().to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parses `LOG_LEVEL`; unknown values fall back to `info`.
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "trace" => LogLevel::Trace,
+            "debug" => LogLevel::Debug,
+            "warn" | "warning" => LogLevel::Warn,
+            "error" => LogLevel::Error,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+/// Writes one JSON object per line to stderr, which Spin collects per component.
+#[derive(Debug, Clone)]
+pub struct JsonLogger {
+    service: String,
+    level: LogLevel,
+    request_id: Option<String>,
+}
+
+impl JsonLogger {
+    pub fn new(service: &str, level: LogLevel) -> Self {
+        Self {
+            service: service.to_string(),
+            level,
+            request_id: None,
+        }
+    }
+
+    /// At `LOG_LEVEL`, for components configured through the environment.
+    pub fn from_env(service: &str) -> Self {
+        Self::new(service, LogLevel::parse(&std::env::var("LOG_LEVEL").unwrap_or_default()))
+    }
+
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level >= self.level
+    }
+
+    /// Builds the log line; `fields` must be a JSON object or null.
+    pub fn format(&self, level: LogLevel, message: &str, fields: Value) -> Value {
+        let mut line = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "level": level,
+            "service": self.service,
+            "message": message,
+        });
+        if let Some(request_id) = &self.request_id {
+            line["request_id"] = json!(request_id);
+        }
+        if let Value::Object(fields) = fields {
+            for (key, value) in fields {
+                line[key] = value;
+            }
+        }
+        line
+    }
+
+    pub fn log(&self, level: LogLevel, message: &str, fields: Value) {
+        if self.enabled(level) {
+            eprintln!("{}", self.format(level, message, fields));
+        }
+    }
+
+    pub fn info(&self, message: &str, fields: Value) {
+        self.log(LogLevel::Info, message, fields);
+    }
+
+    pub fn warn(&self, message: &str, fields: Value) {
+        self.log(LogLevel::Warn, message, fields);
+    }
+
+    pub fn error(&self, message: &str, fields: Value) {
+        self.log(LogLevel::Error, message, fields);
+    }
+}
+
+/// Adds `request_id` to a JSON object error body; other bodies are returned unchanged.
+pub fn inject_request_id(body: &[u8], request_id: &str) -> Option<Vec<u8>> {
+    let mut value: Value = serde_json::from_slice(body).ok()?;
+    value.as_object_mut()?.insert("request_id".to_string(), json!(request_id));
+    serde_json::to_vec(&value).ok()
+}
+
+/// JSON log lines at `LOG_LEVEL` (falling back to `RUST_LOG`) for the
+/// standalone services. Fields of the enclosing span, such as `request_id`,
+/// are included in every line.
+#[cfg(feature = "server")]
+pub fn init_logging() {
+    let filter = std::env::var("LOG_LEVEL")
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .unwrap_or_else(|_| "info".to_string());
+
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
+        .init();
+}
+
+/// Span carrying the gateway's `X-Request-Id` for everything logged inside it.
+#[cfg(feature = "server")]
+pub fn request_span(request_id: &str) -> tracing::Span {
+    tracing::info_span!("request", request_id = %request_id)
+}
+
+/// Axum middleware handling the request inside `request_span`, keeping the
+/// gateway's `X-Request-Id` or minting one, and echoing it on the response.
+#[cfg(feature = "server")]
+pub async fn with_request_span(
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use tracing::Instrument;
+
+    let request_id = request_id_from(
+        request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+    );
+    let mut response = next.run(request).instrument(request_span(&request_id)).await;
+    if let Ok(value) = axum::http::HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// A finished server span, ready to be exported to an OpenTelemetry collector.
+#[derive(Debug, Clone)]
+pub struct SpanRecord {
+    pub name: String,
+    pub request_id: String,
+    pub start_unix_nanos: i64,
+    pub end_unix_nanos: i64,
+    pub http_method: String,
+    pub http_route: String,
+    pub http_status: u16,
+}
+
+impl SpanRecord {
+    /// Trace ids are derived from the request id so logs and traces line up.
+    pub fn trace_id(&self) -> String {
+        match uuid::Uuid::parse_str(&self.request_id) {
+            Ok(id) => id.simple().to_string(),
+            Err(_) => {
+                let digest = self
+                    .request_id
+                    .bytes()
+                    .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+                format!("{:016x}{:016x}", digest, digest.rotate_left(32))
+            }
+        }
+    }
+
+    /// OTLP/HTTP JSON `ExportTraceServiceRequest`, posted to `<collector>/v1/traces`.
+    pub fn to_otlp_json(&self, service: &str) -> Value {
+        let span_id = uuid::Uuid::new_v4().simple().to_string()[..16].to_string();
+        let status_code = if self.http_status >= 500 { 2 } else { 0 };
+
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [
+                        {"key": "service.name", "value": {"stringValue": service}}
+                    ]
+                },
+                "scopeSpans": [{
+                    "scope": {"name": "albergue"},
+                    "spans": [{
+                        "traceId": self.trace_id(),
+                        "spanId": span_id,
+                        "name": self.name,
+                        "kind": 2,
+                        "startTimeUnixNano": self.start_unix_nanos.to_string(),
+                        "endTimeUnixNano": self.end_unix_nanos.to_string(),
+                        "attributes": [
+                            {"key": "http.request.method", "value": {"stringValue": self.http_method}},
+                            {"key": "http.route", "value": {"stringValue": self.http_route}},
+                            {"key": "http.response.status_code", "value": {"intValue": self.http_status.to_string()}},
+                            {"key": "request_id", "value": {"stringValue": self.request_id}}
+                        ],
+                        "status": {"code": status_code}
+                    }]
+                }]
+            }]
+        })
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use shared::health::*;
+    use shared::AlbergueError;
+
+    #[test]
+    fn test_all_dependencies_up() {
+        let report = HealthReport::from_dependencies(
+            "gateway-bff",
+            "0.1.0",
+            vec![
+                DependencyHealth::new("database", DependencyStatus::Up, 3).critical(),
+                DependencyHealth::new("ministry", DependencyStatus::Up, 120),
+            ],
+        );
+
+        assert_eq!(report.status, DependencyStatus::Up);
+        assert!(!report.degraded_mode);
+        assert!(report.is_ready());
+    }
+
+    #[test]
+    fn test_non_critical_failure_degrades() {
+        let report = HealthReport::from_dependencies(
+            "gateway-bff",
+            "0.1.0",
+            vec![
+                DependencyHealth::new("database", DependencyStatus::Up, 3).critical(),
+                DependencyHealth::new("telegram", DependencyStatus::Down, 40),
+            ],
+        );
+
+        assert_eq!(report.status, DependencyStatus::Degraded);
+        assert!(report.degraded_mode);
+        assert!(report.is_ready());
+    }
+
+    #[test]
+    fn test_critical_failure_is_not_ready() {
+        let report = HealthReport::from_dependencies(
+            "gateway-bff",
+            "0.1.0",
+            vec![DependencyHealth::new("database", DependencyStatus::Down, 5000).critical()],
+        );
+
+        assert_eq!(report.status, DependencyStatus::Down);
+        assert!(!report.is_ready());
+    }
+
+    #[test]
+    fn test_from_check_outcomes() {
+        assert_eq!(DependencyHealth::from_check("smtp", 10, Ok(true)).status, DependencyStatus::Up);
+        assert_eq!(
+            DependencyHealth::from_check("smtp", SLOW_PROBE_THRESHOLD_MS + 1, Ok(true)).status,
+            DependencyStatus::Degraded
+        );
+        assert_eq!(DependencyHealth::from_check("smtp", 10, Ok(false)).status, DependencyStatus::Down);
+
+        let failed = DependencyHealth::from_check(
+            "twilio",
+            10,
+            Err(AlbergueError::ExternalService {
+                service: "twilio".to_string(),
+                message: "timeout".to_string(),
+            }),
+        );
+        assert_eq!(failed.status, DependencyStatus::Down);
+        assert!(failed.message.unwrap().contains("timeout"));
+    }
+
+    #[test]
+    fn test_nested_component_report() {
+        let notification = HealthReport::from_dependencies(
+            "notification-service",
+            "0.1.0",
+            vec![
+                DependencyHealth::new("smtp", DependencyStatus::Up, 20),
+                DependencyHealth::new("telegram", DependencyStatus::Down, 30),
+            ],
+        );
+
+        let dependencies = HealthReport::nested("notification", notification, 55, true);
+
+        assert_eq!(dependencies[0].name, "notification");
+        assert_eq!(dependencies[0].status, DependencyStatus::Degraded);
+        assert!(dependencies[0].critical);
+        assert_eq!(dependencies[2].name, "notification.telegram");
+        assert!(!dependencies[2].critical);
+    }
+
+    #[tokio::test]
+    async fn test_probe_records_result() {
+        let health = probe("database", async { Ok(true) }).await;
+
+        assert_eq!(health.name, "database");
+        assert_eq!(health.status, DependencyStatus::Up);
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let json = serde_json::to_value(DependencyHealth::new("db", DependencyStatus::Degraded, 1)).unwrap();
+        assert_eq!(json["status"], "degraded");
+    }
+}
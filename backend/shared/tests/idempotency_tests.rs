@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod tests {
+    use shared::idempotency::*;
+    use shared::AlbergueResult;
+    use std::collections::HashMap;
+    use std::sync::{Barrier, Mutex};
+    use std::thread;
+
+    #[derive(Default)]
+    struct InMemoryStore {
+        records: Mutex<HashMap<String, IdempotencyRecord>>,
+    }
+
+    impl IdempotencyStore for &InMemoryStore {
+        fn get(&self, key: &str) -> AlbergueResult<Option<IdempotencyRecord>> {
+            Ok(self.records.lock().unwrap().get(key).cloned())
+        }
+
+        fn insert_if_absent(&self, key: &str, record: &IdempotencyRecord) -> AlbergueResult<bool> {
+            let mut records = self.records.lock().unwrap();
+            if records.contains_key(key) {
+                return Ok(false);
+            }
+            records.insert(key.to_string(), record.clone());
+            Ok(true)
+        }
+
+        fn replace(&self, key: &str, current: &IdempotencyRecord, new: &IdempotencyRecord) -> AlbergueResult<bool> {
+            let mut records = self.records.lock().unwrap();
+            if records.get(key) != Some(current) {
+                return Ok(false);
+            }
+            records.insert(key.to_string(), new.clone());
+            Ok(true)
+        }
+
+        fn delete(&self, key: &str, current: &IdempotencyRecord) -> AlbergueResult<bool> {
+            let mut records = self.records.lock().unwrap();
+            if records.get(key) != Some(current) {
+                return Ok(false);
+            }
+            records.remove(key);
+            Ok(true)
+        }
+    }
+
+    const WINDOW: i64 = 3600;
+    const BOOKING: &[u8] = br#"{"bed_type":"DormA","nights":1}"#;
+
+    fn claimed(decision: Decision) -> Claim {
+        match decision {
+            Decision::Proceed(claim) => claim,
+            other => panic!("expected to claim the key, got {:?}", other),
+        }
+    }
+
+    fn created() -> StoredResponse {
+        StoredResponse {
+            status: 201,
+            content_type: Some("application/json".to_string()),
+            body: br#"{"id":"b-1"}"#.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_scope() {
+        assert!(applies_to("POST", "/api/booking/create"));
+        assert!(applies_to("post", "/api/payments/intent"));
+        assert!(applies_to("POST", "/api/notifications/send"));
+        assert!(!applies_to("GET", "/api/booking/status"));
+        assert!(!applies_to("POST", "/api/validation/document"));
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(is_valid_key("3f1c7e0a-reservar"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key(&"k".repeat(256)));
+    }
+
+    #[test]
+    fn test_fingerprint_depends_on_payload() {
+        let a = fingerprint("POST", "/api/booking/create", BOOKING);
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, fingerprint("post", "/api/booking/create", BOOKING));
+        assert_ne!(a, fingerprint("POST", "/api/booking/create", b"{}"));
+        assert_ne!(a, fingerprint("POST", "/api/notifications/send", BOOKING));
+    }
+
+    #[test]
+    fn test_repeat_request_is_replayed() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+
+        let claim = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        assert!(idempotency.complete("key-1", &claim, created()).unwrap());
+
+        assert_eq!(idempotency.begin("key-1", &fp, 200).unwrap(), Decision::Replay(created()));
+    }
+
+    #[test]
+    fn test_conflicting_payload_is_rejected() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+        let other = fingerprint("POST", "/api/booking/create", b"{\"bed_type\":\"Private\"}");
+
+        let claim = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        idempotency.complete("key-1", &claim, created()).unwrap();
+
+        assert_eq!(idempotency.begin("key-1", &other, 102).unwrap(), Decision::Conflict);
+    }
+
+    #[test]
+    fn test_concurrent_duplicate_is_in_flight() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/notifications/send", b"{}");
+
+        claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        assert_eq!(idempotency.begin("key-1", &fp, 101).unwrap(), Decision::InFlight);
+    }
+
+    #[test]
+    fn test_server_errors_are_not_stored() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+
+        let claim = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        let failure = StoredResponse {
+            status: 503,
+            content_type: None,
+            body: vec![],
+        };
+        assert!(idempotency.complete("key-1", &claim, failure).unwrap());
+
+        claimed(idempotency.begin("key-1", &fp, 102).unwrap());
+    }
+
+    #[test]
+    fn test_key_expires_after_window() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+        let other = fingerprint("POST", "/api/booking/create", b"{}");
+
+        let claim = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        idempotency.complete("key-1", &claim, created()).unwrap();
+
+        claimed(idempotency.begin("key-1", &other, 100 + WINDOW).unwrap());
+    }
+
+    /// Runs `begin` for `key` on `threads` threads at once.
+    fn race(idempotency: &Idempotency<&InMemoryStore>, key: &str, fp: &str, threads: usize, now: i64) -> Vec<Decision> {
+        let start = Barrier::new(threads);
+        thread::scope(|scope| {
+            let racers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        start.wait();
+                        idempotency.begin(key, fp, now).unwrap()
+                    })
+                })
+                .collect();
+            racers.into_iter().map(|racer| racer.join().unwrap()).collect()
+        })
+    }
+
+    #[test]
+    fn test_only_one_concurrent_begin_proceeds() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+
+        for attempt in 0..50 {
+            let key = format!("key-{}", attempt);
+            let decisions = race(&idempotency, &key, &fp, 2, 100);
+
+            assert_eq!(decisions.iter().filter(|d| matches!(d, Decision::Proceed(_))).count(), 1);
+            assert!(decisions.contains(&Decision::InFlight));
+        }
+    }
+
+    #[test]
+    fn test_abandoned_request_is_taken_over_after_its_lease() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+
+        claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        assert_eq!(
+            idempotency.begin("key-1", &fp, 100 + IN_FLIGHT_LEASE_SECONDS - 1).unwrap(),
+            Decision::InFlight
+        );
+
+        // Of the duplicates arriving once the lease is up, one takes the key over
+        let decisions = race(&idempotency, "key-1", &fp, 4, 100 + IN_FLIGHT_LEASE_SECONDS);
+        let mut winners = decisions.into_iter().filter(|d| matches!(d, Decision::Proceed(_)));
+        let claim = claimed(winners.next().unwrap());
+        assert!(winners.next().is_none());
+
+        assert!(idempotency.complete("key-1", &claim, created()).unwrap());
+        assert_eq!(idempotency.begin("key-1", &fp, 200 + IN_FLIGHT_LEASE_SECONDS).unwrap(), Decision::Replay(created()));
+    }
+
+    #[test]
+    fn test_request_taken_over_does_not_complete_the_key() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+        let slow = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        let retry = claimed(idempotency.begin("key-1", &fp, 100 + IN_FLIGHT_LEASE_SECONDS).unwrap());
+
+        // The slow request neither answers nor frees the key the retry now holds
+        let late = StoredResponse {
+            status: 200,
+            content_type: None,
+            body: b"late".to_vec(),
+        };
+        assert!(!idempotency.complete("key-1", &slow, late).unwrap());
+        let failure = StoredResponse {
+            status: 502,
+            content_type: None,
+            body: vec![],
+        };
+        assert!(!idempotency.complete("key-1", &slow, failure).unwrap());
+        assert_eq!(
+            idempotency.begin("key-1", &fp, 101 + IN_FLIGHT_LEASE_SECONDS).unwrap(),
+            Decision::InFlight
+        );
+
+        assert!(idempotency.complete("key-1", &retry, created()).unwrap());
+        assert_eq!(
+            idempotency.begin("key-1", &fp, 102 + IN_FLIGHT_LEASE_SECONDS).unwrap(),
+            Decision::Replay(created())
+        );
+    }
+
+    #[test]
+    fn test_renewed_claim_is_not_taken_over() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+        let mut claim = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+
+        assert!(idempotency.renew("key-1", &mut claim, 100 + IN_FLIGHT_LEASE_SECONDS - 1).unwrap());
+        assert_eq!(
+            idempotency.begin("key-1", &fp, 100 + IN_FLIGHT_LEASE_SECONDS).unwrap(),
+            Decision::InFlight
+        );
+
+        assert!(idempotency.complete("key-1", &claim, created()).unwrap());
+        assert_eq!(idempotency.begin("key-1", &fp, 200 + IN_FLIGHT_LEASE_SECONDS).unwrap(), Decision::Replay(created()));
+    }
+
+    #[test]
+    fn test_renewal_fails_once_taken_over() {
+        let store = InMemoryStore::default();
+        let idempotency = Idempotency::new(&store, WINDOW);
+        let fp = fingerprint("POST", "/api/booking/create", BOOKING);
+        let mut slow = claimed(idempotency.begin("key-1", &fp, 100).unwrap());
+        claimed(idempotency.begin("key-1", &fp, 100 + IN_FLIGHT_LEASE_SECONDS).unwrap());
+
+        assert!(!idempotency.renew("key-1", &mut slow, 101 + IN_FLIGHT_LEASE_SECONDS).unwrap());
+        assert!(!idempotency.complete("key-1", &slow, created()).unwrap());
+    }
+}
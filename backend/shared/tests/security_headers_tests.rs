@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use shared::security_headers::SecurityPolicy;
+
+    const APP: &str = "https://alberguedelcarrascalejo.com";
+    const ADMIN: &str = "https://admin.alberguedelcarrascalejo.com";
+
+    fn policy() -> SecurityPolicy {
+        SecurityPolicy::from_settings(&format!("{}, http://localhost:5173/", APP), ADMIN)
+            .with_override("/api/info/cards", "https://turismo.example.org")
+    }
+
+    fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_settings_parsing() {
+        let policy = policy();
+        assert_eq!(policy.allowed_origins, vec![APP.to_string(), "http://localhost:5173".to_string()]);
+        assert_eq!(policy.admin_origin.as_deref(), Some(ADMIN));
+
+        let empty = SecurityPolicy::from_settings("", "");
+        assert!(empty.allowed_origins.is_empty());
+        assert!(empty.admin_origin.is_none());
+    }
+
+    #[test]
+    fn test_allowlisted_origin_without_credentials() {
+        let headers = policy().cors_headers(Some(APP), "/api/reviews/all");
+
+        assert_eq!(header(&headers, "Access-Control-Allow-Origin"), Some(APP));
+        assert_eq!(header(&headers, "Access-Control-Allow-Credentials"), None);
+        assert_eq!(header(&headers, "Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn test_unknown_origin_gets_no_cors() {
+        let headers = policy().cors_headers(Some("https://evil.example"), "/api/reviews/all");
+
+        assert_eq!(header(&headers, "Access-Control-Allow-Origin"), None);
+        assert_eq!(header(&headers, "Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn test_admin_routes_only_for_admin_origin() {
+        let policy = policy();
+
+        let admin = policy.cors_headers(Some(ADMIN), "/api/auth/verify");
+        assert_eq!(header(&admin, "Access-Control-Allow-Origin"), Some(ADMIN));
+        assert_eq!(header(&admin, "Access-Control-Allow-Credentials"), Some("true"));
+
+        let public = policy.cors_headers(Some(APP), "/api/auth/verify");
+        assert_eq!(header(&public, "Access-Control-Allow-Origin"), None);
+
+        // The admin origin still gets plain CORS on public routes
+        let elsewhere = policy.cors_headers(Some(ADMIN), "/api/booking/pricing");
+        assert_eq!(header(&elsewhere, "Access-Control-Allow-Origin"), Some(ADMIN));
+        assert_eq!(header(&elsewhere, "Access-Control-Allow-Credentials"), None);
+    }
+
+    #[test]
+    fn test_default_security_headers() {
+        let headers = policy().security_headers("/api/booking/pricing");
+
+        assert!(header(&headers, "Strict-Transport-Security").unwrap().contains("max-age="));
+        assert_eq!(header(&headers, "X-Content-Type-Options"), Some("nosniff"));
+        assert!(header(&headers, "Referrer-Policy").is_some());
+        assert!(header(&headers, "Permissions-Policy").is_some());
+        assert!(header(&headers, "Content-Security-Policy").unwrap().contains("frame-ancestors 'none'"));
+        assert_eq!(header(&headers, "X-Frame-Options"), Some("DENY"));
+    }
+
+    #[test]
+    fn test_info_cards_are_embeddable() {
+        let policy = policy();
+        let headers = policy.response_headers(Some("https://turismo.example.org"), "/api/info/cards");
+
+        assert!(header(&headers, "Content-Security-Policy")
+            .unwrap()
+            .contains("frame-ancestors https://turismo.example.org"));
+        assert_eq!(header(&headers, "X-Frame-Options"), None);
+        assert_eq!(
+            header(&headers, "Access-Control-Allow-Origin"),
+            Some("https://turismo.example.org")
+        );
+        assert_eq!(header(&headers, "Access-Control-Allow-Credentials"), None);
+    }
+}
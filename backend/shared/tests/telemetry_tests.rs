@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use shared::telemetry::*;
+
+    #[test]
+    fn test_request_id_propagated_when_valid() {
+        assert_eq!(request_id_from(Some("abc-123_x.y")), "abc-123_x.y");
+    }
+
+    #[test]
+    fn test_request_id_generated_when_missing_or_invalid() {
+        let generated = request_id_from(None);
+        assert!(uuid::Uuid::parse_str(&generated).is_ok());
+
+        let injected = request_id_from(Some("abc\r\nSet-Cookie: x"));
+        assert_ne!(injected, "abc\r\nSet-Cookie: x");
+        assert!(uuid::Uuid::parse_str(&injected).is_ok());
+
+        let too_long = "a".repeat(200);
+        assert_ne!(request_id_from(Some(&too_long)), too_long);
+    }
+
+    #[test]
+    fn test_log_level_parsing_and_filtering() {
+        assert_eq!(LogLevel::parse("DEBUG"), LogLevel::Debug);
+        assert_eq!(LogLevel::parse("nonsense"), LogLevel::Info);
+
+        let logger = JsonLogger::new("gateway-bff", LogLevel::Warn);
+        assert!(!logger.enabled(LogLevel::Info));
+        assert!(logger.enabled(LogLevel::Error));
+    }
+
+    #[test]
+    fn test_log_line_is_structured() {
+        let logger = JsonLogger::new("gateway-bff", LogLevel::Info).with_request_id("req-1");
+        let line = logger.format(LogLevel::Info, "request completed", json!({"status": 200}));
+
+        assert_eq!(line["level"], "info");
+        assert_eq!(line["service"], "gateway-bff");
+        assert_eq!(line["request_id"], "req-1");
+        assert_eq!(line["status"], 200);
+        assert!(line["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_request_id_injected_into_error_body() {
+        let body = inject_request_id(br#"{"error":"Not Found"}"#, "req-1").unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(value["error"], "Not Found");
+        assert_eq!(value["request_id"], "req-1");
+        assert!(inject_request_id(b"plain text", "req-1").is_none());
+        assert!(inject_request_id(b"[1,2]", "req-1").is_none());
+    }
+
+    #[test]
+    fn test_otlp_payload() {
+        let span = SpanRecord {
+            name: "GET /api/health".to_string(),
+            request_id: "6f1c2b9e-3f4a-4e8b-9c1d-2a3b4c5d6e7f".to_string(),
+            start_unix_nanos: 1_000,
+            end_unix_nanos: 2_000,
+            http_method: "GET".to_string(),
+            http_route: "/api/health".to_string(),
+            http_status: 503,
+        };
+
+        let payload = span.to_otlp_json("gateway-bff");
+        let otlp_span = &payload["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+
+        assert_eq!(otlp_span["traceId"], "6f1c2b9e3f4a4e8b9c1d2a3b4c5d6e7f");
+        assert_eq!(otlp_span["spanId"].as_str().unwrap().len(), 16);
+        assert_eq!(otlp_span["status"]["code"], 2);
+        assert_eq!(
+            payload["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"],
+            "gateway-bff"
+        );
+    }
+
+    #[test]
+    fn test_trace_id_for_non_uuid_request_id() {
+        let span = SpanRecord {
+            name: "GET /".to_string(),
+            request_id: "client-supplied".to_string(),
+            start_unix_nanos: 0,
+            end_unix_nanos: 0,
+            http_method: "GET".to_string(),
+            http_route: "/".to_string(),
+            http_status: 200,
+        };
+
+        assert_eq!(span.trace_id().len(), 32);
+        assert_eq!(span.trace_id(), span.trace_id());
+    }
+}
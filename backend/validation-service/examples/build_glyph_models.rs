@@ -0,0 +1,104 @@
+//! Renders fonts into the glyph models embedded by the OCR engine.
+//!
+//! cargo run --example build_glyph_models -- \
+//!     --mrz OCR-B.otf --latin DejaVuSans.ttf DejaVuSerif.ttf --out models
+//!
+//! The MRZ model in the repository was rendered from DejaVuSansMono.ttf,
+//! DejaVuSansMono-Bold.ttf, DejaVuSans.ttf and DejaVuSans-Bold.ttf, as no
+//! OCR-B font could be bundled; rebuild it with `--mrz` and an OCR-B font
+//! whose licence allows it.
+
+use ab_glyph::{point, Font, FontVec, GlyphId, PxScale, ScaleFont};
+use std::path::PathBuf;
+use validation_service::domain::ocr::glyph_model::{Glyph, GlyphModel, Placement};
+
+const SCALE: f32 = 64.0;
+const MRZ_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789<";
+const LATIN_ALPHABET: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789ÁÉÍÓÚÑÜÇáéíóúñüç.,:;/-()'ºª";
+
+fn render(font: &FontVec, ch: char) -> Option<Glyph> {
+    let id = font.glyph_id(ch);
+    if id.0 == 0 {
+        return None;
+    }
+    let scaled = font.as_scaled(PxScale::from(SCALE));
+    let baseline = scaled.ascent();
+    let outline = |id: GlyphId| font.outline_glyph(id.with_scale_and_position(SCALE, point(0.0, baseline)));
+
+    let glyph = outline(id)?;
+    let bounds = glyph.px_bounds();
+    let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+    let mut pixels = vec![0.0; width * height];
+    glyph.draw(|x, y, coverage| {
+        if (x as usize) < width && (y as usize) < height && coverage >= 0.5 {
+            pixels[y as usize * width + x as usize] = 1.0;
+        }
+    });
+
+    let cap = outline(font.glyph_id('H'))?.px_bounds();
+    let placement = Some(Placement {
+        height: bounds.height() / cap.height(),
+        top: (baseline - bounds.min.y) / cap.height(),
+    });
+
+    Some(Glyph {
+        width,
+        height,
+        pixels,
+        placement,
+    })
+}
+
+fn build(name: &str, alphabet: &str, fonts: &[PathBuf]) -> String {
+    let mut glyphs = vec![];
+    for path in fonts {
+        let data = std::fs::read(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let font = FontVec::try_from_vec(data).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        for ch in alphabet.chars() {
+            match render(&font, ch) {
+                Some(glyph) => glyphs.push((ch, glyph)),
+                None => eprintln!("{}: no glyph for {:?}", path.display(), ch),
+            }
+        }
+    }
+    let sources: Vec<String> = fonts
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .collect();
+    format!(
+        "# Rendered from {}\n{}",
+        sources.join(", "),
+        GlyphModel::from_glyphs(name, &glyphs).to_source()
+    )
+}
+
+fn main() {
+    let mut mrz_fonts = vec![];
+    let mut latin_fonts = vec![];
+    let mut out = PathBuf::from("models");
+
+    let mut target = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mrz" | "--latin" => target = Some(arg),
+            "--out" => out = PathBuf::from(args.next().expect("--out needs a directory")),
+            path => match target.as_deref() {
+                Some("--mrz") => mrz_fonts.push(PathBuf::from(path)),
+                Some("--latin") => latin_fonts.push(PathBuf::from(path)),
+                _ => panic!("unexpected argument {}", path),
+            },
+        }
+    }
+
+    if !mrz_fonts.is_empty() {
+        let model = build("mrz", MRZ_ALPHABET, &mrz_fonts);
+        std::fs::write(out.join("mrz.glyphs"), model).expect("write mrz.glyphs");
+    }
+    if !latin_fonts.is_empty() {
+        let model = build("latin", LATIN_ALPHABET, &latin_fonts);
+        std::fs::write(out.join("latin.glyphs"), model).expect("write latin.glyphs");
+    }
+}
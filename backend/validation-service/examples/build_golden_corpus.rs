@@ -0,0 +1,21 @@
+//! Renders the images of the golden corpus from its JSON specs.
+//!
+//! cargo run --example build_golden_corpus
+//!
+//! Run after adding a spec to tests/golden or changing how fixtures are
+//! drawn, then rerun the corpus tests to see how accuracy moved.
+
+#[path = "../tests/fixtures/mod.rs"]
+mod fixtures;
+
+use fixtures::golden;
+
+fn main() {
+    for specimen in golden::specimens() {
+        std::fs::write(specimen.front_image(), fixtures::png(&specimen.front.render())).unwrap();
+        if let (Some(back), Some(path)) = (&specimen.back, specimen.back_image()) {
+            std::fs::write(path, fixtures::png(&back.render())).unwrap();
+        }
+        println!("{}", specimen.name);
+    }
+}
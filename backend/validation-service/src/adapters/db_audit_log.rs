@@ -0,0 +1,45 @@
+use crate::adapters::spin_db::{json_column, optional_text_column, timestamp_column, Column, SpinDb};
+use crate::domain::review::AuditEntry;
+use crate::ports::audit_log::AuditLog;
+use shared::AlbergueResult;
+
+const TABLE: &str = "audit_log";
+
+/// Audit entries as `audit_log` rows, with the request they came from.
+pub struct DbAuditLog {
+    db: SpinDb,
+}
+
+impl DbAuditLog {
+    pub fn new(db: SpinDb) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl AuditLog for DbAuditLog {
+    async fn record(&self, entry: &AuditEntry) -> AlbergueResult<()> {
+        let sql = "INSERT INTO audit_log (table_name, record_id, action, user_id, user_role, changed_fields, \
+             old_values, new_values, request_id, created_at) \
+             VALUES ($1, $2::text::uuid, $3, $4, $5, $6::text::jsonb, $7::text::jsonb, $8::text::jsonb, $9, \
+             $10::text::timestamptz)";
+        self.db.query(TABLE, sql, &to_row(entry)?)?;
+        Ok(())
+    }
+}
+
+/// `entry` as the values of an `audit_log` insert.
+pub fn to_row(entry: &AuditEntry) -> AlbergueResult<Vec<Column>> {
+    Ok(vec![
+        Column::Text(entry.table_name.clone()),
+        Column::Text(entry.record_id.to_string()),
+        Column::Text(entry.action.as_str().to_string()),
+        Column::Text(entry.user_id.clone()),
+        Column::Text(entry.user_role.clone()),
+        json_column(TABLE, &entry.changed_fields)?,
+        json_column(TABLE, &entry.old_values)?,
+        json_column(TABLE, &entry.new_values)?,
+        optional_text_column(&entry.request_id),
+        timestamp_column(&entry.created_at),
+    ])
+}
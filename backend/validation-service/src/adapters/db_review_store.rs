@@ -0,0 +1,126 @@
+use crate::adapters::spin_db::{json_column, optional_text_column, timestamp_column, Column, Row, SpinDb};
+use crate::ports::review_store::ReviewStore;
+use shared::{AlbergueError, AlbergueResult, DocumentReview, DocumentType, ReviewStatus};
+use uuid::Uuid;
+
+const TABLE: &str = "document_reviews";
+
+/// The columns `to_row` writes and `from_row` reads, in that order.
+const COLUMNS: &str = "id::text, status, document_type, extracted_data::text, field_confidence::text, \
+     fields_to_review::text, confidence_score, errors::text, ocr_text::text, front_image_url, \
+     back_image_url, corrections::text, created_at::text, decided_by, decided_at::text, rejection_reason";
+
+/// Reviews as `document_reviews` rows.
+pub struct DbReviewStore {
+    db: SpinDb,
+}
+
+impl DbReviewStore {
+    pub fn new(db: SpinDb) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ReviewStore for DbReviewStore {
+    async fn save(&self, review: &DocumentReview) -> AlbergueResult<()> {
+        let sql = "INSERT INTO document_reviews (id, status, document_type, extracted_data, field_confidence, \
+             fields_to_review, confidence_score, errors, ocr_text, front_image_url, back_image_url, \
+             corrections, created_at, decided_by, decided_at, rejection_reason) \
+             VALUES ($1::text::uuid, $2, $3, $4::text::jsonb, $5::text::jsonb, $6::text::jsonb, $7, \
+             $8::text::jsonb, $9::text::jsonb, $10, $11, $12::text::jsonb, $13::text::timestamptz, $14, \
+             $15::text::timestamptz, $16) \
+             ON CONFLICT (id) DO UPDATE SET status = EXCLUDED.status, corrections = EXCLUDED.corrections, \
+             decided_by = EXCLUDED.decided_by, decided_at = EXCLUDED.decided_at, \
+             rejection_reason = EXCLUDED.rejection_reason";
+        self.db.query(TABLE, sql, &to_row(review)?)?;
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<DocumentReview>> {
+        let sql = format!("SELECT {} FROM document_reviews WHERE id = $1::text::uuid", COLUMNS);
+        let rows = self.db.query(TABLE, &sql, &[Column::Text(id.to_string())])?;
+        rows.into_iter().next().map(from_row).transpose()
+    }
+
+    async fn list(&self, status: ReviewStatus) -> AlbergueResult<Vec<DocumentReview>> {
+        let sql = format!("SELECT {} FROM document_reviews WHERE status = $1 ORDER BY created_at", COLUMNS);
+        let rows = self.db.query(TABLE, &sql, &[Column::Text(status_name(status).to_string())])?;
+        rows.into_iter().map(from_row).collect()
+    }
+}
+
+/// `document_reviews.status`, as its check constraint allows.
+fn status_name(status: ReviewStatus) -> &'static str {
+    match status {
+        ReviewStatus::Pending => "pending",
+        ReviewStatus::Approved => "approved",
+        ReviewStatus::Rejected => "rejected",
+    }
+}
+
+/// `document_reviews.document_type`: the name `DocumentType` serializes to.
+fn type_name(document_type: DocumentType) -> String {
+    serde_json::to_value(document_type)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// `review` as the values of `COLUMNS`.
+pub fn to_row(review: &DocumentReview) -> AlbergueResult<Vec<Column>> {
+    Ok(vec![
+        Column::Text(review.id.to_string()),
+        Column::Text(status_name(review.status).to_string()),
+        Column::Text(type_name(review.document_type)),
+        json_column(TABLE, &review.extracted_data)?,
+        json_column(TABLE, &review.field_confidence)?,
+        json_column(TABLE, &review.fields_to_review)?,
+        Column::Real(review.confidence_score),
+        json_column(TABLE, &review.errors)?,
+        json_column(TABLE, &review.ocr_text)?,
+        optional_text_column(&review.front_image_url),
+        optional_text_column(&review.back_image_url),
+        json_column(TABLE, &review.corrections)?,
+        timestamp_column(&review.created_at),
+        optional_text_column(&review.decided_by),
+        review.decided_at.as_ref().map_or(Column::Null, timestamp_column),
+        optional_text_column(&review.rejection_reason),
+    ])
+}
+
+/// A review from a row of `COLUMNS`.
+pub fn from_row(columns: Vec<Column>) -> AlbergueResult<DocumentReview> {
+    let row = Row::new(TABLE, columns);
+    let bad = |column: &str, value: &str| AlbergueError::Database {
+        message: format!("{}: unknown {} {}", TABLE, column, value),
+    };
+    let id = row.text(0)?;
+    let status = match row.text(1)?.as_str() {
+        "pending" => ReviewStatus::Pending,
+        "approved" => ReviewStatus::Approved,
+        "rejected" => ReviewStatus::Rejected,
+        other => return Err(bad("status", other)),
+    };
+    let document_type = row.text(2)?;
+
+    Ok(DocumentReview {
+        id: Uuid::parse_str(&id).map_err(|_| bad("id", &id))?,
+        status,
+        document_type: serde_json::from_value::<DocumentType>(document_type.clone().into())
+            .map_err(|_| bad("document_type", &document_type))?,
+        extracted_data: row.json(3)?,
+        field_confidence: row.json(4)?,
+        fields_to_review: row.json(5)?,
+        confidence_score: row.real(6)?,
+        errors: row.json(7)?,
+        ocr_text: row.json(8)?,
+        front_image_url: row.optional_text(9)?,
+        back_image_url: row.optional_text(10)?,
+        corrections: row.json(11)?,
+        created_at: row.timestamp(12)?,
+        decided_by: row.optional_text(13)?,
+        decided_at: row.optional_timestamp(14)?,
+        rejection_reason: row.optional_text(15)?,
+    })
+}
@@ -0,0 +1,57 @@
+use spin_sdk::http::{Method, Request, Response};
+
+use crate::domain::upload::UploadFormat;
+use crate::ports::image_converter::ImageConverter;
+use shared::{AlbergueError, AlbergueResult};
+
+/// An image conversion service that answers a POST of an image with the
+/// same image as JPEG, such as imaginary's `/convert?type=jpeg`. HEVC
+/// decoding needs native code the WASM component cannot carry.
+pub struct HttpImageConverter {
+    url: String,
+}
+
+impl HttpImageConverter {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+fn content_type(format: UploadFormat) -> &'static str {
+    match format {
+        UploadFormat::Jpeg => "image/jpeg",
+        UploadFormat::Png => "image/png",
+        UploadFormat::WebP => "image/webp",
+        UploadFormat::Heif => "image/heic",
+        UploadFormat::Pdf => "application/pdf",
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ImageConverter for HttpImageConverter {
+    async fn to_jpeg(&self, data: &[u8], format: UploadFormat) -> AlbergueResult<Vec<u8>> {
+        let request = Request::builder()
+            .method(Method::Post)
+            .uri(&self.url)
+            .header("content-type", content_type(format))
+            .header("accept", "image/jpeg")
+            .body(data.to_vec())
+            .build();
+        let response: Response = spin_sdk::http::send(request)
+            .await
+            .map_err(|e| AlbergueError::Internal {
+                message: format!("Image conversion request failed: {}", e),
+            })?;
+
+        match *response.status() {
+            200..=299 => Ok(response.into_body()),
+            // The converter could not read it either
+            400..=499 => Err(AlbergueError::Validation {
+                message: format!("The {} image could not be read", format.name()),
+            }),
+            status => Err(AlbergueError::Internal {
+                message: format!("Image conversion failed: converter answered {}", status),
+            }),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+use crate::domain::portrait::{PortraitKey, SealedPortrait};
+use crate::ports::portrait_store::PortraitStore;
+use shared::{AlbergueError, AlbergueResult};
+
+/// Sealed portraits as files under a directory, one subdirectory per
+/// retention date. For development and single-host deployments.
+pub struct LocalPortraitStore {
+    root: PathBuf,
+}
+
+impl LocalPortraitStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn file(&self, key: &PortraitKey) -> PathBuf {
+        self.root.join(key.path())
+    }
+}
+
+fn storage_error(action: &str, e: std::io::Error) -> AlbergueError {
+    AlbergueError::Internal {
+        message: format!("Failed to {} portrait: {}", action, e),
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl PortraitStore for LocalPortraitStore {
+    async fn put(&self, key: &PortraitKey, portrait: &SealedPortrait) -> AlbergueResult<String> {
+        let file = self.file(key);
+        if let Some(day) = file.parent() {
+            std::fs::create_dir_all(day).map_err(|e| storage_error("store", e))?;
+        }
+        std::fs::write(&file, portrait.as_bytes()).map_err(|e| storage_error("store", e))?;
+        Ok(format!("file://{}", file.display()))
+    }
+
+    async fn get(&self, key: &PortraitKey) -> AlbergueResult<Option<SealedPortrait>> {
+        match std::fs::read(self.file(key)) {
+            Ok(bytes) => Ok(Some(SealedPortrait::from_stored(bytes))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error("read", e)),
+        }
+    }
+
+    async fn purge_expired(&self, today: NaiveDate) -> AlbergueResult<usize> {
+        let days = match std::fs::read_dir(&self.root) {
+            Ok(days) => days,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(storage_error("list", e)),
+        };
+
+        let mut purged = 0;
+        for day in days {
+            let day = day.map_err(|e| storage_error("list", e))?;
+            let expired = day
+                .file_name()
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%Y-%m-%d").ok())
+                .is_some_and(|retain_until| retain_until < today);
+            if !expired {
+                continue;
+            }
+            purged += std::fs::read_dir(day.path())
+                .map_err(|e| storage_error("list", e))?
+                .count();
+            std::fs::remove_dir_all(day.path()).map_err(|e| storage_error("delete", e))?;
+        }
+        Ok(purged)
+    }
+}
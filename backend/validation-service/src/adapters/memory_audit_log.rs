@@ -0,0 +1,37 @@
+use crate::domain::review::AuditEntry;
+use crate::ports::audit_log::AuditLog;
+use shared::AlbergueResult;
+use std::sync::{Arc, Mutex};
+
+/// Audit entries in memory, for development and tests.
+#[derive(Clone)]
+pub struct MemoryAuditLog {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl Default for MemoryAuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryAuditLog {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Everything recorded, oldest first.
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl AuditLog for MemoryAuditLog {
+    async fn record(&self, entry: &AuditEntry) -> AlbergueResult<()> {
+        self.entries.lock().unwrap().push(entry.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::ports::review_store::ReviewStore;
+use shared::{AlbergueResult, DocumentReview, ReviewStatus};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Reviews in memory. Clones share their reviews, so the validation and
+/// review services can be handed one store each.
+#[derive(Clone)]
+pub struct MemoryReviewStore {
+    reviews: Arc<Mutex<HashMap<Uuid, DocumentReview>>>,
+}
+
+impl Default for MemoryReviewStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryReviewStore {
+    pub fn new() -> Self {
+        Self {
+            reviews: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ReviewStore for MemoryReviewStore {
+    async fn save(&self, review: &DocumentReview) -> AlbergueResult<()> {
+        let mut reviews = self.reviews.lock().unwrap();
+        reviews.insert(review.id, review.clone());
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<DocumentReview>> {
+        let reviews = self.reviews.lock().unwrap();
+        Ok(reviews.get(&id).cloned())
+    }
+
+    async fn list(&self, status: ReviewStatus) -> AlbergueResult<Vec<DocumentReview>> {
+        let reviews = self.reviews.lock().unwrap();
+        let mut listed: Vec<DocumentReview> = reviews.values().filter(|r| r.status == status).cloned().collect();
+        listed.sort_by_key(|r| r.created_at);
+        Ok(listed)
+    }
+}
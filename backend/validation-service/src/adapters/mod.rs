@@ -1 +1,10 @@
-pub mod tesseract_ocr;
\ No newline at end of file
+pub mod db_audit_log;
+pub mod db_review_store;
+pub mod http_image_converter;
+pub mod local_portrait_store;
+pub mod memory_audit_log;
+pub mod memory_review_store;
+pub mod rust_barcode;
+pub mod rust_ocr;
+pub mod s3_portrait_store;
+pub mod spin_db;
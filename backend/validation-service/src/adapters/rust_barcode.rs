@@ -0,0 +1,41 @@
+use crate::domain::barcode::{Barcode, QrDecoder};
+use crate::ports::barcode_reader::BarcodeReader;
+use shared::{AlbergueError, AlbergueResult, BarcodeSymbology};
+
+/// Barcode reading in plain Rust, like `RustOcr`. Decodes QR codes.
+pub struct RustBarcodeReader {
+    qr_decoder: QrDecoder,
+}
+
+impl Default for RustBarcodeReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RustBarcodeReader {
+    pub fn new() -> Self {
+        Self {
+            qr_decoder: QrDecoder::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl BarcodeReader for RustBarcodeReader {
+    async fn read_barcodes(&self, image_data: &[u8]) -> AlbergueResult<Vec<Barcode>> {
+        let image = image::load_from_memory(image_data).map_err(|e| AlbergueError::OCRProcessing {
+            message: format!("Failed to decode image: {}", e),
+        })?;
+
+        Ok(self
+            .qr_decoder
+            .decode(&image.to_luma8())
+            .into_iter()
+            .map(|text| Barcode {
+                symbology: BarcodeSymbology::Qr,
+                text,
+            })
+            .collect())
+    }
+}
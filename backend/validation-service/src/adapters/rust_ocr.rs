@@ -0,0 +1,51 @@
+use crate::domain::ocr::recognizer::{OcrResult, Recognizer};
+use crate::domain::ocr::ImageProcessor;
+use crate::ports::ocr_client::OCRClient;
+use shared::{AlbergueError, AlbergueResult};
+
+/// OCR written in plain Rust so the component builds for `wasm32-wasi`
+/// without a system libtesseract.
+pub struct RustOcr {
+    image_processor: ImageProcessor,
+    recognizer: Recognizer,
+}
+
+impl Default for RustOcr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RustOcr {
+    pub fn new() -> Self {
+        Self {
+            image_processor: ImageProcessor::new(),
+            recognizer: Recognizer::new(),
+        }
+    }
+
+    fn recognize(&self, image_data: &[u8]) -> AlbergueResult<OcrResult> {
+        let image = image::load_from_memory(image_data).map_err(|e| AlbergueError::OCRProcessing {
+            message: format!("Failed to decode image: {}", e),
+        })?;
+
+        let prepared = self.image_processor.prepare(&image.to_luma8());
+        if let Some(reason) = prepared.quality.rejection_reason() {
+            return Err(AlbergueError::Validation {
+                message: format!("Image rejected: {}", reason),
+            });
+        }
+        Ok(self.recognizer.recognize(&prepared.image))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl OCRClient for RustOcr {
+    async fn extract_text(&self, image_data: &[u8]) -> AlbergueResult<String> {
+        Ok(self.recognize(image_data)?.text)
+    }
+
+    async fn extract_text_with_confidence(&self, image_data: &[u8]) -> AlbergueResult<OcrResult> {
+        self.recognize(image_data)
+    }
+}
@@ -0,0 +1,328 @@
+use chrono::{NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use spin_sdk::http::{Method, Request, Response};
+
+use crate::domain::portrait::{PortraitKey, SealedPortrait};
+use crate::ports::portrait_store::PortraitStore;
+use shared::{AlbergueError, AlbergueResult};
+
+/// Where an S3-compatible bucket is and how to sign for it. MinIO takes the
+/// same requests as AWS when addressed path-style.
+#[derive(Clone)]
+pub struct S3Settings {
+    /// `https://s3.eu-south-2.amazonaws.com` or `http://localhost:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+// The secret key stays out of logs
+impl std::fmt::Debug for S3Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Settings")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key", &self.access_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sealed portraits as objects in an S3 bucket, keyed `{date}/{id}.enc`.
+pub struct S3PortraitStore {
+    settings: S3Settings,
+    signer: SigV4,
+}
+
+impl S3PortraitStore {
+    pub fn new(settings: S3Settings) -> Self {
+        let signer = SigV4 {
+            region: settings.region.clone(),
+            access_key: settings.access_key.clone(),
+            secret_key: settings.secret_key.clone(),
+        };
+        Self { settings, signer }
+    }
+
+    fn endpoint(&self) -> &str {
+        self.settings.endpoint.trim_end_matches('/')
+    }
+
+    fn host(&self) -> &str {
+        let endpoint = self.endpoint();
+        endpoint.split_once("://").map_or(endpoint, |(_, host)| host)
+    }
+
+    fn object_path(&self, object: &str) -> String {
+        format!("/{}/{}", self.settings.bucket, object)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> AlbergueResult<Response> {
+        let payload_hash = hex(&Sha256::digest(&body));
+        let headers = [
+            ("host", self.host().to_string()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", Utc::now().format("%Y%m%dT%H%M%SZ").to_string()),
+        ];
+        let authorization =
+            self.signer
+                .authorization(method_name(&method), path, query, &headers, &payload_hash)?;
+
+        let query = canonical_query(query);
+        let mut url = format!("{}{}", self.endpoint(), path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let mut request = Request::builder();
+        request.method(method).uri(url).body(body);
+        // The host header comes from the URL
+        for (name, value) in headers.iter().skip(1) {
+            request.header(*name, value.as_str());
+        }
+        request.header("authorization", authorization);
+
+        spin_sdk::http::send(request.build())
+            .await
+            .map_err(|e| AlbergueError::Internal {
+                message: format!("Portrait storage request failed: {}", e),
+            })
+    }
+
+    /// Keys under `prefix`, or with `delimiter` the common prefixes.
+    async fn list(&self, prefix: &str, delimiter: Option<&str>) -> AlbergueResult<Vec<String>> {
+        let path = format!("/{}", self.settings.bucket);
+        let (scope, element) = match delimiter {
+            Some(_) => ("CommonPrefixes", "Prefix"),
+            None => ("Contents", "Key"),
+        };
+
+        let mut found = vec![];
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(delimiter) = delimiter {
+                query.push(("delimiter", delimiter));
+            }
+            if let Some(token) = &continuation {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::Get, &path, &query, vec![]).await?;
+            expect_success(&response, "list")?;
+
+            let listing = String::from_utf8_lossy(response.body());
+            for entry in xml_elements(&listing, scope) {
+                found.extend(xml_elements(&entry, element));
+            }
+            let truncated = xml_elements(&listing, "IsTruncated").first().map(String::as_str) == Some("true");
+            continuation = xml_elements(&listing, "NextContinuationToken").into_iter().next();
+            if !truncated || continuation.is_none() {
+                return Ok(found);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl PortraitStore for S3PortraitStore {
+    async fn put(&self, key: &PortraitKey, portrait: &SealedPortrait) -> AlbergueResult<String> {
+        let path = self.object_path(&key.path());
+        let response = self
+            .send(Method::Put, &path, &[], portrait.as_bytes().to_vec())
+            .await?;
+        expect_success(&response, "store")?;
+        Ok(format!("{}{}", self.endpoint(), path))
+    }
+
+    async fn get(&self, key: &PortraitKey) -> AlbergueResult<Option<SealedPortrait>> {
+        let response = self
+            .send(Method::Get, &self.object_path(&key.path()), &[], vec![])
+            .await?;
+        if *response.status() == 404 {
+            return Ok(None);
+        }
+        expect_success(&response, "read")?;
+        Ok(Some(SealedPortrait::from_stored(response.into_body())))
+    }
+
+    async fn purge_expired(&self, today: NaiveDate) -> AlbergueResult<usize> {
+        let mut purged = 0;
+        for day in self.list("", Some("/")).await? {
+            let expired = NaiveDate::parse_from_str(day.trim_end_matches('/'), "%Y-%m-%d")
+                .is_ok_and(|retain_until| retain_until < today);
+            if !expired {
+                continue;
+            }
+            for object in self.list(&day, None).await? {
+                let response = self
+                    .send(Method::Delete, &self.object_path(&object), &[], vec![])
+                    .await?;
+                expect_success(&response, "delete")?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+fn expect_success(response: &Response, action: &str) -> AlbergueResult<()> {
+    match *response.status() {
+        200..=299 => Ok(()),
+        status => Err(AlbergueError::Internal {
+            message: format!("Failed to {} portrait: storage answered {}", action, status),
+        }),
+    }
+}
+
+fn method_name(method: &Method) -> &'static str {
+    match method {
+        Method::Put => "PUT",
+        Method::Delete => "DELETE",
+        _ => "GET",
+    }
+}
+
+/// The text of every `<tag>…</tag>` in `xml`, entities decoded. ListObjectsV2
+/// responses need nothing more.
+fn xml_elements(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut found = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else {
+            break;
+        };
+        found.push(
+            after[..end]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+/// AWS Signature Version 4 for S3 requests.
+pub struct SigV4 {
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl SigV4 {
+    /// The `Authorization` header for a request. `headers` must hold every
+    /// header to sign, `host` and `x-amz-date` (`20130524T000000Z`) among
+    /// them; `path` is already URI-encoded.
+    pub fn authorization(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, String)],
+        payload_hash: &str,
+    ) -> AlbergueResult<String> {
+        let amz_date = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("x-amz-date"))
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| AlbergueError::Internal {
+                message: "S3 request has no x-amz-date header".to_string(),
+            })?;
+        let date = amz_date
+            .get(..8)
+            .filter(|d| d.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or_else(|| AlbergueError::Internal {
+                message: format!("Invalid x-amz-date {}", amz_date),
+            })?;
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        let mut headers: Vec<(String, &str)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim()))
+            .collect();
+        headers.sort();
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            path,
+            canonical_query(query),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [date, self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.secret_key).into_bytes(), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        Ok(format!(
+            "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+            self.access_key, scope, signed_headers, signature
+        ))
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Query parameters URI-encoded and sorted, as both the URL and the
+/// canonical request take them.
+fn canonical_query(query: &[(&str, &str)]) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .iter()
+        .map(|(name, value)| (uri_encode(name), uri_encode(value)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn uri_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
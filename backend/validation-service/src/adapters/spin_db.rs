@@ -0,0 +1,203 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::de::DeserializeOwned;
+use shared::{AlbergueError, AlbergueResult};
+use spin_sdk::{pg, sqlite};
+
+/// The database `DATABASE_URL` names: Postgres for a `postgres://` URL,
+/// otherwise the component's default SQLite database. Statements are
+/// written for Postgres; SQLite gets them without `$n` and `::type` casts.
+pub struct SpinDb {
+    connection: Connection,
+}
+
+enum Connection {
+    Postgres(pg::Connection),
+    Sqlite(sqlite::Connection),
+}
+
+/// Query parameters and columns. Postgres only converts text, numbers and
+/// booleans, so UUID, JSONB and timestamp columns go through `::text`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    Text(String),
+    Int(i64),
+    Real(f32),
+    Null,
+}
+
+impl SpinDb {
+    pub fn open(url: &str) -> AlbergueResult<Self> {
+        let connection = if url.starts_with("postgres") {
+            Connection::Postgres(pg::Connection::open(url).map_err(|e| db_error("connection", e))?)
+        } else {
+            Connection::Sqlite(sqlite::Connection::open_default().map_err(|e| db_error("connection", e))?)
+        };
+        Ok(Self { connection })
+    }
+
+    /// Runs `sql` and returns its rows; `table` names it in errors.
+    pub fn query(&self, table: &str, sql: &str, params: &[Column]) -> AlbergueResult<Vec<Vec<Column>>> {
+        match &self.connection {
+            Connection::Postgres(connection) => {
+                let params: Vec<pg::ParameterValue> = params
+                    .iter()
+                    .map(|param| match param {
+                        Column::Text(text) => pg::ParameterValue::Str(text.clone()),
+                        Column::Int(int) => pg::ParameterValue::Int64(*int),
+                        Column::Real(real) => pg::ParameterValue::Floating32(*real),
+                        Column::Null => pg::ParameterValue::DbNull,
+                    })
+                    .collect();
+                let rows = connection.query(sql, &params).map_err(|e| db_error(table, e))?.rows;
+                Ok(rows
+                    .into_iter()
+                    .map(|row| {
+                        row.into_iter()
+                            .map(|value| match value {
+                                pg::DbValue::Str(text) => Column::Text(text),
+                                pg::DbValue::Int64(int) => Column::Int(int),
+                                pg::DbValue::Int32(int) => Column::Int(int.into()),
+                                pg::DbValue::Floating32(real) => Column::Real(real),
+                                pg::DbValue::Floating64(real) => Column::Real(real as f32),
+                                _ => Column::Null,
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+            Connection::Sqlite(connection) => {
+                let params: Vec<sqlite::Value> = params
+                    .iter()
+                    .map(|param| match param {
+                        Column::Text(text) => sqlite::Value::Text(text.clone()),
+                        Column::Int(int) => sqlite::Value::Integer(*int),
+                        Column::Real(real) => sqlite::Value::Real(f64::from(*real)),
+                        Column::Null => sqlite::Value::Null,
+                    })
+                    .collect();
+                let result = connection
+                    .execute(&sqlite_sql(sql), &params)
+                    .map_err(|e| db_error(table, e))?;
+                Ok(result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        row.values
+                            .into_iter()
+                            .map(|value| match value {
+                                sqlite::Value::Text(text) => Column::Text(text),
+                                sqlite::Value::Integer(int) => Column::Int(int),
+                                sqlite::Value::Real(real) => Column::Real(real as f32),
+                                _ => Column::Null,
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// `sql` for SQLite: `$n` placeholders as `?n`, and `::type` casts dropped
+/// since every column there is already text or a number.
+pub fn sqlite_sql(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => out.push('?'),
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                while chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_').is_some() {}
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn db_error(table: &str, e: impl std::fmt::Display) -> AlbergueError {
+    AlbergueError::Database {
+        message: format!("{}: {}", table, e),
+    }
+}
+
+fn bad_column(table: &str, index: usize) -> AlbergueError {
+    AlbergueError::Database {
+        message: format!("{}: unexpected value in column {}", table, index),
+    }
+}
+
+/// A row's columns by position, read back into the types they were written
+/// from.
+pub struct Row<'a> {
+    table: &'a str,
+    columns: Vec<Column>,
+}
+
+impl<'a> Row<'a> {
+    pub fn new(table: &'a str, columns: Vec<Column>) -> Self {
+        Self { table, columns }
+    }
+
+    pub fn optional_text(&self, index: usize) -> AlbergueResult<Option<String>> {
+        match self.columns.get(index) {
+            Some(Column::Text(text)) => Ok(Some(text.clone())),
+            Some(Column::Null) => Ok(None),
+            _ => Err(bad_column(self.table, index)),
+        }
+    }
+
+    pub fn text(&self, index: usize) -> AlbergueResult<String> {
+        self.optional_text(index)?.ok_or_else(|| bad_column(self.table, index))
+    }
+
+    pub fn real(&self, index: usize) -> AlbergueResult<f32> {
+        match self.columns.get(index) {
+            Some(Column::Real(real)) => Ok(*real),
+            Some(Column::Int(int)) => Ok(*int as f32),
+            _ => Err(bad_column(self.table, index)),
+        }
+    }
+
+    /// A JSON column, read as `::text`.
+    pub fn json<T: DeserializeOwned>(&self, index: usize) -> AlbergueResult<T> {
+        serde_json::from_str(&self.text(index)?).map_err(|_| bad_column(self.table, index))
+    }
+
+    pub fn optional_timestamp(&self, index: usize) -> AlbergueResult<Option<DateTime<Utc>>> {
+        match self.optional_text(index)? {
+            Some(text) => parse_timestamp(&text).map(Some).ok_or_else(|| bad_column(self.table, index)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn timestamp(&self, index: usize) -> AlbergueResult<DateTime<Utc>> {
+        self.optional_timestamp(index)?.ok_or_else(|| bad_column(self.table, index))
+    }
+}
+
+/// A timestamp column as text: RFC 3339 as written, Postgres'
+/// `2026-05-01 09:30:00.5+00` for `timestamptz::text`, or SQLite's
+/// `datetime('now')` for defaults.
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z"))
+        .map(|at| at.with_timezone(&Utc))
+        .ok()
+        .or_else(|| Some(NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()?.and_utc()))
+}
+
+/// How timestamps are written, so that SQLite sorts them as text too.
+pub fn timestamp_column(at: &DateTime<Utc>) -> Column {
+    Column::Text(at.to_rfc3339_opts(chrono::SecondsFormat::Micros, true))
+}
+
+/// A JSON value for a JSONB, or SQLite text, column.
+pub fn json_column<T: serde::Serialize>(table: &str, value: &T) -> AlbergueResult<Column> {
+    serde_json::to_string(value).map(Column::Text).map_err(|e| db_error(table, e))
+}
+
+pub fn optional_text_column(value: &Option<String>) -> Column {
+    value.clone().map_or(Column::Null, Column::Text)
+}
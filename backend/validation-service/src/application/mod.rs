@@ -1 +1,2 @@
+pub mod review_service;
 pub mod validation_service;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use crate::domain::review::{self, AuditEntry, GoldenCase, Reviewer};
+use crate::ports::audit_log::AuditLog;
+use crate::ports::review_store::ReviewStore;
+use chrono::Utc;
+use shared::{AlbergueError, AlbergueResult, DocumentReview, ExtractedData, ReviewStatus};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// An approval: the data to register the pilgrim with, and the reading as a
+/// golden corpus case.
+#[derive(Debug, Clone)]
+pub struct ApprovedReview {
+    pub review: DocumentReview,
+    pub data: ExtractedData,
+    pub golden_case: GoldenCase,
+}
+
+/// The hospitalero's side of the review queue that `ValidationService`
+/// fills. Every decision is recorded in the audit log before it is stored.
+pub struct ReviewService {
+    store: Box<dyn ReviewStore>,
+    audit_log: Box<dyn AuditLog>,
+}
+
+impl ReviewService {
+    pub fn new(store: Box<dyn ReviewStore>, audit_log: Box<dyn AuditLog>) -> Self {
+        Self { store, audit_log }
+    }
+
+    /// Reviews waiting on a decision, oldest first.
+    pub async fn pending(&self) -> AlbergueResult<Vec<DocumentReview>> {
+        self.store.list(ReviewStatus::Pending).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> AlbergueResult<DocumentReview> {
+        self.store.get(id).await?.ok_or_else(|| AlbergueError::NotFound {
+            resource: format!("Review {}", id),
+        })
+    }
+
+    /// Records corrections to what was read, replacing earlier ones to the
+    /// same fields. The reading itself is kept as it was.
+    pub async fn correct(
+        &self,
+        id: Uuid,
+        corrections: BTreeMap<String, String>,
+        reviewer: &Reviewer,
+    ) -> AlbergueResult<DocumentReview> {
+        let before = self.get(id).await?;
+        review::ensure_pending(&before)?;
+        let mut after = before.clone();
+        after.corrections.extend(corrections);
+        // Checked now, not at approval
+        review::corrected(&after.extracted_data, &after.corrections)?;
+
+        self.decide(reviewer, &before, after, &["corrections"]).await
+    }
+
+    /// Approves the reading with its corrections, and any sent with the
+    /// approval.
+    pub async fn approve(
+        &self,
+        id: Uuid,
+        corrections: BTreeMap<String, String>,
+        reviewer: &Reviewer,
+    ) -> AlbergueResult<ApprovedReview> {
+        let before = self.get(id).await?;
+        review::ensure_pending(&before)?;
+        let mut after = before.clone();
+        after.corrections.extend(corrections);
+        let data = review::corrected(&after.extracted_data, &after.corrections)?;
+        after.status = ReviewStatus::Approved;
+        after.decided_by = Some(reviewer.user_id.clone());
+        after.decided_at = Some(Utc::now());
+
+        let review = self
+            .decide(reviewer, &before, after, &["status", "corrections", "decided_by", "decided_at"])
+            .await?;
+        let golden_case = GoldenCase::from_review(&review)?;
+        Ok(ApprovedReview {
+            review,
+            data,
+            golden_case,
+        })
+    }
+
+    pub async fn reject(&self, id: Uuid, reason: &str, reviewer: &Reviewer) -> AlbergueResult<DocumentReview> {
+        if reason.trim().is_empty() {
+            return Err(AlbergueError::Validation {
+                message: "A rejection needs a reason".to_string(),
+            });
+        }
+        let before = self.get(id).await?;
+        review::ensure_pending(&before)?;
+        let mut after = before.clone();
+        after.status = ReviewStatus::Rejected;
+        after.rejection_reason = Some(reason.trim().to_string());
+        after.decided_by = Some(reviewer.user_id.clone());
+        after.decided_at = Some(Utc::now());
+
+        self.decide(reviewer, &before, after, &["status", "rejection_reason", "decided_by", "decided_at"])
+            .await
+    }
+
+    /// Every approved reading as a golden corpus case.
+    pub async fn golden_cases(&self) -> AlbergueResult<Vec<GoldenCase>> {
+        self.store
+            .list(ReviewStatus::Approved)
+            .await?
+            .iter()
+            .map(GoldenCase::from_review)
+            .collect()
+    }
+
+    async fn decide(
+        &self,
+        reviewer: &Reviewer,
+        before: &DocumentReview,
+        after: DocumentReview,
+        changed_fields: &[&str],
+    ) -> AlbergueResult<DocumentReview> {
+        self.audit_log
+            .record(&AuditEntry::decision(reviewer, before, &after, changed_fields))
+            .await?;
+        self.store.save(&after).await?;
+        Ok(after)
+    }
+}
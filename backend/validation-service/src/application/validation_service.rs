@@ -1,166 +1,975 @@
-use crate::adapters::tesseract_ocr::TesseractOCR;
-use crate::domain::ocr::{ConfidenceScorer, ImageProcessor, TextExtractor};
+use crate::adapters::rust_barcode::RustBarcodeReader;
+use crate::adapters::rust_ocr::RustOcr;
+use crate::domain::barcode::{self, Barcode, BarcodeMismatch};
+use crate::domain::chip::{ChipData, ChipReader, CscaList, LdsFiles, SignatureCheck};
+use crate::domain::ocr::document_classifier::Classification;
+use crate::domain::ocr::image_processor::Layout;
+use crate::domain::entities::Document;
+use crate::domain::forgery::ForgeryDetector;
+use crate::domain::matching::FieldMatcher;
+use crate::domain::ocr::confidence_scorer::CheckOutcome;
+use crate::domain::ocr::recognizer::OcrWord;
+use crate::domain::ocr::{ConfidenceScorer, DocumentClassifier, ImageProcessor};
+use crate::domain::portrait::{Portrait, PortraitCipher, PortraitKey, PortraitLocator};
+use crate::domain::rules::ValidityRules;
+use crate::domain::upload::{Upload, UploadDecoder, UploadFormat, UploadLimits};
 use crate::domain::validators::dni_validator::DniValidator;
-use crate::domain::validators::mrz_validator::MrzValidator;
+use crate::domain::validators::driving_licence_validator::DrivingLicenceValidator;
+use crate::domain::validators::id_card_validator::IdCardValidator;
+use crate::domain::validators::mrz_validator::{self, mrz_comparable, MrzCheck, MrzData, MrzValidator};
 use crate::domain::validators::nie_validator::NieValidator;
 use crate::domain::validators::passport_validator::PassportValidator;
+use crate::ports::barcode_reader::BarcodeReader;
+use crate::ports::image_converter::ImageConverter;
 use crate::ports::ocr_client::OCRClient;
+use crate::ports::portrait_store::PortraitStore;
+use crate::ports::review_store::ReviewStore;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use shared::{
-    AlbergueError, AlbergueResult, DocumentType, ExtractedData, ValidationRequest,
-    ValidationResponse,
+    AlbergueError, AlbergueResult, BarcodeReading, ChipDump, ChipReading, DocumentClassification, DocumentReview,
+    DocumentType, ReviewStatus,
+    ExtractedData, FaceImageFormat, FaceImageInfo, FieldConflict, MatchVerdict, ValidationRequest, ValidationResponse,
+    ValidationWarning, ValidationWarningCode,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// A detected type this sure replaces the one the pilgrim picked.
+const OVERRIDE_CONFIDENCE: f32 = 0.9;
+
+/// Fields read with less confidence than this are flagged for review.
+const REVIEW_THRESHOLD: f32 = 0.6;
+
+/// A check-in cannot be filed without these.
+const KEY_FIELDS: [&str; 4] = ["document_number", "name", "surname", "birth_date"];
+
+/// A forgery risk this high is raised as a warning as well.
+const FORGERY_WARNING_SCORE: f32 = 0.5;
+
+/// Readings less sure than this overall are queued for review, when there
+/// is a review queue.
+pub const DEFAULT_REVIEW_CONFIDENCE: f32 = 0.7;
+
+/// Longest an upload held for review is kept, sealed.
+const REVIEW_IMAGE_RETENTION_DAYS: i64 = 30;
+
+/// What was read off a document, before validation.
+struct Extraction {
+    data: ExtractedData,
+    mrz: Option<MrzData>,
+    conflicts: Vec<FieldConflict>,
+    /// Fields a barcode supplied
+    barcode_fields: Vec<String>,
+    /// Fields a passively authenticated chip supplied
+    chip_fields: Vec<String>,
+}
+
+impl Extraction {
+    fn visual_only(data: ExtractedData) -> Self {
+        Self {
+            data,
+            mrz: None,
+            conflicts: vec![],
+            barcode_fields: vec![],
+            chip_fields: vec![],
+        }
+    }
+}
 
 pub struct ValidationService {
-    ocr_client: TesseractOCR,
+    ocr_client: Box<dyn OCRClient>,
+    barcode_reader: Box<dyn BarcodeReader>,
+    chip_reader: ChipReader,
     image_processor: ImageProcessor,
-    text_extractor: TextExtractor,
+    document_classifier: DocumentClassifier,
     confidence_scorer: ConfidenceScorer,
-    dni_validator: DniValidator,
     nie_validator: NieValidator,
     passport_validator: PassportValidator,
+    id_card_validator: IdCardValidator,
+    driving_licence_validator: DrivingLicenceValidator,
     mrz_validator: MrzValidator,
+    validity_rules: ValidityRules,
+    portrait_locator: PortraitLocator,
+    forgery_detector: ForgeryDetector,
+    field_matcher: FieldMatcher,
+    upload_decoder: UploadDecoder,
+    /// Converts HEIC uploads; they are refused without it
+    image_converter: Option<Box<dyn ImageConverter>>,
+    /// Where ID photos go, sealed; none are kept without it
+    portrait_storage: Option<(Box<dyn PortraitStore>, PortraitCipher)>,
+    /// Where unsure readings wait for a hospitalero; they simply fail without it
+    review_store: Option<Box<dyn ReviewStore>>,
+    review_confidence: f32,
+}
+
+impl Default for ValidationService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ValidationService {
     pub fn new() -> Self {
         Self {
-            ocr_client: TesseractOCR::new(),
+            ocr_client: Box::new(RustOcr::new()),
+            barcode_reader: Box::new(RustBarcodeReader::new()),
+            chip_reader: ChipReader::new(),
             image_processor: ImageProcessor::new(),
-            text_extractor: TextExtractor::new(),
+            document_classifier: DocumentClassifier::new(),
             confidence_scorer: ConfidenceScorer::new(),
-            dni_validator: DniValidator::new(),
             nie_validator: NieValidator::new(),
             passport_validator: PassportValidator::new(),
+            id_card_validator: IdCardValidator::new(),
+            driving_licence_validator: DrivingLicenceValidator::new(),
             mrz_validator: MrzValidator::new(),
+            validity_rules: ValidityRules::new(),
+            portrait_locator: PortraitLocator::new(),
+            forgery_detector: ForgeryDetector::new(),
+            field_matcher: FieldMatcher::new(),
+            upload_decoder: UploadDecoder::new(),
+            image_converter: None,
+            portrait_storage: None,
+            review_store: None,
+            review_confidence: DEFAULT_REVIEW_CONFIDENCE,
         }
     }
 
+    pub fn with_ocr_client(mut self, ocr_client: Box<dyn OCRClient>) -> Self {
+        self.ocr_client = ocr_client;
+        self
+    }
+
+    pub fn with_barcode_reader(mut self, barcode_reader: Box<dyn BarcodeReader>) -> Self {
+        self.barcode_reader = barcode_reader;
+        self
+    }
+
+    pub fn with_validity_rules(mut self, validity_rules: ValidityRules) -> Self {
+        self.validity_rules = validity_rules;
+        self
+    }
+
+    /// Trusts chips whose security object traces to one of `cscas`; chip
+    /// data is only compared with the print without them.
+    pub fn with_cscas(mut self, cscas: CscaList) -> Self {
+        self.chip_reader = ChipReader::new().with_cscas(cscas);
+        self
+    }
+
+    pub fn with_upload_limits(mut self, limits: UploadLimits) -> Self {
+        self.upload_decoder = UploadDecoder::new().with_limits(limits);
+        self
+    }
+
+    pub fn with_image_converter(mut self, image_converter: Box<dyn ImageConverter>) -> Self {
+        self.image_converter = Some(image_converter);
+        self
+    }
+
+    pub fn with_portrait_store(mut self, store: Box<dyn PortraitStore>, cipher: PortraitCipher) -> Self {
+        self.portrait_storage = Some((store, cipher));
+        self
+    }
+
+    /// Queues readings with key fields to review, or less than `confidence`
+    /// sure overall, in `store`.
+    pub fn with_review_queue(mut self, store: Box<dyn ReviewStore>, confidence: f32) -> Self {
+        self.review_store = Some(store);
+        self.review_confidence = confidence;
+        self
+    }
+
     pub async fn validate_document(
         &self,
         request: ValidationRequest,
     ) -> AlbergueResult<ValidationResponse> {
-        // Decode base64 images
-        let front_image =
-            base64::decode(&request.front_image).map_err(|e| AlbergueError::Validation {
-                message: format!("Invalid front image encoding: {}", e),
-            })?;
-
-        let back_image = if let Some(back_b64) = &request.back_image {
-            Some(
-                base64::decode(back_b64).map_err(|e| AlbergueError::Validation {
-                    message: format!("Invalid back image encoding: {}", e),
-                })?,
-            )
-        } else {
-            None
+        // Decode the uploads; a scanned PDF may hold both sides
+        let front_upload = self.read_upload(&request.front_image, "front").await?;
+        let back_upload = match &request.back_image {
+            Some(back_b64) => Some(self.read_upload(back_b64, "back").await?),
+            None => None,
         };
+        // Each page scaled for OCR, with the file it came as
+        let mut front_pages = front_upload.pages.into_iter().zip(front_upload.originals);
+        let (front_image, front_original) = front_pages.next().unwrap_or_default();
+        let (back_image, back_original) = match back_upload {
+            Some(back) => back.pages.into_iter().zip(back.originals).next(),
+            None => front_pages.next(),
+        }
+        .unzip();
 
         // Perform OCR
-        let front_text = self.ocr_client.extract_text(&front_image).await?;
-        let back_text = if let Some(back_data) = &back_image {
-            Some(self.ocr_client.extract_text(back_data).await?)
+        let front = self.ocr_client.extract_text_with_confidence(&front_image).await?;
+        let back = if let Some(back_data) = &back_image {
+            Some(self.ocr_client.extract_text_with_confidence(back_data).await?)
         } else {
             None
         };
 
-        // Combine OCR text
-        let combined_text = match back_text {
-            Some(back) => format!("{} {}", front_text, back),
-            None => front_text,
+        // Only a passively authenticated chip is read from
+        let chip = match &request.chip_data {
+            Some(dump) => Some(self.chip_reader.read(&Self::decode_chip(dump)?)?),
+            None => None,
+        };
+        let chip_mrz = chip.as_ref().filter(|chip| chip.is_verified()).map(|chip| &chip.mrz);
+
+        // Barcodes add nothing to what a chip holds
+        let mut barcodes = vec![];
+        if chip_mrz.is_none() {
+            barcodes = self.barcode_reader.read_barcodes(&front_image).await?;
+            if let Some(back_data) = &back_image {
+                barcodes.extend(self.barcode_reader.read_barcodes(back_data).await?);
+            }
+        }
+
+        // Combine OCR text, keeping MRZ lines on lines of their own
+        let combined_text = match &back {
+            Some(back) => format!("{}\n{}", front.text, back.text),
+            None => front.text.clone(),
+        };
+        let ocr_text: Vec<String> = std::iter::once(front.text.clone())
+            .chain(back.as_ref().map(|back| back.text.clone()))
+            .collect();
+        let words: Vec<OcrWord> = front
+            .words
+            .into_iter()
+            .chain(back.into_iter().flat_map(|b| b.words))
+            .collect();
+
+        // Classify before extraction, so a TIE sent as a DNI is read as a TIE
+        let layout = self
+            .image_processor
+            .detect_layout(&front_image)
+            .unwrap_or(Layout::Unknown);
+        let classified_text = match chip_mrz {
+            Some(mrz) => format!("{}\n{}", mrz.lines.join("\n"), combined_text),
+            None => combined_text.clone(),
         };
+        let detected = self.document_classifier.classify(&classified_text, layout);
+        let (document_type, classification) = Self::resolve_document_type(request.document_type, detected)?;
 
         // Extract data based on document type
-        let extracted_data = match request.document_type {
-            DocumentType::DNI => DniValidator::extract_data_from_ocr(&combined_text)?,
-            DocumentType::NIE => self.extract_nie_data(&combined_text)?,
-            DocumentType::Passport => self.extract_passport_data(&combined_text)?,
+        let mut extraction = match document_type {
+            DocumentType::DNI => self.extract_dni_data(&combined_text, chip_mrz)?,
+            DocumentType::NIE => self.extract_nie_data(&combined_text, chip_mrz)?,
+            DocumentType::TIE => self.extract_tie_data(&combined_text, chip_mrz)?,
+            DocumentType::Passport => self.extract_passport_data(&combined_text, chip_mrz)?,
+            DocumentType::EuIdCard => self.extract_id_card_data(&combined_text, chip_mrz)?,
+            DocumentType::DrivingLicence => Extraction::visual_only(
+                self.driving_licence_validator.extract_licence_data(&combined_text)?,
+            ),
+        };
+        let (barcodes, barcode_warnings) = Self::apply_barcodes(&barcodes, &mut extraction);
+        let (chip_reading, chip_warnings) = match &chip {
+            Some(chip) => {
+                let (reading, warnings) = Self::apply_chip(chip, document_type, &mut extraction);
+                (Some(reading), warnings)
+            }
+            None => (None, vec![]),
         };
 
         // Validate document
-        let is_valid = self.validate_document_logic(&request.document_type, &extracted_data)?;
+        let mut errors = self.validate_document_logic(&document_type, &extraction)?;
+        if let Some(chip) = chip.as_ref().filter(|chip| !chip.altered_groups.is_empty()) {
+            let groups: Vec<String> = chip.altered_groups.iter().map(|g| format!("DG{}", g)).collect();
+            errors.push(format!(
+                "Chip data groups {} do not match the document security object",
+                groups.join(", ")
+            ));
+        }
+
+        // Then whether it can be used today, by whom
+        let document = Document::new(document_type, extraction.data.clone(), errors.is_empty());
+        let mut outcome = self.validity_rules.check(&document, &extraction.conflicts);
+        errors.extend(outcome.errors);
+        outcome.warnings.extend(barcode_warnings);
+        outcome.warnings.extend(chip_warnings);
 
         // Calculate confidence score
-        let confidence_score = self.calculate_confidence(&extracted_data);
+        let field_confidence = self.score_fields(document_type, &extraction, &words);
+        let confidence_score = self.calculate_confidence(&field_confidence);
+        let fields_to_review: Vec<String> = KEY_FIELDS
+            .iter()
+            .filter(|field| !field_confidence.contains_key(**field))
+            .map(|field| field.to_string())
+            .chain(
+                field_confidence
+                    .iter()
+                    .filter(|(_, confidence)| **confidence < REVIEW_THRESHOLD)
+                    .map(|(field, _)| field.clone()),
+            )
+            .collect();
+        let requires_manual_review = fields_to_review.iter().any(|f| KEY_FIELDS.contains(&f.as_str()));
+
+        // Recapture and editing signs are for the hospitalero to weigh, never
+        // errors. They are looked for in the files as sent: scaling for OCR
+        // drops metadata and smooths away screen patterns
+        let mut reasons = self.forgery_detector.inspect_image(&front_original, "front");
+        if let Some(back_data) = &back_original {
+            reasons.extend(self.forgery_detector.inspect_image(back_data, "back"));
+        }
+        reasons.extend(self.forgery_detector.inspect_data(&extraction.data, extraction.mrz.as_ref()));
+        let forgery_risk = self.forgery_detector.assess(reasons);
+        if forgery_risk.score >= FORGERY_WARNING_SCORE {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::PossibleForgery,
+                field: None,
+                message: format!(
+                    "The images may not be an honest capture of the document ({:.0}%): {}",
+                    forgery_risk.score * 100.0,
+                    forgery_risk.reasons[0].message
+                ),
+            });
+        }
+
+        // What the pilgrim typed is checked against the document, not the
+        // other way round: a mismatch is for staff to look at
+        let field_matches = match &request.entered_data {
+            Some(entered) => self.field_matcher.compare(entered, &extraction.data, document_type),
+            None => vec![],
+        };
+        let mismatched: Vec<&str> = field_matches
+            .iter()
+            .filter(|m| matches!(m.verdict, MatchVerdict::Different | MatchVerdict::Swapped))
+            .map(|m| m.field.as_str())
+            .collect();
+        if !mismatched.is_empty() {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::EnteredDataMismatch,
+                field: None,
+                message: format!("What was typed differs from the document: {}", mismatched.join(", ")),
+            });
+        }
+
+        // The photo is only kept for a pilgrim who can check in with it
+        let id_photo_url = match request.data_retention_until {
+            Some(retain_until) if errors.is_empty() && document_type != DocumentType::NIE => {
+                let chip = chip.as_ref().filter(|chip| chip.altered_groups.is_empty());
+                // The type tells the shape more surely than the image does
+                let layout = match document_type {
+                    DocumentType::Passport => Layout::PassportPage,
+                    _ => Layout::Id1Card,
+                };
+                self.store_portrait(retain_until, chip, &front_image, layout, &mut outcome.warnings)
+                    .await
+            }
+            _ => None,
+        };
 
-        Ok(ValidationResponse {
-            is_valid,
-            extracted_data,
+        let mut response = ValidationResponse {
+            is_valid: errors.is_empty(),
+            extracted_data: extraction.data,
             confidence_score,
-            errors: vec![],
-        })
+            errors,
+            conflicts: extraction.conflicts,
+            classification,
+            warnings: outcome.warnings,
+            field_confidence,
+            fields_to_review,
+            requires_manual_review,
+            barcodes,
+            chip: chip_reading,
+            id_photo_url,
+            forgery_risk,
+            field_matches,
+            review_id: None,
+        };
+
+        // Too unsure to rely on: a hospitalero decides rather than the
+        // registration failing
+        if let Some(store) = &self.review_store {
+            if response.requires_manual_review || response.confidence_score < self.review_confidence {
+                let pages = [Some(front_image.as_slice()), back_image.as_deref()];
+                let review = self
+                    .open_review(document_type, &response, ocr_text, pages, request.data_retention_until)
+                    .await;
+                store.save(&review).await?;
+                response.review_id = Some(review.id);
+            }
+        }
+        Ok(response)
     }
 
-    fn extract_nie_data(&self, _ocr_text: &str) -> AlbergueResult<ExtractedData> {
-        // Simplified NIE extraction - would be implemented similarly to DNI
-        Ok(ExtractedData {
-            document_number: None,
-            name: None,
-            surname: None,
-            birth_date: None,
-            nationality: None,
-            expiry_date: None,
-        })
+    /// Deletes the ID photos and review images retained until before
+    /// `today`, returning how many. Nothing is kept without a portrait store.
+    pub async fn purge_expired_portraits(&self, today: NaiveDate) -> AlbergueResult<usize> {
+        match &self.portrait_storage {
+            Some((store, _)) => store.purge_expired(today).await,
+            None => Ok(0),
+        }
+    }
+
+    /// A pending review of `response`, its uploads sealed in the portrait
+    /// store when there is one.
+    async fn open_review(
+        &self,
+        document_type: DocumentType,
+        response: &ValidationResponse,
+        ocr_text: Vec<String>,
+        pages: [Option<&[u8]>; 2],
+        retain_until: Option<DateTime<Utc>>,
+    ) -> DocumentReview {
+        let mut urls = [None, None];
+        if let Some((store, cipher)) = &self.portrait_storage {
+            let longest = Utc::now() + Duration::days(REVIEW_IMAGE_RETENTION_DAYS);
+            let retain_until = retain_until.map_or(longest, |until| until.min(longest)).date_naive();
+            for (url, page) in urls.iter_mut().zip(pages) {
+                let Some(page) = page else {
+                    continue;
+                };
+                // Without its images a review can still be decided on the fields
+                let key = PortraitKey::new(retain_until);
+                if let Ok(sealed) = cipher.seal_bytes(&key, page) {
+                    *url = store.put(&key, &sealed).await.ok();
+                }
+            }
+        }
+        let [front_image_url, back_image_url] = urls;
+
+        DocumentReview {
+            id: Uuid::new_v4(),
+            status: ReviewStatus::Pending,
+            document_type,
+            extracted_data: response.extracted_data.clone(),
+            field_confidence: response.field_confidence.clone(),
+            fields_to_review: response.fields_to_review.clone(),
+            confidence_score: response.confidence_score,
+            errors: response.errors.clone(),
+            ocr_text,
+            front_image_url,
+            back_image_url,
+            corrections: BTreeMap::new(),
+            created_at: Utc::now(),
+            decided_by: None,
+            decided_at: None,
+            rejection_reason: None,
+        }
+    }
+
+    /// The pages of one base64 upload. HEIC goes through the image
+    /// converter first, when there is one.
+    async fn read_upload(&self, encoded: &str, side: &str) -> AlbergueResult<Upload> {
+        let data = self.upload_decoder.decode_base64(encoded, side)?;
+        match (UploadFormat::sniff(&data), &self.image_converter) {
+            (Some(UploadFormat::Heif), Some(converter)) => {
+                self.upload_decoder.format(&data)?;
+                let jpeg = converter.to_jpeg(&data, UploadFormat::Heif).await?;
+                self.upload_decoder.decode(&jpeg)
+            }
+            _ => self.upload_decoder.decode(&data),
+        }
+    }
+
+    /// Seals the holder's photo and stores it until `retain_until`. A photo
+    /// that cannot be found or stored is a warning, never an error.
+    async fn store_portrait(
+        &self,
+        retain_until: DateTime<Utc>,
+        chip: Option<&ChipData>,
+        front_image: &[u8],
+        layout: Layout,
+        warnings: &mut Vec<ValidationWarning>,
+    ) -> Option<String> {
+        let (store, cipher) = self.portrait_storage.as_ref()?;
+        if retain_until <= Utc::now() {
+            return None;
+        }
+
+        // The chip's photo beats a crop of a photographed card
+        let chip_face = chip
+            .filter(|chip| chip.is_verified() && chip.matching_groups.contains(&2))
+            .and_then(|chip| chip.face.as_ref())
+            .filter(|face| face.format == FaceImageFormat::Jpeg)
+            .map(|face| Portrait {
+                jpeg: face.data.clone(),
+                width: face.width as u32,
+                height: face.height as u32,
+            });
+        let portrait = match chip_face {
+            Some(face) => Ok(Some(face)),
+            None => self.portrait_locator.locate(front_image, layout),
+        };
+
+        let message = match portrait {
+            Ok(Some(portrait)) => {
+                let key = PortraitKey::new(retain_until.date_naive());
+                let stored = match cipher.seal(&key, &portrait) {
+                    Ok(sealed) => store.put(&key, &sealed).await,
+                    Err(e) => Err(e),
+                };
+                match stored {
+                    Ok(url) => return Some(url),
+                    Err(e) => format!("The ID photo could not be stored: {}", e),
+                }
+            }
+            Ok(None) => "No ID photo was found on the document".to_string(),
+            Err(e) => format!("The ID photo could not be cropped: {}", e),
+        };
+        warnings.push(ValidationWarning {
+            code: ValidationWarningCode::PortraitNotStored,
+            field: None,
+            message,
+        });
+        None
     }
 
-    fn extract_passport_data(&self, _ocr_text: &str) -> AlbergueResult<ExtractedData> {
-        // Simplified passport extraction - would implement MRZ parsing
-        Ok(ExtractedData {
-            document_number: None,
-            name: None,
-            surname: None,
-            birth_date: None,
-            nationality: None,
-            expiry_date: None,
+    fn decode_chip(dump: &ChipDump) -> AlbergueResult<LdsFiles> {
+        let decode = |name: &str, file: &str| {
+            base64::decode(file).map_err(|e| AlbergueError::Validation {
+                message: format!("Invalid chip {} encoding: {}", name, e),
+            })
+        };
+        let decode_optional = |name: &str, file: &Option<String>| file.as_deref().map(|f| decode(name, f)).transpose();
+
+        Ok(LdsFiles {
+            ef_com: decode_optional("EF.COM", &dump.ef_com)?,
+            dg1: decode("DG1", &dump.dg1)?,
+            dg2: decode_optional("DG2", &dump.dg2)?,
+            dg11: decode_optional("DG11", &dump.dg11)?,
+            sod: decode_optional("EF.SOD", &dump.sod)?,
         })
     }
 
+    /// Lets the DG11 details of a verified chip override what was read, its
+    /// MRZ having already replaced the printed one. An unverified chip is
+    /// only compared with what was read.
+    fn apply_chip(
+        chip: &ChipData,
+        document_type: DocumentType,
+        extraction: &mut Extraction,
+    ) -> (ChipReading, Vec<ValidationWarning>) {
+        let mut reading = ChipReading {
+            data_groups: chip.data_groups.clone(),
+            verified_groups: chip.verified_groups(),
+            fields: vec![],
+            face_image: chip.face.as_ref().map(|face| FaceImageInfo {
+                format: face.format,
+                width: face.width,
+                height: face.height,
+            }),
+        };
+        if !chip.altered_groups.is_empty() {
+            return (reading, vec![]);
+        }
+
+        // The personal number is the DNI or NIE on Spanish documents, but
+        // some national number unrelated to the passport on others
+        let mut holder = chip.holder.clone();
+        if !matches!(document_type, DocumentType::DNI | DocumentType::NIE | DocumentType::TIE) {
+            holder.document_number = None;
+        }
+
+        let mismatch_warning = |mismatch: BarcodeMismatch, kept: &str| ValidationWarning {
+            code: ValidationWarningCode::ChipMismatch,
+            field: Some(mismatch.field.clone()),
+            message: format!(
+                "The chip reads {} as {}, the printed text as {}; the {} value was kept",
+                mismatch.field, mismatch.barcode_value, mismatch.read_value, kept
+            ),
+        };
+        if !chip.is_verified() {
+            let (chip_data, _) = barcode::overlay(&holder, &chip.mrz.to_extracted_data());
+            let (_, mismatches) = barcode::overlay(&chip_data, &extraction.data);
+            let mut warnings: Vec<ValidationWarning> =
+                mismatches.into_iter().map(|m| mismatch_warning(m, "printed")).collect();
+            warnings.push(ValidationWarning {
+                code: ValidationWarningCode::ChipUnverified,
+                field: None,
+                message: match &chip.signature {
+                    SignatureCheck::Untrusted(reason) => format!(
+                        "The chip's security object could not be verified: {}; the printed text was kept",
+                        reason
+                    ),
+                    _ => "No security object came with the chip data, so it could not be verified; the printed text was kept"
+                        .to_string(),
+                },
+            });
+            return (reading, warnings);
+        }
+
+        let (merged, mismatches) = barcode::overlay(&holder, &extraction.data);
+        extraction.data = merged;
+
+        reading.fields = field_values(&chip.mrz.to_extracted_data())
+            .into_iter()
+            .map(|(field, _)| field)
+            .chain(barcode::payload::supplied_fields(&holder))
+            .fold(vec![], |mut fields: Vec<String>, field| {
+                if !fields.iter().any(|f| f == field) {
+                    fields.push(field.to_string());
+                }
+                fields
+            });
+        extraction.chip_fields = reading.fields.clone();
+
+        let warnings = mismatches.into_iter().map(|m| mismatch_warning(m, "chip")).collect();
+        (reading, warnings)
+    }
+
+    /// Lets the fields barcodes carry override what OCR read, warning where
+    /// the two disagree.
+    fn apply_barcodes(
+        barcodes: &[Barcode],
+        extraction: &mut Extraction,
+    ) -> (Vec<BarcodeReading>, Vec<ValidationWarning>) {
+        let mut readings = vec![];
+        let mut warnings = vec![];
+
+        for code in barcodes {
+            let Some(carried) = barcode::parse_payload(&code.text) else {
+                continue;
+            };
+            let (merged, mismatches) = barcode::overlay(&carried, &extraction.data);
+            let fields: Vec<String> = barcode::payload::supplied_fields(&carried)
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+
+            extraction.data = merged;
+            extraction.barcode_fields.extend(fields.iter().cloned());
+            warnings.extend(mismatches.into_iter().map(|mismatch| ValidationWarning {
+                code: ValidationWarningCode::BarcodeMismatch,
+                field: Some(mismatch.field.clone()),
+                message: format!(
+                    "The barcode reads {} as {}, the printed text as {}; the barcode value was kept",
+                    mismatch.field, mismatch.barcode_value, mismatch.read_value
+                ),
+            }));
+            readings.push(BarcodeReading {
+                symbology: code.symbology,
+                fields,
+            });
+        }
+        (readings, warnings)
+    }
+
+    /// The type to validate as: the detected one when the classifier is near
+    /// certain or the pilgrim did not choose, the chosen one otherwise.
+    fn resolve_document_type(
+        requested: Option<DocumentType>,
+        detected: Option<Classification>,
+    ) -> AlbergueResult<(DocumentType, Option<DocumentClassification>)> {
+        let Some(detected) = detected else {
+            let document_type = requested.ok_or_else(|| AlbergueError::Validation {
+                message: "Could not tell the document type from the images; please choose it"
+                    .to_string(),
+            })?;
+            return Ok((document_type, None));
+        };
+
+        let disagrees = requested.filter(|r| *r != detected.document_type);
+        let overridden = disagrees.is_some() && detected.confidence >= OVERRIDE_CONFIDENCE;
+        let document_type = match disagrees {
+            Some(requested) if !overridden => requested,
+            _ => detected.document_type,
+        };
+        let warning = disagrees.map(|requested| {
+            if overridden {
+                format!(
+                    "Validated as {:?}: the document looks like a {:?}, not a {:?}",
+                    detected.document_type, detected.document_type, requested
+                )
+            } else {
+                format!(
+                    "The document looks like a {:?}, not a {:?}; validated as {:?}",
+                    detected.document_type, requested, requested
+                )
+            }
+        });
+
+        Ok((
+            document_type,
+            Some(DocumentClassification {
+                detected_type: detected.document_type,
+                confidence: detected.confidence,
+                issuing_country: detected.issuing_country,
+                evidence: detected.evidence,
+                requested_type: requested,
+                overridden,
+                warning,
+            }),
+        ))
+    }
+
+    /// Reads the MRZ when there is one and lets it override the visual zone.
+    /// The chip's copy of the MRZ, when there is one, stands in for the
+    /// printed one.
+    fn merge_with_mrz(
+        &self,
+        ocr_text: &str,
+        chip_mrz: Option<&MrzData>,
+        visual: ExtractedData,
+        mrz_number: fn(&MrzData) -> Option<String>,
+    ) -> Extraction {
+        let mrz = match chip_mrz {
+            Some(mrz) => mrz.clone(),
+            None => match self.mrz_validator.correct(ocr_text) {
+                Ok(corrected) => corrected.data,
+                Err(_) => return Extraction::visual_only(visual),
+            },
+        };
+
+        let mut from_mrz = mrz.to_extracted_data();
+        from_mrz.document_number = mrz_number(&mrz);
+        let (data, conflicts) = mrz_validator::reconcile(&from_mrz, &visual);
+
+        Extraction {
+            data,
+            mrz: Some(mrz),
+            conflicts,
+            barcode_fields: vec![],
+            chip_fields: vec![],
+        }
+    }
+
+    fn extract_dni_data(&self, ocr_text: &str, chip_mrz: Option<&MrzData>) -> AlbergueResult<Extraction> {
+        let visual = DniValidator::extract_data_from_ocr(ocr_text)?;
+
+        // The back MRZ holds the support number in the document number field
+        // and the DNI in the optional data
+        let mut extraction = self.merge_with_mrz(ocr_text, chip_mrz, visual, |mrz| {
+            [&mrz.optional_data, &mrz.optional_data_2]
+                .into_iter()
+                .find(|value| DniValidator::validate_format(value))
+                .cloned()
+        });
+        if let Some(mrz) = &extraction.mrz {
+            if DniValidator::validate_support_number(&mrz.document_number) {
+                extraction.data.support_number = Some(mrz.document_number.clone());
+            }
+        }
+        Ok(extraction)
+    }
+
+    fn extract_nie_data(&self, ocr_text: &str, chip_mrz: Option<&MrzData>) -> AlbergueResult<Extraction> {
+        let visual = self.nie_validator.extract_nie_data(ocr_text)?;
+
+        // TIE cards carry the card number in the document number field and the
+        // NIE itself in the optional data
+        Ok(self.merge_with_mrz(ocr_text, chip_mrz, visual, |mrz| {
+            [&mrz.optional_data, &mrz.optional_data_2, &mrz.document_number]
+                .into_iter()
+                .find(|value| NieValidator::is_nie_format(value))
+                .cloned()
+        }))
+    }
+
+    fn extract_tie_data(&self, ocr_text: &str, chip_mrz: Option<&MrzData>) -> AlbergueResult<Extraction> {
+        let mut extraction = self.extract_nie_data(ocr_text, chip_mrz)?;
+
+        // Whatever the MRZ numbers other than the NIE is the card itself
+        if let Some(mrz) = &extraction.mrz {
+            if !mrz.document_number.is_empty() && !NieValidator::is_nie_format(&mrz.document_number) {
+                extraction.data.support_number = Some(mrz.document_number.clone());
+            }
+        }
+        Ok(extraction)
+    }
+
+    fn extract_id_card_data(&self, ocr_text: &str, chip_mrz: Option<&MrzData>) -> AlbergueResult<Extraction> {
+        // Identity cards print the same labels as passports, in more languages
+        let visual = self.passport_validator.extract_passport_data(ocr_text)?;
+
+        Ok(self.merge_with_mrz(ocr_text, chip_mrz, visual, |mrz| {
+            (!mrz.document_number.is_empty()).then(|| mrz.document_number.clone())
+        }))
+    }
+
+    fn extract_passport_data(&self, ocr_text: &str, chip_mrz: Option<&MrzData>) -> AlbergueResult<Extraction> {
+        let visual = self.passport_validator.extract_passport_data(ocr_text)?;
+
+        Ok(self.merge_with_mrz(ocr_text, chip_mrz, visual, |mrz| {
+            (!mrz.document_number.is_empty()).then(|| mrz.document_number.clone())
+        }))
+    }
+
+    /// Reasons the document is not valid; empty when it is.
     fn validate_document_logic(
         &self,
         doc_type: &DocumentType,
-        data: &ExtractedData,
-    ) -> AlbergueResult<bool> {
+        extraction: &Extraction,
+    ) -> AlbergueResult<Vec<String>> {
+        let mut errors: Vec<String> = extraction
+            .mrz
+            .iter()
+            .flat_map(|mrz| mrz.failed_checks.iter().map(|check| check.to_string()))
+            .collect();
+
+        let Some(doc_number) = &extraction.data.document_number else {
+            errors.push("Document number not found".to_string());
+            return Ok(errors);
+        };
+
         match doc_type {
             DocumentType::DNI => {
-                if let Some(doc_number) = &data.document_number {
-                    Ok(DniValidator::validate_checksum(doc_number))
-                } else {
-                    Ok(false)
+                if !DniValidator::validate_checksum(doc_number) {
+                    errors.push("DNI control letter does not match".to_string());
+                }
+                if let Some(support_number) = &extraction.data.support_number {
+                    if !DniValidator::validate_support_number(support_number) {
+                        errors.push("Invalid IDESP support number format".to_string());
+                    }
+                }
+            }
+            DocumentType::NIE | DocumentType::TIE => {
+                if !self.nie_validator.validate_nie(doc_number)? {
+                    errors.push("NIE control letter does not match".to_string());
+                }
+            }
+            DocumentType::Passport => {
+                // Passport numbers have no national checksum; the MRZ check
+                // digits are what vouch for them
+                if extraction.mrz.is_none() {
+                    errors.push("No machine readable zone found".to_string());
+                }
+                if !self.passport_validator.validate_passport(doc_number)? {
+                    errors.push("Invalid passport number format".to_string());
+                }
+            }
+            DocumentType::EuIdCard => {
+                let Some(mrz) = &extraction.mrz else {
+                    errors.push("No machine readable zone found".to_string());
+                    return Ok(errors);
+                };
+                let state = mrz.issuing_state.as_str();
+                if !IdCardValidator::is_accepted_state(state) {
+                    errors.push(format!(
+                        "Identity cards issued by {} are not accepted; please use a passport",
+                        state
+                    ));
+                } else if self.id_card_validator.validate_number(state, doc_number) == Some(false) {
+                    errors.push(format!("Invalid {} identity card number", state));
+                }
+            }
+            DocumentType::DrivingLicence => {
+                if !self.driving_licence_validator.validate_licence_number(doc_number) {
+                    errors.push("Invalid driving licence number".to_string());
                 }
             }
-            DocumentType::NIE => Ok(true),      // Simplified
-            DocumentType::Passport => Ok(true), // Simplified
         }
+
+        Ok(errors)
     }
 
-    fn calculate_confidence(&self, data: &ExtractedData) -> f32 {
-        let mut score = 0.0;
-        let mut total_fields = 0.0;
+    /// Per-field confidence from the value's format, the OCR confidence of
+    /// the words it was read from, and any check digit or control letter.
+    fn score_fields(
+        &self,
+        document_type: DocumentType,
+        extraction: &Extraction,
+        words: &[OcrWord],
+    ) -> BTreeMap<String, f32> {
+        let from_mrz: Vec<&str> = extraction
+            .mrz
+            .as_ref()
+            .map(|mrz| field_values(&mrz.to_extracted_data()).into_iter().map(|(f, _)| f).collect())
+            .unwrap_or_default();
+        let mrz_words: Vec<f32> = words.iter().filter(|w| w.mrz).map(|w| w.confidence).collect();
 
-        if data.document_number.is_some() {
-            score += 1.0;
-        }
-        total_fields += 1.0;
+        field_values(&extraction.data)
+            .into_iter()
+            .map(|(field, value)| {
+                let format_score = self.confidence_scorer.calculate_field_confidence(field, &value);
+                let ocr_confidence = if from_mrz.contains(&field) {
+                    mean(&mrz_words)
+                } else {
+                    visual_word_confidence(&value, words)
+                };
+                let check = self.check_outcome(field, &value, document_type, extraction);
+                // A verified chip is right wherever the print disagrees
+                let conflicting = !extraction.chip_fields.iter().any(|f| f == field)
+                    && extraction.conflicts.iter().any(|c| c.field == field);
+                let confidence = self.confidence_scorer.combine_field_confidence(
+                    format_score,
+                    ocr_confidence,
+                    check,
+                    conflicting,
+                );
+                (field.to_string(), confidence)
+            })
+            .collect()
+    }
 
-        if data.name.is_some() {
-            score += 1.0;
+    /// Whether a check digit or control letter covers `value`.
+    fn check_outcome(
+        &self,
+        field: &str,
+        value: &str,
+        document_type: DocumentType,
+        extraction: &Extraction,
+    ) -> CheckOutcome {
+        // Barcodes carry their own error correction, chips their hashes
+        if extraction.barcode_fields.iter().chain(&extraction.chip_fields).any(|f| f == field) {
+            return CheckOutcome::Passed;
         }
-        total_fields += 1.0;
+        let outcome = |passed: bool| if passed { CheckOutcome::Passed } else { CheckOutcome::Failed };
+        let mrz = extraction.mrz.as_ref();
+        let mrz_check = |check: MrzCheck, covers: bool| match mrz {
+            Some(mrz) if covers => outcome(!mrz.failed_checks.contains(&check)),
+            _ => CheckOutcome::Unchecked,
+        };
 
-        if data.surname.is_some() {
-            score += 1.0;
+        match (field, document_type) {
+            ("document_number", DocumentType::DNI) => outcome(DniValidator::validate_checksum(value)),
+            ("document_number", DocumentType::NIE | DocumentType::TIE) => {
+                outcome(self.nie_validator.validate_nie(value).unwrap_or(false))
+            }
+            ("document_number" | "support_number", _) => {
+                mrz_check(MrzCheck::DocumentNumber, mrz.is_some_and(|m| m.document_number == value))
+            }
+            ("birth_date", _) => mrz_check(MrzCheck::BirthDate, mrz.is_some_and(|m| m.birth_date.is_some())),
+            ("expiry_date", _) => mrz_check(MrzCheck::ExpiryDate, mrz.is_some_and(|m| m.expiry_date.is_some())),
+            _ => CheckOutcome::Unchecked,
         }
-        total_fields += 1.0;
+    }
 
-        if data.birth_date.is_some() {
-            score += 1.0;
-        }
-        total_fields += 1.0;
+    /// Mean of the field confidences, scaled by how many key fields were read.
+    fn calculate_confidence(&self, field_confidence: &BTreeMap<String, f32>) -> f32 {
+        let scores: HashMap<String, f32> = field_confidence.clone().into_iter().collect();
+        let read = KEY_FIELDS.iter().filter(|f| field_confidence.contains_key(**f)).count();
 
-        if total_fields > 0.0 {
-            score / total_fields
-        } else {
-            0.0
-        }
+        self.confidence_scorer.calculate_overall_confidence(&scores) * read as f32 / KEY_FIELDS.len() as f32
     }
 }
+
+/// The fields of `data` that were read, as the scorer expects to see them.
+fn field_values(data: &ExtractedData) -> Vec<(&'static str, String)> {
+    let date = |d: &Option<chrono::DateTime<chrono::Utc>>| d.map(|d| d.format("%d/%m/%Y").to_string());
+    [
+        ("document_number", data.document_number.clone()),
+        ("name", data.name.clone()),
+        ("surname", data.surname.clone()),
+        ("birth_date", date(&data.birth_date)),
+        ("nationality", data.nationality.clone()),
+        ("issue_date", date(&data.issue_date)),
+        ("expiry_date", date(&data.expiry_date)),
+        ("sex", data.sex.clone()),
+        ("address", data.address.clone()),
+        ("municipality", data.municipality.clone()),
+        ("province", data.province.clone()),
+        ("birthplace", data.birthplace.clone()),
+        ("support_number", data.support_number.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| value.map(|v| (field, v)))
+    .collect()
+}
+
+/// Mean confidence of the visual-zone words that spell out `value`, or
+/// `None` when the OCR reported no words for it.
+fn visual_word_confidence(value: &str, words: &[OcrWord]) -> Option<f32> {
+    let confidences: Vec<f32> = mrz_comparable(value)
+        .split_whitespace()
+        .filter_map(|token| {
+            words
+                .iter()
+                .find(|w| !w.mrz && mrz_comparable(&w.text).split_whitespace().any(|t| t == token))
+                .map(|w| w.confidence)
+        })
+        .collect();
+    mean(&confidences)
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
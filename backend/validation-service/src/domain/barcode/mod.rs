@@ -0,0 +1,15 @@
+pub mod payload;
+pub mod qr_decoder;
+pub mod reed_solomon;
+
+pub use payload::{overlay, parse_payload, BarcodeMismatch};
+pub use qr_decoder::QrDecoder;
+
+use shared::BarcodeSymbology;
+
+/// A barcode decoded from a document image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Barcode {
+    pub symbology: BarcodeSymbology,
+    pub text: String,
+}
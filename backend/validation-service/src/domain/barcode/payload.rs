@@ -0,0 +1,268 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use shared::ExtractedData;
+
+use crate::domain::validators::mrz_validator::mrz_comparable;
+
+/// Keys each `ExtractedData` field goes by, as `normalize_key` spells them.
+const KEYS: &[(&str, &[&str])] = &[
+    (
+        "document_number",
+        &[
+            "DNI", "NIE", "NIF", "DOCUMENT NUMBER", "DOCUMENT NO", "DOC NUMBER", "NUMERO", "NUMERO DOCUMENTO",
+            "NUM DOCUMENTO", "DOCUMENTO", "ID NUMBER", "NUMBER", "PERMIT NUMBER", "LICENCE NUMBER", "LICENSE NUMBER",
+        ],
+    ),
+    ("name", &["NOMBRE", "NOMBRES", "NAME", "GIVEN NAME", "GIVEN NAMES", "FIRST NAME", "FORENAMES"]),
+    ("surname", &["APELLIDOS", "APELLIDO", "SURNAME", "SURNAMES", "LAST NAME", "FAMILY NAME"]),
+    (
+        "birth_date",
+        &["FECHA NACIMIENTO", "FECHA DE NACIMIENTO", "NACIMIENTO", "BIRTH DATE", "DATE OF BIRTH", "BIRTHDATE", "DOB"],
+    ),
+    (
+        "expiry_date",
+        &[
+            "CADUCIDAD", "FECHA CADUCIDAD", "VALIDEZ", "VALIDO HASTA", "EXPIRY", "EXPIRY DATE", "DATE OF EXPIRY",
+            "EXPIRES", "VALID UNTIL",
+        ],
+    ),
+    (
+        "issue_date",
+        &["EXPEDICION", "FECHA EXPEDICION", "EMISION", "FECHA EMISION", "ISSUE DATE", "DATE OF ISSUE", "ISSUED"],
+    ),
+    ("nationality", &["NACIONALIDAD", "NATIONALITY"]),
+    ("sex", &["SEXO", "SEX", "GENDER"]),
+    ("support_number", &["SOPORTE", "NUM SOPORTE", "NUMERO SOPORTE", "IDESP", "CARD NUMBER"]),
+    ("address", &["DOMICILIO", "DIRECCION", "ADDRESS", "STREET"]),
+    ("municipality", &["MUNICIPIO", "LOCALIDAD", "POBLACION", "CITY", "TOWN"]),
+    ("province", &["PROVINCIA", "PROVINCE", "REGION"]),
+    ("birthplace", &["LUGAR NACIMIENTO", "LUGAR DE NACIMIENTO", "PLACE OF BIRTH", "BIRTHPLACE"]),
+];
+
+/// A field the barcode and the printed text read differently; the barcode
+/// value is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarcodeMismatch {
+    pub field: String,
+    pub barcode_value: String,
+    pub read_value: String,
+}
+
+/// Reads the identity fields a barcode carries: JSON objects, or
+/// `KEY: value` and `KEY=value` lists separated by newlines, `;` or `|`.
+/// `None` when it carries none, e.g. a bare verification URL.
+pub fn parse_payload(text: &str) -> Option<ExtractedData> {
+    let trimmed = text.trim_start();
+    let data = if trimmed.starts_with('{') {
+        from_pairs(json_pairs(trimmed)?)
+    } else {
+        let pairs = trimmed
+            .split(['\n', '\r', ';', '|'])
+            .filter_map(|item| item.split_once([':', '=']))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        from_pairs(pairs)
+    };
+
+    (!supplied_fields(&data).is_empty()).then_some(data)
+}
+
+/// Names of the fields `data` has a value for.
+pub fn supplied_fields(data: &ExtractedData) -> Vec<&'static str> {
+    let text = [
+        ("document_number", &data.document_number),
+        ("name", &data.name),
+        ("surname", &data.surname),
+        ("nationality", &data.nationality),
+        ("sex", &data.sex),
+        ("address", &data.address),
+        ("municipality", &data.municipality),
+        ("province", &data.province),
+        ("birthplace", &data.birthplace),
+        ("support_number", &data.support_number),
+    ];
+    let dates = [
+        ("birth_date", &data.birth_date),
+        ("issue_date", &data.issue_date),
+        ("expiry_date", &data.expiry_date),
+    ];
+    text.iter()
+        .filter(|(_, value)| value.is_some())
+        .map(|(field, _)| *field)
+        .chain(dates.iter().filter(|(_, value)| value.is_some()).map(|(field, _)| *field))
+        .collect()
+}
+
+/// `read` with every field the barcode carries replaced by the barcode's
+/// value. Barcodes are error corrected, so they beat OCR; where the two
+/// disagree the difference is reported.
+pub fn overlay(barcode: &ExtractedData, read: &ExtractedData) -> (ExtractedData, Vec<BarcodeMismatch>) {
+    let mut merged = read.clone();
+    let mut mismatches = vec![];
+
+    let mut take = |field: &str, from_barcode: &Option<String>, value: &mut Option<String>| {
+        let Some(from_barcode) = from_barcode else {
+            return;
+        };
+        if let Some(read) = value.as_deref() {
+            if !agree(field, from_barcode, read) {
+                mismatches.push(BarcodeMismatch {
+                    field: field.to_string(),
+                    barcode_value: from_barcode.clone(),
+                    read_value: read.to_string(),
+                });
+            }
+        }
+        *value = Some(from_barcode.clone());
+    };
+    take("document_number", &barcode.document_number, &mut merged.document_number);
+    take("name", &barcode.name, &mut merged.name);
+    take("surname", &barcode.surname, &mut merged.surname);
+    take("nationality", &barcode.nationality, &mut merged.nationality);
+    take("sex", &barcode.sex, &mut merged.sex);
+    take("address", &barcode.address, &mut merged.address);
+    take("municipality", &barcode.municipality, &mut merged.municipality);
+    take("province", &barcode.province, &mut merged.province);
+    take("birthplace", &barcode.birthplace, &mut merged.birthplace);
+    take("support_number", &barcode.support_number, &mut merged.support_number);
+
+    let date = |d: Option<DateTime<Utc>>| d.map(|d| d.date_naive().to_string());
+    let mut take_date = |field: &str, from_barcode: Option<DateTime<Utc>>, value: &mut Option<DateTime<Utc>>| {
+        let Some(from_barcode) = from_barcode else {
+            return;
+        };
+        if let (Some(b), Some(r)) = (date(Some(from_barcode)), date(*value)) {
+            if b != r {
+                mismatches.push(BarcodeMismatch {
+                    field: field.to_string(),
+                    barcode_value: b,
+                    read_value: r,
+                });
+            }
+        }
+        *value = Some(from_barcode);
+    };
+    take_date("birth_date", barcode.birth_date, &mut merged.birth_date);
+    take_date("issue_date", barcode.issue_date, &mut merged.issue_date);
+    take_date("expiry_date", barcode.expiry_date, &mut merged.expiry_date);
+
+    (merged, mismatches)
+}
+
+fn agree(field: &str, barcode: &str, read: &str) -> bool {
+    let (barcode, read) = (mrz_comparable(barcode), mrz_comparable(read));
+    match field {
+        // Either may be cut short, the MRZ especially
+        "name" | "surname" => barcode.starts_with(&read) || read.starts_with(&barcode),
+        // Printed in words as often as in codes; only codes compare
+        "nationality" if barcode.len() != 3 || read.len() != 3 => true,
+        _ => barcode.replace(' ', "") == read.replace(' ', ""),
+    }
+}
+
+/// Uppercase words without accents, camel case and underscores split:
+/// `dateOfBirth`, `date_of_birth` and `Date of birth` all read `DATE OF BIRTH`.
+fn normalize_key(key: &str) -> String {
+    let mut spaced = String::new();
+    let mut previous_lower = false;
+    for c in key.trim().trim_matches('"').chars() {
+        if c.is_uppercase() && previous_lower {
+            spaced.push(' ');
+        }
+        previous_lower = c.is_lowercase();
+        spaced.push(c);
+    }
+    mrz_comparable(&spaced)
+}
+
+fn field_for_key(key: &str) -> Option<&'static str> {
+    let key = normalize_key(key);
+    KEYS.iter().find(|(_, keys)| keys.contains(&key.as_str())).map(|(field, _)| *field)
+}
+
+/// Top-level values of a JSON object, and those of objects one level down.
+fn json_pairs(text: &str) -> Option<Vec<(String, String)>> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let mut pairs = vec![];
+    let mut push = |key: &str, value: &serde_json::Value| match value {
+        serde_json::Value::String(s) => pairs.push((key.to_string(), s.clone())),
+        serde_json::Value::Number(n) => pairs.push((key.to_string(), n.to_string())),
+        _ => {}
+    };
+    for (key, value) in value.as_object()? {
+        match value.as_object() {
+            Some(inner) => inner.iter().for_each(|(key, value)| push(key, value)),
+            None => push(key, value),
+        }
+    }
+    Some(pairs)
+}
+
+fn from_pairs(pairs: Vec<(String, String)>) -> ExtractedData {
+    let mut data = ExtractedData::default();
+    for (key, value) in pairs {
+        let value = value.trim().trim_matches('"').trim();
+        let field = KEYS
+            .iter()
+            .find(|(field, _)| *field == key)
+            .map(|(field, _)| *field)
+            .or_else(|| field_for_key(&key));
+        let (Some(field), false) = (field, value.is_empty()) else {
+            continue;
+        };
+
+        let text = || Some(value.to_string());
+        match field {
+            "document_number" => data.document_number = Some(value.to_uppercase().replace([' ', '-'], "")),
+            "name" => data.name = data.name.take().or_else(text),
+            "surname" => data.surname = text(),
+            "birth_date" => data.birth_date = parse_date(value),
+            "expiry_date" => data.expiry_date = parse_date(value),
+            "issue_date" => data.issue_date = parse_date(value),
+            "nationality" => data.nationality = Some(value.to_uppercase()),
+            "sex" => data.sex = parse_sex(value),
+            "support_number" => data.support_number = Some(value.to_uppercase().replace(' ', "")),
+            "address" => data.address = text(),
+            "municipality" => data.municipality = text(),
+            "province" => data.province = text(),
+            _ => data.birthplace = text(),
+        }
+    }
+    data
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let numbers: Vec<u32> = value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let (year, month, day) = match numbers.as_slice() {
+        [year, month, day] if *year > 31 => (*year, *month, *day),
+        [day, month, year] => (*year, *month, *day),
+        [compact] if value.trim().len() == 8 => {
+            let digits = value.trim();
+            let part = |range: std::ops::Range<usize>| digits[range].parse::<u32>().ok();
+            // Years first when they look like years, days first otherwise
+            if (1900..2100).contains(&(compact / 10_000)) {
+                (part(0..4)?, part(4..6)?, part(6..8)?)
+            } else {
+                (part(4..8)?, part(2..4)?, part(0..2)?)
+            }
+        }
+        _ => return None,
+    };
+    let date = NaiveDate::from_ymd_opt(year as i32, month, day)?;
+    Some(date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+/// ICAO sex code from a letter, a word or an ISO 5218 digit.
+fn parse_sex(value: &str) -> Option<String> {
+    let code = match mrz_comparable(value).as_str() {
+        "M" | "H" | "1" | "MALE" | "HOMBRE" | "VARON" | "MASCULINO" => "M",
+        "F" | "2" | "FEMALE" | "MUJER" | "FEMENINO" => "F",
+        "X" | "9" => "X",
+        _ => return None,
+    };
+    Some(code.to_string())
+}
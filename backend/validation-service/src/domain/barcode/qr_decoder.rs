@@ -0,0 +1,755 @@
+use image::GrayImage;
+
+use super::reed_solomon;
+use crate::domain::ocr::image_processor::Homography;
+use crate::domain::ocr::segmentation;
+
+/// Error correction codewords per block, by level (L, M, Q, H) and version.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+/// Error correction blocks, by level (L, M, Q, H) and version.
+const ECC_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Format and version information may be this many bits off and still read.
+const MAX_INFO_BIT_ERRORS: u32 = 3;
+
+/// More codes than this in one image are not looked for.
+const MAX_CODES: usize = 4;
+
+/// A finder pattern, the 7 by 7 square in three corners of every code.
+#[derive(Debug, Clone, Copy)]
+struct Finder {
+    x: f32,
+    y: f32,
+    module: f32,
+    hits: u32,
+}
+
+impl Finder {
+    fn distance(&self, other: &Finder) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+/// Where a code's three finders sit in the image.
+#[derive(Debug, Clone, Copy)]
+struct Corners {
+    top_left: (f32, f32),
+    top_right: (f32, f32),
+    bottom_left: (f32, f32),
+    module: f32,
+}
+
+/// Finds and decodes QR codes (ISO/IEC 18004, models 2, versions 1 to 40)
+/// in a photo or scan: locates the finder patterns, samples the module grid
+/// through the perspective they imply, then undoes the mask and corrects
+/// errors with Reed-Solomon before reading the data segments.
+pub struct QrDecoder;
+
+impl Default for QrDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QrDecoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The text of every code found in `gray`.
+    pub fn decode(&self, gray: &GrayImage) -> Vec<String> {
+        // A global threshold, without the inversion text binarisation does:
+        // modules are dark on light even when a dark blanket fills the photo
+        let threshold = segmentation::otsu_threshold(gray);
+        let ink = Ink {
+            width: gray.width() as i64,
+            height: gray.height() as i64,
+            dark: gray.pixels().map(|p| p[0] <= threshold).collect(),
+        };
+
+        let mut finders = find_finders(&ink);
+        let mut texts = vec![];
+        while texts.len() < MAX_CODES {
+            let Some((corners, used)) = pick_corners(&finders) else {
+                break;
+            };
+            if let Some(text) = decode_at(&ink, &corners) {
+                texts.push(text);
+            }
+            let mut used = used;
+            used.sort_unstable_by(|a, b| b.cmp(a));
+            for i in used {
+                finders.remove(i);
+            }
+        }
+        texts
+    }
+
+    /// Reads a code already sampled into modules, `true` for dark, rows top
+    /// to bottom.
+    pub fn decode_modules(&self, modules: &[Vec<bool>]) -> Option<String> {
+        let size = modules.len();
+        if !(size_of(1)..=size_of(40)).contains(&size)
+            || !(size - 17).is_multiple_of(4)
+            || modules.iter().any(|row| row.len() != size)
+        {
+            return None;
+        }
+        decode_grid(&Grid::new(modules.to_vec()))
+    }
+}
+
+/// The dark pixels of an image.
+struct Ink {
+    width: i64,
+    height: i64,
+    dark: Vec<bool>,
+}
+
+impl Ink {
+    fn at(&self, x: i64, y: i64) -> Option<bool> {
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y)).then(|| self.dark[(y * self.width + x) as usize])
+    }
+}
+
+/// Whether five run lengths read dark, light, dark, light, dark in the
+/// 1:1:3:1:1 proportion of a finder.
+fn finder_ratio(counts: &[usize; 5]) -> bool {
+    let total: usize = counts.iter().sum();
+    if total < 7 || counts.contains(&0) {
+        return false;
+    }
+    let module = total as f32 / 7.0;
+    let tolerance = module / 2.0;
+    counts
+        .iter()
+        .zip([1.0, 1.0, 3.0, 1.0, 1.0])
+        .all(|(&count, expected)| (count as f32 - module * expected).abs() < tolerance * expected)
+}
+
+/// The five runs through `along(0)` when it is in the middle of a finder,
+/// with the centre of the middle run.
+fn runs_through(along: impl Fn(i64) -> Option<bool>) -> Option<([usize; 5], f32)> {
+    if along(0) != Some(true) {
+        return None;
+    }
+    let mut counts = [0usize; 5];
+    let mut i = 0;
+    for (run, dark) in [(2, true), (1, false), (0, true)] {
+        while along(i) == Some(dark) {
+            counts[run] += 1;
+            i -= 1;
+        }
+    }
+    let first = i + 1;
+    i = 1;
+    for (run, dark) in [(2, true), (3, false), (4, true)] {
+        while along(i) == Some(dark) {
+            counts[run] += 1;
+            i += 1;
+        }
+    }
+    finder_ratio(&counts)
+        .then(|| (counts, first as f32 + (counts[0] + counts[1]) as f32 + counts[2] as f32 / 2.0))
+}
+
+/// Finder centres, found scanning rows for the 1:1:3:1:1 run pattern and
+/// confirmed down the column and back across the row.
+fn find_finders(ink: &Ink) -> Vec<Finder> {
+    let mut finders: Vec<Finder> = vec![];
+
+    for y in 0..ink.height {
+        let mut counts = [0usize; 5];
+        let mut run = 0;
+        for x in 0..=ink.width {
+            let dark = ink.at(x, y).unwrap_or(false);
+            if dark == (run % 2 == 0) {
+                counts[run] += 1;
+                continue;
+            }
+            if run < 4 {
+                if counts[run] > 0 || run % 2 == 1 {
+                    run += 1;
+                    counts[run] += 1;
+                }
+                continue;
+            }
+
+            // Five runs ended at x
+            if finder_ratio(&counts) {
+                let centre_x = (x as usize - counts[4] - counts[3]) as f32 - counts[2] as f32 / 2.0;
+                if let Some(finder) = confirm(ink, centre_x, y, counts.iter().sum()) {
+                    add_finder(&mut finders, finder);
+                }
+            }
+            counts = [counts[2], counts[3], counts[4], 1, 0];
+            run = 3;
+        }
+    }
+    finders
+}
+
+/// A finder at (`x`, `y`) whose horizontal runs total `row_total`, if the
+/// column and the row through its centre agree.
+fn confirm(ink: &Ink, x: f32, y: i64, row_total: usize) -> Option<Finder> {
+    let column = x as i64;
+    let (vertical, centre_y) = runs_through(|i| ink.at(column, y + i))?;
+    let centre_y = y as f32 + centre_y;
+    let row = centre_y as i64;
+    let (horizontal, centre_x) = runs_through(|i| ink.at(column + i, row))?;
+
+    let (vertical, horizontal): (usize, usize) = (vertical.iter().sum(), horizontal.iter().sum());
+    let similar = |a: usize, b: usize| 5 * a.abs_diff(b) < 2 * b;
+    (similar(vertical, row_total) && similar(horizontal, row_total)).then(|| Finder {
+        x: column as f32 + centre_x,
+        y: centre_y,
+        module: (vertical + horizontal) as f32 / 14.0,
+        hits: 1,
+    })
+}
+
+/// Folds `finder` into a nearby one already found, or adds it.
+fn add_finder(finders: &mut Vec<Finder>, finder: Finder) {
+    let same = finders.iter_mut().find(|f| {
+        (f.x - finder.x).abs() <= f.module
+            && (f.y - finder.y).abs() <= f.module
+            && (f.module - finder.module).abs() <= f.module.max(1.0)
+    });
+    match same {
+        Some(f) => {
+            let n = f.hits as f32;
+            f.x = (f.x * n + finder.x) / (n + 1.0);
+            f.y = (f.y * n + finder.y) / (n + 1.0);
+            f.module = (f.module * n + finder.module) / (n + 1.0);
+            f.hits += 1;
+        }
+        None => finders.push(finder),
+    }
+}
+
+/// The three finders most like the corners of one code: two equal sides at a
+/// right angle, with modules of similar size. Returns their indices too.
+fn pick_corners(finders: &[Finder]) -> Option<(Corners, Vec<usize>)> {
+    // Text and edges rarely pass the cross checks more than once
+    let mut candidates: Vec<usize> = (0..finders.len()).filter(|&i| finders[i].hits >= 2).collect();
+    candidates.sort_by_key(|&i| std::cmp::Reverse(finders[i].hits));
+    candidates.truncate(12);
+
+    let mut best: Option<(f32, [usize; 3])> = None;
+    for (ai, &a) in candidates.iter().enumerate() {
+        for (bi, &b) in candidates.iter().enumerate().skip(ai + 1) {
+            for &c in candidates.iter().skip(bi + 1) {
+                let [fa, fb, fc] = [finders[a], finders[b], finders[c]];
+                let modules = [fa.module, fb.module, fc.module];
+                let (min, max) = modules.iter().fold((f32::MAX, 0f32), |(lo, hi), &m| (lo.min(m), hi.max(m)));
+                if max > min * 1.5 {
+                    continue;
+                }
+
+                // The corner opposite the longest side is the top left
+                let mut sides = [(fb.distance(&fc), a), (fa.distance(&fc), b), (fa.distance(&fb), c)];
+                sides.sort_by(|x, y| x.0.total_cmp(&y.0));
+                let [(short, _), (long, _), (hypotenuse, _)] = sides;
+                if short < 10.0 * max {
+                    continue;
+                }
+                let score = (long - short) / long + (hypotenuse - (short * short + long * long).sqrt()).abs() / hypotenuse;
+                if score < 0.4 && best.is_none_or(|(s, _)| score < s) {
+                    best = Some((score, [sides[2].1, sides[0].1, sides[1].1]));
+                }
+            }
+        }
+    }
+
+    let (_, [corner, p, q]) = best?;
+    let (top_left, mut top_right, mut bottom_left) = (finders[corner], finders[p], finders[q]);
+    // Top right is clockwise from the top left, y pointing down
+    let cross = (top_right.x - top_left.x) * (bottom_left.y - top_left.y)
+        - (top_right.y - top_left.y) * (bottom_left.x - top_left.x);
+    if cross < 0.0 {
+        std::mem::swap(&mut top_right, &mut bottom_left);
+    }
+
+    let corners = Corners {
+        top_left: (top_left.x, top_left.y),
+        top_right: (top_right.x, top_right.y),
+        bottom_left: (bottom_left.x, bottom_left.y),
+        module: (top_left.module + top_right.module + bottom_left.module) / 3.0,
+    };
+    Some((corners, vec![corner, p, q]))
+}
+
+fn size_of(version: usize) -> usize {
+    version * 4 + 17
+}
+
+/// Reads the code the finders at `corners` belong to, trying the version
+/// their spacing suggests and its neighbours.
+fn decode_at(ink: &Ink, corners: &Corners) -> Option<String> {
+    let distance = |a: (f32, f32), b: (f32, f32)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+    let across = (distance(corners.top_left, corners.top_right) + distance(corners.top_left, corners.bottom_left))
+        / 2.0
+        / corners.module;
+    let estimate = ((across + 7.0 - 17.0) / 4.0).round().clamp(1.0, 40.0) as usize;
+
+    for version in [estimate, estimate.saturating_sub(1), estimate + 1] {
+        if !(1..=40).contains(&version) {
+            continue;
+        }
+        let grid = sample(ink, corners, version);
+        let grid = match grid.version_info() {
+            Some(read) if read != version => sample(ink, corners, read),
+            _ => grid,
+        };
+        if let Some(text) = decode_grid(&grid) {
+            return Some(text);
+        }
+    }
+    None
+}
+
+/// Samples the modules of a `version` code, mapping module space onto the
+/// image through the three finders and, from version 2, the bottom right
+/// alignment pattern.
+fn sample(ink: &Ink, corners: &Corners, version: usize) -> Grid {
+    let size = size_of(version);
+    let span = size as f32 - 7.0;
+    let (tl, tr, bl) = (corners.top_left, corners.top_right, corners.bottom_left);
+    let step_x = ((tr.0 - tl.0) / span, (tr.1 - tl.1) / span);
+    let step_y = ((bl.0 - tl.0) / span, (bl.1 - tl.1) / span);
+
+    let far = size as f32 - 3.5;
+    let (fourth_module, fourth_image) = match find_alignment(ink, corners, version, step_x, step_y) {
+        Some(found) => ((far - 3.0, far - 3.0), found),
+        // No alignment pattern to go by; assume no perspective
+        None => ((far, far), (tr.0 + bl.0 - tl.0, tr.1 + bl.1 - tl.1)),
+    };
+    let to_image = Homography::between(
+        &[(3.5, 3.5), (far, 3.5), (3.5, far), fourth_module],
+        &[tl, tr, bl, fourth_image],
+    );
+
+    let modules = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    let Some(to_image) = &to_image else {
+                        return false;
+                    };
+                    let (x, y) = to_image.map(col as f32 + 0.5, row as f32 + 0.5);
+                    ink.at(x.floor() as i64, y.floor() as i64).unwrap_or(false)
+                })
+                .collect()
+        })
+        .collect();
+    Grid::new(modules)
+}
+
+/// The centre of the alignment pattern nearest the bottom right corner,
+/// searched for around where the finders put it.
+fn find_alignment(
+    ink: &Ink,
+    corners: &Corners,
+    version: usize,
+    step_x: (f32, f32),
+    step_y: (f32, f32),
+) -> Option<(f32, f32)> {
+    if version < 2 {
+        return None;
+    }
+    let modules_from_finder = size_of(version) as f32 - 10.0;
+    let tl = corners.top_left;
+    let expected = (
+        tl.0 + (step_x.0 + step_y.0) * modules_from_finder,
+        tl.1 + (step_x.1 + step_y.1) * modules_from_finder,
+    );
+
+    // Dark centre, light ring, dark border
+    let score = |centre: (f32, f32)| {
+        let mut matches = 0;
+        for j in -2i32..=2 {
+            for i in -2i32..=2 {
+                let x = centre.0 + i as f32 * step_x.0 + j as f32 * step_y.0;
+                let y = centre.1 + i as f32 * step_x.1 + j as f32 * step_y.1;
+                let dark = i.abs().max(j.abs()) != 1;
+                if ink.at(x.floor() as i64, y.floor() as i64) == Some(dark) {
+                    matches += 1;
+                }
+            }
+        }
+        matches
+    };
+
+    let mut best = (0, vec![]);
+    for b in -16i32..=16 {
+        for a in -16i32..=16 {
+            let (a, b) = (a as f32 / 4.0, b as f32 / 4.0);
+            let centre = (
+                expected.0 + a * step_x.0 + b * step_y.0,
+                expected.1 + a * step_x.1 + b * step_y.1,
+            );
+            let s = score(centre);
+            if s > best.0 {
+                best = (s, vec![centre]);
+            } else if s == best.0 {
+                best.1.push(centre);
+            }
+        }
+    }
+
+    let (score, centres) = best;
+    if score < 23 {
+        return None;
+    }
+    let n = centres.len() as f32;
+    Some((
+        centres.iter().map(|c| c.0).sum::<f32>() / n,
+        centres.iter().map(|c| c.1).sum::<f32>() / n,
+    ))
+}
+
+/// A sampled code: `true` modules are dark.
+struct Grid {
+    size: usize,
+    modules: Vec<Vec<bool>>,
+}
+
+impl Grid {
+    fn new(modules: Vec<Vec<bool>>) -> Self {
+        Self {
+            size: modules.len(),
+            modules,
+        }
+    }
+
+    fn at(&self, col: usize, row: usize) -> bool {
+        self.modules[row][col]
+    }
+
+    fn version(&self) -> usize {
+        (self.size - 17) / 4
+    }
+
+    /// Error correction level (0 to 3 for L, M, Q, H) and mask, from
+    /// whichever copy of the format information reads closer to a valid one.
+    fn format(&self) -> Option<(usize, usize)> {
+        let size = self.size;
+        let mut first = 0u32;
+        let mut second = 0u32;
+        // Bit 14 first
+        for col in (0..6).chain([7, 8]) {
+            first = first << 1 | self.at(col, 8) as u32;
+        }
+        first = first << 1 | self.at(8, 7) as u32;
+        for row in (0..6).rev() {
+            first = first << 1 | self.at(8, row) as u32;
+        }
+        for row in (size - 7..size).rev() {
+            second = second << 1 | self.at(8, row) as u32;
+        }
+        for col in size - 8..size {
+            second = second << 1 | self.at(col, 8) as u32;
+        }
+
+        let (data, distance) = (0..32u32)
+            .map(|data| {
+                let code = bch_code(data, 10, 0x537) ^ 0x5412;
+                (data, (code ^ first).count_ones().min((code ^ second).count_ones()))
+            })
+            .min_by_key(|&(_, distance)| distance)?;
+        if distance > MAX_INFO_BIT_ERRORS {
+            return None;
+        }
+        // Levels are coded M, L, H, Q
+        let level = [1, 0, 3, 2][(data >> 3) as usize];
+        Some((level, (data & 7) as usize))
+    }
+
+    /// The version the version information blocks give, from version 7 on.
+    fn version_info(&self) -> Option<usize> {
+        if self.version() < 7 {
+            return None;
+        }
+        let size = self.size;
+        let (mut below, mut beside) = (0u32, 0u32);
+        for i in (0..18).rev() {
+            let (a, b) = (size - 11 + i % 3, i / 3);
+            below = below << 1 | self.at(b, a) as u32;
+            beside = beside << 1 | self.at(a, b) as u32;
+        }
+
+        let (version, distance) = (7..=40u32)
+            .map(|version| {
+                let code = bch_code(version, 12, 0x1F25);
+                (version, (code ^ below).count_ones().min((code ^ beside).count_ones()))
+            })
+            .min_by_key(|&(_, distance)| distance)?;
+        (distance <= MAX_INFO_BIT_ERRORS).then_some(version as usize)
+    }
+
+    /// Finder, timing, alignment, format and version modules, which carry no
+    /// data.
+    fn function_modules(&self) -> Vec<Vec<bool>> {
+        let size = self.size;
+        let version = self.version();
+        let mut function = vec![vec![false; size]; size];
+        let mut mark = |col: usize, row: usize, width: usize, height: usize| {
+            for line in &mut function[row..row + height] {
+                line[col..col + width].fill(true);
+            }
+        };
+
+        // Finders with their separators, and the format information
+        mark(0, 0, 9, 9);
+        mark(size - 8, 0, 8, 9);
+        mark(0, size - 8, 9, 8);
+        // Timing
+        mark(6, 0, 1, size);
+        mark(0, 6, size, 1);
+
+        let positions = alignment_positions(version);
+        for &row in &positions {
+            for &col in &positions {
+                let on_finder = (row == 6 && (col == 6 || col == size - 7)) || (row == size - 7 && col == 6);
+                if !on_finder {
+                    mark(col - 2, row - 2, 5, 5);
+                }
+            }
+        }
+
+        if version >= 7 {
+            mark(size - 11, 0, 3, 6);
+            mark(0, size - 11, 6, 3);
+        }
+        function
+    }
+
+    /// Data and error correction codewords, unmasked, in zigzag order.
+    fn codewords(&self, mask: usize) -> Vec<u8> {
+        let size = self.size;
+        let function = self.function_modules();
+        let mut bits = vec![];
+
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vertical in 0..size {
+                let row = if upward { size - 1 - vertical } else { vertical };
+                for col in [right, right - 1] {
+                    if !function[row][col] {
+                        bits.push(self.at(col, row) ^ masked(mask, col, row));
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+
+        bits.chunks_exact(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, &bit| acc << 1 | bit as u8))
+            .collect()
+    }
+}
+
+/// `data` followed by its BCH remainder, as format and version information
+/// are coded.
+fn bch_code(data: u32, remainder_bits: u32, generator: u32) -> u32 {
+    let mut remainder = data;
+    for _ in 0..remainder_bits {
+        remainder = (remainder << 1) ^ ((remainder >> (remainder_bits - 1)) * generator);
+    }
+    data << remainder_bits | remainder
+}
+
+fn masked(mask: usize, col: usize, row: usize) -> bool {
+    let (x, y) = (col, row);
+    match mask {
+        0 => (x + y) % 2 == 0,
+        1 => y % 2 == 0,
+        2 => x % 3 == 0,
+        3 => (x + y) % 3 == 0,
+        4 => (x / 3 + y / 2) % 2 == 0,
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3) % 2 == 0,
+        _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+    }
+}
+
+/// Rows (and columns) of the alignment pattern centres.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+    let count = version / 7 + 2;
+    let step = if version == 32 { 26 } else { (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2 };
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size_of(version) - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+fn raw_codewords(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules / 8
+}
+
+fn decode_grid(grid: &Grid) -> Option<String> {
+    let version = grid.version();
+    let (level, mask) = grid.format()?;
+    let codewords = grid.codewords(mask);
+
+    // Undo the interleaving: data codewords go round the blocks in turn, the
+    // long blocks' extra one last, then the error correction codewords
+    let block_count = ECC_BLOCKS[level][version] as usize;
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[level][version] as usize;
+    let raw = raw_codewords(version);
+    let short_blocks = block_count - raw % block_count;
+    let short_len = raw / block_count;
+    let mut blocks: Vec<Vec<u8>> = vec![vec![]; block_count];
+    let mut next = codewords.iter();
+    for i in 0..=short_len {
+        for (j, block) in blocks.iter_mut().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                block.push(*next.next()?);
+            }
+        }
+    }
+
+    let mut data = vec![];
+    for block in &mut blocks {
+        reed_solomon::correct(block, ecc_len)?;
+        data.extend_from_slice(&block[..block.len() - ecc_len]);
+    }
+    read_segments(&data, version)
+}
+
+/// Reads bits most significant first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.position
+    }
+
+    fn read(&mut self, bits: usize) -> Option<u32> {
+        if bits > self.remaining() {
+            return None;
+        }
+        let mut value = 0u32;
+        for _ in 0..bits {
+            let byte = self.bytes[self.position / 8];
+            value = value << 1 | ((byte >> (7 - self.position % 8)) & 1) as u32;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Text of the data segments: numeric, alphanumeric and byte, the latter as
+/// UTF-8 unless an ECI or invalid UTF-8 says Latin-1.
+fn read_segments(data: &[u8], version: usize) -> Option<String> {
+    let size_class = match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    };
+    let mut reader = BitReader { bytes: data, position: 0 };
+    let mut text = String::new();
+    let mut latin1 = false;
+
+    while reader.remaining() >= 4 {
+        match reader.read(4)? {
+            0 => break,
+            // Numeric
+            1 => {
+                let mut count = reader.read([10, 12, 14][size_class])? as usize;
+                while count > 0 {
+                    let digits = count.min(3);
+                    let value = reader.read([4, 7, 10][digits - 1])?;
+                    if value >= 10u32.pow(digits as u32) {
+                        return None;
+                    }
+                    text.push_str(&format!("{:0width$}", value, width = digits));
+                    count -= digits;
+                }
+            }
+            // Alphanumeric
+            2 => {
+                let mut count = reader.read([9, 11, 13][size_class])? as usize;
+                while count >= 2 {
+                    let value = reader.read(11)? as usize;
+                    text.push(*ALPHANUMERIC.get(value / 45)? as char);
+                    text.push(*ALPHANUMERIC.get(value % 45)? as char);
+                    count -= 2;
+                }
+                if count == 1 {
+                    text.push(*ALPHANUMERIC.get(reader.read(6)? as usize)? as char);
+                }
+            }
+            // Structured append: position and parity, not needed for one code
+            3 => {
+                reader.read(16)?;
+            }
+            // Byte
+            4 => {
+                let count = reader.read([8, 16, 16][size_class])? as usize;
+                let bytes: Vec<u8> = (0..count).map(|_| reader.read(8).map(|b| b as u8)).collect::<Option<_>>()?;
+                match (latin1, String::from_utf8(bytes.clone())) {
+                    (false, Ok(utf8)) => text.push_str(&utf8),
+                    _ => text.extend(bytes.iter().map(|&b| b as char)),
+                }
+            }
+            // FNC1 in first position carries nothing
+            5 => {}
+            // Extended channel interpretation
+            7 => {
+                let designator = match reader.read(1)? {
+                    0 => reader.read(7)?,
+                    _ => match reader.read(1)? {
+                        0 => reader.read(14)?,
+                        _ => {
+                            reader.read(1)?;
+                            reader.read(21)?
+                        }
+                    },
+                };
+                latin1 = matches!(designator, 1 | 3);
+            }
+            // FNC1 in second position: an application indicator
+            9 => {
+                reader.read(8)?;
+            }
+            // Kanji and anything else
+            _ => return None,
+        }
+    }
+    Some(text)
+}
@@ -0,0 +1,160 @@
+//! Reed-Solomon error correction over GF(256) as QR codes use it: field
+//! polynomial x^8 + x^4 + x^3 + x^2 + 1, generator roots α^0 to α^(n-1).
+
+const FIELD_POLYNOMIAL: u16 = 0x11D;
+
+struct Tables {
+    /// α^i, twice over so products of logs need no reduction
+    exp: [u8; 510],
+    log: [u8; 256],
+}
+
+const TABLES: Tables = build_tables();
+
+const fn build_tables() -> Tables {
+    let mut exp = [0u8; 510];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= FIELD_POLYNOMIAL;
+        }
+        i += 1;
+    }
+    while i < 510 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    Tables { exp, log }
+}
+
+fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    TABLES.exp[TABLES.log[a as usize] as usize + TABLES.log[b as usize] as usize]
+}
+
+fn div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    TABLES.exp[TABLES.log[a as usize] as usize + 255 - TABLES.log[b as usize] as usize]
+}
+
+fn alpha_pow(power: usize) -> u8 {
+    TABLES.exp[power % 255]
+}
+
+/// Value at `x` of the polynomial with coefficients `poly`, lowest degree
+/// first.
+fn eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().rev().fold(0, |acc, &coefficient| mul(acc, x) ^ coefficient)
+}
+
+/// Corrects `block`, data then `ecc_len` error correction codewords, in
+/// place. Returns how many codewords were wrong, or `None` when there were
+/// more than the code can correct.
+pub fn correct(block: &mut [u8], ecc_len: usize) -> Option<usize> {
+    let n = block.len();
+    if ecc_len == 0 || ecc_len >= n || n > 255 {
+        return None;
+    }
+
+    // The block is a polynomial with its first codeword as the highest
+    // coefficient
+    let syndromes: Vec<u8> = (0..ecc_len)
+        .map(|i| {
+            let root = alpha_pow(i);
+            block.iter().fold(0, |acc, &codeword| mul(acc, root) ^ codeword)
+        })
+        .collect();
+    if syndromes.iter().all(|&s| s == 0) {
+        return Some(0);
+    }
+
+    let locator = berlekamp_massey(&syndromes);
+    let errors = locator.len() - 1;
+    if errors * 2 > ecc_len {
+        return None;
+    }
+
+    // Chien search: codeword k carries degree n - 1 - k, and is wrong when
+    // the locator vanishes at that degree's inverse root
+    let positions: Vec<usize> = (0..n)
+        .filter(|&k| eval(&locator, alpha_pow(255 - (n - 1 - k) % 255)) == 0)
+        .collect();
+    if positions.len() != errors {
+        return None;
+    }
+
+    // Forney: the evaluator is S(x)Λ(x) mod x^ecc_len
+    let mut evaluator = vec![0u8; ecc_len];
+    for (i, &s) in syndromes.iter().enumerate() {
+        for (j, &l) in locator.iter().enumerate() {
+            if i + j < ecc_len {
+                evaluator[i + j] ^= mul(s, l);
+            }
+        }
+    }
+    let derivative: Vec<u8> = locator
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &l)| if i % 2 == 1 { l } else { 0 })
+        .collect();
+
+    for &k in &positions {
+        let x = alpha_pow(n - 1 - k);
+        let x_inverse = div(1, x);
+        let denominator = eval(&derivative, x_inverse);
+        if denominator == 0 {
+            return None;
+        }
+        block[k] ^= mul(x, div(eval(&evaluator, x_inverse), denominator));
+    }
+    Some(errors)
+}
+
+/// The error locator polynomial, lowest degree first, trimmed to its degree.
+fn berlekamp_massey(syndromes: &[u8]) -> Vec<u8> {
+    let mut locator = vec![1u8];
+    let mut previous = vec![1u8];
+    let mut degree = 0;
+    let mut shift = 1;
+    let mut previous_discrepancy = 1u8;
+
+    for step in 0..syndromes.len() {
+        let discrepancy = (1..=degree)
+            .filter(|&i| i < locator.len())
+            .fold(syndromes[step], |acc, i| acc ^ mul(locator[i], syndromes[step - i]));
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = div(discrepancy, previous_discrepancy);
+        let mut next = locator.clone();
+        next.resize(next.len().max(previous.len() + shift), 0);
+        for (i, &p) in previous.iter().enumerate() {
+            next[i + shift] ^= mul(scale, p);
+        }
+
+        if 2 * degree <= step {
+            degree = step + 1 - degree;
+            previous = std::mem::replace(&mut locator, next);
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            locator = next;
+            shift += 1;
+        }
+    }
+
+    locator.truncate(degree + 1);
+    locator
+}
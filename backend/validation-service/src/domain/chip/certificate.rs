@@ -0,0 +1,327 @@
+//! X.509 certificates of the ePassport PKI (ICAO 9303 part 12): each
+//! country's signing CA (CSCA) certifies its document signers, and a
+//! document signer signs the security object on every chip it issues.
+
+use chrono::NaiveDate;
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::{Pkcs1v15Sign, Pss, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+
+use super::security_object::HashAlgorithm;
+use super::tlv::{self, malformed, Tlv};
+use shared::AlbergueResult;
+
+const SEQUENCE: u32 = 0x30;
+const INTEGER: u32 = 0x02;
+const BIT_STRING: u32 = 0x03;
+const OBJECT_IDENTIFIER: u32 = 0x06;
+const UTC_TIME: u32 = 0x17;
+const GENERALIZED_TIME: u32 = 0x18;
+const EXPLICIT_0: u32 = 0xA0;
+const EXPLICIT_2: u32 = 0xA2;
+
+const RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const RSASSA_PSS: &str = "1.2.840.113549.1.1.10";
+const EC_PUBLIC_KEY: &str = "1.2.840.10045.2.1";
+const P256: &str = "1.2.840.10045.3.1.7";
+const P384: &str = "1.3.132.0.34";
+
+/// An algorithm OID and its parameters, DER, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgorithmIdentifier {
+    pub oid: String,
+    pub parameters: Option<Vec<u8>>,
+}
+
+impl AlgorithmIdentifier {
+    pub fn parse(identifier: &Tlv) -> AlbergueResult<Self> {
+        let fields = identifier.children()?;
+        let oid = fields
+            .first()
+            .filter(|f| f.tag == OBJECT_IDENTIFIER)
+            .and_then(|f| tlv::object_identifier(f.value))
+            .ok_or_else(|| malformed("bad algorithm identifier"))?;
+        Ok(Self {
+            oid,
+            // An explicit NULL is the same as none
+            parameters: fields.get(1).filter(|p| p.tag != 0x05).map(|p| p.raw.to_vec()),
+        })
+    }
+}
+
+/// One certificate, split into what chains are checked on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Certificate {
+    /// The signed part, DER
+    tbs: Vec<u8>,
+    pub serial_number: Vec<u8>,
+    /// Issuer and subject names, DER, compared byte for byte
+    pub issuer: Vec<u8>,
+    pub subject: Vec<u8>,
+    pub not_before: NaiveDate,
+    pub not_after: NaiveDate,
+    key_algorithm: AlgorithmIdentifier,
+    /// The subject public key, without its leading unused-bits byte
+    key: Vec<u8>,
+    signature_algorithm: AlgorithmIdentifier,
+    signature: Vec<u8>,
+}
+
+impl Certificate {
+    pub fn parse(der: &[u8]) -> AlbergueResult<Self> {
+        let (certificate, _) = tlv::parse_one(der)?;
+        let [tbs, signature_algorithm, signature] = certificate.children()?[..] else {
+            return Err(malformed("certificate is not three parts"));
+        };
+
+        // The version comes first, and is left out for v1
+        let fields: Vec<Tlv> = tbs.children()?.into_iter().skip_while(|f| f.tag == EXPLICIT_0).collect();
+        let [serial_number, _, issuer, validity, subject, public_key, ..] = fields[..] else {
+            return Err(malformed("certificate is incomplete"));
+        };
+        if serial_number.tag != INTEGER || issuer.tag != SEQUENCE || subject.tag != SEQUENCE {
+            return Err(malformed("certificate fields out of order"));
+        }
+        let [not_before, not_after] = validity.children()?[..] else {
+            return Err(malformed("certificate validity is not two times"));
+        };
+        let [key_algorithm, key] = public_key.children()?[..] else {
+            return Err(malformed("subject public key info is not two parts"));
+        };
+
+        Ok(Self {
+            tbs: tbs.raw.to_vec(),
+            serial_number: serial_number.value.to_vec(),
+            issuer: issuer.raw.to_vec(),
+            subject: subject.raw.to_vec(),
+            not_before: time(&not_before)?,
+            not_after: time(&not_after)?,
+            key_algorithm: AlgorithmIdentifier::parse(&key_algorithm)?,
+            key: bit_string(&key)?.to_vec(),
+            signature_algorithm: AlgorithmIdentifier::parse(&signature_algorithm)?,
+            signature: bit_string(&signature)?.to_vec(),
+        })
+    }
+
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        (self.not_before..=self.not_after).contains(&date)
+    }
+
+    /// Whether `issuer` names and signed this certificate.
+    pub fn is_issued_by(&self, issuer: &Certificate) -> bool {
+        self.issuer == issuer.subject
+            && issuer
+                .verify(&self.tbs, &self.signature_algorithm, None, &self.signature)
+                .is_ok()
+    }
+
+    /// Checks that this certificate's key made `signature` over `message`.
+    /// `hash` is for algorithms such as plain rsaEncryption that leave the
+    /// digest to be named elsewhere. Errors say why it does not check out.
+    pub fn verify(
+        &self,
+        message: &[u8],
+        algorithm: &AlgorithmIdentifier,
+        hash: Option<HashAlgorithm>,
+        signature: &[u8],
+    ) -> Result<(), String> {
+        let (scheme, hash) = Scheme::of(algorithm, hash)?;
+        let digest = hash.digest(message);
+        let checked = match (self.public_key()?, scheme) {
+            (PublicKey::Rsa(key), Scheme::Pkcs1) => key.verify(pkcs1(hash), &digest, signature).is_ok(),
+            (PublicKey::Rsa(key), Scheme::Pss { salt_length }) => {
+                key.verify(pss(hash, salt_length), &digest, signature).is_ok()
+            }
+            (PublicKey::P256(key), Scheme::Ecdsa) => p256::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(&digest, &signature).is_ok()),
+            (PublicKey::P384(key), Scheme::Ecdsa) => p384::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(&digest, &signature).is_ok()),
+            _ => return Err(format!("a {} key cannot check a {} signature", self.key_algorithm.oid, algorithm.oid)),
+        };
+        if checked {
+            Ok(())
+        } else {
+            Err("the signature does not match".to_string())
+        }
+    }
+
+    fn public_key(&self) -> Result<PublicKey, String> {
+        match self.key_algorithm.oid.as_str() {
+            RSA_ENCRYPTION | RSASSA_PSS => RsaPublicKey::from_pkcs1_der(&self.key)
+                .map(PublicKey::Rsa)
+                .map_err(|e| format!("bad RSA key: {}", e)),
+            EC_PUBLIC_KEY => {
+                // Named curves only; explicit parameters would need matching
+                // against every curve's constants
+                let curve = self
+                    .key_algorithm
+                    .parameters
+                    .as_deref()
+                    .and_then(|p| tlv::parse_one(p).ok())
+                    .filter(|(p, _)| p.tag == OBJECT_IDENTIFIER)
+                    .and_then(|(p, _)| tlv::object_identifier(p.value))
+                    .ok_or("EC keys with explicit curve parameters are not supported")?;
+                let bad_point = |e| format!("bad EC key: {}", e);
+                match curve.as_str() {
+                    P256 => p256::ecdsa::VerifyingKey::from_sec1_bytes(&self.key)
+                        .map(PublicKey::P256)
+                        .map_err(bad_point),
+                    P384 => p384::ecdsa::VerifyingKey::from_sec1_bytes(&self.key)
+                        .map(PublicKey::P384)
+                        .map_err(bad_point),
+                    other => Err(format!("unsupported curve {}", other)),
+                }
+            }
+            other => Err(format!("unsupported key algorithm {}", other)),
+        }
+    }
+}
+
+/// The CSCA certificates document signers are checked against, as a
+/// country's master list or the ICAO PKD publishes them.
+#[derive(Debug, Clone, Default)]
+pub struct CscaList {
+    certificates: Vec<Certificate>,
+}
+
+impl CscaList {
+    /// Reads every `CERTIFICATE` block of a PEM bundle.
+    pub fn from_pem(pem: &str) -> AlbergueResult<Self> {
+        let mut certificates = vec![];
+        let mut block: Option<String> = None;
+        for line in pem.lines().map(str::trim) {
+            match (line, block.as_mut()) {
+                ("-----BEGIN CERTIFICATE-----", None) => block = Some(String::new()),
+                ("-----END CERTIFICATE-----", Some(encoded)) => {
+                    let der = base64::decode(&*encoded).map_err(|e| malformed(format!("bad PEM certificate: {}", e)))?;
+                    certificates.push(Certificate::parse(&der)?);
+                    block = None;
+                }
+                (line, Some(encoded)) => encoded.push_str(line),
+                _ => {}
+            }
+        }
+        Ok(Self { certificates })
+    }
+
+    pub fn add(&mut self, certificate: Certificate) {
+        self.certificates.push(certificate);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// A CSCA, valid on `date`, that issued `certificate`. Several may share
+    /// a name across key rollovers, so each is tried.
+    pub fn issuer_of(&self, certificate: &Certificate, date: NaiveDate) -> Option<&Certificate> {
+        self.certificates
+            .iter()
+            .filter(|csca| csca.is_valid_on(date))
+            .find(|csca| certificate.is_issued_by(csca))
+    }
+}
+
+enum PublicKey {
+    Rsa(RsaPublicKey),
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scheme {
+    Pkcs1,
+    Pss { salt_length: usize },
+    Ecdsa,
+}
+
+impl Scheme {
+    /// The scheme and digest a signature `algorithm` stands for.
+    fn of(algorithm: &AlgorithmIdentifier, hash: Option<HashAlgorithm>) -> Result<(Self, HashAlgorithm), String> {
+        let named = |scheme: Scheme| hash.map(|h| (scheme, h)).ok_or(format!("{} names no digest", algorithm.oid));
+        match algorithm.oid.as_str() {
+            "1.2.840.113549.1.1.5" => Ok((Self::Pkcs1, HashAlgorithm::Sha1)),
+            "1.2.840.113549.1.1.14" => Ok((Self::Pkcs1, HashAlgorithm::Sha224)),
+            "1.2.840.113549.1.1.11" => Ok((Self::Pkcs1, HashAlgorithm::Sha256)),
+            "1.2.840.113549.1.1.12" => Ok((Self::Pkcs1, HashAlgorithm::Sha384)),
+            "1.2.840.113549.1.1.13" => Ok((Self::Pkcs1, HashAlgorithm::Sha512)),
+            "1.2.840.10045.4.1" => Ok((Self::Ecdsa, HashAlgorithm::Sha1)),
+            "1.2.840.10045.4.3.1" => Ok((Self::Ecdsa, HashAlgorithm::Sha224)),
+            "1.2.840.10045.4.3.2" => Ok((Self::Ecdsa, HashAlgorithm::Sha256)),
+            "1.2.840.10045.4.3.3" => Ok((Self::Ecdsa, HashAlgorithm::Sha384)),
+            "1.2.840.10045.4.3.4" => Ok((Self::Ecdsa, HashAlgorithm::Sha512)),
+            RSA_ENCRYPTION => named(Self::Pkcs1),
+            EC_PUBLIC_KEY => named(Self::Ecdsa),
+            RSASSA_PSS => Self::pss(algorithm.parameters.as_deref())
+                .map_err(|e| format!("bad RSASSA-PSS parameters: {}", e)),
+            other => Err(format!("unsupported signature algorithm {}", other)),
+        }
+    }
+
+    /// `RSASSA-PSS-params ::= SEQUENCE { [0] hashAlgorithm DEFAULT sha1,
+    /// [1] maskGenAlgorithm, [2] saltLength DEFAULT 20, ... }`. The mask
+    /// is taken to use the same digest, as ICAO 9303 requires.
+    fn pss(parameters: Option<&[u8]>) -> AlbergueResult<(Self, HashAlgorithm)> {
+        let mut hash = HashAlgorithm::Sha1;
+        let mut salt_length = 20;
+        if let Some(parameters) = parameters {
+            let (parameters, _) = tlv::parse_one(parameters)?;
+            if let Some(identifier) = parameters.child(EXPLICIT_0)? {
+                let oid = AlgorithmIdentifier::parse(&identifier.expect_child(SEQUENCE)?)?.oid;
+                hash = HashAlgorithm::from_oid(&oid)
+                    .ok_or_else(|| malformed(format!("unsupported hash algorithm {}", oid)))?;
+            }
+            if let Some(length) = parameters.child(EXPLICIT_2)? {
+                salt_length = tlv::unsigned(length.expect_child(INTEGER)?.value)
+                    .ok_or_else(|| malformed("bad salt length"))? as usize;
+            }
+        }
+        Ok((Self::Pss { salt_length }, hash))
+    }
+}
+
+fn pkcs1(hash: HashAlgorithm) -> Pkcs1v15Sign {
+    match hash {
+        HashAlgorithm::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+        HashAlgorithm::Sha224 => Pkcs1v15Sign::new::<Sha224>(),
+        HashAlgorithm::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+        HashAlgorithm::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+        HashAlgorithm::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+    }
+}
+
+fn pss(hash: HashAlgorithm, salt_length: usize) -> Pss {
+    match hash {
+        HashAlgorithm::Sha1 => Pss::new_with_salt::<Sha1>(salt_length),
+        HashAlgorithm::Sha224 => Pss::new_with_salt::<Sha224>(salt_length),
+        HashAlgorithm::Sha256 => Pss::new_with_salt::<Sha256>(salt_length),
+        HashAlgorithm::Sha384 => Pss::new_with_salt::<Sha384>(salt_length),
+        HashAlgorithm::Sha512 => Pss::new_with_salt::<Sha512>(salt_length),
+    }
+}
+
+/// The content of a `BIT STRING` of whole bytes.
+fn bit_string<'a>(object: &Tlv<'a>) -> AlbergueResult<&'a [u8]> {
+    match object.value {
+        [0, bits @ ..] if object.tag == BIT_STRING => Ok(bits),
+        _ => Err(malformed("bad bit string")),
+    }
+}
+
+/// The day of a `UTCTime` (`YYMMDDhhmmssZ`, years from 1950) or a
+/// `GeneralizedTime` (`YYYYMMDDhhmmssZ`).
+fn time(object: &Tlv) -> AlbergueResult<NaiveDate> {
+    let text = std::str::from_utf8(object.value).map_err(|_| malformed("bad time"))?;
+    let digits = |range: std::ops::Range<usize>| text.get(range).and_then(|d| d.parse::<u32>().ok());
+    let date = match object.tag {
+        UTC_TIME => digits(0..2).and_then(|yy| {
+            let year = if yy < 50 { 2000 + yy } else { 1900 + yy };
+            NaiveDate::from_ymd_opt(year as i32, digits(2..4)?, digits(4..6)?)
+        }),
+        GENERALIZED_TIME => digits(0..4).and_then(|year| NaiveDate::from_ymd_opt(year as i32, digits(4..6)?, digits(6..8)?)),
+        _ => None,
+    };
+    date.ok_or_else(|| malformed(format!("bad time {}", text)))
+}
@@ -0,0 +1,186 @@
+//! The logical data structure files (ICAO 9303 part 10) read off a chip:
+//! EF.COM, DG1 (the MRZ), DG2 (the face) and DG11 (more holder details).
+
+use chrono::{NaiveDate, TimeZone, Utc};
+
+use super::tlv::{self, malformed, Tlv};
+use crate::domain::validators::mrz_validator::MrzFormat;
+use shared::{AlbergueResult, ExtractedData, FaceImageFormat};
+
+const EF_COM_TAG: u32 = 0x60;
+const TAG_LIST: u32 = 0x5C;
+const DG1_TAG: u32 = 0x61;
+const MRZ_TAG: u32 = 0x5F1F;
+const DG2_TAG: u32 = 0x75;
+const BIOMETRIC_GROUP: u32 = 0x7F61;
+const BIOMETRIC_TEMPLATE: u32 = 0x7F60;
+const BIOMETRIC_DATA: u32 = 0x5F2E;
+const DG11_TAG: u32 = 0x6B;
+const FULL_NAME: u32 = 0x5F0E;
+const PERSONAL_NUMBER: u32 = 0x5F10;
+const FULL_BIRTH_DATE: u32 = 0x5F2B;
+const BIRTHPLACE: u32 = 0x5F11;
+const ADDRESS: u32 = 0x5F42;
+
+/// Tag of each data group's file, DG1 to DG16.
+const DATA_GROUP_TAGS: [u8; 16] = [
+    0x61, 0x75, 0x63, 0x76, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70,
+];
+
+/// The data group a file tag stands for.
+pub fn data_group_number(tag: u8) -> Option<u8> {
+    DATA_GROUP_TAGS.iter().position(|t| *t == tag).map(|i| i as u8 + 1)
+}
+
+/// The outer object of an LDS file, checking it is the one expected.
+fn open<'a>(file: &'a [u8], tag: u32, name: &str) -> AlbergueResult<Tlv<'a>> {
+    let (outer, _) = tlv::parse_one(file)?;
+    if outer.tag != tag {
+        return Err(malformed(format!("{} starts with tag {:X}", name, outer.tag)));
+    }
+    Ok(outer)
+}
+
+/// The data groups EF.COM says the chip holds.
+pub fn read_ef_com(file: &[u8]) -> AlbergueResult<Vec<u8>> {
+    let com = open(file, EF_COM_TAG, "EF.COM")?;
+    let list = com.expect_child(TAG_LIST)?;
+    list.value
+        .iter()
+        .map(|&tag| data_group_number(tag).ok_or_else(|| malformed(format!("EF.COM lists tag {:X}", tag))))
+        .collect()
+}
+
+/// The MRZ in DG1, split into its lines.
+pub fn read_dg1(file: &[u8]) -> AlbergueResult<(MrzFormat, Vec<String>)> {
+    let dg1 = open(file, DG1_TAG, "DG1")?;
+    let mrz = std::str::from_utf8(dg1.expect_child(MRZ_TAG)?.value)
+        .map_err(|_| malformed("DG1 MRZ is not text"))?;
+
+    let format = [MrzFormat::TD1, MrzFormat::TD2, MrzFormat::TD3]
+        .into_iter()
+        .find(|f| f.line_count() * f.line_length() == mrz.len())
+        .ok_or_else(|| malformed(format!("DG1 MRZ is {} characters long", mrz.len())))?;
+    let lines = mrz
+        .as_bytes()
+        .chunks(format.line_length())
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect();
+    Ok((format, lines))
+}
+
+/// The holder's face as encoded in DG2.
+#[derive(Clone, PartialEq)]
+pub struct FaceImage {
+    pub format: FaceImageFormat,
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>,
+}
+
+// Holder biometrics; keep the bytes out of logs
+impl std::fmt::Debug for FaceImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaceImage")
+            .field("format", &self.format)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("data", &format_args!("{} bytes", self.data.len()))
+            .finish()
+    }
+}
+
+/// The first face in DG2, an ISO/IEC 19794-5 facial record.
+pub fn read_dg2(file: &[u8]) -> AlbergueResult<FaceImage> {
+    let dg2 = open(file, DG2_TAG, "DG2")?;
+    let record = dg2
+        .expect_child(BIOMETRIC_GROUP)?
+        .expect_child(BIOMETRIC_TEMPLATE)?
+        .expect_child(BIOMETRIC_DATA)?
+        .value;
+
+    // Header: "FAC\0", version, record length and face count
+    if !record.starts_with(b"FAC\0") || record.len() < 14 {
+        return Err(malformed("DG2 holds no facial record"));
+    }
+    let face = &record[14..];
+
+    // Facial information: block length, feature point count and 14 bytes of
+    // pose and appearance, then 8 bytes per feature point
+    let u16_at = |bytes: &[u8], at: usize| bytes.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let block_length = face
+        .get(..4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(|| malformed("DG2 facial record cut short"))?;
+    let feature_points = u16_at(face, 4).ok_or_else(|| malformed("DG2 facial record cut short"))? as usize;
+    let info = 20 + feature_points * 8;
+
+    // Image information: type, data type, width, height and 6 more bytes
+    let format = match face.get(info + 1) {
+        Some(0) => FaceImageFormat::Jpeg,
+        Some(1) => FaceImageFormat::Jpeg2000,
+        Some(other) => return Err(malformed(format!("DG2 image data type {}", other))),
+        None => return Err(malformed("DG2 facial record cut short")),
+    };
+    let width = u16_at(face, info + 2).unwrap_or(0);
+    let height = u16_at(face, info + 4).unwrap_or(0);
+    let data = face
+        .get(info + 12..block_length.min(face.len()))
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| malformed("DG2 holds no image data"))?;
+
+    Ok(FaceImage {
+        format,
+        width,
+        height,
+        data: data.to_vec(),
+    })
+}
+
+/// The holder details DG11 adds: names in full where the MRZ truncates
+/// them, the personal number, birthplace and address.
+pub fn read_dg11(file: &[u8]) -> AlbergueResult<ExtractedData> {
+    let dg11 = open(file, DG11_TAG, "DG11")?;
+    let mut holder = ExtractedData::default();
+
+    for field in dg11.children()? {
+        let Ok(text) = std::str::from_utf8(field.value) else {
+            continue;
+        };
+        let text = text.trim_matches(|c: char| c == '<' || c.is_whitespace());
+        if text.is_empty() {
+            continue;
+        }
+        // Parts are separated by fillers, and the name parts by two
+        let parts = |separator: &str| -> Vec<String> {
+            text.split(separator)
+                .map(|p| p.replace('<', " ").trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        };
+
+        match field.tag {
+            FULL_NAME => {
+                let names = parts("<<");
+                holder.surname = names.first().cloned();
+                holder.name = names.get(1).cloned();
+            }
+            PERSONAL_NUMBER => holder.document_number = Some(text.replace('<', "")),
+            FULL_BIRTH_DATE => {
+                holder.birth_date = NaiveDate::parse_from_str(text, "%Y%m%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|d| Utc.from_utc_datetime(&d));
+            }
+            BIRTHPLACE => holder.birthplace = Some(parts("<").join(", ")),
+            ADDRESS => {
+                let lines = parts("<");
+                holder.address = lines.first().cloned();
+                holder.municipality = lines.get(1).cloned();
+                holder.province = lines.get(2).cloned();
+            }
+            _ => {}
+        }
+    }
+    Ok(holder)
+}
@@ -0,0 +1,159 @@
+pub mod certificate;
+pub mod lds;
+pub mod security_object;
+pub mod tlv;
+
+pub use certificate::{Certificate, CscaList};
+pub use lds::FaceImage;
+pub use security_object::{HashAlgorithm, SecurityObject};
+
+use chrono::{NaiveDate, Utc};
+
+use crate::domain::validators::mrz_validator::{MrzData, MrzValidator};
+use shared::{AlbergueResult, ExtractedData};
+
+/// LDS files as an NFC reader dumped them off a DNIe or ePassport chip.
+#[derive(Debug, Clone, Default)]
+pub struct LdsFiles {
+    pub ef_com: Option<Vec<u8>>,
+    pub dg1: Vec<u8>,
+    pub dg2: Option<Vec<u8>>,
+    pub dg11: Option<Vec<u8>>,
+    pub sod: Option<Vec<u8>>,
+}
+
+impl LdsFiles {
+    /// The data groups sent, by number.
+    fn data_groups(&self) -> Vec<(u8, &[u8])> {
+        [(1, Some(&self.dg1)), (2, self.dg2.as_ref()), (11, self.dg11.as_ref())]
+            .into_iter()
+            .filter_map(|(number, file)| file.map(|f| (number, f.as_slice())))
+            .collect()
+    }
+}
+
+/// How far the security object's signature could be traced.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureCheck {
+    /// No security object came with the dump
+    Missing,
+    /// Not traced to a trusted CSCA, and why
+    Untrusted(String),
+    /// Signed by a document signer a trusted CSCA certified
+    Trusted,
+}
+
+/// What a chip holds about its holder, and how far it can be trusted.
+#[derive(Debug, Clone)]
+pub struct ChipData {
+    /// Data groups the chip holds: those EF.COM lists, or those sent
+    pub data_groups: Vec<u8>,
+    pub mrz: MrzData,
+    /// DG11 details, when sent
+    pub holder: ExtractedData,
+    pub face: Option<FaceImage>,
+    pub signature: SignatureCheck,
+    /// Data groups whose hash matches the security object, whether or not
+    /// its signature could be traced
+    pub matching_groups: Vec<u8>,
+    /// Data groups whose hash does not match, or that it does not list
+    pub altered_groups: Vec<u8>,
+}
+
+impl ChipData {
+    /// Passively authenticated: the hashes match a security object whose
+    /// signature traces to a trusted CSCA, and nothing was altered. Hashes
+    /// alone prove nothing, as anyone can list the hashes of their own files.
+    pub fn is_verified(&self) -> bool {
+        self.signature == SignatureCheck::Trusted && self.altered_groups.is_empty()
+    }
+
+    /// The data groups `is_verified` vouches for.
+    pub fn verified_groups(&self) -> Vec<u8> {
+        if self.signature == SignatureCheck::Trusted {
+            self.matching_groups.clone()
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Reads LDS dumps, checks their data groups against the security object
+/// and traces its signature to the CSCAs it was given.
+pub struct ChipReader {
+    mrz_validator: MrzValidator,
+    cscas: CscaList,
+}
+
+impl Default for ChipReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChipReader {
+    pub fn new() -> Self {
+        Self {
+            mrz_validator: MrzValidator::new(),
+            cscas: CscaList::default(),
+        }
+    }
+
+    pub fn with_cscas(mut self, cscas: CscaList) -> Self {
+        self.cscas = cscas;
+        self
+    }
+
+    pub fn read(&self, files: &LdsFiles) -> AlbergueResult<ChipData> {
+        self.read_at(files, Utc::now().date_naive())
+    }
+
+    /// Reads relative to `today`, which decides the century of the MRZ's
+    /// two-digit years and which certificates are valid.
+    pub fn read_at(&self, files: &LdsFiles, today: NaiveDate) -> AlbergueResult<ChipData> {
+        let sent = files.data_groups();
+        let data_groups = match &files.ef_com {
+            Some(ef_com) => lds::read_ef_com(ef_com)?,
+            None => sent.iter().map(|(number, _)| *number).collect(),
+        };
+
+        // Hashes cover each file exactly as stored, so any padding the
+        // reader added after it is left out
+        let mut matching_groups = vec![];
+        let mut altered_groups = vec![];
+        let mut signature = SignatureCheck::Missing;
+        if let Some(sod) = &files.sod {
+            let security_object = SecurityObject::parse(sod)?;
+            for (number, file) in &sent {
+                let (stored, _) = tlv::parse_one(file)?;
+                if security_object.matches(*number, stored.raw) {
+                    matching_groups.push(*number);
+                } else {
+                    altered_groups.push(*number);
+                }
+            }
+            signature = match security_object.verify_signature(&self.cscas, today) {
+                Ok(()) => SignatureCheck::Trusted,
+                Err(reason) => SignatureCheck::Untrusted(reason),
+            };
+        }
+
+        let (format, lines) = lds::read_dg1(&files.dg1)?;
+        let mrz = self.mrz_validator.parse_lines(format, &lines, today);
+        let holder = match &files.dg11 {
+            Some(dg11) => lds::read_dg11(dg11)?,
+            None => ExtractedData::default(),
+        };
+        let face = files.dg2.as_deref().map(lds::read_dg2).transpose()?;
+
+        Ok(ChipData {
+            data_groups,
+            mrz,
+            holder,
+            face,
+            signature,
+            matching_groups,
+            altered_groups,
+        })
+    }
+}
@@ -0,0 +1,280 @@
+//! EF.SOD, the document security object: a CMS `SignedData` whose content
+//! lists the hash of every data group on the chip (ICAO 9303 part 10).
+
+use chrono::NaiveDate;
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use std::collections::BTreeMap;
+
+use super::certificate::{AlgorithmIdentifier, Certificate, CscaList};
+use super::tlv::{self, malformed, Tlv};
+use shared::AlbergueResult;
+
+const SOD_TAG: u32 = 0x77;
+const SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const LDS_SECURITY_OBJECT: &str = "2.23.136.1.1.1";
+const MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+
+const SEQUENCE: u32 = 0x30;
+const INTEGER: u32 = 0x02;
+const OCTET_STRING: u32 = 0x04;
+const CONSTRUCTED_OCTET_STRING: u32 = 0x24;
+const OBJECT_IDENTIFIER: u32 = 0x06;
+const SET: u32 = 0x31;
+const EXPLICIT_0: u32 = 0xA0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub(super) fn from_oid(oid: &str) -> Option<Self> {
+        match oid {
+            "1.3.14.3.2.26" => Some(Self::Sha1),
+            "2.16.840.1.101.3.4.2.4" => Some(Self::Sha224),
+            "2.16.840.1.101.3.4.2.1" => Some(Self::Sha256),
+            "2.16.840.1.101.3.4.2.2" => Some(Self::Sha384),
+            "2.16.840.1.101.3.4.2.3" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha224 => Sha224::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+/// The data group hashes a security object lists, and the signature over
+/// them. The hashes alone only show the files are the ones the SOD was made
+/// for; [`SecurityObject::verify_signature`] shows who made it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityObject {
+    pub algorithm: HashAlgorithm,
+    /// By data group number
+    pub hashes: BTreeMap<u8, Vec<u8>>,
+    /// The signed LDS security object, DER
+    content: Vec<u8>,
+    /// The document signer certificates the SOD carries
+    pub certificates: Vec<Certificate>,
+    signer: Option<SignerInfo>,
+}
+
+/// `SignerInfo` (RFC 5652) of the document signer.
+#[derive(Debug, Clone, PartialEq)]
+struct SignerInfo {
+    /// Issuer and serial number of its certificate; `None` when it is named
+    /// by key identifier instead
+    certificate: Option<(Vec<u8>, Vec<u8>)>,
+    digest_algorithm: AlgorithmIdentifier,
+    /// The signed attributes, tagged as the `SET` they are signed as
+    signed_attributes: Option<Vec<u8>>,
+    message_digest: Option<Vec<u8>>,
+    signature_algorithm: AlgorithmIdentifier,
+    signature: Vec<u8>,
+}
+
+impl SecurityObject {
+    pub fn parse(sod: &[u8]) -> AlbergueResult<Self> {
+        let (outer, _) = tlv::parse_one(sod)?;
+        if outer.tag != SOD_TAG {
+            return Err(malformed(format!("EF.SOD starts with tag {:X}", outer.tag)));
+        }
+
+        let content_info = outer.expect_child(SEQUENCE)?;
+        if oid_of(&content_info)? != SIGNED_DATA {
+            return Err(malformed("EF.SOD is not CMS signed data"));
+        }
+        let signed_data = content_info.expect_child(EXPLICIT_0)?.expect_child(SEQUENCE)?;
+
+        // version, digestAlgorithms, then the encapsulated content
+        let encapsulated = signed_data
+            .children()?
+            .into_iter()
+            .filter(|c| c.tag == SEQUENCE)
+            .find(|c| oid_of(c).is_ok_and(|oid| oid == LDS_SECURITY_OBJECT))
+            .ok_or_else(|| malformed("EF.SOD holds no LDS security object"))?;
+        let content = octets(&encapsulated.expect_child(EXPLICIT_0)?.children()?)?;
+        let (algorithm, hashes) = Self::parse_lds_security_object(&content)?;
+
+        // Then [0] certificates, [1] CRLs and the signer infos
+        let certificates = match signed_data.child(EXPLICIT_0)? {
+            Some(set) => set
+                .children()?
+                .iter()
+                .map(|c| Certificate::parse(c.raw))
+                .collect::<AlbergueResult<_>>()?,
+            None => vec![],
+        };
+        let signer = match signed_data.children()?.into_iter().rev().find(|c| c.tag == SET) {
+            Some(signer_infos) => signer_infos.children()?.first().map(SignerInfo::parse).transpose()?,
+            None => None,
+        };
+
+        Ok(Self {
+            algorithm,
+            hashes,
+            content,
+            certificates,
+            signer,
+        })
+    }
+
+    /// Traces the signature back to one of `cscas`: the signed attributes
+    /// hold the digest of the hashes, the document signer's key signed
+    /// them, and a CSCA signed the document signer's certificate, both
+    /// certificates being valid on `today`. Errors say where it broke off.
+    pub fn verify_signature(&self, cscas: &CscaList, today: NaiveDate) -> Result<(), String> {
+        let signer = self.signer.as_ref().ok_or("the security object is not signed")?;
+        let digest_algorithm = HashAlgorithm::from_oid(&signer.digest_algorithm.oid)
+            .ok_or_else(|| format!("unsupported digest algorithm {}", signer.digest_algorithm.oid))?;
+
+        let message = match (&signer.signed_attributes, &signer.message_digest) {
+            (Some(attributes), Some(digest)) if *digest == digest_algorithm.digest(&self.content) => attributes,
+            (Some(_), _) => return Err("the signed digest does not match the data group hashes".to_string()),
+            (None, _) => &self.content,
+        };
+
+        // ICAO 9303 has the SOD carry its document signer's certificate
+        let document_signer = match &signer.certificate {
+            Some((issuer, serial_number)) => self
+                .certificates
+                .iter()
+                .find(|c| c.issuer == *issuer && c.serial_number == *serial_number),
+            None => self.certificates.first().filter(|_| self.certificates.len() == 1),
+        }
+        .ok_or("the security object does not carry its signer's certificate")?;
+        document_signer
+            .verify(message, &signer.signature_algorithm, Some(digest_algorithm), &signer.signature)
+            .map_err(|e| format!("document signer: {}", e))?;
+
+        if !document_signer.is_valid_on(today) {
+            return Err(format!(
+                "the document signer certificate is valid from {} to {}",
+                document_signer.not_before, document_signer.not_after
+            ));
+        }
+        if cscas.is_empty() {
+            return Err("no CSCA certificates are configured".to_string());
+        }
+        cscas
+            .issuer_of(document_signer, today)
+            .map(|_| ())
+            .ok_or_else(|| "no trusted CSCA certified the document signer".to_string())
+    }
+
+    /// `LDSSecurityObject ::= SEQUENCE { version, hashAlgorithm,
+    /// dataGroupHashValues SEQUENCE OF { dataGroupNumber, hashValue }, ... }`
+    fn parse_lds_security_object(der: &[u8]) -> AlbergueResult<(HashAlgorithm, BTreeMap<u8, Vec<u8>>)> {
+        let (object, _) = tlv::parse_one(der)?;
+        let fields = object.children()?;
+        let [_, algorithm, hashes, ..] = fields.as_slice() else {
+            return Err(malformed("LDS security object is incomplete"));
+        };
+
+        let oid = oid_of(algorithm)?;
+        let algorithm = HashAlgorithm::from_oid(&oid)
+            .ok_or_else(|| malformed(format!("unsupported hash algorithm {}", oid)))?;
+
+        let hashes = hashes
+            .children()?
+            .iter()
+            .map(|entry| {
+                let number = entry.expect_child(INTEGER)?;
+                let number = tlv::unsigned(number.value)
+                    .filter(|n| (1..=16).contains(n))
+                    .ok_or_else(|| malformed("data group number out of range"))?;
+                Ok((number as u8, entry.expect_child(OCTET_STRING)?.value.to_vec()))
+            })
+            .collect::<AlbergueResult<BTreeMap<u8, Vec<u8>>>>()?;
+
+        Ok((algorithm, hashes))
+    }
+
+    /// Whether `file`, data group `number` as read off the chip, hashes to
+    /// the value listed for it.
+    pub fn matches(&self, number: u8, file: &[u8]) -> bool {
+        self.hashes
+            .get(&number)
+            .is_some_and(|hash| *hash == self.algorithm.digest(file))
+    }
+}
+
+impl SignerInfo {
+    /// `SignerInfo ::= SEQUENCE { version, sid, digestAlgorithm,
+    /// [0] signedAttrs OPTIONAL, signatureAlgorithm, signature, ... }`
+    fn parse(info: &Tlv) -> AlbergueResult<Self> {
+        let fields = info.children()?;
+        let [_, sid, digest_algorithm, rest @ ..] = fields.as_slice() else {
+            return Err(malformed("signer info is incomplete"));
+        };
+        let (signed_attributes, rest) = match rest {
+            [attributes, rest @ ..] if attributes.tag == EXPLICIT_0 => (Some(attributes), rest),
+            _ => (None, rest),
+        };
+        let [signature_algorithm, signature, ..] = rest else {
+            return Err(malformed("signer info has no signature"));
+        };
+
+        let certificate = match sid.tag {
+            SEQUENCE => match sid.children()?[..] {
+                [issuer, serial_number] => Some((issuer.raw.to_vec(), serial_number.value.to_vec())),
+                _ => return Err(malformed("bad signer identifier")),
+            },
+            _ => None,
+        };
+
+        let mut message_digest = None;
+        if let Some(attributes) = signed_attributes {
+            for attribute in attributes.children()? {
+                if oid_of(&attribute)? == MESSAGE_DIGEST {
+                    let digest = attribute.expect_child(SET)?.expect_child(OCTET_STRING)?;
+                    message_digest = Some(digest.value.to_vec());
+                }
+            }
+        }
+
+        Ok(Self {
+            certificate,
+            digest_algorithm: AlgorithmIdentifier::parse(digest_algorithm)?,
+            // Signed as an explicit SET OF, not with the [0] it is stored under
+            signed_attributes: signed_attributes.map(|a| [&[SET as u8][..], &a.raw[1..]].concat()),
+            message_digest,
+            signature_algorithm: AlgorithmIdentifier::parse(signature_algorithm)?,
+            signature: signature.value.to_vec(),
+        })
+    }
+}
+
+fn oid_of(sequence: &Tlv) -> AlbergueResult<String> {
+    let oid = sequence.expect_child(OBJECT_IDENTIFIER)?;
+    tlv::object_identifier(oid.value).ok_or_else(|| malformed("bad object identifier"))
+}
+
+/// The content of an `OCTET STRING`, which BER may split into pieces.
+fn octets(objects: &[Tlv]) -> AlbergueResult<Vec<u8>> {
+    let object = objects.first().ok_or_else(|| malformed("empty content"))?;
+    match object.tag {
+        OCTET_STRING => Ok(object.value.to_vec()),
+        CONSTRUCTED_OCTET_STRING => octets_concat(&object.children()?),
+        tag => Err(malformed(format!("content tagged {:X}", tag))),
+    }
+}
+
+fn octets_concat(pieces: &[Tlv]) -> AlbergueResult<Vec<u8>> {
+    let mut joined = vec![];
+    for piece in pieces {
+        joined.extend(octets(std::slice::from_ref(piece))?);
+    }
+    Ok(joined)
+}
@@ -0,0 +1,161 @@
+//! BER-TLV as the LDS files and the DER inside the security object use it:
+//! multi-byte tags, short and long form lengths, and the indefinite length
+//! some CMS encoders emit.
+
+use shared::{AlbergueError, AlbergueResult};
+
+/// One tag-length-value object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tlv<'a> {
+    /// All tag bytes, big-endian: `0x61`, `0x5F1F`, `0x7F61`
+    pub tag: u32,
+    pub value: &'a [u8],
+    /// The whole encoding, tag and length included
+    pub raw: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Whether the value is itself made of TLV objects.
+    pub fn is_constructed(&self) -> bool {
+        self.raw[0] & 0x20 != 0
+    }
+
+    pub fn children(&self) -> AlbergueResult<Vec<Tlv<'a>>> {
+        parse_all(self.value)
+    }
+
+    /// The first child tagged `tag`.
+    pub fn child(&self, tag: u32) -> AlbergueResult<Option<Tlv<'a>>> {
+        Ok(self.children()?.into_iter().find(|c| c.tag == tag))
+    }
+
+    /// Like [`Tlv::child`], failing when it is missing.
+    pub fn expect_child(&self, tag: u32) -> AlbergueResult<Tlv<'a>> {
+        self.child(tag)?
+            .ok_or_else(|| malformed(format!("tag {:X} has no {:X}", self.tag, tag)))
+    }
+}
+
+/// The first object in `bytes` and whatever follows it.
+pub fn parse_one(bytes: &[u8]) -> AlbergueResult<(Tlv<'_>, &[u8])> {
+    let mut at = 0;
+    let first = *bytes.first().ok_or_else(|| malformed("no tag"))?;
+    let mut tag = first as u32;
+    at += 1;
+    if first & 0x1F == 0x1F {
+        loop {
+            let byte = *bytes.get(at).ok_or_else(|| malformed("tag cut short"))?;
+            if tag > 0x00FF_FFFF {
+                return Err(malformed("tag longer than four bytes"));
+            }
+            tag = (tag << 8) | byte as u32;
+            at += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    let length_byte = *bytes.get(at).ok_or_else(|| malformed("no length"))?;
+    at += 1;
+    let (value_start, value_end) = match length_byte {
+        0x80 => {
+            // Indefinite: children up to an end-of-contents marker
+            if first & 0x20 == 0 {
+                return Err(malformed("indefinite length on a primitive tag"));
+            }
+            let mut rest = &bytes[at..];
+            while !rest.starts_with(&[0, 0]) {
+                rest = parse_one(rest)?.1;
+            }
+            let start = at;
+            let end = bytes.len() - rest.len();
+            at = end + 2;
+            (start, end)
+        }
+        0x81..=0x84 => {
+            let count = (length_byte & 0x7F) as usize;
+            let length_bytes = bytes.get(at..at + count).ok_or_else(|| malformed("length cut short"))?;
+            let length = length_bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            let start = at + count;
+            at = start.checked_add(length).ok_or_else(|| malformed("length overflows"))?;
+            (start, at)
+        }
+        0x85..=0xFF => return Err(malformed("length longer than four bytes")),
+        short => {
+            let start = at;
+            at += short as usize;
+            (start, at)
+        }
+    };
+    if at > bytes.len() {
+        return Err(malformed(format!("tag {:X} runs past the end of its data", tag)));
+    }
+
+    Ok((
+        Tlv {
+            tag,
+            value: &bytes[value_start..value_end],
+            raw: &bytes[..at],
+        },
+        &bytes[at..],
+    ))
+}
+
+/// Every object in `bytes`, which must hold nothing else.
+pub fn parse_all(bytes: &[u8]) -> AlbergueResult<Vec<Tlv<'_>>> {
+    let mut objects = vec![];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (object, after) = parse_one(rest)?;
+        objects.push(object);
+        rest = after;
+    }
+    Ok(objects)
+}
+
+/// An unsigned DER `INTEGER`, if it fits.
+pub fn unsigned(value: &[u8]) -> Option<u64> {
+    let digits = match value {
+        [0, rest @ ..] => rest,
+        _ => value,
+    };
+    (digits.len() <= 8 && !value.is_empty()).then(|| digits.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// A DER `OBJECT IDENTIFIER` in dotted form.
+pub fn object_identifier(value: &[u8]) -> Option<String> {
+    let mut arcs = vec![];
+    let mut arc: u64 = 0;
+    for &byte in value {
+        arc = arc.checked_mul(128)? | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    if arcs.is_empty() || value.last()? & 0x80 != 0 {
+        return None;
+    }
+
+    let first = arcs.remove(0);
+    let (top, second) = match first {
+        0..=39 => (0, first),
+        40..=79 => (1, first - 40),
+        _ => (2, first - 80),
+    };
+    Some(
+        [top, second]
+            .into_iter()
+            .chain(arcs)
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+pub(crate) fn malformed(detail: impl std::fmt::Display) -> AlbergueError {
+    AlbergueError::Validation {
+        message: format!("Malformed chip data: {}", detail),
+    }
+}
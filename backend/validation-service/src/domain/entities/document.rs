@@ -1,4 +1,5 @@
-use chrono::{DateTime, Utc};
+use crate::domain::validators::nie_validator::NieValidator;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use shared::{DocumentType, ExtractedData};
 use uuid::Uuid;
 
@@ -9,8 +10,9 @@ pub struct Document {
     pub document_number: String,
     pub holder_name: String,
     pub holder_surname: String,
-    pub birth_date: DateTime<Utc>,
+    pub birth_date: Option<DateTime<Utc>>,
     pub nationality: String,
+    pub issue_date: Option<DateTime<Utc>>,
     pub expiry_date: Option<DateTime<Utc>>,
     pub is_valid: bool,
     pub validation_timestamp: DateTime<Utc>,
@@ -24,8 +26,9 @@ impl Document {
             document_number: extracted_data.document_number.unwrap_or_default(),
             holder_name: extracted_data.name.unwrap_or_default(),
             holder_surname: extracted_data.surname.unwrap_or_default(),
-            birth_date: extracted_data.birth_date.unwrap_or_else(|| Utc::now()),
+            birth_date: extracted_data.birth_date,
             nationality: extracted_data.nationality.unwrap_or_default(),
+            issue_date: extracted_data.issue_date,
             expiry_date: extracted_data.expiry_date,
             is_valid,
             validation_timestamp: Utc::now(),
@@ -33,17 +36,31 @@ impl Document {
     }
 
     pub fn is_expired(&self) -> bool {
+        self.is_expired_on(Utc::now().date_naive())
+    }
+
+    /// A document is valid through the whole of its expiry day.
+    pub fn is_expired_on(&self, today: NaiveDate) -> bool {
         match self.expiry_date {
-            Some(expiry) => expiry < Utc::now(),
+            Some(expiry) => expiry.date_naive() < today,
             None => false,
         }
     }
 
+    /// Completed years on `today`, when the birth date was read.
+    pub fn age_on(&self, today: NaiveDate) -> Option<u32> {
+        let birth = self.birth_date?.date_naive();
+        let had_birthday = (today.month(), today.day()) >= (birth.month(), birth.day());
+        let years = today.year() - birth.year() - if had_birthday { 0 } else { 1 };
+        u32::try_from(years).ok()
+    }
+
     pub fn validate_checksum(&self) -> bool {
         match self.document_type {
             DocumentType::DNI => self.validate_dni_checksum(),
-            DocumentType::NIE => self.validate_nie_checksum(),
-            DocumentType::Passport => true, // Passport validation is different
+            DocumentType::NIE | DocumentType::TIE => self.validate_nie_checksum(),
+            // Vouched for by MRZ check digits or national rules instead
+            DocumentType::Passport | DocumentType::EuIdCard | DocumentType::DrivingLicence => true,
         }
     }
 
@@ -60,7 +77,7 @@ impl Document {
             let expected_letter = letters.chars().nth((number % 23) as usize);
 
             if let Some(expected) = expected_letter {
-                return letter_part.chars().next() == Some(expected);
+                return letter_part.starts_with(expected);
             }
         }
 
@@ -68,33 +85,8 @@ impl Document {
     }
 
     fn validate_nie_checksum(&self) -> bool {
-        if self.document_number.len() != 9 {
-            return false;
-        }
-
-        // NIE format: X1234567L or Y1234567L
-        let first_char = self.document_number.chars().next().unwrap_or(' ');
-        if first_char != 'X' && first_char != 'Y' {
-            return false;
-        }
-
-        let number_part = &self.document_number[1..8];
-        let letter_part = &self.document_number[8..];
-
-        if let Ok(mut number) = number_part.parse::<u32>() {
-            // X = 0, Y = 1
-            if first_char == 'Y' {
-                number += 10000000;
-            }
-
-            let letters = "TRWAGMYFPDXBNJZSQVHLCKE";
-            let expected_letter = letters.chars().nth((number % 23) as usize);
-
-            if let Some(expected) = expected_letter {
-                return letter_part.chars().next() == Some(expected);
-            }
-        }
-
-        false
+        NieValidator::new()
+            .validate_nie(&self.document_number)
+            .unwrap_or(false)
     }
 }
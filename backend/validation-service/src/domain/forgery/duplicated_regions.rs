@@ -0,0 +1,192 @@
+//! Copy-move edits: a patch of the image pasted over another part of it,
+//! usually to cover a photo or a date. Blocks are matched by a coarse
+//! signature at every position; many matches sharing one offset mark the
+//! copy.
+//!
+//! Only continuous-tone areas count. Printed text repeats itself (`<<<<`
+//! in an MRZ) and a straight edge matches itself along its length, so
+//! blocks that are mostly ink and paper are left out.
+
+use image::{imageops, GrayImage};
+use std::collections::HashMap;
+
+use super::Finding;
+use shared::ForgerySignal;
+
+/// Images are compared at this size at most.
+const MAX_SIDE: u32 = 640;
+const BLOCK: usize = 16;
+/// Sub-cells per side whose means make a block's signature.
+const CELLS: usize = 4;
+/// Signature levels; coarse enough to survive recompression.
+const QUANTUM: f32 = 12.0;
+/// Blocks flatter than this standard deviation match anything.
+const MIN_DEVIATION: f32 = 10.0;
+/// Signatures shared by more blocks than this are texture, not a copy.
+const MAX_BUCKET: usize = 6;
+/// A copy must move at least this far, and match at this many positions
+/// spread at least `MIN_EXTENT` pixels each way.
+const MIN_OFFSET: i32 = 24;
+const MIN_MATCHES: usize = 40;
+const MIN_EXTENT: u32 = 24;
+/// Mean difference per pixel between a block and its copy.
+const MAX_DIFFERENCE: f32 = 8.0;
+/// Share of a block's pixels between its darkest and lightest tones for
+/// it to count as continuous tone rather than print.
+const MIN_MIDTONES: f32 = 0.3;
+
+pub fn inspect(gray: &GrayImage) -> Option<Finding> {
+    let scale = (MAX_SIDE as f32 / gray.width().max(gray.height()) as f32).min(1.0);
+    let gray = if scale < 1.0 {
+        let (w, h) = ((gray.width() as f32 * scale) as u32, (gray.height() as f32 * scale) as u32);
+        imageops::resize(gray, w.max(1), h.max(1), imageops::FilterType::Triangle)
+    } else {
+        gray.clone()
+    };
+    let (width, height) = (gray.width() as usize, gray.height() as usize);
+    if width < BLOCK * 2 || height < BLOCK * 2 {
+        return None;
+    }
+    let integral = Integral::new(&gray);
+
+    let mut buckets: HashMap<[u8; CELLS * CELLS], Vec<(u32, u32)>> = HashMap::new();
+    for y in 0..=height - BLOCK {
+        for x in 0..=width - BLOCK {
+            if integral.deviation(x, y, BLOCK) < MIN_DEVIATION {
+                continue;
+            }
+            buckets.entry(integral.signature(x, y)).or_default().push((x as u32, y as u32));
+        }
+    }
+
+    // Positions of the first block of each matched pair, by offset
+    let mut offsets: HashMap<(i32, i32), Vec<(u32, u32)>> = HashMap::new();
+    for positions in buckets.values().filter(|p| (2..=MAX_BUCKET).contains(&p.len())) {
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                let offset = (b.0 as i32 - a.0 as i32, b.1 as i32 - a.1 as i32);
+                if offset.0.abs().max(offset.1.abs()) >= MIN_OFFSET {
+                    offsets.entry(offset).or_default().push(*a);
+                }
+            }
+        }
+    }
+
+    let ((dx, dy), positions) = offsets
+        .into_iter()
+        .filter(|(_, positions)| positions.len() >= MIN_MATCHES)
+        .filter(|(_, positions)| extent(positions, |p| p.0) >= MIN_EXTENT && extent(positions, |p| p.1) >= MIN_EXTENT)
+        .filter(|(offset, positions)| {
+            let copies = positions
+                .iter()
+                .filter(|a| {
+                    let b = ((a.0 as i32 + offset.0) as u32, (a.1 as i32 + offset.1) as u32);
+                    difference(&gray, **a, b) <= MAX_DIFFERENCE && midtones(&gray, **a) >= MIN_MIDTONES
+                })
+                .count();
+            copies * 2 >= positions.len()
+        })
+        .max_by_key(|(_, positions)| positions.len())?;
+
+    let matched = positions.len();
+    let strength = 0.5 + 0.5 * ((matched - MIN_MATCHES) as f32 / (MIN_MATCHES * 4) as f32).min(1.0);
+    let (dx, dy) = ((dx as f32 / scale).round(), (dy as f32 / scale).round());
+    Some(Finding::new(
+        ForgerySignal::DuplicatedRegion,
+        strength,
+        format!(
+            "An area of about {}x{} pixels appears twice, {} across and {} down from the original",
+            ((extent(&positions, |p| p.0) + BLOCK as u32) as f32 / scale).round(),
+            ((extent(&positions, |p| p.1) + BLOCK as u32) as f32 / scale).round(),
+            dx,
+            dy
+        ),
+    ))
+}
+
+fn extent(positions: &[(u32, u32)], axis: impl Fn(&(u32, u32)) -> u32) -> u32 {
+    let values = positions.iter().map(&axis);
+    values.clone().max().unwrap_or(0) - values.min().unwrap_or(0)
+}
+
+/// Mean absolute difference between the blocks at `a` and `b`.
+fn difference(gray: &GrayImage, a: (u32, u32), b: (u32, u32)) -> f32 {
+    let mut total = 0u32;
+    for y in 0..BLOCK as u32 {
+        for x in 0..BLOCK as u32 {
+            let pa = gray.get_pixel(a.0 + x, a.1 + y)[0] as i32;
+            let pb = gray.get_pixel(b.0 + x, b.1 + y)[0] as i32;
+            total += (pa - pb).unsigned_abs();
+        }
+    }
+    total as f32 / (BLOCK * BLOCK) as f32
+}
+
+/// Share of the block's pixels clear of both its darkest and lightest
+/// quarter of tones.
+fn midtones(gray: &GrayImage, at: (u32, u32)) -> f32 {
+    let pixels: Vec<u8> = (0..BLOCK as u32)
+        .flat_map(|y| (0..BLOCK as u32).map(move |x| (x, y)))
+        .map(|(x, y)| gray.get_pixel(at.0 + x, at.1 + y)[0])
+        .collect();
+    let (low, high) = (*pixels.iter().min().unwrap() as f32, *pixels.iter().max().unwrap() as f32);
+    let quarter = (high - low) / 4.0;
+    let middle = pixels
+        .iter()
+        .filter(|&&p| (low + quarter..=high - quarter).contains(&(p as f32)))
+        .count();
+    middle as f32 / pixels.len() as f32
+}
+
+/// Sums and squared sums for constant-time block statistics.
+struct Integral {
+    width: usize,
+    sums: Vec<u64>,
+    squares: Vec<u64>,
+}
+
+impl Integral {
+    fn new(gray: &GrayImage) -> Self {
+        let (width, height) = (gray.width() as usize, gray.height() as usize);
+        let stride = width + 1;
+        let mut sums = vec![0u64; stride * (height + 1)];
+        let mut squares = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let (mut row, mut row_squares) = (0u64, 0u64);
+            for x in 0..width {
+                let v = gray.get_pixel(x as u32, y as u32)[0] as u64;
+                row += v;
+                row_squares += v * v;
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+                squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_squares;
+            }
+        }
+        Self { width, sums, squares }
+    }
+
+    fn area(&self, table: &[u64], x: usize, y: usize, size: usize) -> u64 {
+        let stride = self.width + 1;
+        table[(y + size) * stride + x + size] + table[y * stride + x]
+            - table[y * stride + x + size]
+            - table[(y + size) * stride + x]
+    }
+
+    fn deviation(&self, x: usize, y: usize, size: usize) -> f32 {
+        let n = (size * size) as f64;
+        let mean = self.area(&self.sums, x, y, size) as f64 / n;
+        let variance = self.area(&self.squares, x, y, size) as f64 / n - mean * mean;
+        variance.max(0.0).sqrt() as f32
+    }
+
+    /// Quantised means of the block's sub-cells.
+    fn signature(&self, x: usize, y: usize) -> [u8; CELLS * CELLS] {
+        let cell = BLOCK / CELLS;
+        let mut signature = [0u8; CELLS * CELLS];
+        for (i, level) in signature.iter_mut().enumerate() {
+            let (cx, cy) = (x + (i % CELLS) * cell, y + (i / CELLS) * cell);
+            let mean = self.area(&self.sums, cx, cy, cell) as f32 / (cell * cell) as f32;
+            *level = (mean / QUANTUM) as u8;
+        }
+        signature
+    }
+}
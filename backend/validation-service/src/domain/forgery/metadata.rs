@@ -0,0 +1,280 @@
+//! What the file says about where it came from: JPEG EXIF and PNG text
+//! chunks. Missing metadata proves nothing, as messaging apps strip it; what
+//! is there can still contradict a straight camera capture.
+
+use chrono::NaiveDateTime;
+
+use super::Finding;
+use shared::ForgerySignal;
+
+/// Editors whose name in `Software` means the pixels were worked on.
+const EDITORS: [&str; 12] = [
+    "photoshop", "gimp", "lightroom", "snapseed", "picsart", "pixlr", "paint.net", "affinity", "canva",
+    "facetune", "pixelmator", "photopea",
+];
+
+/// Capture and modification times further apart than this mean the file
+/// was saved again later.
+const MAX_RESAVE_SECONDS: i64 = 60;
+
+const SOFTWARE: u16 = 0x0131;
+const DATE_TIME: u16 = 0x0132;
+const IMAGE_DESCRIPTION: u16 = 0x010E;
+const EXIF_IFD: u16 = 0x8769;
+const USER_COMMENT: u16 = 0x9286;
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+const PIXEL_X_DIMENSION: u16 = 0xA002;
+const PIXEL_Y_DIMENSION: u16 = 0xA003;
+
+/// The metadata fields the checks use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageMetadata {
+    pub software: Option<String>,
+    pub description: Option<String>,
+    pub modified: Option<String>,
+    pub captured: Option<String>,
+    pub pixel_dimensions: Option<(u32, u32)>,
+}
+
+pub fn inspect(image_data: &[u8], width: u32, height: u32) -> Vec<Finding> {
+    let metadata = if image_data.starts_with(&[0xFF, 0xD8]) {
+        read_jpeg(image_data)
+    } else if image_data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        read_png(image_data)
+    } else {
+        None
+    };
+    let Some(metadata) = metadata else {
+        return vec![];
+    };
+
+    let mut findings = vec![];
+    let mentions = |needle: &str| {
+        [&metadata.software, &metadata.description]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(needle))
+    };
+    if mentions("screenshot") {
+        findings.push(Finding::new(
+            ForgerySignal::ExifInconsistency,
+            0.9,
+            "The file is marked as a screenshot",
+        ));
+    }
+    if let Some(software) = &metadata.software {
+        let lower = software.to_lowercase();
+        if let Some(editor) = EDITORS.iter().find(|e| lower.contains(**e)) {
+            findings.push(Finding::new(
+                ForgerySignal::ExifInconsistency,
+                0.8,
+                format!("The file was saved by an image editor ({})", editor),
+            ));
+        }
+    }
+
+    let parse = |text: &Option<String>| {
+        text.as_deref()
+            .and_then(|t| NaiveDateTime::parse_from_str(t.trim_end_matches('\0'), "%Y:%m:%d %H:%M:%S").ok())
+    };
+    if let (Some(captured), Some(modified)) = (parse(&metadata.captured), parse(&metadata.modified)) {
+        let seconds = (modified - captured).num_seconds();
+        if seconds.abs() > MAX_RESAVE_SECONDS {
+            findings.push(Finding::new(
+                ForgerySignal::ExifInconsistency,
+                0.5,
+                format!("The file was modified {} after it was taken", describe_span(seconds)),
+            ));
+        }
+    }
+
+    if let Some((x, y)) = metadata.pixel_dimensions {
+        if (x, y) != (width, height) && (y, x) != (width, height) {
+            findings.push(Finding::new(
+                ForgerySignal::ExifInconsistency,
+                0.4,
+                format!("The camera recorded {}x{} but the image is {}x{}", x, y, width, height),
+            ));
+        }
+    }
+    findings
+}
+
+fn describe_span(seconds: i64) -> String {
+    let seconds = seconds.abs();
+    match seconds {
+        0..=3599 => format!("{} minutes", seconds / 60),
+        3600..=86399 => format!("{} hours", seconds / 3600),
+        _ => format!("{} days", seconds / 86400),
+    }
+}
+
+/// The EXIF block of a JPEG, if it has one.
+pub fn read_jpeg(data: &[u8]) -> Option<ImageMetadata> {
+    let mut at = 2;
+    while at + 4 <= data.len() {
+        if data[at] != 0xFF {
+            return None;
+        }
+        let marker = data[at + 1];
+        // Start of scan: no more metadata segments
+        if marker == 0xDA {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[at + 2], data[at + 3]]) as usize;
+        let segment = data.get(at + 4..at + 2 + length)?;
+        if marker == 0xE1 {
+            if let Some(tiff) = segment.strip_prefix(b"Exif\0\0") {
+                return read_tiff(tiff);
+            }
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+/// `Software`, `Description` and XMP text in a PNG's text chunks.
+pub fn read_png(data: &[u8]) -> Option<ImageMetadata> {
+    let mut metadata = ImageMetadata::default();
+    let mut found = false;
+    let mut at: usize = 8;
+    // A length that overflows is a corrupt file: no metadata at all
+    while let Some(header_end) = at.checked_add(8).filter(|&end| end <= data.len()) {
+        let length = u32::from_be_bytes(data[at..at + 4].try_into().ok()?) as usize;
+        let kind = &data[at + 4..header_end];
+        let body_end = header_end.checked_add(length)?;
+        let Some(body) = data.get(header_end..body_end) else {
+            break;
+        };
+        if kind == b"tEXt" || kind == b"iTXt" {
+            if let Some((keyword, text)) = text_chunk(kind, body) {
+                found = true;
+                match keyword.as_str() {
+                    "Software" => metadata.software = Some(text),
+                    "Description" | "Comment" | "XML:com.adobe.xmp" => {
+                        let joined = [metadata.description.take(), Some(text)].into_iter().flatten();
+                        metadata.description = Some(joined.collect::<Vec<_>>().join("\n"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if kind == b"eXIf" {
+            if let Some(exif) = read_tiff(body) {
+                return Some(exif);
+            }
+        }
+        if kind == b"IEND" {
+            break;
+        }
+        // Past the CRC
+        at = body_end.checked_add(4)?;
+    }
+    found.then_some(metadata)
+}
+
+/// Keyword and text of an uncompressed `tEXt` or `iTXt` chunk.
+fn text_chunk(kind: &[u8], body: &[u8]) -> Option<(String, String)> {
+    let split = body.iter().position(|&b| b == 0)?;
+    let keyword = String::from_utf8_lossy(&body[..split]).into_owned();
+    let rest = &body[split + 1..];
+    let text = if kind == b"iTXt" {
+        // Compression flag and method, then language and translated keyword
+        let (&compressed, rest) = rest.split_first()?;
+        if compressed != 0 {
+            return None;
+        }
+        let rest = rest.get(1..)?;
+        let language_end = rest.iter().position(|&b| b == 0)?;
+        let rest = &rest[language_end + 1..];
+        let translated_end = rest.iter().position(|&b| b == 0)?;
+        &rest[translated_end + 1..]
+    } else {
+        rest
+    };
+    Some((keyword, String::from_utf8_lossy(text).into_owned()))
+}
+
+/// The fields of IFD0 and the EXIF IFD a TIFF structure holds.
+fn read_tiff(tiff: &[u8]) -> Option<ImageMetadata> {
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| -> Option<u16> {
+        let b: [u8; 2] = tiff.get(at..at.checked_add(2)?)?.try_into().ok()?;
+        Some(if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    };
+    let u32_at = |at: usize| -> Option<u32> {
+        let b: [u8; 4] = tiff.get(at..at.checked_add(4)?)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    };
+
+    // Each entry: tag, type, count, then the value or an offset to it. An
+    // offset that overflows is a corrupt file: no metadata at all
+    let entries = |ifd: usize| -> Option<Vec<(u16, u16, u32, usize)>> {
+        let count = u16_at(ifd).unwrap_or(0) as usize;
+        let mut entries = vec![];
+        for i in 0..count {
+            let entry = ifd.checked_add(2 + i * 12)?;
+            let value_at = entry.checked_add(8)?;
+            if let (Some(tag), Some(kind), Some(count)) = (u16_at(entry), u16_at(entry + 2), u32_at(entry + 4)) {
+                entries.push((tag, kind, count, value_at));
+            }
+        }
+        Some(entries)
+    };
+    let text = |kind: u16, count: u32, value_at: usize| -> Option<String> {
+        if kind != 2 && kind != 7 {
+            return None;
+        }
+        let count = count as usize;
+        let start = if count <= 4 { value_at } else { u32_at(value_at)? as usize };
+        let bytes = tiff.get(start..start.checked_add(count)?)?;
+        // UserComment starts with an eight byte character code
+        let bytes = if kind == 7 { bytes.get(8..).unwrap_or_default() } else { bytes };
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string())
+    };
+    let number = |kind: u16, value_at: usize| -> Option<u32> {
+        match kind {
+            3 => u16_at(value_at).map(u32::from),
+            4 => u32_at(value_at),
+            _ => None,
+        }
+    };
+
+    let mut metadata = ImageMetadata::default();
+    let mut exif_ifd = None;
+    for (tag, kind, count, value_at) in entries(u32_at(4)? as usize)? {
+        match tag {
+            SOFTWARE => metadata.software = text(kind, count, value_at),
+            IMAGE_DESCRIPTION => metadata.description = text(kind, count, value_at),
+            DATE_TIME => metadata.modified = text(kind, count, value_at),
+            EXIF_IFD => exif_ifd = u32_at(value_at),
+            _ => {}
+        }
+    }
+
+    let (mut x, mut y) = (None, None);
+    let exif_entries = match exif_ifd {
+        Some(ifd) => entries(ifd as usize)?,
+        None => vec![],
+    };
+    for (tag, kind, count, value_at) in exif_entries {
+        match tag {
+            DATE_TIME_ORIGINAL => metadata.captured = text(kind, count, value_at),
+            USER_COMMENT => {
+                if let Some(comment) = text(kind, count, value_at).filter(|c| !c.is_empty()) {
+                    let joined = [metadata.description.take(), Some(comment)].into_iter().flatten();
+                    metadata.description = Some(joined.collect::<Vec<_>>().join("\n"));
+                }
+            }
+            PIXEL_X_DIMENSION => x = number(kind, value_at),
+            PIXEL_Y_DIMENSION => y = number(kind, value_at),
+            _ => {}
+        }
+    }
+    metadata.pixel_dimensions = x.zip(y);
+    Some(metadata)
+}
@@ -0,0 +1,111 @@
+//! Signs that an upload is not an honest capture of the document: a photo
+//! of a screen or a print, a screenshot, or an edited image. Each check
+//! gives reasons with a strength; none rejects a document on its own.
+
+pub mod duplicated_regions;
+pub mod metadata;
+pub mod mrz_font;
+pub mod recapture;
+pub mod specimens;
+
+use image::DynamicImage;
+
+use crate::domain::ocr::ImageProcessor;
+use crate::domain::validators::mrz_validator::MrzData;
+use shared::{ExtractedData, ForgeryReason, ForgeryRisk, ForgerySignal};
+
+/// How far one signal at full strength goes towards certainty: specimen
+/// data is conclusive, metadata is easily explained away.
+fn weight(signal: ForgerySignal) -> f32 {
+    match signal {
+        ForgerySignal::SpecimenDocument => 0.95,
+        ForgerySignal::DuplicatedRegion => 0.7,
+        ForgerySignal::MrzFontMismatch => 0.7,
+        ForgerySignal::ScreenGrid => 0.6,
+        ForgerySignal::Moire => 0.5,
+        ForgerySignal::ExifInconsistency => 0.4,
+    }
+}
+
+/// One finding before it is tied to an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub signal: ForgerySignal,
+    pub strength: f32,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(signal: ForgerySignal, strength: f32, message: impl Into<String>) -> Self {
+        Self {
+            signal,
+            strength: strength.clamp(0.0, 1.0),
+            message: message.into(),
+        }
+    }
+
+    fn on(self, image: Option<&str>) -> ForgeryReason {
+        ForgeryReason {
+            signal: self.signal,
+            image: image.map(str::to_string),
+            strength: self.strength,
+            message: self.message,
+        }
+    }
+}
+
+pub struct ForgeryDetector {
+    image_processor: ImageProcessor,
+}
+
+impl Default for ForgeryDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ForgeryDetector {
+    pub fn new() -> Self {
+        Self {
+            image_processor: ImageProcessor::new(),
+        }
+    }
+
+    /// Image signals for one side of the document, `side` naming it in the
+    /// reasons. An image that cannot be decoded gives none; OCR reports it.
+    pub fn inspect_image(&self, image_data: &[u8], side: &str) -> Vec<ForgeryReason> {
+        let Ok(img) = image::load_from_memory(image_data) else {
+            return vec![];
+        };
+        let rgb = img.to_rgb8();
+        let gray = DynamicImage::ImageRgb8(rgb.clone()).to_luma8();
+
+        let mut findings = metadata::inspect(image_data, img.width(), img.height());
+        findings.extend(recapture::moire(&rgb));
+        findings.extend(recapture::screen_grid(&gray));
+        findings.extend(duplicated_regions::inspect(&gray));
+        findings.extend(mrz_font::inspect(&self.image_processor.prepare(&gray).image));
+        findings.into_iter().map(|f| f.on(Some(side))).collect()
+    }
+
+    /// Signals in what was read off the document rather than its images.
+    pub fn inspect_data(&self, data: &ExtractedData, mrz: Option<&MrzData>) -> Vec<ForgeryReason> {
+        specimens::inspect(data, mrz).into_iter().map(|f| f.on(None)).collect()
+    }
+
+    /// Combines reasons as independent evidence: each takes its weighted
+    /// share of the doubt the others leave.
+    pub fn assess(&self, mut reasons: Vec<ForgeryReason>) -> ForgeryRisk {
+        reasons.sort_by(|a, b| {
+            (weight(b.signal) * b.strength).total_cmp(&(weight(a.signal) * a.strength))
+        });
+        let doubt: f32 = reasons
+            .iter()
+            .map(|r| 1.0 - weight(r.signal) * r.strength)
+            .product();
+        ForgeryRisk {
+            score: 1.0 - doubt,
+            reasons,
+        }
+    }
+}
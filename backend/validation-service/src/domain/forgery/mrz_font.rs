@@ -0,0 +1,96 @@
+//! Retyped MRZ characters. Every character of a genuine MRZ is printed in
+//! one pass, OCR-B at one size on one baseline at a fixed pitch; one typed
+//! over in an editor rarely matches all three.
+
+use image::GrayImage;
+
+use super::Finding;
+use crate::domain::ocr::glyph_model::GlyphModel;
+use crate::domain::ocr::segmentation::{self, BoundingBox};
+use shared::ForgerySignal;
+
+/// Specks smaller than this many pixels are noise, as for OCR.
+const MIN_COMPONENT_PIXELS: usize = 4;
+/// How far a character's height may stray from the line's, as a share.
+const MAX_HEIGHT_DEVIATION: f32 = 0.12;
+/// How far off the line's baseline a character may sit, in cap heights.
+const MAX_BASELINE_OFFSET: f32 = 0.1;
+/// How far off its cell centre a character may sit, as a share of the pitch.
+const MAX_PITCH_OFFSET: f32 = 0.3;
+/// Characters out of place before the MRZ is flagged; one can be a smudge.
+const MIN_MISMATCHES: usize = 2;
+
+pub fn inspect(gray: &GrayImage) -> Option<Finding> {
+    let mut gray = gray.clone();
+    if segmentation::is_light_on_dark(&gray) {
+        image::imageops::invert(&mut gray);
+    }
+    let binary = segmentation::binarize(&gray);
+    let components = segmentation::connected_components(&binary, MIN_COMPONENT_PIXELS);
+    let lines = segmentation::group_lines(segmentation::merge_stacked(components));
+
+    let mut resized = vec![];
+    let mut off_pitch = vec![];
+    for line in &lines {
+        let Some(count) = line.mrz_cell_count() else {
+            continue;
+        };
+        // Fillers are shorter than everything else and prove nothing
+        let glyphs: Vec<(char, BoundingBox, BoundingBox)> = line
+            .fixed_pitch_cells(count)
+            .into_iter()
+            .filter_map(|cell| {
+                let (bbox, glyph) = segmentation::crop_glyph(&binary, &cell, None)?;
+                let (ch, _) = GlyphModel::mrz().classify(&glyph)?;
+                (ch != '<').then_some((ch, bbox, cell))
+            })
+            .collect();
+        if glyphs.len() < MIN_MISMATCHES * 2 {
+            continue;
+        }
+        let height = median(glyphs.iter().map(|(_, bbox, _)| bbox.height));
+        let baseline = median(glyphs.iter().map(|(_, bbox, _)| bbox.bottom()));
+
+        for (ch, bbox, cell) in &glyphs {
+            let centre = |b: &BoundingBox| b.x as f32 + b.width as f32 / 2.0;
+            if (centre(bbox) - centre(cell)).abs() > cell.width as f32 * MAX_PITCH_OFFSET {
+                off_pitch.push(*ch);
+            } else if (bbox.height as f32 - height).abs() > height * MAX_HEIGHT_DEVIATION
+                || (bbox.bottom() as f32 - baseline).abs() > height * MAX_BASELINE_OFFSET
+            {
+                resized.push(*ch);
+            }
+        }
+    }
+
+    let mismatches = resized.len() + off_pitch.len();
+    if mismatches < MIN_MISMATCHES {
+        return None;
+    }
+    let mut parts = vec![];
+    if !resized.is_empty() {
+        parts.push(format!(
+            "{} off the line's size or baseline ({})",
+            resized.len(),
+            resized.iter().collect::<String>()
+        ));
+    }
+    if !off_pitch.is_empty() {
+        parts.push(format!(
+            "{} off the fixed pitch ({})",
+            off_pitch.len(),
+            off_pitch.iter().collect::<String>()
+        ));
+    }
+    Some(Finding::new(
+        ForgerySignal::MrzFontMismatch,
+        0.5 + 0.1 * (mismatches - MIN_MISMATCHES) as f32,
+        format!("MRZ characters {}", parts.join(", ")),
+    ))
+}
+
+fn median(values: impl Iterator<Item = u32>) -> f32 {
+    let mut values: Vec<u32> = values.collect();
+    values.sort_unstable();
+    values.get(values.len() / 2).copied().unwrap_or(0) as f32
+}
@@ -0,0 +1,184 @@
+//! Traces of photographing a screen: the display's pixel grid, fine and
+//! regular in both directions, and the coloured interference stripes
+//! (moiré) where that grid beats against the camera's own.
+
+use image::{imageops, GrayImage, RgbImage};
+
+use super::Finding;
+use shared::ForgerySignal;
+
+/// Only the centre of large images is examined; a screen covers all of it.
+const MAX_SIDE: u32 = 1024;
+/// Display pixel pitches, in image pixels, a hand-held photo resolves.
+const GRID_PERIODS: std::ops::RangeInclusive<usize> = 3..=8;
+/// Correlation of the fine detail with itself one period on, per direction.
+const GRID_CORRELATION: f32 = 0.3;
+/// Moiré stripe periods, in pixels of the half-size chroma image.
+const MOIRE_PERIODS: std::ops::RangeInclusive<usize> = 4..=40;
+const MOIRE_CORRELATION: f32 = 0.35;
+/// Colour swing, in levels, below which there are no stripes to speak of.
+const MIN_CHROMA_SPREAD: f32 = 3.0;
+
+/// A display's subpixel grid: fine detail that repeats at the same short
+/// period across and down the image.
+pub fn screen_grid(gray: &GrayImage) -> Option<Finding> {
+    let gray = centre(gray);
+    let detail = high_pass(&gray);
+    let (width, height) = (gray.width() as usize, gray.height() as usize);
+
+    let across = periodic_peak(&detail, width, height, GRID_PERIODS, true)?;
+    let down = periodic_peak(&detail, width, height, GRID_PERIODS, false)?;
+    let correlation = across.1.min(down.1);
+    if correlation < GRID_CORRELATION {
+        return None;
+    }
+    Some(Finding::new(
+        ForgerySignal::ScreenGrid,
+        (correlation - GRID_CORRELATION) / (1.0 - GRID_CORRELATION) * 0.5 + 0.5,
+        format!(
+            "A regular {}x{} pixel grid covers the image, as a photographed screen shows",
+            across.0, down.0
+        ),
+    ))
+}
+
+/// Colour stripes repeating at a steady period in some direction.
+pub fn moire(rgb: &RgbImage) -> Option<Finding> {
+    let rgb = centre_rgb(rgb);
+    let half = imageops::resize(&rgb, (rgb.width() / 2).max(1), (rgb.height() / 2).max(1), imageops::FilterType::Triangle);
+    let (width, height) = (half.width() as usize, half.height() as usize);
+
+    let mut best: Option<(usize, f32)> = None;
+    for channel in [0, 2] {
+        // Red or blue against green: plain grey print has none
+        let chroma: Vec<f32> = half
+            .pixels()
+            .map(|p| p[channel] as f32 - p[1] as f32)
+            .collect();
+        let chroma = band_pass(&chroma, width, height);
+        if spread(&chroma) < MIN_CHROMA_SPREAD {
+            continue;
+        }
+        for across in [true, false] {
+            if let Some(peak) = periodic_peak(&chroma, width, height, MOIRE_PERIODS, across) {
+                if best.is_none_or(|(_, c)| peak.1 > c) {
+                    best = Some(peak);
+                }
+            }
+        }
+    }
+
+    let (period, correlation) = best?;
+    if correlation < MOIRE_CORRELATION {
+        return None;
+    }
+    Some(Finding::new(
+        ForgerySignal::Moire,
+        (correlation - MOIRE_CORRELATION) / (1.0 - MOIRE_CORRELATION) * 0.5 + 0.5,
+        format!("Colour stripes repeat every {} pixels, a moiré pattern", period * 2),
+    ))
+}
+
+fn centre(gray: &GrayImage) -> GrayImage {
+    let (w, h) = (gray.width().min(MAX_SIDE), gray.height().min(MAX_SIDE));
+    imageops::crop_imm(gray, (gray.width() - w) / 2, (gray.height() - h) / 2, w, h).to_image()
+}
+
+fn centre_rgb(rgb: &RgbImage) -> RgbImage {
+    let (w, h) = (rgb.width().min(MAX_SIDE), rgb.height().min(MAX_SIDE));
+    imageops::crop_imm(rgb, (rgb.width() - w) / 2, (rgb.height() - h) / 2, w, h).to_image()
+}
+
+/// Each pixel less the mean of its 3x3 neighbourhood.
+fn high_pass(gray: &GrayImage) -> Vec<f32> {
+    let values: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+    let smooth = box_blur(&values, gray.width() as usize, gray.height() as usize, 1);
+    values.iter().zip(smooth).map(|(v, s)| v - s).collect()
+}
+
+/// Detail between a few pixels and a few dozen: the 3x3 mean less the
+/// 33x33 mean.
+fn band_pass(values: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let fine = box_blur(values, width, height, 1);
+    let coarse = box_blur(values, width, height, 16);
+    fine.iter().zip(coarse).map(|(f, c)| f - c).collect()
+}
+
+/// Mean over a `2 * radius + 1` square, clipped at the edges.
+fn box_blur(values: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    // Integral image with a zero row and column in front
+    let stride = width + 1;
+    let mut integral = vec![0f64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row = 0f64;
+        for x in 0..width {
+            row += values[y * width + x] as f64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row;
+        }
+    }
+
+    let mut blurred = Vec::with_capacity(values.len());
+    for y in 0..height {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[y1 * stride + x1] - integral[y0 * stride + x1] - integral[y1 * stride + x0]
+                + integral[y0 * stride + x0];
+            blurred.push((sum / ((y1 - y0) * (x1 - x0)) as f64) as f32);
+        }
+    }
+    blurred
+}
+
+fn spread(values: &[f32]) -> f32 {
+    let mean = values.iter().sum::<f32>() / values.len().max(1) as f32;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len().max(1) as f32).sqrt()
+}
+
+/// Correlation of `values` with itself shifted by `lag` along a direction.
+fn autocorrelation(values: &[f32], width: usize, height: usize, lag: usize, across: bool) -> f32 {
+    let (mut product, mut energy) = (0f64, 0f64);
+    for y in 0..height {
+        for x in 0..width {
+            let v = values[y * width + x] as f64;
+            energy += v * v;
+            let shifted = if across {
+                (x + lag < width).then(|| values[y * width + x + lag])
+            } else {
+                (y + lag < height).then(|| values[(y + lag) * width + x])
+            };
+            if let Some(s) = shifted {
+                product += v * s as f64;
+            }
+        }
+    }
+    if energy <= f64::EPSILON {
+        return 0.0;
+    }
+    (product / energy) as f32
+}
+
+/// The period in `periods` where the signal best repeats: a local maximum
+/// of the autocorrelation that recurs at twice the period.
+fn periodic_peak(
+    values: &[f32],
+    width: usize,
+    height: usize,
+    periods: std::ops::RangeInclusive<usize>,
+    across: bool,
+) -> Option<(usize, f32)> {
+    let span = if across { width } else { height };
+    let last = (*periods.end() * 2 + 1).min(span.saturating_sub(1));
+    if *periods.start() + 1 > last {
+        return None;
+    }
+    let correlations: Vec<f32> = (0..=last)
+        .map(|lag| autocorrelation(values, width, height, lag, across))
+        .collect();
+
+    periods
+        .filter(|&lag| lag < last && lag * 2 <= last)
+        .filter(|&lag| correlations[lag] > correlations[lag - 1] && correlations[lag] >= correlations[lag + 1])
+        .map(|lag| (lag, correlations[lag].min(correlations[lag * 2])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
@@ -0,0 +1,92 @@
+//! Data from published specimen documents. Specimen images are easy to
+//! find online and are what gets uploaded when a guest will not show the
+//! real thing.
+
+use super::Finding;
+use crate::domain::validators::mrz_validator::MrzData;
+use shared::{ExtractedData, ForgerySignal};
+
+struct Specimen {
+    document: &'static str,
+    /// Document and card numbers printed on it
+    numbers: &'static [&'static str],
+    surname: &'static str,
+    given_names: &'static str,
+}
+
+const SPECIMENS: [Specimen; 4] = [
+    Specimen {
+        document: "ICAO 9303 passport",
+        numbers: &["L898902C3", "L898902C"],
+        surname: "ERIKSSON",
+        given_names: "ANNA MARIA",
+    },
+    Specimen {
+        document: "ICAO 9303 identity card",
+        numbers: &["D23145890"],
+        surname: "ERIKSSON",
+        given_names: "ANNA MARIA",
+    },
+    Specimen {
+        document: "Spanish DNI",
+        numbers: &["99999999R", "BAA000589"],
+        surname: "ESPAÑOLA ESPAÑOLA",
+        given_names: "CARMEN",
+    },
+    Specimen {
+        document: "German identity card and passport",
+        numbers: &["T22000129", "C01X00T47", "LZ6311T47"],
+        surname: "MUSTERMANN",
+        given_names: "ERIKA",
+    },
+];
+
+/// Upper case letters and digits only, `Ñ` as the MRZ spells it.
+fn normalize(text: &str) -> String {
+    text.to_uppercase()
+        .replace('Ñ', "N")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect()
+}
+
+pub fn inspect(data: &ExtractedData, mrz: Option<&MrzData>) -> Option<Finding> {
+    let numbers: Vec<String> = [
+        data.document_number.as_deref(),
+        data.support_number.as_deref(),
+        mrz.map(|m| m.document_number.as_str()),
+        mrz.map(|m| m.optional_data.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(normalize)
+    .filter(|n| !n.is_empty())
+    .collect();
+    let surname = normalize(data.surname.as_deref().or(mrz.map(|m| m.surname.as_str())).unwrap_or_default());
+    let given_names = normalize(data.name.as_deref().or(mrz.map(|m| m.given_names.as_str())).unwrap_or_default());
+
+    SPECIMENS.iter().find_map(|specimen| {
+        let number = specimen
+            .numbers
+            .iter()
+            .find(|n| numbers.iter().any(|read| read == *n))
+            .copied();
+        let name = !surname.is_empty()
+            && surname == normalize(specimen.surname)
+            && given_names == normalize(specimen.given_names);
+
+        match (number, name) {
+            (Some(number), _) => Some(Finding::new(
+                ForgerySignal::SpecimenDocument,
+                1.0,
+                format!("Number {} is the published {} specimen's", number, specimen.document),
+            )),
+            (None, true) => Some(Finding::new(
+                ForgerySignal::SpecimenDocument,
+                0.8,
+                format!("The holder's name is the published {} specimen's", specimen.document),
+            )),
+            (None, false) => None,
+        }
+    })
+}
@@ -0,0 +1,263 @@
+//! Compares what the pilgrim typed with what the document says. Names are
+//! compared with accents, case and punctuation set aside, and also as the
+//! MRZ transliterates them (ICAO 9303 part 3), so a typed "Müller" matches
+//! both the MRZ's MUELLER and the MULLER of a card that drops the umlaut.
+
+pub mod surnames;
+
+use shared::{DocumentType, EnteredData, ExtractedData, FieldMatch, MatchVerdict};
+
+pub use crate::domain::validators::mrz_validator::icao_transliterate;
+use crate::domain::validators::mrz_validator::mrz_spellings;
+
+/// Values this similar are taken for a typo or a misread; less, for
+/// different values.
+pub const SIMILAR_THRESHOLD: f32 = 0.8;
+
+/// Whether `a` and `b` are one name but for accents, hyphens, spacing or
+/// MRZ transliteration.
+pub fn equivalent(a: &str, b: &str) -> bool {
+    let (a, b) = (mrz_spellings(a), mrz_spellings(b));
+    a.iter()
+        .any(|a| b.iter().any(|b| a.replace(' ', "") == b.replace(' ', "")))
+}
+
+/// 0 to 1, how alike two names are under any of their spellings. Word order
+/// is set aside, and a name typed as only some of the document's words
+/// counts as similar.
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let mut best: f32 = 0.0;
+    for a in mrz_spellings(a) {
+        for b in mrz_spellings(b) {
+            best = best
+                .max(ratio(&a.replace(' ', ""), &b.replace(' ', "")))
+                .max(ratio(&sorted_words(&a), &sorted_words(&b)));
+            let (words_a, words_b): (Vec<&str>, Vec<&str>) = (a.split(' ').collect(), b.split(' ').collect());
+            let (fewer, more) = if words_a.len() <= words_b.len() { (words_a, words_b) } else { (words_b, words_a) };
+            if !fewer.is_empty() && fewer.iter().all(|w| more.contains(w)) {
+                best = best.max(SIMILAR_THRESHOLD);
+            }
+        }
+    }
+    best
+}
+
+fn sorted_words(value: &str) -> String {
+    let mut words: Vec<&str> = value.split(' ').collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+/// 1 less the edit distance as a share of the longer string.
+fn ratio(a: &str, b: &str) -> f32 {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f32 / longest as f32
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Uppercase with single spaces, the way names are stored.
+fn tidy(value: &str) -> String {
+    value.to_uppercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Letters outside ASCII: how much of a name's spelling a value keeps.
+fn accents(value: &str) -> usize {
+    value.chars().filter(|c| c.is_alphabetic() && !c.is_ascii()).count()
+}
+
+pub struct FieldMatcher;
+
+impl Default for FieldMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FieldMatcher {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Each field the pilgrim or the document has a value for, compared.
+    pub fn compare(&self, entered: &EnteredData, read: &ExtractedData, document_type: DocumentType) -> Vec<FieldMatch> {
+        let spanish = document_type == DocumentType::DNI
+            || [&read.nationality, &entered.nationality]
+                .iter()
+                .any(|n| n.as_deref().map(|n| nationality_code(&tidy(n))).as_deref() == Some("ESP"));
+
+        // The document gives one surname; split it as the pilgrim was asked to
+        let (read_last_1, read_last_2) = match &read.surname {
+            Some(surname) if spanish || entered.last_name_2.is_some() => {
+                surnames::split(surname, entered.last_name_1.as_deref())
+            }
+            Some(surname) => (tidy(surname), None),
+            None => (String::new(), None),
+        };
+        let read_last_1 = read.surname.as_ref().map(|_| read_last_1);
+
+        let mut matches = vec![];
+        let names = [
+            ("first_name", &entered.first_name, &read.name),
+            ("last_name_1", &entered.last_name_1, &read_last_1),
+            ("last_name_2", &entered.last_name_2, &read_last_2),
+        ];
+        let swapped = Self::names_swapped(entered, read);
+        for (field, typed, on_document) in names {
+            if let Some(found) = Self::compare_name(field, typed.as_deref(), on_document.as_deref(), swapped) {
+                matches.push(found);
+            }
+        }
+
+        let typed_number = entered.document_number.as_deref().map(tidy);
+        let read_number = read.document_number.as_deref().map(tidy);
+        if let Some(found) = Self::compare_codes("document_number", typed_number, read_number, |v| {
+            v.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+        }) {
+            matches.push(found);
+        }
+
+        let date = |d: &chrono::DateTime<chrono::Utc>| d.format("%Y-%m-%d").to_string();
+        if let Some(found) = Self::compare_dates(entered.birth_date.as_ref().map(date), read.birth_date.as_ref().map(date)) {
+            matches.push(found);
+        }
+
+        if let Some(found) = Self::compare_codes(
+            "nationality",
+            entered.nationality.as_deref().map(tidy),
+            read.nationality.as_deref().map(tidy),
+            nationality_code,
+        ) {
+            matches.push(found);
+        }
+        matches
+    }
+
+    /// Whether the pilgrim put their given names in the surname fields and
+    /// their surnames in the given name field.
+    fn names_swapped(entered: &EnteredData, read: &ExtractedData) -> bool {
+        let (Some(first), Some(last_1), Some(name), Some(surname)) =
+            (&entered.first_name, &entered.last_name_1, &read.name, &read.surname)
+        else {
+            return false;
+        };
+        let last = match &entered.last_name_2 {
+            Some(last_2) => format!("{} {}", last_1, last_2),
+            None => last_1.clone(),
+        };
+        similarity(first, name) < SIMILAR_THRESHOLD
+            && similarity(first, surname) >= SIMILAR_THRESHOLD
+            && similarity(&last, name) >= SIMILAR_THRESHOLD
+    }
+
+    fn compare_name(field: &str, typed: Option<&str>, read: Option<&str>, swapped: bool) -> Option<FieldMatch> {
+        let (similarity, verdict, canonical) = match (typed, read) {
+            (None, None) => return None,
+            (Some(value), None) | (None, Some(value)) => (0.0, MatchVerdict::Missing, tidy(value)),
+            (Some(typed), Some(read)) if swapped => (similarity(typed, read), MatchVerdict::Swapped, tidy(read)),
+            (Some(typed), Some(read)) => {
+                let (typed, read) = (tidy(typed), tidy(read));
+                if typed == read {
+                    (1.0, MatchVerdict::Same, read)
+                } else if equivalent(&typed, &read) {
+                    // Keep the fuller spelling: an MRZ or a careless card
+                    // loses accents, the pilgrim rarely adds any
+                    let canonical = if accents(&typed) > accents(&read) { typed } else { read };
+                    (1.0, MatchVerdict::Equivalent, canonical)
+                } else {
+                    let score = similarity(&typed, &read);
+                    let verdict = if score >= SIMILAR_THRESHOLD { MatchVerdict::Similar } else { MatchVerdict::Different };
+                    (score, verdict, read)
+                }
+            }
+        };
+        Some(FieldMatch {
+            field: field.to_string(),
+            entered: typed.map(tidy),
+            read: read.map(tidy),
+            similarity,
+            verdict,
+            canonical: Some(canonical),
+        })
+    }
+
+    /// Codes compared as `normalize` writes them; the document's value is
+    /// always the one kept.
+    fn compare_codes(
+        field: &str,
+        typed: Option<String>,
+        read: Option<String>,
+        normalize: impl Fn(&str) -> String,
+    ) -> Option<FieldMatch> {
+        let (similarity, verdict) = match (&typed, &read) {
+            (None, None) => return None,
+            (Some(_), None) | (None, Some(_)) => (0.0, MatchVerdict::Missing),
+            (Some(t), Some(r)) if t == r => (1.0, MatchVerdict::Same),
+            (Some(t), Some(r)) if normalize(t) == normalize(r) => (1.0, MatchVerdict::Equivalent),
+            (Some(t), Some(r)) => {
+                let score = ratio(&normalize(t), &normalize(r));
+                let verdict = if score >= SIMILAR_THRESHOLD { MatchVerdict::Similar } else { MatchVerdict::Different };
+                (score, verdict)
+            }
+        };
+        Some(FieldMatch {
+            field: field.to_string(),
+            canonical: read.as_deref().or(typed.as_deref()).map(&normalize),
+            entered: typed,
+            read,
+            similarity,
+            verdict,
+        })
+    }
+
+    /// Dates as `YYYY-MM-DD`. Day and month typed the other way round, as
+    /// a form in a foreign date order invites, counts as similar.
+    fn compare_dates(typed: Option<String>, read: Option<String>) -> Option<FieldMatch> {
+        let day_month_swapped = |a: &str, b: &str| a.len() == 10 && b.len() == 10 && a[..4] == b[..4] && a[5..7] == b[8..] && a[8..] == b[5..7];
+        let (similarity, verdict) = match (&typed, &read) {
+            (None, None) => return None,
+            (Some(_), None) | (None, Some(_)) => (0.0, MatchVerdict::Missing),
+            (Some(t), Some(r)) if t == r => (1.0, MatchVerdict::Same),
+            (Some(t), Some(r)) => {
+                let score = ratio(t, r);
+                let verdict = if score >= SIMILAR_THRESHOLD || day_month_swapped(t, r) {
+                    MatchVerdict::Similar
+                } else {
+                    MatchVerdict::Different
+                };
+                (score, verdict)
+            }
+        };
+        Some(FieldMatch {
+            field: "birth_date".to_string(),
+            canonical: read.clone().or(typed.clone()),
+            entered: typed,
+            read,
+            similarity,
+            verdict,
+        })
+    }
+}
+
+/// An ICAO nationality code as three letters; Germany's is the single `D`.
+fn nationality_code(value: &str) -> String {
+    match value.trim_end_matches('<') {
+        "D" => "DEU".to_string(),
+        code => code.to_string(),
+    }
+}
@@ -0,0 +1,89 @@
+//! Spanish double surnames. A Spaniard carries two, the father's first then
+//! the mother's, and either may run to several words: DE LA FUENTE,
+//! ORTEGA Y GASSET. A document prints them as one line and its MRZ runs
+//! them together, so the boundary has to be worked out again.
+
+use super::{similarity, tidy, SIMILAR_THRESHOLD};
+use crate::domain::validators::mrz_validator::mrz_comparable;
+
+/// Words that belong with the word after them.
+const PARTICLES: [&str; 8] = ["DE", "DEL", "LA", "LAS", "LOS", "SAN", "SANTA", "VON"];
+
+/// Words that join the surname before them to the one after: a compound
+/// surname, not the boundary between two.
+const JOINERS: [&str; 2] = ["Y", "I"];
+
+/// `surname` as the first and second surnames. `first_hint` is what the
+/// pilgrim typed as the first one; it settles where the boundary falls when
+/// it matches a run of the leading words. Otherwise particles stay with the
+/// following word and joined surnames stay whole, and everything after the
+/// first surname is taken as the second.
+pub fn split(surname: &str, first_hint: Option<&str>) -> (String, Option<String>) {
+    let words: Vec<String> = tidy(surname).split(' ').filter(|w| !w.is_empty()).map(String::from).collect();
+    if words.len() < 2 {
+        return (words.join(" "), None);
+    }
+
+    if let Some(hint) = first_hint {
+        let best = (1..words.len())
+            .map(|at| (at, similarity(&words[..at].join(" "), hint)))
+            .fold(None, |best: Option<(usize, f32)>, (at, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((at, score)),
+            });
+        if let Some((at, score)) = best {
+            // The whole surname may be what was typed as the first one
+            if score >= SIMILAR_THRESHOLD && score > similarity(&words.join(" "), hint) {
+                return (words[..at].join(" "), Some(words[at..].join(" ")));
+            }
+        }
+    }
+
+    let units = units(&words);
+    match units.split_first() {
+        Some((first, rest)) if !rest.is_empty() => (first.clone(), Some(rest.join(" "))),
+        _ => (words.join(" "), None),
+    }
+}
+
+/// The words grouped into single surnames.
+fn units(words: &[String]) -> Vec<String> {
+    let is = |list: &[&str], word: &str| list.contains(&mrz_comparable(word).as_str());
+    let mut units: Vec<String> = vec![];
+    let mut pending: Vec<&str> = vec![];
+    let mut join_next = false;
+    for word in words {
+        if is(&JOINERS, word) && !units.is_empty() && pending.is_empty() {
+            let last = units.last_mut().expect("checked not empty");
+            last.push(' ');
+            last.push_str(word);
+            join_next = true;
+        } else if is(&PARTICLES, word) {
+            pending.push(word);
+        } else {
+            pending.push(word);
+            let unit = pending.join(" ");
+            pending.clear();
+            match units.last_mut() {
+                Some(last) if join_next => {
+                    last.push(' ');
+                    last.push_str(&unit);
+                }
+                _ => units.push(unit),
+            }
+            join_next = false;
+        }
+    }
+    // Particles with nothing after them belong to the surname before
+    if !pending.is_empty() {
+        let rest = pending.join(" ");
+        match units.last_mut() {
+            Some(last) => {
+                last.push(' ');
+                last.push_str(&rest);
+            }
+            None => units.push(rest),
+        }
+    }
+    units
+}
@@ -1,7 +1,23 @@
 pub mod validators;
+pub mod barcode;
+pub mod chip;
 pub mod entities;
+pub mod forgery;
+pub mod matching;
 pub mod ocr;
+pub mod portrait;
+pub mod review;
+pub mod rules;
+pub mod upload;
 
 pub use validators::*;
+pub use barcode::*;
+pub use chip::*;
 pub use entities::*;
-pub use ocr::*;
\ No newline at end of file
+pub use forgery::*;
+pub use matching::*;
+pub use ocr::*;
+pub use portrait::*;
+pub use review::*;
+pub use rules::*;
+pub use upload::*;
\ No newline at end of file
@@ -1,28 +1,70 @@
-use shared::AlbergueResult;
 use std::collections::HashMap;
 
+/// A passed check digit or control letter catches any single misread
+/// character, so it leaves little doubt about the value.
+const CHECKED_FLOOR: f32 = 0.95;
+const FAILED_CHECK_CEILING: f32 = 0.2;
+/// The MRZ value was kept, but the visual zone reads otherwise.
+const CONFLICT_CEILING: f32 = 0.6;
+
+/// Whether a check digit or control letter vouches for a field's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    Failed,
+    Unchecked,
+}
+
 pub struct ConfidenceScorer;
 
+impl Default for ConfidenceScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConfidenceScorer {
     pub fn new() -> Self {
         Self
     }
 
-    pub fn calculate_field_confidence(
-        &self,
-        field_name: &str,
-        extracted_value: &str,
-        context: &str,
-    ) -> f32 {
+    pub fn calculate_field_confidence(&self, field_name: &str, extracted_value: &str) -> f32 {
         match field_name {
             "document_number" => self.score_document_number(extracted_value),
             "name" | "surname" => self.score_name_field(extracted_value),
-            "birth_date" | "expiry_date" => self.score_date_field(extracted_value),
+            "birth_date" | "expiry_date" | "issue_date" => self.score_date_field(extracted_value),
             "nationality" => self.score_nationality_field(extracted_value),
+            "sex" => self.score_sex_field(extracted_value),
+            "support_number" => self.score_support_number(extracted_value),
+            "address" | "municipality" | "province" | "birthplace" => self.score_place_field(extracted_value),
             _ => 0.5, // Default confidence for unknown fields
         }
     }
 
+    /// Blends how plausible a value looks with how surely its characters were
+    /// recognised, then lets a check digit settle it either way.
+    pub fn combine_field_confidence(
+        &self,
+        format_score: f32,
+        ocr_confidence: Option<f32>,
+        check: CheckOutcome,
+        conflicting: bool,
+    ) -> f32 {
+        let mut confidence = match ocr_confidence {
+            Some(ocr) => (format_score * ocr).sqrt(),
+            None => format_score,
+        };
+        match check {
+            CheckOutcome::Passed => confidence = confidence.max(CHECKED_FLOOR),
+            CheckOutcome::Failed => confidence = confidence.min(FAILED_CHECK_CEILING),
+            CheckOutcome::Unchecked => {}
+        }
+        if conflicting {
+            confidence = confidence.min(CONFLICT_CEILING);
+        }
+        confidence.clamp(0.0, 1.0)
+    }
+
     pub fn calculate_overall_confidence(&self, field_scores: &HashMap<String, f32>) -> f32 {
         if field_scores.is_empty() {
             return 0.0;
@@ -142,7 +184,7 @@ impl ConfidenceScorer {
     fn validate_date_logic(&self, date_str: &str) -> f32 {
         // Extract day, month, year and validate logical ranges
         let parts: Vec<&str> = date_str
-            .split(|c| c == '/' || c == '-' || c == '.')
+            .split(['/', '-', '.'])
             .collect();
 
         if parts.len() != 3 {
@@ -154,7 +196,7 @@ impl ConfidenceScorer {
             parts[1].parse::<u32>(),
             parts[2].parse::<u32>(),
         ) {
-            if day >= 1 && day <= 31 && month >= 1 && month <= 12 && year >= 1900 && year <= 2100 {
+            if (1..=31).contains(&day) && (1..=12).contains(&month) && (1900..=2100).contains(&year) {
                 return 0.9;
             }
         }
@@ -197,6 +239,11 @@ impl ConfidenceScorer {
             return 0.95;
         }
 
+        // ICAO codes, as MRZs give them; Germany is `D`
+        if cleaned == "D" || (cleaned.len() == 3 && cleaned.chars().all(|c| c.is_ascii_uppercase())) {
+            return 0.9;
+        }
+
         // Check if it looks like a nationality (mostly letters, reasonable length)
         if cleaned.len() >= 4
             && cleaned.len() <= 20
@@ -208,6 +255,41 @@ impl ConfidenceScorer {
         0.2
     }
 
+    fn score_sex_field(&self, value: &str) -> f32 {
+        match value {
+            "M" | "F" | "X" => 0.95,
+            _ => 0.2,
+        }
+    }
+
+    fn score_support_number(&self, value: &str) -> f32 {
+        use regex::Regex;
+
+        // IDESP on a DNI, card number on a TIE
+        if let Ok(support_regex) = Regex::new(r"^([A-Z]{3}\d{6}|[A-Z]\d{8})$") {
+            if support_regex.is_match(value) {
+                return 0.9;
+            }
+        }
+        0.3
+    }
+
+    fn score_place_field(&self, value: &str) -> f32 {
+        let cleaned = value.trim();
+        if cleaned.is_empty() {
+            return 0.0;
+        }
+
+        // Street lines carry numbers and abbreviations, but are mostly letters
+        let letter_ratio =
+            cleaned.chars().filter(|c| c.is_alphabetic()).count() as f32 / cleaned.chars().count() as f32;
+        if letter_ratio > 0.5 {
+            0.8
+        } else {
+            0.4
+        }
+    }
+
     fn calculate_key_fields_bonus(&self, field_scores: &HashMap<String, f32>) -> f32 {
         let key_fields = ["document_number", "name", "surname"];
         let mut bonus = 0.0;
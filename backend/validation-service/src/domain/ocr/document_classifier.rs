@@ -0,0 +1,196 @@
+use shared::DocumentType;
+
+use super::image_processor::Layout;
+use crate::domain::validators::dni_validator::DniValidator;
+use crate::domain::validators::id_card_validator::IdCardValidator;
+use crate::domain::validators::mrz_validator::{mrz_comparable, MrzData, MrzValidator};
+use crate::domain::validators::nie_validator::NieValidator;
+
+/// Below this the signals are too weak to name a type at all.
+const MIN_CONFIDENCE: f32 = 0.3;
+
+/// Printed titles and labels, accent-folded, with the type they point to and
+/// how strongly.
+const KEYWORDS: &[(&str, DocumentType, f32)] = &[
+    ("DOCUMENTO NACIONAL DE IDENTIDAD", DocumentType::DNI, 0.85),
+    ("DNI", DocumentType::DNI, 0.6),
+    ("NUM SOPORT", DocumentType::DNI, 0.6),
+    ("TARJETA DE IDENTIDAD DE EXTRANJERO", DocumentType::TIE, 0.85),
+    ("PERMISO DE RESIDENCIA", DocumentType::TIE, 0.8),
+    ("CERTIFICADO DE REGISTRO DE CIUDADANO", DocumentType::NIE, 0.85),
+    ("NIE", DocumentType::NIE, 0.6),
+    ("PASAPORTE", DocumentType::Passport, 0.8),
+    ("PASSPORT", DocumentType::Passport, 0.8),
+    ("PASSEPORT", DocumentType::Passport, 0.8),
+    ("REISEPASS", DocumentType::Passport, 0.8),
+    ("PASSAPORTO", DocumentType::Passport, 0.8),
+    ("CARTE NATIONALE D IDENTITE", DocumentType::EuIdCard, 0.7),
+    ("PERSONALAUSWEIS", DocumentType::EuIdCard, 0.7),
+    ("CARTA D IDENTITA", DocumentType::EuIdCard, 0.7),
+    ("CARTAO DE CIDADAO", DocumentType::EuIdCard, 0.7),
+    ("IDENTITY CARD", DocumentType::EuIdCard, 0.7),
+    ("PERMISO DE CONDUCCION", DocumentType::DrivingLicence, 0.8),
+    ("DRIVING LICENCE", DocumentType::DrivingLicence, 0.8),
+    ("PERMIS DE CONDUIRE", DocumentType::DrivingLicence, 0.8),
+    ("FUHRERSCHEIN", DocumentType::DrivingLicence, 0.8),
+    ("PATENTE DI GUIDA", DocumentType::DrivingLicence, 0.8),
+    ("CARTA DE CONDUCAO", DocumentType::DrivingLicence, 0.8),
+];
+
+const LAYOUT_WEIGHT: f32 = 0.3;
+const DNI_NUMBER_WEIGHT: f32 = 0.5;
+const NIE_NUMBER_WEIGHT: f32 = 0.6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub document_type: DocumentType,
+    pub confidence: f32,
+    pub issuing_country: Option<String>,
+    pub evidence: Vec<String>,
+}
+
+/// Evidence gathered for one document type.
+#[derive(Default)]
+struct Score {
+    /// Chance that at least one signal is right, treating them as independent
+    belief: f32,
+    evidence: Vec<String>,
+}
+
+impl Score {
+    fn add(&mut self, weight: f32, evidence: String) {
+        self.belief = 1.0 - (1.0 - self.belief) * (1.0 - weight);
+        self.evidence.push(evidence);
+    }
+}
+
+/// Tells the accepted document types apart from what was read off them: the
+/// MRZ document code when there is one, else printed titles, the number's
+/// own format and the shape of the document.
+pub struct DocumentClassifier {
+    mrz_validator: MrzValidator,
+    nie_validator: NieValidator,
+}
+
+impl Default for DocumentClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentClassifier {
+    pub fn new() -> Self {
+        Self {
+            mrz_validator: MrzValidator::new(),
+            nie_validator: NieValidator::new(),
+        }
+    }
+
+    pub fn classify(&self, ocr_text: &str, layout: Layout) -> Option<Classification> {
+        let mut scores: Vec<(DocumentType, Score)> = DocumentType::ALL
+            .into_iter()
+            .map(|t| (t, Score::default()))
+            .collect();
+        let mut score = |document_type: DocumentType, weight: f32, evidence: String| {
+            if let Some((_, s)) = scores.iter_mut().find(|(t, _)| *t == document_type) {
+                s.add(weight, evidence);
+            }
+        };
+
+        let mrz = self.mrz_validator.correct(ocr_text).ok().map(|c| c.data);
+        if let Some((document_type, weight, evidence)) = mrz.as_ref().and_then(Self::from_mrz) {
+            score(document_type, weight, evidence);
+        }
+
+        let folded = format!(" {} ", mrz_comparable(ocr_text));
+        for (keyword, document_type, weight) in KEYWORDS {
+            if folded.contains(&format!(" {} ", keyword)) {
+                score(*document_type, *weight, format!("Printed \"{}\"", keyword));
+            }
+        }
+
+        for token in ocr_text.split(|c: char| !c.is_ascii_alphanumeric()) {
+            if DniValidator::validate_checksum(token) {
+                score(DocumentType::DNI, DNI_NUMBER_WEIGHT, format!("DNI number {}", token));
+            } else if self.nie_validator.validate_nie(token).unwrap_or(false) {
+                score(DocumentType::NIE, NIE_NUMBER_WEIGHT, format!("NIE number {}", token));
+            }
+        }
+
+        // The shape cannot tell one card from another, so it only counts against
+        // types printed on another format; an MRZ already settles the format
+        if mrz.is_none() {
+            for (document_type, s) in scores.iter_mut() {
+                if !Self::fits(layout, *document_type) {
+                    s.belief *= 1.0 - LAYOUT_WEIGHT;
+                }
+            }
+        }
+
+        scores.sort_by(|(_, a), (_, b)| b.belief.total_cmp(&a.belief));
+        let [(document_type, best), (_, runner_up), ..] = &mut scores[..] else {
+            return None;
+        };
+        if best.belief < MIN_CONFIDENCE || best.belief == runner_up.belief {
+            return None;
+        }
+
+        if mrz.is_none() && Self::fits(layout, *document_type) {
+            match layout {
+                Layout::Id1Card => best.evidence.push("ID-1 card format".to_string()),
+                Layout::PassportPage => best.evidence.push("Passport page format".to_string()),
+                Layout::Unknown => {}
+            }
+        }
+
+        // Discounted by how much of the evidence points elsewhere
+        let confidence = best.belief * best.belief / (best.belief + runner_up.belief);
+        Some(Classification {
+            document_type: *document_type,
+            confidence,
+            issuing_country: mrz.map(|m| m.issuing_state).filter(|s| !s.is_empty()),
+            evidence: std::mem::take(&mut best.evidence),
+        })
+    }
+
+    fn fits(layout: Layout, document_type: DocumentType) -> bool {
+        match layout {
+            Layout::Id1Card => document_type != DocumentType::Passport,
+            Layout::PassportPage => document_type == DocumentType::Passport,
+            Layout::Unknown => true,
+        }
+    }
+
+    /// ICAO 9303 document codes: `P` for passports, `I`, `A` or `C` for
+    /// cards. Spain issues the DNI as `ID` and the TIE as `IR`.
+    fn from_mrz(mrz: &MrzData) -> Option<(DocumentType, f32, String)> {
+        let code = mrz.document_code.as_str();
+        let described = |what: &str| format!("MRZ document code {} issued by {}: {}", code, mrz.issuing_state, what);
+
+        match (code.chars().next()?, mrz.issuing_state.as_str()) {
+            ('P', _) => Some((DocumentType::Passport, 0.98, described("passport"))),
+            ('I' | 'A' | 'C', "ESP") => {
+                let carries_nie = [&mrz.optional_data, &mrz.optional_data_2, &mrz.document_number]
+                    .iter()
+                    .any(|v| NieValidator::is_nie_format(v));
+                if code == "IR" || carries_nie {
+                    Some((DocumentType::TIE, 0.97, described("foreigner identity card (TIE)")))
+                } else if code == "ID" {
+                    Some((DocumentType::DNI, 0.97, described("DNI")))
+                } else {
+                    Some((DocumentType::DNI, 0.9, described("Spanish identity card")))
+                }
+            }
+            ('I' | 'A' | 'C', state) if IdCardValidator::is_accepted_state(state) => {
+                Some((DocumentType::EuIdCard, 0.95, described("EU identity card")))
+            }
+            // Still an identity card, which validation will then refuse
+            ('I' | 'A' | 'C', _) => Some((
+                DocumentType::EuIdCard,
+                0.6,
+                described("identity card from outside the EU"),
+            )),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use shared::{AlbergueError, AlbergueResult};
+use std::sync::OnceLock;
+
+/// Glyphs are compared as a coarse grid of ink coverage.
+pub const GRID_WIDTH: usize = 8;
+pub const GRID_HEIGHT: usize = 10;
+
+const ASPECT_WEIGHT: f32 = 0.5;
+const PLACEMENT_WEIGHT: f32 = 1.0;
+
+/// Distance at which a match is no better than a guess; unrelated glyphs sit
+/// around 0.15 to 0.25.
+const MAX_DISTANCE: f32 = 0.25;
+
+static MRZ: OnceLock<GlyphModel> = OnceLock::new();
+static LATIN: OnceLock<GlyphModel> = OnceLock::new();
+
+/// Where a glyph sits on its text line, in units of the line's cap height.
+/// Tells `o` from `O` and `,` from `'`, which look alike once scaled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub height: f32,
+    /// Distance from the baseline up to the glyph's top
+    pub top: f32,
+}
+
+/// Ink coverage of one segmented character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major coverage, 0 for paper and 1 for ink
+    pub pixels: Vec<f32>,
+    pub placement: Option<Placement>,
+}
+
+impl Glyph {
+    /// Area-averages the bitmap into the comparison grid, so narrow glyphs
+    /// such as `I` still spread over every cell they cover.
+    pub fn features(&self) -> Features {
+        let mut grid = [0.0; GRID_WIDTH * GRID_HEIGHT];
+        let (w, h) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        let cell_w = w / GRID_WIDTH as f32;
+        let cell_h = h / GRID_HEIGHT as f32;
+
+        for (gy, row) in grid.chunks_mut(GRID_WIDTH).enumerate() {
+            let (y0, y1) = (gy as f32 * cell_h, (gy + 1) as f32 * cell_h);
+            for (gx, cell) in row.iter_mut().enumerate() {
+                let (x0, x1) = (gx as f32 * cell_w, (gx + 1) as f32 * cell_w);
+                let mut ink = 0.0;
+                for y in y0.floor() as usize..(y1.ceil() as usize).min(self.height) {
+                    let dy = (y1.min(y as f32 + 1.0) - y0.max(y as f32)).max(0.0);
+                    for x in x0.floor() as usize..(x1.ceil() as usize).min(self.width) {
+                        let dx = (x1.min(x as f32 + 1.0) - x0.max(x as f32)).max(0.0);
+                        ink += dx * dy * self.pixels[y * self.width + x];
+                    }
+                }
+                *cell = ink / (cell_w * cell_h);
+            }
+        }
+
+        Features {
+            grid,
+            aspect: (w / h).min(3.0),
+            placement: self.placement,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Features {
+    pub grid: [f32; GRID_WIDTH * GRID_HEIGHT],
+    /// Width over height
+    pub aspect: f32,
+    pub placement: Option<Placement>,
+}
+
+impl Features {
+    fn distance(&self, other: &Features) -> f32 {
+        let grid = self
+            .grid
+            .iter()
+            .zip(&other.grid)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            / self.grid.len() as f32;
+        let aspect = (self.aspect - other.aspect).powi(2) * ASPECT_WEIGHT;
+        let placement = match (self.placement, other.placement) {
+            (Some(a), Some(b)) => ((a.height - b.height).powi(2) + (a.top - b.top).powi(2)) * PLACEMENT_WEIGHT,
+            _ => 0.0,
+        };
+        grid + aspect + placement
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub ch: char,
+    pub features: Features,
+}
+
+/// Nearest-template character classifier. Models are plain text so they can
+/// be embedded in the WASM component and diffed when regenerated; see
+/// `examples/build_glyph_models.rs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphModel {
+    pub name: String,
+    pub templates: Vec<Template>,
+}
+
+impl GlyphModel {
+    /// Digits, capitals and filler of machine readable zones. Rendered from
+    /// DejaVu Sans Mono and DejaVu Sans, stand-ins for a range of sans
+    /// shapes: no OCR-B font is bundled, and reading real MRZs improves once
+    /// this is rebuilt from one.
+    pub fn mrz() -> &'static GlyphModel {
+        MRZ.get_or_init(|| {
+            Self::parse(include_str!("../../../models/mrz.glyphs")).expect("embedded MRZ model is valid")
+        })
+    }
+
+    /// Latin letters with Spanish diacritics, digits and punctuation, for
+    /// the visual zone.
+    pub fn latin() -> &'static GlyphModel {
+        LATIN.get_or_init(|| {
+            Self::parse(include_str!("../../../models/latin.glyphs")).expect("embedded Latin model is valid")
+        })
+    }
+
+    pub fn from_glyphs(name: &str, glyphs: &[(char, Glyph)]) -> Self {
+        Self {
+            name: name.to_string(),
+            templates: glyphs
+                .iter()
+                .map(|(ch, glyph)| Template {
+                    ch: *ch,
+                    features: glyph.features(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut chars: Vec<char> = self.templates.iter().map(|t| t.ch).collect();
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+
+    /// Best matching character and a confidence in 0..=1 that drops both with
+    /// distance to the template and with how close the runner-up is.
+    pub fn classify(&self, glyph: &Glyph) -> Option<(char, f32)> {
+        self.classify_among(glyph, |_| true)
+    }
+
+    /// As `classify`, restricted to the characters `allowed` accepts.
+    pub fn classify_among(&self, glyph: &Glyph, allowed: impl Fn(char) -> bool) -> Option<(char, f32)> {
+        let features = glyph.features();
+        let mut best: Option<(char, f32)> = None;
+        let mut runner_up = f32::INFINITY;
+
+        for template in self.templates.iter().filter(|t| allowed(t.ch)) {
+            let distance = template.features.distance(&features);
+            match best {
+                Some((ch, d)) if distance >= d => {
+                    if template.ch != ch {
+                        runner_up = runner_up.min(distance);
+                    }
+                }
+                Some((ch, d)) => {
+                    if template.ch != ch {
+                        runner_up = d;
+                    }
+                    best = Some((template.ch, distance));
+                }
+                None => best = Some((template.ch, distance)),
+            }
+        }
+
+        best.map(|(ch, distance)| {
+            let quality = (1.0 - distance / MAX_DISTANCE).clamp(0.0, 1.0);
+            let margin = if runner_up.is_finite() {
+                ((runner_up - distance) / (runner_up + distance + f32::EPSILON)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            (ch, quality * (0.5 + 0.5 * margin))
+        })
+    }
+
+    /// Draws `ch` from its first template, `cap_height` pixels tall per unit
+    /// of placement height. Used to build synthetic test documents.
+    pub fn render(&self, ch: char, cap_height: usize) -> Option<Glyph> {
+        let features = &self.templates.iter().find(|t| t.ch == ch)?.features;
+        let height = features
+            .placement
+            .map_or(cap_height as f32, |p| p.height * cap_height as f32)
+            .round()
+            .max(1.0) as usize;
+        let width = (features.aspect * height as f32).round().max(1.0) as usize;
+
+        // Bilinear between cell centres, thresholded a little low so thin
+        // diagonals stay connected
+        let cell = |gx: f32, gy: f32| {
+            let gx = (gx.max(0.0) as usize).min(GRID_WIDTH - 1);
+            let gy = (gy.max(0.0) as usize).min(GRID_HEIGHT - 1);
+            features.grid[gy * GRID_WIDTH + gx]
+        };
+        let pixels = (0..height)
+            .flat_map(|y| {
+                (0..width).map(move |x| {
+                    let fx = (x as f32 + 0.5) * GRID_WIDTH as f32 / width as f32 - 0.5;
+                    let fy = (y as f32 + 0.5) * GRID_HEIGHT as f32 / height as f32 - 0.5;
+                    let (tx, ty) = (fx - fx.floor(), fy - fy.floor());
+                    let (x0, y0) = (fx.floor(), fy.floor());
+                    let top = cell(x0, y0) * (1.0 - tx) + cell(x0 + 1.0, y0) * tx;
+                    let bottom = cell(x0, y0 + 1.0) * (1.0 - tx) + cell(x0 + 1.0, y0 + 1.0) * tx;
+                    if top * (1.0 - ty) + bottom * ty >= 0.4 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+            })
+            .collect();
+
+        Some(Glyph {
+            width,
+            height,
+            pixels,
+            placement: features.placement,
+        })
+    }
+
+    /// One template per line: `<char> <aspect> <height>,<top>|- <grid as hex>`.
+    pub fn to_source(&self) -> String {
+        let mut out = format!("# Glyph model, generated by examples/build_glyph_models.rs\nmodel {}\n", self.name);
+        out.push_str(&format!("grid {} {}\n", GRID_WIDTH, GRID_HEIGHT));
+        for template in &self.templates {
+            let f = &template.features;
+            let placement = match f.placement {
+                Some(p) => format!("{:.3},{:.3}", p.height, p.top),
+                None => "-".to_string(),
+            };
+            let grid: String = f
+                .grid
+                .iter()
+                .map(|v| format!("{:02x}", (v.clamp(0.0, 1.0) * 255.0).round() as u8))
+                .collect();
+            out.push_str(&format!("{} {:.3} {} {}\n", template.ch, f.aspect, placement, grid));
+        }
+        out
+    }
+
+    pub fn parse(source: &str) -> AlbergueResult<Self> {
+        let invalid = |line: usize, what: &str| AlbergueError::Internal {
+            message: format!("Glyph model line {}: {}", line + 1, what),
+        };
+
+        let mut name = String::new();
+        let mut templates = vec![];
+
+        for (n, line) in source.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("model ") {
+                name = rest.trim().to_string();
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("grid ") {
+                if rest.trim() != format!("{} {}", GRID_WIDTH, GRID_HEIGHT) {
+                    return Err(invalid(n, "unsupported grid size"));
+                }
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split(' ').collect();
+            let [ch, aspect, placement, grid] = parts[..] else {
+                return Err(invalid(n, "expected 4 fields"));
+            };
+            let mut chars = ch.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return Err(invalid(n, "expected a single character"));
+            };
+            let aspect: f32 = aspect.parse().map_err(|_| invalid(n, "bad aspect"))?;
+            let placement = match placement {
+                "-" => None,
+                p => {
+                    let (height, top) = p.split_once(',').ok_or_else(|| invalid(n, "bad placement"))?;
+                    Some(Placement {
+                        height: height.parse().map_err(|_| invalid(n, "bad placement height"))?,
+                        top: top.parse().map_err(|_| invalid(n, "bad placement top"))?,
+                    })
+                }
+            };
+            if grid.len() != GRID_WIDTH * GRID_HEIGHT * 2 {
+                return Err(invalid(n, "bad grid length"));
+            }
+            let mut cells = [0.0; GRID_WIDTH * GRID_HEIGHT];
+            for (i, cell) in cells.iter_mut().enumerate() {
+                let byte = u8::from_str_radix(&grid[i * 2..i * 2 + 2], 16).map_err(|_| invalid(n, "bad grid hex"))?;
+                *cell = byte as f32 / 255.0;
+            }
+
+            templates.push(Template {
+                ch,
+                features: Features {
+                    grid: cells,
+                    aspect,
+                    placement,
+                },
+            });
+        }
+
+        Ok(Self { name, templates })
+    }
+}
@@ -1,9 +1,389 @@
-use image::{DynamicImage, GenericImageView, ImageBuffer, ImageFormat};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, GrayImage, ImageFormat, Luma, Rgb, RgbImage};
 use shared::AlbergueResult;
+use std::fmt;
 use std::io::Cursor;
 
+use super::recognizer::Recognizer;
+use super::segmentation;
+
+/// Width over height of ID-1 cards: DNI, TIE and EU identity cards.
+pub const ID1_ASPECT: f32 = 85.6 / 53.98;
+
+/// Width over height of a TD3 passport data page.
+pub const PASSPORT_PAGE_ASPECT: f32 = 125.0 / 88.0;
+/// How far from a format's aspect ratio an image may be and still match it;
+/// half the gap between ID-1 and TD3.
+const LAYOUT_TOLERANCE: f32 = 0.08;
+
+/// Card detection runs on a copy at most this many pixels across.
+const DETECTION_SIZE: u32 = 400;
+/// Smallest share of the photo a card must cover to be taken for one.
+const MIN_CARD_AREA: f32 = 0.15;
+/// Share of the card outline the card's own pixels must fill; the rest is
+/// printed text and the holder's photo.
+const MIN_CARD_FILL: f32 = 0.5;
+/// Long over short side of an outline that could be an ID-1 card seen at an
+/// angle.
+const CARD_ASPECTS: std::ops::RangeInclusive<f32> = 1.2..=2.1;
+
+/// Without a card outline, text is assumed to be within this many degrees of
+/// level.
+const MAX_SKEW_DEGREES: f32 = 10.0;
+/// Ink pixels sampled when searching for the skew angle.
+const SKEW_SAMPLES: usize = 20_000;
+
+/// Lighting is estimated over about this many blocks across the document,
+/// each larger than a line of text.
+const ILLUMINATION_BLOCKS: u32 = 16;
+const MIN_ILLUMINATION_BLOCK: u32 = 48;
+
+/// How much more confident a turned reading must be to be preferred.
+const ROTATION_MARGIN: f32 = 0.05;
+/// A reading at least this confident settles the orientation; upside-down
+/// text scores well under it.
+const CONFIDENT_ROTATION: f32 = 0.6;
+/// Share of the document's height, from the bottom, where an MRZ sits.
+const MRZ_STRIP: f32 = 0.35;
+
+/// Clipped highlights brighter than the document by this much are glare.
+const GLARE_MARGIN: u8 = 40;
+/// Share of the document glare may cover before fields are unreadable.
+const MAX_GLARE: f32 = 0.02;
+/// Edges whose steepest step is less than this share of their local
+/// contrast are too soft to segment glyphs from.
+const MIN_SHARPNESS: f32 = 0.25;
+const MIN_EDGE_PIXELS: usize = 50;
+
+/// Corners of a document in a photo, clockwise from the top left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quad {
+    pub corners: [(f32, f32); 4],
+}
+
+impl Quad {
+    /// Length of the edge from corner `i` to the next one.
+    pub fn edge(&self, i: usize) -> f32 {
+        let (a, b) = (self.corners[i % 4], self.corners[(i + 1) % 4]);
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    pub fn area(&self) -> f32 {
+        let twice: f32 = (0..4)
+            .map(|i| {
+                let (a, b) = (self.corners[i], self.corners[(i + 1) % 4]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum();
+        twice.abs() / 2.0
+    }
+
+    /// Long side over short side, averaging opposite edges.
+    pub fn aspect(&self) -> f32 {
+        let (across, down) = (self.edge(0) + self.edge(2), self.edge(1) + self.edge(3));
+        across.max(down) / across.min(down).max(1.0)
+    }
+
+    fn scaled(&self, factor: f32) -> Quad {
+        Quad {
+            corners: self.corners.map(|(x, y)| (x * factor, y * factor)),
+        }
+    }
+}
+
+/// Projective map between two planes; flattens a card photographed at an
+/// angle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Homography([f64; 9]);
+
+impl Homography {
+    /// The map taking each point of `from` onto the matching point of `to`,
+    /// or `None` when three of them are collinear.
+    pub fn between(from: &[(f32, f32); 4], to: &[(f32, f32); 4]) -> Option<Self> {
+        let mut system = [[0f64; 9]; 8];
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(to).enumerate() {
+            let (x, y, u, v) = (x as f64, y as f64, u as f64, v as f64);
+            system[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u, u];
+            system[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v, v];
+        }
+
+        // Gauss-Jordan elimination with partial pivoting
+        for col in 0..8 {
+            let pivot = (col..8).max_by(|&a, &b| system[a][col].abs().total_cmp(&system[b][col].abs()))?;
+            if system[pivot][col].abs() < 1e-9 {
+                return None;
+            }
+            system.swap(col, pivot);
+            let pivot_row = system[col];
+            for (r, row) in system.iter_mut().enumerate() {
+                if r == col {
+                    continue;
+                }
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+
+        let mut h = [1.0; 9];
+        for (i, row) in system.iter().enumerate() {
+            h[i] = row[8] / row[i];
+        }
+        Some(Self(h))
+    }
+
+    /// Turn by `degrees` clockwise about `center`.
+    pub fn rotation(degrees: f32, center: (f32, f32)) -> Self {
+        let (sin, cos) = (degrees as f64).to_radians().sin_cos();
+        let (cx, cy) = (center.0 as f64, center.1 as f64);
+        Self([cos, -sin, cx - cos * cx + sin * cy, sin, cos, cy - sin * cx - cos * cy, 0.0, 0.0, 1.0])
+    }
+
+    pub fn map(&self, x: f32, y: f32) -> (f32, f32) {
+        let h = &self.0;
+        let (x, y) = (x as f64, y as f64);
+        let w = h[6] * x + h[7] * y + h[8];
+        (
+            ((h[0] * x + h[1] * y + h[2]) / w) as f32,
+            ((h[3] * x + h[4] * y + h[5]) / w) as f32,
+        )
+    }
+}
+
+/// Builds a `width` by `height` image whose pixel centres `to_source` maps
+/// into `gray`, sampled bilinearly; points off the source read as `fill`.
+pub fn warp(gray: &GrayImage, to_source: &Homography, width: u32, height: u32, fill: u8) -> GrayImage {
+    GrayImage::from_fn(width, height, |u, v| {
+        let (x, y) = to_source.map(u as f32 + 0.5, v as f32 + 0.5);
+        Luma([sample(gray, x - 0.5, y - 0.5).unwrap_or(fill)])
+    })
+}
+
+/// `warp` for each channel of a colour image.
+pub fn warp_rgb(rgb: &RgbImage, to_source: &Homography, width: u32, height: u32, fill: [u8; 3]) -> RgbImage {
+    let channels: Vec<GrayImage> = (0..3)
+        .map(|c| {
+            let channel = GrayImage::from_fn(rgb.width(), rgb.height(), |x, y| Luma([rgb.get_pixel(x, y)[c]]));
+            warp(&channel, to_source, width, height, fill[c])
+        })
+        .collect();
+    RgbImage::from_fn(width, height, |x, y| {
+        Rgb([0, 1, 2].map(|c| channels[c].get_pixel(x, y)[0]))
+    })
+}
+
+fn sample(gray: &GrayImage, x: f32, y: f32) -> Option<u8> {
+    let (w, h) = (gray.width() as f32, gray.height() as f32);
+    if !(-0.5..=w - 0.5).contains(&x) || !(-0.5..=h - 0.5).contains(&y) {
+        return None;
+    }
+    let (x, y) = (x.clamp(0.0, w - 1.0), y.clamp(0.0, h - 1.0));
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(gray.width() - 1), (y0 + 1).min(gray.height() - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let at = |x, y| gray.get_pixel(x, y)[0] as f32;
+
+    let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+    let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+    Some((top * (1.0 - ty) + bottom * ty).round() as u8)
+}
+
+/// Value below which `share` of the pixels fall.
+pub(crate) fn percentile(gray: &GrayImage, share: f32) -> u8 {
+    let mut histogram = [0usize; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    let target = (gray.pixels().len() as f32 * share) as usize;
+    let mut seen = 0;
+    for (value, &count) in histogram.iter().enumerate() {
+        seen += count;
+        if seen > target {
+            return value as u8;
+        }
+    }
+    255
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Id1Card,
+    PassportPage,
+    Unknown,
+}
+
+/// Clockwise quarter turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Upright,
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+impl Rotation {
+    pub fn apply(self, gray: &GrayImage) -> GrayImage {
+        match self {
+            Self::Upright => gray.clone(),
+            Self::Quarter => imageops::rotate90(gray),
+            Self::Half => imageops::rotate180(gray),
+            Self::ThreeQuarters => imageops::rotate270(gray),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QualityIssue {
+    Glare { share: f32 },
+    Blurred { sharpness: f32 },
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Glare { share } => write!(f, "Glare covers {:.0}% of the document", share * 100.0),
+            Self::Blurred { sharpness } => write!(
+                f,
+                "Image is too blurred to read (sharpness {:.2}, minimum {:.2})",
+                sharpness, MIN_SHARPNESS
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageQuality {
+    /// Share of the document lost to clipped highlights
+    pub glare: f32,
+    /// About 1 for a crisp scan; `None` when there are no edges to judge
+    pub sharpness: Option<f32>,
+    pub issues: Vec<QualityIssue>,
+}
+
+impl ImageQuality {
+    /// Why the image should be retaken, if it should.
+    pub fn rejection_reason(&self) -> Option<String> {
+        if self.issues.is_empty() {
+            return None;
+        }
+        Some(self.issues.iter().map(|i| i.to_string()).collect::<Vec<_>>().join("; "))
+    }
+}
+
+/// A document photo made ready for OCR.
+#[derive(Debug, Clone)]
+pub struct PreparedImage {
+    pub image: GrayImage,
+    /// Outline of the card in the original photo, when one was found
+    pub card: Option<Quad>,
+    /// Skew corrected when no card outline was found, degrees clockwise
+    pub skew_degrees: f32,
+    pub rotation: Rotation,
+    pub quality: ImageQuality,
+}
+
+/// Pixels of one connected region of the card/background split.
+struct Blob {
+    pixels: usize,
+    touches_border: bool,
+    /// Points furthest along each of `Blob::DIRECTIONS`
+    extremes: [(f32, f32); 8],
+}
+
+impl Blob {
+    /// Diagonals first, for cards roughly level; then the axes, for cards
+    /// turned near 45 degrees. Both clockwise from the top left.
+    const DIRECTIONS: [(f32, f32); 8] = [
+        (-1.0, -1.0),
+        (1.0, -1.0),
+        (1.0, 1.0),
+        (-1.0, 1.0),
+        (0.0, -1.0),
+        (1.0, 0.0),
+        (0.0, 1.0),
+        (-1.0, 0.0),
+    ];
+
+    fn card_outline(&self, image_area: f32) -> Option<Quad> {
+        if self.touches_border || (self.pixels as f32) < image_area * MIN_CARD_AREA {
+            return None;
+        }
+
+        let diagonal = Quad {
+            corners: [self.extremes[0], self.extremes[1], self.extremes[2], self.extremes[3]],
+        };
+        let axis = Quad {
+            corners: [self.extremes[4], self.extremes[5], self.extremes[6], self.extremes[7]],
+        };
+        let quad = if diagonal.area() >= axis.area() { diagonal } else { axis };
+
+        let fill = self.pixels as f32 / quad.area().max(1.0);
+        (fill >= MIN_CARD_FILL && CARD_ASPECTS.contains(&quad.aspect())).then_some(quad)
+    }
+}
+
+/// 4-connected regions of the pixels `inside` accepts.
+fn blobs(gray: &GrayImage, inside: impl Fn(u8) -> bool) -> Vec<Blob> {
+    let (w, h) = (gray.width() as usize, gray.height() as usize);
+    let mask: Vec<bool> = gray.pixels().map(|p| inside(p[0])).collect();
+    let mut seen = vec![false; w * h];
+    let mut blobs = vec![];
+    let mut stack = vec![];
+
+    for start in 0..w * h {
+        if seen[start] || !mask[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let mut blob = Blob {
+            pixels: 0,
+            touches_border: false,
+            extremes: [(0.0, 0.0); 8],
+        };
+        let mut best = [f32::NEG_INFINITY; 8];
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            blob.pixels += 1;
+            blob.touches_border |= x == 0 || y == 0 || x == w - 1 || y == h - 1;
+
+            let point = (x as f32 + 0.5, y as f32 + 0.5);
+            for (d, (dx, dy)) in Blob::DIRECTIONS.iter().enumerate() {
+                let reach = point.0 * dx + point.1 * dy;
+                if reach > best[d] {
+                    best[d] = reach;
+                    blob.extremes[d] = point;
+                }
+            }
+
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if !seen[n] && mask[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        blobs.push(blob);
+    }
+    blobs
+}
+
 pub struct ImageProcessor;
 
+impl Default for ImageProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ImageProcessor {
     pub fn new() -> Self {
         Self
@@ -15,19 +395,17 @@ impl ImageProcessor {
                 message: format!("Failed to load image: {}", e),
             })?;
 
-        // Convert to grayscale for better OCR
-        let gray_img = img.to_luma8();
-
-        // Apply contrast enhancement
-        let enhanced = self.enhance_contrast(&gray_img);
-
-        // Detect and correct orientation
-        let corrected = self.correct_orientation(enhanced)?;
+        let prepared = self.prepare(&img.to_luma8());
+        if let Some(reason) = prepared.quality.rejection_reason() {
+            return Err(shared::AlbergueError::Validation {
+                message: format!("Image rejected: {}", reason),
+            });
+        }
 
         // Convert back to bytes
         let mut buffer = Vec::new();
         let mut cursor = Cursor::new(&mut buffer);
-        corrected
+        DynamicImage::ImageLuma8(prepared.image)
             .write_to(&mut cursor, ImageFormat::Png)
             .map_err(|e| shared::AlbergueError::Validation {
                 message: format!("Failed to encode image: {}", e),
@@ -36,30 +414,300 @@ impl ImageProcessor {
         Ok(buffer)
     }
 
-    fn enhance_contrast(&self, img: &image::GrayImage) -> DynamicImage {
-        // Simple contrast enhancement - can be improved with histogram equalization
-        let (width, height) = img.dimensions();
-        let mut enhanced = ImageBuffer::new(width, height);
+    /// Straightens and turns a document photo upright, scores whether it is
+    /// fit to read and evens out its lighting and contrast for OCR.
+    pub fn prepare(&self, gray: &GrayImage) -> PreparedImage {
+        let card = self.detect_card(gray);
+        let document = match &card {
+            Some(quad) => self.rectify(gray, quad),
+            None => gray.clone(),
+        };
+
+        // Judged before the lighting is evened out, which would flatten glare
+        // into plain paper
+        let quality = self.assess_quality(&document);
+        let even = self.even_illumination(&document);
+
+        let (straight, skew_degrees) = match card {
+            Some(_) => (even, 0.0),
+            None => {
+                let skew = self.estimate_skew(&even);
+                (self.deskew(&even, skew), skew)
+            }
+        };
+        let rotation = self.detect_rotation(&straight, card.is_some());
+
+        PreparedImage {
+            image: self.enhance_contrast(&rotation.apply(&straight)),
+            card,
+            skew_degrees,
+            rotation,
+            quality,
+        }
+    }
+
+    /// Divides out the paper's brightness, estimated as the brightest pixel of
+    /// each block, so a shadow across the document does not read as ink.
+    pub fn even_illumination(&self, gray: &GrayImage) -> GrayImage {
+        let (w, h) = (gray.width(), gray.height());
+        let block = (w.max(h) / ILLUMINATION_BLOCKS).max(MIN_ILLUMINATION_BLOCK);
+        let (columns, rows) = (w.div_ceil(block), h.div_ceil(block));
+
+        let mut paper = GrayImage::new(columns, rows);
+        for (x, y, pixel) in gray.enumerate_pixels() {
+            let cell = paper.get_pixel_mut(x / block, y / block);
+            cell[0] = cell[0].max(pixel[0]);
+        }
+
+        let mut even = gray.clone();
+        for (x, y, pixel) in even.enumerate_pixels_mut() {
+            // Bilinear between block centres
+            let fx = (x as f32 + 0.5) / block as f32 - 0.5;
+            let fy = (y as f32 + 0.5) / block as f32 - 0.5;
+            let background = sample(&paper, fx, fy).unwrap_or(u8::MAX).max(1);
+            pixel[0] = (pixel[0] as f32 * 255.0 / background as f32).min(255.0) as u8;
+        }
+        even
+    }
+
+    /// Stretches the 1st to 99th percentile over the full range, so faint
+    /// print on a grey card reaches black and white.
+    pub fn enhance_contrast(&self, gray: &GrayImage) -> GrayImage {
+        let (low, high) = (percentile(gray, 0.01), percentile(gray, 0.99));
+        if high.saturating_sub(low) < 32 {
+            return gray.clone();
+        }
+
+        let scale = 255.0 / (high - low) as f32;
+        let mut enhanced = gray.clone();
+        for pixel in enhanced.pixels_mut() {
+            pixel[0] = ((pixel[0].saturating_sub(low)) as f32 * scale).min(255.0) as u8;
+        }
+        enhanced
+    }
+
+    /// Outline of an ID card lying on a contrasting surface, if the photo
+    /// shows one with some margin around it.
+    pub fn detect_card(&self, gray: &GrayImage) -> Option<Quad> {
+        let scale = (DETECTION_SIZE as f32 / gray.width().max(gray.height()) as f32).min(1.0);
+        let width = ((gray.width() as f32 * scale).round() as u32).max(1);
+        let height = ((gray.height() as f32 * scale).round() as u32).max(1);
+        let small = imageops::resize(gray, width, height, FilterType::Triangle);
+        let threshold = segmentation::otsu_threshold(&small);
+        let area = (width * height) as f32;
+
+        // The card may be lighter or darker than what it lies on
+        [true, false]
+            .into_iter()
+            .flat_map(|light| blobs(&small, move |v| (v > threshold) == light))
+            .filter_map(|blob| blob.card_outline(area))
+            .max_by(|a, b| a.area().total_cmp(&b.area()))
+            .map(|quad| quad.scaled(1.0 / scale))
+    }
 
-        for (x, y, pixel) in img.enumerate_pixels() {
-            let gray_value = pixel[0];
-            // Apply simple contrast stretching
-            let enhanced_value = ((gray_value as f32 - 128.0) * 1.2 + 128.0)
-                .max(0.0)
-                .min(255.0) as u8;
-            enhanced.put_pixel(x, y, image::Luma([enhanced_value]));
+    /// Maps the card onto a rectangle of its format, long side across. The
+    /// result may still be upside down; see `detect_rotation`.
+    pub fn rectify(&self, gray: &GrayImage, card: &Quad) -> GrayImage {
+        match self.card_frame(card) {
+            Some((to_photo, width, height)) => warp(gray, &to_photo, width, height, percentile(gray, 0.9)),
+            None => gray.clone(),
         }
+    }
+
+    /// The map from an ID-1 rectangle, or a TD3 passport page when the
+    /// outline is nearer that shape, long side across, onto `card` in the
+    /// photo, with the rectangle's width and height.
+    pub fn card_frame(&self, card: &Quad) -> Option<(Homography, u32, u32)> {
+        let mut corners = card.corners;
+        if card.edge(0) + card.edge(2) < card.edge(1) + card.edge(3) {
+            corners.rotate_left(1);
+        }
+        let aspect = if (card.aspect() - PASSPORT_PAGE_ASPECT).abs() < (card.aspect() - ID1_ASPECT).abs() {
+            PASSPORT_PAGE_ASPECT
+        } else {
+            ID1_ASPECT
+        };
+        let across = Quad { corners };
+        let width = across.edge(0).max(across.edge(2)).round().max(1.0);
+        let height = (width / aspect).round().max(1.0);
+
+        let target = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)];
+        Homography::between(&target, &corners).map(|to_photo| (to_photo, width as u32, height as u32))
+    }
+
+    /// Angle of the text lines in degrees clockwise, found as the projection
+    /// that stacks ink into the sharpest rows.
+    pub fn estimate_skew(&self, gray: &GrayImage) -> f32 {
+        let binary = segmentation::binarize(gray);
+        let ink = binary.ink.iter().filter(|&&i| i).count();
+        if ink < MIN_EDGE_PIXELS {
+            return 0.0;
+        }
+        let stride = ink.div_ceil(SKEW_SAMPLES);
+        let points: Vec<(f32, f32)> = binary
+            .ink
+            .iter()
+            .enumerate()
+            .filter(|(_, &i)| i)
+            .step_by(stride)
+            .map(|(i, _)| ((i as u32 % binary.width) as f32, (i as u32 / binary.width) as f32))
+            .collect();
+
+        let offset = (binary.width + binary.height) as f32;
+        let score = |degrees: f32| {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            let mut rows = vec![0u64; 2 * offset as usize + 1];
+            for &(x, y) in &points {
+                rows[(y * cos - x * sin + offset) as usize] += 1;
+            }
+            rows.iter().map(|n| n * n).sum::<u64>()
+        };
 
-        DynamicImage::ImageLuma8(enhanced)
+        // Coarse half-degree steps, then tenths around the best
+        let mut best = (0.0, score(0.0));
+        for step in -20..=20 {
+            let degrees = step as f32 * MAX_SKEW_DEGREES / 20.0;
+            let s = score(degrees);
+            if s > best.1 {
+                best = (degrees, s);
+            }
+        }
+        let coarse = best.0;
+        for step in -4..=4 {
+            let degrees = coarse + step as f32 * 0.1;
+            let s = score(degrees);
+            if s > best.1 {
+                best = (degrees, s);
+            }
+        }
+        best.0
+    }
+
+    /// Turns the image back by `degrees`, filling the corners with paper.
+    pub fn deskew(&self, gray: &GrayImage, degrees: f32) -> GrayImage {
+        if degrees.abs() < 0.05 {
+            return gray.clone();
+        }
+        let center = (gray.width() as f32 / 2.0, gray.height() as f32 / 2.0);
+        warp(
+            gray,
+            &Homography::rotation(degrees, center),
+            gray.width(),
+            gray.height(),
+            percentile(gray, 0.9),
+        )
     }
 
-    fn correct_orientation(&self, img: DynamicImage) -> AlbergueResult<DynamicImage> {
-        // Simple orientation detection based on text line analysis
-        // In a real implementation, this would use more sophisticated algorithms
+    /// The turn after which the glyph models read the text most confidently.
+    /// A rectified card is already landscape, so `landscape` only weighs
+    /// upright against upside down.
+    ///
+    /// Each turn's bottom strip is read first: an MRZ read there with
+    /// confidence settles it at a fraction of the cost. Otherwise whole
+    /// readings are tried, upright first, stopping at the first confident
+    /// one.
+    pub fn detect_rotation(&self, gray: &GrayImage, landscape: bool) -> Rotation {
+        let turns: &[Rotation] = if landscape {
+            &[Rotation::Upright, Rotation::Half]
+        } else {
+            &[Rotation::Upright, Rotation::Quarter, Rotation::Half, Rotation::ThreeQuarters]
+        };
+        let recognizer = Recognizer::new();
+
+        for &turn in turns {
+            let turned = turn.apply(gray);
+            let strip_height = ((turned.height() as f32 * MRZ_STRIP) as u32).max(1);
+            let strip = imageops::crop_imm(&turned, 0, turned.height() - strip_height, turned.width(), strip_height);
+            let reading = recognizer.recognize(&strip.to_image());
+            if reading.words.iter().any(|w| w.mrz) && reading.confidence >= CONFIDENT_ROTATION {
+                return turn;
+            }
+        }
+
+        let mut readings = vec![];
+        for &turn in turns {
+            let confidence = recognizer.recognize(&turn.apply(gray)).confidence;
+            if confidence >= CONFIDENT_ROTATION {
+                return turn;
+            }
+            readings.push((turn, confidence));
+        }
+        let upright = readings[0].1;
+        readings
+            .into_iter()
+            .skip(1)
+            .filter(|&(_, confidence)| confidence > upright + ROTATION_MARGIN)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(Rotation::Upright, |(turn, _)| turn)
+    }
+
+    /// Glare and blur, with the reasons to ask for a new photo.
+    pub fn assess_quality(&self, gray: &GrayImage) -> ImageQuality {
+        // On white paper nothing stands out above the document, however bright
+        let glare = match percentile(gray, 0.5).checked_add(GLARE_MARGIN) {
+            Some(floor) => {
+                let floor = floor.max(250);
+                gray.pixels().filter(|p| p[0] >= floor).count() as f32 / gray.pixels().len().max(1) as f32
+            }
+            None => 0.0,
+        };
+        let sharpness = self.sharpness(gray);
+
+        let mut issues = vec![];
+        if glare > MAX_GLARE {
+            issues.push(QualityIssue::Glare { share: glare });
+        }
+        if let Some(sharpness) = sharpness.filter(|&s| s < MIN_SHARPNESS) {
+            issues.push(QualityIssue::Blurred { sharpness });
+        }
+
+        ImageQuality {
+            glare,
+            sharpness,
+            issues,
+        }
+    }
+
+    /// Laplacian response at the strongest edges over the contrast around
+    /// them: about 1 where ink meets paper within a pixel, falling as focus
+    /// or motion spreads the step.
+    fn sharpness(&self, gray: &GrayImage) -> Option<f32> {
+        let (w, h) = (gray.width(), gray.height());
+        if w < 5 || h < 5 {
+            return None;
+        }
+        let at = |x: u32, y: u32| gray.get_pixel(x, y)[0] as i32;
+
+        let mut ratios = vec![];
+        for y in 2..h - 2 {
+            for x in 2..w - 2 {
+                let gradient = (at(x + 1, y) - at(x - 1, y)).abs() + (at(x, y + 1) - at(x, y - 1)).abs();
+                if gradient < 16 {
+                    continue;
+                }
+                let (mut low, mut high) = (255, 0);
+                for ny in y - 2..=y + 2 {
+                    for nx in x - 2..=x + 2 {
+                        low = low.min(at(nx, ny));
+                        high = high.max(at(nx, ny));
+                    }
+                }
+                if high - low < 32 {
+                    continue;
+                }
+                let laplacian = 4 * at(x, y) - at(x - 1, y) - at(x + 1, y) - at(x, y - 1) - at(x, y + 1);
+                ratios.push(laplacian.abs() as f32 / (high - low) as f32);
+            }
+        }
+        if ratios.len() < MIN_EDGE_PIXELS {
+            return None;
+        }
 
-        // For now, return the image as-is
-        // TODO: Implement Hough line detection for document orientation
-        Ok(img)
+        // The steepest tenth: every edge has a soft shoulder, only the sharp
+        // ones also have a crisp step
+        ratios.sort_by(|a, b| b.total_cmp(a));
+        let top = &ratios[..ratios.len().div_ceil(10)];
+        Some(top.iter().sum::<f32>() / top.len() as f32)
     }
 
     pub fn extract_document_regions(&self, image_data: &[u8]) -> AlbergueResult<Vec<Vec<u8>>> {
@@ -81,23 +729,26 @@ impl ImageProcessor {
         Ok(vec![buffer])
     }
 
-    pub fn detect_document_type(&self, image_data: &[u8]) -> AlbergueResult<String> {
-        // Analyze image dimensions and layout to detect document type
+    /// Whether the image shows an ID-1 card or a passport data page, from
+    /// the card outline when there is one and the image shape otherwise.
+    pub fn detect_layout(&self, image_data: &[u8]) -> AlbergueResult<Layout> {
         let img =
             image::load_from_memory(image_data).map_err(|e| shared::AlbergueError::Validation {
                 message: format!("Failed to load image: {}", e),
             })?;
 
         let (width, height) = img.dimensions();
-        let aspect_ratio = width as f32 / height as f32;
+        let aspect = match self.detect_card(&img.to_luma8()) {
+            Some(card) => card.aspect(),
+            None => width.max(height) as f32 / width.min(height).max(1) as f32,
+        };
 
-        // Basic heuristics for document type detection
-        if aspect_ratio > 1.5 && aspect_ratio < 1.7 {
-            Ok("dni".to_string()) // Spanish ID card ratio
-        } else if aspect_ratio > 0.6 && aspect_ratio < 0.7 {
-            Ok("passport".to_string()) // Passport page ratio
+        if (ID1_ASPECT - aspect).abs() <= LAYOUT_TOLERANCE {
+            Ok(Layout::Id1Card)
+        } else if (PASSPORT_PAGE_ASPECT - aspect).abs() <= LAYOUT_TOLERANCE {
+            Ok(Layout::PassportPage)
         } else {
-            Ok("unknown".to_string())
+            Ok(Layout::Unknown)
         }
     }
 }
@@ -1,7 +1,12 @@
 pub mod confidence_scorer;
+pub mod document_classifier;
+pub mod glyph_model;
 pub mod image_processor;
+pub mod recognizer;
+pub mod segmentation;
 pub mod text_extractor;
 
 pub use confidence_scorer::ConfidenceScorer;
+pub use document_classifier::DocumentClassifier;
 pub use image_processor::ImageProcessor;
 pub use text_extractor::TextExtractor;
@@ -0,0 +1,216 @@
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use super::glyph_model::{Glyph, GlyphModel};
+use super::segmentation::{self, BinaryImage, BoundingBox, TextLine};
+
+/// Specks smaller than this many pixels are treated as noise.
+const MIN_COMPONENT_PIXELS: usize = 4;
+
+/// Share of `<` among a line's glyphs above which it is read as MRZ.
+const MRZ_FILLER_SHARE: f32 = 0.1;
+
+/// Sensitivity of the local threshold to contrast around each MRZ pixel.
+const SAUVOLA_K: f32 = 0.34;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub bbox: BoundingBox,
+    /// Mean of the word's character confidences, 0 to 1
+    pub confidence: f32,
+    /// Index of the text line the word was read from
+    pub line: usize,
+    /// Read with the OCR-B model as part of a machine readable zone
+    pub mrz: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct OcrResult {
+    /// Words joined by spaces, lines by newlines
+    pub text: String,
+    pub confidence: f32,
+    pub words: Vec<OcrWord>,
+}
+
+/// Segments a page into lines and words and classifies each glyph, reading
+/// MRZ lines with the MRZ model and everything else with the Latin one.
+pub struct Recognizer {
+    mrz_model: &'static GlyphModel,
+    latin_model: &'static GlyphModel,
+}
+
+impl Default for Recognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recognizer {
+    pub fn new() -> Self {
+        Self {
+            mrz_model: GlyphModel::mrz(),
+            latin_model: GlyphModel::latin(),
+        }
+    }
+
+    pub fn recognize(&self, gray: &GrayImage) -> OcrResult {
+        let mut gray = gray.clone();
+        if segmentation::is_light_on_dark(&gray) {
+            image::imageops::invert(&mut gray);
+        }
+
+        let binary = segmentation::binarize(&gray);
+        let components = segmentation::connected_components(&binary, MIN_COMPONENT_PIXELS);
+        let lines = segmentation::group_lines(segmentation::merge_stacked(components));
+
+        // MRZ cells are re-thresholded locally: the band sits at the card's
+        // edge, where shadows and laminate sheen are worst
+        let mrz_binary = lines
+            .iter()
+            .find(|l| l.mrz_cell_count().is_some())
+            .map(|l| segmentation::sauvola(&gray, (l.cap_height * 2.0) as u32 | 1, SAUVOLA_K));
+
+        let mut words = vec![];
+        for (index, line) in lines.iter().enumerate() {
+            let mrz_word = mrz_binary.as_ref().and_then(|b| self.read_mrz_line(b, line, index));
+            match mrz_word {
+                Some(word) => words.push(word),
+                None => words.extend(self.read_latin_line(&binary, line, index)),
+            }
+        }
+
+        let text = lines
+            .iter()
+            .enumerate()
+            .map(|(index, _)| {
+                words
+                    .iter()
+                    .filter(|w| w.line == index)
+                    .map(|w| w.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let confidence = if words.is_empty() {
+            0.0
+        } else {
+            words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32
+        };
+
+        OcrResult {
+            text,
+            confidence,
+            words,
+        }
+    }
+
+    /// MRZ lines have a TD1/TD2/TD3 length at a fixed pitch and fillers.
+    fn read_mrz_line(&self, binary: &BinaryImage, line: &TextLine, index: usize) -> Option<OcrWord> {
+        let cells = line.mrz_cell_count()?;
+
+        let mut text = String::with_capacity(cells);
+        let mut confidences = Vec::with_capacity(cells);
+        for cell in line.fixed_pitch_cells(cells) {
+            // Stay clear of ink bleeding over from the neighbouring cells
+            let inset = cell.width / 10;
+            let inner = BoundingBox {
+                x: cell.x + inset,
+                width: cell.width - 2 * inset,
+                ..cell
+            };
+            match segmentation::crop_glyph(binary, &inner, None) {
+                Some((_, glyph)) => {
+                    let (ch, confidence) = self.mrz_model.classify(&glyph)?;
+                    text.push(ch);
+                    confidences.push(confidence);
+                }
+                // A blank cell can only be a filler worn away
+                None => {
+                    text.push('<');
+                    confidences.push(0.5);
+                }
+            }
+        }
+
+        let fillers = text.chars().filter(|&c| c == '<').count();
+        if (fillers as f32) < cells as f32 * MRZ_FILLER_SHARE {
+            return None;
+        }
+
+        Some(OcrWord {
+            text,
+            bbox: line.bbox,
+            confidence: confidences.iter().sum::<f32>() / cells as f32,
+            line: index,
+            mrz: true,
+        })
+    }
+
+    fn read_latin_line(&self, binary: &BinaryImage, line: &TextLine, index: usize) -> Vec<OcrWord> {
+        line.words()
+            .into_iter()
+            .filter_map(|glyph_indices| {
+                let glyphs: Vec<_> = glyph_indices
+                    .into_iter()
+                    .filter_map(|i| {
+                        let component = &line.glyphs[i].bbox;
+                        segmentation::crop_glyph(binary, component, Some(line.placement_of(component)))
+                    })
+                    .collect();
+                let mut read: Vec<(char, f32)> = glyphs
+                    .iter()
+                    .filter_map(|(_, glyph)| self.latin_model.classify(glyph))
+                    .collect();
+
+                // `I` and `l` look the same in most sans faces: inside a
+                // lowercase word, capitals after the first letter are re-read
+                // as lowercase
+                let lowercase = read.iter().filter(|(c, _)| c.is_lowercase()).count();
+                let uppercase = read.iter().skip(1).filter(|(c, _)| c.is_uppercase()).count();
+                if lowercase > uppercase {
+                    self.reread(&glyphs, &mut read, |c| c.is_uppercase(), |c| !c.is_uppercase(), 1);
+                }
+
+                // Likewise `B` and `8`, `O` and `0`: a stray digit in a word
+                // of letters is re-read as a letter
+                let letters = read.iter().filter(|(c, _)| c.is_alphabetic()).count();
+                let digits = read.iter().filter(|(c, _)| c.is_ascii_digit()).count();
+                if letters > digits * 2 {
+                    self.reread(&glyphs, &mut read, |c| c.is_ascii_digit(), |c| !c.is_ascii_digit(), 0);
+                }
+
+                let bbox = glyphs.iter().map(|(b, _)| *b).reduce(|a, b| a.union(&b))?;
+                Some(OcrWord {
+                    text: read.iter().map(|(c, _)| c).collect(),
+                    bbox,
+                    confidence: read.iter().map(|(_, c)| c).sum::<f32>() / read.len().max(1) as f32,
+                    line: index,
+                    mrz: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Classifies again, among the characters `to` accepts, every glyph from
+    /// `skip` on that was read as a character `from` accepts.
+    fn reread(
+        &self,
+        glyphs: &[(BoundingBox, Glyph)],
+        read: &mut [(char, f32)],
+        from: impl Fn(char) -> bool,
+        to: impl Fn(char) -> bool + Copy,
+        skip: usize,
+    ) {
+        for (position, (_, glyph)) in glyphs.iter().enumerate().skip(skip) {
+            if read.get(position).is_some_and(|(c, _)| from(*c)) {
+                if let Some(again) = self.latin_model.classify_among(glyph, to) {
+                    read[position] = again;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use super::glyph_model::{Glyph, Placement};
+
+/// Character counts of TD1, TD2 and TD3 machine readable zone lines.
+pub const MRZ_LINE_LENGTHS: [usize; 3] = [30, 36, 44];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    pub fn center_y(&self) -> f32 {
+        self.y as f32 + self.height as f32 / 2.0
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        BoundingBox {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+
+    fn horizontal_overlap(&self, other: &BoundingBox) -> u32 {
+        self.right().min(other.right()).saturating_sub(self.x.max(other.x))
+    }
+
+    fn vertical_overlap(&self, other: &BoundingBox) -> u32 {
+        self.bottom().min(other.bottom()).saturating_sub(self.y.max(other.y))
+    }
+
+    fn vertical_gap(&self, other: &BoundingBox) -> u32 {
+        other.y.saturating_sub(self.bottom()).max(self.y.saturating_sub(other.bottom()))
+    }
+}
+
+/// Ink mask: `true` where there is ink.
+#[derive(Debug, Clone)]
+pub struct BinaryImage {
+    pub width: u32,
+    pub height: u32,
+    pub ink: Vec<bool>,
+}
+
+impl BinaryImage {
+    pub fn is_ink(&self, x: u32, y: u32) -> bool {
+        self.ink[(y * self.width + x) as usize]
+    }
+}
+
+/// Threshold that best separates the histogram into two classes.
+pub fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+
+    let total: u64 = histogram.iter().sum();
+    let sum_all: f64 = histogram.iter().enumerate().map(|(v, &n)| v as f64 * n as f64).sum();
+    let (mut weight_bg, mut sum_bg) = (0u64, 0f64);
+    let (mut best, mut best_variance) = (0u8, -1f64);
+
+    for (value, &count) in histogram.iter().enumerate() {
+        weight_bg += count;
+        if weight_bg == 0 {
+            continue;
+        }
+        let weight_fg = total - weight_bg;
+        if weight_fg == 0 {
+            break;
+        }
+        sum_bg += value as f64 * count as f64;
+        let mean_bg = sum_bg / weight_bg as f64;
+        let mean_fg = (sum_all - sum_bg) / weight_fg as f64;
+        let variance = weight_bg as f64 * weight_fg as f64 * (mean_bg - mean_fg).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = value as u8;
+        }
+    }
+    best
+}
+
+/// Whether the dark side of the Otsu split is the majority, i.e. the image
+/// is light text on a dark ground.
+pub fn is_light_on_dark(gray: &GrayImage) -> bool {
+    let threshold = otsu_threshold(gray);
+    gray.pixels().filter(|p| p[0] <= threshold).count() * 2 > gray.pixels().len()
+}
+
+/// Dark text on light paper; light-on-dark images are inverted so that ink
+/// is always the minority class.
+pub fn binarize(gray: &GrayImage) -> BinaryImage {
+    let threshold = otsu_threshold(gray);
+    let inverted = is_light_on_dark(gray);
+    BinaryImage {
+        width: gray.width(),
+        height: gray.height(),
+        ink: gray.pixels().map(|p| (p[0] <= threshold) != inverted).collect(),
+    }
+}
+
+/// Sauvola's local threshold: each pixel is compared with the mean and spread
+/// of the `window` square around it, so a shadow or a gradient across the
+/// photo does not swallow strokes the way one global threshold does. Expects
+/// dark text on light paper.
+pub fn sauvola(gray: &GrayImage, window: u32, k: f32) -> BinaryImage {
+    let (w, h) = (gray.width() as usize, gray.height() as usize);
+
+    // Summed-area tables of values and squares, one row and column of zeros
+    // ahead
+    let mut sum = vec![0u64; (w + 1) * (h + 1)];
+    let mut squares = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let (mut row_sum, mut row_squares) = (0u64, 0u64);
+        for x in 0..w {
+            let v = gray.get_pixel(x as u32, y as u32)[0] as u64;
+            row_sum += v;
+            row_squares += v * v;
+            sum[(y + 1) * (w + 1) + x + 1] = sum[y * (w + 1) + x + 1] + row_sum;
+            squares[(y + 1) * (w + 1) + x + 1] = squares[y * (w + 1) + x + 1] + row_squares;
+        }
+    }
+    let area = |table: &[u64], x0: usize, y0: usize, x1: usize, y1: usize| {
+        table[y1 * (w + 1) + x1] + table[y0 * (w + 1) + x0] - table[y0 * (w + 1) + x1] - table[y1 * (w + 1) + x0]
+    };
+
+    let half = (window / 2).max(1) as usize;
+    let mut ink = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(half), (y + half + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(half), (x + half + 1).min(w));
+            let n = ((x1 - x0) * (y1 - y0)) as f32;
+            let mean = area(&sum, x0, y0, x1, y1) as f32 / n;
+            let variance = (area(&squares, x0, y0, x1, y1) as f32 / n - mean * mean).max(0.0);
+            let threshold = mean * (1.0 + k * (variance.sqrt() / 128.0 - 1.0));
+            ink.push(gray.get_pixel(x as u32, y as u32)[0] as f32 <= threshold);
+        }
+    }
+
+    BinaryImage {
+        width: gray.width(),
+        height: gray.height(),
+        ink,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub bbox: BoundingBox,
+    pub pixels: usize,
+    /// Built from several strokes, e.g. `Ñ`, `i` or `:`
+    pub stacked: bool,
+}
+
+/// 8-connected ink blobs, dropping specks too small to be a character.
+pub fn connected_components(image: &BinaryImage, min_pixels: usize) -> Vec<Component> {
+    let (w, h) = (image.width as usize, image.height as usize);
+    let mut seen = vec![false; w * h];
+    let mut components = vec![];
+    let mut stack = vec![];
+
+    for start in 0..w * h {
+        if seen[start] || !image.ink[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut x0, mut y0, mut x1, mut y1) = (w, h, 0, 0);
+        let mut pixels = 0;
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            pixels += 1;
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+
+            for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                    let n = ny * w + nx;
+                    if !seen[n] && image.ink[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+
+        if pixels >= min_pixels {
+            components.push(Component {
+                bbox: BoundingBox {
+                    x: x0 as u32,
+                    y: y0 as u32,
+                    width: (x1 - x0 + 1) as u32,
+                    height: (y1 - y0 + 1) as u32,
+                },
+                pixels,
+                stacked: false,
+            });
+        }
+    }
+    components
+}
+
+/// Joins accents, tildes and the dots of `i` and `j` to the stroke below or
+/// above them.
+pub fn merge_stacked(mut components: Vec<Component>) -> Vec<Component> {
+    components.sort_by_key(|c| std::cmp::Reverse(c.bbox.height));
+    let mut merged: Vec<Component> = vec![];
+
+    for component in components {
+        let mark = component.bbox;
+        let target = merged.iter_mut().find(|m| {
+            let base = m.bbox;
+            mark.height * 2 <= base.height
+                && mark.horizontal_overlap(&base) * 2 >= mark.width.min(base.width)
+                && mark.vertical_gap(&base) as f32 <= base.height as f32 * 0.35
+                && mark.vertical_overlap(&base) * 2 < mark.height
+        });
+
+        match target {
+            Some(m) => {
+                m.bbox = m.bbox.union(&component.bbox);
+                m.pixels += component.pixels;
+                m.stacked = true;
+            }
+            None => merged.push(component),
+        }
+    }
+    merged
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub bbox: BoundingBox,
+    /// Left to right
+    pub glyphs: Vec<Component>,
+    pub baseline: u32,
+    pub cap_height: f32,
+}
+
+impl TextLine {
+    fn new(glyphs: Vec<Component>) -> Self {
+        let bbox = glyphs.iter().skip(1).fold(glyphs[0].bbox, |b, g| b.union(&g.bbox));
+        let tallest = glyphs.iter().map(|g| g.bbox.height).max().unwrap_or(1);
+
+        let mut bottoms: Vec<u32> = glyphs
+            .iter()
+            .filter(|g| g.bbox.height * 5 >= tallest * 3)
+            .map(|g| g.bbox.bottom())
+            .collect();
+        bottoms.sort_unstable();
+        let baseline = bottoms.get(bottoms.len() / 2).copied().unwrap_or(bbox.bottom());
+
+        // Unaccented glyphs resting on the baseline give the cap height. Not
+        // the very tallest: at small sizes an accent can touch its letter
+        let mut heights: Vec<u32> = glyphs
+            .iter()
+            .filter(|g| !g.stacked && g.bbox.bottom().abs_diff(baseline) * 10 <= tallest)
+            .map(|g| baseline.saturating_sub(g.bbox.y))
+            .collect();
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        let cap_height = heights.get(heights.len() / 4).copied().unwrap_or(tallest).max(1) as f32;
+
+        Self {
+            bbox,
+            glyphs,
+            baseline,
+            cap_height,
+        }
+    }
+
+    pub fn placement_of(&self, bbox: &BoundingBox) -> Placement {
+        Placement {
+            height: bbox.height as f32 / self.cap_height,
+            top: (self.baseline as f32 - bbox.y as f32) / self.cap_height,
+        }
+    }
+
+    /// Word boundaries: gaps clearly wider than the usual letter spacing.
+    pub fn words(&self) -> Vec<Vec<usize>> {
+        let gaps: Vec<u32> = self
+            .glyphs
+            .windows(2)
+            .map(|pair| pair[1].bbox.x.saturating_sub(pair[0].bbox.right()))
+            .collect();
+        let mut sorted = gaps.clone();
+        sorted.sort_unstable();
+        let median_gap = sorted.get(sorted.len() / 2).copied().unwrap_or(0) as f32;
+        let threshold = (self.cap_height * 0.3).max(median_gap * 1.8);
+
+        let mut words = vec![vec![0]];
+        for (i, &gap) in gaps.iter().enumerate() {
+            if gap as f32 > threshold {
+                words.push(vec![]);
+            }
+            words.last_mut().unwrap().push(i + 1);
+        }
+        words
+    }
+
+    /// Number of MRZ character cells this line would hold at its pitch, if
+    /// it is close to a TD1, TD2 or TD3 line length.
+    pub fn mrz_cell_count(&self) -> Option<usize> {
+        if self.glyphs.len() < 2 {
+            return None;
+        }
+        let centers: Vec<f32> = self
+            .glyphs
+            .iter()
+            .map(|g| g.bbox.x as f32 + g.bbox.width as f32 / 2.0)
+            .collect();
+        let mut steps: Vec<f32> = centers.windows(2).map(|c| c[1] - c[0]).collect();
+        steps.sort_by(|a, b| a.total_cmp(b));
+        let pitch = steps[steps.len() / 2];
+        if pitch <= 0.0 {
+            return None;
+        }
+
+        let estimate = self.bbox.width as f32 / pitch;
+        MRZ_LINE_LENGTHS
+            .iter()
+            .copied()
+            .find(|&n| (estimate - n as f32).abs() <= n as f32 * 0.12)
+    }
+
+    /// Splits the line into `count` equal cells centred between the first and
+    /// last glyph, as OCR-B is fixed pitch. Runs of touching fillers (`<<<`)
+    /// come apart this way.
+    pub fn fixed_pitch_cells(&self, count: usize) -> Vec<BoundingBox> {
+        let center = |g: &Component| g.bbox.x as f32 + g.bbox.width as f32 / 2.0;
+        let first = center(&self.glyphs[0]);
+        let last = center(&self.glyphs[self.glyphs.len() - 1]);
+        let pitch = (last - first) / (count.max(2) - 1) as f32;
+
+        (0..count)
+            .map(|i| {
+                let mid = first + i as f32 * pitch;
+                let x0 = (mid - pitch / 2.0).max(0.0).round() as u32;
+                let x1 = (mid + pitch / 2.0).round() as u32;
+                BoundingBox {
+                    x: x0,
+                    y: self.bbox.y,
+                    width: (x1 - x0).max(1),
+                    height: self.bbox.height,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Groups glyphs into lines, top to bottom. Tall glyphs seed lines; small
+/// marks like `,` or `-` join the line whose band they fall in.
+pub fn group_lines(components: Vec<Component>) -> Vec<TextLine> {
+    let mut order: Vec<Component> = components;
+    order.sort_by_key(|c| std::cmp::Reverse(c.bbox.height));
+
+    let mut bands: Vec<(BoundingBox, Vec<Component>)> = vec![];
+    for component in order {
+        let b = component.bbox;
+        let best = bands
+            .iter_mut()
+            .filter(|(band, _)| {
+                let overlaps = band.vertical_overlap(&b) * 2 >= b.height.min(band.height);
+                let small_mark = b.height * 2 <= band.height
+                    && b.center_y() >= band.y as f32
+                    && b.center_y() <= band.bottom() as f32 + band.height as f32 * 0.3;
+                overlaps || small_mark
+            })
+            .min_by(|(x, _), (y, _)| {
+                (x.center_y() - b.center_y())
+                    .abs()
+                    .total_cmp(&(y.center_y() - b.center_y()).abs())
+            });
+
+        match best {
+            Some((_, glyphs)) => glyphs.push(component),
+            None => bands.push((b, vec![component])),
+        }
+    }
+
+    let mut lines: Vec<TextLine> = bands
+        .into_iter()
+        .map(|(_, mut glyphs)| {
+            glyphs.sort_by_key(|g| g.bbox.x);
+            TextLine::new(merge_columns(glyphs))
+        })
+        .collect();
+    lines.sort_by_key(|l| l.bbox.y);
+    lines
+}
+
+/// Within a line, marks stacked in one column are one glyph: `:`, `;`, `=`.
+fn merge_columns(glyphs: Vec<Component>) -> Vec<Component> {
+    let mut merged: Vec<Component> = vec![];
+    for glyph in glyphs {
+        match merged.last_mut() {
+            Some(last) if glyph.bbox.horizontal_overlap(&last.bbox) * 2 >= glyph.bbox.width.min(last.bbox.width) => {
+                last.bbox = last.bbox.union(&glyph.bbox);
+                last.pixels += glyph.pixels;
+                last.stacked = true;
+            }
+            _ => merged.push(glyph),
+        }
+    }
+    merged
+}
+
+/// Ink inside `bbox`, trimmed to the ink's own bounding box.
+pub fn crop_glyph(image: &BinaryImage, bbox: &BoundingBox, placement: Option<Placement>) -> Option<(BoundingBox, Glyph)> {
+    let xs = bbox.x..bbox.right().min(image.width);
+    let ys = bbox.y..bbox.bottom().min(image.height);
+
+    let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+    for y in ys.clone() {
+        for x in xs.clone() {
+            if image.is_ink(x, y) {
+                x0 = x0.min(x);
+                y0 = y0.min(y);
+                x1 = x1.max(x);
+                y1 = y1.max(y);
+            }
+        }
+    }
+    if x0 == u32::MAX {
+        return None;
+    }
+
+    let trimmed = BoundingBox {
+        x: x0,
+        y: y0,
+        width: x1 - x0 + 1,
+        height: y1 - y0 + 1,
+    };
+    let pixels = (y0..=y1)
+        .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+        .map(|(x, y)| if image.is_ink(x, y) { 1.0 } else { 0.0 })
+        .collect();
+
+    Some((
+        trimmed,
+        Glyph {
+            width: trimmed.width as usize,
+            height: trimmed.height as usize,
+            pixels,
+            placement,
+        },
+    ))
+}
@@ -2,6 +2,12 @@ use shared::AlbergueResult;
 
 pub struct TextExtractor;
 
+impl Default for TextExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TextExtractor {
     pub fn new() -> Self {
         Self
@@ -0,0 +1,111 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use super::{Portrait, PortraitKey};
+use shared::{AlbergueError, AlbergueResult};
+
+/// Leading byte of every sealed portrait, for when the format changes.
+const FORMAT_VERSION: u8 = 1;
+const NONCE_LENGTH: usize = 12;
+
+/// A portrait encrypted for storage. Only `PortraitCipher::seal` makes one
+/// from an image, so a store never receives plaintext.
+#[derive(Clone, PartialEq)]
+pub struct SealedPortrait(Vec<u8>);
+
+impl SealedPortrait {
+    /// Bytes read back from a store.
+    pub fn from_stored(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SealedPortrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SealedPortrait({} bytes)", self.0.len())
+    }
+}
+
+/// AES-256-GCM under the service's `ENCRYPTION_KEY`. Each portrait is bound
+/// to its key, so a sealed file copied to another path does not open.
+pub struct PortraitCipher {
+    cipher: Aes256Gcm,
+}
+
+impl PortraitCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(key.into()),
+        }
+    }
+
+    /// From a base64 encoded 32 byte key, as `ENCRYPTION_KEY` holds it.
+    pub fn from_base64_key(key: &str) -> AlbergueResult<Self> {
+        let bytes = base64::decode(key.trim()).map_err(|e| AlbergueError::Internal {
+            message: format!("Invalid encryption key encoding: {}", e),
+        })?;
+        let key: [u8; 32] = bytes.try_into().map_err(|_| AlbergueError::Internal {
+            message: "Encryption key must be 32 bytes".to_string(),
+        })?;
+        Ok(Self::new(&key))
+    }
+
+    pub fn seal(&self, key: &PortraitKey, portrait: &Portrait) -> AlbergueResult<SealedPortrait> {
+        self.seal_bytes(key, &portrait.jpeg)
+    }
+
+    /// Seals any image, such as a whole upload held for review.
+    pub fn seal_bytes(&self, key: &PortraitKey, image: &[u8]) -> AlbergueResult<SealedPortrait> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let path = key.path();
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: image,
+                    aad: path.as_bytes(),
+                },
+            )
+            .map_err(|_| AlbergueError::Internal {
+                message: "Failed to encrypt portrait".to_string(),
+            })?;
+
+        let mut sealed = Vec::with_capacity(1 + NONCE_LENGTH + ciphertext.len());
+        sealed.push(FORMAT_VERSION);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend(ciphertext);
+        Ok(SealedPortrait(sealed))
+    }
+
+    /// The JPEG sealed under `key`; fails when it was sealed under another
+    /// key, with another encryption key, or altered since.
+    pub fn open(&self, key: &PortraitKey, sealed: &SealedPortrait) -> AlbergueResult<Vec<u8>> {
+        let unreadable = || AlbergueError::Internal {
+            message: "Stored portrait cannot be decrypted".to_string(),
+        };
+        let [version, rest @ ..] = sealed.as_bytes() else {
+            return Err(unreadable());
+        };
+        if *version != FORMAT_VERSION || rest.len() < NONCE_LENGTH {
+            return Err(unreadable());
+        }
+
+        let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+        let nonce: [u8; NONCE_LENGTH] = nonce.try_into().map_err(|_| unreadable())?;
+        let path = key.path();
+        self.cipher
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: path.as_bytes(),
+                },
+            )
+            .map_err(|_| unreadable())
+    }
+}
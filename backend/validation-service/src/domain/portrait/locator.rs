@@ -0,0 +1,197 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{imageops, GrayImage, RgbImage};
+
+use super::Portrait;
+use crate::domain::ocr::image_processor::{
+    percentile, warp_rgb, ImageProcessor, Layout, ID1_ASPECT, PASSPORT_PAGE_ASPECT,
+};
+use shared::{AlbergueError, AlbergueResult};
+
+/// Where the photo sits, as fractions of the card or page: the left of an
+/// ID-1 card below its header, and the left of a passport page above its MRZ.
+const ID1_ZONE: Zone = Zone { left: 0.0, right: 0.42, top: 0.08, bottom: 1.0 };
+const PASSPORT_ZONE: Zone = Zone { left: 0.0, right: 0.40, top: 0.10, bottom: 0.85 };
+
+/// How far an outline's aspect may stray from the document's and still be
+/// taken for it; the photo's own backdrop can look like a card.
+const SHAPE_TOLERANCE: f32 = 0.1;
+/// How far a pixel must stand off the background to count as part of the photo.
+const INK_CONTRAST: i16 = 30;
+/// Blocks per zone height; text leaves most blocks sparse, a photo fills them.
+const BLOCKS_ACROSS_HEIGHT: u32 = 48;
+const DENSE_BLOCK: f32 = 0.5;
+/// Share of the zone the photo must cover, and its height over width.
+const MIN_COVERAGE: f32 = 0.12;
+const ASPECT_RANGE: (f32, f32) = (0.8, 2.0);
+/// Margin kept around the photo, as a share of its size.
+const CROP_MARGIN: f32 = 0.08;
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, Clone, Copy)]
+struct Zone {
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+}
+
+/// Finds and crops the holder's photo on a DNI, ID card or passport page.
+pub struct PortraitLocator {
+    image_processor: ImageProcessor,
+}
+
+impl Default for PortraitLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortraitLocator {
+    pub fn new() -> Self {
+        Self {
+            image_processor: ImageProcessor::new(),
+        }
+    }
+
+    /// The photo on the document in `image_data`, or `None` when the layout
+    /// is unknown or nothing photo-like sits where the photo should.
+    pub fn locate(&self, image_data: &[u8], layout: Layout) -> AlbergueResult<Option<Portrait>> {
+        let (zone, aspect) = match layout {
+            Layout::Id1Card => (ID1_ZONE, ID1_ASPECT),
+            Layout::PassportPage => (PASSPORT_ZONE, PASSPORT_PAGE_ASPECT),
+            Layout::Unknown => return Ok(None),
+        };
+        let img = image::load_from_memory(image_data).map_err(|e| AlbergueError::Validation {
+            message: format!("Failed to load image: {}", e),
+        })?;
+        let rgb = img.to_rgb8();
+
+        // A photographed card is cut out first; a scan already is the document
+        let framed = self
+            .image_processor
+            .detect_card(&img.to_luma8())
+            .filter(|card| (card.aspect() - aspect).abs() <= SHAPE_TOLERANCE)
+            .and_then(|card| self.image_processor.card_frame(&card))
+            .map(|(to_photo, width, height)| warp_rgb(&rgb, &to_photo, width, height, [255; 3]));
+        let document = framed.as_ref().unwrap_or(&rgb);
+
+        let found = match find_photo(document, zone) {
+            Some(bounds) => Some((bounds, document.clone())),
+            // The card outline does not tell which way up it lies
+            None if framed.is_some() => {
+                let turned = imageops::rotate180(document);
+                find_photo(&turned, zone).map(|bounds| (bounds, turned))
+            }
+            None => None,
+        };
+        let Some(((x, y, width, height), source)) = found else {
+            return Ok(None);
+        };
+
+        let crop = imageops::crop_imm(&source, x, y, width, height).to_image();
+        Ok(Some(encode(&crop)?))
+    }
+}
+
+/// Bounds of the photo within `zone` of `document`, margin included.
+fn find_photo(document: &RgbImage, zone: Zone) -> Option<(u32, u32, u32, u32)> {
+    let (doc_w, doc_h) = (document.width() as f32, document.height() as f32);
+    let zone_x = (zone.left * doc_w) as u32;
+    let zone_y = (zone.top * doc_h) as u32;
+    let zone_w = (((zone.right - zone.left) * doc_w) as u32).min(document.width() - zone_x);
+    let zone_h = (((zone.bottom - zone.top) * doc_h) as u32).min(document.height() - zone_y);
+    if zone_w < BLOCKS_ACROSS_HEIGHT || zone_h < BLOCKS_ACROSS_HEIGHT {
+        return None;
+    }
+
+    let gray = GrayImage::from_fn(zone_w, zone_h, |x, y| {
+        let [r, g, b] = document.get_pixel(zone_x + x, zone_y + y).0;
+        image::Luma([((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8])
+    });
+    let background = percentile(&gray, 0.9) as i16;
+
+    let block = (zone_h / BLOCKS_ACROSS_HEIGHT).max(1);
+    let (cols, rows) = (zone_w / block, zone_h / block);
+    let dense: Vec<bool> = (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let ink = (0..block * block)
+                .filter(|i| {
+                    let pixel = gray.get_pixel(col * block + i % block, row * block + i / block)[0] as i16;
+                    (pixel - background).abs() > INK_CONTRAST
+                })
+                .count();
+            ink as f32 / (block * block) as f32 > DENSE_BLOCK
+        })
+        .collect();
+
+    let (left, top, right, bottom) = largest_component(&dense, cols, rows)?;
+    let (blocks_w, blocks_h) = ((right - left + 1) as f32, (bottom - top + 1) as f32);
+    let coverage = blocks_w * blocks_h / (cols * rows) as f32;
+    let aspect = blocks_h / blocks_w;
+    if coverage < MIN_COVERAGE || !(ASPECT_RANGE.0..=ASPECT_RANGE.1).contains(&aspect) {
+        return None;
+    }
+
+    let margin_x = blocks_w * block as f32 * CROP_MARGIN;
+    let margin_y = blocks_h * block as f32 * CROP_MARGIN;
+    let x0 = ((zone_x + left * block) as f32 - margin_x).max(0.0) as u32;
+    let y0 = ((zone_y + top * block) as f32 - margin_y).max(0.0) as u32;
+    let x1 = (((zone_x + (right + 1) * block) as f32 + margin_x) as u32).min(document.width());
+    let y1 = (((zone_y + (bottom + 1) * block) as f32 + margin_y) as u32).min(document.height());
+    Some((x0, y0, x1 - x0, y1 - y0))
+}
+
+/// Block bounds (left, top, right, bottom) of the largest 8-connected group
+/// of dense blocks.
+fn largest_component(dense: &[bool], cols: u32, rows: u32) -> Option<(u32, u32, u32, u32)> {
+    let mut seen = vec![false; dense.len()];
+    let mut best: Option<(usize, (u32, u32, u32, u32))> = None;
+
+    for start in 0..dense.len() {
+        if !dense[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut stack = vec![start];
+        let mut size = 0;
+        let (mut left, mut top, mut right, mut bottom) = (cols, rows, 0, 0);
+        while let Some(at) = stack.pop() {
+            size += 1;
+            let (col, row) = (at as u32 % cols, at as u32 / cols);
+            left = left.min(col);
+            right = right.max(col);
+            top = top.min(row);
+            bottom = bottom.max(row);
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let (x, y) = (col as i64 + dx, row as i64 + dy);
+                if x < 0 || y < 0 || x >= cols as i64 || y >= rows as i64 {
+                    continue;
+                }
+                let next = (y as u32 * cols + x as u32) as usize;
+                if dense[next] && !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        if best.is_none_or(|(most, _)| size > most) {
+            best = Some((size, (left, top, right, bottom)));
+        }
+    }
+    best.map(|(_, bounds)| bounds)
+}
+
+fn encode(crop: &RgbImage) -> AlbergueResult<Portrait> {
+    let mut jpeg = vec![];
+    JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode_image(crop)
+        .map_err(|e| AlbergueError::Internal {
+            message: format!("Failed to encode portrait: {}", e),
+        })?;
+    Ok(Portrait {
+        jpeg,
+        width: crop.width(),
+        height: crop.height(),
+    })
+}
@@ -0,0 +1,57 @@
+pub mod cipher;
+pub mod locator;
+
+pub use cipher::{PortraitCipher, SealedPortrait};
+pub use locator::PortraitLocator;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// The holder's photo cut from a document, JPEG encoded.
+#[derive(Clone, PartialEq)]
+pub struct Portrait {
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+// A biometric; keep the bytes out of logs
+impl std::fmt::Debug for Portrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Portrait")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("jpeg", &format_args!("{} bytes", self.jpeg.len()))
+            .finish()
+    }
+}
+
+/// Where a stored portrait lives: grouped by the day its retention ends, so
+/// expired portraits go a whole day at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortraitKey {
+    pub retain_until: NaiveDate,
+    pub id: Uuid,
+}
+
+impl PortraitKey {
+    pub fn new(retain_until: NaiveDate) -> Self {
+        Self {
+            retain_until,
+            id: Uuid::new_v4(),
+        }
+    }
+
+    /// `2033-06-30/0f8b….enc`
+    pub fn path(&self) -> String {
+        format!("{}/{}.enc", self.retain_until.format("%Y-%m-%d"), self.id)
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (day, file) = path.trim_start_matches('/').split_once('/')?;
+        Some(Self {
+            retain_until: NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?,
+            id: Uuid::parse_str(file.strip_suffix(".enc")?).ok()?,
+        })
+    }
+}
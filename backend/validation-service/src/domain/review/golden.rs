@@ -0,0 +1,119 @@
+//! Approved reviews as golden corpus specs, the `tests/golden/{name}.json`
+//! format: what is printed on the document and what should be read from it.
+//! The printed lines are the OCR text with the hospitalero's corrections
+//! put in place of what was misread.
+//!
+//! A case holds the pilgrim's real data. It is for a corpus kept with the
+//! other personal data; replace names and numbers with invented ones before
+//! adding it to `tests/golden`.
+
+use serde::Serialize;
+use serde_json::Value;
+use shared::{AlbergueError, AlbergueResult, DocumentReview, DocumentType, ReviewStatus};
+use std::collections::BTreeMap;
+
+use super::corrected;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GoldenSide {
+    /// How the renderer draws it: `photo` for a card, `page` for paper
+    pub capture: &'static str,
+    pub lines: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GoldenExpected {
+    pub is_valid: bool,
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GoldenCase {
+    /// The file name, without `.json`
+    #[serde(skip)]
+    pub name: String,
+    pub document_type: DocumentType,
+    pub front: GoldenSide,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub back: Option<GoldenSide>,
+    pub expected: GoldenExpected,
+}
+
+impl GoldenCase {
+    /// The case of an approved review.
+    pub fn from_review(review: &DocumentReview) -> AlbergueResult<Self> {
+        if review.status != ReviewStatus::Approved {
+            return Err(AlbergueError::Validation {
+                message: format!("Review {} is not approved", review.id),
+            });
+        }
+        let read = serde_json::to_value(&review.extracted_data).unwrap_or_default();
+        let expected = serde_json::to_value(corrected(&review.extracted_data, &review.corrections)?).unwrap_or_default();
+
+        // Misread values as printed on the document, MRZ lines aside
+        let fixes: Vec<(String, String)> = review
+            .corrections
+            .keys()
+            .filter(|field| !field.ends_with("_date"))
+            .filter_map(|field| Some((read[field].as_str()?.to_string(), expected[field].as_str()?.to_string())))
+            .filter(|(misread, _)| !misread.is_empty())
+            .collect();
+        let side = |text: &String, capture| GoldenSide {
+            capture,
+            lines: text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| match line.contains('<') {
+                    true => line.to_string(),
+                    false => fixes
+                        .iter()
+                        .fold(line.to_string(), |line, (misread, fixed)| line.replace(misread, fixed)),
+                })
+                .collect(),
+        };
+        let front_capture = match review.document_type {
+            DocumentType::NIE | DocumentType::Passport => "page",
+            _ => "photo",
+        };
+
+        Ok(Self {
+            name: format!("review_{}", &review.id.simple().to_string()[..8]),
+            document_type: review.document_type,
+            front: side(review.ocr_text.first().ok_or_else(no_text)?, front_capture),
+            back: review.ocr_text.get(1).map(|text| side(text, "card")),
+            expected: GoldenExpected {
+                is_valid: true,
+                fields: corpus_fields(&expected),
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+fn no_text() -> AlbergueError {
+    AlbergueError::Validation {
+        message: "The review has no OCR text".to_string(),
+    }
+}
+
+/// Fields with a value, in the corpus' notation: dates as `YYYY-MM-DD`.
+fn corpus_fields(data: &Value) -> BTreeMap<String, String> {
+    let Some(fields) = data.as_object() else {
+        return BTreeMap::new();
+    };
+    fields
+        .iter()
+        .filter_map(|(field, value)| {
+            let value = value.as_str()?;
+            let value = match field.ends_with("_date") {
+                true => value.get(..10)?,
+                false => value,
+            };
+            Some((field.clone(), value.to_string()))
+        })
+        .collect()
+}
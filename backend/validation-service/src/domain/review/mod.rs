@@ -0,0 +1,129 @@
+//! Validations read with too little confidence are held for a hospitalero
+//! instead of failing the registration. The hospitalero corrects what was
+//! misread, then approves or rejects; every decision goes to the audit log,
+//! and each approved reading becomes a golden corpus case.
+
+pub mod golden;
+
+pub use golden::GoldenCase;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde_json::{json, Value};
+use shared::{AlbergueError, AlbergueResult, DocumentReview, ExtractedData, ReviewStatus};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// `audit_log.table_name` of review decisions.
+pub const AUDIT_TABLE: &str = "document_reviews";
+
+/// The hospitalero deciding, from their token, and the request they sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reviewer {
+    /// JWT sub claim
+    pub user_id: String,
+    pub user_role: String,
+    /// The gateway's `X-Request-Id`
+    pub request_id: Option<String>,
+}
+
+/// `audit_log.action`, as its check constraint allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Read,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "CREATE",
+            Self::Read => "READ",
+            Self::Update => "UPDATE",
+            Self::Delete => "DELETE",
+        }
+    }
+}
+
+/// One `audit_log` row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub table_name: String,
+    pub record_id: Uuid,
+    pub action: AuditAction,
+    pub user_id: String,
+    pub user_role: String,
+    pub changed_fields: Vec<String>,
+    pub old_values: Value,
+    pub new_values: Value,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    /// A decision on `review`, recording the values of `changed_fields`
+    /// before and after it.
+    pub fn decision(reviewer: &Reviewer, before: &DocumentReview, after: &DocumentReview, changed_fields: &[&str]) -> Self {
+        let record_id = after.id;
+        let (before, after) = (json!(before), json!(after));
+        let pick = |review: &Value| -> Value {
+            changed_fields
+                .iter()
+                .map(|field| (field.to_string(), review[*field].clone()))
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        };
+        Self {
+            table_name: AUDIT_TABLE.to_string(),
+            record_id,
+            action: AuditAction::Update,
+            user_id: reviewer.user_id.clone(),
+            user_role: reviewer.user_role.clone(),
+            changed_fields: changed_fields.iter().map(|f| f.to_string()).collect(),
+            old_values: pick(&before),
+            new_values: pick(&after),
+            request_id: reviewer.request_id.clone(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Fails unless `review` can still be decided on.
+pub fn ensure_pending(review: &DocumentReview) -> AlbergueResult<()> {
+    let decided = match review.status {
+        ReviewStatus::Pending => return Ok(()),
+        ReviewStatus::Approved => "approved",
+        ReviewStatus::Rejected => "rejected",
+    };
+    Err(AlbergueError::Validation {
+        message: format!("Review {} was already {}", review.id, decided),
+    })
+}
+
+/// `data` with `corrections` applied. Fields are `ExtractedData`'s, dates
+/// are `YYYY-MM-DD`, and an empty value clears the field.
+pub fn corrected(data: &ExtractedData, corrections: &BTreeMap<String, String>) -> AlbergueResult<ExtractedData> {
+    let invalid = |message: String| AlbergueError::Validation { message };
+    let mut fields = json!(data);
+    for (field, value) in corrections {
+        let slot = fields
+            .get_mut(field.as_str())
+            .ok_or_else(|| invalid(format!("Unknown field {}", field)))?;
+        let value = value.trim();
+        *slot = if value.is_empty() {
+            Value::Null
+        } else if field.ends_with("_date") {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| invalid(format!("{} must be a YYYY-MM-DD date, not {}", field, value)))?;
+            json!(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        } else if field == "sex" && !matches!(value, "M" | "F" | "X") {
+            return Err(invalid(format!("sex must be M, F or X, not {}", value)));
+        } else {
+            json!(value)
+        };
+    }
+    serde_json::from_value(fields).map_err(|e| AlbergueError::Internal {
+        message: format!("Failed to apply corrections: {}", e),
+    })
+}
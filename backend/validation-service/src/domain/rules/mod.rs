@@ -0,0 +1,3 @@
+pub mod validity_rules;
+
+pub use validity_rules::ValidityRules;
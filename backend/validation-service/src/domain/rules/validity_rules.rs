@@ -0,0 +1,129 @@
+use chrono::{NaiveDate, Utc};
+use shared::{DocumentType, FieldConflict, ValidationWarning, ValidationWarningCode};
+
+use crate::domain::entities::Document;
+
+/// Days an expired DNI is still accepted while its renewal is under way.
+pub const DNI_RENEWAL_GRACE_DAYS: i64 = 90;
+
+/// Guests younger than this must be accompanied by a responsible adult.
+pub const AGE_OF_MAJORITY: u32 = 18;
+
+/// What the rules found: errors make the document invalid, warnings do not.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleOutcome {
+    pub errors: Vec<String>,
+    pub warnings: Vec<ValidationWarning>,
+}
+
+/// Checks a read document is usable for check-in: not expired, dates in a
+/// possible order, the holder's age and agreement between MRZ and visual zone.
+pub struct ValidityRules {
+    dni_renewal_grace_days: i64,
+}
+
+impl Default for ValidityRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValidityRules {
+    pub fn new() -> Self {
+        Self {
+            dni_renewal_grace_days: DNI_RENEWAL_GRACE_DAYS,
+        }
+    }
+
+    pub fn with_dni_renewal_grace_days(mut self, days: i64) -> Self {
+        self.dni_renewal_grace_days = days;
+        self
+    }
+
+    pub fn check(&self, document: &Document, conflicts: &[FieldConflict]) -> RuleOutcome {
+        self.check_on(document, conflicts, Utc::now().date_naive())
+    }
+
+    /// As `check`, as of `today`.
+    pub fn check_on(&self, document: &Document, conflicts: &[FieldConflict], today: NaiveDate) -> RuleOutcome {
+        let mut outcome = RuleOutcome::default();
+
+        self.check_expiry(document, today, &mut outcome);
+        Self::check_dates(document, today, &mut outcome);
+
+        if let Some(age) = document.age_on(today).filter(|age| *age < AGE_OF_MAJORITY) {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::MinorMustBeAccompanied,
+                field: Some("birth_date".to_string()),
+                message: format!("The holder is {}: minors must be accompanied by a responsible adult", age),
+            });
+        }
+
+        for conflict in conflicts {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::MrzMismatch,
+                field: Some(conflict.field.clone()),
+                message: format!(
+                    "The MRZ reads {} as {}, the visual zone as {}; the MRZ value was kept",
+                    conflict.field, conflict.mrz_value, conflict.visual_value
+                ),
+            });
+        }
+
+        outcome
+    }
+
+    fn check_expiry(&self, document: &Document, today: NaiveDate, outcome: &mut RuleOutcome) {
+        let Some(expiry) = document.expiry_date.map(|d| d.date_naive()) else {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::ExpiryUnknown,
+                field: Some("expiry_date".to_string()),
+                message: "No expiry date could be read; check it on the document".to_string(),
+            });
+            return;
+        };
+        if !document.is_expired_on(today) {
+            return;
+        }
+
+        // An expired DNI stays usable while the holder waits for the new one
+        let days_expired = (today - expiry).num_days();
+        if document.document_type == DocumentType::DNI && days_expired <= self.dni_renewal_grace_days {
+            outcome.warnings.push(ValidationWarning {
+                code: ValidationWarningCode::ExpiredUnderRenewal,
+                field: Some("expiry_date".to_string()),
+                message: format!(
+                    "The DNI expired on {}; accepted for {} more days while it is renewed",
+                    expiry,
+                    self.dni_renewal_grace_days - days_expired
+                ),
+            });
+        } else {
+            outcome.errors.push(format!("The document expired on {}", expiry));
+        }
+    }
+
+    fn check_dates(document: &Document, today: NaiveDate, outcome: &mut RuleOutcome) {
+        let birth = document.birth_date.map(|d| d.date_naive());
+        let issue = document.issue_date.map(|d| d.date_naive());
+        let expiry = document.expiry_date.map(|d| d.date_naive());
+
+        if birth.is_some_and(|b| b > today) {
+            outcome.errors.push("The birth date is in the future".to_string());
+        }
+        if issue.is_some_and(|i| i > today) {
+            outcome.errors.push("The issue date is in the future".to_string());
+        }
+
+        let mut in_order = |earlier: Option<NaiveDate>, later: Option<NaiveDate>, message: &str| {
+            if let (Some(earlier), Some(later)) = (earlier, later) {
+                if earlier >= later {
+                    outcome.errors.push(message.to_string());
+                }
+            }
+        };
+        in_order(birth, issue, "The birth date is not before the issue date");
+        in_order(birth, expiry, "The birth date is not before the expiry date");
+        in_order(issue, expiry, "The issue date is not before the expiry date");
+    }
+}
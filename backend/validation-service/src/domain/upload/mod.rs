@@ -0,0 +1,268 @@
+//! Turns what a pilgrim uploads into images the rest of the service reads:
+//! PNG or JPEG, no larger than OCR needs. Formats are told by their content,
+//! not by what the client claims, and sizes are checked before anything is
+//! decoded so one upload cannot exhaust the component's memory.
+
+pub mod pdf;
+
+use std::io::Cursor;
+
+use image::{imageops::FilterType, io::Reader as ImageReader, DynamicImage, ImageFormat};
+use shared::{AlbergueError, AlbergueResult};
+
+use pdf::PageImage;
+
+/// Longest side worth giving OCR: a card filling half a 12 megapixel photo
+/// still reads at this size, and larger only costs time and memory.
+pub const OCR_MAX_SIDE: u32 = 2000;
+
+/// Pages of a PDF read: the front, then the back.
+pub const MAX_PDF_PAGES: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadFormat {
+    Jpeg,
+    Png,
+    WebP,
+    /// HEIC and other HEIF images, as iPhones take them
+    Heif,
+    Pdf,
+}
+
+impl UploadFormat {
+    pub const ALL: [UploadFormat; 5] = [Self::Jpeg, Self::Png, Self::WebP, Self::Heif, Self::Pdf];
+
+    /// The format `data` is in, from its leading bytes.
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(Self::WebP)
+        } else if data.len() >= 12 && &data[4..8] == b"ftyp" && Self::is_heif_brand(&data[8..12]) {
+            Some(Self::Heif)
+        } else if data.starts_with(b"%PDF-") {
+            Some(Self::Pdf)
+        } else {
+            None
+        }
+    }
+
+    fn is_heif_brand(brand: &[u8]) -> bool {
+        matches!(brand, b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1" | b"msf1")
+    }
+
+    /// The format a file extension names, as listed in `SUPPORTED_FORMATS`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::WebP),
+            "heic" | "heif" => Some(Self::Heif),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG",
+            Self::WebP => "WebP",
+            Self::Heif => "HEIC",
+            Self::Pdf => "PDF",
+        }
+    }
+}
+
+/// What one upload may be.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadLimits {
+    /// Size of the file itself
+    pub max_bytes: usize,
+    /// Width times height of any image in it, checked before decoding
+    pub max_pixels: u64,
+    pub formats: Vec<UploadFormat>,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 10 * 1024 * 1024,
+            max_pixels: 24_000_000,
+            formats: UploadFormat::ALL.to_vec(),
+        }
+    }
+}
+
+/// An upload as images the pipeline can read, one per page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+    pub format: UploadFormat,
+    /// PNG or JPEG file bytes for OCR. A JPEG or PNG small enough is passed
+    /// on as sent, metadata and all.
+    pub pages: Vec<Vec<u8>>,
+    /// Each page as it came, before any scaling: the upload itself or the
+    /// JPEG a PDF embeds. Forgery checks read these, as re-encoding drops
+    /// metadata and smooths away recapture patterns.
+    pub originals: Vec<Vec<u8>>,
+}
+
+pub struct UploadDecoder {
+    limits: UploadLimits,
+}
+
+impl Default for UploadDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UploadDecoder {
+    pub fn new() -> Self {
+        Self {
+            limits: UploadLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: UploadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &UploadLimits {
+        &self.limits
+    }
+
+    /// Decodes base64 `encoded`, refusing it by its length before decoding
+    /// when it could not fit `max_bytes`.
+    pub fn decode_base64(&self, encoded: &str, side: &str) -> AlbergueResult<Vec<u8>> {
+        if encoded.len() / 4 * 3 > self.limits.max_bytes + 3 {
+            return Err(self.too_large(side));
+        }
+        let data = base64::decode(encoded).map_err(|e| AlbergueError::Validation {
+            message: format!("Invalid {} image encoding: {}", side, e),
+        })?;
+        if data.len() > self.limits.max_bytes {
+            return Err(self.too_large(side));
+        }
+        Ok(data)
+    }
+
+    fn too_large(&self, side: &str) -> AlbergueError {
+        AlbergueError::Validation {
+            message: format!(
+                "The {} image is larger than {} MB",
+                side,
+                self.limits.max_bytes / (1024 * 1024)
+            ),
+        }
+    }
+
+    /// The format of `data`, if it is one of those accepted.
+    pub fn format(&self, data: &[u8]) -> AlbergueResult<UploadFormat> {
+        match UploadFormat::sniff(data) {
+            Some(format) if self.limits.formats.contains(&format) => Ok(format),
+            Some(UploadFormat::Heif) => Err(heic_unreadable()),
+            _ => Err(AlbergueError::Validation {
+                message: format!(
+                    "Unsupported file format; send one of {}",
+                    self.limits.formats.iter().map(UploadFormat::name).collect::<Vec<_>>().join(", ")
+                ),
+            }),
+        }
+    }
+
+    /// Reads `data` into pages. HEIF cannot be decoded here and must be
+    /// converted to JPEG first.
+    pub fn decode(&self, data: &[u8]) -> AlbergueResult<Upload> {
+        let format = self.format(data)?;
+        let (mut pages, mut originals) = (vec![], vec![]);
+        match format {
+            UploadFormat::Jpeg | UploadFormat::Png | UploadFormat::WebP => {
+                pages.push(self.normalize(data, format)?);
+                originals.push(data.to_vec());
+            }
+            UploadFormat::Pdf => {
+                for page in pdf::page_images(data, MAX_PDF_PAGES, self.limits.max_pixels)? {
+                    match page {
+                        PageImage::Jpeg(jpeg) => {
+                            pages.push(self.normalize(&jpeg, UploadFormat::Jpeg)?);
+                            originals.push(jpeg);
+                        }
+                        // A raw bitmap is kept at full size, as PNG
+                        PageImage::Decoded(image) => {
+                            originals.push(encode(image.clone())?);
+                            pages.push(encode(fit_for_ocr(image))?);
+                        }
+                    }
+                }
+            }
+            UploadFormat::Heif => return Err(heic_unreadable()),
+        }
+        Ok(Upload {
+            format,
+            pages,
+            originals,
+        })
+    }
+
+    /// A JPEG or PNG within `OCR_MAX_SIDE` as it is; anything else decoded,
+    /// scaled down and re-encoded as PNG.
+    fn normalize(&self, data: &[u8], format: UploadFormat) -> AlbergueResult<Vec<u8>> {
+        let image_format = match format {
+            UploadFormat::Jpeg => ImageFormat::Jpeg,
+            UploadFormat::Png => ImageFormat::Png,
+            _ => ImageFormat::WebP,
+        };
+        let unreadable = |e: image::ImageError| AlbergueError::Validation {
+            message: format!("Failed to read {} image: {}", format.name(), e),
+        };
+
+        // Only the header is read for the size
+        let (width, height) = ImageReader::with_format(Cursor::new(data), image_format)
+            .into_dimensions()
+            .map_err(unreadable)?;
+        if width as u64 * height as u64 > self.limits.max_pixels {
+            return Err(AlbergueError::Validation {
+                message: format!(
+                    "The image is {}x{} pixels, more than the {} megapixels accepted",
+                    width,
+                    height,
+                    self.limits.max_pixels / 1_000_000
+                ),
+            });
+        }
+        if width.max(height) <= OCR_MAX_SIDE && format != UploadFormat::WebP {
+            return Ok(data.to_vec());
+        }
+
+        let image = image::load_from_memory_with_format(data, image_format).map_err(unreadable)?;
+        encode(fit_for_ocr(image))
+    }
+}
+
+fn heic_unreadable() -> AlbergueError {
+    AlbergueError::Validation {
+        message: "HEIC images cannot be read; set the camera to Most Compatible or send a JPEG".to_string(),
+    }
+}
+
+/// `image` scaled so its longest side is at most `OCR_MAX_SIDE`.
+pub fn fit_for_ocr(image: DynamicImage) -> DynamicImage {
+    if image.width().max(image.height()) <= OCR_MAX_SIDE {
+        return image;
+    }
+    image.resize(OCR_MAX_SIDE, OCR_MAX_SIDE, FilterType::Triangle)
+}
+
+fn encode(image: DynamicImage) -> AlbergueResult<Vec<u8>> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| AlbergueError::Internal {
+            message: format!("Failed to encode image: {}", e),
+        })?;
+    Ok(png)
+}
@@ -0,0 +1,244 @@
+//! Scanned PDFs. A scanner or phone scanning app puts each page's scan in
+//! as one image, usually a JPEG; the largest image on a page is taken as the
+//! page. PDFs drawn as text and vectors hold no photo of a document and are
+//! refused.
+
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use image::{DynamicImage, GrayImage, ImageFormat, RgbImage};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use shared::{AlbergueError, AlbergueResult};
+
+/// One image from a page, either still encoded or already decoded.
+pub enum PageImage {
+    /// JPEG file bytes, as embedded
+    Jpeg(Vec<u8>),
+    Decoded(DynamicImage),
+}
+
+/// The scans on the first `max_pages` pages, in page order. The first page
+/// must have one; later pages without are skipped, as a scan is often
+/// followed by a printed page of terms.
+pub fn page_images(data: &[u8], max_pages: usize, max_pixels: u64) -> AlbergueResult<Vec<PageImage>> {
+    let doc = Document::load_mem(data).map_err(|e| invalid(format!("Failed to read PDF: {}", e)))?;
+    if doc.is_encrypted() {
+        return Err(invalid("The PDF is password protected".to_string()));
+    }
+
+    let mut images = vec![];
+    for (number, page_id) in doc.get_pages().into_iter().take(max_pages) {
+        match largest_image(&doc, page_id) {
+            Some(stream) => {
+                let image = decode(&doc, stream, max_pixels)
+                    .map_err(|e| invalid(format!("Page {} of the PDF: {}", number, e)))?;
+                images.push(rotate(image, rotation(&doc, page_id))?);
+            }
+            None if images.is_empty() => {
+                return Err(invalid(format!(
+                    "Page {} of the PDF has no scanned image; send a photo or a scan of the document",
+                    number
+                )))
+            }
+            None => {}
+        }
+    }
+    if images.is_empty() {
+        return Err(invalid("The PDF has no pages".to_string()));
+    }
+    Ok(images)
+}
+
+fn invalid(message: String) -> AlbergueError {
+    AlbergueError::Validation { message }
+}
+
+/// Follows `object` if it is a reference.
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
+
+/// `key` on the page or, as PDF allows, on a page tree node above it.
+fn inherited<'a>(doc: &'a Document, page_id: ObjectId, key: &[u8]) -> Option<&'a Object> {
+    let mut node = doc.get_dictionary(page_id).ok()?;
+    // The depth bound guards against a tree that loops
+    for _ in 0..32 {
+        if let Ok(value) = node.get(key) {
+            return resolve(doc, value);
+        }
+        node = doc.get_dictionary(node.get(b"Parent").ok()?.as_reference().ok()?).ok()?;
+    }
+    None
+}
+
+fn largest_image(doc: &Document, page_id: ObjectId) -> Option<&Stream> {
+    let resources = inherited(doc, page_id, b"Resources")?.as_dict().ok()?;
+    let xobjects = resolve(doc, resources.get(b"XObject").ok()?)?.as_dict().ok()?;
+
+    xobjects
+        .iter()
+        .filter_map(|(_, object)| resolve(doc, object)?.as_stream().ok())
+        .filter(|stream| stream.dict.get(b"Subtype").and_then(Object::as_name_str).ok() == Some("Image"))
+        .max_by_key(|stream| dimension(&stream.dict, b"Width") * dimension(&stream.dict, b"Height"))
+}
+
+fn dimension(dict: &Dictionary, key: &[u8]) -> u64 {
+    dict.get(key).and_then(Object::as_i64).unwrap_or(0).max(0) as u64
+}
+
+/// Clockwise quarter turns the page is shown at.
+fn rotation(doc: &Document, page_id: ObjectId) -> i64 {
+    let degrees = inherited(doc, page_id, b"Rotate").and_then(|r| r.as_i64().ok()).unwrap_or(0);
+    degrees.rem_euclid(360) / 90
+}
+
+fn rotate(image: PageImage, quarter_turns: i64) -> AlbergueResult<PageImage> {
+    if quarter_turns == 0 {
+        return Ok(image);
+    }
+    let decoded = match image {
+        PageImage::Jpeg(bytes) => image::load_from_memory_with_format(&bytes, ImageFormat::Jpeg)
+            .map_err(|e| invalid(format!("Failed to decode the PDF's image: {}", e)))?,
+        PageImage::Decoded(decoded) => decoded,
+    };
+    Ok(PageImage::Decoded(match quarter_turns {
+        1 => decoded.rotate90(),
+        2 => decoded.rotate180(),
+        _ => decoded.rotate270(),
+    }))
+}
+
+fn decode(doc: &Document, stream: &Stream, max_pixels: u64) -> Result<PageImage, String> {
+    let (width, height) = (dimension(&stream.dict, b"Width"), dimension(&stream.dict, b"Height"));
+    if width * height > max_pixels {
+        return Err(format!(
+            "its image is {}x{} pixels, more than the {} megapixels accepted",
+            width,
+            height,
+            max_pixels / 1_000_000
+        ));
+    }
+
+    let filters = stream.filters().unwrap_or_default();
+    let samples = match filters.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["DCTDecode"] => return Ok(PageImage::Jpeg(stream.content.clone())),
+        [] => stream.content.clone(),
+        ["FlateDecode"] => {
+            let mut inflated = Vec::new();
+            ZlibDecoder::new(stream.content.as_slice())
+                // A corrupt stream must not inflate past the image's size
+                .take(width * height * 4 + height)
+                .read_to_end(&mut inflated)
+                .map_err(|e| format!("its image data is corrupt: {}", e))?;
+            inflated
+        }
+        other => return Err(format!("its image is compressed as {}, which is not supported", other.join(", "))),
+    };
+
+    let bits = stream.dict.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8);
+    let components = components(doc, &stream.dict)?;
+    if bits != 8 {
+        return Err(format!("its image has {} bits per sample; only 8 are supported", bits));
+    }
+    let samples = match predictor(&stream.dict) {
+        Some(predictor) if predictor >= 10 => unpredict(&samples, width as usize * components, components)
+            .ok_or_else(|| "its image data is corrupt".to_string())?,
+        _ => samples,
+    };
+    raw(samples, width as u32, height as u32, components).ok_or_else(|| "its image data is truncated".to_string())
+}
+
+fn predictor(dict: &Dictionary) -> Option<i64> {
+    let params = dict.get(b"DecodeParms").ok()?;
+    let params = params.as_dict().ok().or_else(|| params.as_array().ok()?.first()?.as_dict().ok())?;
+    params.get(b"Predictor").and_then(Object::as_i64).ok()
+}
+
+/// Samples per pixel of the image's colour space.
+fn components(doc: &Document, dict: &Dictionary) -> Result<usize, String> {
+    let space = dict.get(b"ColorSpace").ok().and_then(|s| resolve(doc, s));
+    let name = space.and_then(|s| {
+        s.as_name_str()
+            .ok()
+            .or_else(|| s.as_array().ok()?.first()?.as_name_str().ok())
+    });
+    match name {
+        Some("DeviceGray") | Some("CalGray") => Ok(1),
+        Some("DeviceRGB") | Some("CalRGB") => Ok(3),
+        Some("DeviceCMYK") => Ok(4),
+        Some("ICCBased") => {
+            let profile = space
+                .and_then(|s| s.as_array().ok()?.get(1))
+                .and_then(|p| resolve(doc, p)?.as_stream().ok());
+            let n = profile.and_then(|p| p.dict.get(b"N").and_then(Object::as_i64).ok());
+            match n {
+                Some(n @ (1 | 3 | 4)) => Ok(n as usize),
+                _ => Err("its image's colour profile is not readable".to_string()),
+            }
+        }
+        Some(other) => Err(format!("its image uses the {} colour space, which is not supported", other)),
+        None => Err("its image has no colour space".to_string()),
+    }
+}
+
+/// Undoes PNG row filters, each row led by its filter type.
+fn unpredict(data: &[u8], row_bytes: usize, bpp: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut previous = vec![0u8; row_bytes];
+    for row in data.chunks(row_bytes + 1) {
+        let (&filter, row) = row.split_first()?;
+        if row.len() < row_bytes {
+            return None;
+        }
+        let mut current = row.to_vec();
+        for i in 0..row_bytes {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            };
+            current[i] = current[i].wrapping_add(predicted);
+        }
+        out.extend_from_slice(&current);
+        previous = current;
+    }
+    Some(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn raw(mut samples: Vec<u8>, width: u32, height: u32, components: usize) -> Option<PageImage> {
+    samples.truncate(width as usize * height as usize * components);
+    let image = match components {
+        1 => DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, samples)?),
+        3 => DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, samples)?),
+        _ => {
+            // Naive CMYK: enough to read a document by
+            let rgb = samples
+                .chunks_exact(4)
+                .flat_map(|p| {
+                    let k = p[3] as u16;
+                    [0, 1, 2].map(|i| 255u16.saturating_sub(p[i] as u16 + k) as u8)
+                })
+                .collect();
+            DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, rgb)?)
+        }
+    };
+    Some(PageImage::Decoded(image))
+}
@@ -1,9 +1,38 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use regex::Regex;
-use shared::{AlbergueError, AlbergueResult, ExtractedData};
+use shared::{AlbergueResult, ExtractedData};
+
+use super::mrz_validator::mrz_comparable;
 
 pub struct DniValidator;
 
+/// Labelled blocks on the back of a DNI. The 3.0 card prints the birthplace
+/// first and the domicile after the parents' names; the 4.0 card opens with
+/// the domicile. Blocks are found by label, so either order reads the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BackSection {
+    Birthplace,
+    Domicile,
+    /// Parents' names and the issuing office code, which the parte does not need
+    Other,
+}
+
+/// Labels as `mrz_comparable` spells them.
+const BACK_LABELS: &[(&str, BackSection)] = &[
+    ("LUGAR DE NACIMIENTO", BackSection::Birthplace),
+    ("PLACE OF BIRTH", BackSection::Birthplace),
+    ("DOMICILIO", BackSection::Domicile),
+    ("ADDRESS", BackSection::Domicile),
+    ("HIJO A DE", BackSection::Other),
+    ("EQUIPO", BackSection::Other),
+];
+
+impl Default for DniValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DniValidator {
     pub fn new() -> Self {
         Self
@@ -29,21 +58,23 @@ impl DniValidator {
             let expected_letter = letters.chars().nth((number % 23) as usize);
 
             if let Some(expected) = expected_letter {
-                return letter_part.chars().next() == Some(expected);
+                return letter_part.starts_with(expected);
             }
         }
 
         false
     }
 
+    /// IDESP support numbers are three letters and six digits, e.g. `BAA000589`.
+    pub fn validate_support_number(support_number: &str) -> bool {
+        let idesp_regex = Regex::new(r"^[A-Z]{3}\d{6}$").unwrap();
+        idesp_regex.is_match(support_number)
+    }
+
     pub fn extract_data_from_ocr(ocr_text: &str) -> AlbergueResult<ExtractedData> {
         let mut extracted = ExtractedData {
-            document_number: None,
-            name: None,
-            surname: None,
-            birth_date: None,
             nationality: Some("ESP".to_string()),
-            expiry_date: None,
+            ..ExtractedData::default()
         };
 
         // Extract DNI number
@@ -52,13 +83,17 @@ impl DniValidator {
             extracted.document_number = Some(captures.as_str().to_string());
         }
 
-        // Extract name and surname (simplified pattern)
-        let name_regex = Regex::new(r"(?i)nombre[:\s]+([A-ZÁÉÍÓÚÑ\s]+)").unwrap();
+        // Extract name and surname, each label's value ending with its line
+        let name_regex = Regex::new(r"(?i)nombre[:\s]+([A-ZÁÉÍÓÚÑ ]+)").unwrap();
+        let surname_regex = Regex::new(r"(?i)apellidos[:\s]+([A-ZÁÉÍÓÚÑ ]+)").unwrap();
         if let Some(captures) = name_regex.captures(ocr_text) {
             if let Some(name_match) = captures.get(1) {
                 let full_name = name_match.as_str().trim();
                 let parts: Vec<&str> = full_name.split_whitespace().collect();
-                if !parts.is_empty() {
+                if let Some(captures) = surname_regex.captures(ocr_text) {
+                    extracted.name = Some(full_name.to_string());
+                    extracted.surname = Some(captures[1].trim().to_string());
+                } else if !parts.is_empty() {
                     extracted.name = Some(parts[0].to_string());
                     if parts.len() > 1 {
                         extracted.surname = Some(parts[1..].join(" "));
@@ -82,6 +117,88 @@ impl DniValidator {
             }
         }
 
+        extracted.issue_date = Self::labelled_date(ocr_text, r"emisi[oó]n|expedici[oó]n");
+        extracted.expiry_date = Self::labelled_date(ocr_text, r"v[aá]lido hasta|validez|caducidad");
+
+        Self::extract_back_data(ocr_text, &mut extracted);
+
         Ok(extracted)
     }
+
+    /// The first `dd/mm/yyyy` date after one of the `labels` alternatives.
+    fn labelled_date(ocr_text: &str, labels: &str) -> Option<DateTime<Utc>> {
+        let regex = Regex::new(&format!(r"(?i)(?:{})[:\s]+(\d{{2}})[/\-.](\d{{2}})[/\-.](\d{{4}})", labels)).unwrap();
+        let captures = regex.captures(ocr_text)?;
+        let naive_date = NaiveDate::from_ymd_opt(
+            captures[3].parse().ok()?,
+            captures[2].parse().ok()?,
+            captures[1].parse().ok()?,
+        )?;
+        Some(naive_date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+    }
+
+    /// Reads the domicile, birthplace and support number printed on the back
+    /// of DNI 3.0 and 4.0 cards.
+    pub fn extract_back_data(ocr_text: &str, extracted: &mut ExtractedData) {
+        // Loose on purpose: a misread support number should fail validation,
+        // not vanish
+        let support_regex =
+            Regex::new(r"(?i)(?:IDESP|SOPORTE|SOPORT)[\s.:]*([A-Z0-9]{9})").unwrap();
+        if let Some(captures) = support_regex.captures(ocr_text) {
+            extracted.support_number = Some(captures[1].to_uppercase());
+        }
+
+        let mut birthplace = vec![];
+        let mut domicile = vec![];
+        let mut section = None;
+        for line in ocr_text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            // MRZ lines close whatever block was open
+            if line.contains("<<") {
+                section = None;
+                continue;
+            }
+
+            let comparable = mrz_comparable(line);
+            let label = BACK_LABELS
+                .iter()
+                .find(|(label, _)| comparable.starts_with(label));
+            let value = match label {
+                Some((label, found)) => {
+                    section = Some(*found);
+                    // The value may follow the label on the same line
+                    let rest = line
+                        .char_indices()
+                        .filter(|(_, c)| c.is_alphanumeric())
+                        .nth(label.replace(' ', "").chars().count())
+                        .map_or("", |(i, _)| &line[i..]);
+                    rest.trim_start_matches(|c: char| !c.is_alphanumeric())
+                }
+                None => line,
+            };
+            if value.is_empty() {
+                continue;
+            }
+
+            match section {
+                Some(BackSection::Birthplace) if birthplace.len() < 2 => birthplace.push(value),
+                Some(BackSection::Domicile) if domicile.len() < 4 => domicile.push(value),
+                _ => {}
+            }
+        }
+
+        if !birthplace.is_empty() {
+            extracted.birthplace = Some(birthplace.join(", "));
+        }
+
+        // Street, municipality and province, the street sometimes wrapping
+        // over two lines
+        let (street, place) = match domicile.len() {
+            0 => return,
+            1 | 2 => domicile.split_at(1),
+            n => domicile.split_at(n - 2),
+        };
+        extracted.address = Some(street.join(" "));
+        extracted.municipality = place.first().map(|s| s.to_string());
+        extracted.province = place.get(1).map(|s| s.to_string());
+    }
 }
@@ -0,0 +1,67 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use shared::{AlbergueResult, ExtractedData};
+
+use super::dni_validator::DniValidator;
+
+/// Reads EU driving licences, whose fields are numbered the same in every
+/// member state: 1 surname, 2 given names, 3 birth date and place, 4b
+/// expiry, 5 licence number.
+pub struct DrivingLicenceValidator;
+
+impl Default for DrivingLicenceValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DrivingLicenceValidator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Spanish licences are numbered with the holder's DNI, so its control
+    /// letter must match; other states only need a plausible number.
+    pub fn validate_licence_number(&self, licence_number: &str) -> bool {
+        if DniValidator::validate_format(licence_number) {
+            return DniValidator::validate_checksum(licence_number);
+        }
+        Regex::new(r"^[A-Z0-9]{5,16}$").unwrap().is_match(licence_number)
+    }
+
+    pub fn extract_licence_data(&self, licence_text: &str) -> AlbergueResult<ExtractedData> {
+        let mut extracted = ExtractedData::default();
+
+        let field = |label: &str| {
+            Regex::new(&format!(r"(?m)^\s*{}\.\s*(.+?)\s*$", label))
+                .unwrap()
+                .captures(licence_text)
+                .map(|captures| captures[1].to_string())
+        };
+        let date = |value: &str| {
+            let captures = Regex::new(r"(\d{2})[/.-](\d{2})[/.-](\d{4})").unwrap().captures(value)?;
+            let naive_date = NaiveDate::from_ymd_opt(
+                captures[3].parse().ok()?,
+                captures[2].parse().ok()?,
+                captures[1].parse().ok()?,
+            )?;
+            Some(DateTime::from_naive_utc_and_offset(naive_date.and_hms_opt(0, 0, 0).unwrap(), Utc))
+        };
+
+        extracted.surname = field("1");
+        extracted.name = field("2");
+        extracted.birth_date = field("3").as_deref().and_then(date);
+        // 4a (issue) and 4b (expiry) often share a line
+        let numbered_date = |label: &str| {
+            Regex::new(&format!(r"{}\.\s*(\d{{2}}[/.-]\d{{2}}[/.-]\d{{4}})", label))
+                .unwrap()
+                .captures(licence_text)
+                .and_then(|captures| date(&captures[1]))
+        };
+        extracted.issue_date = numbered_date("4a");
+        extracted.expiry_date = numbered_date("4b");
+        extracted.document_number = field("5").map(|number| number.replace(' ', ""));
+
+        Ok(extracted)
+    }
+}
@@ -0,0 +1,83 @@
+use regex::Regex;
+
+/// States whose national identity cards Spain accepts in place of a passport:
+/// the EU, the EEA, Switzerland and the microstates. Germany is `D` in MRZs.
+pub const ACCEPTED_STATES: &[&str] = &[
+    "AUT", "BEL", "BGR", "HRV", "CYP", "CZE", "D", "DNK", "EST", "FIN", "FRA", "GRC", "HUN", "IRL",
+    "ITA", "LVA", "LTU", "LUX", "MLT", "NLD", "POL", "PRT", "ROU", "SVK", "SVN", "ESP", "SWE", "ISL",
+    "LIE", "NOR", "CHE", "AND", "MCO", "SMR", "VAT",
+];
+
+/// National rules for identity card numbers, on top of the MRZ check digit.
+pub struct IdCardValidator;
+
+impl Default for IdCardValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdCardValidator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn is_accepted_state(issuing_state: &str) -> bool {
+        ACCEPTED_STATES.contains(&issuing_state)
+    }
+
+    /// Whether `number` follows the issuing state's own format, or `None`
+    /// for states without a known rule.
+    pub fn validate_number(&self, issuing_state: &str, number: &str) -> Option<bool> {
+        match issuing_state {
+            "PRT" => Some(Self::validate_portuguese_cc(number)),
+            "ITA" => Some(Self::validate_italian_cie(number)),
+            "D" | "DEU" => Some(Self::validate_german_id(number)),
+            _ => None,
+        }
+    }
+
+    /// Cartão de Cidadão: 8-digit civil number, its check digit, two version
+    /// characters and a final check digit over all twelve, computed Luhn-style
+    /// with letters counting 10 to 35.
+    pub fn validate_portuguese_cc(number: &str) -> bool {
+        let number: String = number.chars().filter(|c| !c.is_whitespace()).collect();
+        if !Regex::new(r"^\d{9}[A-Z0-9]{2}\d$").unwrap().is_match(&number) {
+            return false;
+        }
+
+        let sum: u32 = number
+            .chars()
+            .rev()
+            .enumerate()
+            .map(|(position, c)| {
+                let value = c.to_digit(36).unwrap_or(0);
+                if position % 2 == 1 {
+                    let doubled = value * 2;
+                    if doubled > 9 {
+                        doubled - 9
+                    } else {
+                        doubled
+                    }
+                } else {
+                    value
+                }
+            })
+            .sum();
+
+        sum.is_multiple_of(10)
+    }
+
+    /// Carta d'Identità Elettronica 3.0: `CA00000AA`.
+    pub fn validate_italian_cie(number: &str) -> bool {
+        Regex::new(r"^C[A-Z]\d{5}[A-Z]{2}$").unwrap().is_match(number)
+    }
+
+    /// Personalausweis since 2010: nine characters without vowels, starting
+    /// with one of `LMNPRTVWXY`.
+    pub fn validate_german_id(number: &str) -> bool {
+        Regex::new(r"^[LMNPRTVWXY][CFGHJKLMNPRTVWXYZ0-9]{8}$")
+            .unwrap()
+            .is_match(number)
+    }
+}
@@ -1,4 +1,6 @@
 pub mod dni_validator;
+pub mod driving_licence_validator;
+pub mod id_card_validator;
 pub mod mrz_validator;
 pub mod nie_validator;
 pub mod passport_validator;
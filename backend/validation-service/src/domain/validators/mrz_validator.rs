@@ -1,165 +1,860 @@
-use chrono::{DateTime, NaiveDate, Utc};
-use regex::Regex;
-use shared::{AlbergueResult, ExtractedData};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shared::{AlbergueError, AlbergueResult, ExtractedData, FieldConflict};
+use std::fmt;
+use std::ops::Range;
+
+/// ICAO 9303 machine readable zone layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MrzFormat {
+    /// ID cards: 3 lines of 30 characters
+    TD1,
+    /// Older ID cards and visas: 2 lines of 36 characters
+    TD2,
+    /// Passports: 2 lines of 44 characters
+    TD3,
+}
+
+impl MrzFormat {
+    pub fn line_length(&self) -> usize {
+        match self {
+            MrzFormat::TD1 => 30,
+            MrzFormat::TD2 => 36,
+            MrzFormat::TD3 => 44,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        match self {
+            MrzFormat::TD1 => 3,
+            MrzFormat::TD2 | MrzFormat::TD3 => 2,
+        }
+    }
+}
+
+/// Check digits defined by ICAO 9303, reported when they do not match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MrzCheck {
+    DocumentNumber,
+    BirthDate,
+    ExpiryDate,
+    OptionalData,
+    Composite,
+}
+
+impl fmt::Display for MrzCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self {
+            MrzCheck::DocumentNumber => "document number",
+            MrzCheck::BirthDate => "birth date",
+            MrzCheck::ExpiryDate => "expiry date",
+            MrzCheck::OptionalData => "optional data",
+            MrzCheck::Composite => "composite",
+        };
+        write!(f, "MRZ {} check digit failed", field)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sex {
+    Male,
+    Female,
+    Unspecified,
+}
+
+impl Sex {
+    fn from_mrz(ch: char) -> Option<Self> {
+        match ch {
+            'M' => Some(Sex::Male),
+            'F' => Some(Sex::Female),
+            '<' | 'X' => Some(Sex::Unspecified),
+            _ => None,
+        }
+    }
+
+    pub fn as_mrz(&self) -> &'static str {
+        match self {
+            Sex::Male => "M",
+            Sex::Female => "F",
+            Sex::Unspecified => "X",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MrzData {
+    pub format: MrzFormat,
+    pub document_code: String,
+    pub issuing_state: String,
+    pub document_number: String,
+    pub nationality: String,
+    pub birth_date: Option<NaiveDate>,
+    pub sex: Option<Sex>,
+    pub expiry_date: Option<NaiveDate>,
+    pub surname: String,
+    pub given_names: String,
+    /// Personal number (TD3) or first optional data field (TD1, TD2).
+    pub optional_data: String,
+    /// Second optional data field, TD1 only.
+    pub optional_data_2: String,
+    pub failed_checks: Vec<MrzCheck>,
+    /// The normalised MRZ lines the data was read from.
+    pub lines: Vec<String>,
+}
+
+impl MrzData {
+    pub fn is_valid(&self) -> bool {
+        self.failed_checks.is_empty()
+    }
+
+    pub fn to_extracted_data(&self) -> ExtractedData {
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        let at_midnight = |d: NaiveDate| DateTime::from_naive_utc_and_offset(d.and_hms_opt(0, 0, 0).unwrap(), Utc);
+
+        ExtractedData {
+            document_number: non_empty(&self.document_number),
+            name: non_empty(&self.given_names),
+            surname: non_empty(&self.surname),
+            birth_date: self.birth_date.map(at_midnight),
+            nationality: non_empty(&self.nationality),
+            expiry_date: self.expiry_date.map(at_midnight),
+            sex: self.sex.map(|s| s.as_mrz().to_string()),
+            ..ExtractedData::default()
+        }
+    }
+}
+
+/// Uppercase, accents folded and punctuation collapsed, the way the MRZ spells names.
+pub fn mrz_comparable(value: &str) -> String {
+    value
+        .to_uppercase()
+        .chars()
+        .map(|c| match c {
+            'Á' | 'À' | 'Â' | 'Ä' | 'Ã' => 'A',
+            'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'Ó' | 'Ò' | 'Ô' | 'Ö' | 'Õ' => 'O',
+            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'Ñ' => 'N',
+            'Ç' => 'C',
+            c if c.is_ascii_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `value` uppercased with the letters ICAO 9303 spells out with more than
+/// one replaced by those letters. Single accented letters are left for
+/// `mrz_comparable` to fold.
+pub fn icao_transliterate(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.to_uppercase().chars() {
+        match c {
+            'Ä' | 'Æ' | 'Ǽ' => out.push_str("AE"),
+            'Å' => out.push_str("AA"),
+            'Ö' | 'Ø' | 'Œ' => out.push_str("OE"),
+            'Ü' => out.push_str("UE"),
+            'ß' => out.push_str("SS"),
+            'Þ' => out.push_str("TH"),
+            'Ĳ' => out.push_str("IJ"),
+            'Ð' | 'Đ' => out.push('D'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The ways an MRZ may spell `value`, folded by `mrz_comparable`: accents
+/// dropped, transliterated per ICAO 9303 (MÜLLER as MUELLER), and with Ñ as
+/// the NXX ICAO 9303 also allows.
+pub fn mrz_spellings(value: &str) -> Vec<String> {
+    let mut spellings = vec![];
+    for spelling in [
+        mrz_comparable(value),
+        mrz_comparable(&icao_transliterate(value)),
+        mrz_comparable(&icao_transliterate(value).replace('Ñ', "NXX")),
+    ] {
+        if !spellings.contains(&spelling) {
+            spellings.push(spelling);
+        }
+    }
+    spellings
+}
+
+/// Merges MRZ and visual-zone readings of the same document. The MRZ is
+/// check-digit protected, so it wins wherever it has a value; disagreements
+/// are reported rather than silently dropped.
+pub fn reconcile(mrz: &ExtractedData, visual: &ExtractedData) -> (ExtractedData, Vec<FieldConflict>) {
+    // The visual zone prints names as they are, so any MRZ spelling agrees
+    fn same_value(m: &str, v: &str) -> bool {
+        let m = mrz_comparable(m).replace(' ', "");
+        mrz_spellings(v).iter().any(|v| v.replace(' ', "") == m)
+    }
+    // The MRZ truncates long names, so a visual name that extends it agrees
+    fn same_name(m: &str, v: &str) -> bool {
+        let m = mrz_comparable(m);
+        mrz_spellings(v).iter().any(|v| v.starts_with(&m))
+    }
+    // Visual zones often print nationality in words ("ESPAÑOLA"); only codes compare
+    fn same_nationality(m: &str, v: &str) -> bool {
+        v.trim().chars().count() != 3 || same_value(m, v)
+    }
+
+    let mut conflicts = vec![];
+    let mut compare = |field: &str, m: Option<String>, v: Option<String>, agree: fn(&str, &str) -> bool| {
+        if let (Some(m), Some(v)) = (m, v) {
+            if !agree(&m, &v) {
+                conflicts.push(FieldConflict {
+                    field: field.to_string(),
+                    mrz_value: m,
+                    visual_value: v,
+                });
+            }
+        }
+    };
+    let date = |d: Option<DateTime<Utc>>| d.map(|d| d.date_naive().to_string());
+
+    compare("document_number", mrz.document_number.clone(), visual.document_number.clone(), same_value);
+    compare("name", mrz.name.clone(), visual.name.clone(), same_name);
+    compare("surname", mrz.surname.clone(), visual.surname.clone(), same_name);
+    compare("birth_date", date(mrz.birth_date), date(visual.birth_date), same_value);
+    compare("nationality", mrz.nationality.clone(), visual.nationality.clone(), same_nationality);
+    compare("expiry_date", date(mrz.expiry_date), date(visual.expiry_date), same_value);
+    compare("sex", mrz.sex.clone(), visual.sex.clone(), same_value);
+
+    let merged = ExtractedData {
+        document_number: mrz.document_number.clone().or_else(|| visual.document_number.clone()),
+        name: mrz.name.clone().or_else(|| visual.name.clone()),
+        surname: mrz.surname.clone().or_else(|| visual.surname.clone()),
+        birth_date: mrz.birth_date.or(visual.birth_date),
+        nationality: mrz.nationality.clone().or_else(|| visual.nationality.clone()),
+        expiry_date: mrz.expiry_date.or(visual.expiry_date),
+        sex: mrz.sex.clone().or_else(|| visual.sex.clone()),
+        // Only the visual zone prints addresses and birthplaces
+        ..visual.clone()
+    };
+    (merged, conflicts)
+}
+
+/// What may legally appear at an MRZ position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    /// Letters and filler: codes, country codes, sex, names
+    Alpha,
+    /// Dates and check digits
+    Digit,
+    /// Check digit that is `<` when the field overflows or is empty
+    DigitOrFiller,
+    /// Document numbers and optional data
+    AlphaNum,
+}
+
+/// Characters OCR routinely swaps for one another.
+const CONFUSIONS: &[(char, char)] = &[('0', 'O'), ('1', 'I'), ('5', 'S'), ('8', 'B'), ('<', 'K')];
+
+/// Each confusable character doubles the readings of a field, so only this
+/// many per field are varied.
+const MAX_AMBIGUOUS_PER_FIELD: usize = 10;
+
+fn confusion_of(ch: char) -> Option<char> {
+    CONFUSIONS.iter().find_map(|&(a, b)| match ch {
+        _ if ch == a => Some(b),
+        _ if ch == b => Some(a),
+        _ => None,
+    })
+}
+
+/// A character changed during correction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MrzCorrection {
+    pub line: usize,
+    pub position: usize,
+    pub from: char,
+    pub to: char,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrectedMrz {
+    pub lines: Vec<String>,
+    /// Confidence in each character of `lines`, from 0 to 1.
+    pub confidence: Vec<Vec<f32>>,
+    pub corrections: Vec<MrzCorrection>,
+    pub data: MrzData,
+}
+
+impl CorrectedMrz {
+    pub fn min_confidence(&self) -> f32 {
+        self.confidence.iter().flatten().copied().fold(1.0, f32::min)
+    }
+}
+
+/// One reading of a field: the character chosen at each ambiguous position.
+type Assignment = Vec<((usize, usize), char)>;
+
+struct ProtectedField {
+    segments: Vec<(usize, Range<usize>)>,
+    check: (usize, usize),
+}
+
+impl ProtectedField {
+    fn new(segments: Vec<(usize, Range<usize>)>, check: (usize, usize)) -> Self {
+        Self { segments, check }
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|(line, range)| range.clone().map(move |i| (*line, i)))
+    }
+
+    fn verifies(&self, grid: &[Vec<char>]) -> bool {
+        let field: String = self.positions().map(|(l, i)| grid[l][i]).collect();
+        MrzValidator::verify_check_digit(&field, grid[self.check.0][self.check.1])
+    }
+}
 
 pub struct MrzValidator;
 
+impl Default for MrzValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MrzValidator {
     pub fn new() -> Self {
         Self
     }
 
-    pub fn validate_mrz_checksum(&self, mrz_line: &str) -> AlbergueResult<bool> {
-        if mrz_line.len() != 44 {
-            return Ok(false);
+    fn char_value(ch: char) -> Option<u32> {
+        match ch {
+            '0'..='9' => ch.to_digit(10),
+            'A'..='Z' => Some(ch as u32 - 'A' as u32 + 10),
+            '<' => Some(0),
+            _ => None,
         }
+    }
 
-        // MRZ check digit algorithm
+    /// ICAO 9303 check digit: character values weighted 7, 3, 1 repeating, modulo 10.
+    pub fn check_digit(field: &str) -> Option<u32> {
         let weights = [7, 3, 1];
-        let mut sum = 0;
-
-        for (i, ch) in mrz_line.chars().enumerate() {
-            let value = match ch {
-                '0'..='9' => ch.to_digit(10).unwrap() as usize,
-                'A'..='Z' => (ch as u8 - b'A' + 10) as usize,
-                '<' => 0,
-                _ => return Ok(false),
-            };
+        field
+            .chars()
+            .enumerate()
+            .try_fold(0, |sum, (i, ch)| Some(sum + Self::char_value(ch)? * weights[i % 3]))
+            .map(|sum| sum % 10)
+    }
 
-            sum += value * weights[i % 3];
+    /// Whether `digit` is the check digit of `field`. A filler `<` stands
+    /// for zero, which ICAO allows for empty optional fields.
+    pub fn verify_check_digit(field: &str, digit: char) -> bool {
+        let expected = match Self::check_digit(field) {
+            Some(expected) => expected,
+            None => return false,
+        };
+        match digit {
+            '<' => expected == 0 && field.chars().all(|c| c == '<'),
+            _ => digit.to_digit(10) == Some(expected),
         }
+    }
+
+    /// Picks the MRZ lines out of OCR text, ignoring anything that is not
+    /// made of MRZ characters or does not have an MRZ line length. A printed
+    /// title can have the right length once its spaces are gone, so the zone
+    /// must also open with a document code and use fillers somewhere.
+    pub fn find_mrz_lines(&self, text: &str) -> Option<(MrzFormat, Vec<String>)> {
+        let lines: Vec<String> = text
+            .lines()
+            .map(|l| l.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase())
+            .filter(|l| !l.is_empty() && l.chars().all(|c| Self::char_value(c).is_some()))
+            .collect();
 
-        Ok(sum % 10 == 0)
+        for format in [MrzFormat::TD3, MrzFormat::TD2, MrzFormat::TD1] {
+            let count = format.line_count();
+            let found = lines
+                .windows(count)
+                .find(|w| {
+                    w.iter().all(|l| l.len() == format.line_length())
+                        && w[0].starts_with(['A', 'C', 'I', 'P', 'V'])
+                        && w.iter().any(|l| l.contains('<'))
+                });
+            if let Some(found) = found {
+                return Some((format, found.to_vec()));
+            }
+        }
+        None
     }
 
-    pub fn extract_mrz_data(&self, mrz_text: &str) -> AlbergueResult<ExtractedData> {
-        let mut extracted = ExtractedData::default();
+    pub fn parse(&self, mrz_text: &str) -> AlbergueResult<MrzData> {
+        self.parse_at(mrz_text, Utc::now().date_naive())
+    }
 
-        // TD1 format (3 lines, 30 characters each) - ID cards
-        let td1_regex = Regex::new(r"([A-Z0-9<]{30})\n([A-Z0-9<]{30})\n([A-Z0-9<]{30})").unwrap();
+    /// Parses relative to `today`, which decides the century of two-digit years.
+    pub fn parse_at(&self, mrz_text: &str, today: NaiveDate) -> AlbergueResult<MrzData> {
+        let (format, lines) = self.find_mrz_lines(mrz_text).ok_or_else(|| AlbergueError::Validation {
+            message: "No machine readable zone found".to_string(),
+        })?;
 
-        // TD3 format (2 lines, 44 characters each) - Passports
-        let td3_regex = Regex::new(r"([A-Z0-9<]{44})\n([A-Z0-9<]{44})").unwrap();
+        Ok(self.parse_lines(format, &lines, today))
+    }
 
-        if let Some(captures) = td3_regex.captures(mrz_text) {
-            self.parse_td3_format(&captures[1], &captures[2], &mut extracted)?;
-        } else if let Some(captures) = td1_regex.captures(mrz_text) {
-            self.parse_td1_format(&captures[1], &captures[2], &captures[3], &mut extracted)?;
+    pub fn parse_lines(&self, format: MrzFormat, lines: &[String], today: NaiveDate) -> MrzData {
+        match format {
+            MrzFormat::TD1 => self.parse_td1_format(lines, today),
+            MrzFormat::TD2 => self.parse_td2_format(lines, today),
+            MrzFormat::TD3 => self.parse_td3_format(lines, today),
         }
-
-        Ok(extracted)
     }
 
-    fn parse_td3_format(
-        &self,
-        line1: &str,
-        line2: &str,
-        extracted: &mut ExtractedData,
-    ) -> AlbergueResult<()> {
-        // Line 1: P<COUNTRY<SURNAME<<GIVEN_NAMES<<<<<<<<<<<<<<<
-        if line1.len() >= 44 {
-            let country = &line1[2..5];
-            let name_part = &line1[5..44];
+    fn char_classes(format: MrzFormat) -> Vec<Vec<CharClass>> {
+        use CharClass::*;
+        let layout: Vec<Vec<(Range<usize>, CharClass)>> = match format {
+            MrzFormat::TD3 => vec![
+                vec![(0..44, Alpha)],
+                vec![
+                    (0..9, AlphaNum), (9..10, Digit), (10..13, Alpha), (13..20, Digit), (20..21, Alpha),
+                    (21..28, Digit), (28..42, AlphaNum), (42..43, DigitOrFiller), (43..44, Digit),
+                ],
+            ],
+            MrzFormat::TD2 => vec![
+                vec![(0..36, Alpha)],
+                vec![
+                    (0..9, AlphaNum), (9..10, DigitOrFiller), (10..13, Alpha), (13..20, Digit), (20..21, Alpha),
+                    (21..28, Digit), (28..35, AlphaNum), (35..36, Digit),
+                ],
+            ],
+            MrzFormat::TD1 => vec![
+                vec![(0..5, Alpha), (5..14, AlphaNum), (14..15, DigitOrFiller), (15..30, AlphaNum)],
+                vec![
+                    (0..7, Digit), (7..8, Alpha), (8..15, Digit), (15..18, Alpha), (18..29, AlphaNum),
+                    (29..30, Digit),
+                ],
+                vec![(0..30, Alpha)],
+            ],
+        };
 
-            // Extract surname and given names
-            let parts: Vec<&str> = name_part.split("<<").collect();
-            if !parts.is_empty() {
-                extracted.surname = Some(parts[0].replace('<', " ").trim().to_string());
-                if parts.len() > 1 {
-                    extracted.name = Some(parts[1].replace('<', " ").trim().to_string());
-                }
+        layout
+            .into_iter()
+            .map(|line| line.into_iter().flat_map(|(range, class)| range.map(move |_| class)).collect())
+            .collect()
+    }
+
+    /// Fields guarded by a check digit, given the (class-corrected) grid.
+    fn protected_fields(format: MrzFormat, grid: &[Vec<char>]) -> (Vec<ProtectedField>, ProtectedField) {
+        let overflow = |line: usize, check: usize, optional: Range<usize>| -> Option<ProtectedField> {
+            if grid[line][check] != '<' || grid[line][optional.start] == '<' {
+                return None;
             }
+            let end = optional.clone().find(|&i| grid[line][i] == '<').unwrap_or(optional.end);
+            (end - optional.start >= 2).then(|| {
+                ProtectedField::new(vec![(line, check - 9..check), (line, optional.start..end - 1)], (line, end - 1))
+            })
+        };
 
-            extracted.nationality = Some(country.to_string());
+        match format {
+            MrzFormat::TD3 => (
+                vec![
+                    ProtectedField::new(vec![(1, 0..9)], (1, 9)),
+                    ProtectedField::new(vec![(1, 13..19)], (1, 19)),
+                    ProtectedField::new(vec![(1, 21..27)], (1, 27)),
+                    ProtectedField::new(vec![(1, 28..42)], (1, 42)),
+                ],
+                ProtectedField::new(vec![(1, 0..10), (1, 13..20), (1, 21..43)], (1, 43)),
+            ),
+            MrzFormat::TD2 => (
+                vec![
+                    overflow(1, 9, 28..35).unwrap_or_else(|| ProtectedField::new(vec![(1, 0..9)], (1, 9))),
+                    ProtectedField::new(vec![(1, 13..19)], (1, 19)),
+                    ProtectedField::new(vec![(1, 21..27)], (1, 27)),
+                ],
+                ProtectedField::new(vec![(1, 0..10), (1, 13..20), (1, 21..35)], (1, 35)),
+            ),
+            MrzFormat::TD1 => (
+                vec![
+                    overflow(0, 14, 15..30).unwrap_or_else(|| ProtectedField::new(vec![(0, 5..14)], (0, 14))),
+                    ProtectedField::new(vec![(1, 0..6)], (1, 6)),
+                    ProtectedField::new(vec![(1, 8..14)], (1, 14)),
+                ],
+                ProtectedField::new(vec![(0, 5..30), (1, 0..7), (1, 8..15), (1, 18..29)], (1, 29)),
+            ),
         }
+    }
+
+    /// Substitution forced by the position's alphabet, e.g. `O` in a date.
+    fn forced_substitution(class: CharClass, ch: char) -> Option<char> {
+        let alternative = confusion_of(ch)?;
+        let fits = |c: char| match class {
+            CharClass::Alpha => c.is_ascii_uppercase() || c == '<',
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::DigitOrFiller => c.is_ascii_digit() || c == '<',
+            CharClass::AlphaNum => true,
+        };
+        (!fits(ch) && fits(alternative)).then_some(alternative)
+    }
 
-        // Line 2: PASSPORT_NO<COUNTRY<BIRTH_DATE<SEX<EXPIRY_DATE<PERSONAL_NO
-        if line2.len() >= 44 {
-            let passport_no = &line2[0..9].replace('<', "");
-            extracted.document_number = Some(passport_no.to_string());
+    /// Every assignment of the field's confusable characters that satisfies
+    /// its check digit, the uncorrected reading first when it is valid.
+    fn field_candidates(
+        field: &ProtectedField,
+        grid: &[Vec<char>],
+        classes: &[Vec<CharClass>],
+    ) -> Vec<Assignment> {
+        let ambiguous: Vec<(usize, usize)> = field
+            .positions()
+            .filter(|&(l, i)| classes[l][i] == CharClass::AlphaNum && confusion_of(grid[l][i]).is_some())
+            .take(MAX_AMBIGUOUS_PER_FIELD)
+            .collect();
 
-            // Parse birth date (YYMMDD)
-            let birth_date = &line2[13..19];
-            if let Ok(date) = self.parse_mrz_date(birth_date) {
-                extracted.birth_date = Some(date);
+        let mut candidates = vec![];
+        let mut trial = grid.to_vec();
+        for mask in 0u32..(1 << ambiguous.len()) {
+            let assignment: Assignment = ambiguous
+                .iter()
+                .enumerate()
+                .map(|(bit, &(l, i))| {
+                    let ch = if mask & (1 << bit) == 0 { grid[l][i] } else { confusion_of(grid[l][i]).unwrap() };
+                    ((l, i), ch)
+                })
+                .collect();
+            for &((l, i), ch) in &assignment {
+                trial[l][i] = ch;
             }
-
-            // Parse expiry date (YYMMDD)
-            let expiry_date = &line2[21..27];
-            if let Ok(date) = self.parse_mrz_date(expiry_date) {
-                extracted.expiry_date = Some(date);
+            if field.verifies(&trial) {
+                candidates.push(assignment);
             }
         }
+        candidates
+    }
 
-        Ok(())
+    /// Repairs common OCR confusions (`0/O`, `1/I`, `5/S`, `8/B`, `</K`):
+    /// first where a position's alphabet rules one reading out, then by
+    /// keeping only readings whose check digits validate.
+    pub fn correct(&self, mrz_text: &str) -> AlbergueResult<CorrectedMrz> {
+        self.correct_at(mrz_text, Utc::now().date_naive())
     }
 
-    fn parse_td1_format(
-        &self,
-        line1: &str,
-        line2: &str,
-        line3: &str,
-        extracted: &mut ExtractedData,
-    ) -> AlbergueResult<()> {
-        // Line 1: DOCUMENT_TYPE<COUNTRY<DOCUMENT_NUMBER
-        if line1.len() >= 30 {
-            let country = &line1[2..5];
-            let doc_number = &line1[5..14].replace('<', "");
+    pub fn correct_at(&self, mrz_text: &str, today: NaiveDate) -> AlbergueResult<CorrectedMrz> {
+        let (format, lines) = self.find_mrz_lines(mrz_text).ok_or_else(|| AlbergueError::Validation {
+            message: "No machine readable zone found".to_string(),
+        })?;
 
-            extracted.nationality = Some(country.to_string());
-            extracted.document_number = Some(doc_number.to_string());
+        let classes = Self::char_classes(format);
+        let original: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+        let mut grid = original.clone();
+        let mut confidence: Vec<Vec<f32>> = lines.iter().map(|l| vec![1.0; l.len()]).collect();
+
+        // 1. Alphabet-forced substitutions
+        for (l, line) in grid.iter_mut().enumerate() {
+            for (i, ch) in line.iter_mut().enumerate() {
+                if let Some(fixed) = Self::forced_substitution(classes[l][i], *ch) {
+                    *ch = fixed;
+                    confidence[l][i] = 0.8;
+                }
+            }
         }
 
-        // Line 2: BIRTH_DATE<SEX<EXPIRY_DATE<NATIONALITY<OPTIONAL
-        if line2.len() >= 30 {
-            let birth_date = &line2[0..6];
-            if let Ok(date) = self.parse_mrz_date(birth_date) {
-                extracted.birth_date = Some(date);
+        // Filler read as `K` after a name has ended
+        for &l in match format {
+            MrzFormat::TD1 => &[2][..],
+            MrzFormat::TD2 | MrzFormat::TD3 => &[0][..],
+        } {
+            let line = &mut grid[l];
+            let tail_start = line.iter().rposition(|&c| c != '<' && c != 'K').map_or(0, |p| p + 1);
+            if let Some(first_filler) = line[tail_start..].iter().position(|&c| c == '<') {
+                for i in tail_start + first_filler..line.len() {
+                    if line[i] == 'K' {
+                        line[i] = '<';
+                        confidence[l][i] = 0.8;
+                    }
+                }
             }
+        }
 
-            let expiry_date = &line2[8..14];
-            if let Ok(date) = self.parse_mrz_date(expiry_date) {
-                extracted.expiry_date = Some(date);
+        // 2. Ambiguous characters in check-digit protected fields
+        let (fields, composite) = Self::protected_fields(format, &grid);
+        let per_field: Vec<Vec<Assignment>> = fields
+            .iter()
+            .map(|f| Self::field_candidates(f, &grid, &classes))
+            .collect();
+
+        // Pick one candidate per field so the composite check also holds
+        let mut chosen = vec![0usize; fields.len()];
+        let mut indices = vec![0usize; fields.len()];
+        'search: loop {
+            let mut trial = grid.clone();
+            for (f, &idx) in indices.iter().enumerate() {
+                if let Some(candidate) = per_field[f].get(idx) {
+                    for &((l, i), ch) in candidate {
+                        trial[l][i] = ch;
+                    }
+                }
             }
+            if composite.verifies(&trial) {
+                chosen = indices.clone();
+                break;
+            }
+            // Next combination, odometer style
+            for f in 0..indices.len() {
+                indices[f] += 1;
+                if indices[f] < per_field[f].len() {
+                    continue 'search;
+                }
+                indices[f] = 0;
+            }
+            break;
         }
 
-        // Line 3: SURNAME<<GIVEN_NAMES<<<<<<<<<<<<<<<
-        if line3.len() >= 30 {
-            let parts: Vec<&str> = line3.split("<<").collect();
-            if !parts.is_empty() {
-                extracted.surname = Some(parts[0].replace('<', " ").trim().to_string());
-                if parts.len() > 1 {
-                    extracted.name = Some(parts[1].replace('<', " ").trim().to_string());
+        for (f, candidates) in per_field.iter().enumerate() {
+            let Some(picked) = candidates.get(chosen[f]) else {
+                // No reading satisfies the check digit: leave the field as read
+                for (l, i) in fields[f].positions() {
+                    if confusion_of(grid[l][i]).is_some() && classes[l][i] == CharClass::AlphaNum {
+                        confidence[l][i] = confidence[l][i].min(0.5);
+                    }
+                }
+                continue;
+            };
+            for &((l, i), ch) in picked {
+                let agreeing = candidates
+                    .iter()
+                    .filter(|c| c.iter().any(|&(pos, other)| pos == (l, i) && other == ch))
+                    .count();
+                let share = agreeing as f32 / candidates.len() as f32;
+                if ch != grid[l][i] {
+                    grid[l][i] = ch;
+                    confidence[l][i] = 0.9 * share;
+                } else {
+                    confidence[l][i] = confidence[l][i].min(share);
                 }
             }
         }
 
-        Ok(())
+        let corrections = original
+            .iter()
+            .zip(&grid)
+            .enumerate()
+            .flat_map(|(l, (before, after))| {
+                before
+                    .iter()
+                    .zip(after)
+                    .enumerate()
+                    .filter(|(_, (a, b))| a != b)
+                    .map(move |(i, (&from, &to))| MrzCorrection { line: l, position: i, from, to })
+            })
+            .collect();
+
+        let lines: Vec<String> = grid.iter().map(|l| l.iter().collect()).collect();
+        let data = self.parse_lines(format, &lines, today);
+
+        Ok(CorrectedMrz {
+            lines,
+            confidence,
+            corrections,
+            data,
+        })
     }
 
-    fn parse_mrz_date(&self, date_str: &str) -> Result<DateTime<Utc>, &'static str> {
-        if date_str.len() != 6 {
-            return Err("Invalid date format");
+    pub fn extract_mrz_data(&self, mrz_text: &str) -> AlbergueResult<ExtractedData> {
+        match self.correct(mrz_text) {
+            Ok(corrected) => Ok(corrected.data.to_extracted_data()),
+            Err(_) => Ok(ExtractedData::default()),
         }
+    }
 
-        let year_part: i32 = date_str[0..2].parse().map_err(|_| "Invalid year")?;
-        let month: u32 = date_str[2..4].parse().map_err(|_| "Invalid month")?;
-        let day: u32 = date_str[4..6].parse().map_err(|_| "Invalid day")?;
+    fn parse_td3_format(&self, lines: &[String], today: NaiveDate) -> MrzData {
+        let (line1, line2) = (&lines[0], &lines[1]);
+        let c = |s: &str, i: usize| s.as_bytes()[i] as char;
+        let mut failed_checks = vec![];
 
-        // Handle 2-digit year (assume 20xx for years < 50, 19xx for years >= 50)
-        let year = if year_part < 50 {
-            2000 + year_part
-        } else {
-            1900 + year_part
-        };
+        // Line 2: number, check, nationality, birth, check, sex, expiry, check, personal number, check, composite
+        if !Self::verify_check_digit(&line2[0..9], c(line2, 9)) {
+            failed_checks.push(MrzCheck::DocumentNumber);
+        }
+        if !Self::verify_check_digit(&line2[13..19], c(line2, 19)) {
+            failed_checks.push(MrzCheck::BirthDate);
+        }
+        if !Self::verify_check_digit(&line2[21..27], c(line2, 27)) {
+            failed_checks.push(MrzCheck::ExpiryDate);
+        }
+        if !Self::verify_check_digit(&line2[28..42], c(line2, 42)) {
+            failed_checks.push(MrzCheck::OptionalData);
+        }
+        let composite = format!("{}{}{}", &line2[0..10], &line2[13..20], &line2[21..43]);
+        if !Self::verify_check_digit(&composite, c(line2, 43)) {
+            failed_checks.push(MrzCheck::Composite);
+        }
+
+        let (surname, given_names) = Self::parse_names(&line1[5..44]);
+
+        MrzData {
+            format: MrzFormat::TD3,
+            document_code: Self::clean(&line1[0..2]),
+            issuing_state: Self::clean(&line1[2..5]),
+            document_number: Self::clean(&line2[0..9]),
+            nationality: Self::clean(&line2[10..13]),
+            birth_date: Self::parse_birth_date(&line2[13..19], today),
+            sex: Sex::from_mrz(c(line2, 20)),
+            expiry_date: Self::parse_expiry_date(&line2[21..27], today),
+            surname,
+            given_names,
+            optional_data: Self::clean(&line2[28..42]),
+            optional_data_2: String::new(),
+            failed_checks,
+            lines: lines.to_vec(),
+        }
+    }
+
+    fn parse_td2_format(&self, lines: &[String], today: NaiveDate) -> MrzData {
+        let (line1, line2) = (&lines[0], &lines[1]);
+        let c = |s: &str, i: usize| s.as_bytes()[i] as char;
+        let mut failed_checks = vec![];
+
+        let (document_number, optional_data) =
+            Self::document_number_with_overflow(&line2[0..9], c(line2, 9), &line2[28..35], &mut failed_checks);
+
+        if !Self::verify_check_digit(&line2[13..19], c(line2, 19)) {
+            failed_checks.push(MrzCheck::BirthDate);
+        }
+        if !Self::verify_check_digit(&line2[21..27], c(line2, 27)) {
+            failed_checks.push(MrzCheck::ExpiryDate);
+        }
+        let composite = format!("{}{}{}", &line2[0..10], &line2[13..20], &line2[21..35]);
+        if !Self::verify_check_digit(&composite, c(line2, 35)) {
+            failed_checks.push(MrzCheck::Composite);
+        }
+
+        let (surname, given_names) = Self::parse_names(&line1[5..36]);
+
+        MrzData {
+            format: MrzFormat::TD2,
+            document_code: Self::clean(&line1[0..2]),
+            issuing_state: Self::clean(&line1[2..5]),
+            document_number,
+            nationality: Self::clean(&line2[10..13]),
+            birth_date: Self::parse_birth_date(&line2[13..19], today),
+            sex: Sex::from_mrz(c(line2, 20)),
+            expiry_date: Self::parse_expiry_date(&line2[21..27], today),
+            surname,
+            given_names,
+            optional_data,
+            optional_data_2: String::new(),
+            failed_checks,
+            lines: lines.to_vec(),
+        }
+    }
+
+    fn parse_td1_format(&self, lines: &[String], today: NaiveDate) -> MrzData {
+        let (line1, line2, line3) = (&lines[0], &lines[1], &lines[2]);
+        let c = |s: &str, i: usize| s.as_bytes()[i] as char;
+        let mut failed_checks = vec![];
+
+        // Line 1: code, issuing state, document number, check, optional data
+        let (document_number, optional_data) =
+            Self::document_number_with_overflow(&line1[5..14], c(line1, 14), &line1[15..30], &mut failed_checks);
+
+        // Line 2: birth, check, sex, expiry, check, nationality, optional data, composite
+        if !Self::verify_check_digit(&line2[0..6], c(line2, 6)) {
+            failed_checks.push(MrzCheck::BirthDate);
+        }
+        if !Self::verify_check_digit(&line2[8..14], c(line2, 14)) {
+            failed_checks.push(MrzCheck::ExpiryDate);
+        }
+        let composite = format!("{}{}{}{}", &line1[5..30], &line2[0..7], &line2[8..15], &line2[18..29]);
+        if !Self::verify_check_digit(&composite, c(line2, 29)) {
+            failed_checks.push(MrzCheck::Composite);
+        }
+
+        // Line 3: names
+        let (surname, given_names) = Self::parse_names(line3);
+
+        MrzData {
+            format: MrzFormat::TD1,
+            document_code: Self::clean(&line1[0..2]),
+            issuing_state: Self::clean(&line1[2..5]),
+            document_number,
+            nationality: Self::clean(&line2[15..18]),
+            birth_date: Self::parse_birth_date(&line2[0..6], today),
+            sex: Sex::from_mrz(c(line2, 7)),
+            expiry_date: Self::parse_expiry_date(&line2[8..14], today),
+            surname,
+            given_names,
+            optional_data,
+            optional_data_2: Self::clean(&line2[18..29]),
+            failed_checks,
+            lines: lines.to_vec(),
+        }
+    }
+
+    /// TD1 and TD2 document numbers longer than 9 characters put `<` in the
+    /// check digit position and continue in the optional data, where the
+    /// last character before the filler is the check digit.
+    fn document_number_with_overflow(
+        number: &str,
+        check: char,
+        optional: &str,
+        failed_checks: &mut Vec<MrzCheck>,
+    ) -> (String, String) {
+        if check == '<' && !optional.starts_with('<') {
+            let overflow_len = optional.find('<').unwrap_or(optional.len());
+            if overflow_len >= 2 {
+                let overflow = &optional[..overflow_len - 1];
+                let overflow_check = optional.as_bytes()[overflow_len - 1] as char;
+                let full_number = format!("{}{}", number, overflow);
+                if !Self::verify_check_digit(&full_number, overflow_check) {
+                    failed_checks.push(MrzCheck::DocumentNumber);
+                }
+                return (Self::clean(&full_number), Self::clean(&optional[overflow_len..]));
+            }
+        }
+
+        if !Self::verify_check_digit(number, check) {
+            failed_checks.push(MrzCheck::DocumentNumber);
+        }
+        (Self::clean(number), Self::clean(optional))
+    }
+
+    /// Primary and secondary identifiers are separated by `<<`, words by `<`.
+    fn parse_names(field: &str) -> (String, String) {
+        let mut parts = field.trim_end_matches('<').splitn(2, "<<");
+        let surname = parts.next().unwrap_or_default().replace('<', " ").trim().to_string();
+        let given_names = parts.next().unwrap_or_default().replace('<', " ").trim().to_string();
+        (surname, given_names)
+    }
+
+    fn clean(field: &str) -> String {
+        field.trim_end_matches('<').replace('<', " ").trim().to_string()
+    }
+
+    fn parse_yymmdd(date_str: &str, century: i32) -> Option<NaiveDate> {
+        if date_str.len() != 6 || !date_str.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let year: i32 = date_str[0..2].parse().ok()?;
+        let month: u32 = date_str[2..4].parse().ok()?;
+        let day: u32 = date_str[4..6].parse().ok()?;
+        NaiveDate::from_ymd_opt(century + year, month, day)
+    }
+
+    /// Nobody is born in the future: a two-digit year after this year's is
+    /// taken to be last century.
+    fn parse_birth_date(date_str: &str, today: NaiveDate) -> Option<NaiveDate> {
+        let current_century = today.year() / 100 * 100;
+        match Self::parse_yymmdd(date_str, current_century)? {
+            date if date > today => Self::parse_yymmdd(date_str, current_century - 100),
+            date => Some(date),
+        }
+    }
 
-        if let Some(naive_date) = NaiveDate::from_ymd_opt(year, month, day) {
-            Ok(DateTime::from_naive_utc_and_offset(
-                naive_date.and_hms_opt(0, 0, 0).unwrap(),
-                Utc,
-            ))
-        } else {
-            Err("Invalid date")
+    /// Documents are valid for at most a few decades, so an expiry more than
+    /// 50 years ahead is read as last century (a long-expired document).
+    fn parse_expiry_date(date_str: &str, today: NaiveDate) -> Option<NaiveDate> {
+        let current_century = today.year() / 100 * 100;
+        match Self::parse_yymmdd(date_str, current_century)? {
+            date if date.year() > today.year() + 50 => Self::parse_yymmdd(date_str, current_century - 100),
+            date => Some(date),
         }
     }
 }
@@ -4,16 +4,24 @@ use shared::{AlbergueResult, ExtractedData};
 
 pub struct NieValidator;
 
+impl Default for NieValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NieValidator {
     pub fn new() -> Self {
         Self
     }
 
+    pub fn is_nie_format(value: &str) -> bool {
+        Regex::new(r"^[XYZ]\d{7}[A-Z]$").unwrap().is_match(value)
+    }
+
     pub fn validate_nie(&self, nie_number: &str) -> AlbergueResult<bool> {
         // NIE format: Letter (X,Y,Z) + 7 digits + control letter
-        let nie_regex = Regex::new(r"^[XYZ]\d{7}[A-Z]$").unwrap();
-
-        if !nie_regex.is_match(nie_number) {
+        if !Self::is_nie_format(nie_number) {
             return Ok(false);
         }
 
@@ -48,13 +56,13 @@ impl NieValidator {
         }
 
         // Extract name patterns (Spanish NIE format)
-        let name_regex = Regex::new(r"(?i)nombre[:\s]+([A-ZÁÉÍÓÚÑÜ\s]+)").unwrap();
+        let name_regex = Regex::new(r"(?i)nombre[:\s]+([A-ZÁÉÍÓÚÑÜ ]+)").unwrap();
         if let Some(captures) = name_regex.captures(nie_text) {
             extracted.name = Some(captures[1].trim().to_string());
         }
 
         // Extract surnames
-        let surname_regex = Regex::new(r"(?i)apellidos[:\s]+([A-ZÁÉÍÓÚÑÜ\s]+)").unwrap();
+        let surname_regex = Regex::new(r"(?i)apellidos[:\s]+([A-ZÁÉÍÓÚÑÜ ]+)").unwrap();
         if let Some(captures) = surname_regex.captures(nie_text) {
             extracted.surname = Some(captures[1].trim().to_string());
         }
@@ -75,7 +83,7 @@ impl NieValidator {
         }
 
         // Extract nationality for NIE documents
-        let nationality_regex = Regex::new(r"(?i)nacionalidad[:\s]+([A-ZÁÉÍÓÚÑÜ\s]+)").unwrap();
+        let nationality_regex = Regex::new(r"(?i)nacionalidad[:\s]+([A-ZÁÉÍÓÚÑÜ ]+)").unwrap();
         if let Some(captures) = nationality_regex.captures(nie_text) {
             extracted.nationality = Some(captures[1].trim().to_string());
         }
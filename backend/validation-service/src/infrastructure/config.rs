@@ -0,0 +1,222 @@
+use crate::adapters::db_audit_log::DbAuditLog;
+use crate::adapters::db_review_store::DbReviewStore;
+use crate::adapters::http_image_converter::HttpImageConverter;
+use crate::adapters::local_portrait_store::LocalPortraitStore;
+use crate::adapters::s3_portrait_store::{S3PortraitStore, S3Settings};
+use crate::adapters::spin_db::SpinDb;
+use crate::application::review_service::ReviewService;
+use crate::application::validation_service::{ValidationService, DEFAULT_REVIEW_CONFIDENCE};
+use crate::domain::chip::CscaList;
+use crate::domain::portrait::PortraitCipher;
+use crate::domain::upload::{UploadFormat, UploadLimits};
+use crate::ports::image_converter::ImageConverter;
+use crate::ports::portrait_store::PortraitStore;
+use shared::{AlbergueError, AlbergueResult};
+
+/// Where cropped ID photos go. Storage is off unless `PORTRAIT_STORAGE` is
+/// set; photos are then sealed with `ENCRYPTION_KEY` before they leave the
+/// service.
+#[derive(Debug, Clone)]
+pub enum PortraitStorageConfig {
+    Disabled,
+    Local { dir: String },
+    S3(S3Settings),
+}
+
+impl PortraitStorageConfig {
+    pub fn from_env() -> AlbergueResult<Self> {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let required = |name: &str| {
+            std::env::var(name).map_err(|_| AlbergueError::Internal {
+                message: format!("{} must be set for S3 portrait storage", name),
+            })
+        };
+
+        match var("PORTRAIT_STORAGE").as_str() {
+            "" | "none" => Ok(Self::Disabled),
+            "local" => Ok(Self::Local {
+                dir: std::env::var("PORTRAIT_STORAGE_DIR").unwrap_or_else(|_| "./data/portraits".to_string()),
+            }),
+            "s3" => Ok(Self::S3(S3Settings {
+                endpoint: required("PORTRAIT_S3_ENDPOINT")?,
+                bucket: std::env::var("PORTRAIT_S3_BUCKET").unwrap_or_else(|_| "id-photos".to_string()),
+                region: std::env::var("PORTRAIT_S3_REGION").unwrap_or_else(|_| "eu-south-2".to_string()),
+                access_key: required("PORTRAIT_S3_ACCESS_KEY")?,
+                secret_key: required("PORTRAIT_S3_SECRET_KEY")?,
+            })),
+            other => Err(AlbergueError::Internal {
+                message: format!("Unknown PORTRAIT_STORAGE {}; expected local or s3", other),
+            }),
+        }
+    }
+
+    /// The configured store and the cipher for it, or `None` when disabled.
+    pub fn build(self) -> AlbergueResult<Option<(Box<dyn PortraitStore>, PortraitCipher)>> {
+        let store: Box<dyn PortraitStore> = match self {
+            Self::Disabled => return Ok(None),
+            Self::Local { dir } => Box::new(LocalPortraitStore::new(dir)),
+            Self::S3(settings) => Box::new(S3PortraitStore::new(settings)),
+        };
+        let key = std::env::var("ENCRYPTION_KEY").map_err(|_| AlbergueError::Internal {
+            message: "ENCRYPTION_KEY must be set to store ID photos".to_string(),
+        })?;
+        Ok(Some((store, PortraitCipher::from_base64_key(&key)?)))
+    }
+}
+
+/// What uploads are accepted, from `DOCUMENT_MAX_SIZE_MB`,
+/// `DOCUMENT_MAX_MEGAPIXELS` and `SUPPORTED_FORMATS`, and where HEIC
+/// images are converted, from `IMAGE_CONVERTER_URL`. HEIC is only accepted
+/// when there is a converter.
+#[derive(Debug, Clone)]
+pub struct UploadConfig {
+    pub limits: UploadLimits,
+    pub converter_url: Option<String>,
+}
+
+impl UploadConfig {
+    pub fn from_env() -> AlbergueResult<Self> {
+        let defaults = UploadLimits::default();
+        let number = |name: &str, default: u64| -> AlbergueResult<u64> {
+            match std::env::var(name) {
+                Ok(value) if !value.trim().is_empty() => value.trim().parse().map_err(|_| AlbergueError::Internal {
+                    message: format!("{} must be a whole number, not {}", name, value),
+                }),
+                _ => Ok(default),
+            }
+        };
+        let max_megabytes = number("DOCUMENT_MAX_SIZE_MB", (defaults.max_bytes / (1024 * 1024)) as u64)?;
+        let max_megapixels = number("DOCUMENT_MAX_MEGAPIXELS", defaults.max_pixels / 1_000_000)?;
+
+        let formats = match std::env::var("SUPPORTED_FORMATS") {
+            Ok(list) if !list.trim().is_empty() => {
+                let mut formats = vec![];
+                for name in list.split(',') {
+                    let format = UploadFormat::from_extension(name).ok_or_else(|| AlbergueError::Internal {
+                        message: format!("Unknown format {} in SUPPORTED_FORMATS", name.trim()),
+                    })?;
+                    if !formats.contains(&format) {
+                        formats.push(format);
+                    }
+                }
+                formats
+            }
+            _ => defaults.formats,
+        };
+        let converter_url = std::env::var("IMAGE_CONVERTER_URL").ok().filter(|url| !url.trim().is_empty());
+
+        Ok(Self {
+            limits: UploadLimits {
+                max_bytes: max_megabytes as usize * 1024 * 1024,
+                max_pixels: max_megapixels * 1_000_000,
+                formats: formats
+                    .into_iter()
+                    .filter(|format| *format != UploadFormat::Heif || converter_url.is_some())
+                    .collect(),
+            },
+            converter_url,
+        })
+    }
+
+    /// The limits and, when configured, the converter for HEIC images.
+    pub fn build(self) -> (UploadLimits, Option<Box<dyn ImageConverter>>) {
+        let converter = self
+            .converter_url
+            .map(|url| Box::new(HttpImageConverter::new(url)) as Box<dyn ImageConverter>);
+        (self.limits, converter)
+    }
+}
+
+/// How sure a reading must be not to be queued for review, from
+/// `REVIEW_CONFIDENCE_THRESHOLD`, a percentage like `OCR_CONFIDENCE_THRESHOLD`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewConfig {
+    pub confidence: f32,
+}
+
+impl ReviewConfig {
+    pub fn from_env() -> AlbergueResult<Self> {
+        let confidence = match std::env::var("REVIEW_CONFIDENCE_THRESHOLD") {
+            Ok(value) if !value.trim().is_empty() => match value.trim().parse::<f32>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => percent / 100.0,
+                _ => {
+                    return Err(AlbergueError::Internal {
+                        message: format!("REVIEW_CONFIDENCE_THRESHOLD must be a percentage, not {}", value),
+                    })
+                }
+            },
+            _ => DEFAULT_REVIEW_CONFIDENCE,
+        };
+        Ok(Self { confidence })
+    }
+}
+
+/// The CSCA certificates chip signatures are traced to, from the PEM file
+/// `CSCA_CERTIFICATES` names. Without any, no chip is trusted over the
+/// printed text.
+#[derive(Debug, Clone)]
+pub struct ChipConfig {
+    pub cscas: CscaList,
+}
+
+impl ChipConfig {
+    pub fn from_env() -> AlbergueResult<Self> {
+        let cscas = match std::env::var("CSCA_CERTIFICATES") {
+            Ok(path) if !path.trim().is_empty() => {
+                let pem = std::fs::read_to_string(path.trim()).map_err(|e| AlbergueError::Internal {
+                    message: format!("CSCA_CERTIFICATES {} cannot be read: {}", path.trim(), e),
+                })?;
+                CscaList::from_pem(&pem)?
+            }
+            _ => CscaList::default(),
+        };
+        Ok(Self { cscas })
+    }
+}
+
+/// The database of the review queue and audit log, from `DATABASE_URL`:
+/// Postgres for a `postgres://` URL, Spin's default SQLite database for any
+/// other value. Nothing is queued for review while it is unset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseConfig {
+    pub url: Option<String>,
+}
+
+impl DatabaseConfig {
+    pub fn from_env() -> Self {
+        let url = std::env::var("DATABASE_URL").ok().filter(|url| !url.trim().is_empty());
+        Self { url }
+    }
+}
+
+/// The validation service as the environment configures it: upload limits,
+/// trusted CSCAs and, when `PORTRAIT_STORAGE` is set, where ID photos go;
+/// with a database, unsure readings are queued for review.
+pub fn validation_service_from_env() -> AlbergueResult<ValidationService> {
+    let (limits, converter) = UploadConfig::from_env()?.build();
+    let mut service = ValidationService::new()
+        .with_upload_limits(limits)
+        .with_cscas(ChipConfig::from_env()?.cscas);
+    if let Some(converter) = converter {
+        service = service.with_image_converter(converter);
+    }
+    if let Some((store, cipher)) = PortraitStorageConfig::from_env()?.build()? {
+        service = service.with_portrait_store(store, cipher);
+    }
+    if let Some(url) = DatabaseConfig::from_env().url {
+        let store = DbReviewStore::new(SpinDb::open(&url)?);
+        service = service.with_review_queue(Box::new(store), ReviewConfig::from_env()?.confidence);
+    }
+    Ok(service)
+}
+
+/// The hospitalero's review queue over the database `DATABASE_URL` names.
+pub fn review_service_from_env() -> AlbergueResult<ReviewService> {
+    let url = DatabaseConfig::from_env().url.ok_or_else(|| AlbergueError::Internal {
+        message: "DATABASE_URL must be set to review documents".to_string(),
+    })?;
+    Ok(ReviewService::new(
+        Box::new(DbReviewStore::new(SpinDb::open(&url)?)),
+        Box::new(DbAuditLog::new(SpinDb::open(&url)?)),
+    ))
+}
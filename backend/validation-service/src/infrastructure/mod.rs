@@ -1 +1,2 @@
-// Infrastructure layer for external concerns
\ No newline at end of file
+// Infrastructure layer for external concerns
+pub mod config;
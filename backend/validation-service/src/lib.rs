@@ -1,18 +1,77 @@
 use anyhow::Result;
-use http::{Method, Request, StatusCode};
-use spin_sdk::http::{IntoResponse, ResponseBuilder};
-use spin_sdk::http_component;
+use chrono::Utc;
+use http::{HeaderValue, Method, Request, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use shared::health::HealthReport;
+use shared::telemetry::{request_id_from, JsonLogger, REQUEST_ID_HEADER};
+use shared::{
+    AlbergueError, AlbergueResult, ReviewCorrectionRequest, ReviewRejectionRequest, ValidationRequest,
+};
+use spin_sdk::http::{Response, ResponseBuilder};
+use uuid::Uuid;
 
-#[http_component]
-fn handle_request(req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+pub mod adapters;
+pub mod application;
+pub mod domain;
+pub mod infrastructure;
+pub mod ports;
+
+use domain::review::Reviewer;
+use infrastructure::config::{review_service_from_env, validation_service_from_env};
+
+/// The admin a request was forwarded for, set by the gateway once it has
+/// checked their token. Only the gateway can reach this component.
+const USER_ID_HEADER: &str = "X-User-Id";
+const USER_ROLE_HEADER: &str = "X-User-Role";
+
+const SERVICE_NAME: &str = "validation-service";
+
+#[cfg_attr(target_arch = "wasm32", spin_sdk::http_component)]
+pub async fn handle_request(mut req: Request<Vec<u8>>) -> Result<Response> {
+    // Keep the gateway's request id, so logs and audit entries carry it
+    let request_id = request_id_from(
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        req.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    let logger = JsonLogger::from_env(SERVICE_NAME).with_request_id(&request_id);
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+
+    let result = route(req).await;
+    match &result {
+        Ok(response) => logger.info(
+            "request handled",
+            json!({"method": method, "path": path, "status": response.status()}),
+        ),
+        Err(e) => logger.error(
+            "request failed",
+            json!({"method": method, "path": path, "error": e.to_string()}),
+        ),
+    }
+    result.map(|mut response| {
+        response.set_header(REQUEST_ID_HEADER, request_id);
+        response
+    })
+}
+
+async fn route(req: Request<Vec<u8>>) -> Result<Response> {
     let method = req.method();
     let path = req.uri().path();
-    
+
     match (method, path) {
-        (&Method::POST, "/validate/document") => handle_document_validation(req),
+        (&Method::GET, "/health") => health(),
+        (&Method::POST, "/validate/document") => handle_document_validation(req).await,
         (&Method::POST, "/validate/dni") => handle_dni_validation(req),
         (&Method::POST, "/validate/nie") => handle_nie_validation(req),
         (&Method::POST, "/validate/passport") => handle_passport_validation(req),
+        (&Method::POST, "/portraits/purge") => handle_portrait_purge().await,
+        (&Method::GET, "/reviews") => handle_review_list(&req).await,
+        (&Method::POST, path) if path.starts_with("/reviews/") => handle_review_decision(&req, path).await,
         _ => Ok(ResponseBuilder::new(StatusCode::NOT_FOUND)
             .header("content-type", "application/json")
             .body(r#"{"error":"Validation endpoint not found"}"#)
@@ -20,15 +79,154 @@ fn handle_request(req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
     }
 }
 
-fn handle_document_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
-    // TODO: Implement OCR document processing
+/// Liveness for the gateway's `/health`; this component has no dependencies
+/// of its own to report yet.
+fn health() -> Result<Response> {
+    let report =
+        HealthReport::from_dependencies(SERVICE_NAME, env!("CARGO_PKG_VERSION"), vec![]);
     Ok(ResponseBuilder::new(StatusCode::OK)
         .header("content-type", "application/json")
-        .body(r#"{"status":"valid","confidence":0.95,"extracted_data":{}}"#)
+        .body(serde_json::to_string(&report)?)
         .build())
 }
 
-fn handle_dni_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response {
+    ResponseBuilder::new(status)
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .build()
+}
+
+fn error_response(error: AlbergueError) -> Response {
+    let status = match &error {
+        AlbergueError::Validation { .. } => StatusCode::BAD_REQUEST,
+        AlbergueError::Authentication { .. } => StatusCode::UNAUTHORIZED,
+        AlbergueError::Authorization { .. } => StatusCode::FORBIDDEN,
+        AlbergueError::NotFound { .. } => StatusCode::NOT_FOUND,
+        AlbergueError::OCRProcessing { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        AlbergueError::ExternalService { .. } => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    json_response(status, json!({"error": error.to_string()}))
+}
+
+async fn handle_document_validation(req: Request<Vec<u8>>) -> Result<Response> {
+    let request: ValidationRequest = match serde_json::from_slice(req.body()) {
+        Ok(request) => request,
+        Err(e) => return Ok(json_response(StatusCode::BAD_REQUEST, json!({"error": e.to_string()}))),
+    };
+    let response = match validation_service_from_env() {
+        Ok(service) => service.validate_document(request).await,
+        Err(e) => Err(e),
+    };
+    Ok(match response {
+        Ok(response) => json_response(StatusCode::OK, serde_json::to_value(response)?),
+        Err(e) => error_response(e),
+    })
+}
+
+/// Deletes the ID photos past their retention date; run daily by
+/// `task portraits:purge` through the gateway.
+async fn handle_portrait_purge() -> Result<Response> {
+    let purged = match validation_service_from_env() {
+        Ok(service) => service.purge_expired_portraits(Utc::now().date_naive()).await,
+        Err(e) => Err(e),
+    };
+    Ok(match purged {
+        Ok(purged) => json_response(StatusCode::OK, json!({"purged": purged})),
+        Err(e) => error_response(e),
+    })
+}
+
+/// The hospitalero behind a review request, from the gateway's headers.
+fn reviewer(req: &Request<Vec<u8>>) -> AlbergueResult<Reviewer> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let user_id = header(USER_ID_HEADER).ok_or_else(|| AlbergueError::Authentication {
+        message: "Reviews are only reachable through the gateway's admin routes".to_string(),
+    })?;
+    let user_role = header(USER_ROLE_HEADER).unwrap_or_default();
+    if user_role != "admin" {
+        return Err(AlbergueError::Authorization {
+            message: "Only admins review documents".to_string(),
+        });
+    }
+    Ok(Reviewer {
+        user_id,
+        user_role,
+        request_id: header(REQUEST_ID_HEADER),
+    })
+}
+
+fn parse_body<T: DeserializeOwned>(req: &Request<Vec<u8>>) -> AlbergueResult<T> {
+    serde_json::from_slice(req.body()).map_err(|e| AlbergueError::Validation { message: e.to_string() })
+}
+
+/// Reviews waiting on a decision, oldest first.
+async fn handle_review_list(req: &Request<Vec<u8>>) -> Result<Response> {
+    let pending = match reviewer(req) {
+        Ok(_) => match review_service_from_env() {
+            Ok(reviews) => reviews.pending().await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    Ok(match pending {
+        Ok(pending) => json_response(StatusCode::OK, json!({"reviews": pending})),
+        Err(e) => error_response(e),
+    })
+}
+
+/// `POST /reviews/{id}/corrections`, `/approve` or `/reject`.
+async fn handle_review_decision(req: &Request<Vec<u8>>, path: &str) -> Result<Response> {
+    Ok(match decide_review(req, path).await {
+        Ok(body) => json_response(StatusCode::OK, body),
+        Err(e) => error_response(e),
+    })
+}
+
+async fn decide_review(req: &Request<Vec<u8>>, path: &str) -> AlbergueResult<Value> {
+    let not_found = || AlbergueError::NotFound {
+        resource: path.to_string(),
+    };
+    let (id, action) = path.trim_start_matches("/reviews/").split_once('/').ok_or_else(not_found)?;
+    let id = Uuid::parse_str(id).map_err(|_| not_found())?;
+    let reviewer = reviewer(req)?;
+    let reviews = review_service_from_env()?;
+
+    match action {
+        "corrections" => {
+            let request: ReviewCorrectionRequest = parse_body(req)?;
+            Ok(json!(reviews.correct(id, request.corrections, &reviewer).await?))
+        }
+        "approve" => {
+            // Corrections are optional with an approval
+            let request: ReviewCorrectionRequest = match req.body().is_empty() {
+                true => ReviewCorrectionRequest::default(),
+                false => parse_body(req)?,
+            };
+            let approved = reviews.approve(id, request.corrections, &reviewer).await?;
+            Ok(json!({
+                "review": approved.review,
+                "data": approved.data,
+                "golden_case_name": approved.golden_case.name,
+                "golden_case": approved.golden_case,
+            }))
+        }
+        "reject" => {
+            let request: ReviewRejectionRequest = parse_body(req)?;
+            Ok(json!(reviews.reject(id, &request.reason, &reviewer).await?))
+        }
+        _ => Err(not_found()),
+    }
+}
+
+fn handle_dni_validation(_req: Request<Vec<u8>>) -> Result<Response> {
     // TODO: Implement DNI checksum validation
     Ok(ResponseBuilder::new(StatusCode::OK)
         .header("content-type", "application/json")
@@ -36,7 +234,7 @@ fn handle_dni_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
         .build())
 }
 
-fn handle_nie_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+fn handle_nie_validation(_req: Request<Vec<u8>>) -> Result<Response> {
     // TODO: Implement NIE validation
     Ok(ResponseBuilder::new(StatusCode::OK)
         .header("content-type", "application/json")
@@ -44,10 +242,10 @@ fn handle_nie_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
         .build())
 }
 
-fn handle_passport_validation(_req: Request<Vec<u8>>) -> Result<impl IntoResponse> {
+fn handle_passport_validation(_req: Request<Vec<u8>>) -> Result<Response> {
     // TODO: Implement passport MRZ validation
     Ok(ResponseBuilder::new(StatusCode::OK)
         .header("content-type", "application/json")
         .body(r#"{"status":"valid","mrz_valid":true}"#)
         .build())
-}
\ No newline at end of file
+}
@@ -0,0 +1,8 @@
+use crate::domain::review::AuditEntry;
+use shared::AlbergueResult;
+
+/// Where decisions on personal data are recorded, as `audit_log` rows.
+#[async_trait::async_trait(?Send)]
+pub trait AuditLog {
+    async fn record(&self, entry: &AuditEntry) -> AlbergueResult<()>;
+}
@@ -0,0 +1,8 @@
+use crate::domain::barcode::Barcode;
+use shared::AlbergueResult;
+
+#[async_trait::async_trait(?Send)]
+pub trait BarcodeReader {
+    /// Every barcode that could be decoded in the image; none is not an error.
+    async fn read_barcodes(&self, image_data: &[u8]) -> AlbergueResult<Vec<Barcode>>;
+}
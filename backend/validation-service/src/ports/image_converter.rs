@@ -0,0 +1,8 @@
+use crate::domain::upload::UploadFormat;
+use shared::AlbergueResult;
+
+/// Converts images the service cannot decode itself, HEIC above all, to JPEG.
+#[async_trait::async_trait(?Send)]
+pub trait ImageConverter {
+    async fn to_jpeg(&self, data: &[u8], format: UploadFormat) -> AlbergueResult<Vec<u8>>;
+}
@@ -1 +1,6 @@
-pub mod ocr_client;
\ No newline at end of file
+pub mod audit_log;
+pub mod barcode_reader;
+pub mod image_converter;
+pub mod ocr_client;
+pub mod portrait_store;
+pub mod review_store;
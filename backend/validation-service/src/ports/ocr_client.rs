@@ -1,10 +1,9 @@
+use crate::domain::ocr::recognizer::OcrResult;
 use shared::AlbergueResult;
 
 #[async_trait::async_trait(?Send)]
 pub trait OCRClient {
     async fn extract_text(&self, image_data: &[u8]) -> AlbergueResult<String>;
-    async fn extract_text_with_confidence(
-        &self,
-        image_data: &[u8],
-    ) -> AlbergueResult<(String, f32)>;
+    /// Text plus per-word bounding boxes and confidences.
+    async fn extract_text_with_confidence(&self, image_data: &[u8]) -> AlbergueResult<OcrResult>;
 }
@@ -0,0 +1,15 @@
+use crate::domain::portrait::{PortraitKey, SealedPortrait};
+use chrono::NaiveDate;
+use shared::AlbergueResult;
+
+/// Keeps sealed ID photos until their retention date. Stores only ever see
+/// ciphertext; see `PortraitCipher`.
+#[async_trait::async_trait(?Send)]
+pub trait PortraitStore {
+    /// Stores `portrait` under `key`, returning the URL recorded as the
+    /// pilgrim's `id_photo_url`.
+    async fn put(&self, key: &PortraitKey, portrait: &SealedPortrait) -> AlbergueResult<String>;
+    async fn get(&self, key: &PortraitKey) -> AlbergueResult<Option<SealedPortrait>>;
+    /// Deletes every portrait retained until before `today`, returning how many.
+    async fn purge_expired(&self, today: NaiveDate) -> AlbergueResult<usize>;
+}
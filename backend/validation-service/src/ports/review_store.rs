@@ -0,0 +1,12 @@
+use shared::{AlbergueResult, DocumentReview, ReviewStatus};
+use uuid::Uuid;
+
+/// Keeps validations held for a hospitalero, as `document_reviews` rows.
+#[async_trait::async_trait(?Send)]
+pub trait ReviewStore {
+    /// Inserts `review`, or replaces the one with its id.
+    async fn save(&self, review: &DocumentReview) -> AlbergueResult<()>;
+    async fn get(&self, id: Uuid) -> AlbergueResult<Option<DocumentReview>>;
+    /// Reviews in `status`, oldest first.
+    async fn list(&self, status: ReviewStatus) -> AlbergueResult<Vec<DocumentReview>>;
+}
@@ -0,0 +1,209 @@
+mod fixtures;
+
+use fixtures::*;
+use qrcode::{EcLevel, QrCode, Version};
+use shared::{AlbergueResult, BarcodeSymbology, DocumentType, ValidationRequest, ValidationWarningCode};
+use validation_service::adapters::rust_barcode::RustBarcodeReader;
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::barcode::{parse_payload, Barcode, QrDecoder};
+use validation_service::ports::barcode_reader::BarcodeReader;
+
+const DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 15/06/1990\nVÁLIDO HASTA: 10/01/2030";
+
+const DNI_QR: &str = "DNI:12345678Z;NOMBRE:JUAN CARLOS;APELLIDOS:GARCIA MARTINEZ;FECHA NACIMIENTO:15/06/1990;CADUCIDAD:10/01/2030;SEXO:M";
+
+struct FixedBarcodes(Vec<(BarcodeSymbology, &'static str)>);
+
+#[async_trait::async_trait(?Send)]
+impl BarcodeReader for FixedBarcodes {
+    async fn read_barcodes(&self, _image_data: &[u8]) -> AlbergueResult<Vec<Barcode>> {
+        Ok(self
+            .0
+            .iter()
+            .map(|(symbology, text)| Barcode {
+                symbology: *symbology,
+                text: text.to_string(),
+            })
+            .collect())
+    }
+}
+
+fn modules(text: &str, version: i16, level: EcLevel) -> Vec<Vec<bool>> {
+    qr_modules(&QrCode::with_version(text, Version::Normal(version), level).unwrap())
+}
+
+/// An ID-1 card with `lines` of text and a QR code of `text` on the right.
+fn card_with_qr(lines: &[(&str, bool)], text: &str) -> image::GrayImage {
+    let mut card = id_card(lines);
+    let code = qr_modules(&QrCode::with_error_correction_level(text, EcLevel::M).unwrap());
+    let scale = 220 / code.len() as u32;
+    draw_modules(&mut card, &code, 856 - 40 - code.len() as u32 * scale, 540 - 40 - code.len() as u32 * scale, scale);
+    card
+}
+
+async fn validate(service: ValidationService, front: String) -> shared::ValidationResponse {
+    service
+        .validate_document(ValidationRequest {
+            document_type: Some(DocumentType::DNI),
+            front_image: front,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod barcode_tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_every_version_and_level() {
+        let decoder = QrDecoder::new();
+        for version in [1, 2, 5, 7, 10, 14, 27, 40] {
+            for level in [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H] {
+                let code = modules("12345678Z", version, level);
+
+                assert_eq!(
+                    decoder.decode_modules(&code).as_deref(),
+                    Some("12345678Z"),
+                    "version {} {:?}",
+                    version,
+                    level
+                );
+                assert_eq!(decoder.decode(&qr_image(&code, 3)), vec!["12345678Z".to_string()]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reads_numeric_alphanumeric_and_byte_segments() {
+        let decoder = QrDecoder::new();
+        for text in ["0123456789012345", "HTTPS://SEDE.EXAMPLE/V/AB12", "Dirección: C/ Mayor 12", "X1234567L 00 é"] {
+            let code = qr_modules(&QrCode::new(text).unwrap());
+            assert_eq!(decoder.decode_modules(&code).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn test_corrects_damaged_modules() {
+        let decoder = QrDecoder::new();
+        let mut code = modules(DNI_QR, 10, EcLevel::H);
+
+        // A scratch across the data area
+        for row in &mut code[30..32] {
+            for module in &mut row[12..40] {
+                *module = !*module;
+            }
+        }
+        assert_eq!(decoder.decode_modules(&code).as_deref(), Some(DNI_QR));
+
+        // Far more than level H can take
+        for (row, line) in code.iter_mut().enumerate().take(45).skip(12) {
+            for (col, module) in line.iter_mut().enumerate().take(45).skip(12) {
+                *module = (row * col) % 3 == 0;
+            }
+        }
+        assert_eq!(decoder.decode_modules(&code), None);
+    }
+
+    #[test]
+    fn test_rejects_grids_past_version_40() {
+        let decoder = QrDecoder::new();
+        let mut code = modules(DNI_QR, 40, EcLevel::L);
+        assert_eq!(decoder.decode_modules(&code).as_deref(), Some(DNI_QR));
+
+        // 181 modules square would be version 41
+        for row in &mut code {
+            row.extend([false; 4]);
+        }
+        code.extend(vec![vec![false; 181]; 4]);
+        assert_eq!(decoder.decode_modules(&code), None);
+    }
+
+    #[test]
+    fn test_reads_qr_on_photographed_card() {
+        let card = card_with_qr(&[("DOCUMENTO NACIONAL DE IDENTIDAD", false), ("DNI 12345678Z", false)], DNI_QR);
+        let photo = photograph(&card, PHOTO_CORNERS, 1000, 750);
+
+        assert_eq!(QrDecoder::new().decode(&photo), vec![DNI_QR.to_string()]);
+        assert!(QrDecoder::new().decode(&id_card(&[("DNI 12345678Z", false)])).is_empty());
+    }
+
+    #[test]
+    fn test_parses_key_value_and_json_payloads() {
+        let data = parse_payload(DNI_QR).unwrap();
+        assert_eq!(data.document_number.as_deref(), Some("12345678Z"));
+        assert_eq!(data.surname.as_deref(), Some("GARCIA MARTINEZ"));
+        assert_eq!(data.birth_date.unwrap().date_naive().to_string(), "1990-06-15");
+        assert_eq!(data.sex.as_deref(), Some("M"));
+
+        let data = parse_payload(
+            r#"{"documentNumber": "x1234567-l", "person": {"givenNames": "Maria", "surname": "Dupont", "dateOfBirth": "1985-03-22", "sex": "female"}}"#,
+        )
+        .unwrap();
+        assert_eq!(data.document_number.as_deref(), Some("X1234567L"));
+        assert_eq!(data.name.as_deref(), Some("Maria"));
+        assert_eq!(data.birth_date.unwrap().date_naive().to_string(), "1985-03-22");
+        assert_eq!(data.sex.as_deref(), Some("F"));
+
+        // A verification link carries no fields
+        assert!(parse_payload("https://sede.example/verify?id=AB12").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_barcode_overrides_ocr_and_warns() {
+        let misread = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTlNEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 16/06/1990";
+        let service = ValidationService::new()
+            .with_ocr_client(Box::new(FixedTextOcr(misread)))
+            .with_barcode_reader(Box::new(FixedBarcodes(vec![(BarcodeSymbology::Qr, DNI_QR)])));
+        let response = validate(service, BLANK_PNG.to_string()).await;
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert_eq!(response.extracted_data.surname.as_deref(), Some("GARCIA MARTINEZ"));
+        assert_eq!(response.extracted_data.birth_date.unwrap().date_naive().to_string(), "1990-06-15");
+        assert_eq!(response.barcodes.len(), 1);
+        assert!(response.barcodes[0].fields.contains(&"expiry_date".to_string()));
+
+        let mismatched: Vec<&str> = response
+            .warnings
+            .iter()
+            .filter(|w| w.code == ValidationWarningCode::BarcodeMismatch)
+            .filter_map(|w| w.field.as_deref())
+            .collect();
+        assert_eq!(mismatched, vec!["surname", "birth_date"]);
+        assert!(response.field_confidence["birth_date"] >= 0.95);
+        assert!(!response.requires_manual_review, "{:?}", response.fields_to_review);
+    }
+
+    #[tokio::test]
+    async fn test_barcode_without_identity_fields_is_ignored() {
+        let service = ValidationService::new()
+            .with_ocr_client(Box::new(FixedTextOcr(DNI_FRONT)))
+            .with_barcode_reader(Box::new(FixedBarcodes(vec![(
+                BarcodeSymbology::Qr,
+                "https://sede.example/verify?id=AB12",
+            )])));
+        let response = validate(service, BLANK_PNG.to_string()).await;
+
+        assert!(response.barcodes.is_empty());
+        assert!(response.warnings.is_empty(), "{:?}", response.warnings);
+    }
+
+    #[tokio::test]
+    async fn test_reads_qr_from_uploaded_image() {
+        let card = card_with_qr(&[("DNI 12345678Z", false)], DNI_QR);
+        let front = base64::encode(png(&photograph(&card, PHOTO_CORNERS, 1000, 750)));
+        let found = RustBarcodeReader::new()
+            .read_barcodes(&base64::decode(&front).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].symbology, BarcodeSymbology::Qr);
+
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr("DNI 12345678Z")));
+        let response = validate(service, front).await;
+
+        assert_eq!(response.extracted_data.name.as_deref(), Some("JUAN CARLOS"));
+        assert!(response.is_valid, "{:?}", response.errors);
+    }
+}
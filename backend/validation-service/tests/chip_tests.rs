@@ -0,0 +1,290 @@
+mod fixtures;
+
+use fixtures::chip::*;
+use fixtures::{BLANK_PNG, FixedTextOcr};
+use sha2::{Digest, Sha256};
+use shared::{ChipDump, DocumentType, FaceImageFormat, ValidationRequest, ValidationResponse, ValidationWarningCode};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::chip::{
+    lds, tlv, Certificate, ChipReader, CscaList, HashAlgorithm, LdsFiles, SecurityObject, SignatureCheck,
+};
+use validation_service::domain::validators::mrz_validator::MrzFormat;
+
+/// The printed front of the DNI on the chip, misread in the second surname
+/// and the birth date.
+const MISREAD_FRONT: &str = "DNI 99999999R\nAPELLIDOS: ESPAÑOLA ESPANOIA\nNOMBRE: CARMEN\nFECHA DE NACIMIENTO: 07/01/1980\nVÁLIDO HASTA: 01/01/2031";
+
+const FULL_NAME: u32 = 0x5F0E;
+const PERSONAL_NUMBER: u32 = 0x5F10;
+const BIRTHPLACE: u32 = 0x5F11;
+const ADDRESS: u32 = 0x5F42;
+
+/// A DNIe dump: EF.COM, DG1, DG2 and DG11, with or without its signed SOD.
+fn dnie_files(with_sod: bool) -> LdsFiles {
+    let dg1 = dg1(&DNI_MRZ);
+    let dg2 = dg2(&jpeg(400), 240, 320);
+    let dg11 = dg11(&[
+        (FULL_NAME, "ESPAÑOLA<ESPAÑOLA<<CARMEN"),
+        (PERSONAL_NUMBER, "99999999R"),
+        (BIRTHPLACE, "MADRID<MADRID"),
+        (ADDRESS, "C. MAYOR 12<ALCALA DE HENARES<MADRID"),
+    ]);
+    let sod = signed_sod(
+        HashAlgorithm::Sha256,
+        &[(1, &dg1), (2, &dg2), (11, &dg11)],
+        &document_signer_certificate(VALID_UNTIL),
+    );
+
+    LdsFiles {
+        ef_com: Some(ef_com(&[1, 2, 11])),
+        dg1,
+        dg2: Some(dg2),
+        dg11: Some(dg11),
+        sod: with_sod.then_some(sod),
+    }
+}
+
+fn dump(files: &LdsFiles) -> ChipDump {
+    let encode = |file: &Option<Vec<u8>>| file.as_ref().map(base64::encode);
+    ChipDump {
+        ef_com: encode(&files.ef_com),
+        dg1: base64::encode(&files.dg1),
+        dg2: encode(&files.dg2),
+        dg11: encode(&files.dg11),
+        sod: encode(&files.sod),
+    }
+}
+
+fn trusted() -> CscaList {
+    CscaList::from_pem(&pem(&csca_certificate())).unwrap()
+}
+
+async fn validate(files: &LdsFiles, cscas: CscaList) -> ValidationResponse {
+    ValidationService::new()
+        .with_ocr_client(Box::new(FixedTextOcr(MISREAD_FRONT)))
+        .with_cscas(cscas)
+        .validate_document(ValidationRequest {
+            document_type: Some(DocumentType::DNI),
+            front_image: BLANK_PNG.to_string(),
+            chip_data: Some(dump(files)),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+fn warned(response: &ValidationResponse, code: ValidationWarningCode) -> Vec<&str> {
+    response
+        .warnings
+        .iter()
+        .filter(|w| w.code == code)
+        .filter_map(|w| w.field.as_deref())
+        .collect()
+}
+
+#[cfg(test)]
+mod chip_tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_tags_and_lengths() {
+        let long = tlv(0x5F2E, &[7; 300]);
+        let padded = [long.as_slice(), &[0xFF]].concat();
+        let (object, rest) = tlv::parse_one(&padded).unwrap();
+        assert_eq!(object.tag, 0x5F2E);
+        assert_eq!(object.value.len(), 300);
+        assert_eq!(object.raw, long.as_slice());
+        assert_eq!(rest, &[0xFF]);
+
+        // Indefinite length, ended by two zero bytes
+        let indefinite = [&[0x30, 0x80][..], &tlv(0x02, &[5]), &tlv(0x04, b"ab"), &[0, 0]].concat();
+        let (sequence, rest) = tlv::parse_one(&indefinite).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(sequence.children().unwrap().len(), 2);
+        assert_eq!(sequence.expect_child(0x04).unwrap().value, b"ab");
+
+        assert_eq!(tlv::object_identifier(&[0x67, 0x81, 0x08, 0x01, 0x01, 0x01]).as_deref(), Some("2.23.136.1.1.1"));
+        assert!(tlv::parse_one(&[0x61, 0x05, 0x01]).is_err());
+        assert!(tlv::parse_one(&[0x5F]).is_err());
+    }
+
+    #[test]
+    fn test_reads_lds_files() {
+        let files = dnie_files(false);
+
+        assert_eq!(lds::read_ef_com(files.ef_com.as_ref().unwrap()).unwrap(), vec![1, 2, 11]);
+
+        let (format, lines) = lds::read_dg1(&files.dg1).unwrap();
+        assert_eq!(format, MrzFormat::TD1);
+        assert_eq!(lines, DNI_MRZ);
+
+        let face = lds::read_dg2(files.dg2.as_ref().unwrap()).unwrap();
+        assert_eq!(face.format, FaceImageFormat::Jpeg);
+        assert_eq!((face.width, face.height), (240, 320));
+        assert_eq!(face.data, jpeg(400));
+        assert!(!format!("{:?}", face).contains("90"), "face bytes must not be logged");
+
+        let holder = lds::read_dg11(files.dg11.as_ref().unwrap()).unwrap();
+        assert_eq!(holder.surname.as_deref(), Some("ESPAÑOLA ESPAÑOLA"));
+        assert_eq!(holder.name.as_deref(), Some("CARMEN"));
+        assert_eq!(holder.document_number.as_deref(), Some("99999999R"));
+        assert_eq!(holder.birthplace.as_deref(), Some("MADRID, MADRID"));
+        assert_eq!(holder.address.as_deref(), Some("C. MAYOR 12"));
+        assert_eq!(holder.municipality.as_deref(), Some("ALCALA DE HENARES"));
+        assert_eq!(holder.province.as_deref(), Some("MADRID"));
+
+        assert!(lds::read_dg1(&tlv(0x61, &tlv(0x5F1F, b"I<ESP"))).is_err());
+        assert!(lds::read_dg2(&dg1(&DNI_MRZ)).is_err());
+    }
+
+    #[test]
+    fn test_verifies_hashes_against_sod() {
+        let files = dnie_files(true);
+        let sod = SecurityObject::parse(files.sod.as_ref().unwrap()).unwrap();
+        assert_eq!(sod.algorithm, HashAlgorithm::Sha256);
+        assert_eq!(sod.hashes.keys().copied().collect::<Vec<_>>(), vec![1, 2, 11]);
+        assert!(sod.matches(1, &files.dg1));
+
+        let reader = ChipReader::new().with_cscas(trusted());
+        let chip = reader.read(&files).unwrap();
+        assert!(chip.is_verified());
+        assert_eq!(chip.matching_groups, vec![1, 2, 11]);
+        assert_eq!(chip.verified_groups(), vec![1, 2, 11]);
+        assert_eq!(chip.mrz.optional_data, "99999999R");
+
+        // Padding after a file is not part of it
+        let mut padded = files.clone();
+        padded.dg1.extend([0xFF; 4]);
+        assert!(reader.read(&padded).unwrap().is_verified());
+
+        // A changed birth date in DG1
+        let mut altered = files.clone();
+        altered.dg1 = dg1(&[DNI_MRZ[0], "8001024F3101012ESP<<<<<<<<<<<5", DNI_MRZ[2]]);
+        let chip = reader.read(&altered).unwrap();
+        assert!(!chip.is_verified());
+        assert_eq!(chip.altered_groups, vec![1]);
+
+        // SHA-1, and a SOD that does not list DG11
+        let mut sha1 = files.clone();
+        sha1.sod = Some(fixtures::chip::sod(HashAlgorithm::Sha1, &[(1, &files.dg1), (2, files.dg2.as_ref().unwrap())]));
+        let chip = reader.read(&sha1).unwrap();
+        assert_eq!(chip.matching_groups, vec![1, 2]);
+        assert_eq!(chip.altered_groups, vec![11]);
+    }
+
+    #[test]
+    fn test_traces_sod_signature_to_csca() {
+        let files = dnie_files(true);
+        let check = |files: &LdsFiles, cscas: CscaList| ChipReader::new().with_cscas(cscas).read(files).unwrap().signature;
+        let untrusted = |files: &LdsFiles, cscas: CscaList| match check(files, cscas) {
+            SignatureCheck::Untrusted(reason) => reason,
+            other => panic!("{:?}", other),
+        };
+
+        assert_eq!(check(&files, trusted()), SignatureCheck::Trusted);
+        assert_eq!(check(&dnie_files(false), trusted()), SignatureCheck::Missing);
+
+        // Matching hashes alone are not enough
+        let chip = ChipReader::new().read(&files).unwrap();
+        assert_eq!(chip.matching_groups, vec![1, 2, 11]);
+        assert!(!chip.is_verified());
+        assert!(chip.verified_groups().is_empty());
+        assert!(untrusted(&files, CscaList::default()).contains("no CSCA"));
+
+        // A CSCA of the same name, but not the one that signed
+        let mut impostor = CscaList::default();
+        impostor.add(Certificate::parse(&impostor_csca_certificate()).unwrap());
+        assert!(untrusted(&files, impostor).contains("no trusted CSCA"));
+
+        let mut unsigned = files.clone();
+        unsigned.sod = Some(fixtures::chip::sod(HashAlgorithm::Sha256, &[(1, &files.dg1)]));
+        assert!(untrusted(&unsigned, trusted()).contains("not signed"));
+
+        // A hash changed after signing, and a changed signature
+        let sod = files.sod.clone().unwrap();
+        let hash = Sha256::digest(&files.dg1);
+        let at = sod.windows(hash.len()).position(|w| w == &hash[..]).unwrap();
+        let mut forged = files.clone();
+        forged.sod = Some(sod.clone());
+        forged.sod.as_mut().unwrap()[at] ^= 0x01;
+        assert!(untrusted(&forged, trusted()).contains("signed digest does not match"));
+        let last = sod.len() - 1;
+        forged.sod = Some(sod);
+        forged.sod.as_mut().unwrap()[last] ^= 0x01;
+        assert!(untrusted(&forged, trusted()).contains("signature does not match"));
+
+        let mut expired = files.clone();
+        expired.sod = Some(signed_sod(
+            HashAlgorithm::Sha256,
+            &[(1, &files.dg1)],
+            &document_signer_certificate("250101000000Z"),
+        ));
+        assert!(untrusted(&expired, trusted()).contains("valid from 2020-01-01 to 2025-01-01"));
+
+        let csca = Certificate::parse(&csca_certificate()).unwrap();
+        assert_eq!(csca.subject, csca.issuer);
+        assert!(csca.is_issued_by(&csca));
+        assert_eq!(csca.not_after.to_string(), "2040-01-01");
+        assert!(CscaList::from_pem("-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chip_overrides_ocr() {
+        let response = validate(&dnie_files(true), trusted()).await;
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        let data = &response.extracted_data;
+        assert_eq!(data.document_number.as_deref(), Some("99999999R"));
+        assert_eq!(data.surname.as_deref(), Some("ESPAÑOLA ESPAÑOLA"));
+        assert_eq!(data.birth_date.unwrap().date_naive().to_string(), "1980-01-01");
+        assert_eq!(data.support_number.as_deref(), Some("BAA000589"));
+        assert_eq!(data.municipality.as_deref(), Some("ALCALA DE HENARES"));
+
+        let chip = response.chip.as_ref().unwrap();
+        assert_eq!(chip.verified_groups, vec![1, 2, 11]);
+        assert!(chip.fields.contains(&"address".to_string()));
+        assert_eq!(chip.face_image.unwrap().format, FaceImageFormat::Jpeg);
+
+        assert!(warned(&response, ValidationWarningCode::MrzMismatch).contains(&"birth_date"));
+        assert!(warned(&response, ValidationWarningCode::ChipUnverified).is_empty());
+        assert!(response.field_confidence["birth_date"] >= 0.95);
+        assert!(response.field_confidence["address"] >= 0.95);
+    }
+
+    #[tokio::test]
+    async fn test_unverified_chip_is_only_compared() {
+        for (files, cscas, reason) in [
+            (dnie_files(false), trusted(), "No security object"),
+            (dnie_files(true), CscaList::default(), "no CSCA certificates are configured"),
+        ] {
+            let response = validate(&files, cscas).await;
+
+            assert_eq!(response.extracted_data.surname.as_deref(), Some("ESPAÑOLA ESPANOIA"));
+            assert!(response.extracted_data.address.is_none());
+            let chip = response.chip.as_ref().unwrap();
+            assert!(chip.verified_groups.is_empty());
+            assert!(chip.fields.is_empty());
+
+            assert!(warned(&response, ValidationWarningCode::ChipMismatch).contains(&"surname"));
+            let unverified = response
+                .warnings
+                .iter()
+                .find(|w| w.code == ValidationWarningCode::ChipUnverified)
+                .unwrap();
+            assert!(unverified.message.contains(reason), "{}", unverified.message);
+            assert!(response.field_confidence["surname"] < 0.95);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_altered_chip_is_not_trusted() {
+        let mut files = dnie_files(true);
+        files.dg11 = Some(dg11(&[(FULL_NAME, "OTRA<PERSONA<<ANA"), (PERSONAL_NUMBER, "99999999R")]));
+        let response = validate(&files, trusted()).await;
+
+        assert!(!response.is_valid);
+        assert!(response.errors.iter().any(|e| e.contains("DG11")), "{:?}", response.errors);
+        assert_eq!(response.extracted_data.surname.as_deref(), Some("ESPAÑOLA ESPANOIA"));
+        assert!(response.chip.as_ref().unwrap().fields.is_empty());
+    }
+}
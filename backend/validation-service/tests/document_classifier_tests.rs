@@ -0,0 +1,122 @@
+mod fixtures;
+
+use fixtures::{BLANK_PNG, FixedTextOcr};
+use shared::{DocumentType, ValidationRequest};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::ocr::image_processor::Layout;
+use validation_service::domain::ocr::DocumentClassifier;
+
+const DNI_BACK: &str = "IDESPBAA000589599999999R<<<<<<\n8001014F3101012ESP<<<<<<<<<<<5\nESPANOLA<ESPANOLA<<CARMEN<<<<<";
+
+const TIE: &str = "TARJETA DE IDENTIDAD DE EXTRANJERO\nIRESPE123456782X1234567L<<<<<<\n8503220F3003220FRA<<<<<<<<<<<0\nDUPONT<BERNARD<<MARIA<<<<<<<<<";
+
+const FRENCH_ID: &str = "IDFRAX4RTBPFW46<<<<<<<<<<<<<<<\n9001011M3001019FRA<<<<<<<<<<<4\nMARTIN<<PIERRE<<<<<<<<<<<<<<<<";
+
+const PASSPORT: &str = "PASAPORTE\nP<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\nL898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+fn request(document_type: Option<DocumentType>) -> ValidationRequest {
+    ValidationRequest {
+        document_type,
+        front_image: BLANK_PNG.to_string(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod document_classifier_tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_by_mrz_document_code() {
+        let classifier = DocumentClassifier::new();
+
+        let dni = classifier.classify(DNI_BACK, Layout::Id1Card).unwrap();
+        assert_eq!(dni.document_type, DocumentType::DNI);
+        assert!(dni.confidence >= 0.9);
+        assert_eq!(dni.issuing_country.as_deref(), Some("ESP"));
+
+        let tie = classifier.classify(TIE, Layout::Id1Card).unwrap();
+        assert_eq!(tie.document_type, DocumentType::TIE);
+        assert!(tie.confidence >= 0.9);
+
+        let passport = classifier.classify(PASSPORT, Layout::Unknown).unwrap();
+        assert_eq!(passport.document_type, DocumentType::Passport);
+        assert!(passport.confidence >= 0.9);
+    }
+
+    #[test]
+    fn test_foreign_identity_card_is_read_through_its_mrz() {
+        let classification = DocumentClassifier::new().classify(FRENCH_ID, Layout::Id1Card).unwrap();
+
+        assert_eq!(classification.document_type, DocumentType::EuIdCard);
+        assert_eq!(classification.issuing_country.as_deref(), Some("FRA"));
+        assert!(classification.evidence[0].contains("EU identity card"));
+    }
+
+    #[test]
+    fn test_keywords_alone_are_less_certain() {
+        let classification = DocumentClassifier::new()
+            .classify("DOCUMENTO NACIONAL DE IDENTIDAD\nNOMBRE: JUAN", Layout::Unknown)
+            .unwrap();
+
+        assert_eq!(classification.document_type, DocumentType::DNI);
+        assert!(classification.confidence < 0.9);
+        assert_eq!(classification.issuing_country, None);
+    }
+
+    #[test]
+    fn test_no_classification_without_signals() {
+        let classifier = DocumentClassifier::new();
+
+        assert_eq!(classifier.classify("NOMBRE: JUAN", Layout::Unknown), None);
+        // A card could be a DNI or a TIE
+        assert_eq!(classifier.classify("", Layout::Id1Card), None);
+    }
+
+    #[tokio::test]
+    async fn test_overrides_type_chosen_by_mistake() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(TIE)));
+        let response = service.validate_document(request(Some(DocumentType::DNI))).await.unwrap();
+        let classification = response.classification.unwrap();
+
+        assert_eq!(classification.detected_type, DocumentType::TIE);
+        assert_eq!(classification.requested_type, Some(DocumentType::DNI));
+        assert!(classification.overridden);
+        assert!(classification.warning.is_some());
+        // Read as a TIE: the NIE from the MRZ, not the card number
+        assert_eq!(response.extracted_data.document_number.as_deref(), Some("X1234567L"));
+        assert!(response.is_valid, "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_warns_without_overriding_on_weak_evidence() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(
+            "DNI\n12345678Z\nNOMBRE: JUAN\nAPELLIDOS: GARCIA",
+        )));
+        let response = service.validate_document(request(Some(DocumentType::Passport))).await.unwrap();
+        let classification = response.classification.unwrap();
+
+        assert_eq!(classification.detected_type, DocumentType::DNI);
+        assert!(!classification.overridden);
+        assert!(classification.warning.unwrap().contains("validated as Passport"));
+    }
+
+    #[tokio::test]
+    async fn test_detects_type_when_none_was_chosen() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(PASSPORT)));
+        let response = service.validate_document(request(None)).await.unwrap();
+        let classification = response.classification.unwrap();
+
+        assert_eq!(classification.detected_type, DocumentType::Passport);
+        assert!(!classification.overridden && classification.warning.is_none());
+        assert_eq!(response.extracted_data.document_number.as_deref(), Some("L898902C3"));
+    }
+
+    #[tokio::test]
+    async fn test_asks_for_type_when_it_cannot_tell() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr("NOMBRE: JUAN")));
+        let error = service.validate_document(request(None)).await.unwrap_err();
+
+        assert!(error.to_string().contains("please choose it"));
+    }
+}
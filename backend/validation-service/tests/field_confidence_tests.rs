@@ -0,0 +1,149 @@
+mod fixtures;
+
+use fixtures::BLANK_PNG;
+use shared::{AlbergueResult, DocumentType, ValidationRequest, ValidationResponse};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::ocr::confidence_scorer::CheckOutcome;
+use validation_service::domain::ocr::recognizer::{OcrResult, OcrWord};
+use validation_service::domain::ocr::segmentation::BoundingBox;
+use validation_service::domain::ocr::ConfidenceScorer;
+use validation_service::ports::ocr_client::OCRClient;
+
+const TD3: [&str; 2] = [
+    "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<",
+    "L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+];
+
+/// Reports each line's words with the confidence given for the line; lines
+/// with fillers are MRZ.
+struct ScoredLinesOcr(Vec<(&'static str, f32)>);
+
+#[async_trait::async_trait(?Send)]
+impl OCRClient for ScoredLinesOcr {
+    async fn extract_text(&self, image_data: &[u8]) -> AlbergueResult<String> {
+        Ok(self.extract_text_with_confidence(image_data).await?.text)
+    }
+
+    async fn extract_text_with_confidence(&self, _image_data: &[u8]) -> AlbergueResult<OcrResult> {
+        let words: Vec<OcrWord> = self
+            .0
+            .iter()
+            .enumerate()
+            .flat_map(|(line, (text, confidence))| {
+                text.split(' ').map(move |word| OcrWord {
+                    text: word.to_string(),
+                    bbox: BoundingBox {
+                        x: 0,
+                        y: line as u32 * 30,
+                        width: 0,
+                        height: 24,
+                    },
+                    confidence: *confidence,
+                    line,
+                    mrz: text.contains('<'),
+                })
+            })
+            .collect();
+        Ok(OcrResult {
+            text: self.0.iter().map(|(text, _)| *text).collect::<Vec<_>>().join("\n"),
+            confidence: 1.0,
+            words,
+        })
+    }
+}
+
+async fn validate(lines: Vec<(&'static str, f32)>, document_type: DocumentType) -> ValidationResponse {
+    ValidationService::new()
+        .with_ocr_client(Box::new(ScoredLinesOcr(lines)))
+        .validate_document(ValidationRequest {
+            document_type: Some(document_type),
+            front_image: BLANK_PNG.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod field_confidence_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_smudged_word_is_flagged_for_review() {
+        let response = validate(
+            vec![
+                ("DNI 12345678Z", 0.9),
+                ("NOMBRE: JUAN GARCIA", 0.9),
+                ("FECHA DE NACIMIENTO: 15/06/1990", 0.15),
+                ("VÁLIDO HASTA: 10/01/2030", 0.9),
+            ],
+            DocumentType::DNI,
+        )
+        .await;
+        let confidence = &response.field_confidence;
+
+        assert!(confidence["name"] > 0.8, "{:?}", confidence);
+        assert!(confidence["birth_date"] < 0.6, "{:?}", confidence);
+        assert_eq!(response.fields_to_review, vec!["birth_date".to_string()]);
+        assert!(response.requires_manual_review);
+    }
+
+    #[tokio::test]
+    async fn test_control_letter_vouches_for_a_faint_number() {
+        let response = validate(
+            vec![("DNI 12345678Z", 0.3), ("NOMBRE: JUAN GARCIA", 0.9), ("FECHA DE NACIMIENTO: 15/06/1990", 0.9)],
+            DocumentType::DNI,
+        )
+        .await;
+
+        assert!(response.field_confidence["document_number"] >= 0.95);
+        assert!(!response.requires_manual_review, "{:?}", response.fields_to_review);
+    }
+
+    #[tokio::test]
+    async fn test_mrz_check_digits_settle_field_confidence() {
+        let response = validate(vec![("PASAPORTE", 0.9), (TD3[0], 0.5), (TD3[1], 0.5)], DocumentType::Passport).await;
+        let confidence = &response.field_confidence;
+
+        for field in ["document_number", "birth_date", "expiry_date"] {
+            assert!(confidence[field] >= 0.95, "{}: {:?}", field, confidence);
+        }
+        // Names have no check digit, so the faint MRZ shows through
+        assert!(confidence["surname"] < 0.8, "{:?}", confidence);
+    }
+
+    #[tokio::test]
+    async fn test_failed_check_digit_caps_confidence() {
+        let bad_birth_check = "L898902C36UTO7408123F1204159ZE184226B<<<<<10";
+        let response =
+            validate(vec![("PASAPORTE", 0.9), (TD3[0], 0.9), (bad_birth_check, 0.9)], DocumentType::Passport).await;
+
+        assert!(response.field_confidence["birth_date"] <= 0.2);
+        assert!(response.fields_to_review.contains(&"birth_date".to_string()));
+        assert!(response.requires_manual_review);
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_fields_need_review() {
+        let response = validate(vec![("DNI 12345678Z", 0.9)], DocumentType::DNI).await;
+
+        assert!(!response.field_confidence.contains_key("surname"));
+        for field in ["name", "surname", "birth_date"] {
+            assert!(response.fields_to_review.contains(&field.to_string()), "{:?}", response.fields_to_review);
+        }
+        assert!(response.requires_manual_review);
+        assert!(response.confidence_score < 0.5);
+    }
+
+    #[test]
+    fn test_combines_format_ocr_and_checks() {
+        let scorer = ConfidenceScorer::new();
+
+        assert_eq!(scorer.combine_field_confidence(0.9, None, CheckOutcome::Unchecked, false), 0.9);
+        let blended = scorer.combine_field_confidence(0.9, Some(0.4), CheckOutcome::Unchecked, false);
+        assert!((blended - 0.6).abs() < 1e-6);
+        assert_eq!(scorer.combine_field_confidence(0.3, Some(0.3), CheckOutcome::Passed, false), 0.95);
+        assert_eq!(scorer.combine_field_confidence(0.9, Some(0.9), CheckOutcome::Failed, false), 0.2);
+        assert_eq!(scorer.combine_field_confidence(0.9, Some(0.9), CheckOutcome::Passed, true), 0.6);
+    }
+}
@@ -0,0 +1,244 @@
+//! Synthetic LDS dumps, encoded the way a DNIe or ePassport chip stores them,
+//! and a test PKI to sign their security objects: an RSA CSCA and the P-256
+//! document signer it certified.
+
+use p256::ecdsa::signature::hazmat::PrehashSigner;
+use p256::pkcs8::EncodePublicKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use validation_service::domain::chip::HashAlgorithm;
+
+/// DNI 3.0 back MRZ: IDESP BAA000589, DNI 99999999R, born 1980-01-01.
+pub const DNI_MRZ: [&str; 3] = [
+    "IDESPBAA000589599999999R<<<<<<",
+    "8001014F3101012ESP<<<<<<<<<<<5",
+    "ESPANOLA<ESPANOLA<<CARMEN<<<<<",
+];
+
+const SIGNED_DATA_OID: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+const LDS_SECURITY_OBJECT_OID: [u8; 6] = [0x67, 0x81, 0x08, 0x01, 0x01, 0x01];
+const SHA1_OID: [u8; 5] = [0x2B, 0x0E, 0x03, 0x02, 0x1A];
+const SHA256_OID: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const CONTENT_TYPE_OID: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x03];
+const MESSAGE_DIGEST_OID: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04];
+const SHA256_WITH_RSA_OID: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0B];
+const ECDSA_WITH_SHA256_OID: [u8; 8] = [0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02];
+const COMMON_NAME_OID: [u8; 3] = [0x55, 0x04, 0x03];
+
+pub const CSCA_NAME: &str = "CSCA SPAIN TEST";
+/// Validity of the test certificates, as `UTCTime`s.
+pub const VALID_FROM: &str = "200101000000Z";
+pub const VALID_UNTIL: &str = "400101000000Z";
+
+/// `tag`, then the DER length of `value`, then `value`.
+pub fn tlv(tag: u32, value: &[u8]) -> Vec<u8> {
+    let mut encoded: Vec<u8> = tag.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    match value.len() {
+        length @ 0..=0x7F => encoded.push(length as u8),
+        length @ 0x80..=0xFF => encoded.extend([0x81, length as u8]),
+        length => encoded.extend([0x82, (length >> 8) as u8, length as u8]),
+    }
+    encoded.extend(value);
+    encoded
+}
+
+pub fn ef_com(groups: &[u8]) -> Vec<u8> {
+    let tags: Vec<u8> = groups
+        .iter()
+        .map(|g| [0x61, 0x75, 0x63, 0x76, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70][*g as usize - 1])
+        .collect();
+    tlv(0x60, &[tlv(0x5F01, b"0107"), tlv(0x5F36, b"040000"), tlv(0x5C, &tags)].concat())
+}
+
+pub fn dg1(mrz: &[&str]) -> Vec<u8> {
+    tlv(0x61, &tlv(0x5F1F, mrz.concat().as_bytes()))
+}
+
+/// Stand-in JPEG bytes: start and end markers around `length` of filler.
+pub fn jpeg(length: usize) -> Vec<u8> {
+    [&[0xFF, 0xD8, 0xFF, 0xE0][..], &vec![0x5A; length], &[0xFF, 0xD9]].concat()
+}
+
+/// A DG2 holding one ISO/IEC 19794-5 facial record around `image`.
+pub fn dg2(image: &[u8], width: u16, height: u16) -> Vec<u8> {
+    let mut face = vec![];
+    face.extend((20 + 12 + image.len() as u32).to_be_bytes());
+    face.extend([0, 0]); // no feature points
+    face.extend([0; 14]);
+    face.extend([0x01, 0x00]); // full frontal, JPEG
+    face.extend(width.to_be_bytes());
+    face.extend(height.to_be_bytes());
+    face.extend([0x01, 0x02, 0, 0, 0, 0]);
+    face.extend(image);
+
+    let mut record = [&b"FAC\0"[..], b"010\0"].concat();
+    record.extend((14 + face.len() as u32).to_be_bytes());
+    record.extend(1u16.to_be_bytes());
+    record.extend(face);
+
+    let template = [tlv(0xA1, &tlv(0x80, &[1, 1])), tlv(0x5F2E, &record)].concat();
+    tlv(0x75, &tlv(0x7F61, &[tlv(0x02, &[1]), tlv(0x7F60, &template)].concat()))
+}
+
+pub fn dg11(fields: &[(u32, &str)]) -> Vec<u8> {
+    let tags: Vec<u8> = fields.iter().flat_map(|(tag, _)| tag.to_be_bytes().into_iter().skip(2)).collect();
+    let values: Vec<u8> = fields.iter().flat_map(|(tag, value)| tlv(*tag, value.as_bytes())).collect();
+    tlv(0x6B, &[tlv(0x5C, &tags), values].concat())
+}
+
+fn digest(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+        HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+        other => panic!("no fixture for {:?}", other),
+    }
+}
+
+/// An EF.SOD listing the hash of each of `groups` (number and file), with
+/// no signer info.
+pub fn sod(algorithm: HashAlgorithm, groups: &[(u8, &[u8])]) -> Vec<u8> {
+    sod_signed_by(algorithm, groups, None)
+}
+
+/// An EF.SOD as `sod` makes it, signed by the test document signer, whose
+/// certificate `document_signer` it carries.
+pub fn signed_sod(algorithm: HashAlgorithm, groups: &[(u8, &[u8])], document_signer: &[u8]) -> Vec<u8> {
+    sod_signed_by(algorithm, groups, Some(document_signer))
+}
+
+fn sod_signed_by(algorithm: HashAlgorithm, groups: &[(u8, &[u8])], document_signer: Option<&[u8]>) -> Vec<u8> {
+    let oid = match algorithm {
+        HashAlgorithm::Sha1 => &SHA1_OID[..],
+        _ => &SHA256_OID[..],
+    };
+    let algorithm_identifier = tlv(0x30, &[tlv(0x06, oid), tlv(0x05, &[])].concat());
+
+    let hashes: Vec<u8> = groups
+        .iter()
+        .flat_map(|(number, file)| tlv(0x30, &[tlv(0x02, &[*number]), tlv(0x04, &digest(algorithm, file))].concat()))
+        .collect();
+    let security_object = tlv(
+        0x30,
+        &[tlv(0x02, &[0]), algorithm_identifier.clone(), tlv(0x30, &hashes)].concat(),
+    );
+
+    let encapsulated = tlv(
+        0x30,
+        &[tlv(0x06, &LDS_SECURITY_OBJECT_OID), tlv(0xA0, &tlv(0x04, &security_object))].concat(),
+    );
+    let (certificates, signer_infos) = match document_signer {
+        Some(certificate) => {
+            let attributes = [
+                tlv(0x30, &[tlv(0x06, &CONTENT_TYPE_OID), tlv(0x31, &tlv(0x06, &LDS_SECURITY_OBJECT_OID))].concat()),
+                tlv(
+                    0x30,
+                    &[tlv(0x06, &MESSAGE_DIGEST_OID), tlv(0x31, &tlv(0x04, &digest(algorithm, &security_object)))].concat(),
+                ),
+            ]
+            .concat();
+            let signature = document_signer_key()
+                .sign_prehash(&Sha256::digest(tlv(0x31, &attributes)))
+                .map(|s: p256::ecdsa::Signature| s.to_der().as_bytes().to_vec())
+                .unwrap();
+            let signer_info = tlv(
+                0x30,
+                &[
+                    tlv(0x02, &[1]),
+                    tlv(0x30, &[name(CSCA_NAME), tlv(0x02, &[2])].concat()),
+                    algorithm_identifier.clone(),
+                    tlv(0xA0, &attributes),
+                    tlv(0x30, &tlv(0x06, &ECDSA_WITH_SHA256_OID)),
+                    tlv(0x04, &signature),
+                ]
+                .concat(),
+            );
+            (tlv(0xA0, certificate), signer_info)
+        }
+        None => (vec![], vec![]),
+    };
+    let signed_data = tlv(
+        0x30,
+        &[
+            tlv(0x02, &[3]),
+            tlv(0x31, &algorithm_identifier),
+            encapsulated,
+            certificates,
+            tlv(0x31, &signer_infos),
+        ]
+        .concat(),
+    );
+    let content_info = tlv(0x30, &[tlv(0x06, &SIGNED_DATA_OID), tlv(0xA0, &signed_data)].concat());
+    tlv(0x77, &content_info)
+}
+
+/// The test CSCA's RSA key. Only ever used to sign fixtures.
+fn csca_key() -> &'static RsaPrivateKey {
+    static KEY: OnceLock<RsaPrivateKey> = OnceLock::new();
+    KEY.get_or_init(|| RsaPrivateKey::from_pkcs8_pem(include_str!("keys/test_csca_rsa.pem")).expect("fixture key"))
+}
+
+fn document_signer_key() -> p256::ecdsa::SigningKey {
+    p256::ecdsa::SigningKey::from_slice(&[0x42; 32]).unwrap()
+}
+
+/// A name of just a common name.
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = tlv(0x30, &[tlv(0x06, &COMMON_NAME_OID), tlv(0x0C, common_name.as_bytes())].concat());
+    tlv(0x30, &tlv(0x31, &attribute))
+}
+
+/// A v3 certificate for `public_key` (DER `SubjectPublicKeyInfo`), signed
+/// with SHA-256 and the CSCA's RSA key.
+fn certificate(serial: u8, subject: &str, not_after: &str, public_key: &[u8]) -> Vec<u8> {
+    let algorithm = tlv(0x30, &[tlv(0x06, &SHA256_WITH_RSA_OID), tlv(0x05, &[])].concat());
+    let tbs = tlv(
+        0x30,
+        &[
+            tlv(0xA0, &tlv(0x02, &[2])),
+            tlv(0x02, &[serial]),
+            algorithm.clone(),
+            name(CSCA_NAME),
+            tlv(0x30, &[tlv(0x17, VALID_FROM.as_bytes()), tlv(0x17, not_after.as_bytes())].concat()),
+            name(subject),
+            public_key.to_vec(),
+        ]
+        .concat(),
+    );
+    let signature = csca_key()
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(&tbs))
+        .unwrap();
+    tlv(0x30, &[tbs, algorithm, tlv(0x03, &[&[0][..], &signature].concat())].concat())
+}
+
+/// The test CSCA's self-signed certificate.
+pub fn csca_certificate() -> Vec<u8> {
+    static CERTIFICATE: OnceLock<Vec<u8>> = OnceLock::new();
+    CERTIFICATE
+        .get_or_init(|| {
+            let public_key = csca_key().to_public_key().to_public_key_der().unwrap();
+            certificate(1, CSCA_NAME, VALID_UNTIL, public_key.as_bytes())
+        })
+        .clone()
+}
+
+/// The test document signer's certificate, valid until `not_after`.
+pub fn document_signer_certificate(not_after: &str) -> Vec<u8> {
+    let public_key = document_signer_key().verifying_key().to_public_key_der().unwrap();
+    certificate(2, "DOCUMENT SIGNER TEST", not_after, public_key.as_bytes())
+}
+
+/// A certificate named like the test CSCA, for a key that is not its own.
+pub fn impostor_csca_certificate() -> Vec<u8> {
+    let public_key = document_signer_key().verifying_key().to_public_key_der().unwrap();
+    certificate(3, CSCA_NAME, VALID_UNTIL, public_key.as_bytes())
+}
+
+/// `der` as a PEM `CERTIFICATE` block.
+pub fn pem(der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let lines: Vec<&str> = encoded.as_bytes().chunks(64).map(|l| std::str::from_utf8(l).unwrap()).collect();
+    format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", lines.join("\n"))
+}
@@ -0,0 +1,122 @@
+//! The golden corpus: synthetic specimens in `tests/golden`, each a JSON
+//! spec of what is printed on the document and what should be read from
+//! it, next to the images rendered from that spec.
+
+use image::GrayImage;
+use serde::Deserialize;
+use shared::DocumentType;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{blurred, id_card, noisy, page, photograph, skewed, unevenly_lit, PHOTO_CORNERS};
+
+const PAGE_SKEW_DEGREES: f32 = 3.0;
+
+/// Every capture is slightly out of focus and grainy, as no lens or sensor
+/// is perfect.
+const LENS_BLUR_SIGMA: f32 = 0.7;
+const SENSOR_NOISE: u8 = 12;
+
+pub fn dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capture {
+    /// A flatbed scan of the card
+    Card,
+    /// The card photographed at an angle on a dark blanket
+    Photo,
+    /// A scanned paper page
+    Page,
+    SkewedPage,
+    UnevenPage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Side {
+    pub capture: Capture,
+    /// As printed, top to bottom; lines with `<` fillers are MRZ
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Expected {
+    pub is_valid: bool,
+    /// Ground truth by `ExtractedData` field name, dates as `YYYY-MM-DD`
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Specimen {
+    #[serde(skip)]
+    pub name: String,
+    pub document_type: DocumentType,
+    pub front: Side,
+    #[serde(default)]
+    pub back: Option<Side>,
+    pub expected: Expected,
+}
+
+impl Side {
+    pub fn render(&self) -> GrayImage {
+        let lines: Vec<(&str, bool)> = self.lines.iter().map(|line| (line.as_str(), line.contains('<'))).collect();
+        let captured = match self.capture {
+            Capture::Card => id_card(&lines),
+            Capture::Photo => photograph(&id_card(&lines), PHOTO_CORNERS, 1000, 750),
+            Capture::Page => page(&lines),
+            Capture::SkewedPage => skewed(&page(&lines), PAGE_SKEW_DEGREES),
+            Capture::UnevenPage => unevenly_lit(&page(&lines)),
+        };
+        noisy(&blurred(&captured, LENS_BLUR_SIGMA), SENSOR_NOISE, self.seed())
+    }
+
+    /// The same noise for the same lines, so rebuilt images only change
+    /// when their spec or the rendering does.
+    fn seed(&self) -> u64 {
+        self.lines
+            .iter()
+            .flat_map(|line| line.bytes())
+            .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    }
+}
+
+impl Specimen {
+    pub fn front_image(&self) -> PathBuf {
+        dir().join(format!("{}_front.png", self.name))
+    }
+
+    pub fn back_image(&self) -> Option<PathBuf> {
+        self.back.as_ref().map(|_| dir().join(format!("{}_back.png", self.name)))
+    }
+}
+
+/// Every specimen in the corpus, by file name.
+pub fn specimens() -> Vec<Specimen> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir())
+        .expect("golden corpus directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json") && !is_baseline(path))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let json = std::fs::read_to_string(&path).unwrap();
+            let mut specimen: Specimen =
+                serde_json::from_str(&json).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            specimen.name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            specimen
+        })
+        .collect()
+}
+
+pub fn baseline_path() -> PathBuf {
+    dir().join("baseline.json")
+}
+
+fn is_baseline(path: &Path) -> bool {
+    path == baseline_path()
+}
@@ -0,0 +1,329 @@
+//! Synthetic document images whose expected reading is known exactly.
+#![allow(dead_code)]
+
+pub mod chip;
+pub mod golden;
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use chrono::NaiveDate;
+use image::{GrayImage, Luma};
+use shared::AlbergueResult;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use validation_service::domain::ocr::image_processor::{self, Homography};
+use validation_service::domain::ocr::recognizer::OcrResult;
+use validation_service::domain::portrait::{PortraitKey, SealedPortrait};
+use validation_service::ports::ocr_client::OCRClient;
+use validation_service::ports::portrait_store::PortraitStore;
+
+/// A 1x1 PNG, base64: for tests whose OCR reading is stubbed.
+pub const BLANK_PNG: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+/// OCR that reads the same text, with full confidence, from any image.
+pub struct FixedTextOcr(pub &'static str);
+
+#[async_trait::async_trait(?Send)]
+impl OCRClient for FixedTextOcr {
+    async fn extract_text(&self, _image_data: &[u8]) -> AlbergueResult<String> {
+        Ok(self.0.to_string())
+    }
+
+    async fn extract_text_with_confidence(&self, _image_data: &[u8]) -> AlbergueResult<OcrResult> {
+        Ok(OcrResult {
+            text: self.0.to_string(),
+            confidence: 1.0,
+            words: vec![],
+        })
+    }
+}
+
+/// Keeps what it is given, shared with the test that made it.
+#[derive(Clone, Default)]
+pub struct MemoryStore(pub Rc<RefCell<Vec<(PortraitKey, SealedPortrait)>>>);
+
+#[async_trait::async_trait(?Send)]
+impl PortraitStore for MemoryStore {
+    async fn put(&self, key: &PortraitKey, portrait: &SealedPortrait) -> AlbergueResult<String> {
+        self.0.borrow_mut().push((*key, portrait.clone()));
+        Ok(format!("memory://{}", key.path()))
+    }
+
+    async fn get(&self, key: &PortraitKey) -> AlbergueResult<Option<SealedPortrait>> {
+        Ok(self.0.borrow().iter().find(|(k, _)| k == key).map(|(_, p)| p.clone()))
+    }
+
+    async fn purge_expired(&self, today: NaiveDate) -> AlbergueResult<usize> {
+        let before = self.0.borrow().len();
+        self.0.borrow_mut().retain(|(k, _)| k.retain_until >= today);
+        Ok(before - self.0.borrow().len())
+    }
+}
+
+pub const CAP: usize = 24;
+
+/// The widest an MRZ character may be, as a share of the pitch.
+const MRZ_INK_WIDTH: f32 = 0.9;
+
+pub const PAPER: u8 = 255;
+pub const CARD: u8 = 215;
+pub const BLANKET: u8 = 40;
+
+/// Fixtures print with a font none of the glyph models was rendered from,
+/// so tests read shapes the recognizer has not seen before.
+fn font() -> &'static FontRef<'static> {
+    static FONT: OnceLock<FontRef<'static>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        FontRef::try_from_slice(include_bytes!("fonts/DejaVuSansCondensed.ttf")).expect("fixture font")
+    })
+}
+
+/// The font size at which capitals are `cap` pixels tall.
+fn scale(cap: usize) -> PxScale {
+    const PROBE: f32 = 100.0;
+    let font = font();
+    let h = font
+        .outline_glyph(font.glyph_id('H').with_scale(PROBE))
+        .expect("fixture font has H")
+        .px_bounds();
+    PxScale::from(PROBE * cap as f32 / h.height())
+}
+
+/// How far `ch` moves the pen at cap height `cap`.
+fn advance(ch: char, cap: usize) -> f32 {
+    let font = font();
+    font.as_scaled(scale(cap)).h_advance(font.glyph_id(ch))
+}
+
+/// Inks `ch` at cap height `cap`, its origin at `x` on `baseline`.
+pub fn draw_char(image: &mut GrayImage, ch: char, cap: usize, x: f32, baseline: f32) {
+    draw_scaled(image, ch, scale(cap), x, baseline);
+}
+
+/// Ink darkens each pixel by the share of it the outline covers, as a
+/// scanner's sensor averages it.
+fn draw_scaled(image: &mut GrayImage, ch: char, scale: PxScale, x: f32, baseline: f32) {
+    let font = font();
+    let glyph = font.glyph_id(ch).with_scale_and_position(scale, point(x, baseline));
+    let Some(outline) = font.outline_glyph(glyph) else {
+        return;
+    };
+    let bounds = outline.px_bounds();
+    outline.draw(|gx, gy, coverage| {
+        let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+        if px >= 0 && py >= 0 && (px as u32) < image.width() && (py as u32) < image.height() {
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            pixel[0] = (pixel[0] as f32 * (1.0 - coverage.min(1.0))).round() as u8;
+        }
+    });
+}
+
+/// Inks `ch` into the MRZ cell starting at `x`, `pitch` wide, the way
+/// OCR-B prints it: letters and digits exactly `cap` pixels tall on the
+/// baseline and narrowed to leave a gap between cells, fillers as the font
+/// draws them.
+pub fn draw_mrz_char(image: &mut GrayImage, ch: char, cap: usize, x: f32, pitch: f32, baseline: f32) {
+    let font = font();
+    let mut scale = scale(cap);
+    let bounds = |scale: PxScale| {
+        font.outline_glyph(font.glyph_id(ch).with_scale(scale))
+            .map(|outline| outline.px_bounds())
+    };
+    let Some(natural) = bounds(scale) else {
+        return;
+    };
+    let mut origin_y = baseline;
+    if ch.is_ascii_alphanumeric() {
+        let fit = cap as f32 / natural.height();
+        let narrow = (pitch * MRZ_INK_WIDTH / (natural.width() * fit)).min(1.0);
+        scale = PxScale { x: scale.x * fit * narrow, y: scale.y * fit };
+        origin_y = baseline - bounds(scale).map_or(0.0, |b| b.max.y);
+    }
+    let Some(fitted) = bounds(scale) else {
+        return;
+    };
+    let origin_x = x + (pitch - fitted.width()) / 2.0 - fitted.min.x;
+    draw_scaled(image, ch, scale, origin_x, origin_y);
+}
+
+/// Prints `lines` onto `page`, the first baseline `top` pixels down. `true`
+/// lines are MRZ, at a fixed pitch.
+pub fn draw_lines(page: &mut GrayImage, lines: &[(&str, bool)], top: usize) {
+    let pitch = (CAP * 3 / 4) as f32;
+
+    for (row, (text, mrz)) in lines.iter().enumerate() {
+        let baseline = (top + row * CAP * 2) as f32;
+        let mut x = 30.0;
+
+        for ch in text.chars() {
+            if *mrz {
+                draw_mrz_char(page, ch, CAP, x, pitch, baseline);
+                x += pitch;
+            } else {
+                draw_char(page, ch, CAP, x, baseline);
+                x += advance(ch, CAP) + CAP as f32 / 8.0;
+            }
+        }
+    }
+}
+
+/// A white page just wide enough for `lines`.
+pub fn page(lines: &[(&str, bool)]) -> GrayImage {
+    let pitch = CAP * 3 / 4;
+    let width = 60 + lines.iter().map(|(text, _)| text.chars().count()).max().unwrap_or(0) * (pitch + 4);
+    let mut image = GrayImage::from_pixel(width as u32, (lines.len() * CAP * 2 + 40) as u32, Luma([PAPER]));
+    draw_lines(&mut image, lines, 20 + CAP * 3 / 2);
+    image
+}
+
+/// An ID-1 card, 856 by 540 pixels, in a light grey stock.
+pub fn id_card(lines: &[(&str, bool)]) -> GrayImage {
+    let mut card = GrayImage::from_pixel(856, 540, Luma([CARD]));
+    draw_lines(&mut card, lines, 80);
+    card
+}
+
+/// Where `portrait` draws the holder's photo on an ID-1 card: left, top,
+/// width and height.
+pub const PORTRAIT_BOUNDS: (u32, u32, u32, u32) = (30, 90, 240, 310);
+
+/// A head-and-shoulders photo in a `width` by `height` frame: a mid grey
+/// backdrop, dark hair, a face and a jacket.
+pub fn portrait(width: u32, height: u32) -> GrayImage {
+    let (w, h) = (width as f32, height as f32);
+    GrayImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as f32, y as f32);
+        let head = ((x - w / 2.0) / (w * 0.22)).powi(2) + ((y - h * 0.38) / (h * 0.22)).powi(2);
+        let tone = if head <= 1.0 {
+            if y < h * 0.24 { 35 } else { 120 }
+        } else if y > h * 0.68 && (x - w / 2.0).abs() < w * 0.45 {
+            60
+        } else {
+            150
+        };
+        Luma([tone])
+    })
+}
+
+/// An ID-1 card with the holder's photo at `PORTRAIT_BOUNDS` and `lines`
+/// to its right.
+pub fn id_card_with_portrait(lines: &[(&str, bool)]) -> GrayImage {
+    let mut card = GrayImage::from_pixel(856, 540, Luma([CARD]));
+    let (left, top, width, height) = PORTRAIT_BOUNDS;
+    image::imageops::replace(&mut card, &portrait(width, height), left as i64, top as i64);
+
+    let mut text = GrayImage::from_pixel(520, 540, Luma([CARD]));
+    draw_lines(&mut text, lines, 80);
+    image::imageops::replace(&mut card, &text, 320, 0);
+    card
+}
+
+/// Card corners in a 1000 by 750 photo, seen from above and to the left.
+pub const PHOTO_CORNERS: [(f32, f32); 4] = [(180.0, 140.0), (830.0, 190.0), (800.0, 600.0), (150.0, 530.0)];
+
+/// `card` as photographed lying on a dark blanket, its corners at
+/// `corners` (clockwise from the card's top left) in a `width` by `height`
+/// photo. Each camera pixel averages the patch of card it sees, so a card
+/// shrunk in the photo is softened first rather than point-sampled.
+pub fn photograph(card: &GrayImage, corners: [(f32, f32); 4], width: u32, height: u32) -> GrayImage {
+    let (w, h) = (card.width() as f32, card.height() as f32);
+    let to_card = Homography::between(&corners, &[(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]).unwrap();
+
+    let edge = |(ax, ay): (f32, f32), (bx, by): (f32, f32)| (bx - ax).hypot(by - ay);
+    let shrink = 2.0 * w / (edge(corners[0], corners[1]) + edge(corners[3], corners[2]));
+    if shrink > 1.0 {
+        let softened = image::imageops::blur(card, shrink / 2.0);
+        image_processor::warp(&softened, &to_card, width, height, BLANKET)
+    } else {
+        image_processor::warp(card, &to_card, width, height, BLANKET)
+    }
+}
+
+/// `gray` turned `degrees` clockwise about its centre.
+pub fn skewed(gray: &GrayImage, degrees: f32) -> GrayImage {
+    let center = (gray.width() as f32 / 2.0, gray.height() as f32 / 2.0);
+    image_processor::warp(gray, &Homography::rotation(-degrees, center), gray.width(), gray.height(), PAPER)
+}
+
+/// `gray` out of focus.
+pub fn blurred(gray: &GrayImage, sigma: f32) -> GrayImage {
+    image::imageops::blur(gray, sigma)
+}
+
+/// `gray` with sensor noise up to `amplitude` levels either way, the same
+/// for the same `seed`.
+pub fn noisy(gray: &GrayImage, amplitude: u8, seed: u64) -> GrayImage {
+    let mut state = seed;
+    let mut noisy = gray.clone();
+    for pixel in noisy.pixels_mut() {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let offset = (state >> 33) as i32 % (2 * amplitude as i32 + 1) - amplitude as i32;
+        pixel[0] = (pixel[0] as i32 + offset).clamp(0, 255) as u8;
+    }
+    noisy
+}
+
+/// A clipped highlight of `radius` pixels, as from a flash on laminate.
+pub fn with_glare(gray: &GrayImage, center: (u32, u32), radius: u32) -> GrayImage {
+    let mut glared = gray.clone();
+    for (x, y, pixel) in glared.enumerate_pixels_mut() {
+        let (dx, dy) = (x as i64 - center.0 as i64, y as i64 - center.1 as i64);
+        if dx * dx + dy * dy <= (radius * radius) as i64 {
+            pixel[0] = 255;
+        }
+    }
+    glared
+}
+
+/// Light falling off from right to left, as from a bedside lamp, without
+/// clipping anywhere.
+pub fn unevenly_lit(gray: &GrayImage) -> GrayImage {
+    let width = gray.width() as f32;
+    let mut lit = gray.clone();
+    for (x, _, pixel) in lit.enumerate_pixels_mut() {
+        let light = 0.25 + 0.7 * x as f32 / width;
+        pixel[0] = (pixel[0] as f32 * light) as u8;
+    }
+    lit
+}
+
+/// The modules of `code`, `true` for dark, rows top to bottom.
+pub fn qr_modules(code: &qrcode::QrCode) -> Vec<Vec<bool>> {
+    let colors = code.to_colors();
+    colors
+        .chunks(code.width())
+        .map(|row| row.iter().map(|c| *c == qrcode::Color::Dark).collect())
+        .collect()
+}
+
+/// Draws `modules` `scale` pixels each, their top left corner at (`left`, `top`).
+pub fn draw_modules(image: &mut GrayImage, modules: &[Vec<bool>], left: u32, top: u32, scale: u32) {
+    for (row, line) in modules.iter().enumerate() {
+        for (col, &dark) in line.iter().enumerate() {
+            let value = if dark { 0 } else { PAPER };
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    image.put_pixel(left + col as u32 * scale + dx, top + row as u32 * scale + dy, Luma([value]));
+                }
+            }
+        }
+    }
+}
+
+/// `modules` alone on white paper, with the four module quiet zone codes
+/// need.
+pub fn qr_image(modules: &[Vec<bool>], scale: u32) -> GrayImage {
+    let side = (modules.len() as u32 + 8) * scale;
+    let mut image = GrayImage::from_pixel(side, side, Luma([PAPER]));
+    draw_modules(&mut image, modules, 4 * scale, 4 * scale, scale);
+    image
+}
+
+pub fn png(gray: &GrayImage) -> Vec<u8> {
+    let mut png = vec![];
+    image::DynamicImage::ImageLuma8(gray.clone())
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    png
+}
@@ -0,0 +1,406 @@
+mod fixtures;
+
+use fixtures::*;
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use shared::{
+    DocumentType, ExtractedData, ForgeryReason, ForgerySignal, ValidationRequest, ValidationResponse,
+    ValidationWarningCode,
+};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::forgery::{metadata, ForgeryDetector};
+
+const DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 15/06/1990\nVÁLIDO HASTA: 10/01/2030";
+
+/// The Spanish police's specimen DNI.
+const SPECIMEN_DNI_FRONT: &str = "DNI 99999999R\nAPELLIDOS: ESPAÑOLA ESPAÑOLA\nNOMBRE: CARMEN\nFECHA DE NACIMIENTO: 01/01/1980\nVÁLIDO HASTA: 01/01/2031";
+
+const MRZ: [&str; 3] = [
+    "IDESPBAB123456<12345678Z<<<<<<",
+    "9006157M3001108ESP<<<<<<<<<<<0",
+    "GARCIA<MARTINEZ<<JUAN<CARLOS<<",
+];
+
+/// The front of a DNI, photo and text, as an honest scan shows it.
+fn clean_card() -> GrayImage {
+    id_card_with_portrait(&[("APELLIDOS: GARCIA", false), ("NOMBRE: JUAN", false)])
+}
+
+/// The back of the same DNI, with its MRZ.
+fn clean_back() -> GrayImage {
+    id_card(&MRZ.map(|line| (line, true)))
+}
+
+/// Smooth mid-grey texture, the same for the same `seed`.
+fn texture(width: u32, height: u32, seed: u64) -> GrayImage {
+    let mut state = seed;
+    let noise = GrayImage::from_fn(width, height, |_, _| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        Luma([20 + (state >> 33) as u8 % 216])
+    });
+    image::imageops::blur(&noise, 1.0)
+}
+
+/// `gray` as a camera sees it on a display whose pixels are `pitch` image
+/// pixels apart: red, green and blue stripes with dark gaps between.
+fn on_screen(gray: &GrayImage, pitch: u32) -> RgbImage {
+    RgbImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let v = gray.get_pixel(x, y)[0] as f32;
+        let gap = if x % pitch == pitch - 1 || y % pitch == pitch - 1 { 0.55 } else { 1.0 };
+        let mut rgb = [v * 0.8; 3];
+        rgb[(x % pitch).min(2) as usize] = v;
+        Rgb(rgb.map(|c| (c * gap) as u8))
+    })
+}
+
+/// Slanted red and blue bands every `period` pixels over `gray`.
+fn with_moire(gray: &GrayImage, period: f32) -> RgbImage {
+    let (dx, dy) = (20f32.to_radians().cos(), 20f32.to_radians().sin());
+    RgbImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let v = gray.get_pixel(x, y)[0] as f32;
+        let wave = 18.0 * (std::f32::consts::TAU * (x as f32 * dx + y as f32 * dy) / period).sin();
+        Rgb([(v + wave).clamp(0.0, 255.0) as u8, v as u8, (v - wave).clamp(0.0, 255.0) as u8])
+    })
+}
+
+/// An MRZ line with the characters at `retyped` typed over in an editor:
+/// the same font still, but a size smaller on the same baseline.
+fn retyped_mrz(line: &str, retyped: &[usize]) -> GrayImage {
+    let pitch = CAP * 3 / 4;
+    let mut page = page(&[(line, true)]);
+    let baseline = 20 + CAP * 3 / 2;
+    for &i in retyped {
+        let ch = line.chars().nth(i).unwrap();
+        let x = 30 + i * pitch;
+        for y in baseline - CAP - 4..baseline + 6 {
+            for dx in 0..pitch {
+                page.put_pixel((x + dx) as u32, y as u32, Luma([PAPER]));
+            }
+        }
+        let cap = CAP * 5 / 6;
+        draw_mrz_char(&mut page, ch, cap, x as f32, pitch as f32, baseline as f32);
+    }
+    page
+}
+
+fn rgb_png(rgb: &RgbImage) -> Vec<u8> {
+    let mut png = vec![];
+    DynamicImage::ImageRgb8(rgb.clone())
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    png
+}
+
+fn jpeg(gray: &GrayImage) -> Vec<u8> {
+    let mut jpeg = vec![];
+    DynamicImage::ImageLuma8(gray.clone())
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+    jpeg
+}
+
+/// Little-endian EXIF with `Software` and `DateTime` in IFD0, and
+/// `DateTimeOriginal` and the pixel dimensions in the EXIF IFD.
+fn exif(software: &str, modified: &str, captured: &str, dimensions: (u16, u16)) -> Vec<u8> {
+    let entry = |tag: u16, kind: u16, count: u32, value: u32| {
+        [tag.to_le_bytes().as_slice(), &kind.to_le_bytes(), &count.to_le_bytes(), &value.to_le_bytes()].concat()
+    };
+    let text = |s: &str| [s.as_bytes(), &[0]].concat();
+    let (software, modified, captured) = (text(software), text(modified), text(captured));
+
+    // Header, IFD0 with three entries, the EXIF IFD with three, then values
+    let ifd0 = 8;
+    let exif_ifd = ifd0 + 2 + 3 * 12 + 4;
+    let values = exif_ifd + 2 + 3 * 12 + 4;
+    let software_at = values;
+    let modified_at = software_at + software.len();
+    let captured_at = modified_at + modified.len();
+
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend((ifd0 as u32).to_le_bytes());
+    tiff.extend(3u16.to_le_bytes());
+    tiff.extend(entry(0x0131, 2, software.len() as u32, software_at as u32));
+    tiff.extend(entry(0x0132, 2, modified.len() as u32, modified_at as u32));
+    tiff.extend(entry(0x8769, 4, 1, exif_ifd as u32));
+    tiff.extend(0u32.to_le_bytes());
+    tiff.extend(3u16.to_le_bytes());
+    tiff.extend(entry(0x9003, 2, captured.len() as u32, captured_at as u32));
+    tiff.extend(entry(0xA002, 3, 1, dimensions.0 as u32));
+    tiff.extend(entry(0xA003, 3, 1, dimensions.1 as u32));
+    tiff.extend(0u32.to_le_bytes());
+    tiff.extend(software);
+    tiff.extend(modified);
+    tiff.extend(captured);
+    tiff
+}
+
+/// `jpeg` with an APP1 EXIF segment right after the start of image.
+fn with_exif(jpeg: &[u8], tiff: &[u8]) -> Vec<u8> {
+    let segment = [b"Exif\0\0".as_slice(), tiff].concat();
+    let mut out = jpeg[..2].to_vec();
+    out.extend([0xFF, 0xE1]);
+    out.extend(((segment.len() + 2) as u16).to_be_bytes());
+    out.extend(segment);
+    out.extend(&jpeg[2..]);
+    out
+}
+
+/// `png` with a `tEXt` chunk after its header chunk.
+fn with_png_text(png: &[u8], keyword: &str, text: &str) -> Vec<u8> {
+    let body = [keyword.as_bytes(), &[0], text.as_bytes()].concat();
+    let chunk = [b"tEXt".as_slice(), &body].concat();
+    // IHDR is always 25 bytes with its length and checksum
+    let mut out = png[..33].to_vec();
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(&chunk);
+    out.extend(crc32(&chunk).to_be_bytes());
+    out.extend(&png[33..]);
+    out
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn signals(reasons: &[ForgeryReason]) -> Vec<ForgerySignal> {
+    reasons.iter().map(|r| r.signal).collect()
+}
+
+async fn validate(text: &'static str) -> ValidationResponse {
+    ValidationService::new()
+        .with_ocr_client(Box::new(FixedTextOcr(text)))
+        .validate_document(ValidationRequest {
+            document_type: Some(DocumentType::DNI),
+            front_image: BLANK_PNG.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod forgery_tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_scan_gives_no_reasons() {
+        let detector = ForgeryDetector::new();
+        let mut reasons = detector.inspect_image(&png(&clean_card()), "front");
+        reasons.extend(detector.inspect_image(&png(&clean_back()), "back"));
+        assert!(reasons.is_empty(), "{:?}", reasons);
+        assert_eq!(detector.assess(reasons).score, 0.0);
+    }
+
+    #[test]
+    fn test_clean_texture_is_not_a_copy() {
+        let reasons = ForgeryDetector::new().inspect_image(&png(&texture(500, 400, 7)), "front");
+        assert!(!signals(&reasons).contains(&ForgerySignal::DuplicatedRegion), "{:?}", reasons);
+    }
+
+    #[test]
+    fn test_photographed_screen_shows_its_pixel_grid() {
+        let screen = on_screen(&clean_card(), 4);
+        let reasons = ForgeryDetector::new().inspect_image(&rgb_png(&screen), "front");
+
+        let grid = reasons.iter().find(|r| r.signal == ForgerySignal::ScreenGrid).expect("screen grid");
+        assert_eq!(grid.image.as_deref(), Some("front"));
+        assert!(grid.message.contains("4x4"), "{}", grid.message);
+    }
+
+    #[test]
+    fn test_colour_bands_are_moire() {
+        let reasons = ForgeryDetector::new().inspect_image(&rgb_png(&with_moire(&clean_back(), 14.0)), "back");
+
+        let moire = reasons.iter().find(|r| r.signal == ForgerySignal::Moire).expect("moiré");
+        assert_eq!(moire.image.as_deref(), Some("back"));
+        assert!(moire.strength >= 0.5);
+    }
+
+    #[test]
+    fn test_pasted_patch_is_found() {
+        let mut image = texture(500, 400, 7);
+        let patch = image::imageops::crop_imm(&image, 40, 60, 110, 90).to_image();
+        image::imageops::replace(&mut image, &patch, 300, 250);
+
+        let reasons = ForgeryDetector::new().inspect_image(&png(&image), "front");
+        let copy = reasons
+            .iter()
+            .find(|r| r.signal == ForgerySignal::DuplicatedRegion)
+            .expect("duplicated region");
+        assert!(copy.message.contains("260 across and 190 down"), "{}", copy.message);
+    }
+
+    #[test]
+    fn test_retyped_mrz_characters_are_found() {
+        let line = MRZ[0];
+        let clean = ForgeryDetector::new().inspect_image(&png(&page(&[(line, true)])), "front");
+        assert!(clean.is_empty(), "{:?}", clean);
+
+        let edited = ForgeryDetector::new().inspect_image(&png(&retyped_mrz(line, &[15, 16, 17])), "front");
+        let font = edited
+            .iter()
+            .find(|r| r.signal == ForgerySignal::MrzFontMismatch)
+            .expect("font mismatch");
+        assert!(font.message.contains("3 off the line's size"), "{}", font.message);
+    }
+
+    #[test]
+    fn test_one_odd_mrz_character_is_not_enough() {
+        let reasons = ForgeryDetector::new().inspect_image(&png(&retyped_mrz(MRZ[0], &[16])), "front");
+        assert!(!signals(&reasons).contains(&ForgerySignal::MrzFontMismatch), "{:?}", reasons);
+    }
+
+    #[test]
+    fn test_exif_from_an_editor_is_flagged() {
+        let card = clean_card();
+        let tiff = exif("Adobe Photoshop 25.0", "2026:03:02 18:00:00", "2026:02:14 09:30:00", (4032, 3024));
+        let reasons = metadata::inspect(&with_exif(&jpeg(&card), &tiff), card.width(), card.height());
+
+        let messages: Vec<&str> = reasons.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(reasons.len(), 3, "{:?}", messages);
+        assert!(messages[0].contains("photoshop"));
+        assert!(messages[1].contains("16 days"));
+        assert!(messages[2].contains("4032x3024"));
+    }
+
+    #[test]
+    fn test_plain_camera_exif_passes() {
+        let card = clean_card();
+        let tiff = exif("Pixel 8", "2026:02:14 09:30:02", "2026:02:14 09:30:00", (540, 856));
+        let data = with_exif(&jpeg(&card), &tiff);
+
+        assert_eq!(
+            metadata::read_jpeg(&data).unwrap().software.as_deref(),
+            Some("Pixel 8")
+        );
+        assert!(metadata::inspect(&data, card.width(), card.height()).is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_offsets_give_no_metadata() {
+        let card = clean_card();
+
+        // A chunk claiming to run past the end of the file
+        let mut data = with_png_text(&png(&card), "Software", "Screenshot");
+        data[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(metadata::read_png(&data), None);
+
+        // IFD0 and the EXIF IFD pointing past the end of the block
+        let mut tiff = exif("Pixel 8", "2026:02:14 09:30:02", "2026:02:14 09:30:00", (540, 856));
+        tiff[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(metadata::read_jpeg(&with_exif(&jpeg(&card), &tiff)).map(|m| m.software), Some(None));
+        let mut tiff = exif("Pixel 8", "2026:02:14 09:30:02", "2026:02:14 09:30:00", (540, 856));
+        tiff[8 + 2 + 2 * 12 + 8..8 + 2 + 2 * 12 + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let read = metadata::read_jpeg(&with_exif(&jpeg(&card), &tiff)).unwrap();
+        assert_eq!((read.software.as_deref(), read.captured), (Some("Pixel 8"), None));
+    }
+
+    #[test]
+    fn test_png_marked_as_screenshot() {
+        let data = with_png_text(&png(&clean_card()), "Software", "Screenshot");
+        assert!(image::load_from_memory(&data).is_ok());
+
+        let reasons = ForgeryDetector::new().inspect_image(&data, "front");
+        assert_eq!(signals(&reasons), vec![ForgerySignal::ExifInconsistency]);
+        assert_eq!(reasons[0].strength, 0.9);
+    }
+
+    #[test]
+    fn test_specimen_numbers_and_names() {
+        let detector = ForgeryDetector::new();
+        let by_number = ExtractedData {
+            document_number: Some("99999999R".to_string()),
+            ..Default::default()
+        };
+        let by_name = ExtractedData {
+            name: Some("Erika".to_string()),
+            surname: Some("Mustermann".to_string()),
+            ..Default::default()
+        };
+        let genuine = ExtractedData {
+            document_number: Some("12345678Z".to_string()),
+            name: Some("JUAN CARLOS".to_string()),
+            surname: Some("GARCIA MARTINEZ".to_string()),
+            ..Default::default()
+        };
+
+        let number = detector.inspect_data(&by_number, None);
+        assert_eq!(signals(&number), vec![ForgerySignal::SpecimenDocument]);
+        assert_eq!(number[0].strength, 1.0);
+        assert!(number[0].image.is_none());
+        assert_eq!(detector.inspect_data(&by_name, None)[0].strength, 0.8);
+        assert!(detector.inspect_data(&genuine, None).is_empty());
+    }
+
+    #[test]
+    fn test_assessment_orders_and_combines_reasons() {
+        let detector = ForgeryDetector::new();
+        let reason = |signal, strength| ForgeryReason {
+            signal,
+            image: None,
+            strength,
+            message: String::new(),
+        };
+
+        let one = detector.assess(vec![reason(ForgerySignal::ExifInconsistency, 0.5)]);
+        let two = detector.assess(vec![
+            reason(ForgerySignal::ExifInconsistency, 0.5),
+            reason(ForgerySignal::ScreenGrid, 0.9),
+        ]);
+        assert!((one.score - 0.2).abs() < 1e-6);
+        assert!(two.score > one.score && two.score < 1.0);
+        assert_eq!(two.reasons[0].signal, ForgerySignal::ScreenGrid);
+    }
+
+    #[tokio::test]
+    async fn test_specimen_document_is_valid_but_warned() {
+        let response = validate(SPECIMEN_DNI_FRONT).await;
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert!(response.forgery_risk.score >= 0.9);
+        let warning = response
+            .warnings
+            .iter()
+            .find(|w| w.code == ValidationWarningCode::PossibleForgery)
+            .expect("forgery warning");
+        assert!(warning.message.contains("99999999R"), "{}", warning.message);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_of_large_uploads_is_inspected() {
+        // Scaling for OCR re-encodes the photo without its EXIF
+        let card = image::imageops::resize(&clean_card(), 2568, 1620, image::imageops::FilterType::Nearest);
+        let tiff = exif("Adobe Photoshop 25.0", "2026:02:14 09:30:02", "2026:02:14 09:30:00", (2568, 1620));
+        let response = ValidationService::new()
+            .with_ocr_client(Box::new(FixedTextOcr(DNI_FRONT)))
+            .validate_document(ValidationRequest {
+                document_type: Some(DocumentType::DNI),
+                front_image: base64::encode(with_exif(&jpeg(&card), &tiff)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let reasons = &response.forgery_risk.reasons;
+        assert!(
+            reasons.iter().any(|r| r.message.contains("photoshop")),
+            "{:?}",
+            reasons
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ordinary_document_has_no_risk() {
+        let response = validate(DNI_FRONT).await;
+
+        assert_eq!(response.forgery_risk.score, 0.0);
+        assert!(response.forgery_risk.reasons.is_empty());
+        assert!(!response.warnings.iter().any(|w| w.code == ValidationWarningCode::PossibleForgery));
+    }
+}
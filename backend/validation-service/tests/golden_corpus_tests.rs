@@ -0,0 +1,252 @@
+mod fixtures;
+
+use fixtures::golden::{self, Specimen};
+use serde::{Deserialize, Serialize};
+use shared::{AlbergueResult, DocumentType, ValidationRequest, ValidationResponse};
+use std::collections::BTreeMap;
+use validation_service::application::validation_service::ValidationService;
+
+/// How far a metric may drop below the baseline before the corpus fails.
+const TOLERANCE: f64 = 0.05;
+
+/// Set to rewrite `tests/golden/baseline.json` with the current accuracy.
+const UPDATE_BASELINE_VAR: &str = "GOLDEN_UPDATE_BASELINE";
+
+#[derive(Debug, Default)]
+struct FieldTally {
+    correct: u32,
+    wrong: u32,
+    missed: u32,
+}
+
+impl FieldTally {
+    /// Share of the values read that were right.
+    fn precision(&self) -> f64 {
+        ratio(self.correct, self.correct + self.wrong)
+    }
+
+    /// Share of the expected values that were read right.
+    fn recall(&self) -> f64 {
+        ratio(self.correct, self.correct + self.wrong + self.missed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct FieldAccuracy {
+    precision: f64,
+    recall: f64,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Accuracy {
+    fields: BTreeMap<String, FieldAccuracy>,
+    /// Share of each document type's specimens read entirely right
+    pass_rates: BTreeMap<String, f64>,
+}
+
+fn ratio(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        return 1.0;
+    }
+    f64::from(part) / f64::from(whole)
+}
+
+fn image(path: &std::path::Path) -> String {
+    base64::encode(std::fs::read(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e)))
+}
+
+async fn run(specimen: &Specimen) -> AlbergueResult<ValidationResponse> {
+    ValidationService::new()
+        .validate_document(ValidationRequest {
+            front_image: image(&specimen.front_image()),
+            back_image: specimen.back_image().map(|path| image(&path)),
+            ..Default::default()
+        })
+        .await
+}
+
+/// `field` as read, in the corpus' notation.
+fn read_value(response: &ValidationResponse, field: &str) -> Option<String> {
+    let data = serde_json::to_value(&response.extracted_data).unwrap();
+    let value = data.get(field)?.as_str()?;
+    if field.ends_with("_date") {
+        // RFC 3339 timestamps, of which the date is all that is printed
+        return value.get(..10).map(str::to_string);
+    }
+    Some(value.to_string())
+}
+
+/// Runs every specimen through the pipeline, printing what went wrong.
+async fn measure(specimens: &[Specimen]) -> Accuracy {
+    let mut tallies: BTreeMap<String, FieldTally> = BTreeMap::new();
+    let mut passes: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+
+    for specimen in specimens {
+        let (passed, total) = passes.entry(type_name(specimen.document_type)).or_default();
+        *total += 1;
+        // A document the pipeline gives up on reads none of its fields
+        let response = match run(specimen).await {
+            Ok(response) => response,
+            Err(e) => {
+                for field in specimen.expected.fields.keys() {
+                    tallies.entry(field.clone()).or_default().missed += 1;
+                }
+                println!("{}:
+  rejected: {}", specimen.name, e);
+                continue;
+            }
+        };
+        let mut failures = vec![];
+
+        let detected = response.classification.as_ref().map(|c| c.detected_type);
+        if detected != Some(specimen.document_type) {
+            failures.push(format!("detected as {:?}", detected));
+        }
+        if response.is_valid != specimen.expected.is_valid {
+            failures.push(format!("is_valid {} ({:?})", response.is_valid, response.errors));
+        }
+        for (field, expected) in &specimen.expected.fields {
+            let tally = tallies.entry(field.clone()).or_default();
+            match read_value(&response, field) {
+                Some(read) if read == *expected => tally.correct += 1,
+                Some(read) => {
+                    tally.wrong += 1;
+                    failures.push(format!("{} read as {:?}, expected {:?}", field, read, expected));
+                }
+                None => {
+                    tally.missed += 1;
+                    failures.push(format!("{} not read, expected {:?}", field, expected));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            *passed += 1;
+        } else {
+            println!("{}:\n  {}", specimen.name, failures.join("\n  "));
+        }
+    }
+
+    Accuracy {
+        fields: tallies
+            .into_iter()
+            .map(|(field, tally)| {
+                let accuracy = FieldAccuracy {
+                    precision: tally.precision(),
+                    recall: tally.recall(),
+                };
+                (field, accuracy)
+            })
+            .collect(),
+        pass_rates: passes
+            .into_iter()
+            .map(|(document_type, (passed, total))| (document_type, ratio(passed, total)))
+            .collect(),
+    }
+}
+
+fn type_name(document_type: DocumentType) -> String {
+    format!("{:?}", document_type)
+}
+
+fn report(accuracy: &Accuracy) {
+    println!("{:<16} {:>9} {:>9}", "field", "precision", "recall");
+    for (field, FieldAccuracy { precision, recall }) in &accuracy.fields {
+        println!("{:<16} {:>9.2} {:>9.2}", field, precision, recall);
+    }
+    println!("{:<16} {:>9}", "document type", "pass rate");
+    for (document_type, rate) in &accuracy.pass_rates {
+        println!("{:<16} {:>9.2}", document_type, rate);
+    }
+}
+
+/// Every metric that fell more than `TOLERANCE` below `baseline`.
+fn regressions(accuracy: &Accuracy, baseline: &Accuracy) -> Vec<String> {
+    let mut regressions = vec![];
+    let mut check = |metric: String, now: f64, before: f64| {
+        if now < before - TOLERANCE {
+            regressions.push(format!("{} fell from {:.2} to {:.2}", metric, before, now));
+        }
+    };
+
+    for (field, before) in &baseline.fields {
+        let now = accuracy.fields.get(field).copied().unwrap_or(FieldAccuracy {
+            precision: 0.0,
+            recall: 0.0,
+        });
+        check(format!("{} precision", field), now.precision, before.precision);
+        check(format!("{} recall", field), now.recall, before.recall);
+    }
+    for (document_type, before) in &baseline.pass_rates {
+        let now = accuracy.pass_rates.get(document_type).copied().unwrap_or(0.0);
+        check(format!("{} pass rate", document_type), now, *before);
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod golden_corpus_tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_images_are_rendered() {
+        for specimen in golden::specimens() {
+            assert!(
+                specimen.front_image().exists() && specimen.back_image().is_none_or(|path| path.exists()),
+                "{} has no images; run `cargo run --example build_golden_corpus`",
+                specimen.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_counts_wrong_values_against_precision_and_recall() {
+        let tally = FieldTally {
+            correct: 3,
+            wrong: 1,
+            missed: 4,
+        };
+
+        assert_eq!(tally.precision(), 0.75);
+        assert_eq!(tally.recall(), 0.375);
+    }
+
+    #[test]
+    fn test_small_drops_are_tolerated() {
+        let accuracy = |precision: f64, pass_rate: f64| Accuracy {
+            fields: BTreeMap::from([("name".to_string(), FieldAccuracy { precision, recall: 1.0 })]),
+            pass_rates: BTreeMap::from([("DNI".to_string(), pass_rate)]),
+        };
+        let baseline = accuracy(0.9, 1.0);
+
+        assert!(regressions(&accuracy(0.88, 1.0), &baseline).is_empty());
+        assert_eq!(regressions(&accuracy(0.8, 0.5), &baseline).len(), 2);
+        // A field the baseline scored but the corpus no longer reads at all
+        assert_eq!(regressions(&Accuracy::default(), &baseline).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_accuracy_has_not_regressed() {
+        let accuracy = measure(&golden::specimens()).await;
+        report(&accuracy);
+
+        let baseline_path = golden::baseline_path();
+        if std::env::var_os(UPDATE_BASELINE_VAR).is_some() {
+            std::fs::write(&baseline_path, serde_json::to_string_pretty(&accuracy).unwrap() + "\n").unwrap();
+            return;
+        }
+        let baseline: Accuracy = serde_json::from_str(
+            &std::fs::read_to_string(&baseline_path)
+                .unwrap_or_else(|_| panic!("no baseline; rerun with {}=1 to record one", UPDATE_BASELINE_VAR)),
+        )
+        .unwrap();
+
+        let regressions = regressions(&accuracy, &baseline);
+        assert!(
+            regressions.is_empty(),
+            "accuracy regressed:\n  {}\nrerun with {}=1 if the drop is intended",
+            regressions.join("\n  "),
+            UPDATE_BASELINE_VAR
+        );
+    }
+}
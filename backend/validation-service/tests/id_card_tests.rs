@@ -0,0 +1,129 @@
+mod fixtures;
+
+use fixtures::{BLANK_PNG, FixedTextOcr};
+use shared::{DocumentType, ValidationRequest};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::validators::driving_licence_validator::DrivingLicenceValidator;
+use validation_service::domain::validators::id_card_validator::IdCardValidator;
+
+/// Cartão de Cidadão specimen; its 12-character number overflows into the
+/// optional data.
+const PORTUGUESE_CC: &str = "I<PRT000000000<ZZ44<<<<<<<<<<<\n8001014F3101012PRT<<<<<<<<<<<2\nSILVA<<MARIA<JOAO<<<<<<<<<<<<<";
+
+/// As above with a wrong national check digit, but MRZ check digits to match.
+const PORTUGUESE_CC_BAD_NUMBER: &str =
+    "I<PRT000000000<ZZ55<<<<<<<<<<<\n8001014F3101012PRT<<<<<<<<<<<2\nSILVA<<MARIA<JOAO<<<<<<<<<<<<<";
+
+const MOROCCAN_ID: &str = "IDMARAB12345671<<<<<<<<<<<<<<<\n8001014M3101012MAR<<<<<<<<<<<0\nALAMI<<YOUSSEF<<<<<<<<<<<<<<<<";
+
+const TIE: &str = "TARJETA DE IDENTIDAD DE EXTRANJERO\nIRESPE123456782X1234567L<<<<<<\n8503220F3003220FRA<<<<<<<<<<<0\nDUPONT<BERNARD<<MARIA<<<<<<<<<";
+
+const SPANISH_LICENCE: &str = "PERMISO DE CONDUCCIÓN\n1. GARCÍA MARTÍNEZ\n2. JUAN CARLOS\n3. 15.06.1990 MADRID\n4a. 01.02.2015 4b. 01.02.2030\n4c. DGT\n5. 12345678Z";
+
+fn request(document_type: Option<DocumentType>) -> ValidationRequest {
+    ValidationRequest {
+        document_type,
+        front_image: BLANK_PNG.to_string(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod id_card_tests {
+    use super::*;
+
+    #[test]
+    fn test_ministry_codes() {
+        assert_eq!(DocumentType::DNI.ministry_code(), "NIF");
+        assert_eq!(DocumentType::NIE.ministry_code(), "NIE");
+        assert_eq!(DocumentType::TIE.ministry_code(), "NIE");
+        assert_eq!(DocumentType::Passport.ministry_code(), "PAS");
+        assert_eq!(DocumentType::EuIdCard.ministry_code(), "OTRO");
+        assert_eq!(DocumentType::DrivingLicence.ministry_code(), "OTRO");
+    }
+
+    #[test]
+    fn test_national_number_rules() {
+        let validator = IdCardValidator::new();
+
+        assert!(IdCardValidator::validate_portuguese_cc("000000000ZZ4"));
+        assert!(IdCardValidator::validate_portuguese_cc("00000000 0 ZZ4"));
+        assert!(!IdCardValidator::validate_portuguese_cc("000000000ZZ5"));
+        assert!(!IdCardValidator::validate_portuguese_cc("00000000ZZ4"));
+
+        assert!(IdCardValidator::validate_italian_cie("CA00000AA"));
+        assert!(!IdCardValidator::validate_italian_cie("AA1234567"));
+
+        // Germany is `D` in the MRZ
+        assert_eq!(validator.validate_number("D", "T22000129"), Some(true));
+        assert_eq!(validator.validate_number("D", "A22000129"), Some(false));
+        assert_eq!(validator.validate_number("FRA", "X4RTBPFW4"), None);
+    }
+
+    #[tokio::test]
+    async fn test_eu_identity_card_end_to_end() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(PORTUGUESE_CC)));
+        let response = service.validate_document(request(None)).await.unwrap();
+
+        assert_eq!(response.classification.unwrap().detected_type, DocumentType::EuIdCard);
+        assert_eq!(response.extracted_data.document_number.as_deref(), Some("000000000ZZ4"));
+        assert_eq!(response.extracted_data.surname.as_deref(), Some("SILVA"));
+        assert!(response.is_valid, "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_national_rule_catches_what_the_mrz_does_not() {
+        let service =
+            ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(PORTUGUESE_CC_BAD_NUMBER)));
+        let response = service.validate_document(request(Some(DocumentType::EuIdCard))).await.unwrap();
+
+        assert!(!response.is_valid);
+        assert_eq!(response.errors, vec!["Invalid PRT identity card number".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_identity_cards_from_outside_the_eu() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(MOROCCAN_ID)));
+        let response = service.validate_document(request(None)).await.unwrap();
+
+        assert_eq!(response.classification.unwrap().detected_type, DocumentType::EuIdCard);
+        assert!(!response.is_valid);
+        assert!(response.errors[0].contains("please use a passport"));
+    }
+
+    #[tokio::test]
+    async fn test_tie_keeps_card_number_as_support_number() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(TIE)));
+        let response = service.validate_document(request(Some(DocumentType::TIE))).await.unwrap();
+
+        assert_eq!(response.extracted_data.document_number.as_deref(), Some("X1234567L"));
+        assert_eq!(response.extracted_data.support_number.as_deref(), Some("E12345678"));
+        assert!(response.is_valid, "{:?}", response.errors);
+    }
+
+    #[test]
+    fn test_reads_numbered_licence_fields() {
+        let validator = DrivingLicenceValidator::new();
+        let data = validator.extract_licence_data(SPANISH_LICENCE).unwrap();
+
+        assert_eq!(data.surname.as_deref(), Some("GARCÍA MARTÍNEZ"));
+        assert_eq!(data.name.as_deref(), Some("JUAN CARLOS"));
+        assert_eq!(data.birth_date.unwrap().date_naive().to_string(), "1990-06-15");
+        assert_eq!(data.expiry_date.unwrap().date_naive().to_string(), "2030-02-01");
+        assert_eq!(data.document_number.as_deref(), Some("12345678Z"));
+
+        // Spanish licences carry the DNI, control letter included
+        assert!(validator.validate_licence_number("12345678Z"));
+        assert!(!validator.validate_licence_number("12345678A"));
+        assert!(validator.validate_licence_number("DL1234567"));
+    }
+
+    #[tokio::test]
+    async fn test_detects_driving_licence_from_its_title() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(SPANISH_LICENCE)));
+        let response = service.validate_document(request(None)).await.unwrap();
+
+        assert_eq!(response.classification.unwrap().detected_type, DocumentType::DrivingLicence);
+        assert!(response.is_valid, "{:?}", response.errors);
+    }
+}
@@ -0,0 +1,169 @@
+mod fixtures;
+
+use fixtures::*;
+use validation_service::adapters::rust_ocr::RustOcr;
+use validation_service::domain::ocr::image_processor::{
+    QualityIssue, Rotation, ID1_ASPECT, PASSPORT_PAGE_ASPECT,
+};
+use validation_service::domain::ocr::segmentation;
+use validation_service::domain::ocr::ImageProcessor;
+use validation_service::domain::validators::mrz_validator::MrzValidator;
+use validation_service::ports::ocr_client::OCRClient;
+
+const CARD_TEXT: [(&str, bool); 2] = [("APELLIDOS: GARCÍA", false), ("NOMBRE: JUAN", false)];
+
+const TD3: [(&str, bool); 2] = [
+    ("P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<", true),
+    ("L898902C36UTO7408122F1204159ZE184226B<<<<<10", true),
+];
+
+/// A TD3 data page, 880 by 620 pixels, its MRZ along the bottom.
+fn passport_page() -> image::GrayImage {
+    let mut page = image::GrayImage::from_pixel(880, 620, image::Luma([CARD]));
+    draw_lines(&mut page, &[("PASAPORTE", false)], 80);
+    draw_lines(&mut page, &TD3, 520);
+    page
+}
+
+/// Passport page corners in a 1000 by 750 photo.
+const PAGE_CORNERS: [(f32, f32); 4] = [(180.0, 140.0), (800.0, 180.0), (780.0, 610.0), (160.0, 560.0)];
+
+#[cfg(test)]
+mod image_processor_tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_card_outline() {
+        let photo = photograph(&id_card(&CARD_TEXT), PHOTO_CORNERS, 1000, 750);
+        let card = ImageProcessor::new().detect_card(&photo).expect("card outline");
+
+        for (found, expected) in card.corners.iter().zip(PHOTO_CORNERS) {
+            assert!((found.0 - expected.0).abs() < 8.0, "{:?} vs {:?}", found, expected);
+            assert!((found.1 - expected.1).abs() < 8.0, "{:?} vs {:?}", found, expected);
+        }
+    }
+
+    #[test]
+    fn test_no_card_outline_on_a_full_page_scan() {
+        assert_eq!(ImageProcessor::new().detect_card(&page(&CARD_TEXT)), None);
+    }
+
+    #[tokio::test]
+    async fn test_rectifies_card_photographed_at_an_angle() {
+        let photo = photograph(&id_card(&CARD_TEXT), PHOTO_CORNERS, 1000, 750);
+        let prepared = ImageProcessor::new().prepare(&photo);
+
+        let aspect = prepared.image.width() as f32 / prepared.image.height() as f32;
+        assert!((aspect - ID1_ASPECT).abs() < 0.01);
+        assert_eq!(prepared.rotation, Rotation::Upright);
+
+        let text = RustOcr::new().extract_text(&png(&photo)).await.unwrap();
+        assert_eq!(text, "APELLIDOS: GARCÍA\nNOMBRE: JUAN");
+    }
+
+    #[tokio::test]
+    async fn test_turns_upside_down_card() {
+        let [a, b, c, d] = PHOTO_CORNERS;
+        let photo = photograph(&id_card(&CARD_TEXT), [c, d, a, b], 1000, 750);
+        let prepared = ImageProcessor::new().prepare(&photo);
+
+        assert_eq!(prepared.rotation, Rotation::Half);
+        let text = RustOcr::new().extract_text(&png(&photo)).await.unwrap();
+        assert_eq!(text, "APELLIDOS: GARCÍA\nNOMBRE: JUAN");
+    }
+
+    #[test]
+    fn test_rectifies_passport_page_to_its_own_shape() {
+        let photo = photograph(&passport_page(), PAGE_CORNERS, 1000, 750);
+        let prepared = ImageProcessor::new().prepare(&photo);
+
+        let aspect = prepared.image.width() as f32 / prepared.image.height() as f32;
+        assert!((aspect - PASSPORT_PAGE_ASPECT).abs() < 0.01, "aspect {}", aspect);
+    }
+
+    #[test]
+    fn test_turns_upside_down_passport_page_by_its_mrz() {
+        let [a, b, c, d] = PAGE_CORNERS;
+        let photo = photograph(&passport_page(), [c, d, a, b], 1000, 750);
+        let prepared = ImageProcessor::new().prepare(&photo);
+
+        assert_eq!(prepared.rotation, Rotation::Half);
+    }
+
+    #[tokio::test]
+    async fn test_straightens_skewed_page() {
+        let skewed_page = skewed(&page(&CARD_TEXT), 4.0);
+        let skew = ImageProcessor::new().estimate_skew(&skewed_page);
+
+        assert!((skew - 4.0).abs() <= 0.3, "estimated {}", skew);
+        let text = RustOcr::new().extract_text(&png(&skewed_page)).await.unwrap();
+        assert_eq!(text, "APELLIDOS: GARCÍA\nNOMBRE: JUAN");
+    }
+
+    #[test]
+    fn test_detects_page_turned_a_quarter() {
+        let turned = image::imageops::rotate90(&page(&CARD_TEXT));
+        let rotation = ImageProcessor::new().detect_rotation(&turned, false);
+
+        assert_eq!(rotation, Rotation::ThreeQuarters);
+    }
+
+    #[test]
+    fn test_scores_blur() {
+        let processor = ImageProcessor::new();
+        let sharp = processor.assess_quality(&page(&CARD_TEXT));
+        let soft = processor.assess_quality(&blurred(&page(&CARD_TEXT), 0.8));
+        let out_of_focus = processor.assess_quality(&blurred(&page(&CARD_TEXT), 3.0));
+
+        assert!(sharp.issues.is_empty() && soft.issues.is_empty());
+        assert!(sharp.sharpness > soft.sharpness);
+        assert!(matches!(out_of_focus.issues[..], [QualityIssue::Blurred { .. }]));
+    }
+
+    #[test]
+    fn test_scores_glare() {
+        let processor = ImageProcessor::new();
+        let clean = processor.assess_quality(&id_card(&CARD_TEXT));
+        let flash = processor.assess_quality(&with_glare(&id_card(&CARD_TEXT), (430, 300), 90));
+
+        assert_eq!(clean.glare, 0.0);
+        assert!(clean.issues.is_empty());
+        assert!(matches!(flash.issues[..], [QualityIssue::Glare { share }] if share > 0.05));
+    }
+
+    #[test]
+    fn test_blank_page_has_nothing_to_reject() {
+        let quality = ImageProcessor::new().assess_quality(&page(&[]));
+
+        assert_eq!(quality.sharpness, None);
+        assert_eq!(quality.rejection_reason(), None);
+    }
+
+    #[test]
+    fn test_sauvola_survives_uneven_light() {
+        let lit = unevenly_lit(&page(&TD3));
+        let ink_share = |ink: &[bool]| ink.iter().filter(|&&i| i).count() as f32 / ink.len() as f32;
+
+        let global = segmentation::binarize(&lit);
+        let local = segmentation::sauvola(&lit, 49, 0.34);
+
+        assert!(ink_share(&global.ink) > 0.2);
+        assert!(ink_share(&local.ink) < 0.15);
+    }
+
+    #[tokio::test]
+    async fn test_reads_mrz_under_uneven_light() {
+        let text = RustOcr::new().extract_text(&png(&unevenly_lit(&page(&TD3)))).await.unwrap();
+
+        let mrz = MrzValidator::new().correct(&text).unwrap();
+        assert_eq!(mrz.lines, TD3.map(|(line, _)| line));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_blurred_photo_with_reason() {
+        let photo = blurred(&page(&CARD_TEXT), 3.0);
+        let error = RustOcr::new().extract_text(&png(&photo)).await.unwrap_err();
+
+        assert!(error.to_string().contains("too blurred"), "{}", error);
+    }
+}
@@ -0,0 +1,237 @@
+mod fixtures;
+
+use fixtures::{BLANK_PNG, FixedTextOcr};
+use chrono::{TimeZone, Utc};
+use shared::{
+    DocumentType, EnteredData, ExtractedData, FieldMatch, MatchVerdict, ValidationRequest,
+    ValidationResponse, ValidationWarningCode,
+};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::matching::{self, surnames, FieldMatcher};
+
+const DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 15/06/1990\nVÁLIDO HASTA: 10/01/2030";
+
+fn read(name: &str, surname: &str, nationality: &str) -> ExtractedData {
+    ExtractedData {
+        document_number: Some("12345678Z".to_string()),
+        name: Some(name.to_string()),
+        surname: Some(surname.to_string()),
+        birth_date: Some(Utc.with_ymd_and_hms(1990, 6, 15, 0, 0, 0).unwrap()),
+        nationality: Some(nationality.to_string()),
+        ..Default::default()
+    }
+}
+
+fn entered(first_name: &str, last_name_1: &str, last_name_2: Option<&str>) -> EnteredData {
+    EnteredData {
+        first_name: Some(first_name.to_string()),
+        last_name_1: Some(last_name_1.to_string()),
+        last_name_2: last_name_2.map(String::from),
+        ..Default::default()
+    }
+}
+
+fn field<'a>(matches: &'a [FieldMatch], name: &str) -> &'a FieldMatch {
+    matches
+        .iter()
+        .find(|m| m.field == name)
+        .unwrap_or_else(|| panic!("no {} in {:?}", name, matches))
+}
+
+async fn validate(entered_data: Option<EnteredData>) -> ValidationResponse {
+    ValidationService::new()
+        .with_ocr_client(Box::new(FixedTextOcr(DNI_FRONT)))
+        .validate_document(ValidationRequest {
+            document_type: Some(DocumentType::DNI),
+            front_image: BLANK_PNG.to_string(),
+            entered_data,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod matching_tests {
+    use super::*;
+
+    #[test]
+    fn test_icao_transliteration_and_plain_folding_both_match() {
+        assert_eq!(matching::icao_transliterate("Müller Ørsted"), "MUELLER OERSTED");
+        assert!(matching::equivalent("Müller", "MUELLER"));
+        assert!(matching::equivalent("Müller", "MULLER"));
+        assert!(matching::equivalent("Peña", "PENA"));
+        assert!(matching::equivalent("García-Pelayo", "GARCIA PELAYO"));
+        assert!(!matching::equivalent("Peña", "PENAS"));
+    }
+
+    #[test]
+    fn test_similarity_forgives_typos_and_word_order() {
+        assert!(matching::similarity("Martínez", "MARTINES") >= matching::SIMILAR_THRESHOLD);
+        assert_eq!(matching::similarity("María José", "JOSE MARIA"), 1.0);
+        assert!(matching::similarity("María", "MARIA DEL CARMEN") >= matching::SIMILAR_THRESHOLD);
+        assert!(matching::similarity("Juan", "PEDRO") < matching::SIMILAR_THRESHOLD);
+    }
+
+    #[test]
+    fn test_double_surnames_are_split() {
+        let split = |s| surnames::split(s, None);
+        assert_eq!(split("GARCIA MARTINEZ"), ("GARCIA".to_string(), Some("MARTINEZ".to_string())));
+        assert_eq!(split("DE LA FUENTE GARCIA"), ("DE LA FUENTE".to_string(), Some("GARCIA".to_string())));
+        assert_eq!(split("GARCIA DEL RIO"), ("GARCIA".to_string(), Some("DEL RIO".to_string())));
+        assert_eq!(split("ORTEGA Y GASSET LOPEZ"), ("ORTEGA Y GASSET".to_string(), Some("LOPEZ".to_string())));
+        assert_eq!(split("GARCÍA-PELAYO RUIZ"), ("GARCÍA-PELAYO".to_string(), Some("RUIZ".to_string())));
+        assert_eq!(split("GARCIA"), ("GARCIA".to_string(), None));
+    }
+
+    #[test]
+    fn test_typed_first_surname_settles_the_boundary() {
+        assert_eq!(
+            surnames::split("GARCIA LOPEZ DE HARO", Some("García López")),
+            ("GARCIA LOPEZ".to_string(), Some("DE HARO".to_string()))
+        );
+        // Without the hint the first word alone is taken
+        assert_eq!(
+            surnames::split("GARCIA LOPEZ DE HARO", None),
+            ("GARCIA".to_string(), Some("LOPEZ DE HARO".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_accents_typed_are_kept_over_the_mrz_spelling() {
+        let matches = FieldMatcher::new().compare(
+            &entered("José María", "Peña", Some("Núñez")),
+            &read("JOSE MARIA", "PENA NUNEZ", "ESP"),
+            DocumentType::DNI,
+        );
+
+        for (name, canonical) in [("first_name", "JOSÉ MARÍA"), ("last_name_1", "PEÑA"), ("last_name_2", "NÚÑEZ")] {
+            let found = field(&matches, name);
+            assert_eq!(found.verdict, MatchVerdict::Equivalent, "{:?}", found);
+            assert_eq!(found.canonical.as_deref(), Some(canonical));
+        }
+    }
+
+    #[test]
+    fn test_umlaut_matches_mrz_transliteration() {
+        let matches = FieldMatcher::new().compare(
+            &entered("Jürgen", "Müller", None),
+            &read("JUERGEN", "MUELLER", "D"),
+            DocumentType::Passport,
+        );
+        let last_name = field(&matches, "last_name_1");
+        assert_eq!(last_name.verdict, MatchVerdict::Equivalent);
+        assert_eq!(last_name.canonical.as_deref(), Some("MÜLLER"));
+        assert!(matches.iter().all(|m| m.field != "last_name_2"));
+    }
+
+    #[test]
+    fn test_foreign_surname_is_not_split() {
+        let matches = FieldMatcher::new().compare(
+            &entered("Anna", "Van der Berg", None),
+            &read("ANNA", "VAN DER BERG", "NLD"),
+            DocumentType::Passport,
+        );
+        assert_eq!(field(&matches, "last_name_1").verdict, MatchVerdict::Same);
+    }
+
+    #[test]
+    fn test_typo_is_similar_and_the_document_wins() {
+        let matches = FieldMatcher::new().compare(
+            &entered("Juan Carlos", "Garcia", Some("Martines")),
+            &read("JUAN CARLOS", "GARCIA MARTINEZ", "ESP"),
+            DocumentType::DNI,
+        );
+        let found = field(&matches, "last_name_2");
+        assert_eq!(found.verdict, MatchVerdict::Similar);
+        assert_eq!(found.canonical.as_deref(), Some("MARTINEZ"));
+        assert_eq!(field(&matches, "first_name").verdict, MatchVerdict::Same);
+    }
+
+    #[test]
+    fn test_swapped_names_are_told_from_different_ones() {
+        let matches = FieldMatcher::new().compare(
+            &entered("García", "Juan Carlos", None),
+            &read("JUAN CARLOS", "GARCIA", "ESP"),
+            DocumentType::DNI,
+        );
+        let first = field(&matches, "first_name");
+        assert_eq!(first.verdict, MatchVerdict::Swapped);
+        assert_eq!(first.canonical.as_deref(), Some("JUAN CARLOS"));
+        assert_eq!(field(&matches, "last_name_1").verdict, MatchVerdict::Swapped);
+
+        let matches = FieldMatcher::new().compare(
+            &entered("Pedro", "Sánchez", None),
+            &read("JUAN CARLOS", "GARCIA", "ESP"),
+            DocumentType::DNI,
+        );
+        assert_eq!(field(&matches, "first_name").verdict, MatchVerdict::Different);
+    }
+
+    #[test]
+    fn test_codes_and_dates_are_compared() {
+        let typed = EnteredData {
+            document_number: Some("12345678-z".to_string()),
+            birth_date: Some(Utc.with_ymd_and_hms(1990, 6, 15, 0, 0, 0).unwrap()),
+            nationality: Some("DEU".to_string()),
+            ..Default::default()
+        };
+        let matches = FieldMatcher::new().compare(&typed, &read("ANNA", "SCHMIDT", "D"), DocumentType::Passport);
+
+        let number = field(&matches, "document_number");
+        assert_eq!(number.verdict, MatchVerdict::Equivalent);
+        assert_eq!(number.canonical.as_deref(), Some("12345678Z"));
+        assert_eq!(field(&matches, "nationality").verdict, MatchVerdict::Equivalent);
+        assert_eq!(field(&matches, "birth_date").verdict, MatchVerdict::Same);
+        // Names only the document has are offered to fill in
+        let first = field(&matches, "first_name");
+        assert_eq!((first.verdict, first.canonical.as_deref()), (MatchVerdict::Missing, Some("ANNA")));
+    }
+
+    #[test]
+    fn test_day_and_month_swapped_is_similar() {
+        let typed = EnteredData {
+            birth_date: Some(Utc.with_ymd_and_hms(1990, 3, 12, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        let mut document = read("ANNA", "SCHMIDT", "D");
+        document.birth_date = Some(Utc.with_ymd_and_hms(1990, 12, 3, 0, 0, 0).unwrap());
+
+        let found = field(&FieldMatcher::new().compare(&typed, &document, DocumentType::Passport), "birth_date").clone();
+        assert_eq!(found.verdict, MatchVerdict::Similar);
+        assert_eq!(found.canonical.as_deref(), Some("1990-12-03"));
+
+        document.birth_date = Some(Utc.with_ymd_and_hms(1985, 7, 30, 0, 0, 0).unwrap());
+        let found = field(&FieldMatcher::new().compare(&typed, &document, DocumentType::Passport), "birth_date").clone();
+        assert_eq!(found.verdict, MatchVerdict::Different);
+    }
+
+    #[tokio::test]
+    async fn test_service_warns_when_typed_data_differs() {
+        let response = validate(Some(EnteredData {
+            first_name: Some("Juan Carlos".to_string()),
+            last_name_1: Some("García".to_string()),
+            last_name_2: Some("Fernández".to_string()),
+            document_number: Some("12345678Z".to_string()),
+            ..Default::default()
+        }))
+        .await;
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert_eq!(field(&response.field_matches, "last_name_1").canonical.as_deref(), Some("GARCÍA"));
+        assert_eq!(field(&response.field_matches, "last_name_2").verdict, MatchVerdict::Different);
+        let warning = response
+            .warnings
+            .iter()
+            .find(|w| w.code == ValidationWarningCode::EnteredDataMismatch)
+            .expect("mismatch warning");
+        assert!(warning.message.contains("last_name_2"), "{}", warning.message);
+    }
+
+    #[tokio::test]
+    async fn test_service_compares_nothing_without_typed_data() {
+        let response = validate(None).await;
+        assert!(response.field_matches.is_empty());
+        assert!(response.warnings.iter().all(|w| w.code != ValidationWarningCode::EnteredDataMismatch));
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::NaiveDate;
+use shared::ExtractedData;
+use validation_service::domain::validators::mrz_validator::{mrz_spellings, reconcile, MrzCheck, MrzFormat, MrzValidator, Sex};
+
+// Specimens from ICAO Doc 9303
+const TD3: &str = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\nL898902C36UTO7408122F1204159ZE184226B<<<<<10";
+const TD2: &str = "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\nD231458907UTO7408122F1204159<<<<<<<6";
+const TD1: &str = "I<UTOD231458907<<<<<<<<<<<<<<<\n7408122F1204159UTO<<<<<<<<<<<6\nERIKSSON<<ANNA<MARIA<<<<<<<<<<";
+
+#[cfg(test)]
+mod mrz_tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2010, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_check_digit() {
+        assert_eq!(MrzValidator::check_digit("L898902C3"), Some(6));
+        assert_eq!(MrzValidator::check_digit("740812"), Some(2));
+        assert_eq!(MrzValidator::check_digit("120415"), Some(9));
+        assert_eq!(MrzValidator::check_digit("<<<<<"), Some(0));
+        assert_eq!(MrzValidator::check_digit("abc"), None);
+    }
+
+    #[test]
+    fn test_td3_passport() {
+        let mrz = MrzValidator::new().parse_at(TD3, today()).unwrap();
+
+        assert_eq!(mrz.format, MrzFormat::TD3);
+        assert!(mrz.is_valid(), "failed: {:?}", mrz.failed_checks);
+        assert_eq!(mrz.document_code, "P");
+        assert_eq!(mrz.issuing_state, "UTO");
+        assert_eq!(mrz.document_number, "L898902C3");
+        assert_eq!(mrz.surname, "ERIKSSON");
+        assert_eq!(mrz.given_names, "ANNA MARIA");
+        assert_eq!(mrz.sex, Some(Sex::Female));
+        assert_eq!(mrz.birth_date, NaiveDate::from_ymd_opt(1974, 8, 12));
+        assert_eq!(mrz.expiry_date, NaiveDate::from_ymd_opt(2012, 4, 15));
+        assert_eq!(mrz.optional_data, "ZE184226B");
+    }
+
+    #[test]
+    fn test_td2_card() {
+        let mrz = MrzValidator::new().parse_at(TD2, today()).unwrap();
+
+        assert_eq!(mrz.format, MrzFormat::TD2);
+        assert!(mrz.is_valid(), "failed: {:?}", mrz.failed_checks);
+        assert_eq!(mrz.document_number, "D23145890");
+        assert_eq!(mrz.given_names, "ANNA MARIA");
+    }
+
+    #[test]
+    fn test_td1_card() {
+        let mrz = MrzValidator::new().parse_at(TD1, today()).unwrap();
+
+        assert_eq!(mrz.format, MrzFormat::TD1);
+        assert!(mrz.is_valid(), "failed: {:?}", mrz.failed_checks);
+        assert_eq!(mrz.document_number, "D23145890");
+        assert_eq!(mrz.nationality, "UTO");
+        assert_eq!(mrz.sex, Some(Sex::Female));
+        assert_eq!(mrz.surname, "ERIKSSON");
+        assert_eq!(mrz.given_names, "ANNA MARIA");
+    }
+
+    #[test]
+    fn test_td1_long_document_number() {
+        // 12-character number: 9 in place, 3 overflow, check digit 1 after them
+        let line1 = format!("{:<<30}", "I<UTOD23145890<AB11");
+        let line2 = "7408122F1204159UTO<<<<<<<<<<<";
+        let composite = format!("{}{}{}{}", &line1[5..30], &line2[0..7], &line2[8..15], &line2[18..29]);
+        let check = MrzValidator::check_digit(&composite).unwrap();
+        let text = format!("{}\n{}{}\nERIKSSON<<ANNA<MARIA<<<<<<<<<<", line1, line2, check);
+
+        let mrz = MrzValidator::new().parse_at(&text, today()).unwrap();
+
+        assert_eq!(mrz.document_number, "D23145890AB1");
+        assert!(!mrz.failed_checks.contains(&MrzCheck::DocumentNumber), "failed: {:?}", mrz.failed_checks);
+        assert!(mrz.is_valid());
+    }
+
+    #[test]
+    fn test_reports_failed_checks() {
+        // Birth date altered without updating its check digit
+        let tampered = TD3.replace("7408122F", "7508122F");
+        let mrz = MrzValidator::new().parse_at(&tampered, today()).unwrap();
+
+        assert!(!mrz.is_valid());
+        assert_eq!(mrz.failed_checks, vec![MrzCheck::BirthDate, MrzCheck::Composite]);
+        assert_eq!(MrzCheck::BirthDate.to_string(), "MRZ birth date check digit failed");
+    }
+
+    #[test]
+    fn test_birth_year_century() {
+        let validator = MrzValidator::new();
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        let young = TD3.replace("7408122", "0501012");
+        let mrz = validator.parse_at(&young, today).unwrap();
+        assert_eq!(mrz.birth_date, NaiveDate::from_ymd_opt(2005, 1, 1));
+
+        let old = TD3.replace("7408122", "3001015");
+        let mrz = validator.parse_at(&old, today).unwrap();
+        assert_eq!(mrz.birth_date, NaiveDate::from_ymd_opt(1930, 1, 1));
+    }
+
+    #[test]
+    fn test_finds_mrz_in_ocr_noise() {
+        let text = format!("PASAPORTE\nPASSPORT\nApellidos / Surname\n{}\n", TD3.replace('\n', "\n  "));
+        let mrz = MrzValidator::new().parse_at(&text, today()).unwrap();
+
+        assert_eq!(mrz.format, MrzFormat::TD3);
+        assert!(mrz.is_valid());
+    }
+
+    #[test]
+    fn test_title_of_mrz_length_is_not_mrz() {
+        // 30 characters once the spaces are gone, like a TD1 line
+        let text = format!("TARJETA DE IDENTIDAD DE EXTRANJERO\n{}", TD1);
+        let (_, lines) = MrzValidator::new().find_mrz_lines(&text).unwrap();
+
+        assert_eq!(lines.join("\n"), TD1);
+    }
+
+    #[test]
+    fn test_no_mrz() {
+        assert!(MrzValidator::new().parse("no machine readable zone here").is_err());
+    }
+
+    #[test]
+    fn test_corrects_digits_read_as_letters() {
+        // Blurry photo: dates and check digits came back with O, I, S and B
+        let blurry = TD3.replace("7408122F1204159", "74O8I22F12O4IS9").replace("B<<<<<10", "B<<<<<IO");
+        let corrected = MrzValidator::new().correct_at(&blurry, today()).unwrap();
+
+        assert_eq!(corrected.lines.join("\n"), TD3);
+        assert!(corrected.data.is_valid(), "failed: {:?}", corrected.data.failed_checks);
+        assert_eq!(corrected.corrections.len(), 7);
+        assert_eq!(corrected.confidence[1][15], 0.8);
+        assert_eq!(corrected.confidence[1][0], 1.0);
+    }
+
+    #[test]
+    fn test_corrects_document_number_by_check_digit() {
+        // O and 0 are both legal in a document number; only one reading validates
+        let blurry = TD3.replace("L898902C3", "L8989O2C3");
+        let corrected = MrzValidator::new().correct_at(&blurry, today()).unwrap();
+
+        assert_eq!(corrected.data.document_number, "L898902C3");
+        assert!(corrected.data.is_valid());
+        assert_eq!(corrected.corrections.len(), 1);
+        assert!(corrected.confidence[1][5] > 0.0 && corrected.confidence[1][5] <= 0.9);
+    }
+
+    #[test]
+    fn test_corrects_filler_read_as_k() {
+        let blurry = TD3.replace("MARIA<<<<<<", "MARIA<<K<K<");
+        let corrected = MrzValidator::new().correct_at(&blurry, today()).unwrap();
+
+        assert_eq!(corrected.data.surname, "ERIKSSON");
+        assert_eq!(corrected.data.given_names, "ANNA MARIA");
+        assert_eq!(corrected.corrections.len(), 2);
+    }
+
+    #[test]
+    fn test_correction_keeps_genuine_failures() {
+        let tampered = TD3.replace("7408122F", "7508122F");
+        let corrected = MrzValidator::new().correct_at(&tampered, today()).unwrap();
+
+        assert!(corrected.corrections.is_empty());
+        assert!(!corrected.data.is_valid());
+    }
+
+    #[test]
+    fn test_extracted_data_carries_sex() {
+        let data = MrzValidator::new().extract_mrz_data(TD3).unwrap();
+
+        assert_eq!(data.document_number.as_deref(), Some("L898902C3"));
+        assert_eq!(data.sex.as_deref(), Some("F"));
+    }
+
+    #[test]
+    fn test_reconcile_prefers_mrz_and_reports_conflicts() {
+        let mrz = MrzValidator::new().parse_at(TD3, today()).unwrap().to_extracted_data();
+        let visual = ExtractedData {
+            document_number: Some("L898902C8".to_string()),
+            name: Some("Anna María".to_string()),
+            surname: Some("ERIKSSON".to_string()),
+            birth_date: mrz.birth_date,
+            nationality: Some("SUECA".to_string()),
+            ..ExtractedData::default()
+        };
+
+        let (merged, conflicts) = reconcile(&mrz, &visual);
+
+        assert_eq!(merged.document_number.as_deref(), Some("L898902C3"));
+        assert_eq!(merged.name.as_deref(), Some("ANNA MARIA"));
+        assert_eq!(merged.nationality.as_deref(), Some("UTO"));
+        assert_eq!(conflicts.len(), 1, "conflicts: {:?}", conflicts);
+        assert_eq!(conflicts[0].field, "document_number");
+        assert_eq!(conflicts[0].visual_value, "L898902C8");
+    }
+
+    #[test]
+    fn test_reconcile_accepts_icao_transliterations() {
+        let mrz = ExtractedData {
+            name: Some("JOSE".to_string()),
+            surname: Some("MUELLER PENXXA".to_string()),
+            ..ExtractedData::default()
+        };
+        let visual = ExtractedData {
+            name: Some("José".to_string()),
+            surname: Some("MÜLLER PEÑA".to_string()),
+            ..ExtractedData::default()
+        };
+
+        let (merged, conflicts) = reconcile(&mrz, &visual);
+
+        assert!(conflicts.is_empty(), "conflicts: {:?}", conflicts);
+        assert_eq!(merged.surname.as_deref(), Some("MUELLER PENXXA"));
+
+        // A card that drops the umlaut agrees too, but a different name does not
+        let folded = ExtractedData {
+            surname: Some("MULLER PENA".to_string()),
+            ..ExtractedData::default()
+        };
+        assert!(reconcile(&folded, &visual).1.is_empty());
+        let other = ExtractedData {
+            surname: Some("MOLLER PENA".to_string()),
+            ..ExtractedData::default()
+        };
+        assert_eq!(reconcile(&other, &visual).1.len(), 1);
+    }
+
+    #[test]
+    fn test_mrz_spellings() {
+        assert_eq!(mrz_spellings("Müller"), vec!["MULLER", "MUELLER"]);
+        assert_eq!(mrz_spellings("Peña"), vec!["PENA", "PENXXA"]);
+        assert_eq!(mrz_spellings("García"), vec!["GARCIA"]);
+    }
+}
@@ -0,0 +1,77 @@
+mod fixtures;
+
+use fixtures::{page, png};
+use validation_service::adapters::rust_ocr::RustOcr;
+use validation_service::domain::ocr::glyph_model::GlyphModel;
+use validation_service::domain::validators::mrz_validator::MrzValidator;
+use validation_service::ports::ocr_client::OCRClient;
+
+/// Prints `lines` in the fixture font: a synthetic document whose expected
+/// reading is known exactly.
+fn synthetic_document(lines: &[(&str, bool)]) -> Vec<u8> {
+    png(&page(lines))
+}
+
+#[cfg(test)]
+mod ocr_engine_tests {
+    use super::*;
+
+    const TD3: [&str; 2] = [
+        "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<",
+        "L898902C36UTO7408122F1204159ZE184226B<<<<<10",
+    ];
+
+    #[tokio::test]
+    async fn test_reads_visual_zone_words_with_boxes() {
+        let png = synthetic_document(&[("NOMBRE: JUAN", false), ("APELLIDOS: GARCÍA", false)]);
+        let result = RustOcr::new().extract_text_with_confidence(&png).await.unwrap();
+
+        assert_eq!(result.text, "NOMBRE: JUAN\nAPELLIDOS: GARCÍA");
+        assert_eq!(result.words.len(), 4);
+        assert!(result.words[0].bbox.right() < result.words[1].bbox.x);
+        assert!(result.words[1].bbox.bottom() < result.words[2].bbox.y);
+        assert!(result.words.iter().all(|w| w.confidence > 0.5 && !w.mrz));
+    }
+
+    #[tokio::test]
+    async fn test_lowercase_words_keep_their_case() {
+        let png = synthetic_document(&[("Nacionalidad: Española", false)]);
+        let result = RustOcr::new().extract_text(&png).await.unwrap();
+
+        assert_eq!(result, "Nacionalidad: Española");
+    }
+
+    #[tokio::test]
+    async fn test_reads_mrz_with_mrz_model() {
+        let png = synthetic_document(&[("PASAPORTE", false), (TD3[0], true), (TD3[1], true)]);
+        let result = RustOcr::new().extract_text_with_confidence(&png).await.unwrap();
+
+        let (title, mrz) = result.text.split_once('\n').unwrap();
+        assert_eq!(title, "PASAPORTE");
+        // `O` and `0` can be one shape to the model; the MRZ's field
+        // alphabets and check digits tell them apart
+        assert_eq!(MrzValidator::new().correct(mrz).unwrap().lines, TD3);
+        assert!(result.words[1].mrz && result.words[2].mrz);
+        assert!(result.confidence > 0.5, "confidence {}", result.confidence);
+    }
+
+    #[tokio::test]
+    async fn test_blank_page() {
+        let png = synthetic_document(&[]);
+        let result = RustOcr::new().extract_text_with_confidence(&png).await.unwrap();
+
+        assert!(result.text.is_empty());
+        assert_eq!(result.confidence, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_undecodable_image() {
+        assert!(RustOcr::new().extract_text(b"not an image").await.is_err());
+    }
+
+    #[test]
+    fn test_models_cover_their_alphabets() {
+        assert_eq!(GlyphModel::mrz().alphabet().len(), 37);
+        assert!(GlyphModel::latin().alphabet().contains(&'Ñ'));
+    }
+}
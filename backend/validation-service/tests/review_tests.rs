@@ -0,0 +1,253 @@
+mod fixtures;
+
+use fixtures::{FixedTextOcr, MemoryStore, BLANK_PNG};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use shared::{AlbergueError, DocumentReview, DocumentType, ReviewStatus, ValidationRequest};
+use std::collections::BTreeMap;
+use validation_service::adapters::db_audit_log;
+use validation_service::adapters::db_review_store;
+use validation_service::adapters::memory_audit_log::MemoryAuditLog;
+use validation_service::adapters::memory_review_store::MemoryReviewStore;
+use validation_service::adapters::spin_db::{sqlite_sql, Column};
+use validation_service::application::review_service::ReviewService;
+use validation_service::application::validation_service::{ValidationService, DEFAULT_REVIEW_CONFIDENCE};
+use validation_service::domain::portrait::PortraitCipher;
+use validation_service::domain::review::{self, AuditAction, Reviewer};
+use validation_service::ports::review_store::ReviewStore;
+
+const DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 15/06/1990\nVÁLIDO HASTA: 10/01/2030";
+
+/// A smudged name and no birth date.
+const SMUDGED_DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAM CARLOS\nVÁLIDO HASTA: 10/01/2030";
+
+fn hospitalero() -> Reviewer {
+    Reviewer {
+        user_id: "auth0|hospitalero".to_string(),
+        user_role: "admin".to_string(),
+        request_id: Some("req-1".to_string()),
+    }
+}
+
+fn corrections(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(field, value)| (field.to_string(), value.to_string())).collect()
+}
+
+fn service(text: &'static str, store: &MemoryReviewStore) -> ValidationService {
+    ValidationService::new()
+        .with_ocr_client(Box::new(FixedTextOcr(text)))
+        .with_review_queue(Box::new(store.clone()), DEFAULT_REVIEW_CONFIDENCE)
+}
+
+fn request() -> ValidationRequest {
+    ValidationRequest {
+        document_type: Some(DocumentType::DNI),
+        front_image: BLANK_PNG.to_string(),
+        ..Default::default()
+    }
+}
+
+/// The smudged DNI, queued, and the review service over its queue.
+async fn queued() -> (DocumentReview, ReviewService, MemoryAuditLog) {
+    let store = MemoryReviewStore::new();
+    let response = service(SMUDGED_DNI_FRONT, &store).validate_document(request()).await.unwrap();
+    let id = response.review_id.expect("queued for review");
+    let audit_log = MemoryAuditLog::new();
+    let reviews = ReviewService::new(Box::new(store), Box::new(audit_log.clone()));
+    (reviews.get(id).await.unwrap(), reviews, audit_log)
+}
+
+#[cfg(test)]
+mod review_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unsure_reading_is_queued_with_its_fields() {
+        let (review, reviews, _) = queued().await;
+
+        assert_eq!(review.status, ReviewStatus::Pending);
+        assert_eq!(review.document_type, DocumentType::DNI);
+        assert_eq!(review.extracted_data.name.as_deref(), Some("JUAM CARLOS"));
+        assert!(review.fields_to_review.contains(&"birth_date".to_string()), "{:?}", review.fields_to_review);
+        assert!(review.field_confidence.contains_key("name"));
+        assert_eq!(review.ocr_text, vec![SMUDGED_DNI_FRONT.to_string()]);
+        // No image storage is configured
+        assert_eq!(review.front_image_url, None);
+        assert_eq!(reviews.pending().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sure_reading_is_not_queued() {
+        let store = MemoryReviewStore::new();
+        let response = service(DNI_FRONT, &store).validate_document(request()).await.unwrap();
+
+        assert!(!response.requires_manual_review, "{:?}", response.fields_to_review);
+        assert_eq!(response.review_id, None);
+        assert!(store.list(ReviewStatus::Pending).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_nothing_is_queued_without_a_review_queue() {
+        let response = ValidationService::new()
+            .with_ocr_client(Box::new(FixedTextOcr(SMUDGED_DNI_FRONT)))
+            .validate_document(request())
+            .await
+            .unwrap();
+        assert!(response.requires_manual_review);
+        assert_eq!(response.review_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_queued_uploads_are_sealed_in_the_portrait_store() {
+        let store = MemoryReviewStore::new();
+        let images = MemoryStore::default();
+        let cipher = PortraitCipher::new(&[7; 32]);
+        let response = service(SMUDGED_DNI_FRONT, &store)
+            .with_portrait_store(Box::new(images.clone()), PortraitCipher::new(&[7; 32]))
+            .validate_document(request())
+            .await
+            .unwrap();
+
+        let review = store.get(response.review_id.unwrap()).await.unwrap().unwrap();
+        let (key, sealed) = images.0.borrow()[0].clone();
+        assert_eq!(review.front_image_url, Some(format!("memory://{}", key.path())));
+        assert_eq!(cipher.open(&key, &sealed).unwrap(), base64::decode(BLANK_PNG).unwrap());
+        assert!(key.retain_until <= (Utc::now() + Duration::days(30)).date_naive());
+    }
+
+    #[tokio::test]
+    async fn test_corrections_are_audited_and_kept_apart_from_the_reading() {
+        let (review, reviews, audit_log) = queued().await;
+
+        let corrected = reviews
+            .correct(review.id, corrections(&[("name", "JUAN CARLOS")]), &hospitalero())
+            .await
+            .unwrap();
+        assert_eq!(corrected.corrections, corrections(&[("name", "JUAN CARLOS")]));
+        assert_eq!(corrected.extracted_data.name.as_deref(), Some("JUAM CARLOS"));
+        assert_eq!(corrected.status, ReviewStatus::Pending);
+
+        let entries = audit_log.entries();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!((entry.table_name.as_str(), entry.record_id), (review::AUDIT_TABLE, review.id));
+        assert_eq!(entry.action, AuditAction::Update);
+        assert_eq!(entry.user_id, "auth0|hospitalero");
+        assert_eq!(entry.request_id.as_deref(), Some("req-1"));
+        assert_eq!(entry.changed_fields, vec!["corrections"]);
+        assert_eq!(entry.old_values["corrections"], serde_json::json!({}));
+        assert_eq!(entry.new_values["corrections"]["name"], "JUAN CARLOS");
+    }
+
+    #[tokio::test]
+    async fn test_bad_corrections_are_refused() {
+        let (review, reviews, audit_log) = queued().await;
+
+        for bad in [("favourite_colour", "blue"), ("birth_date", "15/06/1990"), ("sex", "male")] {
+            let error = reviews.correct(review.id, corrections(&[bad]), &hospitalero()).await;
+            assert!(matches!(error, Err(AlbergueError::Validation { .. })), "{:?}", bad);
+        }
+        assert!(audit_log.entries().is_empty());
+        assert!(reviews.get(review.id).await.unwrap().corrections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_approval_applies_corrections_and_yields_a_golden_case() {
+        let (review, reviews, audit_log) = queued().await;
+        reviews
+            .correct(review.id, corrections(&[("name", "JUAN CARLOS")]), &hospitalero())
+            .await
+            .unwrap();
+
+        let approved = reviews
+            .approve(review.id, corrections(&[("birth_date", "1990-06-15")]), &hospitalero())
+            .await
+            .unwrap();
+
+        assert_eq!(approved.review.status, ReviewStatus::Approved);
+        assert_eq!(approved.review.decided_by.as_deref(), Some("auth0|hospitalero"));
+        assert_eq!(approved.data.name.as_deref(), Some("JUAN CARLOS"));
+        assert_eq!(approved.data.birth_date.map(|d| d.date_naive()), NaiveDate::from_ymd_opt(1990, 6, 15));
+        assert_eq!(audit_log.entries().len(), 2);
+        assert_eq!(audit_log.entries()[1].new_values["status"], "Approved");
+
+        let case: serde_json::Value = serde_json::from_str(&approved.golden_case.to_json()).unwrap();
+        assert_eq!(case["document_type"], "DNI");
+        assert_eq!(case["front"]["capture"], "photo");
+        assert!(case.get("back").is_none());
+        let lines: Vec<&str> = case["front"]["lines"].as_array().unwrap().iter().filter_map(|l| l.as_str()).collect();
+        assert!(lines.contains(&"NOMBRE: JUAN CARLOS"), "{:?}", lines);
+        assert_eq!(case["expected"]["is_valid"], true);
+        assert_eq!(case["expected"]["fields"]["name"], "JUAN CARLOS");
+        assert_eq!(case["expected"]["fields"]["birth_date"], "1990-06-15");
+        assert_eq!(case["expected"]["fields"]["document_number"], "12345678Z");
+
+        assert_eq!(reviews.golden_cases().await.unwrap(), vec![approved.golden_case]);
+        assert!(reviews.pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejection_needs_a_reason_and_ends_the_review() {
+        let (review, reviews, audit_log) = queued().await;
+
+        assert!(reviews.reject(review.id, "  ", &hospitalero()).await.is_err());
+        let rejected = reviews
+            .reject(review.id, "Photo of a photocopy", &hospitalero())
+            .await
+            .unwrap();
+        assert_eq!(rejected.status, ReviewStatus::Rejected);
+        assert_eq!(rejected.rejection_reason.as_deref(), Some("Photo of a photocopy"));
+        assert_eq!(audit_log.entries()[0].new_values["rejection_reason"], "Photo of a photocopy");
+
+        // Decided once only
+        let again = reviews.approve(review.id, BTreeMap::new(), &hospitalero()).await;
+        assert!(matches!(again, Err(AlbergueError::Validation { .. })));
+        assert_eq!(audit_log.entries().len(), 1);
+        assert!(reviews.golden_cases().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_review_is_not_found() {
+        let (_, reviews, _) = queued().await;
+        let missing = reviews.get(uuid::Uuid::new_v4()).await;
+        assert!(matches!(missing, Err(AlbergueError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_reviews_and_decisions_map_to_database_rows() {
+        let (review, reviews, audit_log) = queued().await;
+        let mut approved = reviews
+            .approve(review.id, corrections(&[("name", "JUAN CARLOS")]), &hospitalero())
+            .await
+            .unwrap()
+            .review;
+        // Timestamps are kept to the microsecond
+        approved.created_at = Utc.with_ymd_and_hms(2026, 5, 1, 9, 30, 0).unwrap();
+        approved.decided_at = Some(approved.created_at + Duration::minutes(5));
+
+        let row = db_review_store::to_row(&approved).unwrap();
+        assert_eq!(row[1], Column::Text("approved".to_string()));
+        assert_eq!(row[2], Column::Text("DNI".to_string()));
+        let read = db_review_store::from_row(row.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&approved).unwrap());
+
+        // As Postgres prints `created_at::text`
+        let mut from_postgres = row;
+        from_postgres[12] = Column::Text("2026-05-01 09:30:00.5+00".to_string());
+        let read = db_review_store::from_row(from_postgres).unwrap();
+        assert_eq!(read.created_at, approved.created_at + Duration::milliseconds(500));
+
+        let entry = db_audit_log::to_row(&audit_log.entries()[0]).unwrap();
+        assert_eq!(entry[0], Column::Text("document_reviews".to_string()));
+        assert_eq!(entry[1], Column::Text(review.id.to_string()));
+        assert_eq!(entry[2], Column::Text("UPDATE".to_string()));
+        assert_eq!(entry[8], Column::Text("req-1".to_string()));
+    }
+
+    #[test]
+    fn test_sqlite_statements_drop_postgres_casts() {
+        assert_eq!(
+            sqlite_sql("SELECT id::text FROM t WHERE id = $1::text::uuid AND at < $2::text::timestamptz"),
+            "SELECT id FROM t WHERE id = ?1 AND at < ?2"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+mod fixtures;
+
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
+
+use fixtures::*;
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
+use lopdf::{dictionary, Document, Object, Stream};
+use shared::{AlbergueError, AlbergueResult, DocumentType, ValidationRequest};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::ocr::recognizer::OcrResult;
+use validation_service::domain::upload::{UploadDecoder, UploadFormat, UploadLimits, OCR_MAX_SIDE};
+use validation_service::ports::image_converter::ImageConverter;
+use validation_service::ports::ocr_client::OCRClient;
+
+const DNI_FRONT: &str = "DNI 12345678Z\nAPELLIDOS: GARCIA MARTINEZ\nNOMBRE: JUAN CARLOS\nFECHA DE NACIMIENTO: 15/06/1990\nVÁLIDO HASTA: 10/01/2030";
+
+/// A 1x1 lossless WebP.
+const WEBP: &str = "UklGRhoAAABXRUJQVlA4TA0AAAAvAAAAEAcQERGIiP4HAA==";
+
+/// The start of an iPhone photo: an ISO media box of brand `heic`.
+const HEIC_HEADER: &[u8] = b"\0\0\0\x18ftypheic\0\0\0\0mif1heic";
+
+/// Reads the same text off any image, counting the images it is given.
+#[derive(Clone)]
+struct CountingOcr(Rc<Cell<usize>>);
+
+#[async_trait::async_trait(?Send)]
+impl OCRClient for CountingOcr {
+    async fn extract_text(&self, image_data: &[u8]) -> AlbergueResult<String> {
+        Ok(self.extract_text_with_confidence(image_data).await?.text)
+    }
+
+    async fn extract_text_with_confidence(&self, image_data: &[u8]) -> AlbergueResult<OcrResult> {
+        // Every image reaching OCR must be one it can decode
+        assert!(image::load_from_memory(image_data).is_ok());
+        self.0.set(self.0.get() + 1);
+        Ok(OcrResult {
+            text: DNI_FRONT.to_string(),
+            confidence: 1.0,
+            words: vec![],
+        })
+    }
+}
+
+/// Answers every conversion with the same JPEG.
+struct FixedConverter(Vec<u8>);
+
+#[async_trait::async_trait(?Send)]
+impl ImageConverter for FixedConverter {
+    async fn to_jpeg(&self, data: &[u8], format: UploadFormat) -> AlbergueResult<Vec<u8>> {
+        assert_eq!(format, UploadFormat::Heif);
+        assert!(data.starts_with(HEIC_HEADER));
+        Ok(self.0.clone())
+    }
+}
+
+fn jpeg(image: &GrayImage) -> Vec<u8> {
+    let mut jpeg = vec![];
+    DynamicImage::ImageLuma8(image.clone())
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+    jpeg
+}
+
+/// A grey gradient, different in each direction.
+fn gradient(width: u32, height: u32) -> GrayImage {
+    GrayImage::from_fn(width, height, |x, y| Luma([((x * 200 / width) + (y * 50 / height)) as u8]))
+}
+
+fn jpeg_image(jpeg: &[u8], width: u32, height: u32) -> Stream {
+    Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width as i64,
+            "Height" => height as i64,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+            "Filter" => "DCTDecode",
+        },
+        jpeg.to_vec(),
+    )
+}
+
+/// Grey samples deflated with the PNG Up predictor, as img2pdf writes them.
+fn flate_image(image: &GrayImage) -> Stream {
+    let width = image.width() as usize;
+    let mut filtered = vec![];
+    for (y, row) in image.as_raw().chunks(width).enumerate() {
+        filtered.push(2);
+        for (x, &v) in row.iter().enumerate() {
+            let up = if y > 0 { image.as_raw()[(y - 1) * width + x] } else { 0 };
+            filtered.push(v.wrapping_sub(up));
+        }
+    }
+    let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(&filtered).unwrap();
+
+    Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => image.width() as i64,
+            "Height" => image.height() as i64,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+            "Filter" => "FlateDecode",
+            "DecodeParms" => dictionary! { "Predictor" => 15, "Colors" => 1, "Columns" => image.width() as i64 },
+        },
+        encoder.finish().unwrap(),
+    )
+}
+
+/// A PDF with a page per entry, each showing its image, if any, turned by
+/// its rotation.
+fn pdf(pages: Vec<(Option<Stream>, i64)>) -> Vec<u8> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let mut kids = vec![];
+    for (image, rotate) in pages {
+        let mut resources = dictionary! {};
+        let mut content = b"BT /F1 12 Tf 72 720 Td (Terms) Tj ET".to_vec();
+        if let Some(image) = image {
+            let image_id = doc.add_object(image);
+            resources.set("XObject", dictionary! { "Im0" => image_id });
+            content = b"q 595 0 0 842 0 0 cm /Im0 Do Q".to_vec();
+        }
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Contents" => content_id,
+            "Resources" => resources,
+            "Rotate" => rotate,
+        });
+        kids.push(Object::Reference(page_id));
+    }
+    let count = kids.len() as i64;
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => count }),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut out = vec![];
+    doc.save_to(&mut out).unwrap();
+    out
+}
+
+fn validation_message(result: AlbergueResult<impl std::fmt::Debug>) -> String {
+    match result {
+        Err(AlbergueError::Validation { message }) => message,
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+fn dimensions(page: &[u8]) -> (u32, u32) {
+    image::load_from_memory(page).unwrap().dimensions()
+}
+
+fn request(front: &[u8], back: Option<&[u8]>) -> ValidationRequest {
+    ValidationRequest {
+        document_type: Some(DocumentType::DNI),
+        front_image: base64::encode(front),
+        back_image: back.map(base64::encode),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod upload_tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_are_told_by_content() {
+        let cases: [(&[u8], Option<UploadFormat>); 6] = [
+            (&jpeg(&gradient(8, 8)), Some(UploadFormat::Jpeg)),
+            (&png(&gradient(8, 8)), Some(UploadFormat::Png)),
+            (&base64::decode(WEBP).unwrap(), Some(UploadFormat::WebP)),
+            (HEIC_HEADER, Some(UploadFormat::Heif)),
+            (b"%PDF-1.7\n", Some(UploadFormat::Pdf)),
+            (b"GIF89a", None),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(UploadFormat::sniff(data), expected);
+        }
+        assert_eq!(UploadFormat::from_extension(".JPG"), Some(UploadFormat::Jpeg));
+        assert_eq!(UploadFormat::from_extension("heif"), Some(UploadFormat::Heif));
+        assert_eq!(UploadFormat::from_extension("tiff"), None);
+    }
+
+    #[test]
+    fn test_small_images_pass_through_untouched() {
+        let data = jpeg(&gradient(800, 500));
+        let upload = UploadDecoder::new().decode(&data).unwrap();
+
+        assert_eq!(upload.format, UploadFormat::Jpeg);
+        assert_eq!(upload.pages, vec![data]);
+    }
+
+    #[test]
+    fn test_large_images_are_scaled_for_ocr() {
+        let data = jpeg(&gradient(4000, 3000));
+        let upload = UploadDecoder::new().decode(&data).unwrap();
+
+        assert_eq!(upload.pages.len(), 1);
+        assert!(upload.pages[0].starts_with(b"\x89PNG"));
+        assert_eq!(dimensions(&upload.pages[0]), (OCR_MAX_SIDE, 1500));
+        assert_eq!(upload.originals, vec![data]);
+    }
+
+    #[test]
+    fn test_webp_is_decoded() {
+        let upload = UploadDecoder::new().decode(&base64::decode(WEBP).unwrap()).unwrap();
+
+        assert_eq!(upload.format, UploadFormat::WebP);
+        assert_eq!(dimensions(&upload.pages[0]), (1, 1));
+    }
+
+    #[test]
+    fn test_limits_are_enforced() {
+        let decoder = UploadDecoder::new().with_limits(UploadLimits {
+            max_bytes: 1024 * 1024,
+            max_pixels: 1_000_000,
+            formats: vec![UploadFormat::Jpeg, UploadFormat::Png],
+        });
+
+        let message = validation_message(decoder.decode(&png(&gradient(1200, 1000))));
+        assert!(message.contains("1200x1000"), "{}", message);
+
+        // Refused by its length alone, before the bad encoding is seen
+        let oversized = "!".repeat(2 * 1024 * 1024);
+        let message = validation_message(decoder.decode_base64(&oversized, "front"));
+        assert_eq!(message, "The front image is larger than 1 MB");
+
+        let message = validation_message(decoder.decode(&base64::decode(WEBP).unwrap()));
+        assert_eq!(message, "Unsupported file format; send one of JPEG, PNG");
+    }
+
+    #[test]
+    fn test_scanned_pdf_gives_a_page_each() {
+        let front = jpeg(&gradient(856, 540));
+        let back = gradient(640, 400);
+        let data = pdf(vec![
+            (Some(jpeg_image(&front, 856, 540)), 0),
+            (Some(flate_image(&back)), 0),
+        ]);
+        let upload = UploadDecoder::new().decode(&data).unwrap();
+
+        assert_eq!(upload.format, UploadFormat::Pdf);
+        assert_eq!(upload.pages.len(), 2);
+        // The embedded JPEG as it was; the raw samples exactly
+        assert_eq!(upload.pages[0], front);
+        assert_eq!(image::load_from_memory(&upload.pages[1]).unwrap().to_luma8(), back);
+        assert_eq!(upload.originals[0], front);
+        assert_eq!(image::load_from_memory(&upload.originals[1]).unwrap().to_luma8(), back);
+    }
+
+    #[test]
+    fn test_pdf_page_rotation_is_applied() {
+        let scan = gradient(300, 200);
+        let upload = UploadDecoder::new().decode(&pdf(vec![(Some(flate_image(&scan)), 90)])).unwrap();
+
+        let page = image::load_from_memory(&upload.pages[0]).unwrap().to_luma8();
+        assert_eq!(page.dimensions(), (200, 300));
+        assert_eq!(page, image::imageops::rotate90(&scan));
+    }
+
+    #[test]
+    fn test_pdf_without_a_scan_is_refused() {
+        let message = validation_message(UploadDecoder::new().decode(&pdf(vec![(None, 0)])));
+        assert!(message.contains("no scanned image"), "{}", message);
+
+        // A page of terms after the scan is skipped
+        let scan = jpeg(&gradient(856, 540));
+        let upload = UploadDecoder::new()
+            .decode(&pdf(vec![(Some(jpeg_image(&scan, 856, 540)), 0), (None, 0)]))
+            .unwrap();
+        assert_eq!(upload.pages.len(), 1);
+    }
+
+    #[test]
+    fn test_pdf_images_over_the_pixel_limit_are_not_inflated() {
+        let decoder = UploadDecoder::new().with_limits(UploadLimits {
+            max_pixels: 100_000,
+            ..UploadLimits::default()
+        });
+        let message = validation_message(decoder.decode(&pdf(vec![(Some(flate_image(&gradient(640, 400))), 0)])));
+        assert!(message.starts_with("Page 1 of the PDF"), "{}", message);
+        assert!(message.contains("640x400"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_pdf_second_page_is_the_back() {
+        let calls = Rc::new(Cell::new(0));
+        let data = pdf(vec![
+            (Some(jpeg_image(&jpeg(&gradient(856, 540)), 856, 540)), 0),
+            (Some(flate_image(&gradient(856, 540))), 0),
+        ]);
+        let response = ValidationService::new()
+            .with_ocr_client(Box::new(CountingOcr(calls.clone())))
+            .validate_document(request(&data, None))
+            .await
+            .unwrap();
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_heic_needs_a_converter() {
+        let calls = Rc::new(Cell::new(0));
+        let message = validation_message(
+            ValidationService::new()
+                .with_ocr_client(Box::new(CountingOcr(calls.clone())))
+                .validate_document(request(HEIC_HEADER, None))
+                .await,
+        );
+        assert!(message.contains("Most Compatible"), "{}", message);
+        assert_eq!(calls.get(), 0);
+
+        let response = ValidationService::new()
+            .with_ocr_client(Box::new(CountingOcr(calls.clone())))
+            .with_image_converter(Box::new(FixedConverter(jpeg(&gradient(856, 540)))))
+            .validate_document(request(HEIC_HEADER, None))
+            .await
+            .unwrap();
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_heic_is_refused_when_not_supported() {
+        let limits = UploadLimits {
+            formats: vec![UploadFormat::Jpeg],
+            ..UploadLimits::default()
+        };
+        let message = validation_message(
+            ValidationService::new()
+                .with_upload_limits(limits)
+                .with_image_converter(Box::new(FixedConverter(jpeg(&gradient(856, 540)))))
+                .validate_document(request(HEIC_HEADER, None))
+                .await,
+        );
+
+        assert!(message.contains("Most Compatible"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_upload_is_refused_before_ocr() {
+        let calls = Rc::new(Cell::new(0));
+        let message = validation_message(
+            ValidationService::new()
+                .with_ocr_client(Box::new(CountingOcr(calls.clone())))
+                .validate_document(request(b"GIF89a\x01\x00\x01\x00", None))
+                .await,
+        );
+        assert!(message.starts_with("Unsupported file format"), "{}", message);
+        assert_eq!(calls.get(), 0);
+    }
+}
@@ -0,0 +1,194 @@
+mod fixtures;
+
+use fixtures::{BLANK_PNG, FixedTextOcr};
+use chrono::{DateTime, NaiveDate, Utc};
+use shared::{DocumentType, ExtractedData, FieldConflict, ValidationRequest, ValidationWarningCode};
+use validation_service::application::validation_service::ValidationService;
+use validation_service::domain::entities::Document;
+use validation_service::domain::rules::ValidityRules;
+
+/// ICAO specimen, which expired in April 2012.
+const EXPIRED_PASSPORT: &str = "PASAPORTE\nP<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\nL898902C36UTO7408122F1204159ZE184226B<<<<<10";
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn at_midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+fn document(
+    document_type: DocumentType,
+    birth: Option<NaiveDate>,
+    issue: Option<NaiveDate>,
+    expiry: Option<NaiveDate>,
+) -> Document {
+    let data = ExtractedData {
+        document_number: Some("12345678Z".to_string()),
+        birth_date: birth.map(at_midnight),
+        issue_date: issue.map(at_midnight),
+        expiry_date: expiry.map(at_midnight),
+        ..ExtractedData::default()
+    };
+    Document::new(document_type, data, true)
+}
+
+#[cfg(test)]
+mod validity_rules_tests {
+    use super::*;
+
+    const TODAY: (i32, u32, u32) = (2025, 6, 15);
+
+    fn today() -> NaiveDate {
+        date(TODAY.0, TODAY.1, TODAY.2)
+    }
+
+    #[test]
+    fn test_current_adult_document_passes_cleanly() {
+        let doc = document(DocumentType::DNI, Some(date(1990, 6, 15)), Some(date(2020, 1, 10)), Some(date(2030, 1, 10)));
+        let outcome = ValidityRules::new().check_on(&doc, &[], today());
+
+        assert!(outcome.errors.is_empty(), "{:?}", outcome.errors);
+        assert!(outcome.warnings.is_empty(), "{:?}", outcome.warnings);
+    }
+
+    #[test]
+    fn test_valid_through_expiry_day() {
+        let doc = document(DocumentType::Passport, Some(date(1990, 1, 1)), None, Some(today()));
+
+        assert!(!doc.is_expired_on(today()));
+        assert!(doc.is_expired_on(today().succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn test_expired_passport_is_rejected() {
+        let doc = document(DocumentType::Passport, Some(date(1990, 1, 1)), None, Some(date(2025, 6, 1)));
+        let outcome = ValidityRules::new().check_on(&doc, &[], today());
+
+        assert_eq!(outcome.errors, vec!["The document expired on 2025-06-01".to_string()]);
+    }
+
+    #[test]
+    fn test_dni_under_renewal_gets_grace() {
+        let rules = ValidityRules::new();
+
+        let recently = document(DocumentType::DNI, Some(date(1990, 1, 1)), None, Some(date(2025, 5, 1)));
+        let outcome = rules.check_on(&recently, &[], today());
+        assert!(outcome.errors.is_empty());
+        assert_eq!(outcome.warnings[0].code, ValidationWarningCode::ExpiredUnderRenewal);
+
+        let long_ago = document(DocumentType::DNI, Some(date(1990, 1, 1)), None, Some(date(2024, 1, 1)));
+        assert!(!rules.check_on(&long_ago, &[], today()).errors.is_empty());
+
+        // Only DNIs are renewed in place
+        let nie = document(DocumentType::NIE, Some(date(1990, 1, 1)), None, Some(date(2025, 5, 1)));
+        assert!(!rules.check_on(&nie, &[], today()).errors.is_empty());
+
+        let strict = ValidityRules::new().with_dni_renewal_grace_days(0);
+        assert!(!strict.check_on(&recently, &[], today()).errors.is_empty());
+    }
+
+    #[test]
+    fn test_minor_must_be_accompanied() {
+        let rules = ValidityRules::new();
+
+        // Turns 18 tomorrow
+        let minor = document(DocumentType::DNI, Some(date(2007, 6, 16)), None, Some(date(2030, 1, 1)));
+        let outcome = rules.check_on(&minor, &[], today());
+        assert!(outcome.errors.is_empty());
+        assert_eq!(outcome.warnings.len(), 1);
+        assert_eq!(outcome.warnings[0].code, ValidationWarningCode::MinorMustBeAccompanied);
+        assert!(outcome.warnings[0].message.contains("17"));
+
+        let adult = document(DocumentType::DNI, Some(date(2007, 6, 15)), None, Some(date(2030, 1, 1)));
+        assert!(rules.check_on(&adult, &[], today()).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_dates_out_of_order() {
+        let rules = ValidityRules::new();
+
+        let issued_before_birth =
+            document(DocumentType::Passport, Some(date(2000, 1, 1)), Some(date(1999, 1, 1)), Some(date(2030, 1, 1)));
+        assert_eq!(
+            rules.check_on(&issued_before_birth, &[], today()).errors,
+            vec!["The birth date is not before the issue date".to_string()]
+        );
+
+        let expires_before_issue =
+            document(DocumentType::Passport, Some(date(1980, 1, 1)), Some(date(2026, 1, 1)), Some(date(2025, 12, 1)));
+        let errors = rules.check_on(&expires_before_issue, &[], today()).errors;
+        assert!(errors.contains(&"The issue date is in the future".to_string()));
+        assert!(errors.contains(&"The issue date is not before the expiry date".to_string()));
+
+        let unborn = document(DocumentType::Passport, Some(date(2026, 1, 1)), None, Some(date(2030, 1, 1)));
+        assert!(rules
+            .check_on(&unborn, &[], today())
+            .errors
+            .contains(&"The birth date is in the future".to_string()));
+    }
+
+    #[test]
+    fn test_missing_dates_are_warned_not_defaulted() {
+        let doc = document(DocumentType::DNI, None, None, None);
+        let outcome = ValidityRules::new().check_on(&doc, &[], today());
+
+        assert_eq!(doc.birth_date, None);
+        assert_eq!(doc.age_on(today()), None);
+        assert!(outcome.errors.is_empty());
+        assert_eq!(outcome.warnings.len(), 1);
+        assert_eq!(outcome.warnings[0].code, ValidationWarningCode::ExpiryUnknown);
+    }
+
+    #[test]
+    fn test_mrz_conflicts_become_warnings() {
+        let doc = document(DocumentType::Passport, Some(date(1990, 1, 1)), None, Some(date(2030, 1, 1)));
+        let conflicts = vec![FieldConflict {
+            field: "surname".to_string(),
+            mrz_value: "ERIKSSON".to_string(),
+            visual_value: "ERIKSON".to_string(),
+        }];
+        let outcome = ValidityRules::new().check_on(&doc, &conflicts, today());
+
+        assert_eq!(outcome.warnings.len(), 1);
+        assert_eq!(outcome.warnings[0].code, ValidationWarningCode::MrzMismatch);
+        assert_eq!(outcome.warnings[0].field.as_deref(), Some("surname"));
+    }
+
+    #[tokio::test]
+    async fn test_service_rejects_expired_document() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(EXPIRED_PASSPORT)));
+        let response = service
+            .validate_document(ValidationRequest {
+                document_type: Some(DocumentType::Passport),
+                front_image: BLANK_PNG.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(!response.is_valid);
+        assert!(response.errors.contains(&"The document expired on 2012-04-15".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_service_reads_dni_dates_from_visual_zone() {
+        let service = ValidationService::new().with_ocr_client(Box::new(FixedTextOcr(
+            "DNI\n12345678Z\nNOMBRE: JUAN GARCIA\nFECHA DE NACIMIENTO: 15/06/1990\nEMISIÓN: 10/01/2020\nVÁLIDO HASTA: 10/01/2030",
+        )));
+        let response = service
+            .validate_document(ValidationRequest {
+                document_type: Some(DocumentType::DNI),
+                front_image: BLANK_PNG.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(response.is_valid, "{:?}", response.errors);
+        assert!(response.warnings.is_empty(), "{:?}", response.warnings);
+        assert_eq!(response.extracted_data.issue_date.unwrap().date_naive(), date(2020, 1, 10));
+        assert_eq!(response.extracted_data.expiry_date.unwrap().date_naive(), date(2030, 1, 10));
+    }
+}
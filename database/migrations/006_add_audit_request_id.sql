@@ -0,0 +1,6 @@
+-- Correlate audit trail entries with the request that produced them
+-- The id comes from the gateway's X-Request-Id header
+
+ALTER TABLE audit_log ADD COLUMN request_id VARCHAR(128);
+
+CREATE INDEX idx_audit_log_request_id ON audit_log(request_id);
@@ -0,0 +1,24 @@
+-- Document validations read with too little confidence, held for a
+-- hospitalero to correct and approve or reject. Decisions go to audit_log
+-- under table_name 'document_reviews'.
+
+CREATE TABLE document_reviews (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    document_type VARCHAR(20) NOT NULL,
+    extracted_data JSONB NOT NULL,
+    field_confidence JSONB NOT NULL,
+    fields_to_review JSONB NOT NULL,
+    confidence_score REAL NOT NULL,
+    errors JSONB NOT NULL,
+    ocr_text JSONB NOT NULL, -- per side, for the golden corpus
+    front_image_url TEXT, -- sealed like id_photo_url
+    back_image_url TEXT,
+    corrections JSONB NOT NULL DEFAULT '{}',
+    decided_by VARCHAR(100),
+    decided_at TIMESTAMP WITH TIME ZONE,
+    rejection_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_document_reviews_status ON document_reviews(status, created_at);
@@ -0,0 +1,12 @@
+-- Notifications table as the outbox notification-service sends from
+-- Failed deliveries are retried with backoff until dead-lettered for an admin
+
+ALTER TABLE notifications ADD COLUMN fallback_channel VARCHAR(20) CHECK (fallback_channel IN ('whatsapp', 'sms', 'email', 'telegram'));
+ALTER TABLE notifications ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE notifications ADD COLUMN next_attempt_at TIMESTAMP WITH TIME ZONE DEFAULT NOW();
+
+ALTER TABLE notifications DROP CONSTRAINT notifications_status_check;
+ALTER TABLE notifications ADD CONSTRAINT notifications_status_check
+    CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'bounced', 'dead_letter'));
+
+CREATE INDEX idx_notifications_outbox ON notifications(status, next_attempt_at);
@@ -0,0 +1,4 @@
+-- Look up notifications by the provider's message id
+-- Twilio status callbacks name the message only by its SID
+
+CREATE INDEX idx_notifications_provider_message ON notifications(provider_message_id);
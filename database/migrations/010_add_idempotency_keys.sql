@@ -0,0 +1,11 @@
+-- Idempotency-Key records for the gateway's mutating routes
+-- The primary key makes claiming a key atomic across gateway instances
+
+CREATE TABLE idempotency_keys (
+    key VARCHAR(300) PRIMARY KEY, -- 'idempotency:' and the client's key
+    fingerprint CHAR(64) NOT NULL, -- SHA-256 of method, path and body
+    created_at BIGINT NOT NULL, -- unix seconds the key was claimed
+    response TEXT -- stored response as JSON, NULL while in flight
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
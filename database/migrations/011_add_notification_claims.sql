@@ -0,0 +1,6 @@
+-- Outbox workers claim due notifications for a lease before sending them
+-- A worker that dies mid-send leaves its claim to expire; template data is
+-- kept so a bounced notification can be resent on its fallback channel
+
+ALTER TABLE notifications ADD COLUMN template_data JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE notifications ADD COLUMN claimed_until TIMESTAMP WITH TIME ZONE;
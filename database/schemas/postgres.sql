@@ -111,11 +111,16 @@ CREATE TABLE notifications (
     recipient VARCHAR(255) NOT NULL,
     subject VARCHAR(255),
     message TEXT NOT NULL,
-    status VARCHAR(20) DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'bounced')),
+    fallback_channel VARCHAR(20) CHECK (fallback_channel IN ('whatsapp', 'sms', 'email', 'telegram')),
+    status VARCHAR(20) DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'bounced', 'dead_letter')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     provider_message_id VARCHAR(255),
     sent_at TIMESTAMP WITH TIME ZONE,
     delivered_at TIMESTAMP WITH TIME ZONE,
     error_message TEXT,
+    template_data JSONB NOT NULL DEFAULT '{}',
+    claimed_until TIMESTAMP WITH TIME ZONE, -- held by an outbox worker until then
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
@@ -132,9 +137,38 @@ CREATE TABLE audit_log (
     new_values JSONB,
     ip_address INET,
     user_agent TEXT,
+    request_id VARCHAR(128), -- X-Request-Id from the gateway
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
+-- Document validations held for a hospitalero to review
+CREATE TABLE document_reviews (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    document_type VARCHAR(20) NOT NULL,
+    extracted_data JSONB NOT NULL,
+    field_confidence JSONB NOT NULL,
+    fields_to_review JSONB NOT NULL,
+    confidence_score REAL NOT NULL,
+    errors JSONB NOT NULL,
+    ocr_text JSONB NOT NULL, -- per side, for the golden corpus
+    front_image_url TEXT, -- sealed like id_photo_url
+    back_image_url TEXT,
+    corrections JSONB NOT NULL DEFAULT '{}',
+    decided_by VARCHAR(100),
+    decided_at TIMESTAMP WITH TIME ZONE,
+    rejection_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Idempotency-Key records for the gateway's mutating routes
+CREATE TABLE idempotency_keys (
+    key VARCHAR(300) PRIMARY KEY, -- 'idempotency:' and the client's key
+    fingerprint CHAR(64) NOT NULL, -- SHA-256 of method, path and body
+    created_at BIGINT NOT NULL, -- unix seconds the key was claimed
+    response TEXT -- stored response as JSON, NULL while in flight
+);
+
 -- Dynamic pricing table
 CREATE TABLE pricing (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -158,8 +192,13 @@ CREATE INDEX idx_beds_status ON beds(status);
 CREATE INDEX idx_beds_room_type ON beds(room_type);
 CREATE INDEX idx_payments_booking ON payments(booking_id);
 CREATE INDEX idx_notifications_booking ON notifications(booking_id);
+CREATE INDEX idx_notifications_outbox ON notifications(status, next_attempt_at);
+CREATE INDEX idx_notifications_provider_message ON notifications(provider_message_id);
 CREATE INDEX idx_audit_log_table_record ON audit_log(table_name, record_id);
 CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX idx_audit_log_request_id ON audit_log(request_id);
+CREATE INDEX idx_document_reviews_status ON document_reviews(status, created_at);
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
 
 -- Triggers for updated_at timestamps
 CREATE OR REPLACE FUNCTION update_updated_at_column()
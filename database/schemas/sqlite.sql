@@ -103,11 +103,16 @@ CREATE TABLE notifications (
     recipient TEXT NOT NULL,
     subject TEXT,
     message TEXT NOT NULL,
-    status TEXT DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'bounced')),
+    fallback_channel TEXT CHECK (fallback_channel IN ('whatsapp', 'sms', 'email', 'telegram')),
+    status TEXT DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'delivered', 'failed', 'bounced', 'dead_letter')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT DEFAULT (datetime('now')),
     provider_message_id TEXT,
     sent_at TEXT,
     delivered_at TEXT,
     error_message TEXT,
+    template_data TEXT NOT NULL DEFAULT '{}', -- JSON as TEXT
+    claimed_until TEXT, -- held by an outbox worker until then
     created_at TEXT DEFAULT (datetime('now'))
 );
 
@@ -124,9 +129,38 @@ CREATE TABLE audit_log (
     new_values TEXT, -- JSON as TEXT
     ip_address TEXT,
     user_agent TEXT,
+    request_id TEXT, -- X-Request-Id from the gateway
     created_at TEXT DEFAULT (datetime('now'))
 );
 
+-- Document validations held for a hospitalero to review
+CREATE TABLE document_reviews (
+    id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    document_type TEXT NOT NULL,
+    extracted_data TEXT NOT NULL, -- JSON as TEXT
+    field_confidence TEXT NOT NULL, -- JSON as TEXT
+    fields_to_review TEXT NOT NULL, -- JSON as TEXT
+    confidence_score REAL NOT NULL,
+    errors TEXT NOT NULL, -- JSON as TEXT
+    ocr_text TEXT NOT NULL, -- JSON as TEXT, per side, for the golden corpus
+    front_image_url TEXT, -- sealed like id_photo_url
+    back_image_url TEXT,
+    corrections TEXT NOT NULL DEFAULT '{}', -- JSON as TEXT
+    decided_by TEXT,
+    decided_at TEXT,
+    rejection_reason TEXT,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+-- Idempotency-Key records for the gateway's mutating routes
+CREATE TABLE idempotency_keys (
+    key TEXT PRIMARY KEY, -- 'idempotency:' and the client's key
+    fingerprint TEXT NOT NULL, -- SHA-256 of method, path and body
+    created_at INTEGER NOT NULL, -- unix seconds the key was claimed
+    response TEXT -- stored response as JSON, NULL while in flight
+);
+
 -- Dynamic pricing table
 CREATE TABLE pricing (
     id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
@@ -150,8 +184,13 @@ CREATE INDEX idx_beds_status ON beds(status);
 CREATE INDEX idx_beds_room_type ON beds(room_type);
 CREATE INDEX idx_payments_booking ON payments(booking_id);
 CREATE INDEX idx_notifications_booking ON notifications(booking_id);
+CREATE INDEX idx_notifications_outbox ON notifications(status, next_attempt_at);
+CREATE INDEX idx_notifications_provider_message ON notifications(provider_message_id);
 CREATE INDEX idx_audit_log_table_record ON audit_log(table_name, record_id);
 CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX idx_audit_log_request_id ON audit_log(request_id);
+CREATE INDEX idx_document_reviews_status ON document_reviews(status, created_at);
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
 
 -- Triggers for updated_at timestamps (SQLite version)
 CREATE TRIGGER update_pilgrims_updated_at
@@ -20,7 +20,13 @@ WITH expected_migrations AS (
         '002_add_notifications', 
         '003_add_audit_log',
         '004_add_indexes',
-        '005_seed_pricing'
+        '005_seed_pricing',
+        '006_add_audit_request_id',
+        '007_add_document_reviews',
+        '008_add_notification_outbox',
+        '009_add_notification_provider_index',
+        '010_add_idempotency_keys',
+        '011_add_notification_claims'
     ]) as version
 ),
 actual_migrations AS (
@@ -59,7 +65,7 @@ SELECT
         WHEN table_name = 'bookings' AND COUNT(*) >= 15 THEN 'PASS'
         WHEN table_name = 'payments' AND COUNT(*) >= 9 THEN 'PASS'
         WHEN table_name = 'notifications' AND COUNT(*) >= 11 THEN 'PASS'
-        WHEN table_name = 'audit_log' AND COUNT(*) >= 10 THEN 'PASS'
+        WHEN table_name = 'audit_log' AND COUNT(*) >= 11 THEN 'PASS'
         ELSE 'FAIL: Missing columns'
     END as column_check
 FROM information_schema.columns 
@@ -1,8 +1,126 @@
 // Auth verification service module
 
 use anyhow::Result;
-use spin_sdk::http::{Request, Response};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::Sha256;
+use spin_sdk::http::{Request, Response};
+use spin_sdk::variables;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AuthUser {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    picture: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TokenVerification {
+    valid: bool,
+    user: AuthUser,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LoginRedirect {
+    login_url: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AuthSession {
+    access_token: String,
+    user: AuthUser,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LogoutResult {
+    success: bool,
+    message: String,
+}
+
+/// Claims of an admin's bearer token: an HS256 JWT signed with
+/// `jwt_secret` whose `role` is `admin`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AdminClaims {
+    pub sub: String,
+    pub role: String,
+    /// Expiry, in seconds since the Unix epoch
+    pub exp: i64,
+}
+
+/// Why a bearer token was not accepted as an admin's.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminTokenError {
+    /// Missing, malformed, badly signed or expired: 401
+    Invalid(&'static str),
+    /// Valid, but not an admin's: 403
+    NotAdmin,
+}
+
+/// Checks an HS256 `token` against `secret` at `now`, in Unix seconds.
+pub fn verify_admin_token(token: &str, secret: &[u8], now: i64) -> Result<AdminClaims, AdminTokenError> {
+    use AdminTokenError::Invalid;
+
+    if secret.is_empty() {
+        return Err(Invalid("No JWT secret is configured"));
+    }
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Invalid("Malformed token"));
+    };
+    let decode = |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|_| Invalid("Malformed token"));
+
+    let fields: serde_json::Value = serde_json::from_slice(&decode(header)?).map_err(|_| Invalid("Malformed token"))?;
+    if fields["alg"] != "HS256" {
+        return Err(Invalid("Unsupported token algorithm"));
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).map_err(|_| Invalid("Invalid JWT secret"))?;
+    mac.update(format!("{}.{}", header, payload).as_bytes());
+    mac.verify_slice(&decode(signature)?).map_err(|_| Invalid("Invalid token signature"))?;
+
+    let claims: AdminClaims = serde_json::from_slice(&decode(payload)?).map_err(|_| Invalid("Malformed token claims"))?;
+    if claims.exp <= now {
+        return Err(Invalid("Token expired"));
+    }
+    if claims.role != "admin" {
+        return Err(AdminTokenError::NotAdmin);
+    }
+    Ok(claims)
+}
+
+/// The admin behind `req`, or the 401 or 403 response to send instead.
+pub fn require_admin(req: &Request) -> std::result::Result<AdminClaims, Response> {
+    let token = req
+        .header("Authorization")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    // Without a token there is nothing to verify, so the secret is not read
+    let verified = match token {
+        Some(token) => {
+            let secret = variables::get("jwt_secret").unwrap_or_default();
+            verify_admin_token(token, secret.as_bytes(), chrono::Utc::now().timestamp())
+        }
+        None => Err(AdminTokenError::Invalid("Missing bearer token")),
+    };
+
+    verified.map_err(|e| {
+        let (status, message) = match e {
+            AdminTokenError::Invalid(message) => (401, message),
+            AdminTokenError::NotAdmin => (403, "Admin role required"),
+        };
+        Response::builder()
+            .status(status)
+            .header("Content-Type", "application/json")
+            .body(json!({"error": message}).to_string())
+            .build()
+    })
+}
 
 pub async fn handle(req: &Request) -> Result<Response> {
     let path = req.path();
@@ -24,22 +142,38 @@ pub async fn handle(req: &Request) -> Result<Response> {
     }
 }
 
-async fn verify_token(_req: &Request) -> Result<Response> {
+#[utoipa::path(
+    get,
+    path = "/api/auth/verify",
+    tag = "auth",
+    responses((status = 200, description = "Bearer token verification", body = TokenVerification))
+)]
+pub(crate) async fn verify_token(_req: &Request) -> Result<Response> {
     // TODO: Implement JWT verification with Auth0
+    let verification = TokenVerification {
+        valid: true,
+        user: AuthUser {
+            id: Some("demo-user".to_string()),
+            name: None,
+            email: "demo@example.com".to_string(),
+            picture: None,
+        },
+    };
+
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&json!({
-            "valid": true,
-            "user": {
-                "id": "demo-user",
-                "email": "demo@example.com"
-            }
-        }))?)
+        .body(serde_json::to_string(&verification)?)
         .build())
 }
 
-async fn handle_login(_req: &Request) -> Result<Response> {
+#[utoipa::path(
+    get,
+    path = "/api/auth/login",
+    tag = "auth",
+    responses((status = 200, description = "Auth0 authorize URL", body = LoginRedirect))
+)]
+pub(crate) async fn handle_login(_req: &Request) -> Result<Response> {
     // Get Auth0 configuration from environment
     let auth0_domain = std::env::var("AUTH0_DOMAIN").unwrap_or_else(|_| "guillermolam.auth0.com".to_string());
     let auth0_client_id = std::env::var("AUTH0_CLIENT_ID").unwrap_or_else(|_| "ohunbmaWBOQyEd2ca1orhnFqN1DDPQBd".to_string());
@@ -52,35 +186,50 @@ async fn handle_login(_req: &Request) -> Result<Response> {
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&json!({
-            "login_url": login_url
-        }))?)
+        .body(serde_json::to_string(&LoginRedirect { login_url })?)
         .build())
 }
 
-async fn handle_callback(req: &Request) -> Result<Response> {
+#[utoipa::path(
+    get,
+    path = "/api/auth/callback",
+    tag = "auth",
+    responses((status = 200, description = "Session issued after Auth0 login", body = AuthSession))
+)]
+pub(crate) async fn handle_callback(_req: &Request) -> Result<Response> {
     // TODO: Implement Auth0 callback handling for production
+    let session = AuthSession {
+        access_token: "demo_token_12345".to_string(),
+        user: AuthUser {
+            id: None,
+            name: Some("Admin User".to_string()),
+            email: "admin@alberguedelcarrascalejo.com".to_string(),
+            picture: Some("https://via.placeholder.com/150".to_string()),
+        },
+    };
+
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&json!({
-            "access_token": "demo_token_12345",
-            "user": {
-                "name": "Admin User",
-                "email": "admin@alberguedelcarrascalejo.com",
-                "picture": "https://via.placeholder.com/150"
-            }
-        }))?)
+        .body(serde_json::to_string(&session)?)
         .build())
 }
 
-async fn handle_logout(_req: &Request) -> Result<Response> {
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    responses((status = 200, description = "Session cleared", body = LogoutResult))
+)]
+pub(crate) async fn handle_logout(_req: &Request) -> Result<Response> {
+    let result = LogoutResult {
+        success: true,
+        message: "Logged out successfully".to_string(),
+    };
+
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&json!({
-            "success": true,
-            "message": "Logged out successfully"
-        }))?)
+        .body(serde_json::to_string(&result)?)
         .build())
-}
\ No newline at end of file
+}
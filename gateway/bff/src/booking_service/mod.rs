@@ -0,0 +1,131 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::BookingDto;
+use spin_sdk::http::{Request, Response};
+use utoipa::ToSchema;
+
+use crate::openapi::ErrorResponse;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DashboardStats {
+    total_bookings: u32,
+    pending_checkins: u32,
+    current_occupancy: u32,
+    total_capacity: u32,
+    revenue_today: f64,
+    average_rating: f32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Pricing {
+    bed_price: f64,
+    breakfast_price: f64,
+    dinner_price: f64,
+    laundry_price: f64,
+    currency: String,
+    check_in_time: String,
+    check_out_time: String,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/booking/dashboard/stats" => handle_dashboard_stats().await,
+        "/api/booking/pricing" => handle_pricing().await,
+        "/api/booking/create" => handle_create_booking(req).await,
+        "/api/booking/status" => handle_booking_status(req).await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Booking endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/booking/dashboard/stats",
+    tag = "booking",
+    responses((status = 200, description = "Occupancy and revenue summary", body = DashboardStats))
+)]
+pub(crate) async fn handle_dashboard_stats() -> Result<Response> {
+    let stats = DashboardStats {
+        total_bookings: 245,
+        pending_checkins: 8,
+        current_occupancy: 12,
+        total_capacity: 24,
+        revenue_today: 180.0,
+        average_rating: 4.6,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&stats)?)
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/booking/pricing",
+    tag = "booking",
+    responses((status = 200, description = "Current prices and schedule", body = Pricing))
+)]
+pub(crate) async fn handle_pricing() -> Result<Response> {
+    let pricing = Pricing {
+        bed_price: 15.0,
+        breakfast_price: 5.0,
+        dinner_price: 8.0,
+        laundry_price: 3.0,
+        currency: "EUR".to_string(),
+        check_in_time: "14:00".to_string(),
+        check_out_time: "11:00".to_string(),
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&pricing)?)
+        .build())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/booking/create",
+    tag = "booking",
+    request_body = BookingDto,
+    responses(
+        (status = 201, description = "Booking created", body = BookingDto),
+        (status = 501, description = "Not implemented yet", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_create_booking(_req: &Request) -> Result<Response> {
+    // TODO: Implement booking creation logic
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/booking/status",
+    tag = "booking",
+    responses(
+        (status = 200, description = "Booking status", body = BookingDto),
+        (status = 501, description = "Not implemented yet", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_booking_status(_req: &Request) -> Result<Response> {
+    // TODO: Implement booking status check
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
@@ -0,0 +1,263 @@
+// Deep health checks: database, backend components and the ministry endpoint
+
+use anyhow::Result;
+use futures::future::{self, Either};
+use shared::db::{DatabaseConfig, DatabaseOperations, DatabaseType};
+use shared::health::{probe, DependencyHealth, DependencyStatus, HealthReport};
+use shared::telemetry::REQUEST_ID_HEADER;
+use shared::{AlbergueError, AlbergueResult};
+use spin_sdk::http::{Method, Request, Response};
+use spin_sdk::variables;
+use spin_sdk::wit::wasi::clocks0_2_0::monotonic_clock;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+const DEFAULT_MINISTRY_ENDPOINT: &str =
+    "https://hospedajes.ses.mir.es/hospedajes-web/ws/v1/comunicacion";
+
+/// A probe not answered by then counts as down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Spin components of this application probed over service chaining, and
+/// whether the gateway can serve traffic without them.
+const COMPONENTS: &[(&str, bool)] = &[("booking-service", true), ("validation-service", true)];
+
+pub struct SpinDatabase {
+    config: DatabaseConfig,
+}
+
+impl SpinDatabase {
+    pub fn from_variables() -> Self {
+        let connection_string = variables::get("database_url").unwrap_or_default();
+        let database_type = if connection_string.starts_with("postgres") {
+            DatabaseType::PostgreSQL
+        } else {
+            DatabaseType::SQLite
+        };
+
+        Self {
+            config: DatabaseConfig {
+                database_type,
+                connection_string,
+                max_connections: 1,
+                connection_timeout_seconds: 5,
+            },
+        }
+    }
+
+    pub fn config(&self) -> &DatabaseConfig {
+        &self.config
+    }
+}
+
+impl DatabaseOperations for SpinDatabase {
+    async fn health_check(&self) -> Result<bool, AlbergueError> {
+        let db_error = |e: &dyn std::fmt::Display| AlbergueError::Database {
+            message: e.to_string(),
+        };
+
+        if self.config.is_sqlite() {
+            let connection = spin_sdk::sqlite::Connection::open_default().map_err(|e| db_error(&e))?;
+            connection.execute("SELECT 1", &[]).map_err(|e| db_error(&e))?;
+        } else {
+            let connection =
+                spin_sdk::pg::Connection::open(&self.config.connection_string).map_err(|e| db_error(&e))?;
+            connection.query("SELECT 1", &[]).map_err(|e| db_error(&e))?;
+        }
+
+        Ok(true)
+    }
+
+    async fn get_connection_info(&self) -> HashMap<String, String> {
+        let mut info = HashMap::new();
+        info.insert("type".to_string(), format!("{:?}", self.config.database_type));
+        // Never expose credentials, only the host part of the URL
+        if let Some(host) = self.config.connection_string.split('@').nth(1) {
+            info.insert("host".to_string(), host.split('/').next().unwrap_or_default().to_string());
+        }
+        info
+    }
+}
+
+/// The database is critical: the gateway is not ready without it.
+pub async fn check_database(database: &impl DatabaseOperations) -> DependencyHealth {
+    probe("database", database.health_check()).await.critical()
+}
+
+/// Resolves once `deadline` has passed on the host's monotonic clock.
+pub(crate) struct Sleep {
+    deadline: monotonic_clock::Instant,
+    /// Dropping it unregisters the wakeup
+    _wakeup: Option<spin_executor::CancelOnDropToken>,
+}
+
+impl Sleep {
+    pub(crate) fn new(duration: Duration) -> Self {
+        Self {
+            deadline: monotonic_clock::now().saturating_add(duration.as_nanos() as u64),
+            _wakeup: None,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        if monotonic_clock::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        let pollable = monotonic_clock::subscribe_instant(self.deadline);
+        self._wakeup =
+            Some(spin_executor::push_waker_and_get_token(pollable, context.waker().clone()).into());
+        Poll::Pending
+    }
+}
+
+/// Runs `check`, giving up after `PROBE_TIMEOUT`.
+async fn with_timeout<T>(name: &str, check: impl Future<Output = AlbergueResult<T>>) -> AlbergueResult<T> {
+    futures::pin_mut!(check);
+    match future::select(check, Sleep::new(PROBE_TIMEOUT)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(AlbergueError::ExternalService {
+            service: name.to_string(),
+            message: format!("no answer within {} ms", PROBE_TIMEOUT.as_millis()),
+        }),
+    }
+}
+
+/// Probes a component's `/health`, folding in the dependencies it reports.
+async fn check_component(name: &str, base_url: &str, critical: bool, request_id: &str) -> Vec<DependencyHealth> {
+    let url = format!("{}/health", base_url.trim_end_matches('/'));
+    let started = chrono::Utc::now();
+    let result = with_timeout(name, async {
+        spin_sdk::http::send::<_, Response>(
+            Request::builder()
+                .method(Method::Get)
+                .uri(url)
+                .header(REQUEST_ID_HEADER, request_id)
+                .build(),
+        )
+        .await
+        .map_err(|e| AlbergueError::ExternalService {
+            service: name.to_string(),
+            message: e.to_string(),
+        })
+    })
+    .await;
+    let latency_ms = (chrono::Utc::now() - started).num_milliseconds().max(0) as u64;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let mut health = DependencyHealth::new(name, DependencyStatus::Down, latency_ms)
+                .with_message(e.to_string());
+            health.critical = critical;
+            return vec![health];
+        }
+    };
+
+    // Components that report their own dependencies get them folded in
+    match serde_json::from_slice::<HealthReport>(response.body()) {
+        Ok(report) => HealthReport::nested(name, report, latency_ms, critical),
+        Err(_) => {
+            let ok = (200..300).contains(response.status());
+            let mut health = DependencyHealth::from_check(name, latency_ms, Ok(ok));
+            health.critical = critical;
+            vec![health]
+        }
+    }
+}
+
+async fn check_ministry(request_id: &str) -> DependencyHealth {
+    let url = variables::get("ministry_endpoint_url")
+        .unwrap_or_else(|_| DEFAULT_MINISTRY_ENDPOINT.to_string());
+
+    probe("ministry", with_timeout("ministry", async move {
+        let response: Response = spin_sdk::http::send(
+            Request::builder()
+                .method(Method::Get)
+                .uri(url)
+                .header(REQUEST_ID_HEADER, request_id)
+                .build(),
+        )
+        .await
+        .map_err(|e| AlbergueError::ExternalService {
+            service: "ministry".to_string(),
+            message: e.to_string(),
+        })?;
+
+        // The SOAP endpoint rejects a bare GET; any answer short of a server
+        // error means it is reachable.
+        AlbergueResult::Ok(*response.status() < 500)
+    }))
+    .await
+}
+
+pub async fn health_report(request_id: &str) -> HealthReport {
+    // The database call blocks the component, so it goes first
+    let mut dependencies = vec![check_database(&SpinDatabase::from_variables()).await];
+
+    let mut components: Vec<(&str, String, bool)> = COMPONENTS
+        .iter()
+        .map(|(name, critical)| (*name, format!("http://{}.spin.internal", name), *critical))
+        .collect();
+    // notification-service runs outside Spin and is probed when configured
+    let notification_url = variables::get("notification_service_url").unwrap_or_default();
+    if !notification_url.is_empty() {
+        components.push(("notification-service", notification_url, false));
+    }
+
+    let probes = components
+        .iter()
+        .map(|(name, url, critical)| check_component(name, url, *critical, request_id));
+    let (components, ministry) =
+        future::join(future::join_all(probes), check_ministry(request_id)).await;
+    dependencies.extend(components.into_iter().flatten());
+    dependencies.push(ministry);
+
+    HealthReport::from_dependencies("gateway-bff", env!("CARGO_PKG_VERSION"), dependencies)
+}
+
+fn report_response(report: &HealthReport, status: u16) -> Result<Response> {
+    Ok(Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Cache-Control", "no-store")
+        .body(serde_json::to_string(report)?)
+        .build())
+}
+
+/// 200 while every critical dependency is up, 503 otherwise.
+pub fn readiness_response(report: &HealthReport) -> Result<Response> {
+    let status = if report.is_ready() { 200 } else { 503 };
+    report_response(report, status)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "health",
+    responses((status = 200, description = "Per-dependency status, latency and degraded mode", body = HealthReport))
+)]
+pub(crate) async fn handle_health(req: &Request) -> Result<Response> {
+    let request_id = crate::telemetry::request_id(req).unwrap_or_default();
+    report_response(&health_report(&request_id).await, 200)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to take traffic, possibly in degraded mode", body = HealthReport),
+        (status = 503, description = "Not ready", body = HealthReport)
+    )
+)]
+pub(crate) async fn handle_ready(req: &Request) -> Result<Response> {
+    let request_id = crate::telemetry::request_id(req).unwrap_or_default();
+    readiness_response(&health_report(&request_id).await)
+}
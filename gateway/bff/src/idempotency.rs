@@ -0,0 +1,311 @@
+// Idempotency-Key support for booking, payment and notification POSTs
+
+use anyhow::Result;
+use futures::future::{self, Either};
+use serde_json::json;
+use shared::idempotency::{
+    applies_to, fingerprint, is_valid_key, Claim, Decision, Idempotency, IdempotencyRecord,
+    IdempotencyStore, StoredResponse, DEFAULT_WINDOW_SECONDS, IDEMPOTENCY_KEY_HEADER,
+    IN_FLIGHT_LEASE_SECONDS, REPLAYED_HEADER,
+};
+use shared::telemetry::REQUEST_ID_HEADER;
+use shared::{AlbergueError, AlbergueResult};
+use spin_sdk::http::{Request, Response};
+use spin_sdk::{pg, sqlite, variables};
+use std::time::Duration;
+
+use crate::health::{Sleep, SpinDatabase};
+
+/// How often a running request renews its claim, well within the lease.
+const LEASE_RENEWAL: Duration = Duration::from_secs(IN_FLIGHT_LEASE_SECONDS as u64 / 3);
+
+/// Idempotency records in the `idempotency_keys` table. Its primary key
+/// makes the first claim of a key atomic, and a takeover is an update
+/// conditional on the record still being the one that was read.
+pub struct DbIdempotencyStore {
+    connection: Connection,
+}
+
+enum Connection {
+    Postgres(pg::Connection),
+    Sqlite(sqlite::Connection),
+}
+
+/// Query parameters and columns, in the types `idempotency_keys` uses.
+#[derive(Debug, Clone, PartialEq)]
+enum Column {
+    Text(String),
+    Int(i64),
+    Bool(bool),
+    Null,
+}
+
+impl DbIdempotencyStore {
+    pub fn open() -> AlbergueResult<Self> {
+        let database = SpinDatabase::from_variables();
+        let config = database.config();
+        let connection = if config.is_sqlite() {
+            Connection::Sqlite(sqlite::Connection::open_default().map_err(db_error)?)
+        } else {
+            Connection::Postgres(pg::Connection::open(&config.connection_string).map_err(db_error)?)
+        };
+        Ok(Self { connection })
+    }
+
+    /// Runs `sql`, written with Postgres `$n` placeholders, and returns its rows.
+    fn query(&self, sql: &str, params: &[Column]) -> AlbergueResult<Vec<Vec<Column>>> {
+        match &self.connection {
+            Connection::Postgres(connection) => {
+                let params: Vec<pg::ParameterValue> = params
+                    .iter()
+                    .map(|param| match param {
+                        Column::Text(text) => pg::ParameterValue::Str(text.clone()),
+                        Column::Int(int) => pg::ParameterValue::Int64(*int),
+                        Column::Bool(flag) => pg::ParameterValue::Boolean(*flag),
+                        Column::Null => pg::ParameterValue::DbNull,
+                    })
+                    .collect();
+                let rows = connection.query(sql, &params).map_err(db_error)?.rows;
+                Ok(rows
+                    .into_iter()
+                    .map(|row| {
+                        row.into_iter()
+                            .map(|value| match value {
+                                pg::DbValue::Str(text) => Column::Text(text),
+                                pg::DbValue::Int64(int) => Column::Int(int),
+                                pg::DbValue::Int32(int) => Column::Int(int.into()),
+                                pg::DbValue::Boolean(flag) => Column::Bool(flag),
+                                _ => Column::Null,
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+            Connection::Sqlite(connection) => {
+                let params: Vec<sqlite::Value> = params
+                    .iter()
+                    .map(|param| match param {
+                        Column::Text(text) => sqlite::Value::Text(text.clone()),
+                        Column::Int(int) => sqlite::Value::Integer(*int),
+                        Column::Bool(flag) => sqlite::Value::Integer(i64::from(*flag)),
+                        Column::Null => sqlite::Value::Null,
+                    })
+                    .collect();
+                let result = connection
+                    .execute(&sql.replace('$', "?"), &params)
+                    .map_err(db_error)?;
+                Ok(result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        row.values
+                            .into_iter()
+                            .map(|value| match value {
+                                sqlite::Value::Text(text) => Column::Text(text),
+                                sqlite::Value::Integer(int) => Column::Int(int),
+                                _ => Column::Null,
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+fn db_error(e: impl std::fmt::Display) -> AlbergueError {
+    AlbergueError::Database {
+        message: format!("idempotency_keys: {}", e),
+    }
+}
+
+/// The `response` column: the stored response as JSON, null while in flight.
+fn response_column(record: &IdempotencyRecord) -> AlbergueResult<Column> {
+    match &record.response {
+        Some(response) => serde_json::to_string(response).map(Column::Text).map_err(db_error),
+        None => Ok(Column::Null),
+    }
+}
+
+impl IdempotencyStore for DbIdempotencyStore {
+    fn get(&self, key: &str) -> AlbergueResult<Option<IdempotencyRecord>> {
+        let rows = self.query(
+            "SELECT fingerprint, created_at, response FROM idempotency_keys WHERE key = $1",
+            &[Column::Text(key.to_string())],
+        )?;
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+        match row.as_slice() {
+            [Column::Text(fingerprint), Column::Int(created_at), response] => Ok(Some(IdempotencyRecord {
+                fingerprint: fingerprint.clone(),
+                created_at: *created_at,
+                response: match response {
+                    Column::Text(json) => Some(serde_json::from_str(json).map_err(db_error)?),
+                    _ => None,
+                },
+            })),
+            _ => Err(db_error("unexpected row shape")),
+        }
+    }
+
+    fn insert_if_absent(&self, key: &str, record: &IdempotencyRecord) -> AlbergueResult<bool> {
+        let rows = self.query(
+            "INSERT INTO idempotency_keys (key, fingerprint, created_at, response) \
+             VALUES ($1, $2, $3, $4) ON CONFLICT (key) DO NOTHING RETURNING key",
+            &[
+                Column::Text(key.to_string()),
+                Column::Text(record.fingerprint.clone()),
+                Column::Int(record.created_at),
+                response_column(record)?,
+            ],
+        )?;
+        Ok(!rows.is_empty())
+    }
+
+    fn replace(&self, key: &str, current: &IdempotencyRecord, new: &IdempotencyRecord) -> AlbergueResult<bool> {
+        // A record is told apart by its fingerprint, when it was claimed and
+        // whether it was answered
+        let rows = self.query(
+            "UPDATE idempotency_keys SET fingerprint = $4, created_at = $5, response = $6 \
+             WHERE key = $1 AND fingerprint = $2 AND created_at = $3 AND (response IS NULL) = $7 \
+             RETURNING key",
+            &[
+                Column::Text(key.to_string()),
+                Column::Text(current.fingerprint.clone()),
+                Column::Int(current.created_at),
+                Column::Text(new.fingerprint.clone()),
+                Column::Int(new.created_at),
+                response_column(new)?,
+                Column::Bool(current.response.is_none()),
+            ],
+        )?;
+        Ok(!rows.is_empty())
+    }
+
+    fn delete(&self, key: &str, current: &IdempotencyRecord) -> AlbergueResult<bool> {
+        let rows = self.query(
+            "DELETE FROM idempotency_keys \
+             WHERE key = $1 AND fingerprint = $2 AND created_at = $3 AND (response IS NULL) = $4 \
+             RETURNING key",
+            &[
+                Column::Text(key.to_string()),
+                Column::Text(current.fingerprint.clone()),
+                Column::Int(current.created_at),
+                Column::Bool(current.response.is_none()),
+            ],
+        )?;
+        Ok(!rows.is_empty())
+    }
+}
+
+fn error_response(status: u16, error: &str, message: &str) -> Response {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": error, "message": message}).to_string())
+        .build()
+}
+
+/// Routes the request, replaying the stored response when the same
+/// `Idempotency-Key` and payload were already handled.
+pub async fn handle(req: &Request) -> Result<Response> {
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+    let key = req
+        .header(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    let key = match key {
+        Some(key) if applies_to(&method, &path) => key,
+        _ => return crate::route(req).await,
+    };
+    if !is_valid_key(&key) {
+        return Ok(error_response(400, "Bad Request", "Invalid Idempotency-Key"));
+    }
+
+    let window_seconds = variables::get("idempotency_window_seconds")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_WINDOW_SECONDS);
+    let idempotency = Idempotency::new(DbIdempotencyStore::open()?, window_seconds);
+    let fingerprint = fingerprint(&method, &path, req.body());
+
+    match idempotency.begin(&key, &fingerprint, chrono::Utc::now().timestamp())? {
+        Decision::Replay(stored) => {
+            let mut response = Response::builder().status(stored.status).body(stored.body).build();
+            if let Some(content_type) = stored.content_type {
+                response.set_header("Content-Type", content_type);
+            }
+            response.set_header(REPLAYED_HEADER, "true");
+            Ok(response)
+        }
+        Decision::Conflict => Ok(error_response(
+            422,
+            "Unprocessable Entity",
+            "Idempotency-Key was already used with a different request",
+        )),
+        Decision::InFlight => Ok(error_response(
+            409,
+            "Conflict",
+            "A request with this Idempotency-Key is still being processed",
+        )),
+        Decision::Proceed(mut claim) => {
+            let response = match route_holding(req, &idempotency, &key, &mut claim).await {
+                Ok(response) => response,
+                Err(e) => {
+                    // Free the key so the client can retry
+                    let failure = StoredResponse {
+                        status: 500,
+                        content_type: None,
+                        body: vec![],
+                    };
+                    idempotency.complete(&key, &claim, failure)?;
+                    return Err(e);
+                }
+            };
+
+            let stored = StoredResponse {
+                status: *response.status(),
+                content_type: response
+                    .header("Content-Type")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                body: response.body().to_vec(),
+            };
+            if !idempotency.complete(&key, &claim, stored)? {
+                let request_id = req.header(REQUEST_ID_HEADER).and_then(|v| v.as_str()).unwrap_or_default();
+                crate::telemetry::logger(request_id).warn(
+                    "idempotency key taken over before its response was stored",
+                    json!({ "idempotency_key": key }),
+                );
+            }
+            Ok(response)
+        }
+    }
+}
+
+/// Routes the request, renewing its claim on the key until it finishes so a
+/// slow request is not taken over by a retry.
+async fn route_holding(
+    req: &Request,
+    idempotency: &Idempotency<DbIdempotencyStore>,
+    key: &str,
+    claim: &mut Claim,
+) -> Result<Response> {
+    let routed = crate::route(req);
+    futures::pin_mut!(routed);
+    loop {
+        match future::select(routed, Sleep::new(LEASE_RENEWAL)).await {
+            Either::Left((response, _)) => return response,
+            Either::Right((_, pending)) => {
+                routed = pending;
+                if !idempotency.renew(key, claim, chrono::Utc::now().timestamp())? {
+                    // Taken over regardless; let the request finish
+                    return routed.await;
+                }
+            }
+        }
+    }
+}
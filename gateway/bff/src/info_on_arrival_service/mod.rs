@@ -0,0 +1,89 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use spin_sdk::http::{Request, Response};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct InfoCard {
+    id: String,
+    title: String,
+    description: String,
+    category: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ArrivalInfo {
+    check_in_time: String,
+    check_out_time: String,
+    key_pickup: String,
+    wifi_password: String,
+    emergency_contact: String,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/info/cards" => handle_info_cards().await,
+        "/api/info/arrival" => handle_arrival_info().await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Info endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/info/cards",
+    tag = "info",
+    responses((status = 200, description = "Information cards shown on arrival", body = Vec<InfoCard>))
+)]
+pub(crate) async fn handle_info_cards() -> Result<Response> {
+    let cards = vec![
+        InfoCard {
+            id: "transport".to_string(),
+            title: "Transportation".to_string(),
+            description: "Taxi and bus services available".to_string(),
+            category: "transport".to_string(),
+        },
+        InfoCard {
+            id: "dining".to_string(),
+            title: "Local Dining".to_string(),
+            description: "Restaurants and cafes nearby".to_string(),
+            category: "dining".to_string(),
+        },
+    ];
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&cards)?)
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/info/arrival",
+    tag = "info",
+    responses((status = 200, description = "Check-in practicalities", body = ArrivalInfo))
+)]
+pub(crate) async fn handle_arrival_info() -> Result<Response> {
+    let info = ArrivalInfo {
+        check_in_time: "14:00".to_string(),
+        check_out_time: "11:00".to_string(),
+        key_pickup: "Reception desk".to_string(),
+        wifi_password: "camino2024".to_string(),
+        emergency_contact: "+34 927 XXX XXX".to_string(),
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&info)?)
+        .build())
+}
@@ -1,110 +1,197 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
 use spin_sdk::{
-    http::{IntoResponse, Request, Response},
-    http_component,
+    http::{Method, OutgoingResponse, Request, Response, ResponseOutparam},
+    variables,
+};
+use serde_json::json;
+use shared::security_headers::SecurityPolicy;
+use shared::telemetry::{
+    inject_request_id, request_id_from, JsonLogger, SpanRecord, REQUEST_ID_HEADER,
 };
-use std::collections::HashMap;
 
 // Import all service modules
-mod auth_service;
-mod auth_verify;
-mod booking_service;
-mod info_on_arrival_service;
-mod location_service;
-mod notification_service;
-mod rate_limiter_service;
-mod reviews_service;
-mod security_service;
-mod validation_service;
-
-#[derive(Serialize, Deserialize)]
-struct Review {
-    id: String,
-    author_name: String,
-    rating: u8,
-    text: String,
-    date: String,
-    source: String,
-    verified: bool,
-    helpful_count: u32,
+pub mod auth_verify;
+pub mod booking_service;
+pub mod health;
+pub mod idempotency;
+pub mod info_on_arrival_service;
+pub mod location_service;
+pub mod notification_service;
+pub mod openapi;
+pub mod proxy;
+pub mod rate_limiter_service;
+pub mod reviews_service;
+pub mod security_service;
+pub mod telemetry;
+pub mod validation_service;
+
+#[cfg_attr(target_arch = "wasm32", spin_sdk::http_component)]
+pub async fn handle_request(mut req: Request, response_out: ResponseOutparam) {
+    let started = chrono::Utc::now();
+    let request_id = request_id_from(req.header(REQUEST_ID_HEADER).and_then(|v| v.as_str()));
+    req.set_header(REQUEST_ID_HEADER, request_id.clone());
+    let logger = telemetry::logger(&request_id);
+
+    let policy = security_policy();
+    let origin = req.header("Origin").and_then(|v| v.as_str()).map(str::to_string);
+    let path = req.path().to_string();
+    let method = req.method().to_string();
+
+    let mut response = if *req.method() == Method::Options {
+        match origin.as_deref() {
+            Some(o) if policy.is_origin_allowed(o, &path) => {
+                let mut preflight = Response::builder().status(204).body("").build();
+                for (name, value) in policy.preflight_headers() {
+                    preflight.set_header(name, value);
+                }
+                preflight
+            }
+            _ => Response::builder()
+                .status(403)
+                .header("Content-Type", "application/json")
+                .body(r#"{"error":"Forbidden","message":"Origin not allowed"}"#)
+                .build(),
+        }
+    } else {
+        match idempotency::handle(&req).await {
+            Ok(response) => response,
+            Err(e) => {
+                logger.error("handler failed", json!({"path": path, "error": e.to_string()}));
+                Response::builder()
+                    .status(500)
+                    .header("Content-Type", "application/json")
+                    .body(r#"{"error":"Internal Server Error"}"#)
+                    .build()
+            }
+        }
+    };
+
+    let status = *response.status();
+    if status >= 400 {
+        if let Some(body) = inject_request_id(response.body(), &request_id) {
+            *response.body_mut() = body;
+        }
+    }
+
+    for (name, value) in policy.response_headers(origin.as_deref(), &path) {
+        response.set_header(name, value);
+    }
+    response.set_header(REQUEST_ID_HEADER, request_id.clone());
+
+    let finished = chrono::Utc::now();
+    logger.info(
+        "request completed",
+        json!({
+            "method": method,
+            "path": path,
+            "status": status,
+            "duration_ms": (finished - started).num_milliseconds(),
+        }),
+    );
+
+    // The span is exported once the response is out, so a slow collector
+    // never delays the caller
+    respond(response_out, response, &logger).await;
+
+    let span = SpanRecord {
+        name: format!("{} {}", method, path),
+        request_id,
+        start_unix_nanos: started.timestamp_nanos_opt().unwrap_or_default(),
+        end_unix_nanos: finished.timestamp_nanos_opt().unwrap_or_default(),
+        http_method: method,
+        http_route: path,
+        http_status: status,
+    };
+    telemetry::export_span(&span, &logger).await;
 }
 
-#[derive(Serialize, Deserialize)]
-struct ReviewsResponse {
-    reviews: Vec<Review>,
-    total_count: u32,
-    average_rating: f32,
-    source_breakdown: HashMap<String, u32>,
+/// Sends the buffered response and finishes its body.
+async fn respond(response_out: ResponseOutparam, mut response: Response, logger: &JsonLogger) {
+    let body = std::mem::take(response.body_mut());
+    let sent = match OutgoingResponse::try_from(response) {
+        Ok(outgoing) => response_out
+            .set_with_body(outgoing, body)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = sent {
+        logger.error("response not sent", json!({"error": e}));
+    }
 }
 
-#[http_component]
-async fn handle_request(req: Request) -> Result<impl IntoResponse> {
-    let path = req.uri().path();
+/// CORS allowlist and security headers, configured through Spin variables.
+fn security_policy() -> SecurityPolicy {
+    let allowed_origins = variables::get("cors_allowed_origins").unwrap_or_default();
+    let admin_origin = variables::get("cors_admin_origin").unwrap_or_default();
+    let frame_ancestors =
+        variables::get("info_cards_frame_ancestors").unwrap_or_else(|_| "*".to_string());
 
-    // Add CORS headers
-    let mut response_builder = Response::builder()
-        .header("Access-Control-Allow-Origin", "*")
-        .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
-        .header("Access-Control-Allow-Headers", "Content-Type, Authorization");
+    SecurityPolicy::from_settings(&allowed_origins, &admin_origin)
+        .with_override("/api/info/cards", &frame_ancestors)
+}
 
-    if req.method().as_str() == "OPTIONS" {
-        return Ok(response_builder.status(200).body("").build());
-    }
+/// Dispatches a request to the service module owning its path.
+pub async fn route(req: &Request) -> Result<Response> {
+    let path = req.path();
 
     // Route to appropriate service based on path
     match path {
-        // Health check
-        "/api/health" => handle_health().await,
+        // Health checks
+        "/api/health" => health::handle_health(req).await,
+        "/api/health/ready" => health::handle_ready(req).await,
+
+        // OpenAPI document
+        "/api/openapi.json" => openapi::handle_openapi().await,
 
         // Auth routes
         path if path.starts_with("/api/auth/") => {
-            auth_verify::handle(&req).await
+            auth_verify::handle(req).await
         }
 
         // Booking routes
         path if path.starts_with("/api/booking/") => {
-            booking_service::handle(&req).await
+            booking_service::handle(req).await
         }
 
         // Reviews routes
         path if path.starts_with("/api/reviews/") => {
-            reviews_service::handle(&req).await
+            reviews_service::handle(req).await
         }
 
         // Security routes
         path if path.starts_with("/api/security/") => {
-            security_service::handle(&req).await
+            security_service::handle(req).await
         }
 
         // Rate limiter routes
         path if path.starts_with("/api/rate-limit/") => {
-            rate_limiter_service::handle(&req).await
+            rate_limiter_service::handle(req).await
         }
 
         // Notification routes
         path if path.starts_with("/api/notifications/") => {
-            notification_service::handle(&req).await
+            notification_service::handle(req).await
         }
 
         // Location routes
         path if path.starts_with("/api/location/") => {
-            location_service::handle(&req).await
+            location_service::handle(req).await
         }
 
         // Info on arrival routes
         path if path.starts_with("/api/info/") => {
-            info_on_arrival_service::handle(&req).await
+            info_on_arrival_service::handle(req).await
         }
 
         // Validation routes
         path if path.starts_with("/api/validation/") => {
-            validation_service::handle(&req).await
+            validation_service::handle(req).await
         }
 
         // Default 404
         _ => {
-            Ok(response_builder
+            Ok(Response::builder()
                 .status(404)
                 .header("Content-Type", "application/json")
                 .body(r#"{"error":"Not Found","message":"API endpoint not found"}"#)
@@ -112,18 +199,3 @@ async fn handle_request(req: Request) -> Result<impl IntoResponse> {
         }
     }
 }
-
-async fn handle_health() -> Result<Response> {
-    let health = serde_json::json!({
-        "status": "ok",
-        "service": "gateway-bff",
-        "version": "0.1.0",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
-
-    Ok(Response::builder()
-        .status(200)
-        .header("Content-Type", "application/json")
-        .body(health.to_string())
-        .build())
-}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use spin_sdk::http::{Request, Response};
+use utoipa::ToSchema;
+
+use crate::openapi::ErrorResponse;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Coordinates {
+    lat: f64,
+    lng: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LocationInfo {
+    name: String,
+    address: String,
+    coordinates: Coordinates,
+    camino_stage: String,
+    distance_from_merida_km: f64,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/location/info" => handle_location_info().await,
+        "/api/location/directions" => handle_directions(req).await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Location endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/location/info",
+    tag = "location",
+    responses((status = 200, description = "Albergue location", body = LocationInfo))
+)]
+pub(crate) async fn handle_location_info() -> Result<Response> {
+    let location = LocationInfo {
+        name: "Albergue del Carrascalejo".to_string(),
+        address: "Carrascalejo, Extremadura, Spain".to_string(),
+        coordinates: Coordinates {
+            lat: 39.2436,
+            lng: -5.8739,
+        },
+        camino_stage: "Mérida to Alcuéscar".to_string(),
+        distance_from_merida_km: 18.5,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&location)?)
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/location/directions",
+    tag = "location",
+    responses((status = 501, description = "Not implemented yet", body = ErrorResponse))
+)]
+pub(crate) async fn handle_directions(_req: &Request) -> Result<Response> {
+    // TODO: Implement directions logic
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
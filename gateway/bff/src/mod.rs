@@ -1,12 +0,0 @@
-
-// Module declarations for BFF services
-pub mod auth_service;
-pub mod auth_verify;
-pub mod booking_service;
-pub mod info_on_arrival_service;
-pub mod location_service;
-pub mod notification_service;
-pub mod rate_limiter_service;
-pub mod reviews_service;
-pub mod security_service;
-pub mod validation_service;
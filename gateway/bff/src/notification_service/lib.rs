@@ -1,46 +0,0 @@
-` tags. I will ensure that the indentation and formatting are preserved and that no parts are skipped or omitted. I will also avoid using any forbidden words.
-
-```
-<replit_final_file>
-use anyhow::Result;
-use serde_json::json;
-use spin_sdk::http::{Request, Response};
-
-pub async fn handle(req: &Request) -> Result<Response> {
-    let path = req.uri().path();
-
-    match path {
-        "/api/notifications/send" => handle_send_notification(req).await,
-        "/api/notifications/status" => handle_notification_status(req).await,
-        _ => {
-            Ok(Response::builder()
-                .status(404)
-                .header("Content-Type", "application/json")
-                .body(json!({"error": "Notification endpoint not found"}).to_string())
-                .build())
-        }
-    }
-}
-
-async fn handle_send_notification(_req: &Request) -> Result<Response> {
-    // TODO: Implement notification sending logic
-    Ok(Response::builder()
-        .status(501)
-        .header("Content-Type", "application/json")
-        .body(json!({"error": "Not implemented yet"}).to_string())
-        .build())
-}
-
-async fn handle_notification_status(_req: &Request) -> Result<Response> {
-    let status = json!({
-        "email_service": "operational",
-        "sms_service": "operational",
-        "telegram_service": "operational"
-    });
-
-    Ok(Response::builder()
-        .status(200)
-        .header("Content-Type", "application/json")
-        .body(status.to_string())
-        .build())
-}
\ No newline at end of file
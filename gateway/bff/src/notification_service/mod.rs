@@ -0,0 +1,98 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use spin_sdk::http::{Request, Response};
+use spin_sdk::variables;
+use utoipa::ToSchema;
+
+use crate::openapi::ErrorResponse;
+use crate::proxy;
+
+/// The header Twilio signs its status callbacks with. notification-service
+/// checks it against the body, so both pass through untouched.
+const TWILIO_SIGNATURE_HEADER: &str = "X-Twilio-Signature";
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ChannelStatus {
+    email_service: String,
+    sms_service: String,
+    telegram_service: String,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/notifications/send" => handle_send_notification(req).await,
+        "/api/notifications/status" => handle_notification_status(req).await,
+        "/api/notifications/twilio/status" => handle_twilio_status(req).await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Notification endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/notifications/send",
+    tag = "notifications",
+    responses((status = 501, description = "Not implemented yet", body = ErrorResponse))
+)]
+pub(crate) async fn handle_send_notification(_req: &Request) -> Result<Response> {
+    // TODO: Implement notification sending logic
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/notifications/status",
+    tag = "notifications",
+    responses((status = 200, description = "Per-channel availability", body = ChannelStatus))
+)]
+pub(crate) async fn handle_notification_status(_req: &Request) -> Result<Response> {
+    let status = ChannelStatus {
+        email_service: "operational".to_string(),
+        sms_service: "operational".to_string(),
+        telegram_service: "operational".to_string(),
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&status)?)
+        .build())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/notifications/twilio/status",
+    tag = "notifications",
+    request_body(content = String, content_type = "application/x-www-form-urlencoded", description = "Twilio message status callback"),
+    params(("X-Twilio-Signature" = String, Header, description = "Twilio's signature of the callback")),
+    responses(
+        (status = 204, description = "Delivery status recorded"),
+        (status = 403, description = "Signature missing or invalid", body = ErrorResponse),
+        (status = 503, description = "notification-service not configured", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_twilio_status(req: &Request) -> Result<Response> {
+    // notification-service runs outside Spin, wherever this variable says
+    let url = variables::get("notification_service_url").unwrap_or_default();
+    if url.is_empty() {
+        return Ok(Response::builder()
+            .status(503)
+            .header("Content-Type", "application/json")
+            .body(json!({"error": "Notification service not configured"}).to_string())
+            .build());
+    }
+    let url = format!("{}/twilio/status", url.trim_end_matches('/'));
+    proxy::forward_with(req, &url, None, &[TWILIO_SIGNATURE_HEADER]).await
+}
@@ -0,0 +1,82 @@
+// OpenAPI document for the gateway, generated from handler annotations
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use spin_sdk::http::Response;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    auth_verify, booking_service, health, info_on_arrival_service, location_service,
+    notification_service, rate_limiter_service, reviews_service, security_service,
+    validation_service,
+};
+
+/// Error body returned by handlers that are not implemented or fail.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Albergue del Carrascalejo API",
+        description = "Backend-for-frontend gateway routes consumed by the web app"
+    ),
+    paths(
+        health::handle_health,
+        health::handle_ready,
+        auth_verify::verify_token,
+        auth_verify::handle_login,
+        auth_verify::handle_callback,
+        auth_verify::handle_logout,
+        booking_service::handle_dashboard_stats,
+        booking_service::handle_pricing,
+        booking_service::handle_create_booking,
+        booking_service::handle_booking_status,
+        reviews_service::handle_all_reviews,
+        reviews_service::handle_review_stats,
+        security_service::handle_security_scan,
+        security_service::handle_validation,
+        rate_limiter_service::handle_rate_limit_check,
+        rate_limiter_service::handle_rate_limit_status,
+        notification_service::handle_send_notification,
+        notification_service::handle_notification_status,
+        notification_service::handle_twilio_status,
+        location_service::handle_location_info,
+        location_service::handle_directions,
+        info_on_arrival_service::handle_info_cards,
+        info_on_arrival_service::handle_arrival_info,
+        validation_service::handle_document_validation,
+        validation_service::handle_form_validation,
+        validation_service::handle_portrait_purge,
+        validation_service::handle_review_list,
+        validation_service::handle_review_corrections,
+        validation_service::handle_review_approval,
+        validation_service::handle_review_rejection,
+    ),
+    components(schemas(ErrorResponse)),
+    tags(
+        (name = "health", description = "Dependency health and readiness"),
+        (name = "auth", description = "Auth0 login and token verification"),
+        (name = "booking", description = "Bookings, pricing and occupancy"),
+        (name = "reviews", description = "Aggregated guest reviews"),
+        (name = "security", description = "Security scanning"),
+        (name = "rate-limit", description = "Request rate limiting"),
+        (name = "notifications", description = "Email, SMS and Telegram notifications"),
+        (name = "location", description = "Albergue location and directions"),
+        (name = "info", description = "Information shown on arrival"),
+        (name = "validation", description = "Identity document validation"),
+    )
+)]
+pub struct ApiDoc;
+
+pub(crate) async fn handle_openapi() -> Result<Response> {
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(ApiDoc::openapi().to_pretty_json()?)
+        .build())
+}
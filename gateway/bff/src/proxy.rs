@@ -0,0 +1,49 @@
+// Forwarding of gateway routes to the components behind them
+
+use anyhow::Result;
+use serde_json::json;
+use shared::telemetry::REQUEST_ID_HEADER;
+use spin_sdk::http::{Request, Response};
+
+use crate::auth_verify::AdminClaims;
+
+/// Headers carrying the admin a forwarded request was made by. Backend
+/// components are private, so they can trust what the gateway sets here.
+pub const USER_ID_HEADER: &str = "X-User-Id";
+pub const USER_ROLE_HEADER: &str = "X-User-Role";
+
+/// Sends `req`'s method, body, content type and request id to `url`, and
+/// `admin` when the route requires one. An unreachable component is a 502.
+pub async fn forward(req: &Request, url: &str, admin: Option<&AdminClaims>) -> Result<Response> {
+    forward_with(req, url, admin, &[]).await
+}
+
+/// `forward`, also passing on `headers` the component checks itself.
+pub async fn forward_with(
+    req: &Request,
+    url: &str,
+    admin: Option<&AdminClaims>,
+    headers: &[&str],
+) -> Result<Response> {
+    let mut builder = Request::builder();
+    builder.method(req.method().clone()).uri(url);
+    for name in ["Content-Type", REQUEST_ID_HEADER].iter().chain(headers) {
+        if let Some(value) = req.header(name).and_then(|v| v.as_str()) {
+            builder.header(*name, value);
+        }
+    }
+    if let Some(admin) = admin {
+        builder.header(USER_ID_HEADER, admin.sub.as_str());
+        builder.header(USER_ROLE_HEADER, admin.role.as_str());
+    }
+    let request = builder.body(req.body().to_vec()).build();
+
+    match spin_sdk::http::send::<_, Response>(request).await {
+        Ok(response) => Ok(response),
+        Err(e) => Ok(Response::builder()
+            .status(502)
+            .header("Content-Type", "application/json")
+            .body(json!({"error": "Upstream unavailable", "message": e.to_string()}).to_string())
+            .build()),
+    }
+}
@@ -1,49 +0,0 @@
-
-use anyhow::Result;
-use serde_json::json;
-use spin_sdk::http::{Request, Response};
-
-pub async fn handle(req: &Request) -> Result<Response> {
-    let path = req.uri().path();
-    
-    match path {
-        "/api/rate-limit/check" => handle_rate_limit_check(req).await,
-        "/api/rate-limit/status" => handle_rate_limit_status(req).await,
-        _ => {
-            Ok(Response::builder()
-                .status(404)
-                .header("Content-Type", "application/json")
-                .body(json!({"error": "Rate limit endpoint not found"}).to_string())
-                .build())
-        }
-    }
-}
-
-async fn handle_rate_limit_check(_req: &Request) -> Result<Response> {
-    // TODO: Implement actual rate limiting logic
-    let result = json!({
-        "allowed": true,
-        "remaining": 95,
-        "reset_time": "2024-01-01T01:00:00Z"
-    });
-
-    Ok(Response::builder()
-        .status(200)
-        .header("Content-Type", "application/json")
-        .body(result.to_string())
-        .build())
-}
-
-async fn handle_rate_limit_status(_req: &Request) -> Result<Response> {
-    let status = json!({
-        "requests_per_minute": 100,
-        "current_usage": 5,
-        "blocked_requests": 0
-    });
-
-    Ok(Response::builder()
-        .status(200)
-        .header("Content-Type", "application/json")
-        .body(status.to_string())
-        .build())
-}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use spin_sdk::http::{Request, Response};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RateLimitCheck {
+    allowed: bool,
+    remaining: u32,
+    reset_time: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RateLimitStatus {
+    requests_per_minute: u32,
+    current_usage: u32,
+    blocked_requests: u32,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/rate-limit/check" => handle_rate_limit_check(req).await,
+        "/api/rate-limit/status" => handle_rate_limit_status(req).await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Rate limit endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rate-limit/check",
+    tag = "rate-limit",
+    responses((status = 200, description = "Whether the caller may proceed", body = RateLimitCheck))
+)]
+pub(crate) async fn handle_rate_limit_check(_req: &Request) -> Result<Response> {
+    // TODO: Implement actual rate limiting logic
+    let result = RateLimitCheck {
+        allowed: true,
+        remaining: 95,
+        reset_time: "2024-01-01T01:00:00Z".to_string(),
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&result)?)
+        .build())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rate-limit/status",
+    tag = "rate-limit",
+    responses((status = 200, description = "Current limiter usage", body = RateLimitStatus))
+)]
+pub(crate) async fn handle_rate_limit_status(_req: &Request) -> Result<Response> {
+    let status = RateLimitStatus {
+        requests_per_minute: 100,
+        current_usage: 5,
+        blocked_requests: 0,
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&status)?)
+        .build())
+}
@@ -4,9 +4,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use spin_sdk::http::{Request, Response};
 use std::collections::HashMap;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize)]
-struct Review {
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct Review {
     id: String,
     author_name: String,
     rating: u8,
@@ -17,16 +18,23 @@ struct Review {
     helpful_count: u32,
 }
 
-#[derive(Serialize, Deserialize)]
-struct ReviewsResponse {
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReviewsResponse {
     reviews: Vec<Review>,
     total_count: u32,
     average_rating: f32,
     source_breakdown: HashMap<String, u32>,
 }
 
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReviewStats {
+    total_reviews: u32,
+    average_rating: f32,
+    rating_distribution: HashMap<String, u32>,
+}
+
 pub async fn handle(req: &Request) -> Result<Response> {
-    let path = req.uri().path();
+    let path = req.path();
     
     match path {
         "/api/reviews/all" => handle_all_reviews().await,
@@ -41,7 +49,13 @@ pub async fn handle(req: &Request) -> Result<Response> {
     }
 }
 
-async fn handle_all_reviews() -> Result<Response> {
+#[utoipa::path(
+    get,
+    path = "/api/reviews/all",
+    tag = "reviews",
+    responses((status = 200, description = "Reviews from all sources", body = ReviewsResponse))
+)]
+pub(crate) async fn handle_all_reviews() -> Result<Response> {
     let reviews = vec![
         Review {
             id: "1".to_string(),
@@ -83,22 +97,27 @@ async fn handle_all_reviews() -> Result<Response> {
         .build())
 }
 
-async fn handle_review_stats() -> Result<Response> {
-    let stats = json!({
-        "total_reviews": 47,
-        "average_rating": 4.6,
-        "rating_distribution": {
-            "5": 28,
-            "4": 12,
-            "3": 5,
-            "2": 1,
-            "1": 1
-        }
-    });
+#[utoipa::path(
+    get,
+    path = "/api/reviews/stats",
+    tag = "reviews",
+    responses((status = 200, description = "Rating summary", body = ReviewStats))
+)]
+pub(crate) async fn handle_review_stats() -> Result<Response> {
+    let rating_distribution = [("5", 28), ("4", 12), ("3", 5), ("2", 1), ("1", 1)]
+        .into_iter()
+        .map(|(rating, count)| (rating.to_string(), count))
+        .collect();
+
+    let stats = ReviewStats {
+        total_reviews: 47,
+        average_rating: 4.6,
+        rating_distribution,
+    };
 
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "application/json")
-        .body(stats.to_string())
+        .body(serde_json::to_string(&stats)?)
         .build())
 }
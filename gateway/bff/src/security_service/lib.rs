@@ -1,43 +0,0 @@
-use anyhow::Result;
-use serde_json::json;
-use spin_sdk::http::{Request, Response};
-
-pub async fn handle(req: &Request) -> Result<Response> {
-    let path = req.uri().path();
-
-    match path {
-        "/api/security/scan" => handle_security_scan(req).await,
-        "/api/security/validate" => handle_validation(req).await,
-        _ => {
-            Ok(Response::builder()
-                .status(404)
-                .header("Content-Type", "application/json")
-                .body(json!({"error": "Security endpoint not found"}).to_string())
-                .build())
-        }
-    }
-}
-
-async fn handle_security_scan(_req: &Request) -> Result<Response> {
-    // TODO: Implement security scanning logic
-    let result = json!({
-        "status": "clean",
-        "threats_detected": 0,
-        "scan_time": "2024-01-01T00:00:00Z"
-    });
-
-    Ok(Response::builder()
-        .status(200)
-        .header("Content-Type", "application/json")
-        .body(result.to_string())
-        .build())
-}
-
-async fn handle_validation(_req: &Request) -> Result<Response> {
-    // TODO: Implement validation logic
-    Ok(Response::builder()
-        .status(501)
-        .header("Content-Type", "application/json")
-        .body(json!({"error": "Not implemented yet"}).to_string())
-        .build())
-}
\ No newline at end of file
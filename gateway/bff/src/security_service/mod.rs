@@ -0,0 +1,66 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use spin_sdk::http::{Request, Response};
+use utoipa::ToSchema;
+
+use crate::openapi::ErrorResponse;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SecurityScan {
+    status: String,
+    threats_detected: u32,
+    scan_time: String,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/security/scan" => handle_security_scan(req).await,
+        "/api/security/validate" => handle_validation(req).await,
+        _ => {
+            Ok(Response::builder()
+                .status(404)
+                .header("Content-Type", "application/json")
+                .body(json!({"error": "Security endpoint not found"}).to_string())
+                .build())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/security/scan",
+    tag = "security",
+    responses((status = 200, description = "Latest security scan", body = SecurityScan))
+)]
+pub(crate) async fn handle_security_scan(_req: &Request) -> Result<Response> {
+    // TODO: Implement security scanning logic
+    let result = SecurityScan {
+        status: "clean".to_string(),
+        threats_detected: 0,
+        scan_time: "2024-01-01T00:00:00Z".to_string(),
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&result)?)
+        .build())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/security/validate",
+    tag = "security",
+    responses((status = 501, description = "Not implemented yet", body = ErrorResponse))
+)]
+pub(crate) async fn handle_validation(_req: &Request) -> Result<Response> {
+    // TODO: Implement validation logic
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
@@ -0,0 +1,47 @@
+// Request-scoped logging and optional OpenTelemetry export
+
+use shared::telemetry::{JsonLogger, LogLevel, SpanRecord, REQUEST_ID_HEADER};
+use spin_sdk::http::{Method, Request, Response};
+use spin_sdk::variables;
+
+pub const SERVICE_NAME: &str = "gateway-bff";
+
+pub fn logger(request_id: &str) -> JsonLogger {
+    let level = variables::get("log_level").unwrap_or_else(|_| "info".to_string());
+    JsonLogger::new(SERVICE_NAME, LogLevel::parse(&level)).with_request_id(request_id)
+}
+
+/// Request id assigned by `handle_request`, for forwarding on outbound calls.
+pub fn request_id(req: &Request) -> Option<String> {
+    req.header(REQUEST_ID_HEADER)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// Ships the span to `otel_collector_url` when one is configured. Export
+/// failures are logged and never affect the response.
+pub async fn export_span(span: &SpanRecord, logger: &JsonLogger) {
+    let collector = variables::get("otel_collector_url").unwrap_or_default();
+    if collector.is_empty() {
+        return;
+    }
+
+    let request = Request::builder()
+        .method(Method::Post)
+        .uri(format!("{}/v1/traces", collector.trim_end_matches('/')))
+        .header("Content-Type", "application/json")
+        .body(span.to_otlp_json(SERVICE_NAME).to_string())
+        .build();
+
+    match spin_sdk::http::send::<_, Response>(request).await {
+        Ok(response) if (200..300).contains(response.status()) => {}
+        Ok(response) => logger.warn(
+            "span export rejected",
+            serde_json::json!({"collector_status": response.status()}),
+        ),
+        Err(e) => logger.warn(
+            "span export failed",
+            serde_json::json!({"error": e.to_string()}),
+        ),
+    }
+}
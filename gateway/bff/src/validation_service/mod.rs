@@ -0,0 +1,190 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    DocumentReview, ExtractedData, ReviewCorrectionRequest, ReviewRejectionRequest, ValidationRequest,
+    ValidationResponse,
+};
+use spin_sdk::http::{Method, Request, Response};
+use utoipa::ToSchema;
+
+use crate::auth_verify::require_admin;
+use crate::openapi::ErrorResponse;
+use crate::proxy::forward;
+
+/// The validation component, over service chaining.
+const VALIDATION_SERVICE_URL: &str = "http://validation-service.spin.internal";
+
+/// Count of ID photos a purge deleted.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PortraitPurge {
+    purged: usize,
+}
+
+/// Reviews waiting on a hospitalero, oldest first.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReviewQueue {
+    reviews: Vec<DocumentReview>,
+}
+
+/// An approved review: the data to register the pilgrim with, and the
+/// reading as a golden corpus case, to be saved as `{golden_case_name}.json`
+/// once its personal data is replaced.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ReviewApproval {
+    review: DocumentReview,
+    data: ExtractedData,
+    golden_case_name: String,
+    #[schema(value_type = Object)]
+    golden_case: serde_json::Value,
+}
+
+pub async fn handle(req: &Request) -> Result<Response> {
+    let path = req.path();
+
+    match path {
+        "/api/validation/document" => handle_document_validation(req).await,
+        "/api/validation/form" => handle_form_validation(req).await,
+        "/api/validation/portraits/purge" if *req.method() == Method::Post => handle_portrait_purge(req).await,
+        "/api/validation/reviews" if *req.method() == Method::Get => handle_review_list(req).await,
+        path if path.starts_with("/api/validation/reviews/") && *req.method() == Method::Post => {
+            match path.rsplit('/').next() {
+                Some("corrections") => handle_review_corrections(req).await,
+                Some("approve") => handle_review_approval(req).await,
+                Some("reject") => handle_review_rejection(req).await,
+                _ => not_found(),
+            }
+        }
+        _ => not_found(),
+    }
+}
+
+fn not_found() -> Result<Response> {
+    Ok(Response::builder()
+        .status(404)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Validation endpoint not found"}).to_string())
+        .build())
+}
+
+/// Forwards an admin's request to the same path on the component.
+async fn forward_as_admin(req: &Request) -> Result<Response> {
+    let admin = match require_admin(req) {
+        Ok(admin) => admin,
+        Err(response) => return Ok(response),
+    };
+    let path = req.path().trim_start_matches("/api/validation");
+    forward(req, &format!("{}{}", VALIDATION_SERVICE_URL, path), Some(&admin)).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/document",
+    tag = "validation",
+    request_body = ValidationRequest,
+    responses(
+        (status = 200, description = "Document validation result", body = ValidationResponse),
+        (status = 400, description = "Unreadable upload or request", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_document_validation(req: &Request) -> Result<Response> {
+    forward(req, &format!("{}/validate/document", VALIDATION_SERVICE_URL), None).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/form",
+    tag = "validation",
+    responses((status = 501, description = "Not implemented yet", body = ErrorResponse))
+)]
+pub(crate) async fn handle_form_validation(_req: &Request) -> Result<Response> {
+    // TODO: Implement form validation logic
+    Ok(Response::builder()
+        .status(501)
+        .header("Content-Type", "application/json")
+        .body(json!({"error": "Not implemented yet"}).to_string())
+        .build())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/portraits/purge",
+    tag = "validation",
+    responses(
+        (status = 200, description = "ID photos past their retention date deleted", body = PortraitPurge),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_portrait_purge(req: &Request) -> Result<Response> {
+    // Run daily by `task portraits:purge`
+    forward_as_admin(req).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/validation/reviews",
+    tag = "validation",
+    responses(
+        (status = 200, description = "Document readings waiting on a hospitalero", body = ReviewQueue),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_review_list(req: &Request) -> Result<Response> {
+    forward_as_admin(req).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/reviews/{id}/corrections",
+    tag = "validation",
+    params(("id" = String, Path, description = "Review id")),
+    request_body = ReviewCorrectionRequest,
+    responses(
+        (status = 200, description = "The review with its corrections", body = DocumentReview),
+        (status = 400, description = "Unknown field, malformed value or review already decided", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "No such review", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_review_corrections(req: &Request) -> Result<Response> {
+    forward_as_admin(req).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/reviews/{id}/approve",
+    tag = "validation",
+    params(("id" = String, Path, description = "Review id")),
+    request_body(content = ReviewCorrectionRequest, description = "Last corrections, if any"),
+    responses(
+        (status = 200, description = "The approved reading", body = ReviewApproval),
+        (status = 400, description = "Unknown field, malformed value or review already decided", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "No such review", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_review_approval(req: &Request) -> Result<Response> {
+    forward_as_admin(req).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/validation/reviews/{id}/reject",
+    tag = "validation",
+    params(("id" = String, Path, description = "Review id")),
+    request_body = ReviewRejectionRequest,
+    responses(
+        (status = 200, description = "The rejected review", body = DocumentReview),
+        (status = 400, description = "No reason given or review already decided", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "No such review", body = ErrorResponse)
+    )
+)]
+pub(crate) async fn handle_review_rejection(req: &Request) -> Result<Response> {
+    forward_as_admin(req).await
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use gateway_bff::auth_verify::{verify_admin_token, AdminClaims, AdminTokenError};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    const SECRET: &[u8] = b"test-secret";
+    const NOW: i64 = 1_800_000_000;
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn token(alg: &str, claims: &AdminClaims, secret: &[u8]) -> String {
+        let header = encode(format!(r#"{{"alg":"{}","typ":"JWT"}}"#, alg).as_bytes());
+        let payload = encode(&serde_json::to_vec(claims).unwrap());
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(format!("{}.{}", header, payload).as_bytes());
+        format!("{}.{}.{}", header, payload, encode(&mac.finalize().into_bytes()))
+    }
+
+    fn claims(role: &str, exp: i64) -> AdminClaims {
+        AdminClaims {
+            sub: "auth0|hospitalero".to_string(),
+            role: role.to_string(),
+            exp,
+        }
+    }
+
+    #[test]
+    fn test_accepts_signed_admin_token() {
+        let admin = claims("admin", NOW + 60);
+
+        assert_eq!(verify_admin_token(&token("HS256", &admin, SECRET), SECRET, NOW), Ok(admin));
+    }
+
+    #[test]
+    fn test_rejects_forged_or_expired_tokens() {
+        let admin = claims("admin", NOW + 60);
+        let invalid = |token: &str, secret: &[u8]| {
+            matches!(verify_admin_token(token, secret, NOW), Err(AdminTokenError::Invalid(_)))
+        };
+
+        assert!(invalid(&token("HS256", &admin, b"other-secret"), SECRET));
+        assert!(invalid(&token("HS256", &claims("admin", NOW), SECRET), SECRET));
+        assert!(invalid(&token("none", &admin, SECRET), SECRET));
+        assert!(invalid(&token("HS256", &admin, SECRET), b""));
+        assert!(invalid("not.a-token", SECRET));
+        assert!(invalid("", SECRET));
+
+        // The payload cannot be swapped under a valid signature
+        let signed = token("HS256", &admin, SECRET);
+        let other = token("HS256", &claims("admin", NOW + 3600), SECRET);
+        let parts: Vec<&str> = signed.split('.').collect();
+        let swapped = format!("{}.{}.{}", parts[0], other.split('.').nth(1).unwrap(), parts[2]);
+        assert!(invalid(&swapped, SECRET));
+    }
+
+    #[test]
+    fn test_refuses_other_roles() {
+        let guest = claims("guest", NOW + 60);
+
+        assert_eq!(
+            verify_admin_token(&token("HS256", &guest, SECRET), SECRET, NOW),
+            Err(AdminTokenError::NotAdmin)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use gateway_bff::route;
+    use serde_json::{json, Value};
+    use spin_sdk::http::{Method, Request};
+
+    async fn get(path: &str) -> (u16, Value) {
+        let req = Request::builder().method(Method::Get).uri(path).build();
+        let response = route(&req).await.expect("handler failed");
+        let body = serde_json::from_slice(response.body()).expect("body is not JSON");
+        (*response.status(), body)
+    }
+
+    async fn spec() -> Value {
+        let (status, spec) = get("/api/openapi.json").await;
+        assert_eq!(status, 200);
+        spec
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document_is_served() {
+        let spec = spec().await;
+
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        for schema in [
+            "BookingDto",
+            "ValidationRequest",
+            "InfoCard",
+            "ReviewsResponse",
+            "HealthReport",
+        ] {
+            assert!(
+                spec["components"]["schemas"].get(schema).is_some(),
+                "{} missing from components",
+                schema
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_handlers_match_documented_schema() {
+        let spec = spec().await;
+        let paths = spec["paths"].as_object().unwrap();
+
+        for (path, item) in paths {
+            let Some(operation) = item.get("get") else {
+                continue;
+            };
+            // Health probes talk to the Spin host (variables, outbound HTTP, DB)
+            if path.starts_with("/api/health") {
+                continue;
+            }
+
+            let (status, body) = get(path).await;
+            let schema = &operation["responses"][status.to_string()]["content"]
+                ["application/json"]["schema"];
+            assert!(
+                !schema.is_null(),
+                "GET {} returned undocumented status {}",
+                path,
+                status
+            );
+
+            let mut root = schema.clone();
+            root["components"] = spec["components"].clone();
+            let validator = jsonschema::validator_for(&root).expect("invalid schema");
+
+            let errors: Vec<String> = validator
+                .iter_errors(&body)
+                .map(|e| format!("{} at {}", e, e.instance_path))
+                .collect();
+            assert!(
+                errors.is_empty(),
+                "GET {} diverges from its schema: {:?}",
+                path,
+                errors
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_rejects_diverging_payload() {
+        let spec = spec().await;
+        let mut root = json!({"$ref": "#/components/schemas/Pricing"});
+        root["components"] = spec["components"].clone();
+        let validator = jsonschema::validator_for(&root).unwrap();
+
+        assert!(!validator.is_valid(&json!({"bed_price": "15 EUR"})));
+    }
+}
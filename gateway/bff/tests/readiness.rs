@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use gateway_bff::health::{check_database, readiness_response};
+    use shared::db::DatabaseOperations;
+    use shared::health::{DependencyHealth, DependencyStatus, HealthReport};
+    use shared::AlbergueError;
+    use std::collections::HashMap;
+
+    /// Answers `SELECT 1` as told, without a host database.
+    struct StubDatabase {
+        up: bool,
+    }
+
+    impl DatabaseOperations for StubDatabase {
+        async fn health_check(&self) -> Result<bool, AlbergueError> {
+            if self.up {
+                Ok(true)
+            } else {
+                Err(AlbergueError::Database {
+                    message: "connection refused".to_string(),
+                })
+            }
+        }
+
+        async fn get_connection_info(&self) -> HashMap<String, String> {
+            HashMap::new()
+        }
+    }
+
+    async fn readiness_status(database: StubDatabase, others: Vec<DependencyHealth>) -> u16 {
+        let mut dependencies = vec![check_database(&database).await];
+        dependencies.extend(others);
+        let report = HealthReport::from_dependencies("gateway-bff", "test", dependencies);
+        *readiness_response(&report).unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_ready_with_a_healthy_database() {
+        assert_eq!(readiness_status(StubDatabase { up: true }, vec![]).await, 200);
+    }
+
+    #[tokio::test]
+    async fn test_ready_in_degraded_mode_when_only_optional_dependencies_are_down() {
+        let ministry = DependencyHealth::new("ministry", DependencyStatus::Down, 3000);
+
+        assert_eq!(readiness_status(StubDatabase { up: true }, vec![ministry]).await, 200);
+    }
+
+    #[tokio::test]
+    async fn test_not_ready_without_the_database() {
+        assert_eq!(readiness_status(StubDatabase { up: false }, vec![]).await, 503);
+    }
+}